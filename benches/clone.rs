@@ -0,0 +1,31 @@
+//! Compares cloning a `CubeSphereGrid` with copying the same number of bytes directly.
+
+use std::hint::black_box;
+
+use criterion::{criterion_group, criterion_main, Criterion};
+use surface_grid::{SurfaceGrid, sphere::CubeSphereGrid};
+
+/// The size of each side of each face of the grids.
+const SIZE: usize = 1024;
+
+fn clone(c: &mut Criterion) {
+    let grid: CubeSphereGrid<u64, SIZE> = CubeSphereGrid::from_index_fn(|i| i as u64);
+    let values = grid.to_vec();
+
+    let mut group = c.benchmark_group("clone");
+    group.sample_size(20);
+
+    group.bench_function("cube_sphere_grid", |b| b.iter(|| black_box(&grid).clone()));
+
+    // Copying each face into a new allocation with a single `memcpy` is what a bulk clone does.
+    group.bench_function("memcpy", |b| b.iter(|| {
+        black_box(&values).chunks_exact(SIZE * SIZE)
+            .map(<[u64]>::to_vec)
+            .collect::<Vec<_>>()
+    }));
+
+    group.finish();
+}
+
+criterion_group!(benches, clone);
+criterion_main!(benches);
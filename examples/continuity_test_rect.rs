@@ -30,58 +30,55 @@ fn main() -> Result<(), Box<dyn Error>> {
 
     let mut pixels = Pixels::new(window_size.width, window_size.height, surface_texture)?;
 
-    let buffer: RectangleSphereGrid<_, 400, 200> = RectangleSphereGrid::from_fn(|point| (point.position(1.0)));
+    let buffer: RectangleSphereGrid<_, 400, 200> = RectangleSphereGrid::from_fn(|point| point.position(1.0));
 
     event_loop.run(move |event, target| {
-        match event {
-            Event::WindowEvent { event, .. } => {
-                match event {
-                    WindowEvent::Resized(window_size) => {
-                        // Handle resizing.
-                        if window_size.width != 0 && window_size.height != 0 {
-                            size = PhysicalSize::new(window_size.width, window_size.height);
-
-                            pixels.resize_buffer(size.width, size.height)
-                                .expect("Failed to resize buffer");
-                            pixels.resize_surface(window_size.width, window_size.height)
-                                .expect("Failed to resize surface");
-                        }
-
-                        window.request_redraw()
-                    },
-                    WindowEvent::CloseRequested => {
-                        target.exit()
+        if let Event::WindowEvent { event, .. } = event {
+            match event {
+                WindowEvent::Resized(window_size) => {
+                    // Handle resizing.
+                    if window_size.width != 0 && window_size.height != 0 {
+                        size = PhysicalSize::new(window_size.width, window_size.height);
+
+                        pixels.resize_buffer(size.width, size.height)
+                            .expect("Failed to resize buffer");
+                        pixels.resize_surface(window_size.width, window_size.height)
+                            .expect("Failed to resize surface");
                     }
-                    WindowEvent::RedrawRequested => {
-                        // Display the result using pixels.
-                        let frame = pixels.frame_mut();
-                
-                        for y in 0..size.height {
-                            for x in 0..size.width {
-                                let i = (y as usize * size.width as usize + x as usize) * 4;
-
-                                // Convert the X Y screen coordinates to an equirectangular
-                                // projection of the latitude and longitude.
-                                let latitude = (y as f64 / size.height as f64) * PI - PI / 2.0;
-                                let longitude = (x as f64 / size.width as f64) * PI * 2.0;
-
-                                // Gets the value stored at the latitude and longitude calculated.
-                                let (x, y, z) = buffer[RectangleSpherePoint::from_geographic(latitude, longitude)];
-
-                                frame[i + 0] = ((x + 1.0) / 2.0 * 255.0) as u8;
-                                frame[i + 1] = ((y + 1.0) / 2.0 * 255.0) as u8;
-                                frame[i + 2] = ((z + 1.0) / 2.0 * 255.0) as u8;
-                                frame[i + 3] = 255;
-                            }
-                        }
 
-                        // Render the pixels to the screen.
-                        pixels.render().expect("Failed to render");
-                    },
-                    _ => {}
+                    window.request_redraw()
+                },
+                WindowEvent::CloseRequested => {
+                    target.exit()
                 }
-            },
-            _ => {}
+                WindowEvent::RedrawRequested => {
+                    // Display the result using pixels.
+                    let frame = pixels.frame_mut();
+            
+                    for y in 0..size.height {
+                        for x in 0..size.width {
+                            let i = (y as usize * size.width as usize + x as usize) * 4;
+
+                            // Convert the X Y screen coordinates to an equirectangular
+                            // projection of the latitude and longitude.
+                            let latitude = (y as f64 / size.height as f64) * PI - PI / 2.0;
+                            let longitude = (x as f64 / size.width as f64) * PI * 2.0;
+
+                            // Gets the value stored at the latitude and longitude calculated.
+                            let (x, y, z) = buffer[RectangleSpherePoint::from_geographic(latitude, longitude)];
+
+                            frame[i] = ((x + 1.0) / 2.0 * 255.0) as u8;
+                            frame[i + 1] = ((y + 1.0) / 2.0 * 255.0) as u8;
+                            frame[i + 2] = ((z + 1.0) / 2.0 * 255.0) as u8;
+                            frame[i + 3] = 255;
+                        }
+                    }
+
+                    // Render the pixels to the screen.
+                    pixels.render().expect("Failed to render");
+                },
+                _ => {}
+            }
         }
     })?;
 
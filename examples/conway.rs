@@ -1,10 +1,10 @@
 //! An example implementing conways game of life on the surface of a sphere.
 
-use std::{error::Error, f64::consts::PI, mem::swap, time::{Instant, Duration}};
+use std::{error::Error, f64::consts::PI, time::{Instant, Duration}};
 
 use pixels::{SurfaceTexture, Pixels};
 use rand::{thread_rng, Rng};
-use surface_grid::{sphere::{CubeSphereGrid, CubeSpherePoint, SpherePoint}, SurfaceGrid};
+use surface_grid::{automaton::DoubleBuffered, sphere::{CubeSphereGrid, CubeSpherePoint, SpherePoint}, SurfaceGrid};
 use winit::{event_loop::{EventLoop, ControlFlow}, window::WindowBuilder, dpi::{LogicalSize, PhysicalSize}, event::{Event, WindowEvent, StartCause}};
 
 // The initial window size.
@@ -31,15 +31,14 @@ fn main() -> Result<(), Box<dyn Error>> {
 
     let mut pixels = Pixels::new(window_size.width, window_size.height, surface_texture)?;
 
-    // Create two grids to swap between.
+    // Create a pair of grids to swap between.
     // This saves allocating a new grid for each frame.
     let mut rng = thread_rng();
 
     // The size specified here might be smaller than expected.
     // This is because it is the size of each cube face rather than the size of the whole grid.
     // A size of 512 leads to 1572864 grid cells. This is equivalent to an image around 1500x1500.
-    let mut buffer1: CubeSphereGrid<bool, 256> = CubeSphereGrid::from_fn(|_| rng.gen());
-    let mut buffer2: CubeSphereGrid<bool, 256> = CubeSphereGrid::default();
+    let mut buffers: DoubleBuffered<CubeSphereGrid<bool, 256>> = DoubleBuffered::new(CubeSphereGrid::from_fn(|_| rng.gen()));
 
     event_loop.run(move |event, target| {
         match event {
@@ -70,28 +69,15 @@ fn main() -> Result<(), Box<dyn Error>> {
                         target.exit()
                     }
                     WindowEvent::RedrawRequested => {
-                        // Calculate conways game of life in parallel.
-                        buffer2.set_from_neighbours_diagonals_par(&buffer1, |s1, s2, s3, s4, current, s6, s7, s8, s9| {
+                        // Calculate conways game of life in parallel, swapping the buffers afterwards.
+                        buffers.step(|src, dst| dst.set_from_neighbours_diagonals_par(src, |s1, s2, s3, s4, current, s6, s7, s8, s9| {
                             let count = [s1, s2, s3, s4, s6, s7, s8, s9]
                                 .into_iter()
                                 .filter(|s| **s)
                                 .count();
 
-                            if count < 2 {
-                                false
-                            } else if count > 3 {
-                                false
-                            } else if *current && count == 2 {
-                                true
-                            } else if count == 3 {
-                                true
-                            } else {
-                                false
-                            }
-                        });
-
-                        // Swap the buffers.
-                        swap(&mut buffer2, &mut buffer1);
+                            count == 3 || (*current && count == 2)
+                        }));
 
                         // Display the result using pixels.
                         let frame = pixels.frame_mut();
@@ -106,7 +92,7 @@ fn main() -> Result<(), Box<dyn Error>> {
                                 let longitude = (x as f64 / size.width as f64) * PI * 2.0;
 
                                 // Gets the value stored at the latitude and longitude calculated.
-                                let value = buffer1[CubeSpherePoint::from_geographic(latitude, longitude)];
+                                let value = buffers.current()[CubeSpherePoint::from_geographic(latitude, longitude)];
 
                                 // Set the pixel colour.
                                 if value {
@@ -0,0 +1,955 @@
+//! A module containing a cube sphere grid that can be refined to a higher resolution in places.
+
+use std::{cmp::Ordering, collections::{BTreeMap, btree_map}, f64::consts::PI, ops::{Index, IndexMut}};
+
+use crate::{Direction, GridPoint, OutOfBounds, sphere::{CubeFace, SpherePoint, FACE_ORDER}};
+
+assert_send_sync!([T: Send + Sync, const S: usize]
+    AdaptiveCubeSphereGrid<T, S>,
+    AdaptiveCubeSpherePoint<S>,
+);
+
+/// The greatest number of times that a cell of the base grid can be split.
+pub const MAX_LEVEL: u8 = 16;
+
+/// A cube sphere grid where individual cells can be split into four to give a higher resolution.
+///
+/// The grid starts out as a `CubeSphereGrid` with `S` cells along each side of each face. Each
+/// cell is the root of a quadtree and can be split into four cells with `refine`, which can be
+/// split again up to `MAX_LEVEL` times, and four cells that were split from the same cell can be
+/// joined back together with `coarsen`. This gives a high resolution in the places that need it
+/// without the memory cost of a high resolution over the whole sphere.
+///
+/// The cells that hold values are the leaves of the quadtrees. Use `neighbours` to get the
+/// leaves next to a cell, which may be larger or smaller than the cell itself. Because the
+/// neighbours of a cell depend on how the grid has been refined, this grid does not implement
+/// `SurfaceGrid` and the `GridPoint` methods of its points only move between cells of the same
+/// size.
+///
+/// # Type Parameters
+/// - `T` - The type of data that the grid holds.
+///
+/// # Constant Parameters
+/// - `S` - The size of each side of each face before any cells are split.
+///
+/// # Iteration Order
+/// `iter`, `points` and `into_iter` visit the faces in the same order as `CubeSphereGrid`. On
+/// each face the cells of the base grid are visited row by row and the cells split from each
+/// of them are visited depth first in Z order, which keeps cells that are close together close in
+/// the iteration order. This is also the order given by `Ord` for `AdaptiveCubeSpherePoint`.
+///
+/// # Thread Safety
+/// The leaves are kept in a `BTreeMap` owned by the grid and are only refined or coarsened through
+/// a mutable reference, so the grid is `Send` when `T` is `Send` and `Sync` when `T` is `Sync`.
+///
+/// # Panics
+/// Creating a grid panics if `S` is 0 or greater than 65536.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AdaptiveCubeSphereGrid<T, const S: usize> {
+    /// The values of the leaves of the quadtrees.
+    leaves: BTreeMap<AdaptiveCubeSpherePoint<S>, T>,
+}
+
+impl <T, const S: usize> AdaptiveCubeSphereGrid<T, S> {
+    /// Creates a new grid with every cell at the base resolution.
+    ///
+    /// - `f` - A function that gives the value of each cell.
+    ///
+    /// # Panics
+    /// Panics if `S` is 0 or greater than 65536.
+    pub fn from_fn<F: FnMut(&AdaptiveCubeSpherePoint<S>) -> T>(mut f: F) -> Self {
+        let size = base_size::<S>();
+
+        let leaves = FACE_ORDER.into_iter()
+            .flat_map(|face| (0..size).flat_map(move |y| (0..size).map(move |x| AdaptiveCubeSpherePoint {
+                face,
+                level: 0,
+                x,
+                y,
+            })))
+            .map(|point| {
+                let value = f(&point);
+
+                (point, value)
+            })
+            .collect();
+
+        Self {
+            leaves,
+        }
+    }
+
+    /// Gets the number of cells in the grid.
+    pub fn len(&self) -> usize {
+        self.leaves.len()
+    }
+
+    /// Checks if the grid has no cells, which is never the case.
+    pub fn is_empty(&self) -> bool {
+        self.leaves.is_empty()
+    }
+
+    /// Gets the number of times that the most refined cell has been split.
+    pub fn depth(&self) -> u8 {
+        self.leaves.keys()
+            .map(|point| point.level)
+            .max()
+            .unwrap_or(0)
+    }
+
+    /// Checks if a point is one of the cells of the grid rather than part of a cell or a cell that
+    /// has been split.
+    ///
+    /// - `point` - The point to check.
+    pub fn is_leaf(&self, point: &AdaptiveCubeSpherePoint<S>) -> bool {
+        self.leaves.contains_key(point)
+    }
+
+    /// Gets the cell of the grid that contains a point.
+    ///
+    /// This is the point itself when it is a cell of the grid, or the cell that it was split from
+    /// when it is smaller than the cells of the grid. This returns `None` if the point has been
+    /// split into smaller cells.
+    ///
+    /// - `point` - The point to find the cell of.
+    pub fn leaf(&self, point: &AdaptiveCubeSpherePoint<S>) -> Option<AdaptiveCubeSpherePoint<S>> {
+        let mut current = Some(*point);
+
+        while let Some(point) = current {
+            if self.leaves.contains_key(&point) {
+                return Some(point);
+            }
+
+            current = point.parent();
+        }
+
+        None
+    }
+
+    /// Gets the cell of the grid that contains a geographic position.
+    ///
+    /// - `latitude` - The latitude of the position in radians.
+    /// - `longitude` - The longitude of the position in radians.
+    pub fn leaf_at_geographic(&self, latitude: f64, longitude: f64) -> AdaptiveCubeSpherePoint<S> {
+        let (face, fx, fy) = locate(geographic_position(latitude, longitude));
+
+        (0..=MAX_LEVEL)
+            .map(|level| AdaptiveCubeSpherePoint::from_face_fraction(face, level, fx, fy))
+            .find(|point| self.leaves.contains_key(point))
+            .expect("the grid covers the whole sphere")
+    }
+
+    /// Gets a reference to the value of a cell.
+    ///
+    /// This returns `None` if the point is not one of the cells of the grid. Indexing the grid
+    /// instead also accepts points that are smaller than the cells of the grid.
+    ///
+    /// - `point` - The cell to get the value of.
+    pub fn get(&self, point: &AdaptiveCubeSpherePoint<S>) -> Option<&T> {
+        self.leaves.get(point)
+    }
+
+    /// Gets a mutable reference to the value of a cell.
+    ///
+    /// This returns `None` if the point is not one of the cells of the grid.
+    ///
+    /// - `point` - The cell to get the value of.
+    pub fn get_mut(&mut self, point: &AdaptiveCubeSpherePoint<S>) -> Option<&mut T> {
+        self.leaves.get_mut(point)
+    }
+
+    /// Splits a cell into four cells at the next level.
+    ///
+    /// This returns the new cells in the order given by `AdaptiveCubeSpherePoint::children`.
+    ///
+    /// - `point` - The cell to split.
+    /// - `f` - A function that gives the value of each new cell from its point and the value of
+    ///   the cell that was split.
+    ///
+    /// # Panics
+    /// Panics if the point is not one of the cells of the grid or if it is already at `MAX_LEVEL`.
+    pub fn refine<F: FnMut(&AdaptiveCubeSpherePoint<S>, &T) -> T>(&mut self, point: &AdaptiveCubeSpherePoint<S>, mut f: F) -> [AdaptiveCubeSpherePoint<S>; 4] {
+        let children = point.children();
+
+        let value = self.leaves.remove(point)
+            .unwrap_or_else(|| panic!("{point:?} is not a cell of the grid"));
+
+        for child in children {
+            let child_value = f(&child, &value);
+
+            self.leaves.insert(child, child_value);
+        }
+
+        children
+    }
+
+    /// Joins the four cells that were split from a cell back into that cell.
+    ///
+    /// - `point` - The cell to join the cells back into.
+    /// - `f` - A function that gives the value of the joined cell from the values of the cells
+    ///   being joined, in the order given by `AdaptiveCubeSpherePoint::children`.
+    ///
+    /// # Panics
+    /// Panics if the four cells split from the point are not all cells of the grid.
+    pub fn coarsen<F: FnOnce([T; 4]) -> T>(&mut self, point: &AdaptiveCubeSpherePoint<S>, f: F) {
+        let children = point.children();
+
+        assert!(
+            children.iter().all(|child| self.leaves.contains_key(child)),
+            "the cells split from {point:?} have been split further or are not part of the grid"
+        );
+
+        let values = children.map(|child| self.leaves.remove(&child).unwrap());
+
+        self.leaves.insert(*point, f(values));
+    }
+
+    /// Gets the cells next to a point in one direction.
+    ///
+    /// Moving in a direction follows the `GridPoint` methods of the point. When the cell in that
+    /// direction is the same size as the point or larger, this gives a single cell. When it has
+    /// been split into smaller cells, this gives every one of them that shares an edge with the
+    /// point, in order along the edge.
+    ///
+    /// - `point` - The point to get the neighbours of.
+    /// - `direction` - The direction to look in.
+    pub fn neighbours_in(&self, point: &AdaptiveCubeSpherePoint<S>, direction: Direction) -> Vec<AdaptiveCubeSpherePoint<S>> {
+        let mut neighbours = Vec::new();
+
+        self.collect_neighbours(point, direction, &mut neighbours);
+
+        neighbours
+    }
+
+    /// Gets the cells that share an edge with a point.
+    ///
+    /// This gives the cells from `neighbours_in` for up, right, down and left in that order
+    /// without repeating any cell.
+    ///
+    /// - `point` - The point to get the neighbours of.
+    pub fn neighbours(&self, point: &AdaptiveCubeSpherePoint<S>) -> Vec<AdaptiveCubeSpherePoint<S>> {
+        let mut neighbours = Vec::new();
+
+        for direction in [Direction::Up, Direction::Right, Direction::Down, Direction::Left] {
+            self.collect_neighbours(point, direction, &mut neighbours);
+        }
+
+        neighbours
+    }
+
+    /// Creates a new grid with the same cells by applying a function to each cell and its
+    /// neighbours.
+    ///
+    /// - `f` - A function that takes the value of a cell and the points and values of the cells
+    ///   given by `neighbours` and gives the new value of the cell.
+    pub fn map_neighbours<U, F: FnMut(&T, &[(AdaptiveCubeSpherePoint<S>, &T)]) -> U>(&self, mut f: F) -> AdaptiveCubeSphereGrid<U, S> {
+        let mut neighbours = Vec::new();
+
+        let leaves = self.leaves.iter()
+            .map(|(point, value)| {
+                neighbours.clear();
+                neighbours.extend(self.neighbours(point)
+                    .into_iter()
+                    .map(|neighbour| (neighbour, &self.leaves[&neighbour])));
+
+                (*point, f(value, &neighbours))
+            })
+            .collect();
+
+        AdaptiveCubeSphereGrid {
+            leaves,
+        }
+    }
+
+    /// Iterates over the cells of the grid along with their values.
+    pub fn iter(&self) -> impl ExactSizeIterator<Item = (AdaptiveCubeSpherePoint<S>, &T)> + DoubleEndedIterator {
+        self.leaves.iter()
+            .map(|(point, value)| (*point, value))
+    }
+
+    /// Iterates over the cells of the grid along with mutable references to their values.
+    pub fn iter_mut(&mut self) -> impl ExactSizeIterator<Item = (AdaptiveCubeSpherePoint<S>, &mut T)> + DoubleEndedIterator {
+        self.leaves.iter_mut()
+            .map(|(point, value)| (*point, value))
+    }
+
+    /// Iterates over the cells of the grid.
+    pub fn points(&self) -> impl ExactSizeIterator<Item = AdaptiveCubeSpherePoint<S>> + DoubleEndedIterator + '_ {
+        self.leaves.keys().copied()
+    }
+
+    /// Gets the surface area of the sphere that the grid covers.
+    ///
+    /// - `scale` - The radius of the sphere.
+    pub fn surface_area(&self, scale: f64) -> f64 {
+        4.0 * PI * scale * scale
+    }
+
+    /// Adds the cells next to a point in one direction to a list, skipping cells that are
+    /// already in the list.
+    ///
+    /// When the cell in that direction has been split, the point is split too so that each half of
+    /// its edge can be checked against the smaller cells.
+    ///
+    /// - `point` - The point to get the neighbours of.
+    /// - `direction` - The direction to look in.
+    /// - `neighbours` - The list to add the neighbours to.
+    fn collect_neighbours(&self, point: &AdaptiveCubeSpherePoint<S>, direction: Direction, neighbours: &mut Vec<AdaptiveCubeSpherePoint<S>>) {
+        match self.leaf(&point.step(direction)) {
+            Some(leaf) => if !neighbours.contains(&leaf) {
+                neighbours.push(leaf);
+            },
+            None => for child in point.edge_children(direction) {
+                self.collect_neighbours(&child, direction, neighbours);
+            },
+        }
+    }
+}
+
+impl <T: Default, const S: usize> Default for AdaptiveCubeSphereGrid<T, S> {
+    fn default() -> Self {
+        Self::from_fn(|_| T::default())
+    }
+}
+
+impl <T, const S: usize> Index<AdaptiveCubeSpherePoint<S>> for AdaptiveCubeSphereGrid<T, S> {
+    type Output = T;
+
+    /// Gets the value of the cell that contains a point.
+    ///
+    /// # Panics
+    /// Panics if the point has been split into smaller cells.
+    fn index(&self, index: AdaptiveCubeSpherePoint<S>) -> &Self::Output {
+        let leaf = self.leaf(&index)
+            .unwrap_or_else(|| panic!("{index:?} has been split into smaller cells"));
+
+        &self.leaves[&leaf]
+    }
+}
+
+impl <T, const S: usize> IndexMut<AdaptiveCubeSpherePoint<S>> for AdaptiveCubeSphereGrid<T, S> {
+    /// Gets a mutable reference to the value of the cell that contains a point.
+    ///
+    /// # Panics
+    /// Panics if the point has been split into smaller cells.
+    fn index_mut(&mut self, index: AdaptiveCubeSpherePoint<S>) -> &mut Self::Output {
+        let leaf = self.leaf(&index)
+            .unwrap_or_else(|| panic!("{index:?} has been split into smaller cells"));
+
+        self.leaves.get_mut(&leaf).unwrap()
+    }
+}
+
+impl <T, const S: usize> Index<&AdaptiveCubeSpherePoint<S>> for AdaptiveCubeSphereGrid<T, S> {
+    type Output = T;
+
+    fn index(&self, index: &AdaptiveCubeSpherePoint<S>) -> &Self::Output {
+        &self[*index]
+    }
+}
+
+impl <T, const S: usize> IndexMut<&AdaptiveCubeSpherePoint<S>> for AdaptiveCubeSphereGrid<T, S> {
+    fn index_mut(&mut self, index: &AdaptiveCubeSpherePoint<S>) -> &mut Self::Output {
+        &mut self[*index]
+    }
+}
+
+impl <T, const S: usize> IntoIterator for AdaptiveCubeSphereGrid<T, S> {
+    type Item = (AdaptiveCubeSpherePoint<S>, T);
+
+    type IntoIter = btree_map::IntoIter<AdaptiveCubeSpherePoint<S>, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.leaves.into_iter()
+    }
+}
+
+/// A cell of an `AdaptiveCubeSphereGrid` at any level of refinement.
+///
+/// A point at level `L` lies on a face that is split into `S * 2^L` cells along each side, so each
+/// point at level `L` covers four points at level `L + 1`. The X and Y axes of each face are the
+/// same as for `CubeSpherePoint`.
+///
+/// # Constant Parameters
+/// - `S` - The size of each side of each face before any cells are split.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct AdaptiveCubeSpherePoint<const S: usize> {
+    face: CubeFace,
+    level: u8,
+    x: u32,
+    y: u32,
+}
+
+impl <const S: usize> AdaptiveCubeSpherePoint<S> {
+    /// Creates a new `AdaptiveCubeSpherePoint` checking that the position lies on the face.
+    ///
+    /// This returns an error if the level is greater than `MAX_LEVEL` or if either coordinate is
+    /// `S * 2^level` or greater.
+    ///
+    /// - `face` - The face on which the point lies.
+    /// - `level` - The number of times that the cell of the base grid has been split.
+    /// - `x` - The X position on the face at that level.
+    /// - `y` - The Y position on the face at that level.
+    pub fn try_new(face: CubeFace, level: u8, x: u32, y: u32) -> Result<Self, OutOfBounds> {
+        if level <= MAX_LEVEL && (x as u64) < Self::size(level) && (y as u64) < Self::size(level) {
+            Ok(Self {
+                face,
+                level,
+                x,
+                y,
+            })
+        } else {
+            Err(OutOfBounds)
+        }
+    }
+
+    /// Gets the face on which the point lies.
+    pub fn face(&self) -> CubeFace {
+        self.face
+    }
+
+    /// Gets the number of times that the cell of the base grid has been split to get this point.
+    pub fn level(&self) -> u8 {
+        self.level
+    }
+
+    /// Gets the X position of the point on its face at its level.
+    pub fn x(&self) -> u32 {
+        self.x
+    }
+
+    /// Gets the Y position of the point on its face at its level.
+    pub fn y(&self) -> u32 {
+        self.y
+    }
+
+    /// Gets the point at the previous level that this point was split from.
+    ///
+    /// This returns `None` for points at level 0.
+    pub fn parent(&self) -> Option<Self> {
+        (self.level > 0).then(|| Self {
+            face: self.face,
+            level: self.level - 1,
+            x: self.x / 2,
+            y: self.y / 2,
+        })
+    }
+
+    /// Gets the four points at the next level that this point is split into.
+    ///
+    /// The points are in Z order: `(2x, 2y)`, `(2x + 1, 2y)`, `(2x, 2y + 1)` and then
+    /// `(2x + 1, 2y + 1)`.
+    ///
+    /// # Panics
+    /// Panics if the point is already at `MAX_LEVEL`.
+    pub fn children(&self) -> [Self; 4] {
+        assert!(self.level < MAX_LEVEL, "{self:?} cannot be split past level {MAX_LEVEL}");
+
+        [(0, 0), (1, 0), (0, 1), (1, 1)].map(|(x, y)| Self {
+            face: self.face,
+            level: self.level + 1,
+            x: self.x * 2 + x,
+            y: self.y * 2 + y,
+        })
+    }
+
+    /// Checks if another point lies inside this point.
+    ///
+    /// A point contains itself.
+    ///
+    /// - `other` - The point to check.
+    pub fn contains(&self, other: &Self) -> bool {
+        let shift = other.level.wrapping_sub(self.level);
+
+        self.face == other.face
+            && other.level >= self.level
+            && other.x >> shift == self.x
+            && other.y >> shift == self.y
+    }
+
+    /// Gets the position of the centre of this cell in 3D space.
+    ///
+    /// - `scale` - The scale of the 3D object.
+    pub fn cell_center(&self, scale: f64) -> (f64, f64, f64) {
+        self.face_position(self.x as f64 + 0.5, self.y as f64 + 0.5, scale)
+    }
+
+    /// Gets the position of a corner of this cell in 3D space.
+    ///
+    /// The corners are numbered in the same way as `CubeSpherePoint::cell_corner`.
+    ///
+    /// - `corner` - The corner to get.
+    /// - `scale` - The scale of the 3D object.
+    ///
+    /// # Panics
+    /// Panics if `corner` is greater than 3.
+    pub fn cell_corner(&self, corner: u8, scale: f64) -> (f64, f64, f64) {
+        let (x, y) = match corner {
+            0 => (0.0, 0.0),
+            1 => (1.0, 0.0),
+            2 => (1.0, 1.0),
+            3 => (0.0, 1.0),
+            _ => panic!("a cell only has 4 corners but corner {corner} was requested"),
+        };
+
+        self.face_position(self.x as f64 + x, self.y as f64 + y, scale)
+    }
+
+    /// Gets the solid angle covered by this cell in steradians.
+    ///
+    /// The four cells that a cell is split into cover the same solid angle as the cell.
+    pub fn solid_angle(&self) -> f64 {
+        let size = Self::size(self.level) as f64;
+
+        let area = |x: f64, y: f64| {
+            let u = x * 2.0 / size - 1.0;
+            let v = y * 2.0 / size - 1.0;
+
+            (u * v / (1.0 + u * u + v * v).sqrt()).atan()
+        };
+
+        let (x, y) = (self.x as f64, self.y as f64);
+
+        area(x + 1.0, y + 1.0) - area(x, y + 1.0) - area(x + 1.0, y) + area(x, y)
+    }
+
+    /// Gets the number of cells along each side of a face at a level.
+    ///
+    /// - `level` - The level to get the size of.
+    fn size(level: u8) -> u64 {
+        (base_size::<S>() as u64) << level
+    }
+
+    /// Gets the point at a level that contains a position on a face.
+    ///
+    /// - `face` - The face on which the position lies.
+    /// - `level` - The level of the point.
+    /// - `fx` - The X position on the face from 0 to 1.
+    /// - `fy` - The Y position on the face from 0 to 1.
+    fn from_face_fraction(face: CubeFace, level: u8, fx: f64, fy: f64) -> Self {
+        let size = Self::size(level);
+
+        // Clamp to account for floating point rounding error.
+        let cell = |f: f64| ((f * size as f64) as u64).min(size - 1) as u32;
+
+        Self {
+            face,
+            level,
+            x: cell(fx),
+            y: cell(fy),
+        }
+    }
+
+    /// Gets the position in 3D space of a continuous position on the face of this point measured
+    /// in cells at the level of this point.
+    ///
+    /// - `x` - The X position on the face.
+    /// - `y` - The Y position on the face.
+    /// - `scale` - The scale of the 3D object.
+    fn face_position(&self, x: f64, y: f64, scale: f64) -> (f64, f64, f64) {
+        let size = Self::size(self.level) as f64;
+        let (a, b) = (x * 2.0 / size - 1.0, y * 2.0 / size - 1.0);
+
+        let (x, y, z) = match self.face {
+            CubeFace::Front => (a, b, 1.0),
+            CubeFace::Back => (a, -b, -1.0),
+            CubeFace::Left => (-1.0, b, a),
+            CubeFace::Right => (1.0, b, -a),
+            CubeFace::Top => (a, 1.0, b),
+            CubeFace::Bottom => (a, -1.0, -b),
+        };
+
+        let length = (x * x + y * y + z * z).sqrt();
+
+        (x / length * scale, y / length * scale, z / length * scale)
+    }
+
+    /// Gets the point at the same level in a direction, moving onto the next face when the point
+    /// lies on the edge of its face.
+    ///
+    /// - `direction` - The direction to move in.
+    fn step(&self, direction: Direction) -> Self {
+        let size = Self::size(self.level) as i64;
+        let (x_axis, y_axis) = face_axes(self.face);
+
+        let (axis, distance) = match direction {
+            Direction::Up => (y_axis, -2),
+            Direction::Down => (y_axis, 2),
+            Direction::Left => (x_axis, -2),
+            Direction::Right => (x_axis, 2),
+        };
+
+        let mut centre = self.cube_centre();
+
+        for i in 0..3 {
+            centre[i] += axis[i] * distance;
+        }
+
+        // Fold each coordinate past the edge of the cube over the edge onto the next face.
+        while let Some(i) = centre.iter().position(|c| c.abs() > size) {
+            let normal = centre.iter().position(|c| c.abs() == size).unwrap();
+
+            centre[i] = centre[i].signum() * size;
+            centre[normal] = centre[normal].signum() * (size - 1);
+        }
+
+        Self::from_cube_centre(self.level, centre)
+    }
+
+    /// Gets the two points at the next level that this point is split into which lie along its
+    /// edge in a direction.
+    ///
+    /// - `direction` - The direction of the edge.
+    fn edge_children(&self, direction: Direction) -> [Self; 2] {
+        let [top_left, top_right, bottom_left, bottom_right] = self.children();
+
+        match direction {
+            Direction::Up => [top_left, top_right],
+            Direction::Down => [bottom_left, bottom_right],
+            Direction::Left => [top_left, bottom_left],
+            Direction::Right => [top_right, bottom_right],
+        }
+    }
+
+    /// Gets the centre of this point on a cube with a side length of twice the number of cells
+    /// along each side of a face at its level.
+    ///
+    /// Cell centres lie on odd integer coordinates so this can be converted back to a point exactly
+    /// using `from_cube_centre`.
+    fn cube_centre(&self) -> [i64; 3] {
+        let size = Self::size(self.level) as i64;
+        let a = self.x as i64 * 2 + 1 - size;
+        let b = self.y as i64 * 2 + 1 - size;
+
+        match self.face {
+            CubeFace::Front => [a, b, size],
+            CubeFace::Back => [a, -b, -size],
+            CubeFace::Left => [-size, b, a],
+            CubeFace::Right => [size, b, -a],
+            CubeFace::Top => [a, size, b],
+            CubeFace::Bottom => [a, -size, -b],
+        }
+    }
+
+    /// Gets the point at a level with the specified centre.
+    ///
+    /// This is the inverse of `cube_centre`.
+    ///
+    /// - `level` - The level of the point.
+    /// - `centre` - The position of the centre.
+    fn from_cube_centre(level: u8, [x, y, z]: [i64; 3]) -> Self {
+        let size = Self::size(level) as i64;
+
+        let (face, a, b) = if z == size {
+            (CubeFace::Front, x, y)
+        } else if z == -size {
+            (CubeFace::Back, x, -y)
+        } else if x == -size {
+            (CubeFace::Left, z, y)
+        } else if x == size {
+            (CubeFace::Right, -z, y)
+        } else if y == size {
+            (CubeFace::Top, x, z)
+        } else {
+            (CubeFace::Bottom, x, -z)
+        };
+
+        Self {
+            face,
+            level,
+            x: ((a + size - 1) / 2) as u32,
+            y: ((b + size - 1) / 2) as u32,
+        }
+    }
+
+    /// Gets the key that points are ordered by.
+    ///
+    /// This orders the faces and the cells of the base grid in the same way as `CubeSphereGrid`
+    /// and the cells split from each of them depth first in Z order.
+    fn order_key(&self) -> (usize, u32, u32, u64, u8) {
+        let face = FACE_ORDER.iter()
+            .position(|face| *face == self.face)
+            .unwrap();
+
+        let shift = MAX_LEVEL - self.level;
+        let mask = (1u64 << self.level) - 1;
+
+        (
+            face,
+            self.y >> self.level,
+            self.x >> self.level,
+            interleave((self.x as u64 & mask) << shift, (self.y as u64 & mask) << shift),
+            self.level,
+        )
+    }
+}
+
+impl <const S: usize> PartialOrd for AdaptiveCubeSpherePoint<S> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl <const S: usize> Ord for AdaptiveCubeSpherePoint<S> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.order_key().cmp(&other.order_key())
+    }
+}
+
+impl <const S: usize> GridPoint for AdaptiveCubeSpherePoint<S> {
+    fn up(&self) -> Self {
+        self.step(Direction::Up)
+    }
+
+    fn down(&self) -> Self {
+        self.step(Direction::Down)
+    }
+
+    fn left(&self) -> Self {
+        self.step(Direction::Left)
+    }
+
+    fn right(&self) -> Self {
+        self.step(Direction::Right)
+    }
+
+    fn position(&self, scale: f64) -> (f64, f64, f64) {
+        self.face_position(self.x as f64, self.y as f64, scale)
+    }
+}
+
+impl <const S: usize> SpherePoint for AdaptiveCubeSpherePoint<S> {
+    fn center_position(&self, scale: f64) -> (f64, f64, f64) {
+        self.cell_center(scale)
+    }
+
+    /// Gets the point of the base grid at a geographic position.
+    ///
+    /// Use `AdaptiveCubeSphereGrid::leaf_at_geographic` to get the cell of a refined grid.
+    fn from_geographic(latitude: f64, longitude: f64) -> Self {
+        let (face, fx, fy) = locate(geographic_position(latitude, longitude));
+
+        Self::from_face_fraction(face, 0, fx, fy)
+    }
+
+    fn latitude(&self) -> f64 {
+        let (x, y, z) = self.cell_center(1.0);
+
+        y.atan2((x * x + z * z).sqrt())
+    }
+
+    fn longitude(&self) -> f64 {
+        let (x, _, z) = self.cell_center(1.0);
+
+        x.atan2(z)
+    }
+}
+
+/// Checks that the base size of a grid is supported and gets it as a `u32`.
+///
+/// # Panics
+/// Panics if `S` is 0 or greater than 65536 as the coordinates of the most refined cells would
+/// not fit in a `u32`.
+fn base_size<const S: usize>() -> u32 {
+    assert!((1..=1 << 16).contains(&S), "the size of each face must be between 1 and 65536 but it is {S}");
+
+    S as u32
+}
+
+/// Gets the directions of the X and Y axes of a face.
+///
+/// - `face` - The face to get the axes of.
+fn face_axes(face: CubeFace) -> ([i64; 3], [i64; 3]) {
+    match face {
+        CubeFace::Front => ([1, 0, 0], [0, 1, 0]),
+        CubeFace::Back => ([1, 0, 0], [0, -1, 0]),
+        CubeFace::Left => ([0, 0, 1], [0, 1, 0]),
+        CubeFace::Right => ([0, 0, -1], [0, 1, 0]),
+        CubeFace::Top => ([1, 0, 0], [0, 0, 1]),
+        CubeFace::Bottom => ([1, 0, 0], [0, 0, -1]),
+    }
+}
+
+/// Gets the position on a sphere with a radius of 1 of a geographic position.
+///
+/// - `latitude` - The latitude of the position in radians.
+/// - `longitude` - The longitude of the position in radians.
+fn geographic_position(latitude: f64, longitude: f64) -> (f64, f64, f64) {
+    (latitude.cos() * longitude.sin(), latitude.sin(), latitude.cos() * longitude.cos())
+}
+
+/// Gets the face of the cube that a direction points through and the position on that face
+/// from 0 to 1 along each axis.
+///
+/// - `position` - The direction from the centre of the cube.
+fn locate((x, y, z): (f64, f64, f64)) -> (CubeFace, f64, f64) {
+    let (ax, ay, az) = (x.abs(), y.abs(), z.abs());
+
+    let (face, a, b) = if az >= ax && az >= ay {
+        if z > 0.0 {
+            (CubeFace::Front, x / az, y / az)
+        } else {
+            (CubeFace::Back, x / az, -y / az)
+        }
+    } else if ax >= ay {
+        if x < 0.0 {
+            (CubeFace::Left, z / ax, y / ax)
+        } else {
+            (CubeFace::Right, -z / ax, y / ax)
+        }
+    } else if y > 0.0 {
+        (CubeFace::Top, x / ay, z / ay)
+    } else {
+        (CubeFace::Bottom, x / ay, -z / ay)
+    };
+
+    (face, (a + 1.0) / 2.0, (b + 1.0) / 2.0)
+}
+
+/// Interleaves the bits of two coordinates with the bits of `y` above the bits of `x`.
+///
+/// - `x` - The X coordinate, which must fit in 32 bits.
+/// - `y` - The Y coordinate, which must fit in 32 bits.
+fn interleave(x: u64, y: u64) -> u64 {
+    (0..32).fold(0, |code, bit| code | ((x >> bit) & 1) << (bit * 2) | ((y >> bit) & 1) << (bit * 2 + 1))
+}
+
+#[cfg(test)]
+mod test {
+    use std::f64::consts::PI;
+
+    use approx::assert_relative_eq;
+
+    use crate::{Direction, GridPoint, sphere::{CubeFace, SpherePoint}};
+
+    use super::{AdaptiveCubeSphereGrid, AdaptiveCubeSpherePoint};
+
+    #[test]
+    fn test_adaptive_from_fn_base_cells() {
+        let grid: AdaptiveCubeSphereGrid<u32, 4> = AdaptiveCubeSphereGrid::from_fn(|point| point.x() + point.y());
+
+        assert_eq!(6 * 4 * 4, grid.len());
+        assert_eq!(0, grid.depth());
+        assert!(grid.iter().all(|(point, value)| point.level() == 0 && *value == point.x() + point.y()));
+    }
+
+    #[test]
+    fn test_adaptive_refine_coarsen() {
+        let mut grid: AdaptiveCubeSphereGrid<u32, 4> = AdaptiveCubeSphereGrid::from_fn(|_| 1);
+        let point = AdaptiveCubeSpherePoint::try_new(CubeFace::Front, 0, 1, 2).unwrap();
+
+        let children = grid.refine(&point, |_, value| value * 2);
+
+        assert_eq!(6 * 4 * 4 + 3, grid.len());
+        assert!(!grid.is_leaf(&point));
+        assert!(children.iter().all(|child| grid[*child] == 2 && point.contains(child)));
+
+        grid.refine(&children[3], |_, value| value + 1);
+
+        assert_eq!(3, grid[children[3].children()[0]]);
+        assert_eq!(children[3].children()[0], grid.leaf(&children[3].children()[0].children()[2]).unwrap());
+
+        grid.coarsen(&children[3], |values| values.into_iter().sum());
+        grid.coarsen(&point, |values| values.into_iter().sum());
+
+        assert_eq!(6 * 4 * 4, grid.len());
+        assert_eq!(2 + 2 + 2 + 12, grid[point]);
+    }
+
+    #[test]
+    fn test_adaptive_iteration_order() {
+        let mut grid: AdaptiveCubeSphereGrid<(), 2> = AdaptiveCubeSphereGrid::default();
+        let point = AdaptiveCubeSpherePoint::try_new(CubeFace::Top, 0, 0, 0).unwrap();
+
+        let children = grid.refine(&point, |_, _| ());
+
+        let points: Vec<_> = grid.points().take(5).collect();
+
+        assert_eq!(children.to_vec(), points[..4]);
+        assert_eq!(AdaptiveCubeSpherePoint::try_new(CubeFace::Top, 0, 1, 0).unwrap(), points[4]);
+        assert!(grid.points().zip(grid.points().skip(1)).all(|(a, b)| a < b));
+    }
+
+    #[test]
+    fn test_adaptive_neighbours_across_levels() {
+        let mut grid: AdaptiveCubeSphereGrid<(), 4> = AdaptiveCubeSphereGrid::default();
+        let point = AdaptiveCubeSpherePoint::try_new(CubeFace::Front, 0, 1, 1).unwrap();
+        let right = point.right();
+
+        let children = grid.refine(&right, |_, _| ());
+
+        assert_eq!(vec![children[0], children[2]], grid.neighbours_in(&point, Direction::Right));
+        assert_eq!(vec![point], grid.neighbours_in(&children[0], Direction::Left));
+        assert_eq!(vec![point], grid.neighbours_in(&children[2], Direction::Left));
+        assert_eq!(vec![children[1]], grid.neighbours_in(&children[0], Direction::Right));
+        assert_eq!(vec![right.up()], grid.neighbours_in(&children[0], Direction::Up));
+        assert_eq!(5, grid.neighbours(&point).len());
+    }
+
+    #[test]
+    fn test_adaptive_neighbours_across_faces() {
+        let mut grid: AdaptiveCubeSphereGrid<(), 3> = AdaptiveCubeSphereGrid::default();
+
+        for point in grid.points().collect::<Vec<_>>() {
+            grid.refine(&point, |_, _| ());
+        }
+
+        let coarse = grid.points().next().unwrap().parent().unwrap();
+        grid.coarsen(&coarse, |_| ());
+
+        for point in grid.points() {
+            let neighbours = grid.neighbours(&point);
+
+            assert!(neighbours.len() >= 4);
+
+            for neighbour in neighbours {
+                assert!(grid.neighbours(&neighbour).contains(&point), "{point:?} and {neighbour:?}");
+            }
+        }
+    }
+
+    #[test]
+    fn test_adaptive_step_adjacent() {
+        let grid: AdaptiveCubeSphereGrid<(), 5> = AdaptiveCubeSphereGrid::default();
+
+        for point in grid.points() {
+            let steps = [point.up(), point.down(), point.left(), point.right()];
+
+            for step in steps {
+                assert_ne!(point, step);
+                assert!([step.up(), step.down(), step.left(), step.right()].contains(&point));
+            }
+        }
+    }
+
+    #[test]
+    fn test_adaptive_leaf_at_geographic() {
+        let mut grid: AdaptiveCubeSphereGrid<(), 4> = AdaptiveCubeSphereGrid::default();
+
+        let base = AdaptiveCubeSpherePoint::from_geographic(0.3, 1.0);
+        let children = grid.refine(&base, |_, _| ());
+
+        let leaf = grid.leaf_at_geographic(0.3, 1.0);
+
+        assert!(children.contains(&leaf));
+        assert!(base.contains(&leaf));
+
+        let (latitude, longitude) = (leaf.latitude(), leaf.longitude());
+
+        assert_eq!(leaf, grid.leaf_at_geographic(latitude, longitude));
+    }
+
+    #[test]
+    fn test_adaptive_solid_angle() {
+        let mut grid: AdaptiveCubeSphereGrid<(), 3> = AdaptiveCubeSphereGrid::default();
+
+        let point = grid.points().nth(4).unwrap();
+        let children = grid.refine(&point, |_, _| ());
+        grid.refine(&children[1], |_, _| ());
+
+        let total: f64 = grid.points().map(|point| point.solid_angle()).sum();
+
+        assert_relative_eq!(4.0 * PI, total, epsilon = 1e-9);
+        assert_relative_eq!(point.solid_angle(), children.iter().map(|child| child.solid_angle()).sum::<f64>(), epsilon = 1e-12);
+    }
+}
@@ -0,0 +1,343 @@
+//! A module containing helpers for running cellular automata on surface grids.
+
+use std::mem::swap;
+
+use crate::SurfaceGrid;
+
+/// A pair of grids where each step reads from the current grid and writes into the other before
+/// swapping them.
+///
+/// This avoids allocating a new grid for each step without having to swap the buffers by hand.
+///
+/// # Type Parameters
+/// - `G` - The type of grid that is buffered.
+#[derive(Debug, Clone, Default)]
+pub struct DoubleBuffered<G> {
+    /// The grid holding the current state.
+    current: G,
+    /// The grid that the next state is written to.
+    next: G,
+}
+
+impl <G: Default> DoubleBuffered<G> {
+    /// Creates a new double buffered grid with a default second grid.
+    ///
+    /// - `grid` - The initial state.
+    pub fn new(grid: G) -> Self {
+        Self::from_grids(grid, G::default())
+    }
+}
+
+impl <G> DoubleBuffered<G> {
+    /// Creates a new double buffered grid from two existing grids.
+    ///
+    /// - `current` - The initial state.
+    /// - `next` - The grid to write the first step into. Its contents are scratch data.
+    pub fn from_grids(current: G, next: G) -> Self {
+        Self {
+            current,
+            next,
+        }
+    }
+
+    /// Gets the current state.
+    pub fn current(&self) -> &G {
+        &self.current
+    }
+
+    /// Gets a mutable reference to the current state.
+    pub fn current_mut(&mut self) -> &mut G {
+        &mut self.current
+    }
+
+    /// Converts this into its current state.
+    pub fn into_current(self) -> G {
+        self.current
+    }
+
+    /// Advances by a single step.
+    ///
+    /// The provided function is called with the current state and the grid to write the next
+    /// state into, after which the two grids are swapped. The grid to write into holds an older
+    /// state so every cell should be written.
+    ///
+    /// - `f` - The function that computes the next state.
+    pub fn step<F: FnOnce(&G, &mut G)>(&mut self, f: F) {
+        f(&self.current, &mut self.next);
+
+        self.swap();
+    }
+
+    /// Swaps the current grid with the grid that the next state is written to.
+    fn swap(&mut self) {
+        swap(&mut self.current, &mut self.next);
+    }
+}
+
+/// A cellular automaton running on a surface grid.
+///
+/// Each step is written into a second grid which is then swapped with the current grid so that
+/// stepping does not allocate.
+///
+/// # Type Parameters
+/// - `G` - The type of grid that the automaton runs on.
+///
+/// # Serialization
+/// With the `serde` feature enabled an automaton serializes its current grid and generation. The
+/// second grid only holds scratch data so it is recreated using `Default` when deserializing.
+#[derive(Debug, Clone)]
+pub struct Automaton<G> {
+    /// The current state of the automaton and the grid that the next generation is written to.
+    buffers: DoubleBuffered<G>,
+    /// The number of steps that have been run.
+    generation: u64,
+}
+
+impl <G: Default> Automaton<G> {
+    /// Creates a new automaton starting from a grid.
+    ///
+    /// - `grid` - The initial state of the automaton.
+    pub fn new(grid: G) -> Self {
+        Self {
+            buffers: DoubleBuffered::new(grid),
+            generation: 0,
+        }
+    }
+}
+
+impl <G> Automaton<G> {
+    /// Gets the current state of the automaton.
+    pub fn current(&self) -> &G {
+        self.buffers.current()
+    }
+
+    /// Gets a mutable reference to the current state of the automaton.
+    pub fn current_mut(&mut self) -> &mut G {
+        self.buffers.current_mut()
+    }
+
+    /// Converts this automaton into its current state.
+    pub fn into_current(self) -> G {
+        self.buffers.into_current()
+    }
+
+    /// Gets the number of steps that have been run.
+    pub fn generation(&self) -> u64 {
+        self.generation
+    }
+
+    /// Advances the automaton by a single generation by applying a rule in parallel to each cell
+    /// and its direct neighbours including diagonals.
+    ///
+    /// The provided function is called with the arguments: up_left, up, up_right,
+    /// left, current, right, down_left, down, down_right. This is the order given by
+    /// `DIAGONAL_ORDER`.
+    ///
+    /// - `rule` - The rule to apply.
+    pub fn step<
+                T: Send + Sync,
+                F: Fn(&T, &T, &T, &T, &T, &T, &T, &T, &T) -> T + Send + Sync
+            >(&mut self, rule: F) where G: SurfaceGrid<T> + Sync {
+        self.buffers.step(|current, next| current.step_into(next, rule));
+
+        self.generation += 1;
+    }
+
+    /// Advances the automaton by a number of generations using the same rule as `step`.
+    ///
+    /// - `generations` - The number of generations to run.
+    /// - `rule` - The rule to apply.
+    pub fn run<
+                T: Send + Sync,
+                F: Fn(&T, &T, &T, &T, &T, &T, &T, &T, &T) -> T + Send + Sync
+            >(&mut self, generations: usize, rule: F) where G: SurfaceGrid<T> + Sync {
+        self.run_with(generations, rule, |_, _| {});
+    }
+
+    /// Advances the automaton by a number of generations calling a function after each one.
+    ///
+    /// The callback is called with the new state and generation, which can be used to log or
+    /// record the progress of the automaton.
+    ///
+    /// - `generations` - The number of generations to run.
+    /// - `rule` - The rule to apply.
+    /// - `callback` - The function to call after each generation.
+    pub fn run_with<
+                T: Send + Sync,
+                F: Fn(&T, &T, &T, &T, &T, &T, &T, &T, &T) -> T + Send + Sync,
+                C: FnMut(&G, u64)
+            >(&mut self, generations: usize, rule: F, mut callback: C) where G: SurfaceGrid<T> + Sync {
+        for _ in 0..generations {
+            self.step(&rule);
+
+            callback(self.buffers.current(), self.generation);
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl <G: serde::Serialize> serde::Serialize for Automaton<G> {
+    fn serialize<R: serde::Serializer>(&self, serializer: R) -> Result<R::Ok, R::Error> {
+        #[derive(serde::Serialize)]
+        struct State<'a, G> {
+            generation: u64,
+            current: &'a G,
+        }
+
+        State {
+            generation: self.generation,
+            current: self.buffers.current(),
+        }.serialize(serializer)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl <'de, G: serde::Deserialize<'de> + Default> serde::Deserialize<'de> for Automaton<G> {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        #[derive(serde::Deserialize)]
+        struct State<G> {
+            generation: u64,
+            current: G,
+        }
+
+        let state = State::deserialize(deserializer)?;
+
+        Ok(Self {
+            buffers: DoubleBuffered::new(state.current),
+            generation: state.generation,
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{SurfaceGrid, sphere::RectangleSphereGrid};
+
+    use super::{Automaton, DoubleBuffered};
+
+    /// The rules of Conway's game of life.
+    #[allow(clippy::too_many_arguments)]
+    fn conway(s1: &bool, s2: &bool, s3: &bool, s4: &bool, current: &bool, s6: &bool, s7: &bool, s8: &bool, s9: &bool) -> bool {
+        let count = [s1, s2, s3, s4, s6, s7, s8, s9]
+            .into_iter()
+            .filter(|s| **s)
+            .count();
+
+        count == 3 || (*current && count == 2)
+    }
+
+    /// Creates a grid with a repeating pattern of live cells.
+    fn initial() -> RectangleSphereGrid<bool, 20, 10> {
+        let mut i = 0;
+
+        RectangleSphereGrid::from_fn(|_| {
+            i += 1;
+
+            i % 3 == 0 || i % 7 == 0
+        })
+    }
+
+    #[test]
+    fn test_automaton_step() {
+        let grid = initial();
+
+        let mut automaton = Automaton::new(grid.clone());
+
+        automaton.step(conway);
+        automaton.step(conway);
+
+        let expected = grid.map_neighbours_diagonals(conway)
+            .map_neighbours_diagonals(conway);
+
+        assert_eq!(automaton.generation(), 2);
+        assert_eq!(automaton.current(), &expected);
+    }
+
+    #[test]
+    fn test_automaton_run_blinker() {
+        // Three cells in a row in the middle of the grid.
+        let blinker: RectangleSphereGrid<bool, 20, 10> = RectangleSphereGrid::from_index_fn(|i| (85..88).contains(&i));
+
+        let mut automaton = Automaton::new(blinker.clone());
+
+        automaton.run(1, conway);
+
+        assert_ne!(automaton.current(), &blinker);
+
+        automaton.run(9, conway);
+
+        assert_eq!(automaton.generation(), 10);
+        assert_eq!(automaton.current(), &blinker);
+    }
+
+    #[test]
+    fn test_automaton_run_with() {
+        let mut automaton = Automaton::new(initial());
+        let mut expected = Automaton::new(initial());
+
+        let mut generations = Vec::new();
+
+        automaton.run_with(4, conway, |grid, generation| {
+            expected.step(conway);
+
+            assert_eq!(grid, expected.current());
+
+            generations.push(generation);
+        });
+
+        assert_eq!(generations, vec![1, 2, 3, 4]);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_automaton_serde() {
+        let grid = initial();
+
+        let mut automaton = Automaton::new(grid);
+
+        automaton.step(conway);
+
+        let json = serde_json::to_string(&automaton).unwrap();
+        let mut restored: Automaton<RectangleSphereGrid<bool, 20, 10>> = serde_json::from_str(&json).unwrap();
+
+        assert_eq!(restored.generation(), 1);
+        assert_eq!(restored.current(), automaton.current());
+
+        automaton.step(conway);
+        restored.step(conway);
+
+        assert_eq!(restored.generation(), 2);
+        assert_eq!(restored.current(), automaton.current());
+    }
+
+    #[test]
+    fn test_double_buffered_step() {
+        let grid = initial();
+
+        let mut buffered = DoubleBuffered::new(grid.clone());
+
+        buffered.step(|src, dst| dst.set_from_neighbours_diagonals(src, conway));
+        buffered.step(|src, dst| dst.set_from_neighbours_diagonals_par(src, conway));
+
+        let expected = grid.map_neighbours_diagonals(conway)
+            .map_neighbours_diagonals(conway);
+
+        assert_eq!(buffered.current(), &expected);
+        assert_eq!(buffered.into_current(), expected);
+    }
+
+    #[test]
+    fn test_double_buffered_from_grids() {
+        let scratch: RectangleSphereGrid<u32, 20, 10> = RectangleSphereGrid::from_index_fn(|i| i as u32);
+
+        let mut buffered = DoubleBuffered::from_grids(RectangleSphereGrid::default(), scratch);
+
+        buffered.current_mut().set_from_fn(|_| 1);
+
+        // The previous state is always fully overwritten so the scratch contents are never seen.
+        buffered.step(|src, dst| dst.set_from_fn(|point| src[point] * 2));
+        buffered.step(|src, dst| dst.set_from_fn(|point| src[point] + 3));
+
+        assert!(buffered.current().iter().all(|(_, value)| *value == 5));
+    }
+}
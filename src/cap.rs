@@ -0,0 +1,617 @@
+//! A module containing grids covering a cap of a sphere with a boundary at its rim.
+
+use std::{f64::consts::PI, fmt::Debug, hash::Hash, marker::PhantomData, ops::{Index, IndexMut}, vec};
+
+use rayon::prelude::*;
+
+use crate::{GridPoint, OutOfBounds, Surface, SurfaceGrid, Topology, sphere::SpherePoint};
+
+assert_send_sync!([T: Send + Sync, const W: usize, const H: usize, const D: usize, E: CapRim]
+    CapSphereGrid<T, W, H, D, E>,
+    CapSpherePoint<W, H, D, E>,
+);
+
+/// The ways that the rim of a cap can behave.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum CapEdge {
+    /// Moving off the rim stays in the row at the rim.
+    Clamp,
+    /// Moving off the rim bounces back into the row next to the rim.
+    Reflect,
+}
+
+/// A type parameter that selects how the rim of a `CapSphereGrid` behaves.
+pub trait CapRim: Debug + Clone + Copy + PartialEq + Eq + Hash + Default + Send + Sync {
+    /// The behaviour of the rim.
+    const EDGE: CapEdge;
+}
+
+/// The rim of a cap where moving off the rim stays in the row at the rim.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct Clamped;
+
+impl CapRim for Clamped {
+    const EDGE: CapEdge = CapEdge::Clamp;
+}
+
+/// The rim of a cap where moving off the rim bounces back into the row next to the rim.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct Reflected;
+
+impl CapRim for Reflected {
+    const EDGE: CapEdge = CapEdge::Reflect;
+}
+
+/// A grid covering a cap around the north pole of a sphere, such as a hemisphere.
+///
+/// The cap is split into `H` rings of equal width in latitude going out from the north pole to
+/// the rim, each of which is split into `W` cells of equal width in longitude in the same way as a
+/// `RectangleSphereGrid`. As with `RectangleSphereGrid`, `up` moves towards the pole in the half of
+/// the cap with longitudes below `PI` and away from it in the other half, so that moving over the
+/// pole continues down the other side of the cap. Moving off the rim is chosen by the `E` type
+/// parameter, and the `checked_` methods of `GridPoint` return `None` instead so that the rim can
+/// be treated as an open boundary. To cover a region around another point, rotate positions so
+/// that the centre of the region is at the north pole.
+///
+/// # Type Parameters
+/// - `T` - The type of data that the grid holds.
+/// - `E` - The behaviour of the rim, one of `Clamped` or `Reflected`.
+///
+/// # Constant Parameters
+/// - `W` - The number of cells around the cap, which should be even.
+/// - `H` - The number of rings from the pole to the rim.
+/// - `D` - The angle between the pole and the rim in degrees, from 1 to 180. This is 90 for a
+///   hemisphere.
+///
+/// # Iteration Order
+/// `iter`, `points` and `into_iter` all visit the grid in row-major order, starting at `(0, 0)`
+/// at the pole and increasing `x` before `y`. This order is stable.
+///
+/// # Thread Safety
+/// The cells of the cap are kept in one `Vec` and `E` only chooses how the rim behaves, so the grid
+/// is `Send` when `T` is `Send` and `Sync` when `T` is `Sync`.
+///
+/// # Panics
+/// Getting the position of a point panics if `D` is not between 1 and 180.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct CapSphereGrid<T, const W: usize, const H: usize, const D: usize, E: CapRim = Clamped> {
+    /// The data held in this grid.
+    data: Vec<T>,
+    /// The behaviour of the rim.
+    rim: PhantomData<E>,
+}
+
+impl <T: Default, const W: usize, const H: usize, const D: usize, E: CapRim> Default for CapSphereGrid<T, W, H, D, E> {
+    fn default() -> Self {
+        Self::from_fn(|_| T::default())
+    }
+}
+
+impl <T, const W: usize, const H: usize, const D: usize, E: CapRim> CapSphereGrid<T, W, H, D, E> {
+    /// Gets the values of the cells in the same order as the points of the grid.
+    pub fn as_slice(&self) -> &[T] {
+        &self.data
+    }
+
+    /// Gets mutable references to the values of the cells in the same order as the points of the
+    /// grid.
+    pub fn as_mut_slice(&mut self) -> &mut [T] {
+        &mut self.data
+    }
+
+    /// Gets the values of the cells as bytes in the same order as `as_slice`.
+    #[cfg(feature = "bytemuck")]
+    pub fn as_bytes(&self) -> &[u8] where T: bytemuck::Pod {
+        bytemuck::cast_slice(self.as_slice())
+    }
+
+    /// Gets the values of the cells as mutable bytes in the same order as `as_slice`.
+    #[cfg(feature = "bytemuck")]
+    pub fn as_bytes_mut(&mut self) -> &mut [u8] where T: bytemuck::Pod {
+        bytemuck::cast_slice_mut(self.as_mut_slice())
+    }
+}
+
+impl <T, const W: usize, const H: usize, const D: usize, E: CapRim> SurfaceGrid<T> for CapSphereGrid<T, W, H, D, E> {
+    type Point = CapSpherePoint<W, H, D, E>;
+
+    fn from_fn<F: FnMut(&Self::Point) -> T>(mut f: F) -> Self {
+        Self {
+            data: (0..W * H)
+                .map(|i| f(&CapSpherePoint::from_index(i)))
+                .collect(),
+            rim: PhantomData,
+        }
+    }
+
+    fn from_fn_par<F: Fn(&Self::Point) -> T + Send + Sync>(f: F) -> Self where T: Send + Sync {
+        let mut data = Vec::with_capacity(W * H);
+
+        (0..W * H).into_par_iter()
+            .map(|i| f(&CapSpherePoint::from_index(i)))
+            .collect_into_vec(&mut data);
+
+        Self {
+            data,
+            rim: PhantomData,
+        }
+    }
+
+    fn try_from_fn<X, F: FnMut(&Self::Point) -> Result<T, X>>(mut f: F) -> Result<Self, X> {
+        Ok(Self {
+            data: (0..W * H)
+                .map(|i| f(&CapSpherePoint::from_index(i)))
+                .collect::<Result<_, _>>()?,
+            rim: PhantomData,
+        })
+    }
+
+    fn try_from_fn_par<X: Send, F: Fn(&Self::Point) -> Result<T, X> + Send + Sync>(f: F) -> Result<Self, X> where T: Send + Sync {
+        Ok(Self {
+            data: (0..W * H).into_par_iter()
+                .map(|i| f(&CapSpherePoint::from_index(i)))
+                .collect::<Result<_, _>>()?,
+            rim: PhantomData,
+        })
+    }
+
+    fn from_vec(values: Vec<T>) -> Option<Self> {
+        if values.len() != W * H {
+            return None;
+        }
+
+        Some(Self {
+            data: values,
+            rim: PhantomData,
+        })
+    }
+
+    fn set_from_fn<F: FnMut(&Self::Point) -> T>(&mut self, mut f: F) {
+        for (i, value) in self.data.iter_mut().enumerate() {
+            *value = f(&CapSpherePoint::from_index(i));
+        }
+    }
+
+    fn set_from_fn_par<F: Fn(&Self::Point) -> T + Send + Sync>(&mut self, f: F) where T: Send + Sync {
+        self.data.par_iter_mut().enumerate().for_each(|(i, value)| {
+            *value = f(&CapSpherePoint::from_index(i));
+        });
+    }
+
+    fn iter<'a>(&'a self) -> impl ExactSizeIterator<Item = (Self::Point, &'a T)> + DoubleEndedIterator where T: 'a {
+        self.data.iter()
+            .enumerate()
+            .map(|(i, value)| (CapSpherePoint::from_index(i), value))
+    }
+
+    fn par_iter<'a>(&'a self) -> impl IndexedParallelIterator<Item = (Self::Point, &'a T)> where T: 'a + Send + Sync {
+        self.data.par_iter()
+            .enumerate()
+            .map(|(i, value)| (CapSpherePoint::from_index(i), value))
+    }
+
+    fn par_iter_mut<'a>(&'a mut self) -> impl IndexedParallelIterator<Item = (Self::Point, &'a mut T)> where T: 'a + Send + Sync {
+        self.data.par_iter_mut()
+            .enumerate()
+            .map(|(i, value)| (CapSpherePoint::from_index(i), value))
+    }
+
+    fn points(&self) -> impl ExactSizeIterator<Item = Self::Point> + DoubleEndedIterator {
+        (0..W * H)
+            .map(CapSpherePoint::from_index)
+    }
+
+    fn par_points(&self) -> impl IndexedParallelIterator<Item = Self::Point> {
+        (0..W * H).into_par_iter()
+            .map(CapSpherePoint::from_index)
+    }
+
+    fn par_map_to_vec<U: Send, F: Fn(&Self::Point, &T) -> U + Send + Sync>(&self, f: F) -> Vec<U> where T: Send + Sync {
+        let mut result = Vec::with_capacity(W * H);
+
+        self.data.par_iter()
+            .enumerate()
+            .map(|(i, value)| f(&CapSpherePoint::from_index(i), value))
+            .collect_into_vec(&mut result);
+
+        result
+    }
+
+    fn to_index(&self, point: &Self::Point) -> usize {
+        point.index()
+    }
+
+    fn at(&self, point: &Self::Point) -> &T {
+        &self[point]
+    }
+
+    fn at_mut(&mut self, point: &Self::Point) -> &mut T {
+        &mut self[point]
+    }
+
+    /// Gets the surface area of the cap.
+    ///
+    /// - `scale` - The radius of the sphere.
+    fn surface_area(&self, scale: f64) -> f64 {
+        2.0 * PI * scale * scale * (1.0 - CapSpherePoint::<W, H, D, E>::cap_angle().cos())
+    }
+
+    fn topology(&self) -> Topology {
+        Topology::uniform(Surface::SphereCap, 1, W, H)
+    }
+
+    fn contiguous_values_mut(&mut self) -> Option<&mut [T]> {
+        Some(self.as_mut_slice())
+    }
+}
+
+impl <T, const W: usize, const H: usize, const D: usize, E: CapRim> Index<CapSpherePoint<W, H, D, E>> for CapSphereGrid<T, W, H, D, E> {
+    type Output = T;
+
+    fn index(&self, index: CapSpherePoint<W, H, D, E>) -> &Self::Output {
+        &self.data[index.index()]
+    }
+}
+
+impl <T, const W: usize, const H: usize, const D: usize, E: CapRim> IndexMut<CapSpherePoint<W, H, D, E>> for CapSphereGrid<T, W, H, D, E> {
+    fn index_mut(&mut self, index: CapSpherePoint<W, H, D, E>) -> &mut Self::Output {
+        &mut self.data[index.index()]
+    }
+}
+
+impl <T, const W: usize, const H: usize, const D: usize, E: CapRim> Index<&CapSpherePoint<W, H, D, E>> for CapSphereGrid<T, W, H, D, E> {
+    type Output = T;
+
+    fn index(&self, index: &CapSpherePoint<W, H, D, E>) -> &Self::Output {
+        &self[*index]
+    }
+}
+
+impl <T, const W: usize, const H: usize, const D: usize, E: CapRim> IndexMut<&CapSpherePoint<W, H, D, E>> for CapSphereGrid<T, W, H, D, E> {
+    fn index_mut(&mut self, index: &CapSpherePoint<W, H, D, E>) -> &mut Self::Output {
+        &mut self[*index]
+    }
+}
+
+impl <T, const W: usize, const H: usize, const D: usize, E: CapRim> FromIterator<T> for CapSphereGrid<T, W, H, D, E> {
+    /// Creates a grid from the values of its cells in the same order as `points`.
+    ///
+    /// # Panics
+    /// Panics if the iterator does not yield exactly one value for each cell in the grid.
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        Self::from_vec(iter.into_iter().collect())
+            .expect("the iterator must yield one value for each cell in the grid")
+    }
+}
+
+impl <T, const W: usize, const H: usize, const D: usize, E: CapRim> IntoIterator for CapSphereGrid<T, W, H, D, E> {
+    type Item = (CapSpherePoint<W, H, D, E>, T);
+
+    type IntoIter = vec::IntoIter<Self::Item>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        let data: Vec<_> = self.data.into_iter()
+            .enumerate()
+            .map(|(i, value)| (CapSpherePoint::from_index(i), value))
+            .collect();
+
+        data.into_iter()
+    }
+}
+
+#[cfg(feature = "serde")]
+impl <T: serde::Serialize, const W: usize, const H: usize, const D: usize, E: CapRim> serde::Serialize for CapSphereGrid<T, W, H, D, E> {
+    /// Serializes the values in this grid as a sequence in the same order as `iter`.
+    fn serialize<R: serde::Serializer>(&self, serializer: R) -> Result<R::Ok, R::Error> {
+        serializer.collect_seq(self.data.iter())
+    }
+}
+
+#[cfg(feature = "serde")]
+impl <'de, T: serde::Deserialize<'de>, const W: usize, const H: usize, const D: usize, E: CapRim> serde::Deserialize<'de> for CapSphereGrid<T, W, H, D, E> {
+    /// Deserializes a grid from a sequence of values in the same order as `iter`.
+    fn deserialize<D2: serde::Deserializer<'de>>(deserializer: D2) -> Result<Self, D2::Error> {
+        let values = Vec::<T>::deserialize(deserializer)?;
+        let length = values.len();
+
+        Self::from_vec(values)
+            .ok_or_else(|| serde::de::Error::invalid_length(length, &format!("a sequence of {} values", W * H).as_str()))
+    }
+}
+
+/// A point on a `CapSphereGrid`.
+///
+/// # Constant Parameters
+/// - `W` - The number of cells around the cap.
+/// - `H` - The number of rings from the pole to the rim.
+/// - `D` - The angle between the pole and the rim in degrees.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct CapSpherePoint<const W: usize, const H: usize, const D: usize, E: CapRim = Clamped> {
+    /// The X position around the cap.
+    x: u32,
+    /// The ring, counted from the pole.
+    y: u32,
+    /// The behaviour of the rim.
+    rim: PhantomData<E>,
+}
+
+impl <const W: usize, const H: usize, const D: usize, E: CapRim> CapSpherePoint<W, H, D, E> {
+    /// Creates a new `CapSpherePoint`.
+    ///
+    /// - `x` - The X position around the cap.
+    /// - `y` - The ring.
+    fn new(x: u32, y: u32) -> Self {
+        Self {
+            x,
+            y,
+            rim: PhantomData,
+        }
+    }
+
+    /// Creates a new `CapSpherePoint` checking that the position lies on the grid.
+    ///
+    /// This returns an error if `x` is `W` or greater or `y` is `H` or greater.
+    ///
+    /// - `x` - The X position around the cap.
+    /// - `y` - The ring, counted from the pole.
+    pub fn try_new(x: u32, y: u32) -> Result<Self, OutOfBounds> {
+        if (x as usize) < W && (y as usize) < H {
+            Ok(Self::new(x, y))
+        } else {
+            Err(OutOfBounds)
+        }
+    }
+
+    /// Gets the point at an index in the order that `CapSphereGrid::points` visits them.
+    ///
+    /// - `index` - The index of the point.
+    fn from_index(index: usize) -> Self {
+        Self::new((index % W) as u32, (index / W) as u32)
+    }
+
+    /// Gets the index of this point in the order that `CapSphereGrid::points` visits them.
+    fn index(&self) -> usize {
+        self.x as usize + self.y as usize * W
+    }
+
+    /// Gets the angle between the pole and the rim in radians.
+    ///
+    /// # Panics
+    /// Panics if `D` is not between 1 and 180.
+    fn cap_angle() -> f64 {
+        assert!((1..=180).contains(&D), "the angle of a cap must be between 1 and 180 degrees");
+
+        (D as f64).to_radians()
+    }
+
+    /// Determines whether this point is in the ring at the rim of the cap.
+    pub fn is_rim(&self) -> bool {
+        self.y == H as u32 - 1
+    }
+
+    /// Gets the position of the centre of this cell in 3D space.
+    ///
+    /// - `scale` - The scale of the 3D object.
+    pub fn cell_center(&self, scale: f64) -> (f64, f64, f64) {
+        Self::grid_position(self.x as f64 + 0.5, self.y as f64 + 0.5, scale)
+    }
+
+    /// Gets the solid angle covered by this cell in steradians.
+    ///
+    /// This is the area of the cell on a sphere with a radius of 1.
+    pub fn solid_angle(&self) -> f64 {
+        let inner = self.y as f64 / H as f64 * Self::cap_angle();
+        let outer = (self.y as f64 + 1.0) / H as f64 * Self::cap_angle();
+
+        2.0 * PI / W as f64 * (inner.cos() - outer.cos())
+    }
+
+    /// Determines whether this cell is in the half of the cap where `up` moves away from the pole.
+    fn is_reversed(&self) -> bool {
+        self.x >= W as u32 / 2
+    }
+
+    /// Moves one ring towards the pole, or over it onto the opposite side of the cap.
+    fn inward(&self) -> Self {
+        if self.y == 0 {
+            Self::new((self.x + W as u32 / 2) % W as u32, 0)
+        } else {
+            Self::new(self.x, self.y - 1)
+        }
+    }
+
+    /// Moves one ring away from the pole, or off the rim in the way chosen by `E`.
+    fn outward(&self) -> Self {
+        if !self.is_rim() {
+            Self::new(self.x, self.y + 1)
+        } else if E::EDGE == CapEdge::Reflect && H > 1 {
+            Self::new(self.x, self.y - 1)
+        } else {
+            *self
+        }
+    }
+
+    /// Gets the position in 3D space of a continuous position on the grid.
+    ///
+    /// - `x` - The X position around the cap.
+    /// - `y` - The position from the pole to the rim.
+    /// - `scale` - The scale of the 3D object.
+    fn grid_position(x: f64, y: f64, scale: f64) -> (f64, f64, f64) {
+        let latitude = PI / 2.0 - y / H as f64 * Self::cap_angle();
+        let longitude = x / W as f64 * PI * 2.0;
+
+        (
+            scale * latitude.cos() * longitude.sin(),
+            scale * latitude.sin(),
+            scale * latitude.cos() * longitude.cos(),
+        )
+    }
+}
+
+impl <const W: usize, const H: usize, const D: usize, E: CapRim> GridPoint for CapSpherePoint<W, H, D, E> {
+    fn up(&self) -> Self {
+        if self.is_reversed() {
+            self.outward()
+        } else {
+            self.inward()
+        }
+    }
+
+    fn down(&self) -> Self {
+        if self.is_reversed() {
+            self.inward()
+        } else {
+            self.outward()
+        }
+    }
+
+    fn left(&self) -> Self {
+        Self::new((self.x + W as u32 - 1) % W as u32, self.y)
+    }
+
+    fn right(&self) -> Self {
+        Self::new((self.x + 1) % W as u32, self.y)
+    }
+
+    fn checked_up(&self) -> Option<Self> {
+        (!self.is_reversed() || !self.is_rim()).then(|| self.up())
+    }
+
+    fn checked_down(&self) -> Option<Self> {
+        (self.is_reversed() || !self.is_rim()).then(|| self.down())
+    }
+
+    /// Gets the position of the point in 3D space.
+    ///
+    /// This is the position of the corner of the cell nearest to the pole with the smallest
+    /// longitude. Use `cell_center` to get the centre of the cell.
+    ///
+    /// - `scale` - The scale of the 3D object.
+    fn position(&self, scale: f64) -> (f64, f64, f64) {
+        Self::grid_position(self.x as f64, self.y as f64, scale)
+    }
+}
+
+impl <const W: usize, const H: usize, const D: usize, E: CapRim> SpherePoint for CapSpherePoint<W, H, D, E> {
+    fn center_position(&self, scale: f64) -> (f64, f64, f64) {
+        self.cell_center(scale)
+    }
+
+    /// Gets the point containing a geographic position.
+    ///
+    /// Positions outside of the cap give the nearest point in the ring at the rim.
+    ///
+    /// - `latitude` - The latitude of the position in radians.
+    /// - `longitude` - The longitude of the position in radians.
+    fn from_geographic(latitude: f64, longitude: f64) -> Self {
+        let y = ((PI / 2.0 - latitude) / Self::cap_angle() * H as f64) as u32;
+        let x = (longitude.rem_euclid(PI * 2.0) / (PI * 2.0) * W as f64) as u32;
+
+        Self::new(x.min(W as u32 - 1), y.min(H as u32 - 1))
+    }
+
+    fn latitude(&self) -> f64 {
+        PI / 2.0 - self.y as f64 / H as f64 * Self::cap_angle()
+    }
+
+    fn longitude(&self) -> f64 {
+        self.x as f64 / W as f64 * PI * 2.0
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::f64::consts::PI;
+
+    use approx::assert_relative_eq;
+    use rayon::iter::ParallelIterator;
+
+    use crate::{GridPoint, SurfaceGrid, sphere::SpherePoint};
+
+    use super::{CapSphereGrid, CapSpherePoint, Reflected};
+
+    #[test]
+    fn test_cap_clamped() {
+        let grid: CapSphereGrid<(), 8, 5, 90> = CapSphereGrid::default();
+
+        for point in grid.points() {
+            let outward = if point.x >= 4 { point.checked_up() } else { point.checked_down() };
+
+            assert_eq!(outward.is_none(), point.y == 4);
+            assert!(point.checked_left().is_some() && point.checked_right().is_some());
+
+            if point.y == 4 {
+                assert_eq!(if point.x >= 4 { point.up() } else { point.down() }, point);
+            }
+        }
+
+        assert_eq!(grid.points().count(), 40);
+    }
+
+    #[test]
+    fn test_cap_reflected() {
+        let point: CapSpherePoint<8, 5, 90, Reflected> = CapSpherePoint::new(1, 4);
+
+        assert_eq!(point.down(), CapSpherePoint::new(1, 3));
+        assert_eq!(point.checked_down(), None);
+        assert_eq!(CapSpherePoint::<8, 5, 90, Reflected>::new(5, 4).up(), CapSpherePoint::new(5, 3));
+        assert_eq!(CapSpherePoint::<8, 1, 90, Reflected>::new(1, 0).down(), CapSpherePoint::new(1, 0));
+    }
+
+    #[test]
+    fn test_cap_pole() {
+        let point: CapSpherePoint<8, 5, 90> = CapSpherePoint::new(1, 0);
+
+        // Moving up over the pole continues down the other side of the cap.
+        assert_eq!(point.up(), CapSpherePoint::new(5, 0));
+        assert_eq!(point.up().up(), CapSpherePoint::new(5, 1));
+        assert_eq!(point.checked_up(), Some(point.up()));
+    }
+
+    #[test]
+    fn test_cap_from_geographic() {
+        let grid: CapSphereGrid<(), 16, 6, 30> = CapSphereGrid::default();
+
+        for point in grid.points() {
+            let (x, y, z) = point.cell_center(1.0);
+
+            assert_eq!(CapSpherePoint::nearest_point(x, y, z), point);
+            assert_relative_eq!(point.latitude(), PI / 2.0 - point.y as f64 * PI / 36.0, epsilon = 1e-12);
+        }
+
+        // Positions outside of the cap give a point on the rim.
+        assert_eq!(CapSpherePoint::<16, 6, 30>::from_geographic(-PI / 4.0, 0.1), CapSpherePoint::new(0, 5));
+    }
+
+    #[test]
+    fn test_cap_surface_area() {
+        let hemisphere: CapSphereGrid<(), 16, 6, 90> = CapSphereGrid::default();
+        let sphere: CapSphereGrid<(), 16, 6, 180> = CapSphereGrid::default();
+
+        assert_relative_eq!(hemisphere.surface_area(2.0), 8.0 * PI, epsilon = 1e-9);
+        assert_relative_eq!(sphere.surface_area(1.0), 4.0 * PI, epsilon = 1e-9);
+        assert_relative_eq!(hemisphere.points().map(|point| point.solid_angle()).sum::<f64>(), 2.0 * PI, epsilon = 1e-9);
+    }
+
+    #[test]
+    fn test_cap_iteration_order() {
+        let grid: CapSphereGrid<usize, 8, 3, 45> = CapSphereGrid::from_index_fn(|i| i);
+
+        for (i, (point, value)) in grid.iter().enumerate() {
+            assert_eq!(*value, i);
+            assert_eq!(grid.to_index(&point), i);
+            assert_eq!(point, CapSpherePoint::new(i as u32 % 8, i as u32 / 8));
+        }
+
+        assert_eq!(grid.par_points().collect::<Vec<_>>(), grid.points().collect::<Vec<_>>());
+        assert_eq!(CapSphereGrid::from_fn_par(|point| grid[*point]), grid);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_cap_invalid_angle() {
+        let grid: CapSphereGrid<(), 8, 3, 0> = CapSphereGrid::default();
+
+        grid.surface_area(1.0);
+    }
+}
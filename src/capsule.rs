@@ -0,0 +1,588 @@
+//! A module containing grids wrapped around the surface of a capsule.
+
+use std::{f64::consts::PI, ops::{Index, IndexMut}, vec};
+
+use rayon::prelude::*;
+
+use crate::{GridPoint, OutOfBounds, Surface, SurfaceGrid, Topology, cylinder::CylinderFace};
+
+assert_send_sync!([T: Send + Sync, const W: usize, const H: usize]
+    CapsuleGrid<T, W, H>,
+    CapsulePoint<W, H>,
+);
+
+/// A grid wrapped around a capsule, which is a cylinder with a hemisphere on each end.
+///
+/// The grid is made of rows of `W` cells going around the capsule. There are `W / 4` rows on each
+/// hemisphere, spaced evenly in latitude as in a `RectangleSphereGrid`, and `H` rows along the
+/// side of the cylinder, so the rows on each hemisphere join seamlessly onto the rows on the side.
+/// As with `RectangleSphereGrid`, `up` moves towards the top in the half of the capsule with
+/// angles below `PI` and towards the bottom in the other half, so that moving over the pole of a
+/// hemisphere continues down the other side of the capsule. Moving onto a neighbour and back
+/// always returns to the same point.
+///
+/// # Type Parameters
+/// - `T` - The type of data that the grid holds.
+///
+/// # Constant Parameters
+/// - `W` - The number of cells around the capsule, which must be a multiple of 4.
+/// - `H` - The number of rows along the side of the cylinder.
+///
+/// # Iteration Order
+/// `iter`, `points` and `into_iter` all visit the grid in row-major order, starting at `(0, 0)`
+/// at the top of the capsule and increasing `x` before `y`. This visits the top hemisphere, then
+/// the side and then the bottom hemisphere. This order is stable.
+///
+/// # Thread Safety
+/// The rows of both hemispheres and the side are kept together in one `Vec`, so the grid is `Send`
+/// when `T` is `Send` and `Sync` when `T` is `Sync`.
+///
+/// # Panics
+/// Creating a grid panics if `W` is not a multiple of 4.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct CapsuleGrid<T, const W: usize, const H: usize> {
+    /// The data held in this grid.
+    data: Vec<T>,
+}
+
+impl <T: Default, const W: usize, const H: usize> Default for CapsuleGrid<T, W, H> {
+    fn default() -> Self {
+        Self::from_fn(|_| T::default())
+    }
+}
+
+impl <T, const W: usize, const H: usize> CapsuleGrid<T, W, H> {
+    /// Gets the values of the cells in the same order as the points of the grid.
+    pub fn as_slice(&self) -> &[T] {
+        &self.data
+    }
+
+    /// Gets mutable references to the values of the cells in the same order as the points of the
+    /// grid.
+    pub fn as_mut_slice(&mut self) -> &mut [T] {
+        &mut self.data
+    }
+
+    /// Gets the values of the cells as bytes in the same order as `as_slice`.
+    #[cfg(feature = "bytemuck")]
+    pub fn as_bytes(&self) -> &[u8] where T: bytemuck::Pod {
+        bytemuck::cast_slice(self.as_slice())
+    }
+
+    /// Gets the values of the cells as mutable bytes in the same order as `as_slice`.
+    #[cfg(feature = "bytemuck")]
+    pub fn as_bytes_mut(&mut self) -> &mut [u8] where T: bytemuck::Pod {
+        bytemuck::cast_slice_mut(self.as_mut_slice())
+    }
+}
+
+impl <T, const W: usize, const H: usize> SurfaceGrid<T> for CapsuleGrid<T, W, H> {
+    type Point = CapsulePoint<W, H>;
+
+    fn from_fn<F: FnMut(&Self::Point) -> T>(mut f: F) -> Self {
+        Self {
+            data: (0..CapsulePoint::<W, H>::size())
+                .map(|i| f(&CapsulePoint::from_index(i)))
+                .collect(),
+        }
+    }
+
+    fn from_fn_par<F: Fn(&Self::Point) -> T + Send + Sync>(f: F) -> Self where T: Send + Sync {
+        let size = CapsulePoint::<W, H>::size();
+        let mut data = Vec::with_capacity(size);
+
+        (0..size).into_par_iter()
+            .map(|i| f(&CapsulePoint::from_index(i)))
+            .collect_into_vec(&mut data);
+
+        Self {
+            data,
+        }
+    }
+
+    fn try_from_fn<X, F: FnMut(&Self::Point) -> Result<T, X>>(mut f: F) -> Result<Self, X> {
+        Ok(Self {
+            data: (0..CapsulePoint::<W, H>::size())
+                .map(|i| f(&CapsulePoint::from_index(i)))
+                .collect::<Result<_, _>>()?,
+        })
+    }
+
+    fn try_from_fn_par<X: Send, F: Fn(&Self::Point) -> Result<T, X> + Send + Sync>(f: F) -> Result<Self, X> where T: Send + Sync {
+        Ok(Self {
+            data: (0..CapsulePoint::<W, H>::size()).into_par_iter()
+                .map(|i| f(&CapsulePoint::from_index(i)))
+                .collect::<Result<_, _>>()?,
+        })
+    }
+
+    fn from_vec(values: Vec<T>) -> Option<Self> {
+        if values.len() != CapsulePoint::<W, H>::size() {
+            return None;
+        }
+
+        Some(Self {
+            data: values,
+        })
+    }
+
+    fn set_from_fn<F: FnMut(&Self::Point) -> T>(&mut self, mut f: F) {
+        for (i, value) in self.data.iter_mut().enumerate() {
+            *value = f(&CapsulePoint::from_index(i));
+        }
+    }
+
+    fn set_from_fn_par<F: Fn(&Self::Point) -> T + Send + Sync>(&mut self, f: F) where T: Send + Sync {
+        self.data.par_iter_mut().enumerate().for_each(|(i, value)| {
+            *value = f(&CapsulePoint::from_index(i));
+        });
+    }
+
+    fn iter<'a>(&'a self) -> impl ExactSizeIterator<Item = (Self::Point, &'a T)> + DoubleEndedIterator where T: 'a {
+        self.data.iter()
+            .enumerate()
+            .map(|(i, value)| (CapsulePoint::from_index(i), value))
+    }
+
+    fn par_iter<'a>(&'a self) -> impl IndexedParallelIterator<Item = (Self::Point, &'a T)> where T: 'a + Send + Sync {
+        self.data.par_iter()
+            .enumerate()
+            .map(|(i, value)| (CapsulePoint::from_index(i), value))
+    }
+
+    fn par_iter_mut<'a>(&'a mut self) -> impl IndexedParallelIterator<Item = (Self::Point, &'a mut T)> where T: 'a + Send + Sync {
+        self.data.par_iter_mut()
+            .enumerate()
+            .map(|(i, value)| (CapsulePoint::from_index(i), value))
+    }
+
+    fn points(&self) -> impl ExactSizeIterator<Item = Self::Point> + DoubleEndedIterator {
+        (0..self.data.len())
+            .map(CapsulePoint::from_index)
+    }
+
+    fn par_points(&self) -> impl IndexedParallelIterator<Item = Self::Point> {
+        (0..self.data.len()).into_par_iter()
+            .map(CapsulePoint::from_index)
+    }
+
+    fn par_map_to_vec<U: Send, F: Fn(&Self::Point, &T) -> U + Send + Sync>(&self, f: F) -> Vec<U> where T: Send + Sync {
+        let mut result = Vec::with_capacity(self.data.len());
+
+        self.data.par_iter()
+            .enumerate()
+            .map(|(i, value)| f(&CapsulePoint::from_index(i), value))
+            .collect_into_vec(&mut result);
+
+        result
+    }
+
+    fn to_index(&self, point: &Self::Point) -> usize {
+        point.index()
+    }
+
+    fn at(&self, point: &Self::Point) -> &T {
+        &self[point]
+    }
+
+    fn at_mut(&mut self, point: &Self::Point) -> &mut T {
+        &mut self[point]
+    }
+
+    /// Gets the surface area of the capsule used by `GridPoint::position`.
+    ///
+    /// This is the area of the side of the cylinder plus the area of the two hemispheres.
+    ///
+    /// - `scale` - The radius of the capsule.
+    fn surface_area(&self, scale: f64) -> f64 {
+        let height = CapsulePoint::<W, H>::height(scale);
+
+        2.0 * PI * scale * height + 4.0 * PI * scale * scale
+    }
+
+    fn topology(&self) -> Topology {
+        Topology::uniform(Surface::Capsule, 1, W, H + 2 * CapsulePoint::<W, H>::cap_rows())
+    }
+
+    fn contiguous_values_mut(&mut self) -> Option<&mut [T]> {
+        Some(self.as_mut_slice())
+    }
+}
+
+impl <T, const W: usize, const H: usize> Index<CapsulePoint<W, H>> for CapsuleGrid<T, W, H> {
+    type Output = T;
+
+    fn index(&self, index: CapsulePoint<W, H>) -> &Self::Output {
+        &self.data[index.index()]
+    }
+}
+
+impl <T, const W: usize, const H: usize> IndexMut<CapsulePoint<W, H>> for CapsuleGrid<T, W, H> {
+    fn index_mut(&mut self, index: CapsulePoint<W, H>) -> &mut Self::Output {
+        &mut self.data[index.index()]
+    }
+}
+
+impl <T, const W: usize, const H: usize> Index<&CapsulePoint<W, H>> for CapsuleGrid<T, W, H> {
+    type Output = T;
+
+    fn index(&self, index: &CapsulePoint<W, H>) -> &Self::Output {
+        &self[*index]
+    }
+}
+
+impl <T, const W: usize, const H: usize> IndexMut<&CapsulePoint<W, H>> for CapsuleGrid<T, W, H> {
+    fn index_mut(&mut self, index: &CapsulePoint<W, H>) -> &mut Self::Output {
+        &mut self[*index]
+    }
+}
+
+impl <T, const W: usize, const H: usize> FromIterator<T> for CapsuleGrid<T, W, H> {
+    /// Creates a grid from the values of its cells in the same order as `points`.
+    ///
+    /// # Panics
+    /// Panics if the iterator does not yield exactly one value for each cell in the grid.
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        Self::from_vec(iter.into_iter().collect())
+            .expect("the iterator must yield one value for each cell in the grid")
+    }
+}
+
+impl <T, const W: usize, const H: usize> IntoIterator for CapsuleGrid<T, W, H> {
+    type Item = (CapsulePoint<W, H>, T);
+
+    type IntoIter = vec::IntoIter<Self::Item>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        let data: Vec<_> = self.data.into_iter()
+            .enumerate()
+            .map(|(i, value)| (CapsulePoint::from_index(i), value))
+            .collect();
+
+        data.into_iter()
+    }
+}
+
+#[cfg(feature = "serde")]
+impl <T: serde::Serialize, const W: usize, const H: usize> serde::Serialize for CapsuleGrid<T, W, H> {
+    /// Serializes the values in this grid as a sequence in the same order as `iter`.
+    fn serialize<R: serde::Serializer>(&self, serializer: R) -> Result<R::Ok, R::Error> {
+        serializer.collect_seq(self.data.iter())
+    }
+}
+
+#[cfg(feature = "serde")]
+impl <'de, T: serde::Deserialize<'de>, const W: usize, const H: usize> serde::Deserialize<'de> for CapsuleGrid<T, W, H> {
+    /// Deserializes a grid from a sequence of values in the same order as `iter`.
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let values = Vec::<T>::deserialize(deserializer)?;
+        let length = values.len();
+        let expected = CapsulePoint::<W, H>::size();
+
+        Self::from_vec(values)
+            .ok_or_else(|| serde::de::Error::invalid_length(length, &format!("a sequence of {} values", expected).as_str()))
+    }
+}
+
+/// A point on a `CapsuleGrid`.
+///
+/// # Constant Parameters
+/// - `W` - The number of cells around the capsule.
+/// - `H` - The number of rows along the side of the cylinder.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct CapsulePoint<const W: usize, const H: usize> {
+    /// The X position around the capsule.
+    x: u32,
+    /// The row, counted from the top of the capsule.
+    y: u32,
+}
+
+impl <const W: usize, const H: usize> CapsulePoint<W, H> {
+    /// Creates a new point.
+    ///
+    /// - `x` - The X position around the capsule.
+    /// - `y` - The row.
+    fn new(x: u32, y: u32) -> Self {
+        Self {
+            x, y,
+        }
+    }
+
+    /// Creates a new `CapsulePoint` checking that the position lies on the grid.
+    ///
+    /// This returns an error if `x` is `W` or greater or `y` is not less than the number of rows, which is `H` plus `W / 2` for the hemispheres.
+    ///
+    /// - `x` - The X position around the capsule.
+    /// - `y` - The row, counted from the top of the capsule.
+    pub fn try_new(x: u32, y: u32) -> Result<Self, OutOfBounds> {
+        if (x as usize) < W && (y as usize) < H + 2 * Self::cap_rows() {
+            Ok(Self::new(x, y))
+        } else {
+            Err(OutOfBounds)
+        }
+    }
+
+    /// Gets the number of rows on each hemisphere.
+    ///
+    /// # Panics
+    /// Panics if `W` is not a multiple of 4.
+    fn cap_rows() -> usize {
+        assert!(W > 0 && W.is_multiple_of(4), "the width of a capsule must be a multiple of 4");
+
+        W / 4
+    }
+
+    /// Gets the number of cells on the grid.
+    fn size() -> usize {
+        W * (H + 2 * Self::cap_rows())
+    }
+
+    /// Gets the length of the side of the cylinder.
+    ///
+    /// The cells on the side are square so this is `H` cells of the width given by dividing the
+    /// circumference by `W`.
+    ///
+    /// - `scale` - The radius of the capsule.
+    fn height(scale: f64) -> f64 {
+        PI * 2.0 * scale / W as f64 * H as f64
+    }
+
+    /// Gets the point at an index in the order that `CapsuleGrid::points` visits them.
+    ///
+    /// - `index` - The index of the point.
+    fn from_index(index: usize) -> Self {
+        Self::new((index % W) as u32, (index / W) as u32)
+    }
+
+    /// Gets the index of this point in the order that `CapsuleGrid::points` visits them.
+    fn index(&self) -> usize {
+        self.x as usize + self.y as usize * W
+    }
+
+    /// Gets the part of the capsule that this point lies on.
+    ///
+    /// The hemispheres are the `Top` and `Bottom` faces.
+    pub fn face(&self) -> CylinderFace {
+        let rows = Self::cap_rows() as u32;
+
+        if self.y < rows {
+            CylinderFace::Top
+        } else if self.y < rows + H as u32 {
+            CylinderFace::Side
+        } else {
+            CylinderFace::Bottom
+        }
+    }
+
+    /// Gets the position of the centre of this cell in 3D space.
+    ///
+    /// - `scale` - The radius of the capsule.
+    pub fn cell_center(&self, scale: f64) -> (f64, f64, f64) {
+        Self::grid_position(self.x as f64 + 0.5, self.y as f64 + 0.5, scale)
+    }
+
+    /// Determines whether this cell is in the half of the capsule where `up` moves towards the
+    /// bottom.
+    fn is_reversed(&self) -> bool {
+        self.x >= W as u32 / 2
+    }
+
+    /// Gets the cell on the opposite side of the capsule in the same row.
+    fn opposite(&self) -> Self {
+        Self::new((self.x + W as u32 / 2) % W as u32, self.y)
+    }
+
+    /// Moves one row towards the top, or over the top onto the opposite side of the capsule.
+    fn towards_top(&self) -> Self {
+        if self.y == 0 {
+            self.opposite()
+        } else {
+            Self::new(self.x, self.y - 1)
+        }
+    }
+
+    /// Moves one row towards the bottom, or over the bottom onto the opposite side of the capsule.
+    fn towards_bottom(&self) -> Self {
+        if self.y as usize == H + 2 * Self::cap_rows() - 1 {
+            self.opposite()
+        } else {
+            Self::new(self.x, self.y + 1)
+        }
+    }
+
+    /// Gets the position in 3D space of a continuous position on the grid.
+    ///
+    /// - `x` - The X position around the capsule.
+    /// - `y` - The position from the top of the capsule.
+    /// - `scale` - The radius of the capsule.
+    fn grid_position(x: f64, y: f64, scale: f64) -> (f64, f64, f64) {
+        let rows = Self::cap_rows() as f64;
+        let height = Self::height(scale);
+        let angle = x / W as f64 * PI * 2.0;
+
+        let (radius, y) = if y < rows {
+            let latitude = (rows - y) / rows * PI / 2.0;
+
+            (scale * latitude.cos(), height / 2.0 + scale * latitude.sin())
+        } else if y <= rows + H as f64 {
+            (scale, height / 2.0 - (y - rows) / H as f64 * height)
+        } else {
+            let latitude = (y - rows - H as f64) / rows * PI / 2.0;
+
+            (scale * latitude.cos(), -height / 2.0 - scale * latitude.sin())
+        };
+
+        (radius * angle.sin(), y, radius * angle.cos())
+    }
+}
+
+impl <const W: usize, const H: usize> GridPoint for CapsulePoint<W, H> {
+    fn up(&self) -> Self {
+        if self.is_reversed() {
+            self.towards_bottom()
+        } else {
+            self.towards_top()
+        }
+    }
+
+    fn down(&self) -> Self {
+        if self.is_reversed() {
+            self.towards_top()
+        } else {
+            self.towards_bottom()
+        }
+    }
+
+    fn left(&self) -> Self {
+        Self::new((self.x + W as u32 - 1) % W as u32, self.y)
+    }
+
+    fn right(&self) -> Self {
+        Self::new((self.x + 1) % W as u32, self.y)
+    }
+
+    /// Gets the position of the point in 3D space.
+    ///
+    /// The capsule has a radius of `scale` and is centred on the origin with the Y axis running
+    /// along its middle. The cells on the side are square so its length is `H` cells of the width
+    /// given by dividing the circumference by `W`. This is the position of the corner of the cell
+    /// with the smallest X and Y coordinates.
+    ///
+    /// - `scale` - The radius of the capsule.
+    fn position(&self, scale: f64) -> (f64, f64, f64) {
+        Self::grid_position(self.x as f64, self.y as f64, scale)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::f64::consts::PI;
+
+    use approx::assert_relative_eq;
+    use itertools::Itertools;
+
+    use crate::{GridPoint, SurfaceGrid, cylinder::CylinderFace};
+
+    use super::{CapsuleGrid, CapsulePoint};
+
+    #[test]
+    fn test_capsule_neighbours() {
+        let grid: CapsuleGrid<(), 12, 5> = CapsuleGrid::default();
+
+        assert_eq!(grid.points().count(), 12 * (5 + 6));
+
+        for point in grid.points() {
+            let neighbours = point.neighbours();
+
+            assert!(neighbours.iter().all_unique());
+            assert!(!neighbours.contains(&point));
+
+            // Moving onto a neighbour and back always returns to the same point.
+            for neighbour in neighbours {
+                assert!(neighbour.neighbours().contains(&point), "{point:?} is not a neighbour of {neighbour:?}");
+            }
+        }
+    }
+
+    #[test]
+    fn test_capsule_joins() {
+        let point: CapsulePoint<12, 5> = CapsulePoint::new(1, 3);
+
+        assert_eq!(point.face(), CylinderFace::Side);
+        assert_eq!(point.up().face(), CylinderFace::Top);
+        assert_eq!(CapsulePoint::<12, 5>::new(1, 7).down().face(), CylinderFace::Bottom);
+
+        // Moving up over the top continues down the other side of the capsule.
+        assert_eq!(CapsulePoint::<12, 5>::new(1, 0).up(), CapsulePoint::new(7, 0));
+        assert_eq!(CapsulePoint::<12, 5>::new(1, 0).up().up(), CapsulePoint::new(7, 1));
+    }
+
+    #[test]
+    fn test_capsule_position() {
+        let grid: CapsuleGrid<(), 16, 4> = CapsuleGrid::default();
+        let height = PI / 2.0;
+
+        for point in grid.points() {
+            let (x, y, z) = point.cell_center(2.0);
+            let radius = (x * x + z * z).sqrt();
+
+            match point.face() {
+                CylinderFace::Side => {
+                    assert_relative_eq!(radius, 2.0, epsilon = 1e-12);
+                    assert!(y < height && y > -height);
+                },
+                CylinderFace::Top => {
+                    assert_relative_eq!(radius * radius + (y - height).powi(2), 4.0, epsilon = 1e-12);
+                    assert!(y > height);
+                },
+                CylinderFace::Bottom => {
+                    assert_relative_eq!(radius * radius + (y + height).powi(2), 4.0, epsilon = 1e-12);
+                    assert!(y < -height);
+                },
+            }
+
+            // Neighbouring cells are close together, including across the joins.
+            for neighbour in point.neighbours() {
+                let (nx, ny, nz) = neighbour.cell_center(2.0);
+
+                assert!(((x - nx).powi(2) + (y - ny).powi(2) + (z - nz).powi(2)).sqrt() < 1.0);
+            }
+        }
+
+        let (_, top, _) = CapsulePoint::<16, 4>::new(0, 0).position(2.0);
+        let (_, bottom, _) = CapsulePoint::<16, 4>::new(0, 8).position(2.0);
+
+        assert_relative_eq!(top, height + 2.0);
+        assert_relative_eq!(bottom, -height, epsilon = 1e-12);
+    }
+
+    #[test]
+    fn test_capsule_iteration_order() {
+        let grid: CapsuleGrid<usize, 8, 3> = CapsuleGrid::from_index_fn(|i| i);
+
+        for (i, (point, value)) in grid.iter().enumerate() {
+            assert_eq!(*value, i);
+            assert_eq!(grid.to_index(&point), i);
+        }
+
+        let faces: Vec<_> = grid.points().map(|point| point.face()).dedup().collect();
+
+        assert_eq!(faces, [CylinderFace::Top, CylinderFace::Side, CylinderFace::Bottom]);
+        assert_eq!(CapsuleGrid::from_fn_par(|point| grid[*point]), grid);
+        assert_eq!(CapsuleGrid::from_vec(grid.to_vec()), Some(grid));
+    }
+
+    #[test]
+    fn test_capsule_surface_area() {
+        let grid: CapsuleGrid<(), 16, 4> = CapsuleGrid::default();
+
+        // The side is PI long.
+        assert_relative_eq!(grid.surface_area(2.0), 4.0 * PI * PI + 16.0 * PI);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_capsule_width() {
+        let _: CapsuleGrid<(), 10, 3> = CapsuleGrid::default();
+    }
+}
@@ -0,0 +1,1265 @@
+//! A module containing grids that are split into chunks which can be spilled out of memory.
+
+use std::{fs, io, ops::{Index, IndexMut}, path::{Path, PathBuf}, sync::{OnceLock, atomic::{AtomicU64, AtomicUsize, Ordering}}, vec};
+
+use rayon::prelude::*;
+
+use crate::{GridPoint, SurfaceGrid, Topology};
+
+assert_send_sync!([T: Send + Sync, G: SurfaceGrid<()> + Send + Sync, const C: usize, const R: usize] where [G::Point: Send + Sync]
+    ChunkedGrid<T, G, C, R, DiskStore>,
+    DiskStore,
+);
+
+/// A type that can be written to and read from the bytes of a chunk by a `DiskStore`.
+pub trait ChunkBytes: Sized {
+    /// The number of bytes used by each value.
+    const SIZE: usize;
+
+    /// Appends the bytes of this value to a buffer.
+    ///
+    /// - `bytes` - The buffer to write to.
+    fn write_bytes(&self, bytes: &mut Vec<u8>);
+
+    /// Reads a value from `SIZE` bytes.
+    ///
+    /// - `bytes` - The bytes to read.
+    fn read_bytes(bytes: &[u8]) -> Self;
+}
+
+macro_rules! impl_chunk_bytes {
+    ($($t:ty),*) => {
+        $(
+            impl ChunkBytes for $t {
+                const SIZE: usize = std::mem::size_of::<$t>();
+
+                fn write_bytes(&self, bytes: &mut Vec<u8>) {
+                    bytes.extend_from_slice(&self.to_le_bytes());
+                }
+
+                fn read_bytes(bytes: &[u8]) -> Self {
+                    <$t>::from_le_bytes(bytes.try_into().unwrap())
+                }
+            }
+        )*
+    };
+}
+
+impl_chunk_bytes!(u8, u16, u32, u64, u128, i8, i16, i32, i64, i128, f32, f64);
+
+impl ChunkBytes for bool {
+    const SIZE: usize = 1;
+
+    fn write_bytes(&self, bytes: &mut Vec<u8>) {
+        bytes.push(*self as u8);
+    }
+
+    fn read_bytes(bytes: &[u8]) -> Self {
+        bytes[0] != 0
+    }
+}
+
+/// Somewhere that the chunks of a `ChunkedGrid` are spilled to when they are evicted from memory.
+pub trait ChunkStore<T>: Send + Sync {
+    /// Saves the values of a chunk, replacing any values saved for it before.
+    ///
+    /// - `chunk` - The number of the chunk.
+    /// - `values` - The values of the cells in the chunk.
+    fn save(&self, chunk: usize, values: &[T]) -> io::Result<()>;
+
+    /// Loads the values of a chunk.
+    ///
+    /// This returns `None` if the chunk has not been saved.
+    ///
+    /// - `chunk` - The number of the chunk.
+    fn load(&self, chunk: usize) -> io::Result<Option<Vec<T>>>;
+}
+
+/// A `ChunkStore` that saves each chunk as a file in a directory.
+///
+/// A store created with `Default` uses a new directory in the temporary directory of the system
+/// which is removed when the store is dropped.
+#[derive(Debug)]
+pub struct DiskStore {
+    /// The directory that the chunks are saved in.
+    directory: PathBuf,
+    /// Whether the directory should be removed when the store is dropped.
+    temporary: bool,
+}
+
+impl DiskStore {
+    /// Creates a new store that saves chunks in a directory, creating it if it does not exist.
+    ///
+    /// - `directory` - The directory to save the chunks in.
+    pub fn new<P: AsRef<Path>>(directory: P) -> io::Result<Self> {
+        fs::create_dir_all(directory.as_ref())?;
+
+        Ok(Self {
+            directory: directory.as_ref().to_path_buf(),
+            temporary: false,
+        })
+    }
+
+    /// Gets the directory that the chunks are saved in.
+    pub fn directory(&self) -> &Path {
+        &self.directory
+    }
+
+    /// Gets the path of the file for a chunk.
+    ///
+    /// - `chunk` - The number of the chunk.
+    fn path(&self, chunk: usize) -> PathBuf {
+        self.directory.join(format!("chunk-{}.bin", chunk))
+    }
+}
+
+impl Default for DiskStore {
+    /// Creates a store in a new temporary directory.
+    ///
+    /// # Panics
+    /// Panics if the directory cannot be created.
+    fn default() -> Self {
+        static COUNT: AtomicUsize = AtomicUsize::new(0);
+
+        let name = format!("surface-grid-{}-{}", std::process::id(), COUNT.fetch_add(1, Ordering::Relaxed));
+        let mut store = Self::new(std::env::temp_dir().join(name))
+            .expect("failed to create a temporary directory for a chunked grid");
+
+        store.temporary = true;
+
+        store
+    }
+}
+
+impl Drop for DiskStore {
+    fn drop(&mut self) {
+        if self.temporary {
+            let _ = fs::remove_dir_all(&self.directory);
+        }
+    }
+}
+
+impl <T: ChunkBytes> ChunkStore<T> for DiskStore {
+    fn save(&self, chunk: usize, values: &[T]) -> io::Result<()> {
+        let mut bytes = Vec::with_capacity(values.len() * T::SIZE);
+
+        for value in values {
+            value.write_bytes(&mut bytes);
+        }
+
+        fs::write(self.path(chunk), bytes)
+    }
+
+    fn load(&self, chunk: usize) -> io::Result<Option<Vec<T>>> {
+        match fs::read(self.path(chunk)) {
+            Ok(bytes) => Ok(Some(bytes.chunks_exact(T::SIZE).map(T::read_bytes).collect())),
+            Err(error) if error.kind() == io::ErrorKind::NotFound => Ok(None),
+            Err(error) => Err(error),
+        }
+    }
+}
+
+/// A grid split into chunks that are loaded when they are used and spilled to a store when too
+/// many of them are in memory.
+///
+/// The shape of the grid is given by a grid of `()`, such as `CubeSphereGrid<(), 4096>`, which
+/// takes up no memory and provides the points, their order and their positions. The cells are
+/// split into chunks of `C` cells in the order given by `G`. Reading a cell loads its chunk from
+/// the store if it is not in memory, so the grid can be used through `SurfaceGrid` in the same way
+/// as any other grid. Chunks are only evicted by methods that take `&mut self`, since values that
+/// have been read may still be borrowed, so reading the whole grid through `&self`, such as with
+/// `iter`, loads all of it.
+///
+/// `map_neighbours`, `map_neighbours_diagonals` and their parallel and `_with_position` versions
+/// instead stream through the grid one chunk of the result at a time. The chunks holding that
+/// chunk and the halo of cells around it are read, loading the ones that are not in memory only
+/// until the chunk is done, so stepping a simulation does not load the rest of the grid and at
+/// most `R` chunks of the result are kept in memory.
+///
+/// # Type Parameters
+/// - `T` - The type of data that the grid holds.
+/// - `G` - The grid of `()` that gives the shape of the grid.
+/// - `S` - The store that evicted chunks are spilled to.
+///
+/// # Constant Parameters
+/// - `C` - The number of cells in each chunk.
+/// - `R` - The number of chunks that can be in memory before the least recently used ones are
+///   evicted.
+///
+/// # Iteration Order
+/// `iter`, `points` and `into_iter` visit the cells in the same order as the grid given by `G`.
+/// This order is stable.
+///
+/// # Thread Safety
+/// Chunks are loaded through `&self` without locking the whole grid so the grid is `Sync` when `T`
+/// is `Send` and `Sync`, and it can be read concurrently through a reference or an `Arc`.
+///
+/// # Errors
+/// Reading a cell through `Index`, `iter` or the streaming `map_neighbours` methods panics if the
+/// store fails to load its chunk, as those methods cannot return an error. Use `try_at`,
+/// `try_at_mut` or `map_neighbours_streaming` to get the error instead. A chunk that the store fails
+/// to save when it is evicted stays in memory and the error is returned by the next call to `flush`
+/// or `evict`.
+#[derive(Debug)]
+pub struct ChunkedGrid<T, G: SurfaceGrid<()>, const C: usize, const R: usize, S = DiskStore> {
+    /// The chunks that are in memory.
+    chunks: Vec<OnceLock<Box<[T]>>>,
+    /// Whether each chunk has changed since it was last saved.
+    dirty: Vec<bool>,
+    /// The time that each chunk was last used.
+    used: Vec<AtomicU64>,
+    /// The clock used to order the times that the chunks were used.
+    clock: AtomicU64,
+    /// The number of cells on the grid.
+    len: usize,
+    /// The grid that gives the shape of this grid.
+    layout: G,
+    /// The store that evicted chunks are spilled to.
+    store: S,
+    /// The first error from saving a chunk that has not yet been returned.
+    error: Option<io::Error>,
+}
+
+impl <T, G: SurfaceGrid<()> + Default, const C: usize, const R: usize, S: ChunkStore<T>> ChunkedGrid<T, G, C, R, S> {
+    /// Creates a new grid with all of its chunks unloaded.
+    ///
+    /// # Panics
+    /// Panics if `C` or `R` is 0.
+    ///
+    /// - `store` - The store that evicted chunks are spilled to.
+    fn empty(store: S) -> Self {
+        assert!(C > 0 && R > 0, "a chunked grid must have at least one cell in each chunk and one chunk in memory");
+
+        let layout = G::default();
+        let len = layout.points().count();
+        let count = len.div_ceil(C);
+
+        Self {
+            chunks: (0..count).map(|_| OnceLock::new()).collect(),
+            dirty: vec![false; count],
+            used: (0..count).map(|_| AtomicU64::new(0)).collect(),
+            clock: AtomicU64::new(0),
+            len,
+            layout,
+            store,
+            error: None,
+        }
+    }
+
+    /// Creates a new grid by calling a function for each point, spilling chunks to a store as
+    /// they are filled.
+    ///
+    /// This returns an error if the store fails to save a chunk.
+    ///
+    /// - `store` - The store that evicted chunks are spilled to.
+    /// - `f` - The function to apply.
+    pub fn from_fn_in<F: FnMut(&G::Point) -> T>(store: S, f: F) -> io::Result<Self> {
+        let mut grid = Self::empty(store);
+
+        grid.fill(f);
+        grid.into_result()
+    }
+
+    /// Sets every cell by calling a function for each point, spilling chunks to the store as they
+    /// are filled.
+    ///
+    /// - `f` - The function to apply.
+    fn fill<F: FnMut(&G::Point) -> T>(&mut self, mut f: F) {
+        let layout = G::default();
+        let mut points = layout.points();
+
+        for chunk in 0..self.chunks.len() {
+            let values = points.by_ref().take(C).map(|point| f(&point)).collect();
+
+            self.insert(chunk, values);
+        }
+    }
+
+    /// Applies a function to each cell and its direct neighbours, streaming through the grid one
+    /// chunk of the result at a time.
+    ///
+    /// This gives the same result as `map_neighbours` but puts the result in a given store and
+    /// returns an error instead of panicking if a chunk cannot be loaded or saved.
+    ///
+    /// The provided function is called with the arguments: current, up, down, left, right.
+    ///
+    /// - `store` - The store for the new grid.
+    /// - `f` - The function to apply.
+    pub fn map_neighbours_streaming<F: FnMut(&T, &T, &T, &T, &T) -> T>(&self, store: S, mut f: F) -> io::Result<Self> {
+        self.map_windows(store, false, |window, points| {
+            points.iter()
+                .map(|point| {
+                    let [up, down, left, right] = window.neighbours(point);
+
+                    f(window.at(point), up, down, left, right)
+                })
+                .collect()
+        })?.into_result()
+    }
+
+    /// Creates a new grid one chunk at a time from the cells of this grid around each chunk.
+    ///
+    /// For each chunk of the new grid, the chunks of this grid that hold its cells and their
+    /// neighbours are put in a `ChunkWindow`. Chunks of this grid that are not in memory are loaded
+    /// into the window rather than the grid and dropped once they are no longer needed. This
+    /// returns an error if a chunk cannot be loaded. Errors from saving the chunks of the new grid
+    /// are kept in the new grid.
+    ///
+    /// - `store` - The store for the new grid.
+    /// - `diagonals` - Whether the function reads the diagonal neighbours of each cell.
+    /// - `f` - The function that gives the values of a chunk of the new grid from the window and
+    ///   the points of the chunk.
+    fn map_windows<F: FnMut(&ChunkWindow<'_, T, G, C, R, S>, &[G::Point]) -> Vec<T>>(&self, store: S, diagonals: bool, mut f: F) -> io::Result<Self> {
+        let mut result = Self::empty(store);
+        let mut window = ChunkWindow {
+            grid: self,
+            loaded: Vec::new(),
+        };
+        let mut points = self.layout.points();
+
+        for chunk in 0..self.chunks.len() {
+            let chunk_points: Vec<_> = points.by_ref().take(C).collect();
+
+            // The chunks holding this chunk and the halo of cells around it.
+            let mut halo = Vec::new();
+
+            for point in &chunk_points {
+                halo.extend([point.clone(), point.up(), point.down(), point.left(), point.right()]);
+
+                if diagonals {
+                    halo.extend([point.up_left(), point.up_right(), point.down_left(), point.down_right()]);
+                }
+            }
+
+            let mut needed: Vec<_> = halo.iter()
+                .map(|point| self.layout.to_index(point) / C)
+                .collect();
+
+            needed.sort_unstable();
+            needed.dedup();
+
+            window.load(&needed)?;
+
+            result.insert(chunk, f(&window, &chunk_points));
+        }
+
+        Ok(result)
+    }
+}
+
+impl <T, G: SurfaceGrid<()>, const C: usize, const R: usize, S: ChunkStore<T>> ChunkedGrid<T, G, C, R, S> {
+    /// Gets the store that evicted chunks are spilled to.
+    pub fn store(&self) -> &S {
+        &self.store
+    }
+
+    /// Gets the number of chunks that are in memory.
+    pub fn resident_chunks(&self) -> usize {
+        self.chunks.iter()
+            .filter(|chunk| chunk.get().is_some())
+            .count()
+    }
+
+    /// Gets a reference to the value of a cell, returning an error if its chunk cannot be loaded.
+    ///
+    /// - `point` - The point to get the value of.
+    pub fn try_at(&self, point: &G::Point) -> io::Result<&T> {
+        let index = self.layout.to_index(point);
+
+        Ok(&self.try_chunk(index / C)?[index % C])
+    }
+
+    /// Gets a mutable reference to the value of a cell, returning an error if its chunk cannot be
+    /// loaded or another chunk cannot be saved to make room for it.
+    ///
+    /// - `point` - The point to get the value of.
+    pub fn try_at_mut(&mut self, point: &G::Point) -> io::Result<&mut T> {
+        let index = self.layout.to_index(point);
+
+        self.try_chunk(index / C)?;
+        self.evict_except(&[index / C])?;
+        self.dirty[index / C] = true;
+
+        Ok(&mut self.chunks[index / C].get_mut().unwrap()[index % C])
+    }
+
+    /// Evicts the least recently used chunks until at most `R` chunks are in memory.
+    ///
+    /// This returns an error if a chunk cannot be saved, or if saving a chunk failed since the
+    /// error was last returned.
+    pub fn evict(&mut self) -> io::Result<()> {
+        self.take_error()?;
+        self.evict_except(&[])
+    }
+
+    /// Saves every chunk in memory that has changed since it was last saved.
+    ///
+    /// The chunks stay in memory. This returns an error if a chunk cannot be saved, or if saving a
+    /// chunk failed since the error was last returned.
+    pub fn flush(&mut self) -> io::Result<()> {
+        self.take_error()?;
+
+        for (chunk, values) in self.chunks.iter().enumerate() {
+            if let Some(values) = values.get() {
+                if self.dirty[chunk] {
+                    self.store.save(chunk, values)?;
+                    self.dirty[chunk] = false;
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Returns the first error from saving a chunk that has not yet been returned.
+    fn take_error(&mut self) -> io::Result<()> {
+        match self.error.take() {
+            Some(error) => Err(error),
+            None => Ok(()),
+        }
+    }
+
+    /// Converts this grid into a result holding the first error from saving a chunk if there is
+    /// one.
+    fn into_result(mut self) -> io::Result<Self> {
+        self.take_error()?;
+
+        Ok(self)
+    }
+
+    /// Marks a chunk as used now.
+    ///
+    /// - `chunk` - The number of the chunk.
+    fn touch(&self, chunk: usize) {
+        let time = self.clock.fetch_add(1, Ordering::Relaxed) + 1;
+
+        self.used[chunk].store(time, Ordering::Relaxed);
+    }
+
+    /// Loads the values of a chunk from the store without putting them in memory.
+    ///
+    /// - `chunk` - The number of the chunk.
+    fn load(&self, chunk: usize) -> io::Result<Box<[T]>> {
+        let expected = C.min(self.len - chunk * C);
+
+        match self.store.load(chunk)? {
+            Some(values) if values.len() == expected => Ok(values.into_boxed_slice()),
+            Some(values) => Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("chunk {chunk} has {} values but should have {expected}", values.len()),
+            )),
+            None => Err(io::Error::new(
+                io::ErrorKind::NotFound,
+                format!("chunk {chunk} is not in memory and has not been saved"),
+            )),
+        }
+    }
+
+    /// Gets the values of a chunk, loading it from the store if it is not in memory.
+    ///
+    /// - `chunk` - The number of the chunk.
+    fn try_chunk(&self, chunk: usize) -> io::Result<&[T]> {
+        self.touch(chunk);
+
+        if let Some(values) = self.chunks[chunk].get() {
+            return Ok(values);
+        }
+
+        let values = self.load(chunk)?;
+
+        // Another thread may have loaded the chunk at the same time, in which case its values are
+        // the same as these.
+        Ok(self.chunks[chunk].get_or_init(|| values))
+    }
+
+    /// Gets the values of a chunk, loading it from the store if it is not in memory.
+    ///
+    /// # Panics
+    /// Panics if the chunk cannot be loaded.
+    ///
+    /// - `chunk` - The number of the chunk.
+    fn chunk(&self, chunk: usize) -> &[T] {
+        loaded(self.try_chunk(chunk))
+    }
+
+    /// Gets the values of a chunk to change them, loading it from the store if it is not in
+    /// memory and evicting other chunks if there are too many in memory.
+    ///
+    /// # Panics
+    /// Panics if the chunk cannot be loaded.
+    ///
+    /// - `chunk` - The number of the chunk.
+    fn chunk_mut(&mut self, chunk: usize) -> &mut [T] {
+        self.chunk(chunk);
+        self.evict_or_keep_error(&[chunk]);
+        self.dirty[chunk] = true;
+
+        self.chunks[chunk].get_mut().unwrap()
+    }
+
+    /// Puts the values of a chunk in memory, evicting other chunks if there are too many in memory.
+    ///
+    /// - `chunk` - The number of the chunk.
+    /// - `values` - The values of the cells in the chunk.
+    fn insert(&mut self, chunk: usize, values: Vec<T>) {
+        self.chunks[chunk] = OnceLock::from(values.into_boxed_slice());
+        self.dirty[chunk] = true;
+        self.touch(chunk);
+        self.evict_or_keep_error(&[chunk]);
+    }
+
+    /// Evicts the least recently used chunks until at most `R` chunks are in memory, without
+    /// evicting some of the chunks.
+    ///
+    /// A chunk that cannot be saved is not evicted and the error is returned.
+    ///
+    /// - `keep` - The chunks that must not be evicted.
+    fn evict_except(&mut self, keep: &[usize]) -> io::Result<()> {
+        let mut resident: Vec<_> = (0..self.chunks.len())
+            .filter(|chunk| self.chunks[*chunk].get().is_some() && !keep.contains(chunk))
+            .collect();
+
+        let kept = self.resident_chunks() - resident.len();
+        let excess = (resident.len() + kept).saturating_sub(R).min(resident.len());
+
+        resident.sort_by_key(|chunk| self.used[*chunk].load(Ordering::Relaxed));
+
+        for chunk in resident.into_iter().take(excess) {
+            if self.dirty[chunk] {
+                self.store.save(chunk, self.chunks[chunk].get().unwrap())?;
+                self.dirty[chunk] = false;
+            }
+
+            self.chunks[chunk].take();
+        }
+
+        Ok(())
+    }
+
+    /// Evicts chunks in the same way as `evict_except`, keeping the first error to be returned by
+    /// `flush` or `evict` for the methods that cannot return it.
+    ///
+    /// - `keep` - The chunks that must not be evicted.
+    fn evict_or_keep_error(&mut self, keep: &[usize]) {
+        if let Err(error) = self.evict_except(keep) {
+            self.error.get_or_insert(error);
+        }
+    }
+}
+
+/// The chunks of a `ChunkedGrid` that are read to compute one chunk of a new grid.
+///
+/// Chunks that are in memory are read from the grid and the others are loaded into the window, so
+/// reading through the window never adds chunks to the grid.
+struct ChunkWindow<'a, T, G: SurfaceGrid<()>, const C: usize, const R: usize, S> {
+    /// The grid that is read.
+    grid: &'a ChunkedGrid<T, G, C, R, S>,
+    /// The chunks that were loaded into the window along with their numbers.
+    loaded: Vec<(usize, Box<[T]>)>,
+}
+
+impl <T, G: SurfaceGrid<()>, const C: usize, const R: usize, S: ChunkStore<T>> ChunkWindow<'_, T, G, C, R, S> {
+    /// Makes a set of chunks readable through the window, loading the ones that are not in memory
+    /// and dropping the loaded chunks that are no longer needed.
+    ///
+    /// - `needed` - The chunks to make readable.
+    fn load(&mut self, needed: &[usize]) -> io::Result<()> {
+        self.loaded.retain(|(chunk, _)| needed.contains(chunk));
+
+        for &chunk in needed {
+            self.grid.touch(chunk);
+
+            if self.grid.chunks[chunk].get().is_none() && !self.loaded.iter().any(|(loaded, _)| *loaded == chunk) {
+                self.loaded.push((chunk, self.grid.load(chunk)?));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Gets a reference to the value of a cell.
+    ///
+    /// # Panics
+    /// Panics if the chunk holding the cell was not given to `load`.
+    ///
+    /// - `point` - The point to get the value of.
+    fn at(&self, point: &G::Point) -> &T {
+        let index = self.grid.layout.to_index(point);
+        let chunk = index / C;
+
+        // Chunks cannot be evicted from the grid while it is borrowed by the window.
+        let values = match self.grid.chunks[chunk].get() {
+            Some(values) => values,
+            None => &self.loaded.iter()
+                .find(|(loaded, _)| *loaded == chunk)
+                .expect("the chunk must be loaded into the window before it is read")
+                .1,
+        };
+
+        &values[index % C]
+    }
+
+    /// Gets references to the values of the direct neighbours of a cell in the order: up, down,
+    /// left, right.
+    ///
+    /// - `point` - The point to get the neighbours of.
+    fn neighbours(&self, point: &G::Point) -> [&T; 4] {
+        [self.at(&point.up()), self.at(&point.down()), self.at(&point.left()), self.at(&point.right())]
+    }
+
+    /// Gets references to the values of the neighbours of a cell including diagonals in the
+    /// order: up_left, up, up_right, left, right, down_left, down, down_right.
+    ///
+    /// - `point` - The point to get the neighbours of.
+    fn neighbours_diagonals(&self, point: &G::Point) -> [&T; 8] {
+        [
+            self.at(&point.up_left()), self.at(&point.up()), self.at(&point.up_right()),
+            self.at(&point.left()), self.at(&point.right()),
+            self.at(&point.down_left()), self.at(&point.down()), self.at(&point.down_right())
+        ]
+    }
+}
+
+/// Gets the value of a result from loading a chunk for the methods that cannot return an error.
+///
+/// # Panics
+/// Panics with the error if the chunk could not be loaded.
+///
+/// - `result` - The result of loading the chunk.
+fn loaded<X>(result: io::Result<X>) -> X {
+    result.unwrap_or_else(|error| panic!("failed to load a chunk of a chunked grid: {error}"))
+}
+
+impl <T, G, const C: usize, const R: usize, S> SurfaceGrid<T> for ChunkedGrid<T, G, C, R, S>
+where
+    G: SurfaceGrid<()> + Default + Sync,
+    G::Point: Sync,
+    S: ChunkStore<T> + Default {
+    type Point = G::Point;
+
+    fn from_fn<F: FnMut(&Self::Point) -> T>(f: F) -> Self {
+        let mut grid = Self::empty(S::default());
+
+        grid.fill(f);
+        grid
+    }
+
+    fn from_fn_par<F: Fn(&Self::Point) -> T + Send + Sync>(f: F) -> Self where T: Send + Sync {
+        let mut grid = Self::empty(S::default());
+        let layout = G::default();
+        let mut points = layout.points();
+
+        for chunk in 0..grid.chunks.len() {
+            let chunk_points: Vec<_> = points.by_ref().take(C).collect();
+            let mut values = Vec::with_capacity(chunk_points.len());
+
+            chunk_points.par_iter()
+                .map(&f)
+                .collect_into_vec(&mut values);
+
+            grid.insert(chunk, values);
+        }
+
+        grid
+    }
+
+    fn try_from_fn<X, F: FnMut(&Self::Point) -> Result<T, X>>(mut f: F) -> Result<Self, X> {
+        let mut grid = Self::empty(S::default());
+        let layout = G::default();
+        let mut points = layout.points();
+
+        for chunk in 0..grid.chunks.len() {
+            let values = points.by_ref().take(C).map(|point| f(&point)).collect::<Result<_, _>>()?;
+
+            grid.insert(chunk, values);
+        }
+
+        Ok(grid)
+    }
+
+    fn try_from_fn_par<X: Send, F: Fn(&Self::Point) -> Result<T, X> + Send + Sync>(f: F) -> Result<Self, X> where T: Send + Sync {
+        let mut grid = Self::empty(S::default());
+        let layout = G::default();
+        let mut points = layout.points();
+
+        for chunk in 0..grid.chunks.len() {
+            let chunk_points: Vec<_> = points.by_ref().take(C).collect();
+            let values = chunk_points.par_iter()
+                .map(&f)
+                .collect::<Result<_, _>>()?;
+
+            grid.insert(chunk, values);
+        }
+
+        Ok(grid)
+    }
+
+    fn from_vec(values: Vec<T>) -> Option<Self> {
+        let mut grid = Self::empty(S::default());
+
+        if values.len() != grid.len {
+            return None;
+        }
+
+        let mut values = values.into_iter();
+
+        for chunk in 0..grid.chunks.len() {
+            grid.insert(chunk, values.by_ref().take(C).collect());
+        }
+
+        Some(grid)
+    }
+
+    fn set_from_fn<F: FnMut(&Self::Point) -> T>(&mut self, f: F) {
+        self.fill(f);
+    }
+
+    fn set_from_fn_par<F: Fn(&Self::Point) -> T + Send + Sync>(&mut self, f: F) where T: Send + Sync {
+        let layout = G::default();
+        let mut points = layout.points();
+
+        for chunk in 0..self.chunks.len() {
+            let chunk_points: Vec<_> = points.by_ref().take(C).collect();
+            let mut values = Vec::with_capacity(chunk_points.len());
+
+            chunk_points.par_iter()
+                .map(&f)
+                .collect_into_vec(&mut values);
+
+            self.insert(chunk, values);
+        }
+    }
+
+    fn iter<'a>(&'a self) -> impl ExactSizeIterator<Item = (Self::Point, &'a T)> + DoubleEndedIterator where T: 'a {
+        self.layout.points()
+            .map(|point| (point.clone(), &self[point]))
+    }
+
+    fn par_iter<'a>(&'a self) -> impl IndexedParallelIterator<Item = (Self::Point, &'a T)> where T: 'a + Send + Sync {
+        self.layout.par_points()
+            .map(|point| (point.clone(), &self[point]))
+    }
+
+    fn par_iter_mut<'a>(&'a mut self) -> impl IndexedParallelIterator<Item = (Self::Point, &'a mut T)> where T: 'a + Send + Sync {
+        // Every chunk is loaded and kept in memory while it is borrowed, in the same way as `iter`.
+        for chunk in 0..self.chunks.len() {
+            self.chunk(chunk);
+            self.dirty[chunk] = true;
+        }
+
+        let points: Vec<_> = self.layout.points().collect();
+        let values: Vec<_> = self.chunks.iter_mut()
+            .flat_map(|chunk| chunk.get_mut().unwrap().iter_mut())
+            .collect();
+
+        points.into_par_iter()
+            .zip(values)
+    }
+
+    fn points(&self) -> impl ExactSizeIterator<Item = Self::Point> + DoubleEndedIterator {
+        self.layout.points()
+    }
+
+    fn par_points(&self) -> impl IndexedParallelIterator<Item = Self::Point> {
+        self.layout.par_points()
+    }
+
+    fn par_map_to_vec<U: Send, F: Fn(&Self::Point, &T) -> U + Send + Sync>(&self, f: F) -> Vec<U> where T: Send + Sync {
+        self.layout.par_map_to_vec(|point, _| f(point, &self[point.clone()]))
+    }
+
+    /// Applies a function to each cell and its direct neighbours, streaming through the grid one
+    /// chunk of the result at a time.
+    ///
+    /// The provided function is called with the arguments: current, up, down, left, right.
+    ///
+    /// # Panics
+    /// Panics if a chunk cannot be loaded. Use `map_neighbours_streaming` to get the error instead.
+    ///
+    /// `f` - The function to apply.
+    fn map_neighbours<F: FnMut(&T, &T, &T, &T, &T) -> T>(&self, mut f: F) -> Self {
+        loaded(self.map_windows(S::default(), false, |window, points| {
+            points.iter()
+                .map(|point| {
+                    let [up, down, left, right] = window.neighbours(point);
+
+                    f(window.at(point), up, down, left, right)
+                })
+                .collect()
+        }))
+    }
+
+    /// Applies a function to each cell and its direct neighbours including diagonals, streaming
+    /// through the grid in the same way as `map_neighbours`.
+    ///
+    /// The provided function is called with the arguments: up_left, up, up_right,
+    /// left, current, right, down_left, down, down_right.
+    ///
+    /// `f` - The function to apply.
+    fn map_neighbours_diagonals<
+                F: FnMut(&T, &T, &T, &T, &T, &T, &T, &T, &T) -> T
+            >(&self, mut f: F) -> Self {
+        loaded(self.map_windows(S::default(), true, |window, points| {
+            points.iter()
+                .map(|point| {
+                    let [up_left, up, up_right, left, right, down_left, down, down_right] = window.neighbours_diagonals(point);
+
+                    f(up_left, up, up_right, left, window.at(point), right, down_left, down, down_right)
+                })
+                .collect()
+        }))
+    }
+
+    /// Applies a function in parallel to each cell and its direct neighbours, streaming through
+    /// the grid in the same way as `map_neighbours` and computing each chunk in parallel.
+    ///
+    /// The provided function is called with the arguments: current, up, down, left, right.
+    ///
+    /// `f` - The function to apply.
+    fn map_neighbours_par<
+                F: Fn(&T, &T, &T, &T, &T) -> T + Send + Sync
+            >(&self, f: F) -> Self where T: Send + Sync {
+        loaded(self.map_windows(S::default(), false, |window, points| {
+            let mut values = Vec::with_capacity(points.len());
+
+            points.par_iter()
+                .map(|point| {
+                    let [up, down, left, right] = window.neighbours(point);
+
+                    f(window.at(point), up, down, left, right)
+                })
+                .collect_into_vec(&mut values);
+
+            values
+        }))
+    }
+
+    /// Applies a function in parallel to each cell and its direct neighbours including diagonals,
+    /// streaming through the grid in the same way as `map_neighbours` and computing each chunk in
+    /// parallel.
+    ///
+    /// The provided function is called with the arguments: up_left, up, up_right,
+    /// left, current, right, down_left, down, down_right.
+    ///
+    /// `f` - The function to apply.
+    fn map_neighbours_diagonals_par<
+                F: Fn(&T, &T, &T, &T, &T, &T, &T, &T, &T) -> T + Send + Sync
+            >(&self, f: F) -> Self where T: Send + Sync {
+        loaded(self.map_windows(S::default(), true, |window, points| {
+            let mut values = Vec::with_capacity(points.len());
+
+            points.par_iter()
+                .map(|point| {
+                    let [up_left, up, up_right, left, right, down_left, down, down_right] = window.neighbours_diagonals(point);
+
+                    f(up_left, up, up_right, left, window.at(point), right, down_left, down, down_right)
+                })
+                .collect_into_vec(&mut values);
+
+            values
+        }))
+    }
+
+    /// Applies a function to each cell and its direct neighbours providing the current point,
+    /// streaming through the grid in the same way as `map_neighbours`.
+    ///
+    /// The provided function is called with the arguments: current, position, up, down, left, right.
+    ///
+    /// `f` - The function to apply.
+    fn map_neighbours_with_position<F: FnMut(&T, &Self::Point, &T, &T, &T, &T) -> T>(&self, mut f: F) -> Self {
+        loaded(self.map_windows(S::default(), false, |window, points| {
+            points.iter()
+                .map(|point| {
+                    let [up, down, left, right] = window.neighbours(point);
+
+                    f(window.at(point), point, up, down, left, right)
+                })
+                .collect()
+        }))
+    }
+
+    /// Applies a function to each cell and its direct neighbours including diagonals providing the
+    /// current point, streaming through the grid in the same way as `map_neighbours`.
+    ///
+    /// The provided function is called with the arguments: position,
+    /// up_left, up, up_right,
+    /// left, current, right,
+    /// down_left, down, down_right.
+    ///
+    /// `f` - The function to apply.
+    fn map_neighbours_diagonals_with_position<
+                F: FnMut(&Self::Point, &T, &T, &T, &T, &T, &T, &T, &T, &T) -> T
+            >(&self, mut f: F) -> Self {
+        loaded(self.map_windows(S::default(), true, |window, points| {
+            points.iter()
+                .map(|point| {
+                    let [up_left, up, up_right, left, right, down_left, down, down_right] = window.neighbours_diagonals(point);
+
+                    f(point, up_left, up, up_right, left, window.at(point), right, down_left, down, down_right)
+                })
+                .collect()
+        }))
+    }
+
+    /// Applies a function in parallel to each cell and its direct neighbours providing the current
+    /// point, streaming through the grid in the same way as `map_neighbours` and computing each
+    /// chunk in parallel.
+    ///
+    /// The provided function is called with the arguments: current, position, up, down, left, right.
+    ///
+    /// `f` - The function to apply.
+    fn map_neighbours_par_with_position<
+                F: Fn(&T, &Self::Point, &T, &T, &T, &T) -> T + Send + Sync
+            >(&self, f: F) -> Self where T: Send + Sync {
+        loaded(self.map_windows(S::default(), false, |window, points| {
+            let mut values = Vec::with_capacity(points.len());
+
+            points.par_iter()
+                .map(|point| {
+                    let [up, down, left, right] = window.neighbours(point);
+
+                    f(window.at(point), point, up, down, left, right)
+                })
+                .collect_into_vec(&mut values);
+
+            values
+        }))
+    }
+
+    /// Applies a function in parallel to each cell and its direct neighbours including diagonals
+    /// providing the current point, streaming through the grid in the same way as
+    /// `map_neighbours` and computing each chunk in parallel.
+    ///
+    /// The provided function is called with the arguments: position,
+    /// up_left, up, up_right,
+    /// left, current, right,
+    /// down_left, down, down_right.
+    ///
+    /// `f` - The function to apply.
+    fn map_neighbours_diagonals_par_with_position<
+                F: Fn(&Self::Point, &T, &T, &T, &T, &T, &T, &T, &T, &T) -> T + Send + Sync
+            >(&self, f: F) -> Self where T: Send + Sync {
+        loaded(self.map_windows(S::default(), true, |window, points| {
+            let mut values = Vec::with_capacity(points.len());
+
+            points.par_iter()
+                .map(|point| {
+                    let [up_left, up, up_right, left, right, down_left, down, down_right] = window.neighbours_diagonals(point);
+
+                    f(point, up_left, up, up_right, left, window.at(point), right, down_left, down, down_right)
+                })
+                .collect_into_vec(&mut values);
+
+            values
+        }))
+    }
+
+    fn to_index(&self, point: &Self::Point) -> usize {
+        self.layout.to_index(point)
+    }
+
+    fn at(&self, point: &Self::Point) -> &T {
+        let index = self.layout.to_index(point);
+
+        &self.chunk(index / C)[index % C]
+    }
+
+    fn at_mut(&mut self, point: &Self::Point) -> &mut T {
+        let index = self.layout.to_index(point);
+
+        &mut self.chunk_mut(index / C)[index % C]
+    }
+
+    fn surface_area(&self, scale: f64) -> f64 {
+        self.layout.surface_area(scale)
+    }
+
+    fn topology(&self) -> Topology {
+        self.layout.topology()
+    }
+}
+
+impl <T, G: SurfaceGrid<()>, const C: usize, const R: usize, S: ChunkStore<T>> Index<G::Point> for ChunkedGrid<T, G, C, R, S> {
+    type Output = T;
+
+    fn index(&self, index: G::Point) -> &Self::Output {
+        let index = self.layout.to_index(&index);
+
+        &self.chunk(index / C)[index % C]
+    }
+}
+
+impl <T, G: SurfaceGrid<()>, const C: usize, const R: usize, S: ChunkStore<T>> IndexMut<G::Point> for ChunkedGrid<T, G, C, R, S> {
+    fn index_mut(&mut self, index: G::Point) -> &mut Self::Output {
+        let index = self.layout.to_index(&index);
+
+        &mut self.chunk_mut(index / C)[index % C]
+    }
+}
+
+impl <T, G, const C: usize, const R: usize, S> FromIterator<T> for ChunkedGrid<T, G, C, R, S>
+where
+    G: SurfaceGrid<()> + Default + Sync,
+    G::Point: Sync,
+    S: ChunkStore<T> + Default {
+    /// Creates a grid from the values of its cells in the same order as `points`.
+    ///
+    /// # Panics
+    /// Panics if the iterator does not yield exactly one value for each cell in the grid.
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        Self::from_vec(iter.into_iter().collect())
+            .expect("the iterator must yield one value for each cell in the grid")
+    }
+}
+
+impl <T, G: SurfaceGrid<()>, const C: usize, const R: usize, S: ChunkStore<T>> IntoIterator for ChunkedGrid<T, G, C, R, S> {
+    type Item = (G::Point, T);
+
+    type IntoIter = vec::IntoIter<Self::Item>;
+
+    fn into_iter(mut self) -> Self::IntoIter {
+        let mut values = Vec::with_capacity(self.len);
+
+        for chunk in 0..self.chunks.len() {
+            self.chunk(chunk);
+
+            values.extend(self.chunks[chunk].take().unwrap().into_vec());
+        }
+
+        let data: Vec<_> = self.layout.points()
+            .zip(values)
+            .collect();
+
+        data.into_iter()
+    }
+}
+
+#[cfg(feature = "serde")]
+impl <T: serde::Serialize, G: SurfaceGrid<()>, const C: usize, const R: usize, S: ChunkStore<T>> serde::Serialize for ChunkedGrid<T, G, C, R, S> {
+    /// Serializes the values in this grid as a sequence in the same order as `iter`.
+    fn serialize<Z: serde::Serializer>(&self, serializer: Z) -> Result<Z::Ok, Z::Error> {
+        serializer.collect_seq(self.layout.points().map(|point| &self[point]))
+    }
+}
+
+#[cfg(feature = "serde")]
+impl <'de, T, G, const C: usize, const R: usize, S> serde::Deserialize<'de> for ChunkedGrid<T, G, C, R, S>
+where
+    T: serde::Deserialize<'de>,
+    G: SurfaceGrid<()> + Default + Sync,
+    G::Point: Sync,
+    S: ChunkStore<T> + Default {
+    /// Deserializes a grid from a sequence of values in the same order as `iter`.
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let values = Vec::<T>::deserialize(deserializer)?;
+        let length = values.len();
+        let expected = G::default().points().count();
+
+        Self::from_vec(values)
+            .ok_or_else(|| serde::de::Error::invalid_length(length, &format!("a sequence of {} values", expected).as_str()))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::{collections::HashMap, io, sync::{Mutex, atomic::{AtomicBool, Ordering}}};
+
+    use crate::{SurfaceGrid, sphere::CubeSphereGrid};
+
+    use super::{ChunkStore, ChunkedGrid, DiskStore};
+
+    type Grid = ChunkedGrid<u32, CubeSphereGrid<(), 8>, 32, 3>;
+
+    /// A store that keeps chunks in memory and can be made to fail.
+    #[derive(Debug, Default)]
+    struct FlakyStore {
+        chunks: Mutex<HashMap<usize, Vec<u32>>>,
+        fail_load: AtomicBool,
+        fail_save: AtomicBool,
+    }
+
+    impl ChunkStore<u32> for FlakyStore {
+        fn save(&self, chunk: usize, values: &[u32]) -> io::Result<()> {
+            if self.fail_save.load(Ordering::Relaxed) {
+                return Err(io::Error::other("failed to save"));
+            }
+
+            self.chunks.lock().unwrap().insert(chunk, values.to_vec());
+
+            Ok(())
+        }
+
+        fn load(&self, chunk: usize) -> io::Result<Option<Vec<u32>>> {
+            if self.fail_load.load(Ordering::Relaxed) {
+                return Err(io::Error::other("failed to load"));
+            }
+
+            Ok(self.chunks.lock().unwrap().get(&chunk).cloned())
+        }
+    }
+
+    #[test]
+    fn test_chunked_from_fn() {
+        let grid = Grid::from_index_fn(|i| i as u32);
+
+        assert!(grid.resident_chunks() <= 3);
+
+        for (i, (point, value)) in grid.iter().enumerate() {
+            assert_eq!(*value, i as u32);
+            assert_eq!(grid.to_index(&point), i);
+        }
+
+        // Reading through a shared reference loads every chunk.
+        assert_eq!(grid.resident_chunks(), 12);
+    }
+
+    #[test]
+    fn test_chunked_evict() {
+        let mut grid = Grid::from_index_fn(|i| i as u32);
+        let points: Vec<_> = grid.points().collect();
+
+        assert_eq!(grid.iter().count(), 384);
+
+        grid.evict().unwrap();
+
+        assert_eq!(grid.resident_chunks(), 3);
+
+        for point in points.iter().step_by(7) {
+            grid[*point] += 1;
+
+            assert!(grid.resident_chunks() <= 3);
+        }
+
+        grid.flush().unwrap();
+
+        for (i, point) in points.iter().enumerate() {
+            assert_eq!(grid[*point], i as u32 + (i % 7 == 0) as u32);
+        }
+
+        assert_eq!(std::fs::read_dir(grid.store().directory()).unwrap().count(), 12);
+    }
+
+    #[test]
+    fn test_chunked_disk_store() {
+        let directory = std::env::temp_dir().join(format!("surface-grid-test-{}", std::process::id()));
+        let store = DiskStore::new(&directory).unwrap();
+
+        let grid = Grid::from_fn_in(store, |_| 5).unwrap();
+
+        assert!(directory.join("chunk-0.bin").exists());
+        assert_eq!(grid.to_vec(), vec![5; 384]);
+
+        drop(grid);
+
+        // Stores created with a directory are not removed.
+        assert!(directory.exists());
+
+        std::fs::remove_dir_all(directory).unwrap();
+    }
+
+    #[test]
+    fn test_chunked_map_neighbours_streaming() {
+        let grid = Grid::from_index_fn(|i| i as u32);
+        let dense: CubeSphereGrid<u32, 8> = CubeSphereGrid::from_index_fn(|i| i as u32);
+
+        let f = |current: &u32, up: &u32, down: &u32, left: &u32, right: &u32| current + up + down + left + right;
+
+        let next = grid.map_neighbours_streaming(DiskStore::default(), f).unwrap();
+
+        assert!(grid.resident_chunks() <= 3);
+        assert!(next.resident_chunks() <= 3);
+
+        assert_eq!(next.to_vec(), dense.map_neighbours(f).to_vec());
+    }
+
+    #[test]
+    fn test_chunked_map_neighbours_streams() {
+        let grid = Grid::from_index_fn(|i| i as u32);
+        let dense: CubeSphereGrid<u32, 8> = CubeSphereGrid::from_index_fn(|i| i as u32);
+
+        let f = |current: &u32, up: &u32, down: &u32, left: &u32, right: &u32| current + up + down + left + right;
+        let g = |a: &u32, b: &u32, c: &u32, d: &u32, e: &u32, f: &u32, g: &u32, h: &u32, i: &u32| a + 2 * b + 3 * c + 4 * d + 5 * e + 6 * f + 7 * g + 8 * h + 9 * i;
+
+        let next = grid.map_neighbours(f);
+        let diagonals = grid.map_neighbours_diagonals_par(g);
+
+        // Reading the grid through the streaming methods does not load its other chunks.
+        assert!(grid.resident_chunks() <= 3);
+        assert!(next.resident_chunks() <= 3);
+        assert!(diagonals.resident_chunks() <= 3);
+
+        assert_eq!(dense.map_neighbours(f).to_vec(), next.to_vec());
+        assert_eq!(dense.map_neighbours_diagonals(g).to_vec(), diagonals.to_vec());
+        assert_eq!(dense.map_neighbours_par_with_position(|current, _, up, down, left, right| f(current, up, down, left, right)).to_vec(), grid.map_neighbours_par_with_position(|current, _, up, down, left, right| f(current, up, down, left, right)).to_vec());
+    }
+
+    #[test]
+    fn test_chunked_errors() {
+        let mut grid: ChunkedGrid<u32, CubeSphereGrid<(), 8>, 32, 3, FlakyStore> = ChunkedGrid::from_fn(|_| 1);
+        let points: Vec<_> = grid.points().collect();
+
+        // The first chunk has been evicted so reading it needs the store.
+        grid.store().fail_load.store(true, Ordering::Relaxed);
+
+        assert!(grid.try_at(&points[0]).is_err());
+        assert!(grid.map_neighbours_streaming(FlakyStore::default(), |current, _, _, _, _| *current).is_err());
+
+        grid.store().fail_load.store(false, Ordering::Relaxed);
+        *grid.try_at_mut(&points[0]).unwrap() = 5;
+
+        // A chunk that cannot be saved stays in memory until the error is returned.
+        grid.store().fail_save.store(true, Ordering::Relaxed);
+        grid[points[160]] += 1;
+
+        assert_eq!(grid.resident_chunks(), 4);
+        assert!(grid.flush().is_err());
+
+        grid.store().fail_save.store(false, Ordering::Relaxed);
+        grid.flush().unwrap();
+        grid.evict().unwrap();
+
+        assert_eq!(grid.resident_chunks(), 3);
+        assert_eq!(grid[points[0]], 5);
+        assert_eq!(grid[points[160]], 2);
+        assert_eq!(grid.to_vec().iter().sum::<u32>(), 384 + 5);
+    }
+
+    #[test]
+    fn test_chunked_iteration_order() {
+        let grid = Grid::from_index_fn(|i| i as u32);
+
+        assert_eq!(grid.par_map_to_vec(|_, value| *value), grid.to_vec());
+        assert_eq!(Grid::from_fn_par(|point| grid[*point]).to_vec(), grid.to_vec());
+        assert_eq!(Grid::from_vec(grid.to_vec()).unwrap().to_vec(), grid.to_vec());
+        assert!(Grid::from_vec(vec![0; 3]).is_none());
+        assert_eq!(grid.into_iter().map(|(_, value)| value).collect::<Vec<_>>(), (0..384).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn test_chunked_par_iter_mut() {
+        use rayon::iter::ParallelIterator;
+
+        let layout = CubeSphereGrid::<(), 8>::default();
+        let mut grid = Grid::from_fn(|_| 1);
+
+        grid.evict().unwrap();
+        grid.par_iter_mut().for_each(|(point, value)| *value += layout.to_index(&point) as u32);
+        grid.evict().unwrap();
+
+        assert_eq!((1..385).collect::<Vec<_>>(), grid.to_vec());
+    }
+
+    #[test]
+    fn test_chunked_try_from_fn() {
+        let layout: CubeSphereGrid<(), 8> = CubeSphereGrid::default();
+
+        let grid: Result<Grid, ()> = Grid::try_from_fn_par(|point| Ok(layout.to_index(point) as u32));
+        let failed: Result<Grid, usize> = Grid::try_from_fn(|point| match layout.to_index(point) {
+            100 => Err(100),
+            i => Ok(i as u32),
+        });
+
+        assert_eq!((0..384).collect::<Vec<_>>(), grid.unwrap().iter().map(|(_, value)| *value).collect::<Vec<_>>());
+        assert_eq!(Err(100), failed.map(|_| ()));
+    }
+}
@@ -0,0 +1,505 @@
+//! A module containing cube sphere grids that store each face along a space filling curve.
+
+use std::{f64::consts::PI, marker::PhantomData, ops::{Index, IndexMut}, vec};
+
+use rayon::prelude::*;
+
+use crate::{Surface, SurfaceGrid, Topology, sphere::{self, CubeSphereGrid, CubeSpherePoint, FACE_ORDER}};
+
+assert_send_sync!([T: Send + Sync, const S: usize]
+    CurveCubeSphereGrid<T, S, Morton>,
+    CurveCubeSphereGrid<T, S, Hilbert>,
+);
+
+/// A space filling curve that visits every cell of a square.
+pub trait Curve: Send + Sync {
+    /// Gets the distance along the curve of a cell.
+    ///
+    /// - `x` - The X position of the cell.
+    /// - `y` - The Y position of the cell.
+    /// - `size` - The size of the side of the square, which is a power of two.
+    fn index(x: u32, y: u32, size: u32) -> u64;
+
+    /// Gets the cell at a distance along the curve.
+    ///
+    /// This is the inverse of `index`.
+    ///
+    /// - `index` - The distance along the curve.
+    /// - `size` - The size of the side of the square, which is a power of two.
+    fn coordinates(index: u64, size: u32) -> (u32, u32);
+}
+
+/// The Z order curve, which interleaves the bits of the X and Y positions.
+///
+/// This is cheap to compute but jumps between distant cells more often than `Hilbert`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct Morton;
+
+impl Curve for Morton {
+    fn index(x: u32, y: u32, _: u32) -> u64 {
+        spread(x) | spread(y) << 1
+    }
+
+    fn coordinates(index: u64, _: u32) -> (u32, u32) {
+        (compact(index), compact(index >> 1))
+    }
+}
+
+/// The Hilbert curve, where consecutive cells always share an edge.
+///
+/// This uses the same curve as `CubeSpherePoint::hilbert_index`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct Hilbert;
+
+impl Curve for Hilbert {
+    fn index(x: u32, y: u32, size: u32) -> u64 {
+        sphere::hilbert_index(x, y, size as u64)
+    }
+
+    fn coordinates(index: u64, size: u32) -> (u32, u32) {
+        let (mut x, mut y) = (0, 0);
+        let mut t = index;
+        let mut s = 1;
+
+        while s < size as u64 {
+            let rx = 1 & (t / 2);
+            let ry = 1 & (t ^ rx);
+
+            // Undo the rotation of the quadrant applied by `index`.
+            if ry == 0 {
+                if rx == 1 {
+                    x = s - 1 - x;
+                    y = s - 1 - y;
+                }
+
+                std::mem::swap(&mut x, &mut y);
+            }
+
+            x += s * rx;
+            y += s * ry;
+            t /= 4;
+            s *= 2;
+        }
+
+        (x as u32, y as u32)
+    }
+}
+
+/// A grid with the same shape and points as `CubeSphereGrid` that stores the cells of each face in
+/// the order of a space filling curve rather than row by row.
+///
+/// Cells that are close together on a face are stored close together in memory, so functions
+/// that visit each cell and its neighbours, such as `map_neighbours`, use the cache better than
+/// with `CubeSphereGrid` on large faces. The functions that create or set the whole grid visit the
+/// cells in storage order.
+///
+/// # Type Parameters
+/// - `T` - The type of data that the grid holds.
+/// - `C` - The curve that the cells of each face are stored along, `Hilbert` or `Morton`.
+///
+/// # Constant Parameters
+/// - `S` - The size of each side of each face, which must be a power of two.
+///
+/// # Iteration Order
+/// `iter`, `points` and `into_iter` visit the faces in the same order as `CubeSphereGrid` and the
+/// cells of each face in the order of the curve. This order is stable.
+///
+/// # Thread Safety
+/// The faces are kept in one `Vec` in the order of the curve and `C` only chooses the curve, so the
+/// grid is `Send` when `T` is `Send` and `Sync` when `T` is `Sync`.
+///
+/// # Panics
+/// Creating a grid panics if `S` is not a power of two.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct CurveCubeSphereGrid<T, const S: usize, C: Curve = Hilbert> {
+    data: Vec<T>,
+    curve: PhantomData<C>,
+}
+
+impl <T, const S: usize, C: Curve> CurveCubeSphereGrid<T, S, C> {
+    /// Gets the values of the cells in the same order as the points of the grid.
+    pub fn as_slice(&self) -> &[T] {
+        &self.data
+    }
+
+    /// Gets mutable references to the values of the cells in the same order as the points of the
+    /// grid.
+    pub fn as_mut_slice(&mut self) -> &mut [T] {
+        &mut self.data
+    }
+
+    /// Gets the values of the cells as bytes in the same order as `as_slice`.
+    #[cfg(feature = "bytemuck")]
+    pub fn as_bytes(&self) -> &[u8] where T: bytemuck::Pod {
+        bytemuck::cast_slice(self.as_slice())
+    }
+
+    /// Gets the values of the cells as mutable bytes in the same order as `as_slice`.
+    #[cfg(feature = "bytemuck")]
+    pub fn as_bytes_mut(&mut self) -> &mut [u8] where T: bytemuck::Pod {
+        bytemuck::cast_slice_mut(self.as_mut_slice())
+    }
+
+    /// Gets the point at an index in the order that the cells are stored.
+    ///
+    /// - `index` - The index of the point.
+    fn point_at(index: usize) -> CubeSpherePoint<S> {
+        let (x, y) = C::coordinates((index % (S * S)) as u64, S as u32);
+
+        CubeSpherePoint::new(FACE_ORDER[index / (S * S)], x as u16, y as u16)
+    }
+
+    /// Gets the number of cells in the grid after checking that the size is supported.
+    ///
+    /// # Panics
+    /// Panics if `S` is not a power of two.
+    fn cell_count() -> usize {
+        assert!(S.is_power_of_two(), "the size of each face must be a power of two but it is {S}");
+
+        6 * S * S
+    }
+}
+
+impl <T: Default, const S: usize, C: Curve> Default for CurveCubeSphereGrid<T, S, C> {
+    fn default() -> Self {
+        Self::from_fn(|_| T::default())
+    }
+}
+
+impl <T, const S: usize, C: Curve> SurfaceGrid<T> for CurveCubeSphereGrid<T, S, C> {
+    type Point = CubeSpherePoint<S>;
+
+    fn from_fn<F: FnMut(&Self::Point) -> T>(mut f: F) -> Self {
+        Self {
+            data: (0..Self::cell_count()).map(|index| f(&Self::point_at(index))).collect(),
+            curve: PhantomData,
+        }
+    }
+
+    fn from_fn_par<F: Fn(&Self::Point) -> T + Send + Sync>(f: F) -> Self where T: Send + Sync {
+        Self {
+            data: (0..Self::cell_count()).into_par_iter().map(|index| f(&Self::point_at(index))).collect(),
+            curve: PhantomData,
+        }
+    }
+
+    fn try_from_fn<X, F: FnMut(&Self::Point) -> Result<T, X>>(mut f: F) -> Result<Self, X> {
+        Ok(Self {
+            data: (0..Self::cell_count())
+                .map(|i| f(&Self::point_at(i)))
+                .collect::<Result<_, _>>()?,
+            curve: PhantomData,
+        })
+    }
+
+    fn try_from_fn_par<X: Send, F: Fn(&Self::Point) -> Result<T, X> + Send + Sync>(f: F) -> Result<Self, X> where T: Send + Sync {
+        Ok(Self {
+            data: (0..Self::cell_count()).into_par_iter()
+                .map(|i| f(&Self::point_at(i)))
+                .collect::<Result<_, _>>()?,
+            curve: PhantomData,
+        })
+    }
+
+    fn from_vec(values: Vec<T>) -> Option<Self> {
+        (values.len() == Self::cell_count()).then_some(Self {
+            data: values,
+            curve: PhantomData,
+        })
+    }
+
+    fn set_from_fn<F: FnMut(&Self::Point) -> T>(&mut self, mut f: F) {
+        for (index, value) in self.data.iter_mut().enumerate() {
+            *value = f(&Self::point_at(index));
+        }
+    }
+
+    fn set_from_fn_par<F: Fn(&Self::Point) -> T + Send + Sync>(&mut self, f: F) where T: Send + Sync {
+        self.data.par_iter_mut()
+            .enumerate()
+            .for_each(|(index, value)| *value = f(&Self::point_at(index)));
+    }
+
+    fn iter<'a>(&'a self) -> impl ExactSizeIterator<Item = (Self::Point, &'a T)> + DoubleEndedIterator where T: 'a {
+        self.data.iter()
+            .enumerate()
+            .map(|(index, value)| (Self::point_at(index), value))
+    }
+
+    fn par_iter<'a>(&'a self) -> impl IndexedParallelIterator<Item = (Self::Point, &'a T)> where T: 'a + Send + Sync {
+        self.data.par_iter()
+            .enumerate()
+            .map(|(index, value)| (Self::point_at(index), value))
+    }
+
+    fn par_iter_mut<'a>(&'a mut self) -> impl IndexedParallelIterator<Item = (Self::Point, &'a mut T)> where T: 'a + Send + Sync {
+        self.data.par_iter_mut()
+            .enumerate()
+            .map(|(index, value)| (Self::point_at(index), value))
+    }
+
+    fn points(&self) -> impl ExactSizeIterator<Item = Self::Point> + DoubleEndedIterator {
+        (0..self.data.len()).map(Self::point_at)
+    }
+
+    fn par_points(&self) -> impl IndexedParallelIterator<Item = Self::Point> {
+        (0..self.data.len()).into_par_iter().map(Self::point_at)
+    }
+
+    fn par_map_to_vec<U: Send, F: Fn(&Self::Point, &T) -> U + Send + Sync>(&self, f: F) -> Vec<U> where T: Send + Sync {
+        self.data.par_iter()
+            .enumerate()
+            .map(|(index, value)| f(&Self::point_at(index), value))
+            .collect()
+    }
+
+    fn to_index(&self, point: &Self::Point) -> usize {
+        let (face, x, y) = point.face_coordinates();
+
+        let face = FACE_ORDER.iter()
+            .position(|other| *other == face)
+            .unwrap();
+
+        face * S * S + C::index(x as u32, y as u32, S as u32) as usize
+    }
+
+    fn at(&self, point: &Self::Point) -> &T {
+        &self[point]
+    }
+
+    fn at_mut(&mut self, point: &Self::Point) -> &mut T {
+        &mut self[point]
+    }
+
+    fn surface_area(&self, scale: f64) -> f64 {
+        4.0 * PI * scale * scale
+    }
+
+    fn topology(&self) -> Topology {
+        Topology::uniform(Surface::Sphere, 6, S, S)
+    }
+
+    fn contiguous_values_mut(&mut self) -> Option<&mut [T]> {
+        Some(self.as_mut_slice())
+    }
+}
+
+impl <T, const S: usize, C: Curve> Index<CubeSpherePoint<S>> for CurveCubeSphereGrid<T, S, C> {
+    type Output = T;
+
+    fn index(&self, index: CubeSpherePoint<S>) -> &Self::Output {
+        &self.data[self.to_index(&index)]
+    }
+}
+
+impl <T, const S: usize, C: Curve> IndexMut<CubeSpherePoint<S>> for CurveCubeSphereGrid<T, S, C> {
+    fn index_mut(&mut self, index: CubeSpherePoint<S>) -> &mut Self::Output {
+        let index = self.to_index(&index);
+
+        &mut self.data[index]
+    }
+}
+
+impl <T, const S: usize, C: Curve> Index<&CubeSpherePoint<S>> for CurveCubeSphereGrid<T, S, C> {
+    type Output = T;
+
+    fn index(&self, index: &CubeSpherePoint<S>) -> &Self::Output {
+        &self[*index]
+    }
+}
+
+impl <T, const S: usize, C: Curve> IndexMut<&CubeSpherePoint<S>> for CurveCubeSphereGrid<T, S, C> {
+    fn index_mut(&mut self, index: &CubeSpherePoint<S>) -> &mut Self::Output {
+        &mut self[*index]
+    }
+}
+
+impl <T, const S: usize, C: Curve> FromIterator<T> for CurveCubeSphereGrid<T, S, C> {
+    /// Creates a grid from the values of its cells in the same order as `points`.
+    ///
+    /// # Panics
+    /// Panics if the iterator does not yield exactly one value for each cell in the grid.
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        Self::from_vec(iter.into_iter().collect())
+            .expect("the iterator must yield one value for each cell in the grid")
+    }
+}
+
+impl <T, const S: usize, C: Curve> IntoIterator for CurveCubeSphereGrid<T, S, C> {
+    type Item = (CubeSpherePoint<S>, T);
+
+    type IntoIter = vec::IntoIter<Self::Item>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        let data: Vec<_> = self.data.into_iter()
+            .enumerate()
+            .map(|(index, value)| (Self::point_at(index), value))
+            .collect();
+
+        data.into_iter()
+    }
+}
+
+impl <T, const S: usize, C: Curve> From<CubeSphereGrid<T, S>> for CurveCubeSphereGrid<T, S, C> {
+    fn from(grid: CubeSphereGrid<T, S>) -> Self {
+        let mut cells: Vec<_> = grid.into_iter().map(Some).collect();
+
+        // `CubeSphereGrid` stores its cells in the order of `CubeSpherePoint::index`.
+        Self::from_fn(|point| {
+            let (face, x, y) = point.face_coordinates();
+
+            let face = FACE_ORDER.iter()
+                .position(|other| *other == face)
+                .unwrap();
+
+            cells[(face * S + y as usize) * S + x as usize].take().unwrap().1
+        })
+    }
+}
+
+impl <T, const S: usize, C: Curve> From<CurveCubeSphereGrid<T, S, C>> for CubeSphereGrid<T, S> {
+    fn from(grid: CurveCubeSphereGrid<T, S, C>) -> Self {
+        let mut cells: Vec<_> = grid.data.into_iter().map(Some).collect();
+
+        Self::from_fn(|point| {
+            let (face, x, y) = point.face_coordinates();
+
+            let face = FACE_ORDER.iter()
+                .position(|other| *other == face)
+                .unwrap();
+
+            cells[face * S * S + C::index(x as u32, y as u32, S as u32) as usize].take().unwrap()
+        })
+    }
+}
+
+#[cfg(feature = "serde")]
+impl <T: serde::Serialize, const S: usize, C: Curve> serde::Serialize for CurveCubeSphereGrid<T, S, C> {
+    /// Serializes the values in this grid as a sequence in the same order as `iter`.
+    fn serialize<R: serde::Serializer>(&self, serializer: R) -> Result<R::Ok, R::Error> {
+        serializer.collect_seq(&self.data)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl <'de, T: serde::Deserialize<'de>, const S: usize, C: Curve> serde::Deserialize<'de> for CurveCubeSphereGrid<T, S, C> {
+    /// Deserializes a grid from a sequence of values in the same order as `iter`.
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let values = Vec::<T>::deserialize(deserializer)?;
+        let length = values.len();
+
+        Self::from_vec(values)
+            .ok_or_else(|| serde::de::Error::invalid_length(length, &format!("a sequence of {} values", 6 * S * S).as_str()))
+    }
+}
+
+/// Spreads the bits of a coordinate out so that there is a zero bit between each of them.
+///
+/// - `value` - The coordinate to spread.
+fn spread(value: u32) -> u64 {
+    let mut value = value as u64;
+
+    value = (value | value << 16) & 0x0000_FFFF_0000_FFFF;
+    value = (value | value << 8) & 0x00FF_00FF_00FF_00FF;
+    value = (value | value << 4) & 0x0F0F_0F0F_0F0F_0F0F;
+    value = (value | value << 2) & 0x3333_3333_3333_3333;
+
+    (value | value << 1) & 0x5555_5555_5555_5555
+}
+
+/// Gathers every other bit of a value back into a coordinate.
+///
+/// This is the inverse of `spread`.
+///
+/// - `value` - The value to gather the bits from.
+fn compact(value: u64) -> u32 {
+    let mut value = value & 0x5555_5555_5555_5555;
+
+    value = (value | value >> 1) & 0x3333_3333_3333_3333;
+    value = (value | value >> 2) & 0x0F0F_0F0F_0F0F_0F0F;
+    value = (value | value >> 4) & 0x00FF_00FF_00FF_00FF;
+    value = (value | value >> 8) & 0x0000_FFFF_0000_FFFF;
+
+    (value | value >> 16) as u32
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{SurfaceGrid, GridPoint, sphere::{CubeSphereGrid, CubeSpherePoint}};
+
+    use super::{Curve, CurveCubeSphereGrid, Hilbert, Morton};
+
+    #[test]
+    fn test_curve_round_trip() {
+        for size in [1, 2, 8, 32] {
+            for index in 0..(size * size) as u64 {
+                let (x, y) = Hilbert::coordinates(index, size);
+                assert_eq!(index, Hilbert::index(x, y, size));
+
+                let (x, y) = Morton::coordinates(index, size);
+                assert_eq!(index, Morton::index(x, y, size));
+                assert!(x < size && y < size);
+            }
+        }
+    }
+
+    #[test]
+    fn test_curve_hilbert_adjacent() {
+        for index in 1..64 * 64 {
+            let (ax, ay) = Hilbert::coordinates(index - 1, 64);
+            let (bx, by) = Hilbert::coordinates(index, 64);
+
+            assert_eq!(1, ax.abs_diff(bx) + ay.abs_diff(by));
+        }
+    }
+
+    #[test]
+    fn test_curve_grid_matches_cube_sphere() {
+        let dense: CubeSphereGrid<f64, 16> = CubeSphereGrid::from_fn(|point| point.position(1.0).0 * 3.0 + point.position(1.0).2);
+        let curve: CurveCubeSphereGrid<f64, 16> = dense.clone().into();
+        let morton: CurveCubeSphereGrid<f64, 16, Morton> = CurveCubeSphereGrid::from_fn(|point| dense[*point]);
+
+        let rule = |current: &f64, up: &f64, down: &f64, left: &f64, right: &f64| current - up * 2.0 + down + left * 0.5 - right;
+
+        let expected = dense.map_neighbours(rule);
+
+        assert_eq!(expected, CubeSphereGrid::from(curve.map_neighbours(rule)));
+        assert_eq!(expected, CubeSphereGrid::from(morton.map_neighbours(rule)));
+        assert!(curve.iter().all(|(point, value)| *value == dense[point]));
+    }
+
+    #[test]
+    fn test_curve_grid_index_order() {
+        let grid: CurveCubeSphereGrid<(), 8> = CurveCubeSphereGrid::default();
+
+        let points: Vec<CubeSpherePoint<8>> = grid.points().collect();
+
+        assert_eq!(6 * 8 * 8, points.len());
+        assert!(points.iter().enumerate().all(|(index, point)| grid.to_index(point) == index));
+        assert!(points.windows(2).take(63).all(|pair| pair[0].hilbert_index() + 1 == pair[1].hilbert_index()));
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_curve_grid_not_power_of_two() {
+        let _: CurveCubeSphereGrid<u8, 6> = CurveCubeSphereGrid::default();
+    }
+
+    #[test]
+    fn test_curve_grid_zip_with_cube_sphere() {
+        let cube: CubeSphereGrid<usize, 8> = CubeSphereGrid::from_fn(cube_value);
+        let curve: CurveCubeSphereGrid<usize, 8, Morton> = CurveCubeSphereGrid::from_fn(|point| cube_value(point) + 1);
+
+        assert!(cube.zip(&curve).all(|(_, a, b)| a + 1 == *b));
+
+        let sum: CubeSphereGrid<usize, 8> = curve.zip_map(&cube, |_, a, b| a + b);
+
+        assert!(sum.iter().all(|(point, value)| *value == 2 * cube_value(&point) + 1));
+    }
+
+    fn cube_value(point: &CubeSpherePoint<8>) -> usize {
+        let (face, x, y) = point.face_coordinates();
+
+        face as usize * 64 + y as usize * 8 + x as usize
+    }
+}
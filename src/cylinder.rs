@@ -0,0 +1,965 @@
+//! A module containing grids wrapped around the surface of a cylinder.
+
+use std::{f64::consts::PI, fmt::Debug, hash::Hash, marker::PhantomData, ops::{Index, IndexMut}, vec};
+
+use itertools::Itertools;
+use rayon::prelude::*;
+use static_array::HeapArray2D;
+
+use crate::{GridPoint, OutOfBounds, Surface, SurfaceGrid, Topology};
+
+assert_send_sync!([T: Send + Sync, const W: usize, const H: usize, E: CylinderEnds]
+    CylinderGrid<T, W, H, E>,
+    CylinderPoint<W, H, E>,
+);
+
+/// The ways that the ends of a cylinder can behave.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum CylinderEnd {
+    /// Moving off an end stays in the row at the end.
+    Clamp,
+    /// Moving off an end bounces back into the row next to the end.
+    Reflect,
+    /// The ends are covered by square caps that join onto the side of the cylinder.
+    Cap,
+}
+
+/// A type parameter that selects how the ends of a `CylinderGrid` behave.
+pub trait CylinderEnds: Debug + Clone + Copy + PartialEq + Eq + Hash + Default + Send + Sync {
+    /// The behaviour of the ends.
+    const END: CylinderEnd;
+}
+
+/// The ends of a cylinder where moving off an end stays in the row at the end.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct Clamped;
+
+impl CylinderEnds for Clamped {
+    const END: CylinderEnd = CylinderEnd::Clamp;
+}
+
+/// The ends of a cylinder where moving off an end bounces back into the row next to the end.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct Reflected;
+
+impl CylinderEnds for Reflected {
+    const END: CylinderEnd = CylinderEnd::Reflect;
+}
+
+/// The ends of a cylinder covered by square caps.
+///
+/// Each cap is a square grid with sides of `W / 4` cells that is mapped onto the disk at the end
+/// of the cylinder. Each side of the square joins onto a quarter of the row at the end of the
+/// cylinder in the same way that the faces of a cube join so the width must be a multiple of 4.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct Capped;
+
+impl CylinderEnds for Capped {
+    const END: CylinderEnd = CylinderEnd::Cap;
+}
+
+/// A part of the surface of a cylinder.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum CylinderFace {
+    /// The cap at the top of the cylinder.
+    Top,
+    /// The curved side of the cylinder.
+    Side,
+    /// The cap at the bottom of the cylinder.
+    Bottom,
+}
+
+/// A grid wrapped around the side of a cylinder.
+///
+/// The grid wraps around the cylinder horizontally and the behaviour at the top and bottom is
+/// chosen by the `E` type parameter.
+///
+/// # Type Parameters
+/// - `T` - The type of data that the grid holds.
+/// - `E` - The behaviour of the ends of the cylinder, one of `Clamped`, `Reflected` or `Capped`.
+///
+/// # Constant Parameters
+/// - `W` - The number of cells around the cylinder.
+/// - `H` - The number of cells along the side of the cylinder.
+///
+/// # Iteration Order
+/// `iter`, `points` and `into_iter` visit the top cap, then the side and then the bottom cap, each
+/// in row-major order starting at `(0, 0)` and increasing `x` before `y`. Uncapped grids only have
+/// the side. This order is stable.
+///
+/// # Thread Safety
+/// The side and the two caps are kept in separate arrays owned by the grid and `E` only chooses how
+/// the ends behave, so the grid is `Send` when `T` is `Send` and `Sync` when `T` is `Sync`.
+///
+/// # Panics
+/// Creating a `Capped` grid panics if `W` is not a multiple of 4.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct CylinderGrid<T, const W: usize, const H: usize, E: CylinderEnds = Clamped> {
+    /// The data held on the top cap.
+    top: Vec<T>,
+    /// The data held on the side.
+    side: HeapArray2D<T, W, H>,
+    /// The data held on the bottom cap.
+    bottom: Vec<T>,
+    /// The behaviour of the ends.
+    ends: PhantomData<E>,
+}
+
+impl <T: Default, const W: usize, const H: usize, E: CylinderEnds> Default for CylinderGrid<T, W, H, E> {
+    fn default() -> Self {
+        Self::from_fn(|_| T::default())
+    }
+}
+
+impl <T, const W: usize, const H: usize, E: CylinderEnds> CylinderGrid<T, W, H, E> {
+    /// Gets the values of the cells on a face in the same order as the points of the grid.
+    ///
+    /// The side is `W` by `H` cells and each cap is `cap_size` cells square, all in row-major order.
+    /// The caps are empty when the ends are not capped.
+    ///
+    /// - `face` - The face to get the values of.
+    pub fn face_slice(&self, face: CylinderFace) -> &[T] {
+        match face {
+            CylinderFace::Top => &self.top,
+            CylinderFace::Side => self.side.as_flattened(),
+            CylinderFace::Bottom => &self.bottom,
+        }
+    }
+
+    /// Gets mutable references to the values of the cells on a face in the same order as the
+    /// points of the grid.
+    ///
+    /// - `face` - The face to get the values of.
+    pub fn face_slice_mut(&mut self, face: CylinderFace) -> &mut [T] {
+        match face {
+            CylinderFace::Top => &mut self.top,
+            CylinderFace::Side => self.side.as_flattened_mut(),
+            CylinderFace::Bottom => &mut self.bottom,
+        }
+    }
+
+    /// Gets the values of the cells on a face as bytes in the same order as `face_slice`.
+    ///
+    /// - `face` - The face to get the values of.
+    #[cfg(feature = "bytemuck")]
+    pub fn face_bytes(&self, face: CylinderFace) -> &[u8] where T: bytemuck::Pod {
+        bytemuck::cast_slice(self.face_slice(face))
+    }
+
+    /// Gets the values of the cells on a face as mutable bytes in the same order as `face_slice`.
+    ///
+    /// - `face` - The face to get the values of.
+    #[cfg(feature = "bytemuck")]
+    pub fn face_bytes_mut(&mut self, face: CylinderFace) -> &mut [u8] where T: bytemuck::Pod {
+        bytemuck::cast_slice_mut(self.face_slice_mut(face))
+    }
+}
+
+impl <T, const W: usize, const H: usize, E: CylinderEnds> SurfaceGrid<T> for CylinderGrid<T, W, H, E> {
+    type Point = CylinderPoint<W, H, E>;
+
+    fn from_fn<F: FnMut(&Self::Point) -> T>(mut f: F) -> Self {
+        let size = CylinderPoint::<W, H, E>::cap_size();
+
+        let top = (0..size).cartesian_product(0..size)
+            .map(|(y, x)| f(&CylinderPoint::new(CylinderFace::Top, x as u32, y as u32)))
+            .collect();
+        let side = HeapArray2D::from_fn(|y, x| f(&CylinderPoint::new(CylinderFace::Side, x as u32, y as u32)));
+        let bottom = (0..size).cartesian_product(0..size)
+            .map(|(y, x)| f(&CylinderPoint::new(CylinderFace::Bottom, x as u32, y as u32)))
+            .collect();
+
+        Self {
+            top,
+            side,
+            bottom,
+            ends: PhantomData,
+        }
+    }
+
+    fn from_fn_par<F: Fn(&Self::Point) -> T + Send + Sync>(f: F) -> Self where T: Send + Sync {
+        let size = CylinderPoint::<W, H, E>::cap_size();
+
+        let top = (0..size * size).into_par_iter()
+            .map(|i| f(&CylinderPoint::new(CylinderFace::Top, (i % size) as u32, (i / size) as u32)))
+            .collect();
+        let side = HeapArray2D::from_fn_par(|y, x| f(&CylinderPoint::new(CylinderFace::Side, x as u32, y as u32)));
+        let bottom = (0..size * size).into_par_iter()
+            .map(|i| f(&CylinderPoint::new(CylinderFace::Bottom, (i % size) as u32, (i / size) as u32)))
+            .collect();
+
+        Self {
+            top,
+            side,
+            bottom,
+            ends: PhantomData,
+        }
+    }
+
+    fn try_from_fn<X, F: FnMut(&Self::Point) -> Result<T, X>>(mut f: F) -> Result<Self, X> {
+        let size = CylinderPoint::<W, H, E>::cap_size();
+
+        let values = (0..W * H + 2 * size * size)
+            .map(CylinderPoint::from_index)
+            .map(|point| f(&point))
+            .collect::<Result<_, _>>()?;
+
+        // The values are in the same order as `points`.
+        Ok(Self::from_vec(values).unwrap())
+    }
+
+    fn try_from_fn_par<X: Send, F: Fn(&Self::Point) -> Result<T, X> + Send + Sync>(f: F) -> Result<Self, X> where T: Send + Sync {
+        let size = CylinderPoint::<W, H, E>::cap_size();
+
+        let values = (0..W * H + 2 * size * size).into_par_iter()
+            .map(CylinderPoint::from_index)
+            .map(|point| f(&point))
+            .collect::<Result<_, _>>()?;
+
+        Ok(Self::from_vec(values).unwrap())
+    }
+
+    fn from_vec(values: Vec<T>) -> Option<Self> {
+        let size = CylinderPoint::<W, H, E>::cap_size();
+
+        if values.len() != W * H + 2 * size * size {
+            return None;
+        }
+
+        // `from_fn` visits the points in the same order as `points`.
+        let mut values = values.into_iter();
+
+        Some(Self::from_fn(|_| values.next().unwrap()))
+    }
+
+    fn set_from_fn<F: FnMut(&Self::Point) -> T>(&mut self, mut f: F) {
+        let size = CylinderPoint::<W, H, E>::cap_size();
+
+        (0..W * H + 2 * size * size)
+            .map(CylinderPoint::from_index)
+            .for_each(|point| self[point] = f(&point))
+    }
+
+    fn set_from_fn_par<F: Fn(&Self::Point) -> T + Send + Sync>(&mut self, f: F) where T: Send + Sync {
+        let size = CylinderPoint::<W, H, E>::cap_size();
+
+        self.top.par_iter_mut().enumerate().for_each(|(i, value)| {
+            *value = f(&CylinderPoint::new(CylinderFace::Top, (i % size) as u32, (i / size) as u32));
+        });
+
+        self.side.par_iter_mut().enumerate().for_each(|(y, subarray)| {
+            for (x, value) in subarray.iter_mut().enumerate() {
+                let point = CylinderPoint::new(CylinderFace::Side, x as u32, y as u32);
+
+                *value = f(&point);
+            }
+        });
+
+        self.bottom.par_iter_mut().enumerate().for_each(|(i, value)| {
+            *value = f(&CylinderPoint::new(CylinderFace::Bottom, (i % size) as u32, (i / size) as u32));
+        });
+    }
+
+    fn iter<'a>(&'a self) -> impl ExactSizeIterator<Item = (Self::Point, &'a T)> + DoubleEndedIterator where T: 'a {
+        self.points()
+            .map(|point| (point, &self[point]))
+    }
+
+    fn par_iter<'a>(&'a self) -> impl IndexedParallelIterator<Item = (Self::Point, &'a T)> where T: 'a + Send + Sync {
+        self.par_points()
+            .map(|point| (point, &self[point]))
+    }
+
+    fn par_iter_mut<'a>(&'a mut self) -> impl IndexedParallelIterator<Item = (Self::Point, &'a mut T)> where T: 'a + Send + Sync {
+        let size = CylinderPoint::<W, H, E>::cap_size();
+
+        let cap = |data: &'a mut Vec<T>, face| data.par_iter_mut()
+            .enumerate()
+            .map(move |(i, value)| (CylinderPoint::new(face, (i % size) as u32, (i / size) as u32), value));
+
+        let side = self.side.as_flattened_mut()
+            .par_iter_mut()
+            .enumerate()
+            .map(|(i, value)| (CylinderPoint::new(CylinderFace::Side, (i % W) as u32, (i / W) as u32), value));
+
+        cap(&mut self.top, CylinderFace::Top)
+            .chain(side)
+            .chain(cap(&mut self.bottom, CylinderFace::Bottom))
+    }
+
+    fn points(&self) -> impl ExactSizeIterator<Item = Self::Point> + DoubleEndedIterator {
+        let size = CylinderPoint::<W, H, E>::cap_size();
+
+        (0..W * H + 2 * size * size)
+            .map(CylinderPoint::from_index)
+    }
+
+    fn par_points(&self) -> impl IndexedParallelIterator<Item = Self::Point> {
+        let size = CylinderPoint::<W, H, E>::cap_size();
+
+        (0..W * H + 2 * size * size).into_par_iter()
+            .map(CylinderPoint::from_index)
+    }
+
+    fn par_map_to_vec<U: Send, F: Fn(&Self::Point, &T) -> U + Send + Sync>(&self, f: F) -> Vec<U> where T: Send + Sync {
+        let size = CylinderPoint::<W, H, E>::cap_size();
+
+        let mut result = Vec::with_capacity(W * H + 2 * size * size);
+
+        (0..W * H + 2 * size * size).into_par_iter()
+            .map(|i| {
+                let point = CylinderPoint::from_index(i);
+
+                f(&point, &self[point])
+            })
+            .collect_into_vec(&mut result);
+
+        result
+    }
+
+    fn to_index(&self, point: &Self::Point) -> usize {
+        point.index()
+    }
+
+    fn at(&self, point: &Self::Point) -> &T {
+        &self[point]
+    }
+
+    fn at_mut(&mut self, point: &Self::Point) -> &mut T {
+        &mut self[point]
+    }
+
+    /// Gets the surface area of the cylinder used by `GridPoint::position`.
+    ///
+    /// This is the area of the side of the cylinder plus the area of the caps if it has them.
+    fn surface_area(&self, scale: f64) -> f64 {
+        let height = CylinderPoint::<W, H, E>::height(scale);
+
+        let caps = match E::END {
+            CylinderEnd::Cap => 2.0 * PI * scale * scale,
+            _ => 0.0,
+        };
+
+        2.0 * PI * scale * height + caps
+    }
+
+    fn topology(&self) -> Topology {
+        let size = CylinderPoint::<W, H, E>::cap_size();
+
+        Topology {
+            surface: Surface::Cylinder,
+            face_count: if size == 0 { 1 } else { 3 },
+            width: W.max(size),
+            height: H.max(size),
+            len: W * H + 2 * size * size,
+        }
+    }
+}
+
+impl <T, const W: usize, const H: usize, E: CylinderEnds> Index<CylinderPoint<W, H, E>> for CylinderGrid<T, W, H, E> {
+    type Output = T;
+
+    fn index(&self, index: CylinderPoint<W, H, E>) -> &Self::Output {
+        let size = CylinderPoint::<W, H, E>::cap_size();
+
+        match index.face {
+            CylinderFace::Top => &self.top[index.y as usize * size + index.x as usize],
+            CylinderFace::Side => &self.side[index.y as usize][index.x as usize],
+            CylinderFace::Bottom => &self.bottom[index.y as usize * size + index.x as usize],
+        }
+    }
+}
+
+impl <T, const W: usize, const H: usize, E: CylinderEnds> IndexMut<CylinderPoint<W, H, E>> for CylinderGrid<T, W, H, E> {
+    fn index_mut(&mut self, index: CylinderPoint<W, H, E>) -> &mut Self::Output {
+        let size = CylinderPoint::<W, H, E>::cap_size();
+
+        match index.face {
+            CylinderFace::Top => &mut self.top[index.y as usize * size + index.x as usize],
+            CylinderFace::Side => &mut self.side[index.y as usize][index.x as usize],
+            CylinderFace::Bottom => &mut self.bottom[index.y as usize * size + index.x as usize],
+        }
+    }
+}
+
+impl <T, const W: usize, const H: usize, E: CylinderEnds> Index<&CylinderPoint<W, H, E>> for CylinderGrid<T, W, H, E> {
+    type Output = T;
+
+    fn index(&self, index: &CylinderPoint<W, H, E>) -> &Self::Output {
+        &self[*index]
+    }
+}
+
+impl <T, const W: usize, const H: usize, E: CylinderEnds> IndexMut<&CylinderPoint<W, H, E>> for CylinderGrid<T, W, H, E> {
+    fn index_mut(&mut self, index: &CylinderPoint<W, H, E>) -> &mut Self::Output {
+        &mut self[*index]
+    }
+}
+
+impl <T, const W: usize, const H: usize, E: CylinderEnds> FromIterator<T> for CylinderGrid<T, W, H, E> {
+    /// Creates a grid from the values of its cells in the same order as `points`.
+    ///
+    /// # Panics
+    /// Panics if the iterator does not yield exactly one value for each cell in the grid.
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        Self::from_vec(iter.into_iter().collect())
+            .expect("the iterator must yield one value for each cell in the grid")
+    }
+}
+
+impl <T, const W: usize, const H: usize, E: CylinderEnds> IntoIterator for CylinderGrid<T, W, H, E> {
+    type Item = (CylinderPoint<W, H, E>, T);
+
+    type IntoIter = vec::IntoIter<Self::Item>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        let values = self.top.into_iter()
+            .chain(self.side.into_iter().flat_map(|subarray| subarray.into_iter()))
+            .chain(self.bottom);
+
+        let data: Vec<_> = values.enumerate()
+            .map(|(i, value)| (CylinderPoint::from_index(i), value))
+            .collect();
+
+        data.into_iter()
+    }
+}
+
+#[cfg(feature = "serde")]
+impl <T: serde::Serialize, const W: usize, const H: usize, E: CylinderEnds> serde::Serialize for CylinderGrid<T, W, H, E> {
+    /// Serializes the values in this grid as a sequence in the same order as `iter`.
+    fn serialize<R: serde::Serializer>(&self, serializer: R) -> Result<R::Ok, R::Error> {
+        serializer.collect_seq(self.iter().map(|(_, value)| value))
+    }
+}
+
+#[cfg(feature = "serde")]
+impl <'de, T: serde::Deserialize<'de>, const W: usize, const H: usize, E: CylinderEnds> serde::Deserialize<'de> for CylinderGrid<T, W, H, E> {
+    /// Deserializes a grid from a sequence of values in the same order as `iter`.
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let values = Vec::<T>::deserialize(deserializer)?;
+        let length = values.len();
+        let size = CylinderPoint::<W, H, E>::cap_size();
+
+        Self::from_vec(values)
+            .ok_or_else(|| serde::de::Error::invalid_length(length, &format!("a sequence of {} values", W * H + 2 * size * size).as_str()))
+    }
+}
+
+/// A point on a `CylinderGrid`.
+///
+/// Moving left or right wraps around the cylinder. Moving off the top or bottom of the side
+/// depends on `E`. With `Clamped` and `Reflected` ends the `checked_` methods of `GridPoint`
+/// return `None` when moving off an end.
+///
+/// On a cap, moving down from the bottom row of the top cap and up from the top row of the bottom
+/// cap both lead to the first quarter of the side. The other edges of the caps join onto the rest
+/// of the side in order going right around the cylinder.
+///
+/// # Type Parameters
+/// - `E` - The behaviour of the ends of the cylinder.
+///
+/// # Constant Parameters
+/// - `W` - The number of cells around the cylinder.
+/// - `H` - The number of cells along the side of the cylinder.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct CylinderPoint<const W: usize, const H: usize, E: CylinderEnds = Clamped> {
+    face: CylinderFace,
+    x: u32,
+    y: u32,
+    ends: PhantomData<E>,
+}
+
+impl <const W: usize, const H: usize, E: CylinderEnds> CylinderPoint<W, H, E> {
+    /// Creates a new point.
+    ///
+    /// - `face` - The part of the cylinder that the point lies on.
+    /// - `x` - The X coordinate.
+    /// - `y` - The Y coordinate.
+    fn new(face: CylinderFace, x: u32, y: u32) -> Self {
+        Self {
+            face,
+            x,
+            y,
+            ends: PhantomData,
+        }
+    }
+
+    /// Creates a new `CylinderPoint` checking that the position lies on the grid.
+    ///
+    /// This returns an error if the position is not on the face. The side is `W` by `H` cells and each cap is `cap_size` cells square, so there are no cells on the caps of a cylinder without them.
+    ///
+    /// - `face` - The part of the cylinder that the point lies on.
+    /// - `x` - The X position on the face.
+    /// - `y` - The Y position on the face.
+    pub fn try_new(face: CylinderFace, x: u32, y: u32) -> Result<Self, OutOfBounds> {
+        let (width, height) = match face {
+            CylinderFace::Side => (W, H),
+            _ => (Self::cap_size(), Self::cap_size()),
+        };
+
+        if (x as usize) < width && (y as usize) < height {
+            Ok(Self::new(face, x, y))
+        } else {
+            Err(OutOfBounds)
+        }
+    }
+
+    /// Gets the part of the cylinder that this point lies on.
+    pub fn face(&self) -> CylinderFace {
+        self.face
+    }
+
+    /// Gets the number of cells along each side of a cap, which is 0 for uncapped cylinders.
+    ///
+    /// # Panics
+    /// Panics if the cylinder is capped and `W` is not a multiple of 4.
+    fn cap_size() -> usize {
+        match E::END {
+            CylinderEnd::Cap => {
+                assert!(W > 0 && W.is_multiple_of(4), "the width of a capped cylinder must be a multiple of 4");
+
+                W / 4
+            },
+            _ => 0,
+        }
+    }
+
+    /// Gets the length of the side of the cylinder.
+    ///
+    /// The cells on the side are square so this is `H` cells of the width given by dividing the
+    /// circumference by `W`.
+    ///
+    /// - `scale` - The radius of the cylinder.
+    fn height(scale: f64) -> f64 {
+        PI * 2.0 * scale / W as f64 * H as f64
+    }
+
+    /// Gets the index of this point in the order that `CylinderGrid::points` visits them.
+    fn index(&self) -> usize {
+        let size = Self::cap_size();
+
+        match self.face {
+            CylinderFace::Top => self.y as usize * size + self.x as usize,
+            CylinderFace::Side => size * size + self.y as usize * W + self.x as usize,
+            CylinderFace::Bottom => size * size + W * H + self.y as usize * size + self.x as usize,
+        }
+    }
+
+    /// Gets the point at an index in the order that `CylinderGrid::points` visits them.
+    ///
+    /// This is the inverse of `index`.
+    ///
+    /// - `index` - The index of the point.
+    fn from_index(index: usize) -> Self {
+        let size = Self::cap_size();
+        let cap = size * size;
+
+        if index < cap {
+            Self::new(CylinderFace::Top, (index % size) as u32, (index / size) as u32)
+        } else if index < cap + W * H {
+            let index = index - cap;
+
+            Self::new(CylinderFace::Side, (index % W) as u32, (index / W) as u32)
+        } else {
+            let index = index - cap - W * H;
+
+            Self::new(CylinderFace::Bottom, (index % size) as u32, (index / size) as u32)
+        }
+    }
+
+    /// Gets the cell on a cap next to a column of the side.
+    ///
+    /// - `face` - The cap to get the cell on.
+    /// - `column` - The column of the side.
+    fn cap_cell(face: CylinderFace, column: u32) -> Self {
+        let size = Self::cap_size() as u32;
+        let (edge, i) = (column / size, column % size);
+        let last = size - 1;
+
+        let (x, y) = match (face, edge) {
+            (CylinderFace::Top, 0) => (i, last),
+            (CylinderFace::Top, 1) => (last, last - i),
+            (CylinderFace::Top, 2) => (last - i, 0),
+            (CylinderFace::Top, _) => (0, i),
+            (_, 0) => (i, 0),
+            (_, 1) => (last, i),
+            (_, 2) => (last - i, last),
+            (_, _) => (0, last - i),
+        };
+
+        Self::new(face, x, y)
+    }
+
+    /// Gets a point in a column of the side of the cylinder.
+    ///
+    /// - `column` - The column, which wraps around the cylinder.
+    /// - `y` - The row.
+    fn side(column: u32, y: u32) -> Self {
+        Self::new(CylinderFace::Side, column % W as u32, y)
+    }
+
+    /// Gets the row reached by moving off an end of the side of the cylinder without caps.
+    ///
+    /// - `edge` - The row at the end.
+    /// - `inner` - The row next to the end.
+    fn off_end(&self, edge: u32, inner: u32) -> Self {
+        match E::END {
+            CylinderEnd::Reflect if H > 1 => Self::side(self.x, inner),
+            _ => Self::side(self.x, edge),
+        }
+    }
+
+    /// Gets the angle around the cylinder of a position on a cap.
+    ///
+    /// Each cap is mapped onto its disk so that squares around the centre of the cap become
+    /// circles and each side of the square is spread evenly over the quarter of the circle next to
+    /// the part of the side of the cylinder that it joins.
+    ///
+    /// - `a` - The X position on the cap from -1 to 1.
+    /// - `b` - The Y position on the cap from -1 to 1.
+    fn cap_angle(&self, a: f64, b: f64) -> f64 {
+        let radius = a.abs().max(b.abs());
+
+        let (edge, offset) = match self.face {
+            CylinderFace::Top => if b == radius {
+                (0.0, 1.0 + a / radius)
+            } else if a == radius {
+                (1.0, 1.0 - b / radius)
+            } else if -b == radius {
+                (2.0, 1.0 - a / radius)
+            } else {
+                (3.0, 1.0 + b / radius)
+            },
+            _ => if -b == radius {
+                (0.0, 1.0 + a / radius)
+            } else if a == radius {
+                (1.0, 1.0 + b / radius)
+            } else if b == radius {
+                (2.0, 1.0 - a / radius)
+            } else {
+                (3.0, 1.0 - b / radius)
+            },
+        };
+
+        (edge + offset / 2.0) * PI / 2.0
+    }
+}
+
+impl <const W: usize, const H: usize, E: CylinderEnds> GridPoint for CylinderPoint<W, H, E> {
+    fn up(&self) -> Self {
+        let size = Self::cap_size() as u32;
+
+        match self.face {
+            CylinderFace::Side => if self.y > 0 {
+                Self::side(self.x, self.y - 1)
+            } else if E::END == CylinderEnd::Cap {
+                Self::cap_cell(CylinderFace::Top, self.x)
+            } else {
+                self.off_end(0, 1)
+            },
+            CylinderFace::Top => if self.y > 0 {
+                Self::new(self.face, self.x, self.y - 1)
+            } else {
+                Self::side(3 * size - 1 - self.x, 0)
+            },
+            CylinderFace::Bottom => if self.y > 0 {
+                Self::new(self.face, self.x, self.y - 1)
+            } else {
+                Self::side(self.x, H as u32 - 1)
+            },
+        }
+    }
+
+    fn down(&self) -> Self {
+        let size = Self::cap_size() as u32;
+
+        match self.face {
+            CylinderFace::Side => if self.y < H as u32 - 1 {
+                Self::side(self.x, self.y + 1)
+            } else if E::END == CylinderEnd::Cap {
+                Self::cap_cell(CylinderFace::Bottom, self.x)
+            } else {
+                self.off_end(H as u32 - 1, (H as u32).saturating_sub(2))
+            },
+            CylinderFace::Top => if self.y < size - 1 {
+                Self::new(self.face, self.x, self.y + 1)
+            } else {
+                Self::side(self.x, 0)
+            },
+            CylinderFace::Bottom => if self.y < size - 1 {
+                Self::new(self.face, self.x, self.y + 1)
+            } else {
+                Self::side(3 * size - 1 - self.x, H as u32 - 1)
+            },
+        }
+    }
+
+    fn left(&self) -> Self {
+        let size = Self::cap_size() as u32;
+
+        match self.face {
+            CylinderFace::Side => Self::side(self.x + W as u32 - 1, self.y),
+            _ if self.x > 0 => Self::new(self.face, self.x - 1, self.y),
+            CylinderFace::Top => Self::side(3 * size + self.y, 0),
+            CylinderFace::Bottom => Self::side(4 * size - 1 - self.y, H as u32 - 1),
+        }
+    }
+
+    fn right(&self) -> Self {
+        let size = Self::cap_size() as u32;
+
+        match self.face {
+            CylinderFace::Side => Self::side(self.x + 1, self.y),
+            _ if self.x < size - 1 => Self::new(self.face, self.x + 1, self.y),
+            CylinderFace::Top => Self::side(2 * size - 1 - self.y, 0),
+            CylinderFace::Bottom => Self::side(size + self.y, H as u32 - 1),
+        }
+    }
+
+    fn checked_up(&self) -> Option<Self> {
+        (self.face != CylinderFace::Side || self.y > 0 || E::END == CylinderEnd::Cap).then(|| self.up())
+    }
+
+    fn checked_down(&self) -> Option<Self> {
+        (self.face != CylinderFace::Side || self.y < H as u32 - 1 || E::END == CylinderEnd::Cap).then(|| self.down())
+    }
+
+    /// Gets the position of the point in 3D space.
+    ///
+    /// The cylinder has a radius of `scale` and is centred on the origin with the Y axis running
+    /// along its middle. The cells on the side are square so its length is `H` cells of the width
+    /// given by dividing the circumference by `W`. This is the position of the corner of the cell
+    /// with the smallest X and Y coordinates.
+    ///
+    /// - `scale` - The scale of the 3D object.
+    fn position(&self, scale: f64) -> (f64, f64, f64) {
+        let height = Self::height(scale);
+        let size = Self::cap_size() as f64;
+
+        let (radius, angle, y) = match self.face {
+            CylinderFace::Side => {
+                let angle = self.x as f64 / W as f64 * PI * 2.0;
+
+                (scale, angle, height / 2.0 - self.y as f64 / H as f64 * height)
+            },
+            _ => {
+                let a = self.x as f64 / size * 2.0 - 1.0;
+                let b = self.y as f64 / size * 2.0 - 1.0;
+
+                let radius = a.abs().max(b.abs());
+                let angle = if radius == 0.0 { 0.0 } else { self.cap_angle(a, b) };
+                let y = if self.face == CylinderFace::Top { height / 2.0 } else { -height / 2.0 };
+
+                (radius * scale, angle, y)
+            },
+        };
+
+        (radius * angle.sin(), y, radius * angle.cos())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use approx::assert_relative_eq;
+    use itertools::Itertools;
+
+    use crate::{GridPoint, SurfaceGrid};
+
+    use super::{Capped, CylinderFace, CylinderGrid, CylinderPoint, Reflected};
+
+    #[test]
+    fn test_cylinder_clamped() {
+        let grid: CylinderGrid<(), 8, 5> = CylinderGrid::default();
+
+        for point in grid.points() {
+            assert_eq!(point.checked_up().is_none(), point.y == 0);
+            assert_eq!(point.checked_down().is_none(), point.y == 4);
+
+            if point.y == 0 {
+                assert_eq!(point.up(), point);
+            }
+
+            if point.y == 4 {
+                assert_eq!(point.down(), point);
+            }
+        }
+
+        assert_eq!(grid.points().count(), 40);
+    }
+
+    #[test]
+    fn test_cylinder_reflected() {
+        let point: CylinderPoint<8, 5, Reflected> = CylinderPoint::new(CylinderFace::Side, 3, 0);
+
+        assert_eq!(point.up(), CylinderPoint::new(CylinderFace::Side, 3, 1));
+        assert_eq!(point.checked_up(), None);
+        assert_eq!(CylinderPoint::<8, 5, Reflected>::new(CylinderFace::Side, 3, 4).down(), CylinderPoint::new(CylinderFace::Side, 3, 3));
+        assert_eq!(CylinderPoint::<8, 1, Reflected>::new(CylinderFace::Side, 3, 0).down(), CylinderPoint::new(CylinderFace::Side, 3, 0));
+    }
+
+    #[test]
+    fn test_cylinder_wraps() {
+        let grid: CylinderGrid<(), 8, 5> = CylinderGrid::default();
+
+        for point in grid.points() {
+            assert_eq!((0..8).fold(point, |point, _| point.right()), point);
+            assert_eq!(point.left().right(), point);
+        }
+    }
+
+    #[test]
+    fn test_cylinder_capped_neighbours() {
+        let grid: CylinderGrid<(), 12, 5, Capped> = CylinderGrid::default();
+
+        assert_eq!(grid.points().count(), 12 * 5 + 2 * 9);
+
+        for point in grid.points() {
+            let neighbours = point.neighbours();
+
+            assert!(neighbours.iter().all_unique());
+            assert!(!neighbours.contains(&point));
+
+            // Moving onto a neighbour and back always returns to the same point.
+            for neighbour in neighbours {
+                assert!(neighbour.neighbours().contains(&point), "{point:?} is not a neighbour of {neighbour:?}");
+            }
+        }
+    }
+
+    #[test]
+    fn test_cylinder_capped_joins() {
+        let point: CylinderPoint<12, 5, Capped> = CylinderPoint::new(CylinderFace::Side, 1, 0);
+
+        assert_eq!(point.up(), CylinderPoint::new(CylinderFace::Top, 1, 2));
+        assert_eq!(point.up().down(), point);
+        assert_eq!(CylinderPoint::<12, 5, Capped>::new(CylinderFace::Side, 1, 4).down(), CylinderPoint::new(CylinderFace::Bottom, 1, 0));
+        assert_eq!(CylinderPoint::<12, 5, Capped>::new(CylinderFace::Side, 4, 0).up(), CylinderPoint::new(CylinderFace::Top, 2, 1));
+        assert_eq!(point.checked_up(), Some(point.up()));
+    }
+
+    #[test]
+    fn test_cylinder_iteration_order() {
+        let grid: CylinderGrid<usize, 8, 3, Capped> = CylinderGrid::from_index_fn(|i| i);
+
+        for (i, (point, value)) in grid.iter().enumerate() {
+            assert_eq!(*value, i);
+            assert_eq!(grid.to_index(&point), i);
+        }
+
+        let faces: Vec<_> = grid.points().map(|point| point.face()).dedup().collect();
+
+        assert_eq!(faces, [CylinderFace::Top, CylinderFace::Side, CylinderFace::Bottom]);
+        assert_eq!(grid.par_map_to_vec(|_, value| *value), grid.to_vec());
+        assert_eq!(grid.clone().into_iter().map(|(_, value)| value).collect::<Vec<_>>(), grid.to_vec());
+        assert_eq!(CylinderGrid::from_vec(grid.to_vec()), Some(grid));
+    }
+
+    #[test]
+    fn test_cylinder_set_from_fn_par() {
+        let mut grid: CylinderGrid<usize, 8, 3, Capped> = CylinderGrid::default();
+
+        grid.set_from_fn_par(|point| point.index());
+
+        assert_eq!(grid, CylinderGrid::from_index_fn(|i| i));
+        assert_eq!(CylinderGrid::from_fn_par(|point: &CylinderPoint<8, 3, Capped>| point.index()), grid);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_cylinder_capped_width() {
+        let _: CylinderGrid<(), 10, 3, Capped> = CylinderGrid::default();
+    }
+
+    #[test]
+    fn test_cylinder_position() {
+        let grid: CylinderGrid<(), 16, 4, Capped> = CylinderGrid::default();
+        let height = std::f64::consts::PI / 2.0;
+
+        for point in grid.points() {
+            let (x, y, z) = point.position(2.0);
+            let radius = (x * x + z * z).sqrt();
+
+            match point.face() {
+                CylinderFace::Side => {
+                    assert_relative_eq!(radius, 2.0, epsilon = 1e-12);
+                    assert!(y <= height && y > -height);
+                },
+                CylinderFace::Top => {
+                    assert!(radius <= 2.0 + 1e-12);
+                    assert_relative_eq!(y, height);
+                },
+                CylinderFace::Bottom => {
+                    assert!(radius <= 2.0 + 1e-12);
+                    assert_relative_eq!(y, -height);
+                },
+            }
+        }
+
+        // The corner of a cap on the edge of the cylinder is in the same place as the side.
+        let corner: CylinderPoint<16, 4, Capped> = CylinderPoint::new(CylinderFace::Top, 0, 0);
+        let side: CylinderPoint<16, 4, Capped> = CylinderPoint::new(CylinderFace::Side, 12, 0);
+
+        let (ax, ay, az) = corner.position(1.0);
+        let (bx, by, bz) = side.position(1.0);
+
+        assert_relative_eq!(ax, bx, epsilon = 1e-12);
+        assert_relative_eq!(ay, by, epsilon = 1e-12);
+        assert_relative_eq!(az, bz, epsilon = 1e-12);
+    }
+
+    #[test]
+    fn test_cylinder_surface_area() {
+        let grid: CylinderGrid<(), 16, 4> = CylinderGrid::default();
+        let capped: CylinderGrid<(), 16, 4, Capped> = CylinderGrid::default();
+
+        let pi = std::f64::consts::PI;
+
+        // The side is PI long.
+        assert_relative_eq!(grid.surface_area(2.0), 4.0 * pi * pi);
+        assert_relative_eq!(capped.surface_area(2.0), 4.0 * pi * pi + 8.0 * pi);
+    }
+
+    #[test]
+    fn test_cylinder_par_iter_mut() {
+        use rayon::iter::ParallelIterator;
+
+        let mut grid: CylinderGrid<usize, 8, 3, Capped> = CylinderGrid::default();
+
+        grid.par_iter_mut().for_each(|(point, value)| *value = point.index());
+
+        assert_eq!((0..grid.points().count()).collect::<Vec<_>>(), grid.to_vec());
+    }
+
+    #[test]
+    fn test_cylinder_point_try_new() {
+        assert!(CylinderPoint::<8, 3>::try_new(CylinderFace::Side, 7, 2).is_ok());
+        assert!(CylinderPoint::<8, 3>::try_new(CylinderFace::Side, 8, 0).is_err());
+        assert!(CylinderPoint::<8, 3>::try_new(CylinderFace::Top, 0, 0).is_err());
+        assert!(CylinderPoint::<8, 3, Capped>::try_new(CylinderFace::Top, 0, 0).is_ok());
+    }
+
+    #[test]
+    fn test_cylinder_topology() {
+        let uncapped: CylinderGrid<(), 8, 3> = CylinderGrid::default();
+        let capped: CylinderGrid<(), 8, 3, Capped> = CylinderGrid::default();
+
+        assert_eq!(1, uncapped.topology().face_count);
+        assert_eq!(3, capped.topology().face_count);
+        assert_eq!(uncapped.points().count(), uncapped.len());
+        assert_eq!(capped.points().count(), capped.len());
+    }
+
+    #[test]
+    fn test_cylinder_face_slice() {
+        let grid: CylinderGrid<usize, 8, 3, Capped> = CylinderGrid::from_index_fn(|i| i);
+
+        let faces: Vec<usize> = [CylinderFace::Top, CylinderFace::Side, CylinderFace::Bottom]
+            .into_iter()
+            .flat_map(|face| grid.face_slice(face).to_vec())
+            .collect();
+
+        assert_eq!(grid.to_vec(), faces);
+        assert!(CylinderGrid::<usize, 8, 3>::default().face_slice(CylinderFace::Top).is_empty());
+    }
+}
@@ -0,0 +1,384 @@
+//! A module containing grids on bounded flat surfaces.
+
+use std::{ops::{Index, IndexMut}, vec};
+
+use itertools::Itertools;
+use rayon::prelude::*;
+use static_array::HeapArray2D;
+
+use crate::{GridPoint, OutOfBounds, Surface, SurfaceGrid, Topology};
+
+assert_send_sync!([T: Send + Sync, const W: usize, const H: usize]
+    FlatGrid<T, W, H>,
+    FlatPoint<W, H>,
+);
+
+/// A bounded flat rectangular grid that does not wrap at its edges.
+///
+/// # Type Parameters
+/// - `T` - The type of data that the grid holds.
+///
+/// # Constant Parameters
+/// - `W` - The width of the grid.
+/// - `H` - The height of the grid.
+///
+/// # Iteration Order
+/// `iter`, `points` and `into_iter` all visit the grid in row-major order, starting at `(0, 0)`
+/// and increasing `x` before `y`. This order is stable.
+///
+/// # Thread Safety
+/// The cells are kept in a single `HeapArray2D`, so the grid is `Send` when `T` is `Send` and
+/// `Sync` when `T` is `Sync`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Default)]
+pub struct FlatGrid<T, const W: usize, const H: usize> {
+    /// The data held in this grid.
+    data: HeapArray2D<T, W, H>,
+}
+
+impl <T, const W: usize, const H: usize> FlatGrid<T, W, H> {
+    /// Gets the values of the cells in the same order as the points of the grid.
+    pub fn as_slice(&self) -> &[T] {
+        self.data.as_flattened()
+    }
+
+    /// Gets mutable references to the values of the cells in the same order as the points of the
+    /// grid.
+    pub fn as_mut_slice(&mut self) -> &mut [T] {
+        self.data.as_flattened_mut()
+    }
+
+    /// Gets the values of the cells as bytes in the same order as `as_slice`.
+    #[cfg(feature = "bytemuck")]
+    pub fn as_bytes(&self) -> &[u8] where T: bytemuck::Pod {
+        bytemuck::cast_slice(self.as_slice())
+    }
+
+    /// Gets the values of the cells as mutable bytes in the same order as `as_slice`.
+    #[cfg(feature = "bytemuck")]
+    pub fn as_bytes_mut(&mut self) -> &mut [u8] where T: bytemuck::Pod {
+        bytemuck::cast_slice_mut(self.as_mut_slice())
+    }
+}
+
+impl <T, const W: usize, const H: usize> SurfaceGrid<T> for FlatGrid<T, W, H> {
+    type Point = FlatPoint<W, H>;
+
+    row_major_grid_methods!(FlatPoint);
+
+    fn surface_area(&self, scale: f64) -> f64 {
+        let size = W.max(H) as f64;
+
+        (W * H) as f64 / (size * size) * scale * scale
+    }
+
+    fn topology(&self) -> Topology {
+        Topology::uniform(Surface::Plane, 1, W, H)
+    }
+}
+
+impl <T, const W: usize, const H: usize> Index<FlatPoint<W, H>> for FlatGrid<T, W, H> {
+    type Output = T;
+
+    fn index(&self, index: FlatPoint<W, H>) -> &Self::Output {
+        &self.data[index.y as usize][index.x as usize]
+    }
+}
+
+impl <T, const W: usize, const H: usize> IndexMut<FlatPoint<W, H>> for FlatGrid<T, W, H> {
+    fn index_mut(&mut self, index: FlatPoint<W, H>) -> &mut Self::Output {
+        &mut self.data[index.y as usize][index.x as usize]
+    }
+}
+
+impl <T, const W: usize, const H: usize> Index<&FlatPoint<W, H>> for FlatGrid<T, W, H> {
+    type Output = T;
+
+    fn index(&self, index: &FlatPoint<W, H>) -> &Self::Output {
+        &self[*index]
+    }
+}
+
+impl <T, const W: usize, const H: usize> IndexMut<&FlatPoint<W, H>> for FlatGrid<T, W, H> {
+    fn index_mut(&mut self, index: &FlatPoint<W, H>) -> &mut Self::Output {
+        &mut self[*index]
+    }
+}
+
+impl <T, const W: usize, const H: usize> FromIterator<T> for FlatGrid<T, W, H> {
+    /// Creates a grid from the values of its cells in the same order as `points`.
+    ///
+    /// # Panics
+    /// Panics if the iterator does not yield exactly one value for each cell in the grid.
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        Self::from_vec(iter.into_iter().collect())
+            .expect("the iterator must yield one value for each cell in the grid")
+    }
+}
+
+impl <T, const W: usize, const H: usize> IntoIterator for FlatGrid<T, W, H> {
+    type Item = (FlatPoint<W, H>, T);
+
+    type IntoIter = vec::IntoIter<Self::Item>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        let data: Vec<_> = self.data.into_iter()
+            .enumerate()
+            .flat_map(|(y, subarray)| subarray.into_iter()
+                      .enumerate()
+                      .map(move |(x, value)| (FlatPoint::new(x as u32, y as u32), value))
+                      )
+            .collect();
+
+        data.into_iter()
+    }
+}
+
+#[cfg(feature = "serde")]
+impl <T: serde::Serialize, const W: usize, const H: usize> serde::Serialize for FlatGrid<T, W, H> {
+    /// Serializes the values in this grid as a sequence in the same order as `iter`.
+    fn serialize<R: serde::Serializer>(&self, serializer: R) -> Result<R::Ok, R::Error> {
+        serializer.collect_seq(self.iter().map(|(_, value)| value))
+    }
+}
+
+#[cfg(feature = "serde")]
+impl <'de, T: serde::Deserialize<'de>, const W: usize, const H: usize> serde::Deserialize<'de> for FlatGrid<T, W, H> {
+    /// Deserializes a grid from a sequence of values in the same order as `iter`.
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let values = Vec::<T>::deserialize(deserializer)?;
+        let length = values.len();
+
+        Self::from_vec(values)
+            .ok_or_else(|| serde::de::Error::invalid_length(length, &format!("a sequence of {} values", W * H).as_str()))
+    }
+}
+
+/// A point on a `FlatGrid`.
+///
+/// Moving off an edge of the grid stays at the edge. Use the `checked_` methods of `GridPoint` to
+/// detect when this happens.
+///
+/// # Constant Parameters
+/// - `W` - The width of the grid.
+/// - `H` - The height of the grid.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct FlatPoint<const W: usize, const H: usize> {
+    x: u32,
+    y: u32,
+}
+
+impl <const W: usize, const H: usize> FlatPoint<W, H> {
+    /// Creates a new point, clamping the coordinates to the grid.
+    ///
+    /// - `x` - The X coordinate.
+    /// - `y` - The Y coordinate.
+    fn new(x: u32, y: u32) -> Self {
+        Self {
+            x: x.min(W as u32 - 1),
+            y: y.min(H as u32 - 1),
+        }
+    }
+
+    /// Creates a new `FlatPoint` checking that the position lies on the grid.
+    ///
+    /// This returns an error if `x` is `W` or greater or `y` is `H` or greater.
+    ///
+    /// - `x` - The X coordinate.
+    /// - `y` - The Y coordinate.
+    pub fn try_new(x: u32, y: u32) -> Result<Self, OutOfBounds> {
+        if (x as usize) < W && (y as usize) < H {
+            Ok(Self::new(x, y))
+        } else {
+            Err(OutOfBounds)
+        }
+    }
+}
+
+impl <const W: usize, const H: usize> GridPoint for FlatPoint<W, H> {
+    fn up(&self) -> Self {
+        Self::new(self.x, self.y.saturating_sub(1))
+    }
+
+    fn down(&self) -> Self {
+        Self::new(self.x, self.y + 1)
+    }
+
+    fn left(&self) -> Self {
+        Self::new(self.x.saturating_sub(1), self.y)
+    }
+
+    fn right(&self) -> Self {
+        Self::new(self.x + 1, self.y)
+    }
+
+    fn checked_up(&self) -> Option<Self> {
+        (self.y > 0).then(|| self.up())
+    }
+
+    fn checked_down(&self) -> Option<Self> {
+        (self.y < H as u32 - 1).then(|| self.down())
+    }
+
+    fn checked_left(&self) -> Option<Self> {
+        (self.x > 0).then(|| self.left())
+    }
+
+    fn checked_right(&self) -> Option<Self> {
+        (self.x < W as u32 - 1).then(|| self.right())
+    }
+
+    /// Gets the position of the point in 3D space.
+    ///
+    /// The grid lies in the XY plane as a rectangle centred on the origin with its longest side
+    /// having a length of `scale`. Moving up increases Y and moving right increases X.
+    ///
+    /// - `scale` - The scale of the 3D object.
+    fn position(&self, scale: f64) -> (f64, f64, f64) {
+        let size = W.max(H) as f64;
+
+        let x = (self.x as f64 - W as f64 / 2.0) / size * scale;
+        let y = (H as f64 / 2.0 - self.y as f64) / size * scale;
+
+        (x, y, 0.0)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use approx::assert_relative_eq;
+
+    use crate::{GridPoint, SurfaceGrid};
+
+    use super::{FlatGrid, FlatPoint};
+
+    #[test]
+    fn test_flat_up() {
+        let point: FlatPoint<10, 5> = FlatPoint::new(3, 2);
+
+        assert_eq!(point.up(), FlatPoint::new(3, 1));
+        assert_eq!(FlatPoint::<10, 5>::new(3, 0).up(), FlatPoint::new(3, 0));
+    }
+
+    #[test]
+    fn test_flat_down() {
+        let point: FlatPoint<10, 5> = FlatPoint::new(3, 2);
+
+        assert_eq!(point.down(), FlatPoint::new(3, 3));
+        assert_eq!(FlatPoint::<10, 5>::new(3, 4).down(), FlatPoint::new(3, 4));
+    }
+
+    #[test]
+    fn test_flat_left() {
+        let point: FlatPoint<10, 5> = FlatPoint::new(3, 2);
+
+        assert_eq!(point.left(), FlatPoint::new(2, 2));
+        assert_eq!(FlatPoint::<10, 5>::new(0, 2).left(), FlatPoint::new(0, 2));
+    }
+
+    #[test]
+    fn test_flat_right() {
+        let point: FlatPoint<10, 5> = FlatPoint::new(3, 2);
+
+        assert_eq!(point.right(), FlatPoint::new(4, 2));
+        assert_eq!(FlatPoint::<10, 5>::new(9, 2).right(), FlatPoint::new(9, 2));
+    }
+
+    #[test]
+    fn test_flat_checked_edges() {
+        let grid: FlatGrid<(), 10, 5> = FlatGrid::default();
+
+        for point in grid.points() {
+            assert_eq!(point.checked_up().is_none(), point.y == 0);
+            assert_eq!(point.checked_down().is_none(), point.y == 4);
+            assert_eq!(point.checked_left().is_none(), point.x == 0);
+            assert_eq!(point.checked_right().is_none(), point.x == 9);
+
+            assert!(point.checked_up().is_none_or(|up| up == point.up()));
+            assert!(point.checked_down().is_none_or(|down| down == point.down()));
+            assert!(point.checked_left().is_none_or(|left| left == point.left()));
+            assert!(point.checked_right().is_none_or(|right| right == point.right()));
+        }
+    }
+
+    #[test]
+    fn test_flat_iteration_order() {
+        let grid: FlatGrid<(u32, u32), 3, 2> = FlatGrid::from_fn(|point| (point.x, point.y));
+
+        let expected = [(0, 0), (1, 0), (2, 0), (0, 1), (1, 1), (2, 1)];
+
+        let points: Vec<_> = grid.points().map(|point| (point.x, point.y)).collect();
+        let iter: Vec<_> = grid.iter().map(|(_, value)| *value).collect();
+        let par_map: Vec<_> = grid.par_map_to_vec(|_, value| *value);
+        let into_iter: Vec<_> = grid.into_iter().map(|(_, value)| value).collect();
+
+        assert_eq!(points, expected);
+        assert_eq!(iter, expected);
+        assert_eq!(par_map, expected);
+        assert_eq!(into_iter, expected);
+    }
+
+    #[test]
+    fn test_flat_to_vec() {
+        let grid: FlatGrid<u32, 3, 2> = FlatGrid::from_fn(|point| point.x * 10 + point.y);
+
+        assert_eq!(grid.to_vec(), [0, 10, 20, 1, 11, 21]);
+        assert_eq!(FlatGrid::from_vec(grid.to_vec()), Some(grid));
+        assert_eq!(FlatGrid::<u32, 3, 2>::from_vec(vec![0; 7]), None);
+    }
+
+    #[test]
+    fn test_flat_to_index() {
+        let grid: FlatGrid<(), 10, 5> = FlatGrid::default();
+
+        for (i, point) in grid.points().enumerate() {
+            assert_eq!(grid.to_index(&point), i);
+        }
+    }
+
+    #[test]
+    fn test_flat_set_from_fn_par() {
+        let mut grid: FlatGrid<u32, 10, 5> = FlatGrid::default();
+
+        grid.set_from_fn_par(|point| point.x + point.y * 10);
+
+        assert!(grid.iter().all(|(point, value)| *value == point.x + point.y * 10));
+    }
+
+    #[test]
+    fn test_flat_map_neighbours() {
+        let grid: FlatGrid<u32, 10, 5> = FlatGrid::from_fn(|_| 1);
+
+        let grid2 = grid.map_neighbours(|current, up, down, left, right| current + up + down + left + right);
+
+        for (point, value) in grid2.iter() {
+            assert_eq!(*value, 5);
+            assert_eq!(grid2[point], 5);
+        }
+    }
+
+    #[test]
+    fn test_flat_position() {
+        let point: FlatPoint<10, 5> = FlatPoint::new(0, 0);
+
+        assert_eq!(point.position(1.0), (-0.5, 0.25, 0.0));
+        assert_relative_eq!(point.right().position(1.0).0 - point.position(1.0).0, 0.1);
+        assert_relative_eq!(point.down().position(1.0).1 - point.position(1.0).1, -0.1);
+    }
+
+    #[test]
+    fn test_flat_from_index_fn() {
+        let grid: FlatGrid<u32, 7, 3> = FlatGrid::from_index_fn(|i| i as u32);
+
+        for (point, value) in grid.iter() {
+            assert_eq!(*value as usize, grid.to_index(&point));
+        }
+    }
+
+    #[test]
+    fn test_flat_surface_area() {
+        let grid: FlatGrid<(), 10, 5> = FlatGrid::default();
+
+        assert_relative_eq!(grid.surface_area(1.0), 0.5);
+        assert_relative_eq!(grid.surface_area(4.0), 8.0);
+    }
+}
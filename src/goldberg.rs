@@ -0,0 +1,1068 @@
+//! A module containing grids on spheres made of hexagons and pentagons arranged as a Goldberg
+//! polyhedron.
+
+use std::{f64::consts::PI, ops::{Index, IndexMut}, vec};
+
+use rayon::prelude::*;
+
+use crate::{GridPoint, OutOfBounds, Surface, SurfaceGrid, Topology, sphere::{SpherePoint, add, cross, dot, normalize, scale, subtract}};
+
+assert_send_sync!([T: Send + Sync, const N: usize]
+    GoldbergSphereGrid<T, N>,
+    GoldbergSpherePoint<N>,
+);
+
+/// The steps across a diamond to each of the six neighbours of a hexagon, in order around the
+/// hexagon.
+const HEX_DIRECTIONS: [(i64, i64); 6] = [(1, 0), (1, 1), (0, 1), (-1, 0), (-1, -1), (0, -1)];
+
+/// The diamond used to mark the cell at the north pole.
+const NORTH_POLE: u8 = 10;
+
+/// The diamond used to mark the cell at the south pole.
+const SOUTH_POLE: u8 = 11;
+
+/// A grid covering a sphere with hexagonal cells, as on a Goldberg polyhedron.
+///
+/// The centres of the cells are the vertices of an icosahedron whose faces have each been split
+/// into `N * N` triangles and projected onto the sphere. Most cells have six neighbours but the
+/// twelve cells at the corners of the icosahedron are pentagons with five neighbours, so the grid
+/// has no diagonal directions and avoids the square artifacts of the other sphere grids.
+///
+/// The icosahedron is split into ten diamonds, five around the north pole and five around the
+/// south pole, which are each covered by an `N` by `N` grid, along with one cell at each pole.
+/// Within a diamond the X axis points towards the north east and the Y axis points towards the
+/// south east. The cell at `(0, 0)` on each diamond is a pentagon.
+///
+/// Use `GoldbergSpherePoint::hex_neighbours` and `GoldbergSphereGrid::map_hex_neighbours` to work
+/// with all of the neighbours of a cell. The `GridPoint` directions give four of them: `up` and
+/// `down` step along the X axis and `right` and `left` step along the diagonal between the X and
+/// Y axes.
+///
+/// The diamonds are stored in the order that they appear in an unfolded net of the icosahedron.
+/// Use `GoldbergSpherePoint::atlas_position` and `GoldbergSphereGrid::atlas_faces` to lay the grid
+/// out flat, such as to draw it as an image, and `GoldbergSpherePoint::from_atlas_position` to
+/// find the cell under each pixel.
+///
+/// # Type Parameters
+/// - `T` - The type of data that the grid holds.
+///
+/// # Constant Parameters
+/// - `N` - The number of cells along each side of each diamond.
+///
+/// # Iteration Order
+/// `iter`, `points` and `into_iter` all visit the north pole first, followed by the five northern
+/// diamonds and then the five southern diamonds in order of increasing longitude, and the south
+/// pole last. Each diamond is visited in row-major order, starting at `(0, 0)` and increasing `x`
+/// before `y`. This order is stable.
+///
+/// # Thread Safety
+/// The cells of the ten diamonds and the two poles are kept in one `Vec`, so the grid is `Send`
+/// when `T` is `Send` and `Sync` when `T` is `Sync`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct GoldbergSphereGrid<T, const N: usize> {
+    /// The data held in this grid.
+    data: Vec<T>,
+}
+
+impl <T, const N: usize> GoldbergSphereGrid<T, N> {
+    /// Gets the values of the cells in the same order as the points of the grid.
+    pub fn as_slice(&self) -> &[T] {
+        &self.data
+    }
+
+    /// Gets mutable references to the values of the cells in the same order as the points of the
+    /// grid.
+    pub fn as_mut_slice(&mut self) -> &mut [T] {
+        &mut self.data
+    }
+
+    /// Gets the values of the cells as bytes in the same order as `as_slice`.
+    #[cfg(feature = "bytemuck")]
+    pub fn as_bytes(&self) -> &[u8] where T: bytemuck::Pod {
+        bytemuck::cast_slice(self.as_slice())
+    }
+
+    /// Gets the values of the cells as mutable bytes in the same order as `as_slice`.
+    #[cfg(feature = "bytemuck")]
+    pub fn as_bytes_mut(&mut self) -> &mut [u8] where T: bytemuck::Pod {
+        bytemuck::cast_slice_mut(self.as_mut_slice())
+    }
+
+    /// Creates a new grid by applying a function to each cell and all of its neighbours.
+    ///
+    /// The neighbours are passed in the same order as `GoldbergSpherePoint::hex_neighbours`, so
+    /// there are five of them for pentagons and six for hexagons.
+    ///
+    /// - `f` - The function to apply, which takes the current cell and its neighbours.
+    pub fn map_hex_neighbours<F: FnMut(&T, &[&T]) -> T>(&self, mut f: F) -> Self {
+        Self::from_fn(|point| {
+            let (neighbours, count) = point.hex_neighbour_array();
+            let values = neighbours.map(|neighbour| &self[neighbour]);
+
+            f(&self[*point], &values[..count])
+        })
+    }
+
+    /// Creates a new grid by applying a function to each cell and all of its neighbours in
+    /// parallel.
+    ///
+    /// The neighbours are passed in the same order as `GoldbergSpherePoint::hex_neighbours`, so
+    /// there are five of them for pentagons and six for hexagons.
+    ///
+    /// - `f` - The function to apply, which takes the current cell and its neighbours.
+    pub fn map_hex_neighbours_par<F: Fn(&T, &[&T]) -> T + Send + Sync>(&self, f: F) -> Self where T: Send + Sync {
+        Self::from_fn_par(|point| {
+            let (neighbours, count) = point.hex_neighbour_array();
+            let values = neighbours.map(|neighbour| &self[neighbour]);
+
+            f(&self[*point], &values[..count])
+        })
+    }
+
+    /// Gets the width and height of the atlas used by `GoldbergSpherePoint::atlas_position`.
+    ///
+    /// The atlas is measured in the lengths of the edges of the icosahedron so this is `5.5` by
+    /// `1.5 * sqrt(3)`.
+    pub fn atlas_size(&self) -> (f64, f64) {
+        (5.5, 3.0 * ATLAS_ROW)
+    }
+
+    /// Gets the twenty triangular faces of the icosahedron in the atlas used by
+    /// `GoldbergSpherePoint::atlas_position`.
+    ///
+    /// Each face is given as the diamond that contains it and the positions of its three corners
+    /// in the atlas. The faces are in the same order as the diamonds, with the half of each
+    /// diamond nearer to its pole first.
+    pub fn atlas_faces(&self) -> impl Iterator<Item = (u8, [(f64, f64); 3])> {
+        (0..10).flat_map(|diamond| {
+            let [a, b, c, d] = atlas_corners(diamond);
+
+            [(diamond, [a, b, d]), (diamond, [a, c, d])]
+        })
+    }
+}
+
+/// The height of each row of triangles in the atlas, which is `sqrt(3) / 2`.
+const ATLAS_ROW: f64 = 0.866_025_403_784_438_6;
+
+/// Gets the corners of a diamond in the atlas in the same order as `GoldbergSpherePoint::corners`.
+///
+/// The northern diamonds are laid out from left to right along the top of the atlas with their
+/// tips at the north pole, and each southern diamond sits below and to the right of the northern
+/// diamond with the same number.
+///
+/// - `diamond` - The diamond, from 0 to 9.
+fn atlas_corners(diamond: u8) -> [(f64, f64); 4] {
+    let k = (diamond % 5) as f64;
+
+    let upper = |k: f64| (k, ATLAS_ROW);
+    let lower = |k: f64| (k + 0.5, 2.0 * ATLAS_ROW);
+
+    if diamond < 5 {
+        [upper(k), (k + 0.5, 0.0), lower(k), upper(k + 1.0)]
+    } else {
+        [lower(k), upper(k + 1.0), (k + 1.0, 3.0 * ATLAS_ROW), lower(k + 1.0)]
+    }
+}
+
+impl <T: Default, const N: usize> Default for GoldbergSphereGrid<T, N> {
+    fn default() -> Self {
+        Self::from_fn(|_| T::default())
+    }
+}
+
+impl <T, const N: usize> SurfaceGrid<T> for GoldbergSphereGrid<T, N> {
+    type Point = GoldbergSpherePoint<N>;
+
+    fn from_fn<F: FnMut(&Self::Point) -> T>(mut f: F) -> Self {
+        Self {
+            data: (0..10 * N * N + 2)
+                .map(|i| f(&GoldbergSpherePoint::from_index(i)))
+                .collect(),
+        }
+    }
+
+    fn from_fn_par<F: Fn(&Self::Point) -> T + Send + Sync>(f: F) -> Self where T: Send + Sync {
+        let mut data = Vec::with_capacity(10 * N * N + 2);
+
+        (0..10 * N * N + 2).into_par_iter()
+            .map(|i| f(&GoldbergSpherePoint::from_index(i)))
+            .collect_into_vec(&mut data);
+
+        Self {
+            data,
+        }
+    }
+
+    fn try_from_fn<X, F: FnMut(&Self::Point) -> Result<T, X>>(mut f: F) -> Result<Self, X> {
+        Ok(Self {
+            data: (0..10 * N * N + 2)
+                .map(|i| f(&GoldbergSpherePoint::from_index(i)))
+                .collect::<Result<_, _>>()?,
+        })
+    }
+
+    fn try_from_fn_par<X: Send, F: Fn(&Self::Point) -> Result<T, X> + Send + Sync>(f: F) -> Result<Self, X> where T: Send + Sync {
+        Ok(Self {
+            data: (0..10 * N * N + 2).into_par_iter()
+                .map(|i| f(&GoldbergSpherePoint::from_index(i)))
+                .collect::<Result<_, _>>()?,
+        })
+    }
+
+    fn from_vec(values: Vec<T>) -> Option<Self> {
+        if values.len() != 10 * N * N + 2 {
+            return None;
+        }
+
+        Some(Self {
+            data: values,
+        })
+    }
+
+    fn set_from_fn<F: FnMut(&Self::Point) -> T>(&mut self, mut f: F) {
+        for (i, value) in self.data.iter_mut().enumerate() {
+            *value = f(&GoldbergSpherePoint::from_index(i));
+        }
+    }
+
+    fn set_from_fn_par<F: Fn(&Self::Point) -> T + Send + Sync>(&mut self, f: F) where T: Send + Sync {
+        self.data.par_iter_mut().enumerate().for_each(|(i, value)| {
+            *value = f(&GoldbergSpherePoint::from_index(i));
+        });
+    }
+
+    fn iter<'a>(&'a self) -> impl ExactSizeIterator<Item = (Self::Point, &'a T)> + DoubleEndedIterator where T: 'a {
+        self.data.iter()
+            .enumerate()
+            .map(|(i, value)| (GoldbergSpherePoint::from_index(i), value))
+    }
+
+    fn par_iter<'a>(&'a self) -> impl IndexedParallelIterator<Item = (Self::Point, &'a T)> where T: 'a + Send + Sync {
+        self.data.par_iter()
+            .enumerate()
+            .map(|(i, value)| (GoldbergSpherePoint::from_index(i), value))
+    }
+
+    fn par_iter_mut<'a>(&'a mut self) -> impl IndexedParallelIterator<Item = (Self::Point, &'a mut T)> where T: 'a + Send + Sync {
+        self.data.par_iter_mut()
+            .enumerate()
+            .map(|(i, value)| (GoldbergSpherePoint::from_index(i), value))
+    }
+
+    fn points(&self) -> impl ExactSizeIterator<Item = Self::Point> + DoubleEndedIterator {
+        (0..10 * N * N + 2)
+            .map(GoldbergSpherePoint::from_index)
+    }
+
+    fn par_points(&self) -> impl IndexedParallelIterator<Item = Self::Point> {
+        (0..10 * N * N + 2).into_par_iter()
+            .map(GoldbergSpherePoint::from_index)
+    }
+
+    fn par_map_to_vec<U: Send, F: Fn(&Self::Point, &T) -> U + Send + Sync>(&self, f: F) -> Vec<U> where T: Send + Sync {
+        let mut result = Vec::with_capacity(10 * N * N + 2);
+
+        self.data.par_iter()
+            .enumerate()
+            .map(|(i, value)| f(&GoldbergSpherePoint::from_index(i), value))
+            .collect_into_vec(&mut result);
+
+        result
+    }
+
+    fn to_index(&self, point: &Self::Point) -> usize {
+        point.index()
+    }
+
+    fn at(&self, point: &Self::Point) -> &T {
+        &self[point]
+    }
+
+    fn at_mut(&mut self, point: &Self::Point) -> &mut T {
+        &mut self[point]
+    }
+
+    fn surface_area(&self, scale: f64) -> f64 {
+        4.0 * PI * scale * scale
+    }
+
+    fn topology(&self) -> Topology {
+        // The cells at the poles do not lie on any of the diamonds.
+        Topology {
+            surface: Surface::Sphere,
+            face_count: 10,
+            width: N,
+            height: N,
+            len: 10 * N * N + 2,
+        }
+    }
+
+    fn contiguous_values_mut(&mut self) -> Option<&mut [T]> {
+        Some(self.as_mut_slice())
+    }
+}
+
+impl <T, const N: usize> Index<GoldbergSpherePoint<N>> for GoldbergSphereGrid<T, N> {
+    type Output = T;
+
+    fn index(&self, index: GoldbergSpherePoint<N>) -> &Self::Output {
+        &self.data[index.index()]
+    }
+}
+
+impl <T, const N: usize> IndexMut<GoldbergSpherePoint<N>> for GoldbergSphereGrid<T, N> {
+    fn index_mut(&mut self, index: GoldbergSpherePoint<N>) -> &mut Self::Output {
+        &mut self.data[index.index()]
+    }
+}
+
+impl <T, const N: usize> Index<&GoldbergSpherePoint<N>> for GoldbergSphereGrid<T, N> {
+    type Output = T;
+
+    fn index(&self, index: &GoldbergSpherePoint<N>) -> &Self::Output {
+        &self[*index]
+    }
+}
+
+impl <T, const N: usize> IndexMut<&GoldbergSpherePoint<N>> for GoldbergSphereGrid<T, N> {
+    fn index_mut(&mut self, index: &GoldbergSpherePoint<N>) -> &mut Self::Output {
+        &mut self[*index]
+    }
+}
+
+impl <T, const N: usize> FromIterator<T> for GoldbergSphereGrid<T, N> {
+    /// Creates a grid from the values of its cells in the same order as `points`.
+    ///
+    /// # Panics
+    /// Panics if the iterator does not yield exactly one value for each cell in the grid.
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        Self::from_vec(iter.into_iter().collect())
+            .expect("the iterator must yield one value for each cell in the grid")
+    }
+}
+
+impl <T, const N: usize> IntoIterator for GoldbergSphereGrid<T, N> {
+    type Item = (GoldbergSpherePoint<N>, T);
+
+    type IntoIter = vec::IntoIter<Self::Item>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        let data: Vec<_> = self.data.into_iter()
+            .enumerate()
+            .map(|(i, value)| (GoldbergSpherePoint::from_index(i), value))
+            .collect();
+
+        data.into_iter()
+    }
+}
+
+#[cfg(feature = "serde")]
+impl <T: serde::Serialize, const N: usize> serde::Serialize for GoldbergSphereGrid<T, N> {
+    /// Serializes the values in this grid as a sequence in the same order as `iter`.
+    fn serialize<R: serde::Serializer>(&self, serializer: R) -> Result<R::Ok, R::Error> {
+        serializer.collect_seq(self.data.iter())
+    }
+}
+
+#[cfg(feature = "serde")]
+impl <'de, T: serde::Deserialize<'de>, const N: usize> serde::Deserialize<'de> for GoldbergSphereGrid<T, N> {
+    /// Deserializes a grid from a sequence of values in the same order as `iter`.
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let values = Vec::<T>::deserialize(deserializer)?;
+        let length = values.len();
+
+        Self::from_vec(values)
+            .ok_or_else(|| serde::de::Error::invalid_length(length, &format!("a sequence of {} values", 10 * N * N + 2).as_str()))
+    }
+}
+
+/// A point on a `GoldbergSphereGrid`.
+///
+/// # Directions
+/// `up`, `down`, `left` and `right` follow the axes of the diamond that contains the point. The
+/// axes of neighbouring diamonds are rotated relative to each other, so for cells on the edge of
+/// a diamond, next to a pentagon or at the poles, moving one way and then back may not return to
+/// the same cell. For example `up().down()` from the last column of a diamond lands in the next
+/// diamond around the sphere. Use `hex_neighbours` where the neighbours need to be symmetric.
+///
+/// # Constant Parameters
+/// - `N` - The number of cells along each side of each diamond.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct GoldbergSpherePoint<const N: usize> {
+    /// The diamond that contains the point, or `NORTH_POLE` or `SOUTH_POLE` for the cells at the
+    /// poles.
+    diamond: u8,
+    /// The X position on the diamond.
+    x: u32,
+    /// The Y position on the diamond.
+    y: u32,
+}
+
+impl <const N: usize> GoldbergSpherePoint<N> {
+    /// Creates a new `GoldbergSpherePoint`.
+    ///
+    /// - `diamond` - The diamond.
+    /// - `x` - The X position on the diamond.
+    /// - `y` - The Y position on the diamond.
+    fn new(diamond: u8, x: u32, y: u32) -> Self {
+        Self {
+            diamond, x, y,
+        }
+    }
+
+    /// Creates a new `GoldbergSpherePoint` checking that the position lies on the grid.
+    ///
+    /// This returns an error if `diamond` is 10 or greater or either coordinate is `N` or greater. The cells at the poles are not on a diamond so they cannot be created with this.
+    ///
+    /// - `diamond` - The diamond, from 0 to 9.
+    /// - `x` - The X position on the diamond.
+    /// - `y` - The Y position on the diamond.
+    pub fn try_new(diamond: u8, x: u32, y: u32) -> Result<Self, OutOfBounds> {
+        if diamond < NORTH_POLE && (x as usize) < N && (y as usize) < N {
+            Ok(Self::new(diamond, x, y))
+        } else {
+            Err(OutOfBounds)
+        }
+    }
+
+    /// Gets the point at an index in the order that `GoldbergSphereGrid::points` visits them.
+    ///
+    /// - `index` - The index of the point.
+    fn from_index(index: usize) -> Self {
+        if index == 0 {
+            Self::new(NORTH_POLE, 0, 0)
+        } else if index == 10 * N * N + 1 {
+            Self::new(SOUTH_POLE, 0, 0)
+        } else {
+            let index = index - 1;
+
+            Self::new((index / (N * N)) as u8, (index % N) as u32, (index / N % N) as u32)
+        }
+    }
+
+    /// Gets the index of this point in the order that `GoldbergSphereGrid::points` visits them.
+    fn index(&self) -> usize {
+        match self.diamond {
+            NORTH_POLE => 0,
+            SOUTH_POLE => 10 * N * N + 1,
+            diamond => 1 + diamond as usize * N * N + self.y as usize * N + self.x as usize,
+        }
+    }
+
+    /// Gets the diamond that contains this point.
+    ///
+    /// The northern diamonds are numbered from 0 to 4 and the southern diamonds from 5 to 9 in
+    /// order of increasing longitude. This returns `None` for the cells at the poles.
+    pub fn diamond(&self) -> Option<u8> {
+        (self.diamond < NORTH_POLE).then_some(self.diamond)
+    }
+
+    /// Determines whether this cell is one of the twelve pentagons at the corners of the
+    /// icosahedron.
+    pub fn is_pentagon(&self) -> bool {
+        self.diamond >= NORTH_POLE || (self.x == 0 && self.y == 0)
+    }
+
+    /// Gets all of the neighbours of this point.
+    ///
+    /// This returns five neighbours for pentagons and six for hexagons. The neighbours are in order
+    /// around the cell so that each neighbour is also a neighbour of the ones before and after it,
+    /// with the last neighbour next to the first.
+    pub fn hex_neighbours(&self) -> Vec<Self> {
+        let (neighbours, count) = self.hex_neighbour_array();
+
+        neighbours[..count].to_vec()
+    }
+
+    /// Gets the position of the centre of this cell in the unfolded atlas of the icosahedron.
+    ///
+    /// The atlas lays the ten diamonds out flat in a strip, with the twenty faces of the
+    /// icosahedron as equilateral triangles with sides of length 1. The X axis points to the right
+    /// and the Y axis points down, as in an image, and `GoldbergSphereGrid::atlas_size` gives the
+    /// size of the atlas. The poles appear at the tip of each diamond that touches them so they
+    /// use the tips of diamonds 0 and 5.
+    pub fn atlas_position(&self) -> (f64, f64) {
+        let (diamond, s, t) = match self.diamond {
+            NORTH_POLE => (0, 1.0, 0.0),
+            SOUTH_POLE => (5, 0.0, 1.0),
+            diamond => (diamond, self.x as f64 / N as f64, self.y as f64 / N as f64),
+        };
+
+        let [a, b, c, d] = atlas_corners(diamond);
+
+        let (side, along, across) = if s >= t {
+            (b, s - t, t)
+        } else {
+            (c, t - s, s)
+        };
+
+        (
+            a.0 + (side.0 - a.0) * along + (d.0 - a.0) * across,
+            a.1 + (side.1 - a.1) * along + (d.1 - a.1) * across,
+        )
+    }
+
+    /// Finds the cell nearest to a position in the unfolded atlas of the icosahedron.
+    ///
+    /// This is the inverse of `atlas_position` and can be used to draw the grid as an image of
+    /// the atlas. This returns `None` for positions that are not on any of the faces.
+    ///
+    /// - `x` - The X position in the atlas.
+    /// - `y` - The Y position in the atlas.
+    pub fn from_atlas_position(x: f64, y: f64) -> Option<Self> {
+        for diamond in 0..10 {
+            let [a, b, c, d] = atlas_corners(diamond);
+
+            for (side, is_b) in [(b, true), (c, false)] {
+                let u = (side.0 - a.0, side.1 - a.1);
+                let v = (d.0 - a.0, d.1 - a.1);
+                let p = (x - a.0, y - a.1);
+
+                let determinant = u.0 * v.1 - u.1 * v.0;
+                let along = (p.0 * v.1 - p.1 * v.0) / determinant;
+                let across = (u.0 * p.1 - u.1 * p.0) / determinant;
+
+                if along < -1e-9 || across < -1e-9 || along + across > 1.0 + 1e-9 {
+                    continue;
+                }
+
+                let (s, t) = if is_b {
+                    (along + across, across)
+                } else {
+                    (across, along + across)
+                };
+
+                let (x, y, z) = Self::diamond_position(diamond, s, t);
+
+                return Some(Self::nearest_point(x, y, z));
+            }
+        }
+
+        None
+    }
+
+    /// Gets the neighbours of this point along with the number of neighbours.
+    ///
+    /// Any entries after the neighbours are copies of this point.
+    fn hex_neighbour_array(&self) -> ([Self; 6], usize) {
+        let mut neighbours = [*self; 6];
+        let mut count = 0;
+
+        if self.diamond >= NORTH_POLE {
+            for (k, neighbour) in neighbours.iter_mut().take(5).enumerate() {
+                *neighbour = self.pole_neighbour(k as u8);
+            }
+
+            return (neighbours, 5);
+        }
+
+        for (dx, dy) in HEX_DIRECTIONS {
+            if let Some(neighbour) = self.step(dx, dy) {
+                neighbours[count] = neighbour;
+                count += 1;
+            }
+        }
+
+        (neighbours, count)
+    }
+
+    /// Gets one of the five neighbours of a cell at a pole.
+    ///
+    /// - `k` - The number of the diamond containing the neighbour, counted around the pole.
+    fn pole_neighbour(&self, k: u8) -> Self {
+        let n = N as u32;
+
+        if self.diamond == NORTH_POLE {
+            Self::new(k, n - 1, 0)
+        } else {
+            Self::new(k + 5, 0, n - 1)
+        }
+    }
+
+    /// Gets the point reached by moving in one of the six directions of `HEX_DIRECTIONS`.
+    ///
+    /// The direction missing from a pentagon is replaced by the next one around the cell. The
+    /// cells at the poles have no axes so the direction picks one of their five neighbours.
+    ///
+    /// - `direction` - The index of the direction in `HEX_DIRECTIONS`.
+    fn direction(&self, direction: usize) -> Self {
+        if self.diamond >= NORTH_POLE {
+            return self.pole_neighbour(direction.min(4) as u8);
+        }
+
+        let (dx, dy) = HEX_DIRECTIONS[direction];
+
+        self.step(dx, dy).unwrap_or_else(|| {
+            let (dx, dy) = HEX_DIRECTIONS[(direction + 1) % 6];
+
+            self.step(dx, dy).unwrap()
+        })
+    }
+
+    /// Gets the point reached by moving one cell across a diamond.
+    ///
+    /// This returns `None` when moving in the direction that is missing from a pentagon.
+    ///
+    /// - `dx` - The step in the X direction.
+    /// - `dy` - The step in the Y direction.
+    fn step(&self, dx: i64, dy: i64) -> Option<Self> {
+        Self::wrap(self.diamond as i64, self.x as i64 + dx, self.y as i64 + dy)
+    }
+
+    /// Finds the point at a position that may lie up to one cell off the edges of a diamond.
+    ///
+    /// This returns `None` for the position diagonally off the `(0, 0)` corner of the diamond,
+    /// which is where a pentagon is missing a neighbour.
+    ///
+    /// - `diamond` - The diamond that the position is relative to.
+    /// - `x` - The X position.
+    /// - `y` - The Y position.
+    fn wrap(mut diamond: i64, mut x: i64, mut y: i64) -> Option<Self> {
+        let n = N as i64;
+
+        if x < 0 && y < 0 {
+            return None;
+        }
+
+        // Each step moves onto a neighbouring diamond, which is turned relative to this one when
+        // the two diamonds meet around a pole.
+        loop {
+            if (0..n).contains(&x) && (0..n).contains(&y) {
+                return Some(Self::new(diamond as u8, x as u32, y as u32));
+            }
+
+            if diamond < 5 {
+                if (x, y) == (n, 0) {
+                    return Some(Self::new(NORTH_POLE, 0, 0));
+                }
+
+                (diamond, x, y) = if x >= n {
+                    ((diamond + 1) % 5, x - y, x - n)
+                } else if y >= n {
+                    (diamond + 5, x, y - n)
+                } else if x < 0 {
+                    ((diamond + 4) % 5 + 5, x + n, y)
+                } else {
+                    ((diamond + 4) % 5, y + n, y + n - x)
+                };
+            } else {
+                if (x, y) == (0, n) {
+                    return Some(Self::new(SOUTH_POLE, 0, 0));
+                }
+
+                let k = diamond - 5;
+
+                (diamond, x, y) = if x >= n {
+                    ((k + 1) % 5, x - n, y)
+                } else if y >= n {
+                    ((k + 1) % 5 + 5, y - n, y - x)
+                } else if x < 0 {
+                    ((k + 4) % 5 + 5, x + n - y, x + n)
+                } else {
+                    (k, x, y + n)
+                };
+            }
+        }
+    }
+
+    /// Gets the corners of a diamond on an icosahedron with a radius of 1.
+    ///
+    /// The corners are the origin, the end of the X axis, the end of the Y axis and the corner
+    /// opposite the origin. The diamond is split into two triangles along the line from the
+    /// origin to the opposite corner.
+    ///
+    /// - `diamond` - The diamond, from 0 to 9.
+    fn corners(diamond: u8) -> [(f64, f64, f64); 4] {
+        let k = (diamond % 5) as f64;
+        let latitude = 0.5f64.atan();
+
+        let vertex = |latitude: f64, longitude: f64| (
+            latitude.cos() * longitude.sin(),
+            latitude.sin(),
+            latitude.cos() * longitude.cos(),
+        );
+
+        let upper = |k: f64| vertex(latitude, k * 2.0 * PI / 5.0);
+        let lower = |k: f64| vertex(-latitude, (k + 0.5) * 2.0 * PI / 5.0);
+
+        if diamond < 5 {
+            [upper(k), (0.0, 1.0, 0.0), lower(k), upper(k + 1.0)]
+        } else {
+            [lower(k), upper(k + 1.0), (0.0, -1.0, 0.0), lower(k + 1.0)]
+        }
+    }
+
+    /// Gets the direction from the centre of the sphere of a continuous position on a diamond.
+    ///
+    /// - `diamond` - The diamond, from 0 to 9.
+    /// - `s` - The position along the X axis from 0 to 1.
+    /// - `t` - The position along the Y axis from 0 to 1.
+    fn diamond_position(diamond: u8, s: f64, t: f64) -> (f64, f64, f64) {
+        let [a, b, c, d] = Self::corners(diamond);
+
+        let (side, along, across) = if s >= t {
+            (b, s - t, t)
+        } else {
+            (c, t - s, s)
+        };
+
+        normalize(add(a, add(scale(subtract(side, a), along), scale(subtract(d, a), across))))
+    }
+
+    /// Finds the position on a diamond of a direction from the centre of the sphere.
+    ///
+    /// The direction is projected onto the plane of whichever of the two triangles of the
+    /// diamond it is closest to lying within. This returns the position along the X and Y axes
+    /// from 0 to 1.
+    ///
+    /// - `diamond` - The diamond, from 0 to 9.
+    /// - `direction` - The direction.
+    fn diamond_coordinates(diamond: u8, direction: (f64, f64, f64)) -> (f64, f64) {
+        let [a, b, c, d] = Self::corners(diamond);
+
+        let solve = |side: (f64, f64, f64)| {
+            let u = subtract(side, a);
+            let v = subtract(d, a);
+            let normal = cross(u, v);
+
+            let point = subtract(scale(direction, dot(normal, a) / dot(normal, direction)), a);
+
+            let (uu, uv, vv) = (dot(u, u), dot(u, v), dot(v, v));
+            let (pu, pv) = (dot(point, u), dot(point, v));
+            let determinant = uu * vv - uv * uv;
+
+            ((pu * vv - pv * uv) / determinant, (pv * uu - pu * uv) / determinant)
+        };
+
+        let (along_b, across_b) = solve(b);
+        let (along_c, across_c) = solve(c);
+
+        if along_b.min(across_b) >= along_c.min(across_c) {
+            (along_b + across_b, across_b)
+        } else {
+            (across_c, along_c + across_c)
+        }
+    }
+}
+
+impl <const N: usize> GridPoint for GoldbergSpherePoint<N> {
+    fn up(&self) -> Self {
+        self.direction(0)
+    }
+
+    fn down(&self) -> Self {
+        self.direction(3)
+    }
+
+    /// Gets the point that is immediately to the left of this grid point.
+    ///
+    /// Pentagons other than the ones at the poles have no neighbour to the left so this gives the
+    /// neighbour after it around the cell instead.
+    fn left(&self) -> Self {
+        self.direction(4)
+    }
+
+    fn right(&self) -> Self {
+        self.direction(1)
+    }
+
+    /// Gets the position of the centre of the cell in 3D space.
+    ///
+    /// - `scale` - The scale of the 3D object.
+    fn position(&self, scale: f64) -> (f64, f64, f64) {
+        let (x, y, z) = match self.diamond {
+            NORTH_POLE => (0.0, 1.0, 0.0),
+            SOUTH_POLE => (0.0, -1.0, 0.0),
+            diamond => Self::diamond_position(diamond, self.x as f64 / N as f64, self.y as f64 / N as f64),
+        };
+
+        (x * scale, y * scale, z * scale)
+    }
+}
+
+impl <const N: usize> SpherePoint for GoldbergSpherePoint<N> {
+    fn from_geographic(latitude: f64, longitude: f64) -> Self {
+        let direction = (
+            latitude.cos() * longitude.sin(),
+            latitude.sin(),
+            latitude.cos() * longitude.cos(),
+        );
+
+        let n = N as f64;
+
+        let diamond = (0..10)
+            .max_by(|a, b| {
+                let centre = |diamond: u8| {
+                    let [a, _, _, d] = Self::corners(diamond);
+
+                    dot(add(a, d), direction)
+                };
+
+                centre(*a).total_cmp(&centre(*b))
+            })
+            .unwrap();
+
+        let (s, t) = Self::diamond_coordinates(diamond, direction);
+
+        let x = (s * n).round().clamp(0.0, n);
+        let y = (t * n).round().clamp(0.0, n);
+
+        let mut point = Self::wrap(diamond as i64, x as i64, y as i64).unwrap();
+
+        // The projection of the triangles onto the sphere moves the cells slightly so walk
+        // towards the closest cell.
+        loop {
+            let closest = point.hex_neighbours()
+                .into_iter()
+                .max_by(|a, b| dot(a.position(1.0), direction).total_cmp(&dot(b.position(1.0), direction)))
+                .unwrap();
+
+            if dot(closest.position(1.0), direction) > dot(point.position(1.0), direction) {
+                point = closest;
+            } else {
+                return point;
+            }
+        }
+    }
+
+    fn latitude(&self) -> f64 {
+        let (x, y, z) = self.position(1.0);
+
+        y.atan2((x * x + z * z).sqrt())
+    }
+
+    fn longitude(&self) -> f64 {
+        let (x, _, z) = self.position(1.0);
+
+        x.atan2(z).rem_euclid(2.0 * PI)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::{collections::HashSet, f64::consts::PI};
+
+    use approx::assert_relative_eq;
+
+    use crate::{GridPoint, SurfaceGrid, sphere::SpherePoint};
+
+    use super::{GoldbergSphereGrid, GoldbergSpherePoint};
+
+    #[test]
+    fn test_goldberg_neighbours() {
+        fn check<const N: usize>() {
+            let grid: GoldbergSphereGrid<(), N> = GoldbergSphereGrid::default();
+
+            for point in grid.points() {
+                let neighbours = point.hex_neighbours();
+                let expected = if point.is_pentagon() { 5 } else { 6 };
+
+                assert_eq!(neighbours.len(), expected);
+                assert_eq!(neighbours.iter().collect::<HashSet<_>>().len(), expected);
+                assert!(!neighbours.contains(&point));
+
+                for (i, neighbour) in neighbours.iter().enumerate() {
+                    // Neighbours are joined both ways and form a ring around the cell.
+                    assert!(neighbour.hex_neighbours().contains(&point));
+                    assert!(neighbour.hex_neighbours().contains(&neighbours[(i + 1) % expected]));
+                }
+            }
+
+            assert_eq!(grid.points().filter(GoldbergSpherePoint::is_pentagon).count(), 12);
+        }
+
+        check::<1>();
+        check::<2>();
+        check::<5>();
+    }
+
+    #[test]
+    fn test_goldberg_neighbour_distance() {
+        let grid: GoldbergSphereGrid<(), 6> = GoldbergSphereGrid::default();
+
+        // The spacing of the cells varies by less than 40% across the sphere.
+        let distances: Vec<_> = grid.points()
+            .flat_map(|point| point.hex_neighbours().into_iter().map(move |neighbour| (point, neighbour)))
+            .map(|(point, neighbour)| {
+                let (x, y, z) = point.position(1.0);
+                let (nx, ny, nz) = neighbour.position(1.0);
+
+                ((x - nx).powi(2) + (y - ny).powi(2) + (z - nz).powi(2)).sqrt()
+            })
+            .collect();
+
+        let min = distances.iter().copied().fold(f64::INFINITY, f64::min);
+        let max = distances.iter().copied().fold(0.0, f64::max);
+
+        assert!(max / min < 1.4);
+    }
+
+    #[test]
+    fn test_goldberg_directions() {
+        let grid: GoldbergSphereGrid<(), 4> = GoldbergSphereGrid::default();
+
+        for point in grid.points() {
+            let neighbours = point.hex_neighbours();
+
+            for neighbour in [point.up(), point.down(), point.left(), point.right()] {
+                assert!(neighbours.contains(&neighbour));
+            }
+
+            assert_ne!(point.up(), point.down());
+            assert_ne!(point.left(), point.right());
+        }
+    }
+
+    #[test]
+    fn test_goldberg_poles() {
+        let north = GoldbergSpherePoint::<3>::from_geographic(PI / 2.0, 0.0);
+        let south = GoldbergSpherePoint::<3>::from_geographic(-PI / 2.0, 0.0);
+
+        assert!(north.is_pentagon());
+        assert!(south.is_pentagon());
+        assert_eq!(north.diamond(), None);
+        assert_relative_eq!(north.latitude(), PI / 2.0);
+        assert_relative_eq!(south.latitude(), -PI / 2.0);
+
+        for neighbour in north.hex_neighbours() {
+            assert!(neighbour.diamond().unwrap() < 5);
+        }
+
+        for neighbour in south.hex_neighbours() {
+            assert!(neighbour.diamond().unwrap() >= 5);
+        }
+    }
+
+    #[test]
+    fn test_goldberg_from_geographic() {
+        let grid: GoldbergSphereGrid<(), 5> = GoldbergSphereGrid::default();
+
+        for point in grid.points() {
+            assert_eq!(GoldbergSpherePoint::from_geographic(point.latitude(), point.longitude()), point);
+            assert_eq!(GoldbergSpherePoint::from_geographic(point.latitude(), point.longitude() - 2.0 * PI), point);
+
+            // Positions between a cell and its neighbours stay in the cell.
+            for neighbour in point.hex_neighbours() {
+                let (x, y, z) = point.position(1.0);
+                let (nx, ny, nz) = neighbour.position(1.0);
+
+                let (x, y, z) = (x * 0.6 + nx * 0.4, y * 0.6 + ny * 0.4, z * 0.6 + nz * 0.4);
+
+                let latitude = y.atan2((x * x + z * z).sqrt());
+                let longitude = x.atan2(z);
+
+                assert_eq!(GoldbergSpherePoint::from_geographic(latitude, longitude), point);
+            }
+        }
+    }
+
+    #[test]
+    fn test_goldberg_map_hex_neighbours() {
+        let grid: GoldbergSphereGrid<usize, 3> = GoldbergSphereGrid::from_fn(|point| point.is_pentagon() as usize);
+
+        let counts = grid.map_hex_neighbours(|_, neighbours| neighbours.len());
+
+        for (point, count) in counts.iter() {
+            assert_eq!(*count, point.hex_neighbours().len());
+        }
+
+        let sums = grid.map_hex_neighbours(|value, neighbours| *value + neighbours.iter().copied().sum::<usize>());
+
+        assert_eq!(sums, grid.map_hex_neighbours_par(|value, neighbours| *value + neighbours.iter().copied().sum::<usize>()));
+        assert_eq!(sums.iter().map(|(_, value)| *value).sum::<usize>(), 12 * 6);
+    }
+
+    #[test]
+    fn test_goldberg_atlas_faces() {
+        let grid: GoldbergSphereGrid<(), 3> = GoldbergSphereGrid::default();
+        let (width, height) = grid.atlas_size();
+
+        let faces: Vec<_> = grid.atlas_faces().collect();
+
+        assert_eq!(faces.len(), 20);
+
+        for (_, corners) in faces {
+            for (i, (x, y)) in corners.iter().enumerate() {
+                let (nx, ny) = corners[(i + 1) % 3];
+
+                assert!((0.0..=width).contains(x) && (-1e-12..=height + 1e-12).contains(y));
+                assert_relative_eq!(((x - nx).powi(2) + (y - ny).powi(2)).sqrt(), 1.0, epsilon = 1e-12);
+            }
+        }
+    }
+
+    #[test]
+    fn test_goldberg_atlas_position() {
+        let grid: GoldbergSphereGrid<(), 4> = GoldbergSphereGrid::default();
+
+        for point in grid.points() {
+            let (x, y) = point.atlas_position();
+
+            assert_eq!(GoldbergSpherePoint::from_atlas_position(x, y), Some(point));
+
+            // Neighbours on the same diamond are one cell apart in the atlas.
+            for neighbour in point.hex_neighbours() {
+                if point.diamond().is_some() && neighbour.diamond() == point.diamond() {
+                    let (nx, ny) = neighbour.atlas_position();
+
+                    assert_relative_eq!(((x - nx).powi(2) + (y - ny).powi(2)).sqrt(), 0.25, epsilon = 1e-12);
+                }
+            }
+        }
+
+        assert_eq!(GoldbergSpherePoint::<4>::from_atlas_position(0.5, 0.0), Some(GoldbergSpherePoint::from_geographic(PI / 2.0, 0.0)));
+        assert_eq!(GoldbergSpherePoint::<4>::from_atlas_position(0.0, 2.5), None);
+        assert_eq!(GoldbergSpherePoint::<4>::from_atlas_position(5.4, 0.1), None);
+    }
+
+    #[test]
+    fn test_goldberg_iteration_order() {
+        let grid: GoldbergSphereGrid<usize, 3> = GoldbergSphereGrid::from_index_fn(|i| i);
+
+        for (i, (point, value)) in grid.iter().enumerate() {
+            assert_eq!(*value, i);
+            assert_eq!(grid.to_index(&point), i);
+        }
+
+        assert_eq!(grid.to_vec().len(), 92);
+        assert_eq!(grid.par_map_to_vec(|_, value| *value), grid.to_vec());
+        assert_eq!(grid.clone().into_iter().map(|(_, value)| value).collect::<Vec<_>>(), grid.to_vec());
+        assert_eq!(GoldbergSphereGrid::from_vec(grid.to_vec()), Some(grid));
+    }
+
+    #[test]
+    fn test_goldberg_topology() {
+        let grid: GoldbergSphereGrid<(), 4> = GoldbergSphereGrid::default();
+
+        assert_eq!(10, grid.topology().face_count);
+        assert_eq!(grid.points().count(), grid.len());
+    }
+
+    #[test]
+    fn test_goldberg_directions_across_seams() {
+        let grid: GoldbergSphereGrid<(), 4> = GoldbergSphereGrid::default();
+
+        for point in grid.points() {
+            let neighbours = point.hex_neighbours();
+
+            for neighbour in [point.up(), point.down(), point.left(), point.right()] {
+                assert!(neighbours.contains(&neighbour));
+                assert!(neighbour.hex_neighbours().contains(&point));
+            }
+
+            if point.diamond < 10 && (1..3).contains(&point.x) && (1..3).contains(&point.y) {
+                assert_eq!(point.up().down(), point);
+                assert_eq!(point.down().up(), point);
+                assert_eq!(point.left().right(), point);
+                assert_eq!(point.right().left(), point);
+            }
+        }
+
+        let seam = GoldbergSpherePoint::<4>::new(0, 3, 1);
+
+        assert_eq!(seam.up().down(), GoldbergSpherePoint::new(1, 2, 0));
+        assert_eq!(seam.down().up(), seam);
+    }
+}
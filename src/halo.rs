@@ -0,0 +1,570 @@
+//! A module for splitting grids into partitions with ghost cells so that they can be stepped
+//! separately, such as on different machines or devices.
+
+use std::{collections::HashSet, ops::{Index, IndexMut, Range}};
+
+use crate::{GridPoint, SurfaceGrid};
+
+assert_send_sync!([T: Send + Sync, G: SurfaceGrid<()> + Send + Sync]
+    Partitioning<G>,
+    Partition<T, G>,
+    Halo<T>,
+);
+
+/// A plan for splitting a grid into partitions, each with a border of ghost cells copied from the
+/// partitions next to it.
+///
+/// Each partition owns a contiguous range of the cells of the grid in the order given by
+/// `SurfaceGrid::to_index`. Its ghost cells are the cells owned by other partitions that are
+/// within `width` steps of its own cells, counting diagonal steps. The plan only depends on the
+/// shape of the grid, the number of partitions and the width, so each machine can create the
+/// same plan independently and only exchange `Halo`s.
+///
+/// # Type Parameters
+/// - `G` - The grid of `()` that gives the shape of the grid.
+///
+/// # Thread Safety
+/// The plan is `Send` when `G` is `Send` and `Sync` when `G` is `Sync`.
+#[derive(Debug, Clone)]
+pub struct Partitioning<G: SurfaceGrid<()>> {
+    /// The range of indices owned by each partition.
+    ranges: Vec<Range<usize>>,
+    /// The sorted indices of the ghost cells of each partition.
+    ghosts: Vec<Vec<usize>>,
+    /// The greatest number of steps between a ghost cell and the cells of its partition.
+    width: usize,
+    /// The grid that gives the shape of the grid.
+    layout: G,
+}
+
+impl <G: SurfaceGrid<()> + Default> Partitioning<G> {
+    /// Creates a plan for splitting a grid into partitions of nearly equal sizes.
+    ///
+    /// - `parts` - The number of partitions.
+    /// - `width` - The number of rings of ghost cells around each partition. This is the number
+    ///   of steps that can be taken with `Partition::set_from_neighbours` before the halos must
+    ///   be exchanged.
+    ///
+    /// # Panics
+    /// Panics if `parts` is 0 or greater than the number of cells in the grid.
+    pub fn new(parts: usize, width: usize) -> Self {
+        let layout = G::default();
+        let len = layout.points().count();
+
+        assert!((1..=len).contains(&parts), "a grid with {len} cells cannot be split into {parts} partitions");
+
+        let ranges: Vec<_> = (0..parts)
+            .map(|part| part * len / parts..(part + 1) * len / parts)
+            .collect();
+
+        let ghosts = ranges.iter()
+            .map(|range| Self::find_ghosts(&layout, range, width))
+            .collect();
+
+        Self {
+            ranges,
+            ghosts,
+            width,
+            layout,
+        }
+    }
+
+    /// Finds the ghost cells of a partition.
+    ///
+    /// - `layout` - The grid that gives the shape of the grid.
+    /// - `range` - The indices owned by the partition.
+    /// - `width` - The number of rings of ghost cells.
+    fn find_ghosts(layout: &G, range: &Range<usize>, width: usize) -> Vec<usize> {
+        let mut ghosts = HashSet::new();
+
+        let mut frontier: Vec<_> = layout.points()
+            .skip(range.start)
+            .take(range.len())
+            .collect();
+
+        for _ in 0..width {
+            frontier = frontier.iter()
+                .flat_map(adjacent)
+                .filter(|point| {
+                    let index = layout.to_index(point);
+
+                    !range.contains(&index) && ghosts.insert(index)
+                })
+                .collect();
+        }
+
+        let mut ghosts: Vec<_> = ghosts.into_iter().collect();
+        ghosts.sort_unstable();
+
+        ghosts
+    }
+}
+
+impl <G: SurfaceGrid<()> + Clone> Partitioning<G> {
+    /// Gets the number of partitions.
+    pub fn parts(&self) -> usize {
+        self.ranges.len()
+    }
+
+    /// Gets the number of rings of ghost cells around each partition.
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    /// Gets the partition that owns a point.
+    ///
+    /// - `point` - The point to find the owner of.
+    pub fn owner(&self, point: &G::Point) -> usize {
+        let index = self.layout.to_index(point);
+
+        self.ranges.partition_point(|range| range.end <= index)
+    }
+
+    /// Gets the range of indices of the cells owned by a partition.
+    ///
+    /// - `part` - The partition.
+    pub fn owned_range(&self, part: usize) -> Range<usize> {
+        self.ranges[part].clone()
+    }
+
+    /// Gets the indices of the ghost cells of a partition in increasing order.
+    ///
+    /// - `part` - The partition.
+    pub fn ghost_indices(&self, part: usize) -> &[usize] {
+        &self.ghosts[part]
+    }
+
+    /// Creates a partition by copying its cells and ghost cells from a grid.
+    ///
+    /// - `part` - The partition to create.
+    /// - `grid` - The grid to copy the values from.
+    ///
+    /// # Panics
+    /// Panics if `part` is not less than `parts`.
+    pub fn partition<T: Clone, G2: SurfaceGrid<T, Point = G::Point>>(&self, part: usize, grid: &G2) -> Partition<T, G> {
+        let owned = self.ranges[part].clone();
+        let ghosts = self.ghosts[part].clone();
+
+        let points: Vec<_> = self.layout.points().collect();
+
+        let values = owned.clone()
+            .chain(ghosts.iter().copied())
+            .map(|index| grid[points[index].clone()].clone())
+            .collect();
+
+        let sends = (0..self.parts())
+            .filter(|other| *other != part)
+            .map(|other| {
+                let indices: Vec<_> = self.ghosts[other].iter()
+                    .copied()
+                    .filter(|index| owned.contains(index))
+                    .collect();
+
+                (other, indices)
+            })
+            .filter(|(_, indices)| !indices.is_empty())
+            .collect();
+
+        Partition {
+            part,
+            owned,
+            ghosts,
+            sends,
+            values,
+            layout: self.layout.clone(),
+        }
+    }
+
+    /// Splits a grid into all of its partitions.
+    ///
+    /// - `grid` - The grid to copy the values from.
+    pub fn split<T: Clone, G2: SurfaceGrid<T, Point = G::Point>>(&self, grid: &G2) -> Vec<Partition<T, G>> {
+        (0..self.parts())
+            .map(|part| self.partition(part, grid))
+            .collect()
+    }
+}
+
+/// A part of a grid along with copies of the cells around it, created by `Partitioning`.
+///
+/// A partition can be indexed by the points of the cells that it owns and of its ghost cells.
+/// Stepping a partition with `set_from_neighbours` updates every cell whose neighbours are all in
+/// the partition, so after `width` steps its own cells are still correct but its ghost cells are
+/// out of date. Use `export_halo` and `import_halo` to copy the cells of each partition into the
+/// ghost cells of the others before stepping again.
+///
+/// # Type Parameters
+/// - `T` - The type of data that the grid holds.
+/// - `G` - The grid of `()` that gives the shape of the grid.
+///
+/// # Thread Safety
+/// The partition is `Send` when `T` and `G` are `Send` and `Sync` when `T` and `G` are `Sync`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Partition<T, G: SurfaceGrid<()>> {
+    /// The number of the partition.
+    part: usize,
+    /// The indices of the cells owned by the partition.
+    owned: Range<usize>,
+    /// The sorted indices of the ghost cells.
+    ghosts: Vec<usize>,
+    /// The indices of the cells of this partition needed by each of the other partitions.
+    sends: Vec<(usize, Vec<usize>)>,
+    /// The values of the owned cells followed by the values of the ghost cells.
+    values: Vec<T>,
+    /// The grid that gives the shape of the grid.
+    layout: G,
+}
+
+impl <T, G: SurfaceGrid<()>> Partition<T, G> {
+    /// Gets the number of this partition.
+    pub fn part(&self) -> usize {
+        self.part
+    }
+
+    /// Iterates over the points of the cells owned by this partition along with their values.
+    pub fn iter(&self) -> impl ExactSizeIterator<Item = (G::Point, &T)> + DoubleEndedIterator {
+        self.layout.points()
+            .skip(self.owned.start)
+            .take(self.owned.len())
+            .zip(&self.values)
+    }
+
+    /// Iterates over the points of the ghost cells of this partition along with their values.
+    pub fn iter_ghosts(&self) -> impl Iterator<Item = (G::Point, &T)> {
+        let points: Vec<_> = self.layout.points().collect();
+
+        self.ghosts.iter()
+            .map(move |index| points[*index].clone())
+            .zip(&self.values[self.owned.len()..])
+    }
+
+    /// Checks if this partition owns a point.
+    ///
+    /// - `point` - The point to check.
+    pub fn owns(&self, point: &G::Point) -> bool {
+        self.owned.contains(&self.layout.to_index(point))
+    }
+
+    /// Checks if a point is owned by this partition or is one of its ghost cells.
+    ///
+    /// - `point` - The point to check.
+    pub fn contains(&self, point: &G::Point) -> bool {
+        self.local_index(self.layout.to_index(point)).is_some()
+    }
+
+    /// Gets the values of the cells of this partition needed by another partition.
+    ///
+    /// The halo is empty if the other partition has no ghost cells owned by this partition.
+    ///
+    /// - `to` - The partition to send the values to.
+    pub fn export_halo(&self, to: usize) -> Halo<T> where T: Clone {
+        let indices = self.sends.iter()
+            .find(|(part, _)| *part == to)
+            .map_or_else(Vec::new, |(_, indices)| indices.clone());
+
+        let values = indices.iter()
+            .map(|index| self.values[index - self.owned.start].clone())
+            .collect();
+
+        Halo {
+            from: self.part,
+            to,
+            indices,
+            values,
+        }
+    }
+
+    /// Gets the halos needed by every partition that has ghost cells owned by this partition.
+    pub fn export_halos(&self) -> Vec<Halo<T>> where T: Clone {
+        self.sends.iter()
+            .map(|(part, _)| self.export_halo(*part))
+            .collect()
+    }
+
+    /// Copies the values in a halo from another partition into the ghost cells of this partition.
+    ///
+    /// - `halo` - The halo to copy from.
+    ///
+    /// # Panics
+    /// Panics if the halo was exported for a different partition or if it contains cells that are
+    /// not ghost cells of this partition.
+    pub fn import_halo(&mut self, halo: &Halo<T>) where T: Clone {
+        assert_eq!(self.part, halo.to, "a halo for partition {} cannot be imported into partition {}", halo.to, self.part);
+
+        for (index, value) in halo.indices.iter().zip(&halo.values) {
+            let slot = self.ghosts.binary_search(index)
+                .unwrap_or_else(|_| panic!("cell {index} is not a ghost cell of partition {}", self.part));
+
+            self.values[self.owned.len() + slot] = value.clone();
+        }
+    }
+
+    /// Updates the cells of this partition from their direct neighbours.
+    ///
+    /// The provided function is called with the arguments: current, up, down, left, right. Only
+    /// the cells whose neighbours are all in this partition are updated, which includes every
+    /// cell owned by the partition for the first `width` steps after the halos are exchanged.
+    ///
+    /// - `f` - The function to apply.
+    pub fn set_from_neighbours<F: FnMut(&T, &T, &T, &T, &T) -> T>(&mut self, mut f: F) where T: Clone {
+        let updates: Vec<_> = self.local_points()
+            .into_iter()
+            .filter_map(|(slot, point)| {
+                let [up, down, left, right] = [point.up(), point.down(), point.left(), point.right()]
+                    .map(|neighbour| self.local_index(self.layout.to_index(&neighbour)));
+
+                Some((slot, f(&self.values[slot], &self.values[up?], &self.values[down?], &self.values[left?], &self.values[right?])))
+            })
+            .collect();
+
+        for (slot, value) in updates {
+            self.values[slot] = value;
+        }
+    }
+
+    /// Updates the cells of this partition from their direct neighbours including diagonals.
+    ///
+    /// The provided function is called with the arguments: up_left, up, up_right, left, current,
+    /// right, down_left, down, down_right. This is the order given by `DIAGONAL_ORDER`. Only the
+    /// cells whose neighbours are all in this partition are updated.
+    ///
+    /// - `f` - The function to apply.
+    pub fn set_from_neighbours_diagonals<
+                F: FnMut(&T, &T, &T, &T, &T, &T, &T, &T, &T) -> T
+            >(&mut self, mut f: F) where T: Clone {
+        let updates: Vec<_> = self.local_points()
+            .into_iter()
+            .filter_map(|(slot, point)| {
+                let [up_left, up, up_right, left, right, down_left, down, down_right] = adjacent(&point)
+                    .map(|neighbour| self.local_index(self.layout.to_index(&neighbour)));
+
+                let v = &self.values;
+
+                Some((slot, f(&v[up_left?], &v[up?], &v[up_right?], &v[left?], &v[slot], &v[right?], &v[down_left?], &v[down?], &v[down_right?])))
+            })
+            .collect();
+
+        for (slot, value) in updates {
+            self.values[slot] = value;
+        }
+    }
+
+    /// Copies the values of the cells owned by this partition into a grid.
+    ///
+    /// - `grid` - The grid to copy the values into.
+    pub fn write_into<G2: SurfaceGrid<T, Point = G::Point>>(&self, grid: &mut G2) where T: Clone {
+        for (point, value) in self.iter() {
+            grid[point] = value.clone();
+        }
+    }
+
+    /// Gets the position of a cell in the values of this partition.
+    ///
+    /// - `index` - The index of the cell in the grid.
+    fn local_index(&self, index: usize) -> Option<usize> {
+        if self.owned.contains(&index) {
+            Some(index - self.owned.start)
+        } else {
+            self.ghosts.binary_search(&index)
+                .ok()
+                .map(|slot| self.owned.len() + slot)
+        }
+    }
+
+    /// Gets the position in the values of this partition and the point of every cell.
+    fn local_points(&self) -> Vec<(usize, G::Point)> {
+        let points: Vec<_> = self.layout.points().collect();
+
+        self.owned.clone()
+            .chain(self.ghosts.iter().copied())
+            .enumerate()
+            .map(|(slot, index)| (slot, points[index].clone()))
+            .collect()
+    }
+}
+
+impl <T, G: SurfaceGrid<()>> Index<G::Point> for Partition<T, G> {
+    type Output = T;
+
+    /// Gets the value of a cell owned by this partition or of one of its ghost cells.
+    ///
+    /// # Panics
+    /// Panics if the point is not in this partition.
+    fn index(&self, index: G::Point) -> &Self::Output {
+        let slot = self.local_index(self.layout.to_index(&index))
+            .unwrap_or_else(|| panic!("the point is not in partition {}", self.part));
+
+        &self.values[slot]
+    }
+}
+
+impl <T, G: SurfaceGrid<()>> IndexMut<G::Point> for Partition<T, G> {
+    /// Gets a mutable reference to the value of a cell owned by this partition or of one of its
+    /// ghost cells.
+    ///
+    /// # Panics
+    /// Panics if the point is not in this partition.
+    fn index_mut(&mut self, index: G::Point) -> &mut Self::Output {
+        let slot = self.local_index(self.layout.to_index(&index))
+            .unwrap_or_else(|| panic!("the point is not in partition {}", self.part));
+
+        &mut self.values[slot]
+    }
+}
+
+/// The values of the cells of one partition that are ghost cells of another, exported with
+/// `Partition::export_halo`.
+///
+/// With the `serde` feature enabled a halo can be serialized to send it to another machine.
+///
+/// # Type Parameters
+/// - `T` - The type of data that the grid holds.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Halo<T> {
+    /// The partition that the values were exported from.
+    from: usize,
+    /// The partition that the values are for.
+    to: usize,
+    /// The indices of the cells in the grid.
+    indices: Vec<usize>,
+    /// The values of the cells.
+    values: Vec<T>,
+}
+
+impl <T> Halo<T> {
+    /// Gets the partition that the values were exported from.
+    pub fn from(&self) -> usize {
+        self.from
+    }
+
+    /// Gets the partition that the values are for.
+    pub fn to(&self) -> usize {
+        self.to
+    }
+
+    /// Gets the number of cells in the halo.
+    pub fn len(&self) -> usize {
+        self.values.len()
+    }
+
+    /// Checks if the halo has no cells.
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+
+    /// Iterates over the indices of the cells in the grid along with their values.
+    pub fn iter(&self) -> impl ExactSizeIterator<Item = (usize, &T)> + DoubleEndedIterator {
+        self.indices.iter()
+            .copied()
+            .zip(&self.values)
+    }
+}
+
+/// Gets the eight points around a point in the order: up_left, up, up_right, left, right,
+/// down_left, down, down_right.
+///
+/// - `point` - The point to get the neighbours of.
+fn adjacent<P: GridPoint>(point: &P) -> [P; 8] {
+    [
+        point.up_left(), point.up(), point.up_right(),
+        point.left(), point.right(),
+        point.down_left(), point.down(), point.down_right(),
+    ]
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{GridPoint, SurfaceGrid, flat::FlatGrid, sphere::{CubeSphereGrid, CubeSpherePoint}};
+
+    use super::Partitioning;
+
+    #[test]
+    fn test_partitioning_covers_grid() {
+        let plan: Partitioning<CubeSphereGrid<(), 6>> = Partitioning::new(4, 1);
+        let grid: CubeSphereGrid<(), 6> = CubeSphereGrid::default();
+
+        assert_eq!(4, plan.parts());
+        assert_eq!(6 * 6 * 6, (0..4).map(|part| plan.owned_range(part).len()).sum::<usize>());
+
+        for point in grid.points() {
+            let owner = plan.owner(&point);
+
+            assert!(plan.owned_range(owner).contains(&grid.to_index(&point)));
+
+            for neighbour in [point.up(), point.down(), point.left(), point.right(), point.up_left()] {
+                let index = grid.to_index(&neighbour);
+
+                assert!(plan.owned_range(owner).contains(&index) || plan.ghost_indices(owner).contains(&index));
+            }
+        }
+    }
+
+    #[test]
+    fn test_partition_steps_match_grid() {
+        let grid: CubeSphereGrid<f64, 8> = CubeSphereGrid::from_fn(|point: &CubeSpherePoint<8>| point.position(1.0).1);
+        let plan: Partitioning<CubeSphereGrid<(), 8>> = Partitioning::new(3, 2);
+
+        let rule = |current: &f64, up: &f64, down: &f64, left: &f64, right: &f64| current * 0.5 + (up + down + left + right) * 0.125 + left * 0.01;
+
+        let mut expected = grid.clone();
+        let mut partitions = plan.split(&grid);
+
+        for _ in 0..2 {
+            for _ in 0..2 {
+                expected = expected.map_neighbours(rule);
+
+                for partition in &mut partitions {
+                    partition.set_from_neighbours(rule);
+                }
+            }
+
+            let halos: Vec<_> = partitions.iter()
+                .flat_map(|partition| partition.export_halos())
+                .collect();
+
+            for halo in &halos {
+                partitions[halo.to()].import_halo(halo);
+            }
+        }
+
+        let mut result = grid.clone();
+
+        for partition in &partitions {
+            partition.write_into(&mut result);
+        }
+
+        assert_eq!(expected, result);
+    }
+
+    #[test]
+    fn test_partition_diagonals_match_grid() {
+        let grid: FlatGrid<u32, 9, 7> = FlatGrid::from_index_fn(|index| (index * 7 % 11) as u32);
+        let plan: Partitioning<FlatGrid<(), 9, 7>> = Partitioning::new(2, 1);
+
+        let rule = |a: &u32, b: &u32, c: &u32, d: &u32, e: &u32, f: &u32, g: &u32, h: &u32, i: &u32| (a + b * 2 + c + d + e + f + g + h + i) % 13;
+
+        let expected = grid.map_neighbours_diagonals(rule);
+
+        let mut result = grid.clone();
+
+        for mut partition in plan.split(&grid) {
+            partition.set_from_neighbours_diagonals(rule);
+            partition.write_into(&mut result);
+        }
+
+        assert_eq!(expected, result);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_partition_import_wrong_halo() {
+        let grid: CubeSphereGrid<u8, 4> = CubeSphereGrid::default();
+        let plan: Partitioning<CubeSphereGrid<(), 4>> = Partitioning::new(3, 1);
+
+        let mut partitions = plan.split(&grid);
+        let halo = partitions[0].export_halo(1);
+
+        partitions[2].import_halo(&halo);
+    }
+}
@@ -0,0 +1,909 @@
+//! A module containing grids on spheres using the HEALPix equal area pixelization.
+
+use std::{f64::consts::PI, ops::{Index, IndexMut}, vec};
+
+use rayon::prelude::*;
+
+use crate::{GridPoint, OutOfBounds, Surface, SurfaceGrid, Topology, sphere::SpherePoint};
+
+assert_send_sync!([T: Send + Sync, const N: usize]
+    HealpixSphereGrid<T, N>,
+    HealpixSpherePoint<N>,
+);
+
+/// The ring of base faces that each base face belongs to counted in half faces from the north pole.
+const FACE_RING: [i64; 12] = [2, 2, 2, 2, 3, 3, 3, 3, 4, 4, 4, 4];
+
+/// The longitude of the centre of each base face in eighths of a turn.
+const FACE_LONGITUDE: [i64; 12] = [1, 3, 5, 7, 0, 2, 4, 6, 1, 3, 5, 7];
+
+/// The base face reached by moving off a base face, indexed by the direction of the movement and
+/// then by the face.
+///
+/// The directions are numbered `4 + dx + 3 * dy` where `dx` and `dy` are the steps off the edge of
+/// the face. A negative value means that there is no face in that direction.
+const NEIGHBOUR_FACE: [[i8; 12]; 9] = [
+    [8, 9, 10, 11, -1, -1, -1, -1, 10, 11, 8, 9],
+    [5, 6, 7, 4, 8, 9, 10, 11, 9, 10, 11, 8],
+    [-1, -1, -1, -1, 5, 6, 7, 4, -1, -1, -1, -1],
+    [4, 5, 6, 7, 11, 8, 9, 10, 11, 8, 9, 10],
+    [0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11],
+    [1, 2, 3, 0, 0, 1, 2, 3, 5, 6, 7, 4],
+    [-1, -1, -1, -1, 7, 4, 5, 6, -1, -1, -1, -1],
+    [3, 0, 1, 2, 3, 0, 1, 2, 4, 5, 6, 7],
+    [2, 3, 0, 1, -1, -1, -1, -1, 0, 1, 2, 3],
+];
+
+/// How the coordinates change when moving onto the faces in `NEIGHBOUR_FACE`, indexed by the
+/// direction and then by the ring of the face being left.
+///
+/// Bit 1 reverses X, bit 2 reverses Y and bit 4 swaps X and Y.
+const NEIGHBOUR_TRANSFORM: [[u8; 3]; 9] = [
+    [0, 0, 3],
+    [0, 0, 6],
+    [0, 0, 0],
+    [0, 0, 5],
+    [0, 0, 0],
+    [5, 0, 0],
+    [0, 0, 0],
+    [6, 0, 0],
+    [3, 0, 0],
+];
+
+/// The orderings used to number the pixels of a HEALPix grid.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum HealpixOrdering {
+    /// Pixels are numbered along the rings of constant latitude from the north pole to the south
+    /// pole, starting each ring at a longitude of 0.
+    Ring,
+    /// Pixels are numbered by base face and then along a Z-order curve inside each face.
+    ///
+    /// This ordering is only defined when `N` is a power of two.
+    Nested,
+}
+
+/// A grid covering a sphere using the HEALPix pixelization where every cell has the same area.
+///
+/// The sphere is split into 12 diamond shaped base faces, four around each pole and four around
+/// the equator, which are each covered by an `N` by `N` grid. Within a face the X axis points
+/// towards the north east and the Y axis points towards the north west, so `right` and `up` both
+/// move away from the south corner of the face.
+///
+/// # Type Parameters
+/// - `T` - The type of data that the grid holds.
+///
+/// # Constant Parameters
+/// - `N` - The number of cells along each side of each base face. This is `Nside` in HEALPix.
+///
+/// # Iteration Order
+/// `iter`, `points` and `into_iter` all visit the base faces in HEALPix order, starting with the
+/// northern faces. Each face is visited in row-major order, starting at `(0, 0)` and increasing
+/// `x` before `y`. This order is stable and is the same as the nested ordering when `N` is 1. Use
+/// `to_ordered_vec` and `from_ordered_vec` to convert to the ring or nested orderings.
+///
+/// # Thread Safety
+/// The cells of the twelve base pixels are kept in one `Vec`, so the grid is `Send` when `T` is
+/// `Send` and `Sync` when `T` is `Sync`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct HealpixSphereGrid<T, const N: usize> {
+    /// The data held in this grid.
+    data: Vec<T>,
+}
+
+impl <T, const N: usize> HealpixSphereGrid<T, N> {
+    /// Gets the values of the cells in the same order as the points of the grid.
+    pub fn as_slice(&self) -> &[T] {
+        &self.data
+    }
+
+    /// Gets mutable references to the values of the cells in the same order as the points of the
+    /// grid.
+    pub fn as_mut_slice(&mut self) -> &mut [T] {
+        &mut self.data
+    }
+
+    /// Gets the values of the cells as bytes in the same order as `as_slice`.
+    #[cfg(feature = "bytemuck")]
+    pub fn as_bytes(&self) -> &[u8] where T: bytemuck::Pod {
+        bytemuck::cast_slice(self.as_slice())
+    }
+
+    /// Gets the values of the cells as mutable bytes in the same order as `as_slice`.
+    #[cfg(feature = "bytemuck")]
+    pub fn as_bytes_mut(&mut self) -> &mut [u8] where T: bytemuck::Pod {
+        bytemuck::cast_slice_mut(self.as_mut_slice())
+    }
+
+    /// Creates a vector of the values in this grid indexed by their pixel number in an ordering.
+    ///
+    /// - `ordering` - The ordering of the pixels.
+    ///
+    /// # Panics
+    /// Panics if `ordering` is `Nested` and `N` is not a power of two.
+    pub fn to_ordered_vec(&self, ordering: HealpixOrdering) -> Vec<T> where T: Clone {
+        (0..12 * N * N)
+            .map(|i| self[HealpixSpherePoint::from_pixel_index(i as u64, ordering).unwrap()].clone())
+            .collect()
+    }
+
+    /// Creates a grid from a vector of values indexed by their pixel number in an ordering.
+    ///
+    /// This returns `None` if the vector does not contain exactly `12 * N * N` values.
+    ///
+    /// - `values` - The values of the pixels.
+    /// - `ordering` - The ordering of the pixels.
+    ///
+    /// # Panics
+    /// Panics if `ordering` is `Nested` and `N` is not a power of two.
+    pub fn from_ordered_vec(values: Vec<T>, ordering: HealpixOrdering) -> Option<Self> {
+        if values.len() != 12 * N * N {
+            return None;
+        }
+
+        let mut values: Vec<_> = values.into_iter().map(Some).collect();
+
+        Some(Self::from_fn(|point| values[point.pixel_index(ordering) as usize].take().unwrap()))
+    }
+}
+
+impl <T: Default, const N: usize> Default for HealpixSphereGrid<T, N> {
+    fn default() -> Self {
+        Self::from_fn(|_| T::default())
+    }
+}
+
+impl <T, const N: usize> SurfaceGrid<T> for HealpixSphereGrid<T, N> {
+    type Point = HealpixSpherePoint<N>;
+
+    fn from_fn<F: FnMut(&Self::Point) -> T>(mut f: F) -> Self {
+        Self {
+            data: (0..12 * N * N)
+                .map(|i| f(&HealpixSpherePoint::from_index(i)))
+                .collect(),
+        }
+    }
+
+    fn from_fn_par<F: Fn(&Self::Point) -> T + Send + Sync>(f: F) -> Self where T: Send + Sync {
+        let mut data = Vec::with_capacity(12 * N * N);
+
+        (0..12 * N * N).into_par_iter()
+            .map(|i| f(&HealpixSpherePoint::from_index(i)))
+            .collect_into_vec(&mut data);
+
+        Self {
+            data,
+        }
+    }
+
+    fn try_from_fn<X, F: FnMut(&Self::Point) -> Result<T, X>>(mut f: F) -> Result<Self, X> {
+        Ok(Self {
+            data: (0..12 * N * N)
+                .map(|i| f(&HealpixSpherePoint::from_index(i)))
+                .collect::<Result<_, _>>()?,
+        })
+    }
+
+    fn try_from_fn_par<X: Send, F: Fn(&Self::Point) -> Result<T, X> + Send + Sync>(f: F) -> Result<Self, X> where T: Send + Sync {
+        Ok(Self {
+            data: (0..12 * N * N).into_par_iter()
+                .map(|i| f(&HealpixSpherePoint::from_index(i)))
+                .collect::<Result<_, _>>()?,
+        })
+    }
+
+    fn from_vec(values: Vec<T>) -> Option<Self> {
+        if values.len() != 12 * N * N {
+            return None;
+        }
+
+        Some(Self {
+            data: values,
+        })
+    }
+
+    fn set_from_fn<F: FnMut(&Self::Point) -> T>(&mut self, mut f: F) {
+        for (i, value) in self.data.iter_mut().enumerate() {
+            *value = f(&HealpixSpherePoint::from_index(i));
+        }
+    }
+
+    fn set_from_fn_par<F: Fn(&Self::Point) -> T + Send + Sync>(&mut self, f: F) where T: Send + Sync {
+        self.data.par_iter_mut().enumerate().for_each(|(i, value)| {
+            *value = f(&HealpixSpherePoint::from_index(i));
+        });
+    }
+
+    fn iter<'a>(&'a self) -> impl ExactSizeIterator<Item = (Self::Point, &'a T)> + DoubleEndedIterator where T: 'a {
+        self.data.iter()
+            .enumerate()
+            .map(|(i, value)| (HealpixSpherePoint::from_index(i), value))
+    }
+
+    fn par_iter<'a>(&'a self) -> impl IndexedParallelIterator<Item = (Self::Point, &'a T)> where T: 'a + Send + Sync {
+        self.data.par_iter()
+            .enumerate()
+            .map(|(i, value)| (HealpixSpherePoint::from_index(i), value))
+    }
+
+    fn par_iter_mut<'a>(&'a mut self) -> impl IndexedParallelIterator<Item = (Self::Point, &'a mut T)> where T: 'a + Send + Sync {
+        self.data.par_iter_mut()
+            .enumerate()
+            .map(|(i, value)| (HealpixSpherePoint::from_index(i), value))
+    }
+
+    fn points(&self) -> impl ExactSizeIterator<Item = Self::Point> + DoubleEndedIterator {
+        (0..12 * N * N)
+            .map(HealpixSpherePoint::from_index)
+    }
+
+    fn par_points(&self) -> impl IndexedParallelIterator<Item = Self::Point> {
+        (0..12 * N * N).into_par_iter()
+            .map(HealpixSpherePoint::from_index)
+    }
+
+    fn par_map_to_vec<U: Send, F: Fn(&Self::Point, &T) -> U + Send + Sync>(&self, f: F) -> Vec<U> where T: Send + Sync {
+        let mut result = Vec::with_capacity(12 * N * N);
+
+        self.data.par_iter()
+            .enumerate()
+            .map(|(i, value)| f(&HealpixSpherePoint::from_index(i), value))
+            .collect_into_vec(&mut result);
+
+        result
+    }
+
+    fn to_index(&self, point: &Self::Point) -> usize {
+        point.index()
+    }
+
+    fn at(&self, point: &Self::Point) -> &T {
+        &self[point]
+    }
+
+    fn at_mut(&mut self, point: &Self::Point) -> &mut T {
+        &mut self[point]
+    }
+
+    fn surface_area(&self, scale: f64) -> f64 {
+        4.0 * PI * scale * scale
+    }
+
+    fn topology(&self) -> Topology {
+        Topology::uniform(Surface::Sphere, 12, N, N)
+    }
+
+    fn contiguous_values_mut(&mut self) -> Option<&mut [T]> {
+        Some(self.as_mut_slice())
+    }
+}
+
+impl <T, const N: usize> Index<HealpixSpherePoint<N>> for HealpixSphereGrid<T, N> {
+    type Output = T;
+
+    fn index(&self, index: HealpixSpherePoint<N>) -> &Self::Output {
+        &self.data[index.index()]
+    }
+}
+
+impl <T, const N: usize> IndexMut<HealpixSpherePoint<N>> for HealpixSphereGrid<T, N> {
+    fn index_mut(&mut self, index: HealpixSpherePoint<N>) -> &mut Self::Output {
+        &mut self.data[index.index()]
+    }
+}
+
+impl <T, const N: usize> Index<&HealpixSpherePoint<N>> for HealpixSphereGrid<T, N> {
+    type Output = T;
+
+    fn index(&self, index: &HealpixSpherePoint<N>) -> &Self::Output {
+        &self[*index]
+    }
+}
+
+impl <T, const N: usize> IndexMut<&HealpixSpherePoint<N>> for HealpixSphereGrid<T, N> {
+    fn index_mut(&mut self, index: &HealpixSpherePoint<N>) -> &mut Self::Output {
+        &mut self[*index]
+    }
+}
+
+impl <T, const N: usize> FromIterator<T> for HealpixSphereGrid<T, N> {
+    /// Creates a grid from the values of its cells in the same order as `points`.
+    ///
+    /// # Panics
+    /// Panics if the iterator does not yield exactly one value for each cell in the grid.
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        Self::from_vec(iter.into_iter().collect())
+            .expect("the iterator must yield one value for each cell in the grid")
+    }
+}
+
+impl <T, const N: usize> IntoIterator for HealpixSphereGrid<T, N> {
+    type Item = (HealpixSpherePoint<N>, T);
+
+    type IntoIter = vec::IntoIter<Self::Item>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        let data: Vec<_> = self.data.into_iter()
+            .enumerate()
+            .map(|(i, value)| (HealpixSpherePoint::from_index(i), value))
+            .collect();
+
+        data.into_iter()
+    }
+}
+
+#[cfg(feature = "serde")]
+impl <T: serde::Serialize, const N: usize> serde::Serialize for HealpixSphereGrid<T, N> {
+    /// Serializes the values in this grid as a sequence in the same order as `iter`.
+    fn serialize<R: serde::Serializer>(&self, serializer: R) -> Result<R::Ok, R::Error> {
+        serializer.collect_seq(self.data.iter())
+    }
+}
+
+#[cfg(feature = "serde")]
+impl <'de, T: serde::Deserialize<'de>, const N: usize> serde::Deserialize<'de> for HealpixSphereGrid<T, N> {
+    /// Deserializes a grid from a sequence of values in the same order as `iter`.
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let values = Vec::<T>::deserialize(deserializer)?;
+        let length = values.len();
+
+        Self::from_vec(values)
+            .ok_or_else(|| serde::de::Error::invalid_length(length, &format!("a sequence of {} values", 12 * N * N).as_str()))
+    }
+}
+
+/// A point on a `HealpixSphereGrid`.
+///
+/// # Constant Parameters
+/// - `N` - The number of cells along each side of each base face.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct HealpixSpherePoint<const N: usize> {
+    /// The base face that contains the point.
+    face: u8,
+    /// The X position on the face.
+    x: u32,
+    /// The Y position on the face.
+    y: u32,
+}
+
+impl <const N: usize> HealpixSpherePoint<N> {
+    /// Creates a new `HealpixSpherePoint`.
+    ///
+    /// - `face` - The base face.
+    /// - `x` - The X position on the face.
+    /// - `y` - The Y position on the face.
+    fn new(face: u8, x: u32, y: u32) -> Self {
+        Self {
+            face, x, y,
+        }
+    }
+
+    /// Creates a new `HealpixSpherePoint` checking that the position lies on the grid.
+    ///
+    /// This returns an error if `face` is 12 or greater or either coordinate is `N` or greater.
+    ///
+    /// - `face` - The base face, from 0 to 11.
+    /// - `x` - The X position on the face.
+    /// - `y` - The Y position on the face.
+    pub fn try_new(face: u8, x: u32, y: u32) -> Result<Self, OutOfBounds> {
+        if face < 12 && (x as usize) < N && (y as usize) < N {
+            Ok(Self::new(face, x, y))
+        } else {
+            Err(OutOfBounds)
+        }
+    }
+
+    /// Gets the point at an index in the order that `HealpixSphereGrid::points` visits them.
+    ///
+    /// - `index` - The index of the point.
+    fn from_index(index: usize) -> Self {
+        Self::new((index / (N * N)) as u8, (index % N) as u32, (index / N % N) as u32)
+    }
+
+    /// Gets the index of this point in the order that `HealpixSphereGrid::points` visits them.
+    fn index(&self) -> usize {
+        self.face as usize * N * N + self.y as usize * N + self.x as usize
+    }
+
+    /// Gets the base face that contains this point, numbered from 0 to 11 as in HEALPix.
+    pub fn face(&self) -> u8 {
+        self.face
+    }
+
+    /// Gets the HEALPix pixel number of this point.
+    ///
+    /// - `ordering` - The ordering used to number the pixels.
+    ///
+    /// # Panics
+    /// Panics if `ordering` is `Nested` and `N` is not a power of two.
+    pub fn pixel_index(&self, ordering: HealpixOrdering) -> u64 {
+        match ordering {
+            HealpixOrdering::Ring => self.ring_index(),
+            HealpixOrdering::Nested => {
+                assert!(N.is_power_of_two(), "The nested ordering requires N to be a power of two");
+
+                (self.face as u64 * (N * N) as u64) + spread_bits(self.x) + (spread_bits(self.y) << 1)
+            },
+        }
+    }
+
+    /// Gets the point with a HEALPix pixel number.
+    ///
+    /// This returns `None` if there is no pixel with that number.
+    ///
+    /// - `index` - The pixel number.
+    /// - `ordering` - The ordering used to number the pixels.
+    ///
+    /// # Panics
+    /// Panics if `ordering` is `Nested` and `N` is not a power of two.
+    pub fn from_pixel_index(index: u64, ordering: HealpixOrdering) -> Option<Self> {
+        if index >= (12 * N * N) as u64 {
+            return None;
+        }
+
+        Some(match ordering {
+            HealpixOrdering::Ring => Self::from_ring_index(index),
+            HealpixOrdering::Nested => {
+                assert!(N.is_power_of_two(), "The nested ordering requires N to be a power of two");
+
+                let face = index / (N * N) as u64;
+                let offset = index % (N * N) as u64;
+
+                Self::new(face as u8, compact_bits(offset), compact_bits(offset >> 1))
+            },
+        })
+    }
+
+    /// Gets the pixel number of this point in the ring ordering.
+    fn ring_index(&self) -> u64 {
+        let n = N as i64;
+        let (ring, count, shift) = self.ring();
+
+        let before = if ring < n {
+            2 * count * (count - 1)
+        } else if ring > 3 * n {
+            12 * n * n - 2 * (count + 1) * count
+        } else {
+            2 * n * (n - 1) + (ring - n) * 4 * n
+        };
+
+        let position = self.ring_position(count, shift);
+
+        (before + position - 1) as u64
+    }
+
+    /// Gets the point with a pixel number in the ring ordering.
+    ///
+    /// - `index` - The pixel number, which must be on the grid.
+    fn from_ring_index(index: u64) -> Self {
+        let n = N as i64;
+        let index = index as i64;
+        let polar = 2 * n * (n - 1);
+
+        let (ring, position, shift, count, face) = if index < polar {
+            let ring = (1 + (1 + 2 * index).isqrt()) >> 1;
+            let position = index + 1 - 2 * ring * (ring - 1);
+
+            (ring, position, 0, ring, (position - 1) / ring)
+        } else if index < 12 * n * n - polar {
+            let offset = index - polar;
+            let row = offset / (4 * n);
+            let ring = row + n;
+            let position = offset - row * 4 * n + 1;
+
+            let ascending = (position - (2 * n + 1 - row) / 2 + n - 1) / n;
+            let descending = (position - (row + 1) / 2 + n - 1) / n;
+
+            let face = match ascending.cmp(&descending) {
+                std::cmp::Ordering::Equal => ascending | 4,
+                std::cmp::Ordering::Less => ascending,
+                std::cmp::Ordering::Greater => descending + 8,
+            };
+
+            (ring, position, (ring + n) & 1, n, face)
+        } else {
+            let offset = 12 * n * n - index;
+            let ring = (1 + (2 * offset - 1).isqrt()) >> 1;
+            let position = 4 * ring + 1 - (offset - 2 * ring * (ring - 1));
+
+            (4 * n - ring, position, 0, ring, (position - 1) / ring + 8)
+        };
+
+        let face_ring = ring - FACE_RING[face as usize] * n + 1;
+        let mut face_position = 2 * position - FACE_LONGITUDE[face as usize] * count - shift - 1;
+
+        if face_position >= 2 * n {
+            face_position -= 8 * n;
+        }
+
+        let x = (face_position - face_ring) >> 1;
+        let y = (-face_position - face_ring) >> 1;
+
+        Self::new(face as u8, x as u32, y as u32)
+    }
+
+    /// Gets the ring of constant latitude that contains this point, numbered from 1 at the north
+    /// pole, along with the number of cells in each quarter of the ring and whether the cells in
+    /// the ring are shifted by half a cell.
+    fn ring(&self) -> (i64, i64, i64) {
+        let n = N as i64;
+        let ring = FACE_RING[self.face as usize] * n - self.x as i64 - self.y as i64 - 1;
+
+        if ring < n {
+            (ring, ring, 0)
+        } else if ring > 3 * n {
+            (ring, 4 * n - ring, 0)
+        } else {
+            (ring, n, (ring - n) & 1)
+        }
+    }
+
+    /// Gets the position of this point along its ring, starting from 1.
+    ///
+    /// - `count` - The number of cells in each quarter of the ring.
+    /// - `shift` - Whether the cells of the ring are shifted by half a cell.
+    fn ring_position(&self, count: i64, shift: i64) -> i64 {
+        let n = N as i64;
+        let position = (FACE_LONGITUDE[self.face as usize] * count + self.x as i64 - self.y as i64 + 1 + shift) / 2;
+
+        if position > 4 * n {
+            position - 4 * n
+        } else if position < 1 {
+            position + 4 * n
+        } else {
+            position
+        }
+    }
+
+    /// Gets the height of the centre of this point above the equator on a sphere with a radius
+    /// of 1.
+    fn height(&self) -> f64 {
+        let n = N as f64;
+        let (ring, count, _) = self.ring();
+        let ring = ring as f64;
+        let count = count as f64;
+
+        if ring < n {
+            1.0 - count * count / (3.0 * n * n)
+        } else if ring > 3.0 * n {
+            count * count / (3.0 * n * n) - 1.0
+        } else {
+            (2.0 * n - ring) * 2.0 / (3.0 * n)
+        }
+    }
+
+    /// Gets the point reached by moving one cell across a face.
+    ///
+    /// - `dx` - The step in the X direction.
+    /// - `dy` - The step in the Y direction.
+    fn step(&self, dx: i64, dy: i64) -> Self {
+        let n = N as i64;
+        let mut x = self.x as i64 + dx;
+        let mut y = self.y as i64 + dy;
+        let mut direction = 4;
+
+        if x < 0 {
+            x += n;
+            direction -= 1;
+        } else if x >= n {
+            x -= n;
+            direction += 1;
+        }
+
+        if y < 0 {
+            y += n;
+            direction -= 3;
+        } else if y >= n {
+            y -= n;
+            direction += 3;
+        }
+
+        // Every base face has a neighbour on each of its four sides.
+        let face = NEIGHBOUR_FACE[direction][self.face as usize] as u8;
+        let transform = NEIGHBOUR_TRANSFORM[direction][self.face as usize / 4];
+
+        if transform & 1 != 0 {
+            x = n - x - 1;
+        }
+
+        if transform & 2 != 0 {
+            y = n - y - 1;
+        }
+
+        if transform & 4 != 0 {
+            std::mem::swap(&mut x, &mut y);
+        }
+
+        Self::new(face, x as u32, y as u32)
+    }
+}
+
+impl <const N: usize> GridPoint for HealpixSpherePoint<N> {
+    fn up(&self) -> Self {
+        self.step(0, 1)
+    }
+
+    fn down(&self) -> Self {
+        self.step(0, -1)
+    }
+
+    fn left(&self) -> Self {
+        self.step(-1, 0)
+    }
+
+    fn right(&self) -> Self {
+        self.step(1, 0)
+    }
+
+    /// Gets the position of the centre of the cell in 3D space.
+    ///
+    /// - `scale` - The scale of the 3D object.
+    fn position(&self, scale: f64) -> (f64, f64, f64) {
+        let latitude = self.latitude();
+        let longitude = self.longitude();
+
+        (
+            scale * latitude.cos() * longitude.sin(),
+            scale * latitude.sin(),
+            scale * latitude.cos() * longitude.cos(),
+        )
+    }
+}
+
+impl <const N: usize> SpherePoint for HealpixSpherePoint<N> {
+    fn from_geographic(latitude: f64, longitude: f64) -> Self {
+        let n = N as i64;
+        let z = latitude.sin();
+        let turn = (longitude * 2.0 / PI).rem_euclid(4.0);
+
+        if z.abs() <= 2.0 / 3.0 {
+            let centre = N as f64 * (0.5 + turn);
+            let offset = N as f64 * z * 0.75;
+
+            let ascending = (centre - offset) as i64;
+            let descending = (centre + offset) as i64;
+
+            let ascending_face = ascending / n;
+            let descending_face = descending / n;
+
+            let face = match ascending_face.cmp(&descending_face) {
+                std::cmp::Ordering::Equal => ascending_face | 4,
+                std::cmp::Ordering::Less => ascending_face,
+                std::cmp::Ordering::Greater => descending_face + 8,
+            };
+
+            Self::new(face as u8, (descending % n) as u32, (n - ascending % n - 1) as u32)
+        } else {
+            let quarter = (turn as i64).min(3);
+            let along = turn - quarter as f64;
+            let size = N as f64 * (3.0 * (1.0 - z.abs())).sqrt();
+
+            let ascending = ((along * size) as i64).min(n - 1);
+            let descending = (((1.0 - along) * size) as i64).min(n - 1);
+
+            if z >= 0.0 {
+                Self::new(quarter as u8, (n - descending - 1) as u32, (n - ascending - 1) as u32)
+            } else {
+                Self::new(quarter as u8 + 8, ascending as u32, descending as u32)
+            }
+        }
+    }
+
+    fn latitude(&self) -> f64 {
+        self.height().asin()
+    }
+
+    fn longitude(&self) -> f64 {
+        let (_, count, shift) = self.ring();
+        let position = self.ring_position(count, shift) as f64;
+
+        (position - (shift + 1) as f64 * 0.5) * (PI / 2.0 / count as f64)
+    }
+}
+
+/// Spreads the bits of a value so that there is a zero bit between each of them.
+///
+/// - `value` - The value to spread.
+fn spread_bits(value: u32) -> u64 {
+    (0..32).fold(0, |result, bit| result | (((value as u64 >> bit) & 1) << (2 * bit)))
+}
+
+/// Collects every other bit of a value starting with the lowest, reversing `spread_bits`.
+///
+/// - `value` - The value to collect the bits from.
+fn compact_bits(value: u64) -> u32 {
+    (0..32).fold(0, |result, bit| result | (((value >> (2 * bit)) & 1) << bit) as u32)
+}
+
+#[cfg(test)]
+mod test {
+    use std::{collections::HashSet, f64::consts::PI};
+
+    use approx::assert_relative_eq;
+
+    use crate::{GridPoint, SurfaceGrid, sphere::SpherePoint};
+
+    use super::{HealpixOrdering, HealpixSphereGrid, HealpixSpherePoint};
+
+    #[test]
+    fn test_healpix_ring_index() {
+        let grid: HealpixSphereGrid<(), 3> = HealpixSphereGrid::default();
+
+        let indices: HashSet<_> = grid.points()
+            .map(|point| point.pixel_index(HealpixOrdering::Ring))
+            .collect();
+
+        assert_eq!(indices, (0..108).collect());
+
+        for point in grid.points() {
+            assert_eq!(HealpixSpherePoint::from_pixel_index(point.pixel_index(HealpixOrdering::Ring), HealpixOrdering::Ring), Some(point));
+        }
+
+        assert_eq!(HealpixSpherePoint::<3>::from_pixel_index(108, HealpixOrdering::Ring), None);
+
+        // Known pixel numbers from the HEALPix reference implementation.
+        for face in 0..12 {
+            assert_eq!(HealpixSpherePoint::<1>::new(face, 0, 0).pixel_index(HealpixOrdering::Ring), face as u64);
+        }
+
+        let ring_to_nested = [3, 7, 11, 15, 2, 1, 6, 5, 10, 9, 14, 13, 19, 0];
+
+        for (ring, nested) in ring_to_nested.into_iter().enumerate() {
+            let point = HealpixSpherePoint::<2>::from_pixel_index(ring as u64, HealpixOrdering::Ring).unwrap();
+
+            assert_eq!(point.pixel_index(HealpixOrdering::Nested), nested);
+        }
+    }
+
+    #[test]
+    fn test_healpix_ring_order_latitude() {
+        let grid: HealpixSphereGrid<(), 4> = HealpixSphereGrid::default();
+
+        let mut points: Vec<_> = grid.points().collect();
+        points.sort_by_key(|point| point.pixel_index(HealpixOrdering::Ring));
+
+        // Rings go from north to south and each ring goes eastwards from a longitude of 0.
+        for pair in points.windows(2) {
+            let (a, b) = (pair[0], pair[1]);
+
+            if a.latitude() == b.latitude() {
+                assert!(a.longitude() < b.longitude());
+            } else {
+                assert!(a.latitude() > b.latitude());
+            }
+        }
+
+        assert_eq!(points[0].face(), 0);
+        assert_relative_eq!(points[0].longitude(), PI / 4.0);
+    }
+
+    #[test]
+    fn test_healpix_nested_index() {
+        let grid: HealpixSphereGrid<(), 4> = HealpixSphereGrid::default();
+
+        for point in grid.points() {
+            let index = point.pixel_index(HealpixOrdering::Nested);
+
+            assert_eq!(index / 16, point.face() as u64);
+            assert_eq!(HealpixSpherePoint::from_pixel_index(index, HealpixOrdering::Nested), Some(point));
+        }
+
+        // The first four pixels of a face in the nested ordering form a square.
+        let first: Vec<_> = (0..4)
+            .map(|i| HealpixSpherePoint::<4>::from_pixel_index(i, HealpixOrdering::Nested).unwrap())
+            .collect();
+
+        assert_eq!(first[0].right(), first[1]);
+        assert_eq!(first[0].up(), first[2]);
+        assert_eq!(first[1].up(), first[3]);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_healpix_nested_index_not_power_of_two() {
+        HealpixSpherePoint::<3>::new(0, 0, 0).pixel_index(HealpixOrdering::Nested);
+    }
+
+    #[test]
+    fn test_healpix_ordered_vec() {
+        let grid: HealpixSphereGrid<usize, 2> = HealpixSphereGrid::from_index_fn(|i| i);
+
+        for ordering in [HealpixOrdering::Ring, HealpixOrdering::Nested] {
+            let values = grid.to_ordered_vec(ordering);
+
+            for point in grid.points() {
+                assert_eq!(values[point.pixel_index(ordering) as usize], grid[point]);
+            }
+
+            assert_eq!(HealpixSphereGrid::from_ordered_vec(values, ordering), Some(grid.clone()));
+        }
+
+        assert_eq!(HealpixSphereGrid::<usize, 2>::from_ordered_vec(vec![0; 47], HealpixOrdering::Ring), None);
+    }
+
+    #[test]
+    fn test_healpix_neighbours() {
+        let grid: HealpixSphereGrid<(), 4> = HealpixSphereGrid::default();
+
+        // The largest distance between the centres of neighbouring cells at this resolution.
+        let limit = 0.35;
+
+        for point in grid.points() {
+            let neighbours = [point.up(), point.down(), point.left(), point.right()];
+
+            assert_eq!(neighbours.iter().collect::<HashSet<_>>().len(), 4);
+
+            for neighbour in neighbours {
+                let (x, y, z) = point.position(1.0);
+                let (nx, ny, nz) = neighbour.position(1.0);
+
+                assert!(((x - nx).powi(2) + (y - ny).powi(2) + (z - nz).powi(2)).sqrt() < limit);
+                assert!([neighbour.up(), neighbour.down(), neighbour.left(), neighbour.right()].contains(&point));
+            }
+        }
+    }
+
+    #[test]
+    fn test_healpix_pole() {
+        let north: HashSet<_> = (0..4).map(|face| HealpixSpherePoint::<3>::new(face, 2, 2)).collect();
+
+        for point in &north {
+            assert!(north.contains(&point.up()));
+            assert!(north.contains(&point.right()));
+            assert_relative_eq!(point.latitude(), (1.0 - 1.0 / 27.0f64).asin());
+        }
+    }
+
+    #[test]
+    fn test_healpix_from_geographic() {
+        let grid: HealpixSphereGrid<(), 5> = HealpixSphereGrid::default();
+
+        for point in grid.points() {
+            assert_eq!(HealpixSpherePoint::from_geographic(point.latitude(), point.longitude()), point);
+            assert_eq!(HealpixSpherePoint::from_geographic(point.latitude(), point.longitude() - 2.0 * PI), point);
+        }
+
+        assert_eq!(HealpixSpherePoint::<5>::from_geographic(PI / 2.0, 0.0).face(), 0);
+        assert_eq!(HealpixSpherePoint::<5>::from_geographic(-PI / 2.0, 0.0).face(), 8);
+        assert_eq!(HealpixSpherePoint::<5>::from_geographic(0.0, 0.0).face(), 4);
+    }
+
+    #[test]
+    fn test_healpix_equal_area() {
+        let grid: HealpixSphereGrid<usize, 4> = HealpixSphereGrid::default();
+
+        // Sampling the sphere evenly puts the same number of samples in each cell.
+        let samples = 400;
+        let mut counts = HealpixSphereGrid::<usize, 4>::default();
+
+        for i in 0..samples {
+            for j in 0..samples {
+                let z = (i as f64 + 0.5) / samples as f64 * 2.0 - 1.0;
+                let longitude = (j as f64 + 0.5) / samples as f64 * 2.0 * PI;
+
+                counts[HealpixSpherePoint::from_geographic(z.asin(), longitude)] += 1;
+            }
+        }
+
+        let expected = (samples * samples) as f64 / 192.0;
+
+        for (_, count) in counts.iter() {
+            assert!((*count as f64 - expected).abs() < expected * 0.05);
+        }
+
+        assert_relative_eq!(grid.surface_area(2.0), 16.0 * PI);
+    }
+
+    #[test]
+    fn test_healpix_iteration_order() {
+        let grid: HealpixSphereGrid<usize, 3> = HealpixSphereGrid::from_index_fn(|i| i);
+
+        for (i, (point, value)) in grid.iter().enumerate() {
+            assert_eq!(*value, i);
+            assert_eq!(grid.to_index(&point), i);
+        }
+
+        assert_eq!(grid.par_map_to_vec(|_, value| *value), grid.to_vec());
+        assert_eq!(grid.clone().into_iter().map(|(_, value)| value).collect::<Vec<_>>(), grid.to_vec());
+        assert_eq!(HealpixSphereGrid::from_vec(grid.to_vec()), Some(grid));
+    }
+}
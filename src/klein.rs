@@ -0,0 +1,478 @@
+//! A module containing grids wrapped around the surface of a Klein bottle.
+
+use std::{f64::consts::PI, ops::{Index, IndexMut}, vec};
+
+use itertools::Itertools;
+use rayon::prelude::*;
+use static_array::HeapArray2D;
+
+use crate::{Direction, GridPoint, OutOfBounds, Surface, SurfaceGrid, Topology};
+
+assert_send_sync!([T: Send + Sync, const W: usize, const H: usize]
+    KleinBottleGrid<T, W, H>,
+    KleinBottlePoint<W, H>,
+);
+
+/// A rectangular grid wrapped around a Klein bottle.
+///
+/// The left and right edges of the grid are joined like a cylinder while the top and bottom edges
+/// are joined with the X axis reversed, so moving up from the top row at `x` reaches the bottom
+/// row at `W - 1 - x`. This makes the surface closed but non-orientable: a pattern moved down the
+/// full height of the grid comes back mirrored.
+///
+/// # Type Parameters
+/// - `T` - The type of data that the grid holds.
+///
+/// # Constant Parameters
+/// - `W` - The width of the grid.
+/// - `H` - The height of the grid.
+///
+/// # Iteration Order
+/// `iter`, `points` and `into_iter` all visit the grid in row-major order, starting at `(0, 0)`
+/// and increasing `x` before `y`. This order is stable.
+///
+/// # Thread Safety
+/// The cells are kept in a single `HeapArray2D` and the twist only changes how points move between
+/// them, so the grid is `Send` when `T` is `Send` and `Sync` when `T` is `Sync`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Default)]
+pub struct KleinBottleGrid<T, const W: usize, const H: usize> {
+    /// The data held in this grid.
+    data: HeapArray2D<T, W, H>,
+}
+
+impl <T, const W: usize, const H: usize> KleinBottleGrid<T, W, H> {
+    /// Gets the values of the cells in the same order as the points of the grid.
+    pub fn as_slice(&self) -> &[T] {
+        self.data.as_flattened()
+    }
+
+    /// Gets mutable references to the values of the cells in the same order as the points of the
+    /// grid.
+    pub fn as_mut_slice(&mut self) -> &mut [T] {
+        self.data.as_flattened_mut()
+    }
+
+    /// Gets the values of the cells as bytes in the same order as `as_slice`.
+    #[cfg(feature = "bytemuck")]
+    pub fn as_bytes(&self) -> &[u8] where T: bytemuck::Pod {
+        bytemuck::cast_slice(self.as_slice())
+    }
+
+    /// Gets the values of the cells as mutable bytes in the same order as `as_slice`.
+    #[cfg(feature = "bytemuck")]
+    pub fn as_bytes_mut(&mut self) -> &mut [u8] where T: bytemuck::Pod {
+        bytemuck::cast_slice_mut(self.as_mut_slice())
+    }
+}
+
+impl <T, const W: usize, const H: usize> SurfaceGrid<T> for KleinBottleGrid<T, W, H> {
+    type Point = KleinBottlePoint<W, H>;
+
+    row_major_grid_methods!(KleinBottlePoint);
+
+    /// Gets the surface area of the immersion used by `GridPoint::position`.
+    ///
+    /// As the immersion has no simple formula for its area it is integrated numerically.
+    fn surface_area(&self, scale: f64) -> f64 {
+        const STEPS: usize = 128;
+
+        let step = PI * 2.0 / STEPS as f64;
+
+        (0..STEPS).cartesian_product(0..STEPS)
+            .map(|(i, j)| {
+                let (dt, dv) = immersion_derivatives((i as f64 + 0.5) * step, (j as f64 + 0.5) * step, scale);
+
+                let (x, y, z) = (
+                    dt.1 * dv.2 - dt.2 * dv.1,
+                    dt.2 * dv.0 - dt.0 * dv.2,
+                    dt.0 * dv.1 - dt.1 * dv.0,
+                );
+
+                (x * x + y * y + z * z).sqrt() * step * step
+            })
+            .sum()
+    }
+
+    fn topology(&self) -> Topology {
+        Topology::uniform(Surface::KleinBottle, 1, W, H)
+    }
+}
+
+impl <T, const W: usize, const H: usize> Index<KleinBottlePoint<W, H>> for KleinBottleGrid<T, W, H> {
+    type Output = T;
+
+    fn index(&self, index: KleinBottlePoint<W, H>) -> &Self::Output {
+        &self.data[index.y as usize][index.x as usize]
+    }
+}
+
+impl <T, const W: usize, const H: usize> IndexMut<KleinBottlePoint<W, H>> for KleinBottleGrid<T, W, H> {
+    fn index_mut(&mut self, index: KleinBottlePoint<W, H>) -> &mut Self::Output {
+        &mut self.data[index.y as usize][index.x as usize]
+    }
+}
+
+impl <T, const W: usize, const H: usize> Index<&KleinBottlePoint<W, H>> for KleinBottleGrid<T, W, H> {
+    type Output = T;
+
+    fn index(&self, index: &KleinBottlePoint<W, H>) -> &Self::Output {
+        &self[*index]
+    }
+}
+
+impl <T, const W: usize, const H: usize> IndexMut<&KleinBottlePoint<W, H>> for KleinBottleGrid<T, W, H> {
+    fn index_mut(&mut self, index: &KleinBottlePoint<W, H>) -> &mut Self::Output {
+        &mut self[*index]
+    }
+}
+
+impl <T, const W: usize, const H: usize> FromIterator<T> for KleinBottleGrid<T, W, H> {
+    /// Creates a grid from the values of its cells in the same order as `points`.
+    ///
+    /// # Panics
+    /// Panics if the iterator does not yield exactly one value for each cell in the grid.
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        Self::from_vec(iter.into_iter().collect())
+            .expect("the iterator must yield one value for each cell in the grid")
+    }
+}
+
+impl <T, const W: usize, const H: usize> IntoIterator for KleinBottleGrid<T, W, H> {
+    type Item = (KleinBottlePoint<W, H>, T);
+
+    type IntoIter = vec::IntoIter<Self::Item>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        let data: Vec<_> = self.data.into_iter()
+            .enumerate()
+            .flat_map(|(y, subarray)| subarray.into_iter()
+                      .enumerate()
+                      .map(move |(x, value)| (KleinBottlePoint::new(x as u32, y as u32), value))
+                      )
+            .collect();
+
+        data.into_iter()
+    }
+}
+
+#[cfg(feature = "serde")]
+impl <T: serde::Serialize, const W: usize, const H: usize> serde::Serialize for KleinBottleGrid<T, W, H> {
+    /// Serializes the values in this grid as a sequence in the same order as `iter`.
+    fn serialize<R: serde::Serializer>(&self, serializer: R) -> Result<R::Ok, R::Error> {
+        serializer.collect_seq(self.iter().map(|(_, value)| value))
+    }
+}
+
+#[cfg(feature = "serde")]
+impl <'de, T: serde::Deserialize<'de>, const W: usize, const H: usize> serde::Deserialize<'de> for KleinBottleGrid<T, W, H> {
+    /// Deserializes a grid from a sequence of values in the same order as `iter`.
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let values = Vec::<T>::deserialize(deserializer)?;
+        let length = values.len();
+
+        Self::from_vec(values)
+            .ok_or_else(|| serde::de::Error::invalid_length(length, &format!("a sequence of {} values", W * H).as_str()))
+    }
+}
+
+/// A point on a `KleinBottleGrid`.
+///
+/// Moving left or right off the grid continues from the opposite edge in the same row. Moving up
+/// or down off the grid continues from the opposite edge with the X axis reversed.
+///
+/// # Constant Parameters
+/// - `W` - The width of the grid.
+/// - `H` - The height of the grid.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct KleinBottlePoint<const W: usize, const H: usize> {
+    x: u32,
+    y: u32,
+}
+
+impl <const W: usize, const H: usize> KleinBottlePoint<W, H> {
+    /// Creates a new point.
+    ///
+    /// - `x` - The X coordinate.
+    /// - `y` - The Y coordinate.
+    fn new(x: u32, y: u32) -> Self {
+        Self {
+            x, y
+        }
+    }
+
+    /// Creates a new `KleinBottlePoint` checking that the position lies on the grid.
+    ///
+    /// This returns an error if `x` is `W` or greater or `y` is `H` or greater.
+    ///
+    /// - `x` - The X coordinate.
+    /// - `y` - The Y coordinate.
+    pub fn try_new(x: u32, y: u32) -> Result<Self, OutOfBounds> {
+        if (x as usize) < W && (y as usize) < H {
+            Ok(Self::new(x, y))
+        } else {
+            Err(OutOfBounds)
+        }
+    }
+
+    /// Gets the X coordinate reached by crossing the top or bottom edge of the grid.
+    fn mirrored_x(&self) -> u32 {
+        W as u32 - 1 - self.x
+    }
+}
+
+impl <const W: usize, const H: usize> GridPoint for KleinBottlePoint<W, H> {
+    fn up(&self) -> Self {
+        if self.y == 0 {
+            Self::new(self.mirrored_x(), H as u32 - 1)
+        } else {
+            Self::new(self.x, self.y - 1)
+        }
+    }
+
+    fn down(&self) -> Self {
+        if self.y == H as u32 - 1 {
+            Self::new(self.mirrored_x(), 0)
+        } else {
+            Self::new(self.x, self.y + 1)
+        }
+    }
+
+    fn left(&self) -> Self {
+        if self.x == 0 {
+            Self::new(W as u32 - 1, self.y)
+        } else {
+            Self::new(self.x - 1, self.y)
+        }
+    }
+
+    fn right(&self) -> Self {
+        if self.x == W as u32 - 1 {
+            Self::new(0, self.y)
+        } else {
+            Self::new(self.x + 1, self.y)
+        }
+    }
+
+    fn neighbour_flips_orientation(&self, dir: Direction) -> bool {
+        match dir {
+            Direction::Up => self.y == 0,
+            Direction::Down => self.y == H as u32 - 1,
+            Direction::Left | Direction::Right => false,
+        }
+    }
+
+    /// Gets the position of the point in 3D space.
+    ///
+    /// A Klein bottle cannot be placed in 3D space without passing through itself so this uses the
+    /// figure-8 immersion. The Y axis of the grid goes around a circle with a radius of `scale`
+    /// about the Y axis in 3D space and the X axis goes around a figure-8 shaped cross-section with
+    /// a size of half of `scale` that turns over once on the way around. This is the position of
+    /// the corner of the cell with the smallest X and Y coordinates.
+    ///
+    /// - `scale` - The scale of the 3D object.
+    fn position(&self, scale: f64) -> (f64, f64, f64) {
+        let around = self.y as f64 / H as f64 * PI * 2.0;
+        let section = self.x as f64 / W as f64 * PI * 2.0;
+
+        immersion(around, section, scale)
+    }
+}
+
+/// Gets a position on the figure-8 immersion of a Klein bottle.
+///
+/// - `around` - The angle around the circle through the middle of the immersion.
+/// - `section` - The angle around the figure-8 cross-section.
+/// - `scale` - The radius of the circle through the middle of the immersion.
+fn immersion(around: f64, section: f64, scale: f64) -> (f64, f64, f64) {
+    let size = scale / 2.0;
+    let (sin_half, cos_half) = (around / 2.0).sin_cos();
+
+    let offset = cos_half * section.sin() - sin_half * (2.0 * section).sin();
+    let height = sin_half * section.sin() + cos_half * (2.0 * section).sin();
+
+    let distance = scale + size * offset;
+
+    (distance * around.cos(), size * height, -distance * around.sin())
+}
+
+/// Gets the partial derivatives of `immersion` with respect to both of its angles.
+///
+/// - `around` - The angle around the circle through the middle of the immersion.
+/// - `section` - The angle around the figure-8 cross-section.
+/// - `scale` - The radius of the circle through the middle of the immersion.
+#[allow(clippy::type_complexity)]
+fn immersion_derivatives(around: f64, section: f64, scale: f64) -> ((f64, f64, f64), (f64, f64, f64)) {
+    let size = scale / 2.0;
+    let (sin_half, cos_half) = (around / 2.0).sin_cos();
+    let (sin, cos) = around.sin_cos();
+
+    let offset = cos_half * section.sin() - sin_half * (2.0 * section).sin();
+    let distance = scale + size * offset;
+
+    // Derivatives with respect to the angle around the middle.
+    let offset_around = -(sin_half * section.sin() + cos_half * (2.0 * section).sin()) / 2.0;
+    let height_around = (cos_half * section.sin() - sin_half * (2.0 * section).sin()) / 2.0;
+    let distance_around = size * offset_around;
+
+    // Derivatives with respect to the angle around the cross-section.
+    let offset_section = cos_half * section.cos() - 2.0 * sin_half * (2.0 * section).cos();
+    let height_section = sin_half * section.cos() + 2.0 * cos_half * (2.0 * section).cos();
+    let distance_section = size * offset_section;
+
+    (
+        (distance_around * cos - distance * sin, size * height_around, -distance_around * sin - distance * cos),
+        (distance_section * cos, size * height_section, -distance_section * sin),
+    )
+}
+
+#[cfg(test)]
+mod test {
+    use approx::assert_relative_eq;
+
+    use crate::{Direction, GridPoint, SurfaceGrid};
+
+    use super::{KleinBottleGrid, KleinBottlePoint, immersion, immersion_derivatives};
+
+    #[test]
+    fn test_klein_bottle_left_right() {
+        let point: KleinBottlePoint<10, 5> = KleinBottlePoint::new(3, 2);
+
+        assert_eq!(point.left(), KleinBottlePoint::new(2, 2));
+        assert_eq!(point.right(), KleinBottlePoint::new(4, 2));
+        assert_eq!(KleinBottlePoint::<10, 5>::new(0, 2).left(), KleinBottlePoint::new(9, 2));
+        assert_eq!(KleinBottlePoint::<10, 5>::new(9, 2).right(), KleinBottlePoint::new(0, 2));
+    }
+
+    #[test]
+    fn test_klein_bottle_up_down() {
+        let point: KleinBottlePoint<10, 5> = KleinBottlePoint::new(3, 2);
+
+        assert_eq!(point.up(), KleinBottlePoint::new(3, 1));
+        assert_eq!(point.down(), KleinBottlePoint::new(3, 3));
+        assert_eq!(KleinBottlePoint::<10, 5>::new(3, 0).up(), KleinBottlePoint::new(6, 4));
+        assert_eq!(KleinBottlePoint::<10, 5>::new(3, 4).down(), KleinBottlePoint::new(6, 0));
+    }
+
+    #[test]
+    fn test_klein_bottle_loops() {
+        let grid: KleinBottleGrid<(), 10, 5> = KleinBottleGrid::default();
+
+        for point in grid.points() {
+            assert_eq!(point.up().down(), point);
+            assert_eq!(point.left().right(), point);
+            assert_eq!((0..10).fold(point, |point, _| point.right()), point);
+
+            // Going around the bottle once mirrors the point and going around twice restores it.
+            let once = (0..5).fold(point, |point, _| point.down());
+
+            assert_eq!(once, KleinBottlePoint::new(9 - point.x, point.y));
+            assert_eq!((0..5).fold(once, |point, _| point.down()), point);
+        }
+    }
+
+    #[test]
+    fn test_klein_bottle_non_orientable() {
+        // Walking down the full height of the grid turns right into left.
+        let point: KleinBottlePoint<10, 5> = KleinBottlePoint::new(2, 1);
+
+        let moved = (0..5).fold(point.right(), |point, _| point.down());
+        let start = (0..5).fold(point, |point, _| point.down());
+
+        assert_eq!(moved, start.left());
+    }
+
+    #[test]
+    fn test_klein_bottle_flips_orientation() {
+        let grid: KleinBottleGrid<(), 10, 5> = KleinBottleGrid::default();
+
+        for point in grid.points() {
+            assert_eq!(point.neighbour_flips_orientation(Direction::Up), point.y == 0);
+            assert_eq!(point.neighbour_flips_orientation(Direction::Down), point.y == 4);
+            assert!(!point.neighbour_flips_orientation(Direction::Left));
+            assert!(!point.neighbour_flips_orientation(Direction::Right));
+        }
+    }
+
+    #[test]
+    fn test_klein_bottle_iteration_order() {
+        let grid: KleinBottleGrid<(u32, u32), 3, 2> = KleinBottleGrid::from_fn(|point| (point.x, point.y));
+
+        let expected = [(0, 0), (1, 0), (2, 0), (0, 1), (1, 1), (2, 1)];
+
+        let points: Vec<_> = grid.points().map(|point| (point.x, point.y)).collect();
+        let iter: Vec<_> = grid.iter().map(|(_, value)| *value).collect();
+        let par_map: Vec<_> = grid.par_map_to_vec(|_, value| *value);
+        let into_iter: Vec<_> = grid.into_iter().map(|(_, value)| value).collect();
+
+        assert_eq!(points, expected);
+        assert_eq!(iter, expected);
+        assert_eq!(par_map, expected);
+        assert_eq!(into_iter, expected);
+    }
+
+    #[test]
+    fn test_klein_bottle_to_index() {
+        let grid: KleinBottleGrid<(), 10, 5> = KleinBottleGrid::default();
+
+        for (i, point) in grid.points().enumerate() {
+            assert_eq!(grid.to_index(&point), i);
+        }
+    }
+
+    #[test]
+    fn test_klein_bottle_position() {
+        // Crossing the top and bottom edges mirrors the cross-section.
+        for i in 0..10 {
+            let section = i as f64 * 0.6;
+
+            let (ax, ay, az) = immersion(0.0, section, 1.0);
+            let (bx, by, bz) = immersion(std::f64::consts::PI * 2.0, -section, 1.0);
+
+            assert_relative_eq!(ax, bx, epsilon = 1e-12);
+            assert_relative_eq!(ay, by, epsilon = 1e-12);
+            assert_relative_eq!(az, bz, epsilon = 1e-12);
+        }
+    }
+
+    #[test]
+    fn test_klein_bottle_derivatives() {
+        let step = 1e-6;
+
+        for i in 0..10 {
+            let (around, section) = (i as f64 * 0.7, i as f64 * 1.3);
+
+            let (dt, dv) = immersion_derivatives(around, section, 2.0);
+
+            let (ax, ay, az) = immersion(around + step, section, 2.0);
+            let (bx, by, bz) = immersion(around - step, section, 2.0);
+
+            assert_relative_eq!(dt.0, (ax - bx) / (2.0 * step), epsilon = 1e-6);
+            assert_relative_eq!(dt.1, (ay - by) / (2.0 * step), epsilon = 1e-6);
+            assert_relative_eq!(dt.2, (az - bz) / (2.0 * step), epsilon = 1e-6);
+
+            let (ax, ay, az) = immersion(around, section + step, 2.0);
+            let (bx, by, bz) = immersion(around, section - step, 2.0);
+
+            assert_relative_eq!(dv.0, (ax - bx) / (2.0 * step), epsilon = 1e-6);
+            assert_relative_eq!(dv.1, (ay - by) / (2.0 * step), epsilon = 1e-6);
+            assert_relative_eq!(dv.2, (az - bz) / (2.0 * step), epsilon = 1e-6);
+        }
+    }
+
+    #[test]
+    fn test_klein_bottle_surface_area() {
+        let grid: KleinBottleGrid<(), 10, 5> = KleinBottleGrid::default();
+
+        assert_relative_eq!(grid.surface_area(1.0), 29.92996554, epsilon = 1e-6);
+        assert_relative_eq!(grid.surface_area(2.0), 4.0 * grid.surface_area(1.0), epsilon = 1e-9);
+    }
+
+    #[test]
+    fn test_klein_offset() {
+        // Crossing the reversed edges keeps the offset on the same side of the line of movement.
+        for point in KleinBottleGrid::<(), 6, 5>::default().points() {
+            assert_eq!(point.right().up(), point.offset(1, -1));
+            assert_eq!(point.left().down(), point.offset(-1, 1));
+        }
+    }
+}
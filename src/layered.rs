@@ -0,0 +1,345 @@
+//! A module containing grids that store several fields for each cell in separate layers.
+
+use std::{any::Any, collections::BTreeMap, fmt::{self, Debug}, ops::{Index, IndexMut}};
+
+use crate::SurfaceGrid;
+
+assert_send_sync!([G: SurfaceGrid<()> + Send + Sync]
+    LayeredGrid<G>,
+);
+
+/// A grid that stores several fields for each cell, such as temperature and moisture, in a
+/// separate array for each field.
+///
+/// The shape of the grid is given by a grid of `()`, such as `CubeSphereGrid<(), 64>`, which
+/// takes up no memory and provides the points and their order for every layer. Each layer is
+/// named and can hold a different type, and is accessed by name and type with `layer` and
+/// `layer_mut`. Storing the fields separately means that a pass over one field only reads that
+/// field, which is faster than storing a large struct for each cell.
+///
+/// # Type Parameters
+/// - `G` - The grid of `()` that gives the shape of the grid.
+///
+/// # Iteration Order
+/// The values of each layer are in the same order as the points of the grid given by `G`.
+///
+/// # Thread Safety
+/// Layers must be `Send` and `Sync` so the grid is `Send` when `G` is `Send` and `Sync` when `G`
+/// is `Sync`.
+pub struct LayeredGrid<G: SurfaceGrid<()>> {
+    /// The values of each layer, keyed by name. Each value is a `Vec` of the type of the layer.
+    layers: BTreeMap<String, Box<dyn Any + Send + Sync>>,
+    /// The grid that gives the shape of this grid.
+    layout: G,
+}
+
+impl <G: SurfaceGrid<()> + Default> LayeredGrid<G> {
+    /// Creates a new grid with no layers.
+    pub fn new() -> Self {
+        Self {
+            layers: BTreeMap::new(),
+            layout: G::default(),
+        }
+    }
+}
+
+impl <G: SurfaceGrid<()>> LayeredGrid<G> {
+    /// Adds a layer to the grid, replacing any layer with the same name.
+    ///
+    /// - `name` - The name of the layer.
+    /// - `f` - A function that gives the value of the layer for each cell.
+    pub fn add_layer<T: Send + Sync + 'static, F: FnMut(&G::Point) -> T>(&mut self, name: impl Into<String>, mut f: F) {
+        let values: Vec<T> = self.layout.points()
+            .map(|point| f(&point))
+            .collect();
+
+        self.layers.insert(name.into(), Box::new(values));
+    }
+
+    /// Removes a layer from the grid.
+    ///
+    /// This returns the values of the layer in the same order as the points of the grid, or `None`
+    /// if there is no layer with the name and type.
+    ///
+    /// - `name` - The name of the layer.
+    pub fn remove_layer<T: 'static>(&mut self, name: &str) -> Option<Vec<T>> {
+        if !self.layers.get(name)?.is::<Vec<T>>() {
+            return None;
+        }
+
+        self.layers.remove(name)
+            .and_then(|values| values.downcast().ok())
+            .map(|values| *values)
+    }
+
+    /// Checks if the grid has a layer with a name.
+    ///
+    /// - `name` - The name of the layer.
+    pub fn has_layer(&self, name: &str) -> bool {
+        self.layers.contains_key(name)
+    }
+
+    /// Iterates over the names of the layers in alphabetical order.
+    pub fn layer_names(&self) -> impl Iterator<Item = &str> {
+        self.layers.keys()
+            .map(String::as_str)
+    }
+
+    /// Gets a layer of the grid.
+    ///
+    /// This returns `None` if there is no layer with the name or if the layer holds a different
+    /// type.
+    ///
+    /// - `name` - The name of the layer.
+    pub fn layer<T: 'static>(&self, name: &str) -> Option<Layer<'_, T, G>> {
+        let values = self.layers.get(name)?.downcast_ref::<Vec<T>>()?;
+
+        Some(Layer {
+            values,
+            layout: &self.layout,
+        })
+    }
+
+    /// Gets a mutable layer of the grid.
+    ///
+    /// This returns `None` if there is no layer with the name or if the layer holds a different
+    /// type.
+    ///
+    /// - `name` - The name of the layer.
+    pub fn layer_mut<T: 'static>(&mut self, name: &str) -> Option<LayerMut<'_, T, G>> {
+        let values = self.layers.get_mut(name)?.downcast_mut::<Vec<T>>()?;
+
+        Some(LayerMut {
+            values,
+            layout: &self.layout,
+        })
+    }
+
+    /// Gets a layer to read from and a different layer to write to at the same time.
+    ///
+    /// This returns `None` if either layer is missing or holds a different type.
+    ///
+    /// - `read` - The name of the layer to read from.
+    /// - `write` - The name of the layer to write to.
+    ///
+    /// # Panics
+    /// Panics if both names are the same.
+    pub fn layer_pair_mut<A: 'static, B: 'static>(&mut self, read: &str, write: &str) -> Option<(Layer<'_, A, G>, LayerMut<'_, B, G>)> {
+        assert_ne!(read, write, "a layer cannot be read from and written to at the same time");
+
+        let mut read_values = None;
+        let mut write_values = None;
+
+        for (name, values) in self.layers.iter_mut() {
+            if name == read {
+                read_values = values.downcast_ref::<Vec<A>>();
+            } else if name == write {
+                write_values = values.downcast_mut::<Vec<B>>();
+            }
+        }
+
+        Some((
+            Layer {
+                values: read_values?,
+                layout: &self.layout,
+            },
+            LayerMut {
+                values: write_values?,
+                layout: &self.layout,
+            },
+        ))
+    }
+
+    /// Gets the grid that gives the shape of this grid.
+    pub fn layout(&self) -> &G {
+        &self.layout
+    }
+}
+
+impl <G: SurfaceGrid<()> + Default> Default for LayeredGrid<G> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl <G: SurfaceGrid<()> + Debug> Debug for LayeredGrid<G> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("LayeredGrid")
+            .field("layers", &self.layers.keys().collect::<Vec<_>>())
+            .field("layout", &self.layout)
+            .finish()
+    }
+}
+
+/// A layer of a `LayeredGrid` that can be read.
+///
+/// # Type Parameters
+/// - `T` - The type of data that the layer holds.
+/// - `G` - The grid of `()` that gives the shape of the grid.
+pub struct Layer<'a, T, G: SurfaceGrid<()>> {
+    values: &'a [T],
+    layout: &'a G,
+}
+
+impl <'a, T, G: SurfaceGrid<()>> Layer<'a, T, G> {
+    /// Iterates over the points of the grid along with the values of this layer.
+    pub fn iter(&self) -> impl ExactSizeIterator<Item = (G::Point, &'a T)> + DoubleEndedIterator + '_ {
+        self.layout.points()
+            .zip(self.values.iter())
+    }
+
+    /// Gets the values of this layer in the same order as the points of the grid.
+    pub fn as_slice(&self) -> &'a [T] {
+        self.values
+    }
+}
+
+impl <T, G: SurfaceGrid<()>> Clone for Layer<'_, T, G> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl <T, G: SurfaceGrid<()>> Copy for Layer<'_, T, G> {}
+
+impl <T, G: SurfaceGrid<()>> Index<G::Point> for Layer<'_, T, G> {
+    type Output = T;
+
+    fn index(&self, index: G::Point) -> &Self::Output {
+        &self.values[self.layout.to_index(&index)]
+    }
+}
+
+/// A layer of a `LayeredGrid` that can be written to.
+///
+/// # Type Parameters
+/// - `T` - The type of data that the layer holds.
+/// - `G` - The grid of `()` that gives the shape of the grid.
+pub struct LayerMut<'a, T, G: SurfaceGrid<()>> {
+    values: &'a mut [T],
+    layout: &'a G,
+}
+
+impl <T, G: SurfaceGrid<()>> LayerMut<'_, T, G> {
+    /// Iterates over the points of the grid along with the values of this layer.
+    pub fn iter(&self) -> impl ExactSizeIterator<Item = (G::Point, &T)> + DoubleEndedIterator {
+        self.layout.points()
+            .zip(self.values.iter())
+    }
+
+    /// Iterates over the points of the grid along with mutable references to the values of this
+    /// layer.
+    pub fn iter_mut(&mut self) -> impl ExactSizeIterator<Item = (G::Point, &mut T)> + DoubleEndedIterator {
+        self.layout.points()
+            .zip(self.values.iter_mut())
+    }
+
+    /// Sets the value of every cell of this layer.
+    ///
+    /// - `f` - A function that gives the new value of each cell.
+    pub fn set_from_fn<F: FnMut(&G::Point) -> T>(&mut self, mut f: F) {
+        for (point, value) in self.iter_mut() {
+            *value = f(&point);
+        }
+    }
+
+    /// Gets the values of this layer in the same order as the points of the grid.
+    pub fn as_slice(&self) -> &[T] {
+        self.values
+    }
+
+    /// Gets mutable references to the values of this layer in the same order as the points of
+    /// the grid.
+    pub fn as_mut_slice(&mut self) -> &mut [T] {
+        self.values
+    }
+}
+
+impl <T, G: SurfaceGrid<()>> Index<G::Point> for LayerMut<'_, T, G> {
+    type Output = T;
+
+    fn index(&self, index: G::Point) -> &Self::Output {
+        &self.values[self.layout.to_index(&index)]
+    }
+}
+
+impl <T, G: SurfaceGrid<()>> IndexMut<G::Point> for LayerMut<'_, T, G> {
+    fn index_mut(&mut self, index: G::Point) -> &mut Self::Output {
+        &mut self.values[self.layout.to_index(&index)]
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{GridPoint, SurfaceGrid, sphere::{CubeSphereGrid, CubeSpherePoint}};
+
+    use super::LayeredGrid;
+
+    #[test]
+    fn test_layered_typed_access() {
+        let mut grid: LayeredGrid<CubeSphereGrid<(), 4>> = LayeredGrid::new();
+
+        grid.add_layer("temp", |point: &CubeSpherePoint<4>| point.position(1.0).1 as f32);
+        grid.add_layer("land", |_: &CubeSpherePoint<4>| false);
+
+        assert_eq!(vec!["land", "temp"], grid.layer_names().collect::<Vec<_>>());
+        assert!(grid.layer::<f32>("temp").is_some());
+        assert!(grid.layer::<f64>("temp").is_none());
+        assert!(grid.layer::<bool>("moisture").is_none());
+
+        let temp = grid.layer::<f32>("temp").unwrap();
+
+        for (point, value) in temp.iter() {
+            assert_eq!(point.position(1.0).1 as f32, *value);
+            assert_eq!(*value, temp[point]);
+        }
+    }
+
+    #[test]
+    fn test_layered_layer_mut() {
+        let mut grid: LayeredGrid<CubeSphereGrid<(), 4>> = LayeredGrid::new();
+
+        grid.add_layer("height", |_: &CubeSpherePoint<4>| 0u32);
+
+        let point = grid.layout().points().nth(7).unwrap();
+
+        let mut height = grid.layer_mut::<u32>("height").unwrap();
+        height[point] = 5;
+        height.set_from_fn(|other| if *other == point { 6 } else { 1 });
+
+        let height = grid.layer::<u32>("height").unwrap();
+
+        assert_eq!(6, height[point]);
+        assert_eq!(6 * 4 * 4 + 5, height.as_slice().iter().sum::<u32>());
+    }
+
+    #[test]
+    fn test_layered_layer_pair_mut() {
+        let mut grid: LayeredGrid<CubeSphereGrid<(), 4>> = LayeredGrid::new();
+
+        grid.add_layer("a", |point: &CubeSpherePoint<4>| point.position(1.0).0);
+        grid.add_layer("b", |_: &CubeSpherePoint<4>| 0.0f64);
+
+        assert!(grid.layer_pair_mut::<f64, u8>("a", "b").is_none());
+
+        let (a, mut b) = grid.layer_pair_mut::<f64, f64>("a", "b").unwrap();
+
+        b.set_from_fn(|point| a[point.up()] * 2.0);
+
+        let b = grid.layer::<f64>("b").unwrap();
+
+        assert!(b.iter().all(|(point, value)| *value == point.up().position(1.0).0 * 2.0));
+    }
+
+    #[test]
+    fn test_layered_remove_layer() {
+        let mut grid: LayeredGrid<CubeSphereGrid<(), 2>> = LayeredGrid::new();
+
+        grid.add_layer("a", |_: &CubeSpherePoint<2>| 3u8);
+
+        assert!(grid.remove_layer::<u16>("a").is_none());
+        assert!(grid.has_layer("a"));
+        assert_eq!(Some(vec![3u8; 24]), grid.remove_layer::<u8>("a"));
+        assert!(!grid.has_layer("a"));
+    }
+}
@@ -0,0 +1,226 @@
+//! A module containing grids that only compute the values of their cells when they are used.
+
+use std::{fmt::{self, Debug}, ops::{Index, IndexMut}, sync::OnceLock};
+
+use rayon::prelude::*;
+
+use crate::SurfaceGrid;
+
+assert_send_sync!([T: Send + Sync, G: SurfaceGrid<()> + Send + Sync, F: Fn(&G::Point) -> T + Send + Sync]
+    LazyGrid<T, G, F>,
+);
+
+/// A grid that computes the value of each cell with a function the first time that the cell is
+/// used and then keeps it.
+///
+/// The shape of the grid is given by a grid of `()`, such as `CubeSphereGrid<(), 1024>`, which
+/// takes up no memory and provides the points and their order. This suits values that are
+/// expensive to generate, such as fractal noise for the terrain of a planet, when only some of
+/// the cells are read, as the cells that are never read are never computed.
+///
+/// Cells are computed when the grid is indexed, even through a shared reference, so the function
+/// must give the same value for a point every time it is called. A cell that has been set through
+/// `IndexMut` keeps its new value until it is reset with `reset` or `reset_all`.
+///
+/// # Type Parameters
+/// - `T` - The type of data that the grid holds.
+/// - `G` - The grid of `()` that gives the shape of the grid.
+/// - `F` - The type of the function that computes the value of each cell.
+///
+/// # Iteration Order
+/// `iter` and `iter_computed` visit the cells in the same order as the grid given by `G`.
+///
+/// # Thread Safety
+/// The grid is `Send` when `T`, `G` and `F` are `Send` and `Sync` when they are `Send` and
+/// `Sync`. A cell that is read by several threads at once is only computed once.
+pub struct LazyGrid<T, G: SurfaceGrid<()>, F: Fn(&G::Point) -> T> {
+    /// The values of the cells that have been computed in the order of the points of the grid.
+    cells: Vec<OnceLock<T>>,
+    /// The function that computes the value of a cell.
+    generator: F,
+    /// The grid that gives the shape of this grid.
+    layout: G,
+}
+
+impl <T, G: SurfaceGrid<()> + Default, F: Fn(&G::Point) -> T> LazyGrid<T, G, F> {
+    /// Creates a new grid where no cells have been computed.
+    ///
+    /// - `generator` - The function that computes the value of a cell.
+    pub fn new(generator: F) -> Self {
+        let layout = G::default();
+
+        Self {
+            cells: layout.points().map(|_| OnceLock::new()).collect(),
+            generator,
+            layout,
+        }
+    }
+}
+
+impl <T, G: SurfaceGrid<()>, F: Fn(&G::Point) -> T> LazyGrid<T, G, F> {
+    /// Gets the value of a cell if it has been computed without computing it.
+    ///
+    /// - `point` - The cell to get.
+    pub fn get_computed(&self, point: &G::Point) -> Option<&T> {
+        self.cells[self.layout.to_index(point)].get()
+    }
+
+    /// Checks if the value of a cell has been computed.
+    ///
+    /// - `point` - The cell to check.
+    pub fn is_computed(&self, point: &G::Point) -> bool {
+        self.get_computed(point).is_some()
+    }
+
+    /// Gets the number of cells that have been computed.
+    pub fn computed_count(&self) -> usize {
+        self.cells.iter()
+            .filter(|cell| cell.get().is_some())
+            .count()
+    }
+
+    /// Iterates over the cells that have been computed without computing any more.
+    pub fn iter_computed(&self) -> impl Iterator<Item = (G::Point, &T)> {
+        self.layout.points()
+            .zip(&self.cells)
+            .filter_map(|(point, cell)| cell.get().map(|value| (point, value)))
+    }
+
+    /// Iterates over every cell, computing the cells that have not been computed.
+    pub fn iter(&self) -> impl ExactSizeIterator<Item = (G::Point, &T)> + DoubleEndedIterator {
+        self.layout.points()
+            .zip(&self.cells)
+            .map(|(point, cell)| {
+                let value = cell.get_or_init(|| (self.generator)(&point));
+
+                (point, value)
+            })
+    }
+
+    /// Computes every cell that has not been computed in parallel.
+    pub fn compute_all_par(&self) where T: Send + Sync, F: Sync, G: Sync, G::Point: Sync {
+        self.layout.par_points()
+            .for_each(|point| {
+                self.cells[self.layout.to_index(&point)].get_or_init(|| (self.generator)(&point));
+            });
+    }
+
+    /// Forgets the value of a cell so that it is computed again the next time it is used.
+    ///
+    /// This returns the value of the cell if it had been computed.
+    ///
+    /// - `point` - The cell to reset.
+    pub fn reset(&mut self, point: &G::Point) -> Option<T> {
+        self.cells[self.layout.to_index(point)].take()
+    }
+
+    /// Forgets the values of every cell so that they are computed again when they are used.
+    pub fn reset_all(&mut self) {
+        for cell in &mut self.cells {
+            cell.take();
+        }
+    }
+
+    /// Computes every cell and copies them into a grid of the same shape.
+    pub fn to_grid<G2: SurfaceGrid<T, Point = G::Point>>(&self) -> G2 where T: Clone {
+        G2::from_fn(|point| self[point.clone()].clone())
+    }
+
+    /// Gets the grid that gives the shape of this grid.
+    pub fn layout(&self) -> &G {
+        &self.layout
+    }
+}
+
+impl <T, G: SurfaceGrid<()>, F: Fn(&G::Point) -> T> Index<G::Point> for LazyGrid<T, G, F> {
+    type Output = T;
+
+    /// Gets the value of a cell, computing it if it has not been computed.
+    fn index(&self, index: G::Point) -> &Self::Output {
+        self.cells[self.layout.to_index(&index)].get_or_init(|| (self.generator)(&index))
+    }
+}
+
+impl <T, G: SurfaceGrid<()>, F: Fn(&G::Point) -> T> IndexMut<G::Point> for LazyGrid<T, G, F> {
+    /// Gets a mutable reference to the value of a cell, computing it if it has not been computed.
+    fn index_mut(&mut self, index: G::Point) -> &mut Self::Output {
+        let cell = &mut self.cells[self.layout.to_index(&index)];
+
+        cell.get_or_init(|| (self.generator)(&index));
+
+        cell.get_mut().unwrap()
+    }
+}
+
+impl <T: Debug, G: SurfaceGrid<()> + Debug, F: Fn(&G::Point) -> T> Debug for LazyGrid<T, G, F> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("LazyGrid")
+            .field("cells", &self.cells)
+            .field("layout", &self.layout)
+            .finish_non_exhaustive()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use crate::{GridPoint, SurfaceGrid, sphere::{CubeSphereGrid, CubeSpherePoint}};
+
+    use super::LazyGrid;
+
+    #[test]
+    fn test_lazy_computes_once() {
+        let calls = AtomicUsize::new(0);
+
+        let grid: LazyGrid<f64, CubeSphereGrid<(), 16>, _> = LazyGrid::new(|point: &CubeSpherePoint<16>| {
+            calls.fetch_add(1, Ordering::Relaxed);
+
+            point.position(1.0).1
+        });
+
+        let point = grid.layout().points().nth(100).unwrap();
+
+        assert!(!grid.is_computed(&point));
+        assert_eq!(point.position(1.0).1, grid[point]);
+        assert_eq!(point.position(1.0).1, grid[point]);
+        assert_eq!(point.up().position(1.0).1, grid[point.up()]);
+
+        assert_eq!(2, calls.load(Ordering::Relaxed));
+        assert_eq!(2, grid.computed_count());
+        assert_eq!(2, grid.iter_computed().count());
+        assert!(grid.is_computed(&point));
+    }
+
+    #[test]
+    fn test_lazy_index_mut_reset() {
+        let mut grid: LazyGrid<u32, CubeSphereGrid<(), 4>, _> = LazyGrid::new(|_: &CubeSpherePoint<4>| 5);
+
+        let point = grid.layout().points().next().unwrap();
+
+        grid[point] += 1;
+
+        assert_eq!(6, grid[point]);
+        assert_eq!(Some(6), grid.reset(&point));
+        assert_eq!(None, grid.get_computed(&point));
+        assert_eq!(5, grid[point]);
+
+        grid.reset_all();
+
+        assert_eq!(0, grid.computed_count());
+    }
+
+    #[test]
+    fn test_lazy_compute_all_to_grid() {
+        let grid: LazyGrid<f64, CubeSphereGrid<(), 8>, _> = LazyGrid::new(|point: &CubeSpherePoint<8>| point.position(1.0).0);
+
+        grid.compute_all_par();
+
+        assert_eq!(6 * 8 * 8, grid.computed_count());
+
+        let dense: CubeSphereGrid<f64, 8> = grid.to_grid();
+
+        assert_eq!(CubeSphereGrid::<f64, 8>::from_fn(|point| point.position(1.0).0), dense);
+        assert!(grid.iter().all(|(point, value)| *value == dense[point]));
+    }
+}
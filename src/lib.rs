@@ -8,29 +8,329 @@
 //! ### Spheres
 //! - `RectangleSphereGrid` - Uses an equirectangular projection to wrap a rectangle around the sphere.
 //! - `CubeSphereGrid` - Projects a cube over the sphere with each face being a square grid.
+//! - `EqualAreaCubeSphereGrid` - Uses the faces of `CubeSphereGrid` with an equal area projection so that every cell has the same area.
+//! - `EquiAngularCubeSphereGrid` - Uses the faces of `CubeSphereGrid` with the cells spaced evenly in angle so that they vary less in size.
+//! - `HealpixSphereGrid` - Uses the HEALPix pixelization so that every cell has the same area.
+//! - `OctaSphereGrid` - Projects an octahedron over the sphere with its faces joined into four square diamonds.
+//! - `GoldbergSphereGrid` - Covers the sphere with hexagons and twelve pentagons so that each cell has five or six neighbours.
+//! - `YinYangSphereGrid` - Covers the sphere with two overlapping latitude-longitude patches so that there are no poles.
+//! - `ReducedSphereGrid` - A latitude-longitude grid where the rows have fewer cells towards the poles so that the cells have similar areas.
+//! - `SinusoidalSphereGrid` - Uses the sinusoidal equal area projection stored as a rectangle so that the cells on the sphere have similar areas.
+//! - `CapSphereGrid` - Covers a cap around a pole of a sphere, such as a hemisphere, with a boundary at its rim.
+//! - `AdaptiveCubeSphereGrid` - A `CubeSphereGrid` where individual cells can be split to give a higher resolution in places.
+//!
+//! ### Flat
+//! - `FlatGrid` - A bounded rectangular grid that does not wrap at its edges.
+//! - `PlaneGrid` - A rectangular grid with edges that wrap, clamp, mirror or read a constant value.
+//!
+//! ### Torus
+//! - `TorusGrid` - Wraps a rectangle around a torus so that both axes wrap.
+//!
+//! ### Klein Bottle
+//! - `KleinBottleGrid` - Joins the edges of a rectangle like a Klein bottle, with one pair of edges reversed.
+//!
+//! ### Cylinder
+//! - `CylinderGrid` - Wraps around a cylinder with ends that clamp, reflect or are covered by caps.
+//!
+//! ### Capsule
+//! - `CapsuleGrid` - Wraps around a cylinder with a hemisphere on each end.
+//!
+//! ### Storage
+//! - `SparseSphereGrid` - Has the shape of another grid but only stores the cells that are not set to a default value.
+//! - `ChunkedGrid` - Has the shape of another grid but splits it into chunks that are spilled to disk when too many are in memory.
+//! - `LayeredGrid` - Has the shape of another grid and stores several named fields for each cell in separate arrays.
+//! - `LazyGrid` - Has the shape of another grid and computes the value of each cell the first time it is used.
+//! - `StoredGrid` - Has the shape of another grid and keeps its cells in a storage backend such as a `Vec`, a boxed slice or a borrowed slice.
+//! - `MmapGrid` - Has the shape of another grid and stores its cells in a memory-mapped file. Requires the `mmap` feature.
+//! - `CurveCubeSphereGrid` - Has the shape of a `CubeSphereGrid` but stores each face along a Hilbert or Morton curve so that nearby cells are close in memory.
+//! - `PaddedCubeSphereGrid` - Has the shape of a `CubeSphereGrid` but stores a border around each face copied from the neighbouring faces so that neighbours are found without crossing faces.
+//!
+//! ### Views
+//! - `GridView` and `GridViewMut` - Borrow a rectangle of cells or the cells within a range of latitudes and longitudes from any grid and only visit those cells.
+//!
+//! ### Level of Detail
+//! - `GridPyramid` - Holds a grid along with successively coarser versions of it built by averaging or taking the minimum or maximum of its cells.
+//!
+//! ## Features
+//! - `serde` - Implements `Serialize` and `Deserialize` for the grids, `Automaton` and `Halo`.
+//! - `wgpu` - Adds `CubeSphereGrid::upload_cubemap` for creating cube map textures.
+//! - `mmap` - Adds `MmapGrid` for storing grids in memory-mapped files.
+//! - `bytemuck` - Adds `as_bytes` and `face_bytes` for viewing the cells of grids with `Pod` values as bytes.
 
-use std::ops::{IndexMut, Index};
+use std::{cmp::Ordering, collections::{BinaryHeap, HashSet}, ops::{IndexMut, Index, Add, Mul}, error::Error, fmt::{self, Display}};
 
-use rayon::iter::ParallelIterator;
+use rayon::{iter::{IndexedParallelIterator, IntoParallelIterator, ParallelIterator}, slice::ParallelSliceMut};
+use sphere::SpherePoint;
+
+/// Fails to compile if any of the listed types stop being `Send` and `Sync`.
+///
+/// The generic parameters that the types use are given in brackets, followed by an optional
+/// `where` clause in brackets and then the types to check.
+macro_rules! assert_send_sync {
+    ([$($generics:tt)*] $(where [$($bounds:tt)*])? $($ty:ty),+ $(,)?) => {
+        const _: () = {
+            fn assert_send_sync<T: Send + Sync>() {}
+
+            #[allow(dead_code)]
+            fn assert_types<$($generics)*>() $(where $($bounds)*)? {
+                $(assert_send_sync::<$ty>();)+
+            }
+        };
+    };
+}
+
+/// Implements the methods of `SurfaceGrid` that are shared by the grids that keep a single
+/// `W` by `H` rectangle of cells in a `HeapArray2D` in row-major order, such as `FlatGrid` and
+/// `RectangleSphereGrid`.
+///
+/// The point type must have a `new(x, y)` constructor and `x` and `y` fields, and the grid must
+/// have a `data` field holding the `HeapArray2D` and an `as_mut_slice` method. The grid still
+/// provides its own `surface_area` and `topology`.
+macro_rules! row_major_grid_methods {
+    ($point:ident) => {
+        fn from_fn<F: FnMut(&Self::Point) -> T>(mut f: F) -> Self {
+            Self {
+                data: HeapArray2D::from_fn(|y, x| {
+                    let point = $point::new(x as u32, y as u32);
+
+                    f(&point)
+                })
+            }
+        }
+
+        fn from_fn_par<F: Fn(&Self::Point) -> T + Send + Sync>(f: F) -> Self where T: Send + Sync {
+            Self {
+                data: HeapArray2D::from_fn_par(|y, x| {
+                    let point = $point::new(x as u32, y as u32);
+
+                    f(&point)
+                })
+            }
+        }
+
+        fn try_from_fn<X, F: FnMut(&Self::Point) -> Result<T, X>>(mut f: F) -> Result<Self, X> {
+            let values = (0..H).cartesian_product(0..W)
+                .map(|(y, x)| $point::new(x as u32, y as u32))
+                .map(|point| f(&point))
+                .collect::<Result<_, _>>()?;
+
+            // The values are in the same order as `points`.
+            Ok(Self::from_vec(values).unwrap())
+        }
+
+        fn try_from_fn_par<X: Send, F: Fn(&Self::Point) -> Result<T, X> + Send + Sync>(f: F) -> Result<Self, X> where T: Send + Sync {
+            let values = (0..W * H).into_par_iter()
+                .map(|i| $point::new((i % W) as u32, (i / W) as u32))
+                .map(|point| f(&point))
+                .collect::<Result<_, _>>()?;
+
+            Ok(Self::from_vec(values).unwrap())
+        }
+
+        fn from_vec(values: Vec<T>) -> Option<Self> {
+            if values.len() != W * H {
+                return None;
+            }
+
+            // `from_fn` visits the points in the same order as `points`.
+            let mut values = values.into_iter();
+
+            Some(Self::from_fn(|_| values.next().unwrap()))
+        }
+
+        fn set_from_fn<F: FnMut(&Self::Point) -> T>(&mut self, mut f: F) {
+            (0..H).cartesian_product(0..W)
+                .map(|(y, x)| $point::new(x as u32, y as u32))
+                .for_each(|point| self[point] = f(&point))
+        }
+
+        fn set_from_fn_par<F: Fn(&Self::Point) -> T + Send + Sync>(&mut self, f: F) where T: Send + Sync {
+            self.data.par_iter_mut().enumerate().for_each(|(y, subarray)| {
+                for (x, value) in subarray.iter_mut().enumerate() {
+                    let point = $point::new(x as u32, y as u32);
+
+                    *value = f(&point);
+                }
+            })
+        }
+
+        fn iter<'a>(&'a self) -> impl ExactSizeIterator<Item = (Self::Point, &'a T)> + DoubleEndedIterator where T: 'a {
+            self.data.as_flattened().iter()
+                .enumerate()
+                .map(|(i, value)| ($point::new((i % W) as u32, (i / W) as u32), value))
+        }
+
+        fn par_iter<'a>(&'a self) -> impl IndexedParallelIterator<Item = (Self::Point, &'a T)> where T: 'a + Send + Sync {
+            self.data.as_flattened().par_iter()
+                .enumerate()
+                .map(|(i, value)| ($point::new((i % W) as u32, (i / W) as u32), value))
+        }
+
+        fn par_iter_mut<'a>(&'a mut self) -> impl IndexedParallelIterator<Item = (Self::Point, &'a mut T)> where T: 'a + Send + Sync {
+            self.data.as_flattened_mut().par_iter_mut()
+                .enumerate()
+                .map(|(i, value)| ($point::new((i % W) as u32, (i / W) as u32), value))
+        }
+
+        fn points(&self) -> impl ExactSizeIterator<Item = Self::Point> + DoubleEndedIterator {
+            (0..W * H).map(|i| $point::new((i % W) as u32, (i / W) as u32))
+        }
+
+        fn par_points(&self) -> impl IndexedParallelIterator<Item = Self::Point> {
+            (0..W * H).into_par_iter()
+                .map(|i| $point::new((i % W) as u32, (i / W) as u32))
+        }
+
+        fn par_map_to_vec<U: Send, F: Fn(&Self::Point, &T) -> U + Send + Sync>(&self, f: F) -> Vec<U> where T: Send + Sync {
+            let mut result = Vec::with_capacity(W * H);
+
+            (0..W * H).into_par_iter()
+                .map(|i| {
+                    let (x, y) = (i % W, i / W);
+
+                    f(&$point::new(x as u32, y as u32), &self.data[y][x])
+                })
+                .collect_into_vec(&mut result);
+
+            result
+        }
+
+        fn to_index(&self, point: &Self::Point) -> usize {
+            point.y as usize * W + point.x as usize
+        }
+
+        fn at(&self, point: &Self::Point) -> &T {
+            &self[point]
+        }
+
+        fn at_mut(&mut self, point: &Self::Point) -> &mut T {
+            &mut self[point]
+        }
+
+        fn contiguous_values_mut(&mut self) -> Option<&mut [T]> {
+            Some(self.as_mut_slice())
+        }
+    };
+}
 
 pub mod sphere;
+pub mod healpix;
+pub mod octa;
+pub mod goldberg;
+pub mod yinyang;
+pub mod reduced;
+pub mod sinusoidal;
+pub mod cap;
+pub mod flat;
+pub mod plane;
+pub mod torus;
+pub mod klein;
+pub mod cylinder;
+pub mod capsule;
+pub mod adaptive;
+pub mod sparse;
+pub mod chunked;
+pub mod layered;
+pub mod storage;
+pub mod lazy;
+pub mod curve;
+pub mod padded;
+#[cfg(feature = "mmap")]
+pub mod mmap;
+pub mod pyramid;
+pub mod halo;
+pub mod view;
+pub mod automaton;
 
 /// A grid wrapped around a surface.
-pub trait SurfaceGrid<T> : IndexMut<Self::Point> + Index<Self::Point, Output = T> + IntoIterator<Item = (Self::Point, T)> {
+///
+/// Grids are indexed by value with their point type. The grids in this crate that have their own
+/// point type can also be indexed by reference, as in `grid[&point]`, so loops that keep using a
+/// point do not need to clone it. The grids that wrap another grid, such as `SparseSphereGrid` and
+/// `StoredGrid`, can only be indexed by value as the point type comes from the wrapped grid, but
+/// every grid can be read and written from a reference to a point with `at` and `at_mut`.
+pub trait SurfaceGrid<T> : IndexMut<Self::Point> + Index<Self::Point, Output = T> + IntoIterator<Item = (Self::Point, T), IntoIter: ExactSizeIterator + DoubleEndedIterator> {
     /// The type of a point on this grid.
     type Point: GridPoint + Send;
 
     /// Creates a new surface grid by calling the specified function for each point in the grid.
     ///
+    /// The function is called for each point in the same order as `points`.
+    ///
     /// - `f` - The function to apply.
     fn from_fn<F: FnMut(&Self::Point) -> T>(f: F) -> Self;
 
+    /// Creates a new surface grid by calling the specified function with the index of each point in
+    /// the grid.
+    ///
+    /// The function is called with each index from 0 to the number of cells in the same order as
+    /// `points` so each cell is given the index returned by `to_index`.
+    ///
+    /// - `f` - The function to apply.
+    fn from_index_fn<F: FnMut(usize) -> T>(mut f: F) -> Self where Self: Sized {
+        let mut index = 0;
+
+        Self::from_fn(|_| {
+            let value = f(index);
+
+            index += 1;
+
+            value
+        })
+    }
+
+    /// Creates a new surface grid from a `Vec` of values in the same order as `points`.
+    ///
+    /// This returns `None` if the length of the `Vec` is not the number of cells in the grid.
+    /// The order of `points` is also the order of `iter`, `into_iter` and `to_vec`, so values
+    /// taken from a grid in any of those ways load back into the same cells. The grids in this
+    /// crate also implement `FromIterator` in the same order, which panics instead of returning
+    /// `None`.
+    ///
+    /// - `values` - The values of the cells.
+    fn from_vec(values: Vec<T>) -> Option<Self> where Self: Sized;
+
     /// Creates a new surface grid by calling the specified function in parallel for each point in
     /// the grid.
     ///
     /// - `f` - The function to apply.
     fn from_fn_par<F: Fn(&Self::Point) -> T + Send + Sync>(f: F) -> Self where T: Send + Sync;
 
+    /// Creates a new surface grid by calling a fallible function for each point in the grid.
+    ///
+    /// The function is called for each point in the same order as `points`. The first error that
+    /// it returns is returned without calling it for the remaining points.
+    ///
+    /// - `f` - The function to apply.
+    fn try_from_fn<X, F: FnMut(&Self::Point) -> Result<T, X>>(f: F) -> Result<Self, X> where Self: Sized;
+
+    /// Creates a new surface grid by calling a fallible function in parallel for each point in the
+    /// grid.
+    ///
+    /// Once the function returns an error the points that have not been started are skipped. If it
+    /// returns more than one error then it is not specified which of them is returned.
+    ///
+    /// - `f` - The function to apply.
+    fn try_from_fn_par<X: Send, F: Fn(&Self::Point) -> Result<T, X> + Send + Sync>(f: F) -> Result<Self, X> where T: Send + Sync, Self: Sized;
+
+    /// Creates a new surface grid by calling the specified function for each point in the grid
+    /// and using the default value for the points where it returns `None`.
+    ///
+    /// The function is called for each point in the same order as `points`.
+    ///
+    /// - `f` - The function to apply.
+    fn from_fn_sparse<F: FnMut(&Self::Point) -> Option<T>>(mut f: F) -> Self where T: Default, Self: Sized {
+        Self::from_fn(|point| f(point).unwrap_or_default())
+    }
+
+    /// Creates a new surface grid by calling the specified function in parallel for each point in
+    /// the grid and using the default value for the points where it returns `None`.
+    ///
+    /// - `f` - The function to apply.
+    fn from_fn_sparse_par<F: Fn(&Self::Point) -> Option<T> + Send + Sync>(f: F) -> Self where T: Default + Send + Sync, Self: Sized {
+        Self::from_fn_par(|point| f(point).unwrap_or_default())
+    }
+
     /// Applies a function to each cell and its direct neighbours.
     ///
     /// The provided function is called with the arguments: current, up, down, left, right.
@@ -38,25 +338,26 @@ pub trait SurfaceGrid<T> : IndexMut<Self::Point> + Index<Self::Point, Output = T
     /// `f` - The function to apply.
     fn map_neighbours<F: FnMut(&T, &T, &T, &T, &T) -> T>(&self, mut f: F) -> Self where Self: Sized {
         Self::from_fn(|current| {
-            f(&self[current.clone()], &self[current.up()], &self[current.down()], &self[current.left()], &self[current.right()])
+            let [up, down, left, right] = gather_neighbours(self, current);
+
+            f(self.at(current), up, down, left, right)
         })
     }
     
     /// Applies a function to each cell and its direct neighbours including diagonals.
     ///
     /// The provided function is called with the arguments: up_left, up, up_right,
-    /// left, current, right, down_left, down, down_right.
+    /// left, current, right, down_left, down, down_right. This is the order given by
+    /// `DIAGONAL_ORDER`.
     ///
     /// `f` - The function to apply.
     fn map_neighbours_diagonals<
                 F: FnMut(&T, &T, &T, &T, &T, &T, &T, &T, &T) -> T
             >(&self, mut f: F) -> Self where Self: Sized {
         Self::from_fn(|current| {
-            f(
-                &self[current.up().left()], &self[current.up()], &self[current.up().right()],
-                &self[current.left()], &self[current.clone()], &self[current.right()],
-                &self[current.down().left()], &self[current.down()], &self[current.down().right()]
-                )
+            let [up_left, up, up_right, left, right, down_left, down, down_right] = gather_neighbours_diagonals(self, current);
+
+            f(up_left, up, up_right, left, self.at(current), right, down_left, down, down_right)
         })
     }
     
@@ -69,25 +370,26 @@ pub trait SurfaceGrid<T> : IndexMut<Self::Point> + Index<Self::Point, Output = T
                 F: Fn(&T, &T, &T, &T, &T) -> T + Send + Sync
             >(&self, f: F) -> Self where Self: Sized + Sync, T: Send + Sync {
         Self::from_fn_par(|current| {
-            f(&self[current.clone()], &self[current.up()], &self[current.down()], &self[current.left()], &self[current.right()])
+            let [up, down, left, right] = gather_neighbours(self, current);
+
+            f(self.at(current), up, down, left, right)
         })
     }
     
     /// Applies a function in parallel to each cell and its direct neighbours including diagonals.
     ///
     /// The provided function is called with the arguments: up_left, up, up_right,
-    /// left, current, right, down_left, down, down_right.
+    /// left, current, right, down_left, down, down_right. This is the order given by
+    /// `DIAGONAL_ORDER`.
     ///
     /// `f` - The function to apply.
     fn map_neighbours_diagonals_par<
                 F: Fn(&T, &T, &T, &T, &T, &T, &T, &T, &T) -> T + Send + Sync
             >(&self, f: F) -> Self where Self: Sized + Sync, T: Send + Sync {
         Self::from_fn_par(|current| {
-            f(
-                &self[current.up().left()], &self[current.up()], &self[current.up().right()],
-                &self[current.left()], &self[current.clone()], &self[current.right()],
-                &self[current.down().left()], &self[current.down()], &self[current.down().right()]
-                )
+            let [up_left, up, up_right, left, right, down_left, down, down_right] = gather_neighbours_diagonals(self, current);
+
+            f(up_left, up, up_right, left, self.at(current), right, down_left, down, down_right)
         })
     }
     
@@ -98,7 +400,9 @@ pub trait SurfaceGrid<T> : IndexMut<Self::Point> + Index<Self::Point, Output = T
     /// `f` - The function to apply.
     fn map_neighbours_with_position<F: FnMut(&T, &Self::Point, &T, &T, &T, &T) -> T>(&self, mut f: F) -> Self where Self: Sized {
         Self::from_fn(|current| {
-            f(&self[current.clone()], current, &self[current.up()], &self[current.down()], &self[current.left()], &self[current.right()])
+            let [up, down, left, right] = gather_neighbours(self, current);
+
+            f(self.at(current), current, up, down, left, right)
         })
     }
     
@@ -115,11 +419,9 @@ pub trait SurfaceGrid<T> : IndexMut<Self::Point> + Index<Self::Point, Output = T
                 F: FnMut(&Self::Point, &T, &T, &T, &T, &T, &T, &T, &T, &T) -> T
             >(&self, mut f: F) -> Self where Self: Sized {
         Self::from_fn(|current| {
-            f(current,
-                &self[current.up().left()], &self[current.up()], &self[current.up().right()],
-                &self[current.left()], &self[current.clone()], &self[current.right()],
-                &self[current.down().left()], &self[current.down()], &self[current.down().right()]
-                )
+            let [up_left, up, up_right, left, right, down_left, down, down_right] = gather_neighbours_diagonals(self, current);
+
+            f(current, up_left, up, up_right, left, self.at(current), right, down_left, down, down_right)
         })
     }
     
@@ -133,7 +435,9 @@ pub trait SurfaceGrid<T> : IndexMut<Self::Point> + Index<Self::Point, Output = T
                 F: Fn(&T, &Self::Point, &T, &T, &T, &T) -> T + Send + Sync
             >(&self, f: F) -> Self where Self: Sized + Sync, T: Send + Sync {
         Self::from_fn_par(|current| {
-            f(&self[current.clone()], current, &self[current.up()], &self[current.down()], &self[current.left()], &self[current.right()])
+            let [up, down, left, right] = gather_neighbours(self, current);
+
+            f(self.at(current), current, up, down, left, right)
         })
     }
     
@@ -150,14 +454,281 @@ pub trait SurfaceGrid<T> : IndexMut<Self::Point> + Index<Self::Point, Output = T
                 F: Fn(&Self::Point, &T, &T, &T, &T, &T, &T, &T, &T, &T) -> T + Send + Sync
             >(&self, f: F) -> Self where Self: Sized + Sync, T: Send + Sync {
         Self::from_fn_par(|current| {
-            f(current,
-                &self[current.up().left()], &self[current.up()], &self[current.up().right()],
-                &self[current.left()], &self[current.clone()], &self[current.right()],
-                &self[current.down().left()], &self[current.down()], &self[current.down().right()]
-                )
+            let [up_left, up, up_right, left, right, down_left, down, down_right] = gather_neighbours_diagonals(self, current);
+
+            f(current, up_left, up, up_right, left, self.at(current), right, down_left, down, down_right)
+        })
+    }
+
+    /// Applies a function to each cell and the cells within `R` steps of it.
+    ///
+    /// The provided function is called with the values of the neighbourhood in the order given by
+    /// `gather_neighbours_radius`, so the current cell is in the middle of the slice. A radius of
+    /// 1 with `Neighbourhood::Moore` gives the cells in the order of `DIAGONAL_ORDER`.
+    ///
+    /// `neighbourhood` - The shape of the neighbourhood.
+    /// `f` - The function to apply.
+    fn map_neighbours_radius<const R: usize, F: FnMut(&[&T]) -> T>(&self, neighbourhood: Neighbourhood, mut f: F) -> Self where Self: Sized {
+        let mut neighbours = Vec::with_capacity(neighbourhood.len(R));
+
+        Self::from_fn(|current| {
+            gather_neighbours_radius(self, current, R, neighbourhood, &mut neighbours);
+
+            f(&neighbours)
+        })
+    }
+
+    /// Applies a function in parallel to each cell and the cells within `R` steps of it.
+    ///
+    /// The provided function is called with the values of the neighbourhood in the order given by
+    /// `gather_neighbours_radius`, so the current cell is in the middle of the slice.
+    ///
+    /// `neighbourhood` - The shape of the neighbourhood.
+    /// `f` - The function to apply.
+    fn map_neighbours_radius_par<
+                const R: usize,
+                F: Fn(&[&T]) -> T + Send + Sync
+            >(&self, neighbourhood: Neighbourhood, f: F) -> Self where Self: Sized + Sync, T: Send + Sync {
+        Self::from_fn_par(|current| {
+            let mut neighbours = Vec::with_capacity(neighbourhood.len(R));
+            gather_neighbours_radius(self, current, R, neighbourhood, &mut neighbours);
+
+            f(&neighbours)
+        })
+    }
+
+    /// Applies a function to each cell and the cells within `R` steps of it providing the current
+    /// point.
+    ///
+    /// The provided function is called with the current point and the values of the neighbourhood
+    /// in the order given by `gather_neighbours_radius`.
+    ///
+    /// `neighbourhood` - The shape of the neighbourhood.
+    /// `f` - The function to apply.
+    fn map_neighbours_radius_with_position<
+                const R: usize,
+                F: FnMut(&Self::Point, &[&T]) -> T
+            >(&self, neighbourhood: Neighbourhood, mut f: F) -> Self where Self: Sized {
+        let mut neighbours = Vec::with_capacity(neighbourhood.len(R));
+
+        Self::from_fn(|current| {
+            gather_neighbours_radius(self, current, R, neighbourhood, &mut neighbours);
+
+            f(current, &neighbours)
+        })
+    }
+
+    /// Applies a function in parallel to each cell and the cells within `R` steps of it providing
+    /// the current point.
+    ///
+    /// The provided function is called with the current point and the values of the neighbourhood
+    /// in the order given by `gather_neighbours_radius`.
+    ///
+    /// `neighbourhood` - The shape of the neighbourhood.
+    /// `f` - The function to apply.
+    fn map_neighbours_radius_par_with_position<
+                const R: usize,
+                F: Fn(&Self::Point, &[&T]) -> T + Send + Sync
+            >(&self, neighbourhood: Neighbourhood, f: F) -> Self where Self: Sized + Sync, T: Send + Sync {
+        Self::from_fn_par(|current| {
+            let mut neighbours = Vec::with_capacity(neighbourhood.len(R));
+            gather_neighbours_radius(self, current, R, neighbourhood, &mut neighbours);
+
+            f(current, &neighbours)
+        })
+    }
+
+    /// Applies a function to each cell and the cells at a list of offsets from it.
+    ///
+    /// Each offset is given as `(dx, dy)`, where positive values are to the right and down, and is
+    /// found with `GridPoint::offset` so the stencil keeps its shape across the seams of the grid.
+    /// The provided function is called with the values in the same order as `offsets`, so
+    /// include `(0, 0)` to read the current cell.
+    ///
+    /// `offsets` - The offsets of the cells to read.
+    /// `f` - The function to apply.
+    fn map_stencil<F: FnMut(&[&T]) -> T>(&self, offsets: &[(i32, i32)], mut f: F) -> Self where Self: Sized {
+        let mut values = Vec::with_capacity(offsets.len());
+
+        Self::from_fn(|current| {
+            gather_stencil(self, current, offsets, &mut values);
+
+            f(&values)
+        })
+    }
+
+    /// Applies a function in parallel to each cell and the cells at a list of offsets from it.
+    ///
+    /// The offsets are used in the same way as in `map_stencil`.
+    ///
+    /// `offsets` - The offsets of the cells to read.
+    /// `f` - The function to apply.
+    fn map_stencil_par<
+                F: Fn(&[&T]) -> T + Send + Sync
+            >(&self, offsets: &[(i32, i32)], f: F) -> Self where Self: Sized + Sync, T: Send + Sync {
+        Self::from_fn_par(|current| {
+            let mut values = Vec::with_capacity(offsets.len());
+            gather_stencil(self, current, offsets, &mut values);
+
+            f(&values)
+        })
+    }
+
+    /// Applies a function to each cell and the cells at a list of offsets from it providing the
+    /// current point.
+    ///
+    /// The provided function is called with the current point and the values in the same order
+    /// as `offsets`, which are used in the same way as in `map_stencil`.
+    ///
+    /// `offsets` - The offsets of the cells to read.
+    /// `f` - The function to apply.
+    fn map_stencil_with_position<
+                F: FnMut(&Self::Point, &[&T]) -> T
+            >(&self, offsets: &[(i32, i32)], mut f: F) -> Self where Self: Sized {
+        let mut values = Vec::with_capacity(offsets.len());
+
+        Self::from_fn(|current| {
+            gather_stencil(self, current, offsets, &mut values);
+
+            f(current, &values)
+        })
+    }
+
+    /// Applies a function in parallel to each cell and the cells at a list of offsets from it
+    /// providing the current point.
+    ///
+    /// The offsets are used in the same way as in `map_stencil`.
+    ///
+    /// `offsets` - The offsets of the cells to read.
+    /// `f` - The function to apply.
+    fn map_stencil_par_with_position<
+                F: Fn(&Self::Point, &[&T]) -> T + Send + Sync
+            >(&self, offsets: &[(i32, i32)], f: F) -> Self where Self: Sized + Sync, T: Send + Sync {
+        Self::from_fn_par(|current| {
+            let mut values = Vec::with_capacity(offsets.len());
+            gather_stencil(self, current, offsets, &mut values);
+
+            f(current, &values)
+        })
+    }
+
+    /// Convolves this grid with a square kernel of weights.
+    ///
+    /// Each cell is set to the sum of the cells around it multiplied by the weight at the same
+    /// position in the kernel. The kernel is indexed as `kernel[y][x]` with the current cell at the
+    /// centre, and the cells are found in the same way as in `map_stencil` so the kernel keeps its
+    /// shape across the seams of the grid.
+    ///
+    /// # Panics
+    /// Panics if `N` is even since the kernel has no centre.
+    ///
+    /// `kernel` - The weights to multiply the cells by.
+    fn convolve<const N: usize>(&self, kernel: &[[T; N]; N]) -> Self
+            where Self: Sized, T: Clone + Add<Output = T> + Mul<Output = T> + Default {
+        self.convolve_with(kernel, |sum, weight, value| sum + weight.clone() * value.clone())
+    }
+
+    /// Convolves this grid with a square kernel of weights in parallel.
+    ///
+    /// The kernel is used in the same way as in `convolve`.
+    ///
+    /// # Panics
+    /// Panics if `N` is even since the kernel has no centre.
+    ///
+    /// `kernel` - The weights to multiply the cells by.
+    fn convolve_par<const N: usize>(&self, kernel: &[[T; N]; N]) -> Self
+            where Self: Sized + Sync, T: Clone + Add<Output = T> + Mul<Output = T> + Default + Send + Sync {
+        self.convolve_with_par(kernel, |sum, weight, value| sum + weight.clone() * value.clone())
+    }
+
+    /// Convolves this grid with a square kernel using a function to combine each weight and cell.
+    ///
+    /// The provided function is called with the running total, starting from `T::default()`, a
+    /// weight and the value of the cell at the same position in the kernel, and returns the new
+    /// total. This allows the weights to be a different type to the cells. The kernel is used in
+    /// the same way as in `convolve`.
+    ///
+    /// # Panics
+    /// Panics if `N` is even since the kernel has no centre.
+    ///
+    /// `kernel` - The weights to combine with the cells.
+    /// `combine` - The function that adds a weighted cell to the total.
+    fn convolve_with<
+                const N: usize,
+                W,
+                F: FnMut(T, &W, &T) -> T
+            >(&self, kernel: &[[W; N]; N], mut combine: F) -> Self where Self: Sized, T: Default {
+        let offsets = kernel_offsets::<N>();
+
+        self.map_stencil(&offsets, |values| {
+            kernel.as_flattened().iter()
+                .zip(values)
+                .fold(T::default(), |sum, (weight, value)| combine(sum, weight, value))
+        })
+    }
+
+    /// Convolves this grid with a square kernel in parallel using a function to combine each weight
+    /// and cell.
+    ///
+    /// The kernel and function are used in the same way as in `convolve_with`.
+    ///
+    /// # Panics
+    /// Panics if `N` is even since the kernel has no centre.
+    ///
+    /// `kernel` - The weights to combine with the cells.
+    /// `combine` - The function that adds a weighted cell to the total.
+    fn convolve_with_par<
+                const N: usize,
+                W: Sync,
+                F: Fn(T, &W, &T) -> T + Send + Sync
+            >(&self, kernel: &[[W; N]; N], combine: F) -> Self where Self: Sized + Sync, T: Default + Send + Sync {
+        let offsets = kernel_offsets::<N>();
+
+        self.map_stencil_par(&offsets, |values| {
+            kernel.as_flattened().iter()
+                .zip(values)
+                .fold(T::default(), |sum, (weight, value)| combine(sum, weight, value))
         })
     }
     
+    /// Applies a function to each cell and the cells that the grid joins to it by an edge or a
+    /// corner.
+    ///
+    /// The provided function is called with the value of the current cell and the values of the
+    /// cells given by `GridPoint::moore_neighbours`, in the same order. Unlike
+    /// `map_neighbours_diagonals` the number of neighbours can vary, such as the seven neighbours
+    /// of the cells at the corners of a cube, so no cell is counted twice.
+    ///
+    /// `f` - The function to apply.
+    fn map_neighbours_exact<F: FnMut(&T, &[&T]) -> T>(&self, mut f: F) -> Self where Self: Sized {
+        let mut neighbours = Vec::with_capacity(8);
+
+        Self::from_fn(|current| {
+            neighbours.clear();
+            neighbours.extend(current.moore_neighbours().map(|point| &self[point]));
+
+            f(self.at(current), &neighbours)
+        })
+    }
+
+    /// Applies a function in parallel to each cell and the cells that the grid joins to it by an
+    /// edge or a corner.
+    ///
+    /// The provided function is called in the same way as in `map_neighbours_exact`.
+    ///
+    /// `f` - The function to apply.
+    fn map_neighbours_exact_par<
+                F: Fn(&T, &[&T]) -> T + Send + Sync
+            >(&self, f: F) -> Self where Self: Sized + Sync, T: Send + Sync {
+        Self::from_fn_par(|current| {
+            let neighbours: Vec<_> = current.moore_neighbours()
+                .map(|point| &self[point])
+                .collect();
+
+            f(self.at(current), &neighbours)
+        })
+    }
+
     /// Updates this surface grid by calling the specified function for each point in the grid.
     ///
     /// - `f` - The function to apply.
@@ -169,6 +740,153 @@ pub trait SurfaceGrid<T> : IndexMut<Self::Point> + Index<Self::Point, Output = T
     /// - `f` - The function to apply.
     fn set_from_fn_par<F: Fn(&Self::Point) -> T + Send + Sync>(&mut self, f: F) where T: Send + Sync;
 
+    /// Sets the values of several cells at once.
+    ///
+    /// The updates are applied in order so when a point is updated more than once the last value
+    /// is kept.
+    ///
+    /// - `updates` - The points to update along with their new values.
+    fn apply_updates<I: IntoIterator<Item = (Self::Point, T)>>(&mut self, updates: I) {
+        for (point, value) in updates {
+            self[point] = value;
+        }
+    }
+
+    /// Sets the values of several cells at once in parallel.
+    ///
+    /// The updates are sorted by the index of their point and then written straight into the
+    /// cells that they update, so the time taken depends on the number of updates rather than the
+    /// size of the grid. The cells are written in parallel when the grid keeps its values in
+    /// `contiguous_values_mut` and one after another otherwise. When a point is updated more than
+    /// once the last value is kept, as in `apply_updates`.
+    ///
+    /// - `updates` - The points to update along with their new values.
+    fn apply_updates_par<I: IntoParallelIterator<Item = (Self::Point, T)>>(&mut self, updates: I) where T: Send + Sync, Self: Sync {
+        let mut updates: Vec<(usize, Self::Point, T)> = updates.into_par_iter()
+            .map(|(point, value)| (self.to_index(&point), point, value))
+            .collect();
+
+        // The sort is stable so the last update to a point is moved into the one that is kept.
+        updates.par_sort_by_key(|(index, _, _)| *index);
+        updates.dedup_by(|later, earlier| {
+            let duplicate = later.0 == earlier.0;
+
+            if duplicate {
+                std::mem::swap(&mut later.2, &mut earlier.2);
+            }
+
+            duplicate
+        });
+
+        let Some(mut values) = self.contiguous_values_mut() else {
+            for (_, point, value) in updates {
+                self[point] = value;
+            }
+
+            return;
+        };
+
+        // Split the values at each updated index so that the cells can be written in parallel.
+        let mut cells = Vec::with_capacity(updates.len());
+        let mut start = 0;
+
+        for (index, _, _) in &updates {
+            let (cell, rest) = std::mem::take(&mut values)[index - start..]
+                .split_first_mut()
+                .unwrap();
+
+            cells.push(cell);
+            values = rest;
+            start = index + 1;
+        }
+
+        cells.into_par_iter()
+            .zip(updates)
+            .for_each(|(cell, (_, _, value))| *cell = value);
+    }
+
+    /// Calls a function with a mutable reference to the value of each cell.
+    ///
+    /// - `f` - The function to call with each value.
+    fn for_each<F: FnMut(&mut T)>(&mut self, mut f: F) {
+        self.for_each_with_point(|_, value| f(value));
+    }
+
+    /// Calls a function with a mutable reference to the value of each cell in parallel.
+    ///
+    /// - `f` - The function to call with each value.
+    fn for_each_par<F: Fn(&mut T) + Send + Sync>(&mut self, f: F) where T: Send + Sync {
+        self.par_iter_mut().for_each(|(_, value)| f(value));
+    }
+
+    /// Calls a function with each point and a mutable reference to its value.
+    ///
+    /// This updates the cells in place, such as to seed the cells within a band of latitudes,
+    /// without rebuilding the grid with `set_from_fn`. By default the points are collected first
+    /// so that the cells can be borrowed through `IndexMut`.
+    ///
+    /// - `f` - The function to call with each point and its value.
+    fn for_each_with_point<F: FnMut(&Self::Point, &mut T)>(&mut self, mut f: F) {
+        let points: Vec<_> = self.points().collect();
+
+        for point in points {
+            f(&point, self.at_mut(&point));
+        }
+    }
+
+    /// Calls a function with each point and a mutable reference to its value in parallel.
+    ///
+    /// - `f` - The function to call with each point and its value.
+    fn for_each_with_point_par<F: Fn(&Self::Point, &mut T) + Send + Sync>(&mut self, f: F) where T: Send + Sync {
+        self.par_iter_mut().for_each(|(point, value)| f(&point, value));
+    }
+
+    /// Replaces the value of each cell with the result of a function of its current value.
+    ///
+    /// - `f` - The function that gives the new value of each cell.
+    fn map_in_place<F: FnMut(&T) -> T>(&mut self, mut f: F) {
+        self.for_each(|value| *value = f(value));
+    }
+
+    /// Replaces the value of each cell with the result of a function of its current value in
+    /// parallel.
+    ///
+    /// - `f` - The function that gives the new value of each cell.
+    fn map_in_place_par<F: Fn(&T) -> T + Send + Sync>(&mut self, f: F) where T: Send + Sync {
+        self.for_each_par(|value| *value = f(value));
+    }
+
+    /// Replaces the value of each cell that matches a predicate with the result of a function of
+    /// its current value.
+    ///
+    /// The other cells are left unchanged, so this suits updates that only affect part of the grid
+    /// such as melting the ice in the cells above a temperature.
+    ///
+    /// - `predicate` - The function that decides whether a cell is updated.
+    /// - `f` - The function that gives the new value of each matching cell.
+    fn update_where<
+                P: FnMut(&Self::Point, &T) -> bool,
+                F: FnMut(&T) -> T
+            >(&mut self, mut predicate: P, mut f: F) {
+        self.for_each_with_point(|point, value| if predicate(point, value) {
+            *value = f(value);
+        });
+    }
+
+    /// Replaces the value of each cell that matches a predicate with the result of a function of
+    /// its current value in parallel.
+    ///
+    /// - `predicate` - The function that decides whether a cell is updated.
+    /// - `f` - The function that gives the new value of each matching cell.
+    fn update_where_par<
+                P: Fn(&Self::Point, &T) -> bool + Send + Sync,
+                F: Fn(&T) -> T + Send + Sync
+            >(&mut self, predicate: P, f: F) where T: Send + Sync {
+        self.for_each_with_point_par(|point, value| if predicate(point, value) {
+            *value = f(value);
+        });
+    }
+
     /// Applies a function to each cell and its direct neighbours.
     ///
     /// The provided function is called with the arguments: current, up, down, left, right.
@@ -181,14 +899,17 @@ pub trait SurfaceGrid<T> : IndexMut<Self::Point> + Index<Self::Point, Output = T
                 F: FnMut(&U, &U, &U, &U, &U) -> T
             >(&mut self, source: &G, mut f: F) {
         self.set_from_fn(|current| {
-            f(&source[current.clone()], &source[current.up()], &source[current.down()], &source[current.left()], &source[current.right()])
+            let [up, down, left, right] = gather_neighbours(source, current);
+
+            f(source.at(current), up, down, left, right)
         })
     }
     
     /// Applies a function to each cell and its direct neighbours including diagonals.
     ///
     /// The provided function is called with the arguments: up_left, up, up_right,
-    /// left, current, right, down_left, down, down_right.
+    /// left, current, right, down_left, down, down_right. This is the order given by
+    /// `DIAGONAL_ORDER`.
     ///
     /// `source` - The source grid from which to read data.
     /// `f` - The function to apply.
@@ -198,11 +919,9 @@ pub trait SurfaceGrid<T> : IndexMut<Self::Point> + Index<Self::Point, Output = T
                 F: FnMut(&U, &U, &U, &U, &U, &U, &U, &U, &U) -> T
             >(&mut self, source: &G, mut f: F) {
         self.set_from_fn(|current| {
-            f(
-                &source[current.up().left()], &source[current.up()], &source[current.up().right()],
-                &source[current.left()], &source[current.clone()], &source[current.right()],
-                &source[current.down().left()], &source[current.down()], &source[current.down().right()]
-                )
+            let [up_left, up, up_right, left, right, down_left, down, down_right] = gather_neighbours_diagonals(source, current);
+
+            f(up_left, up, up_right, left, source.at(current), right, down_left, down, down_right)
         })
     }
     
@@ -217,15 +936,18 @@ pub trait SurfaceGrid<T> : IndexMut<Self::Point> + Index<Self::Point, Output = T
                 G: SurfaceGrid<U, Point = Self::Point> + Sync,
                 F: Fn(&U, &U, &U, &U, &U) -> T + Send + Sync
             >(&mut self, source: &G, f: F) where T: Send + Sync {
-        self.set_from_fn(|current| {
-            f(&source[current.clone()], &source[current.up()], &source[current.down()], &source[current.left()], &source[current.right()])
+        self.set_from_fn_par(|current| {
+            let [up, down, left, right] = gather_neighbours(source, current);
+
+            f(source.at(current), up, down, left, right)
         })
     }
     
     /// Applies a function to each cell and its direct neighbours including diagonals in parallel.
     ///
     /// The provided function is called with the arguments: up_left, up, up_right,
-    /// left, current, right, down_left, down, down_right.
+    /// left, current, right, down_left, down, down_right. This is the order given by
+    /// `DIAGONAL_ORDER`.
     ///
     /// `source` - The source grid from which to read data.
     /// `f` - The function to apply.
@@ -234,14 +956,29 @@ pub trait SurfaceGrid<T> : IndexMut<Self::Point> + Index<Self::Point, Output = T
                 G: SurfaceGrid<U, Point = Self::Point> + Sync,
                 F: Fn(&U, &U, &U, &U, &U, &U, &U, &U, &U) -> T + Send + Sync
             >(&mut self, source: &G, f: F) where T: Send + Sync {
-        self.set_from_fn(|current| {
-            f(
-                &source[current.up().left()], &source[current.up()], &source[current.up().right()],
-                &source[current.left()], &source[current.clone()], &source[current.right()],
-                &source[current.down().left()], &source[current.down()], &source[current.down().right()]
-                )
+        self.set_from_fn_par(|current| {
+            let [up_left, up, up_right, left, right, down_left, down, down_right] = gather_neighbours_diagonals(source, current);
+
+            f(up_left, up, up_right, left, source.at(current), right, down_left, down, down_right)
         })
     }
+
+    /// Applies a function to each cell and its direct neighbours including diagonals in parallel
+    /// writing the results into an existing grid.
+    ///
+    /// This allows a simulation to reuse two buffers instead of allocating a new grid each step.
+    ///
+    /// The provided function is called with the arguments: up_left, up, up_right,
+    /// left, current, right, down_left, down, down_right. This is the order given by
+    /// `DIAGONAL_ORDER`.
+    ///
+    /// `dest` - The grid to write the results into.
+    /// `f` - The function to apply.
+    fn step_into<
+                F: Fn(&T, &T, &T, &T, &T, &T, &T, &T, &T) -> T + Send + Sync
+            >(&self, dest: &mut Self, f: F) where T: Send + Sync, Self: Sized + Sync {
+        dest.set_from_neighbours_diagonals_par(self, f)
+    }
     
     /// Applies a function to each cell and its direct neighbours providing the position.
     ///
@@ -255,7 +992,9 @@ pub trait SurfaceGrid<T> : IndexMut<Self::Point> + Index<Self::Point, Output = T
                 F: FnMut(&U, &Self::Point, &U, &U, &U, &U) -> T
             >(&mut self, source: &G, mut f: F) {
         self.set_from_fn(|current| {
-            f(&source[current.clone()], current, &source[current.up()], &source[current.down()], &source[current.left()], &source[current.right()])
+            let [up, down, left, right] = gather_neighbours(source, current);
+
+            f(source.at(current), current, up, down, left, right)
         })
     }
     
@@ -274,11 +1013,9 @@ pub trait SurfaceGrid<T> : IndexMut<Self::Point> + Index<Self::Point, Output = T
                 F: FnMut(&Self::Point, &U, &U, &U, &U, &U, &U, &U, &U, &U) -> T
             >(&mut self, source: &G, mut f: F) {
         self.set_from_fn(|current| {
-            f(current,
-                &source[current.up().left()], &source[current.up()], &source[current.up().right()],
-                &source[current.left()], &source[current.clone()], &source[current.right()],
-                &source[current.down().left()], &source[current.down()], &source[current.down().right()]
-                )
+            let [up_left, up, up_right, left, right, down_left, down, down_right] = gather_neighbours_diagonals(source, current);
+
+            f(current, up_left, up, up_right, left, source.at(current), right, down_left, down, down_right)
         })
     }
     
@@ -293,8 +1030,10 @@ pub trait SurfaceGrid<T> : IndexMut<Self::Point> + Index<Self::Point, Output = T
                 G: SurfaceGrid<U, Point = Self::Point> + Sync,
                 F: Fn(&U, &Self::Point, &U, &U, &U, &U) -> T + Send + Sync
             >(&mut self, source: &G, f: F) where T: Send + Sync {
-        self.set_from_fn(|current| {
-            f(&source[current.clone()], current, &source[current.up()], &source[current.down()], &source[current.left()], &source[current.right()])
+        self.set_from_fn_par(|current| {
+            let [up, down, left, right] = gather_neighbours(source, current);
+
+            f(source.at(current), current, up, down, left, right)
         })
     }
     
@@ -312,28 +1051,598 @@ pub trait SurfaceGrid<T> : IndexMut<Self::Point> + Index<Self::Point, Output = T
                 G: SurfaceGrid<U, Point = Self::Point> + Sync,
                 F: Fn(&Self::Point, &U, &U, &U, &U, &U, &U, &U, &U, &U) -> T + Send + Sync
             >(&mut self, source: &G, f: F) where T: Send + Sync {
-        self.set_from_fn(|current| {
-            f(current,
-                &source[current.up().left()], &source[current.up()], &source[current.up().right()],
-                &source[current.left()], &source[current.clone()], &source[current.right()],
-                &source[current.down().left()], &source[current.down()], &source[current.down().right()]
-                )
+        self.set_from_fn_par(|current| {
+            let [up_left, up, up_right, left, right, down_left, down, down_right] = gather_neighbours_diagonals(source, current);
+
+            f(current, up_left, up, up_right, left, source.at(current), right, down_left, down, down_right)
+        })
+    }
+
+    /// Applies a function to each cell and the cells within `R` steps of it.
+    ///
+    /// The provided function is called with the values of the neighbourhood in the order given by
+    /// `gather_neighbours_radius`, so the current cell is in the middle of the slice.
+    ///
+    /// `source` - The source grid from which to read data.
+    /// `neighbourhood` - The shape of the neighbourhood.
+    /// `f` - The function to apply.
+    fn set_from_neighbours_radius<
+                const R: usize,
+                U,
+                G: SurfaceGrid<U, Point = Self::Point>,
+                F: FnMut(&[&U]) -> T
+            >(&mut self, source: &G, neighbourhood: Neighbourhood, mut f: F) {
+        let mut neighbours = Vec::with_capacity(neighbourhood.len(R));
+
+        self.set_from_fn(|current| {
+            gather_neighbours_radius(source, current, R, neighbourhood, &mut neighbours);
+
+            f(&neighbours)
+        })
+    }
+
+    /// Applies a function to each cell and the cells within `R` steps of it in parallel.
+    ///
+    /// The provided function is called with the values of the neighbourhood in the order given by
+    /// `gather_neighbours_radius`, so the current cell is in the middle of the slice.
+    ///
+    /// `source` - The source grid from which to read data.
+    /// `neighbourhood` - The shape of the neighbourhood.
+    /// `f` - The function to apply.
+    fn set_from_neighbours_radius_par<
+                const R: usize,
+                U,
+                G: SurfaceGrid<U, Point = Self::Point> + Sync,
+                F: Fn(&[&U]) -> T + Send + Sync
+            >(&mut self, source: &G, neighbourhood: Neighbourhood, f: F) where T: Send + Sync {
+        self.set_from_fn_par(|current| {
+            let mut neighbours = Vec::with_capacity(neighbourhood.len(R));
+            gather_neighbours_radius(source, current, R, neighbourhood, &mut neighbours);
+
+            f(&neighbours)
+        })
+    }
+
+    /// Applies a function to each cell and the cells within `R` steps of it providing the
+    /// position.
+    ///
+    /// The provided function is called with the current point and the values of the neighbourhood
+    /// in the order given by `gather_neighbours_radius`.
+    ///
+    /// `source` - The source grid from which to read data.
+    /// `neighbourhood` - The shape of the neighbourhood.
+    /// `f` - The function to apply.
+    fn set_from_neighbours_radius_with_position<
+                const R: usize,
+                U,
+                G: SurfaceGrid<U, Point = Self::Point>,
+                F: FnMut(&Self::Point, &[&U]) -> T
+            >(&mut self, source: &G, neighbourhood: Neighbourhood, mut f: F) {
+        let mut neighbours = Vec::with_capacity(neighbourhood.len(R));
+
+        self.set_from_fn(|current| {
+            gather_neighbours_radius(source, current, R, neighbourhood, &mut neighbours);
+
+            f(current, &neighbours)
+        })
+    }
+
+    /// Applies a function to each cell and the cells within `R` steps of it in parallel providing
+    /// the position.
+    ///
+    /// The provided function is called with the current point and the values of the neighbourhood
+    /// in the order given by `gather_neighbours_radius`.
+    ///
+    /// `source` - The source grid from which to read data.
+    /// `neighbourhood` - The shape of the neighbourhood.
+    /// `f` - The function to apply.
+    fn set_from_neighbours_radius_par_with_position<
+                const R: usize,
+                U,
+                G: SurfaceGrid<U, Point = Self::Point> + Sync,
+                F: Fn(&Self::Point, &[&U]) -> T + Send + Sync
+            >(&mut self, source: &G, neighbourhood: Neighbourhood, f: F) where T: Send + Sync {
+        self.set_from_fn_par(|current| {
+            let mut neighbours = Vec::with_capacity(neighbourhood.len(R));
+            gather_neighbours_radius(source, current, R, neighbourhood, &mut neighbours);
+
+            f(current, &neighbours)
+        })
+    }
+
+    /// Applies a function to each cell and combines the results into a single value.
+    ///
+    /// This avoids allocating an intermediate grid when only an aggregate value is required.
+    ///
+    /// - `map` - The function to apply to each cell.
+    /// - `identity` - The initial value of the reduction.
+    /// - `reduce` - The function used to combine two values.
+    fn map_reduce<A, M: Fn(&T) -> A, R: Fn(A, A) -> A>(&self, map: M, identity: A, reduce: R) -> A {
+        self.iter()
+            .map(|(_, value)| map(value))
+            .fold(identity, reduce)
+    }
+
+    /// Applies a function to each cell in parallel and combines the results into a single value.
+    ///
+    /// As the reduction happens in parallel the identity may be used more than once and the results
+    /// may be combined in any order so `reduce` should be associative.
+    ///
+    /// - `map` - The function to apply to each cell.
+    /// - `identity` - The initial value of the reduction.
+    /// - `reduce` - The function used to combine two values.
+    fn par_map_reduce<
+                A: Clone + Send + Sync,
+                M: Fn(&T) -> A + Send + Sync,
+                R: Fn(A, A) -> A + Send + Sync
+            >(&self, map: M, identity: A, reduce: R) -> A where T: Send + Sync {
+        self.par_iter()
+            .map(|(_, value)| map(value))
+            .reduce(|| identity.clone(), reduce)
+    }
+
+    /// Combines the points and values in this grid into a single value.
+    ///
+    /// The function is called for each cell in the same order as `iter`.
+    ///
+    /// - `init` - The initial value.
+    /// - `f` - The function used to combine the current value with a cell.
+    fn fold<A, F: FnMut(A, &Self::Point, &T) -> A>(&self, init: A, mut f: F) -> A {
+        self.iter()
+            .fold(init, |acc, (point, value)| f(acc, &point, value))
+    }
+
+    /// Combines the points and values in this grid into a single value in parallel.
+    ///
+    /// Each thread folds the cells it visits starting from a value created by `identity` and the
+    /// results are then combined with `reduce`. The cells are folded in an unspecified order so
+    /// `reduce` should be associative.
+    ///
+    /// - `identity` - The function used to create the initial value of each fold.
+    /// - `fold` - The function used to combine a value with a cell.
+    /// - `reduce` - The function used to combine two values.
+    fn par_fold<
+                A: Send,
+                I: Fn() -> A + Send + Sync,
+                F: Fn(A, &Self::Point, &T) -> A + Send + Sync,
+                R: Fn(A, A) -> A + Send + Sync
+            >(&self, identity: I, fold: F, reduce: R) -> A where T: Send + Sync {
+        self.par_iter()
+            .fold(&identity, |acc, (point, value)| fold(acc, &point, value))
+            .reduce(&identity, reduce)
+    }
+
+    /// Sums the values in this grid.
+    fn sum(&self) -> T where T: Clone + Add<Output = T> + Default {
+        self.iter()
+            .fold(T::default(), |sum, (_, value)| sum + value.clone())
+    }
+
+    /// Sums the values in this grid in parallel.
+    ///
+    /// The order in which the values are added depends on how the work is split between threads so
+    /// floating point results may differ between runs. Use `deterministic_sum` when the result
+    /// needs to be reproducible.
+    fn par_sum(&self) -> T where T: Clone + Add<Output = T> + Default + Send + Sync {
+        self.par_iter()
+            .fold(T::default, |sum, (_, value)| sum + value.clone())
+            .reduce(T::default, |a, b| a + b)
+    }
+
+    /// Sums the values in this grid in parallel so that the result is the same regardless of the
+    /// number of threads used.
+    ///
+    /// The values are added using pairwise summation in the order given by `to_index`. Since the
+    /// order of the additions is fixed, floating point results are reproducible, and pairwise
+    /// summation also has a smaller rounding error than adding the values one at a time.
+    fn deterministic_sum(&self) -> T where T: Clone + Add<Output = T> + Default + Send + Sync {
+        pairwise_sum(&self.par_map_to_vec(|_, value| value.clone()))
+    }
+
+    /// Counts the cells with a different value in another grid.
+    ///
+    /// A count of `0` means the grids are equal, which can be used to detect when repeatedly
+    /// updating a grid has converged without collecting the differences.
+    ///
+    /// - `other` - The grid to compare with.
+    fn changed_cells(&self, other: &Self) -> usize where T: PartialEq {
+        self.iter()
+            .filter(|(point, value)| *value != other.at(point))
+            .count()
+    }
+
+    /// Counts the cells with a different value in another grid in parallel.
+    ///
+    /// This gives the same result as `changed_cells`.
+    ///
+    /// - `other` - The grid to compare with.
+    fn par_changed_cells(&self, other: &Self) -> usize where T: PartialEq + Send + Sync, Self: Sync {
+        self.par_iter()
+            .filter(|(point, value)| *value != other.at(point))
+            .count()
+    }
+
+    /// Gets the value of the cell nearest to the specified position in 3D space.
+    ///
+    /// The position does not need to lie on the surface of the sphere as only its direction from
+    /// the centre of the sphere is used.
+    ///
+    /// - `x` - The X position.
+    /// - `y` - The Y position.
+    /// - `z` - The Z position.
+    fn sample_nearest_3d(&self, x: f64, y: f64, z: f64) -> &T where Self::Point: SpherePoint {
+        &self[Self::Point::nearest_point(x, y, z)]
+    }
+
+    /// Iterates over the points in this grid that lie within a range of geographic coordinates.
+    ///
+    /// A point lies within the range if the centre of its cell, given by
+    /// `SpherePoint::center_sphere_coordinates`, is within both ranges. Both ranges are inclusive. If the start of the longitude
+    /// range is greater than the end then the range wraps around the sphere so a range of
+    /// `(6.0, 0.5)` covers the longitudes either side of 0.
+    ///
+    /// - `lat_range` - The range of latitudes in radians.
+    /// - `lon_range` - The range of longitudes in radians.
+    fn points_in_region(&self, lat_range: (f64, f64), lon_range: (f64, f64)) -> impl Iterator<Item = Self::Point> where Self::Point: SpherePoint {
+        self.points()
+            .filter(move |point| sphere::in_region(point, lat_range, lon_range))
+    }
+
+    /// Extracts the lines where the value of the cells crosses a threshold.
+    ///
+    /// This uses marching squares between the centres of the cells. The squares continue across
+    /// the seams of the grid, except where three cells meet at the corners of a cube which leaves a
+    /// small gap in the lines. Each line is a list of latitudes and longitudes in radians and the
+    /// first and last positions of a closed loop are the same.
+    ///
+    /// - `threshold` - The value to extract the lines at.
+    /// - `key` - The function used to get the value of each cell.
+    fn contour<K: Fn(&T) -> f64>(&self, threshold: f64, key: K) -> Vec<Vec<(f64, f64)>> where Self::Point: SpherePoint, Self: Sized {
+        sphere::contour(self, threshold, key)
+    }
+
+    /// Sets every cell within a range of geographic coordinates to a value.
+    ///
+    /// The cells are the same as those given by `points_in_region` so the longitude range wraps
+    /// around the sphere if its start is greater than its end.
+    ///
+    /// - `lat_range` - The range of latitudes in radians.
+    /// - `lon_range` - The range of longitudes in radians.
+    /// - `value` - The value to set the cells to.
+    fn fill_region(&mut self, lat_range: (f64, f64), lon_range: (f64, f64), value: T) where Self::Point: SpherePoint, T: Clone {
+        let points: Vec<_> = self.points_in_region(lat_range, lon_range).collect();
+
+        for point in points {
+            self[point] = value.clone();
+        }
+    }
+
+    /// Finds the centre of the cells with values matching a predicate.
+    ///
+    /// The centres of the matching cells are averaged in 3D space and the result is projected back
+    /// onto the surface of the sphere, which avoids the problems with averaging latitudes and
+    /// longitudes across the antimeridian or near the poles.
+    ///
+    /// This returns `None` if no cells match or if the matching cells are spread evenly enough that
+    /// their average position is the centre of the sphere.
+    ///
+    /// - `predicate` - The function that decides which cells to include.
+    /// - `scale` - The radius of the sphere.
+    fn centroid<F: Fn(&T) -> bool>(&self, predicate: F, scale: f64) -> Option<Self::Point> where Self::Point: SpherePoint {
+        let (count, (x, y, z)) = self.iter()
+            .filter(|(_, value)| predicate(value))
+            .map(|(point, _)| point.center_position(scale))
+            .fold((0, (0.0, 0.0, 0.0)), |(count, (x, y, z)), (px, py, pz)| {
+                (count + 1, (x + px, y + py, z + pz))
+            });
+
+        let length = (x * x + y * y + z * z).sqrt();
+
+        if count == 0 || length <= f64::EPSILON * scale * count as f64 {
+            return None;
+        }
+
+        Some(Self::Point::from_geographic((y / length).asin(), x.atan2(z)))
+    }
+
+    /// Visits the cells of this grid in order of the lowest total cost to reach them from a
+    /// starting point.
+    ///
+    /// This is a Dijkstra expansion over the direct neighbours of each cell where moving into a
+    /// cell costs the value returned by `cost` for that cell. The start has a cost of `0.0` and
+    /// each cell is visited once along with the lowest total cost to reach it. Cells with a cost of
+    /// `f64::INFINITY` cannot be entered. Costs should not be negative.
+    ///
+    /// - `start` - The point to start from.
+    /// - `cost` - The function giving the cost to move into each cell.
+    fn expand_by_cost<F: Fn(&Self::Point) -> f64>(&self, start: &Self::Point, cost: F) -> impl Iterator<Item = (Self::Point, f64)> {
+        let mut visited = HashSet::new();
+        let mut queue = BinaryHeap::from([CostEntry {
+            cost: 0.0,
+            point: start.clone(),
+        }]);
+
+        std::iter::from_fn(move || {
+            while let Some(CostEntry { cost: total, point }) = queue.pop() {
+                if !visited.insert(self.to_index(&point)) {
+                    continue;
+                }
+
+                let neighbours = [point.checked_up(), point.checked_down(), point.checked_left(), point.checked_right()];
+
+                for neighbour in neighbours.into_iter().flatten() {
+                    let step = cost(&neighbour);
+
+                    if step < f64::INFINITY && !visited.contains(&self.to_index(&neighbour)) {
+                        queue.push(CostEntry {
+                            cost: total + step,
+                            point: neighbour,
+                        });
+                    }
+                }
+
+                return Some((point, total));
+            }
+
+            None
+        })
+    }
+
+    /// Gets a reference to the value of a cell, or `None` if the point does not lie on the grid.
+    ///
+    /// Points of the grids in this crate can only be created on the grid so this always returns
+    /// `Some` for them. Use the `try_new` constructor of the point type to check a position that
+    /// comes from user input before looking it up.
+    ///
+    /// - `point` - The point to get the value of.
+    fn get(&self, point: &Self::Point) -> Option<&T> {
+        Some(self.at(point))
+    }
+
+    /// Gets a mutable reference to the value of a cell, or `None` if the point does not lie on the
+    /// grid.
+    ///
+    /// This always returns `Some` for the grids in this crate in the same way as `get`.
+    ///
+    /// - `point` - The point to get the value of.
+    fn get_mut(&mut self, point: &Self::Point) -> Option<&mut T> {
+        Some(self.at_mut(point))
+    }
+
+    /// Gets a reference to the value of a cell from a reference to its point.
+    ///
+    /// This is the same as indexing the grid with the point but does not take the point by value.
+    /// By default the point is cloned to index the grid, so grids that can be indexed by a
+    /// reference to their point override this to avoid the clone. The provided methods of this
+    /// trait read cells through it.
+    ///
+    /// - `point` - The point to get the value of.
+    fn at(&self, point: &Self::Point) -> &T {
+        &self[point.clone()]
+    }
+
+    /// Gets a mutable reference to the value of a cell from a reference to its point.
+    ///
+    /// This is the same as `at` for mutable references.
+    ///
+    /// - `point` - The point to get the value of.
+    fn at_mut(&mut self, point: &Self::Point) -> &mut T {
+        &mut self[point.clone()]
+    }
+
+    /// Copies the values in this grid into a `Vec` in the same order as `points`.
+    ///
+    /// Each grid documents this order so the `Vec` can be used to pass the data to other code.
+    /// `from_vec` converts the `Vec` back into a grid.
+    fn to_vec(&self) -> Vec<T> where T: Clone {
+        self.iter()
+            .map(|(_, value)| value.clone())
+            .collect()
+    }
+
+    /// Iterates over the points in this grid and their values.
+    ///
+    /// The points are visited in the same order as `points` and `into_iter`. Each grid documents
+    /// its order, which is stable. The iterator knows its exact length and can be reversed.
+    fn iter<'a>(&'a self) -> impl ExactSizeIterator<Item = (Self::Point, &'a T)> + DoubleEndedIterator where T: 'a;
+
+    /// Iterates over the points in this grid and their values in parallel.
+    ///
+    /// The iterator is indexed so that rayon can split it evenly between threads, and it visits
+    /// the points in the same order as `iter` when collected.
+    fn par_iter<'a>(&'a self) -> impl IndexedParallelIterator<Item = (Self::Point, &'a T)> where T: 'a + Send + Sync;
+
+    /// Iterates over the points in this grid and mutable references to their values in parallel.
+    ///
+    /// This updates each cell in place from its current value, which suits updates that do not
+    /// depend on the neighbours of a cell. Use `set_from_neighbours_par` for updates that do.
+    fn par_iter_mut<'a>(&'a mut self) -> impl IndexedParallelIterator<Item = (Self::Point, &'a mut T)> where T: 'a + Send + Sync;
+
+    /// Iterates over the points in this grid along with their values and the values of their
+    /// direct neighbours.
+    ///
+    /// The neighbours are given in the order: up, down, left, right. The points are visited in the
+    /// same order as `iter`.
+    fn iter_with_neighbours<'a>(&'a self) -> impl Iterator<Item = (Self::Point, &'a T, [&'a T; 4])> where T: 'a, Self: Sized {
+        self.iter()
+            .map(|(point, value)| {
+                let neighbours = gather_neighbours(self, &point);
+
+                (point, value, neighbours)
+            })
+    }
+
+    /// Iterates over the points in this grid along with their values and the values of their
+    /// direct neighbours in parallel.
+    ///
+    /// The neighbours are given in the order: up, down, left, right.
+    fn par_iter_with_neighbours<'a>(&'a self) -> impl ParallelIterator<Item = (Self::Point, &'a T, [&'a T; 4])> where T: 'a + Send + Sync, Self: Sized + Sync {
+        self.par_iter()
+            .map(|(point, value)| {
+                let neighbours = gather_neighbours(self, &point);
+
+                (point, value, neighbours)
+            })
+    }
+
+    /// Gets the direct neighbours of a point along with their values.
+    ///
+    /// The neighbours are given in the order: up, down, left, right, which is the order of
+    /// `GridPoint::neighbours`.
+    ///
+    /// - `point` - The point to get the neighbours of.
+    fn neighbours_of<'a>(&'a self, point: &Self::Point) -> [(Self::Point, &'a T); 4] {
+        point.neighbours().map(|neighbour| {
+            let value = self.at(&neighbour);
+
+            (neighbour, value)
+        })
+    }
+
+    /// Iterates over the points in this grid along with their values in this grid and another grid
+    /// with the same point type.
+    ///
+    /// Both grids are iterated in lockstep so when they visit the points in the same order, as any
+    /// two grids of the same type do, neither grid is indexed. If the other grid visits a point in
+    /// a different order then its value is looked up by indexing instead. The points are visited
+    /// in the same order as `iter`.
+    ///
+    /// - `other` - The grid to iterate alongside this one.
+    fn zip<'a, U: 'a, G: SurfaceGrid<U, Point = Self::Point>>(&'a self, other: &'a G) -> impl Iterator<Item = (Self::Point, &'a T, &'a U)> where T: 'a {
+        self.iter()
+            .zip(other.iter())
+            .map(|((point, value), (other_point, other_value))| {
+                let other_value = if point == other_point {
+                    other_value
+                } else {
+                    other.at(&point)
+                };
+
+                (point, value, other_value)
+            })
+    }
+
+    /// Creates a new grid by applying a function to the values of each point in this grid and
+    /// another grid with the same point type.
+    ///
+    /// The grids are iterated in lockstep in the same way as `zip` and the new grid can be any
+    /// grid with the same point type, for example a grid with the same shape as this one holding
+    /// a different type of value.
+    ///
+    /// The provided function is called with the arguments: position, value, other value.
+    ///
+    /// - `other` - The grid to combine with this one.
+    /// - `f` - The function to apply.
+    fn zip_map<
+                U, V, G: SurfaceGrid<U, Point = Self::Point>, O: SurfaceGrid<V, Point = Self::Point>,
+                F: FnMut(&Self::Point, &T, &U) -> V
+            >(&self, other: &G, mut f: F) -> O {
+        let mut values = self.zip(other);
+
+        O::from_fn(|point| match values.next() {
+            Some((zip_point, value, other_value)) if &zip_point == point => f(point, value, other_value),
+            _ => f(point, self.at(point), other.at(point)),
         })
     }
 
-    /// Iterates over the points in this grid and their values.
-    fn iter<'a>(&'a self) -> impl Iterator<Item = (Self::Point, &'a T)> where T: 'a;
-
-    /// Iterates over the points in this grid and their values in parallel.
-    fn par_iter<'a>(&'a self) -> impl ParallelIterator<Item = (Self::Point, &'a T)> where T: 'a + Send + Sync;
-
     /// Iterates over the points in this grid.
-    fn points(&self) -> impl Iterator<Item = Self::Point>;
+    ///
+    /// The points are visited in the same order as `iter` and `into_iter`. The iterator knows its
+    /// exact length and can be reversed.
+    fn points(&self) -> impl ExactSizeIterator<Item = Self::Point> + DoubleEndedIterator;
 
     /// Iterates over the points in this grid in parallel.
-    fn par_points(&self) -> impl ParallelIterator<Item = Self::Point>;
+    fn par_points(&self) -> impl IndexedParallelIterator<Item = Self::Point>;
+
+    /// Applies a function to each cell in parallel and collects the results into a `Vec`.
+    ///
+    /// The `Vec` is allocated once with the exact number of cells and the result for each point is
+    /// stored at the index given by `to_index` so the order is the same as `iter`.
+    ///
+    /// - `f` - The function to apply.
+    fn par_map_to_vec<U: Send, F: Fn(&Self::Point, &T) -> U + Send + Sync>(&self, f: F) -> Vec<U> where T: Send + Sync {
+        let mut result = Vec::with_capacity(self.len());
+
+        self.par_iter()
+            .map(|(point, value)| f(&point, value))
+            .collect_into_vec(&mut result);
+
+        result
+    }
+
+    /// Gets the index of a point in the order that `points` visits them.
+    ///
+    /// Indices start at 0 and are less than the number of cells in the grid. By default the point
+    /// is searched for in `points`, which takes time proportional to the number of cells, so grids
+    /// should override this where the index can be calculated.
+    ///
+    /// Every grid in this crate calculates the index. Methods such as `apply_updates_par` and
+    /// `contour`, and wrappers such as `LazyGrid`, `LayeredGrid`, `GridView` and `Partitioning`,
+    /// use the `to_index` of the grid they are given, so they only search when that grid does not
+    /// override this.
+    ///
+    /// # Panics
+    /// By default this panics if the point is not in the grid.
+    ///
+    /// - `point` - The point to get the index of.
+    fn to_index(&self, point: &Self::Point) -> usize {
+        self.points()
+            .position(|other| other == *point)
+            .expect("point is not in the grid")
+    }
+
+    /// Gets mutable references to the values of every cell as a single slice in the order of
+    /// `to_index`.
+    ///
+    /// This returns `None` by default and for grids that do not keep all of their values in one
+    /// slice, such as grids that keep each face separately.
+    fn contiguous_values_mut(&mut self) -> Option<&mut [T]> {
+        None
+    }
+
+    /// Gets the area of the surface covered by this grid.
+    ///
+    /// This uses the same scale as `GridPoint::position` so for a grid covering a whole sphere
+    /// this is `4 * PI * scale * scale`.
+    ///
+    /// This has no default as the area cannot be found from the points of a grid alone, so grids
+    /// outside of this crate must implement it. This is a breaking change from version 0.3.1.
+    ///
+    /// - `scale` - The scale of the 3D object.
+    fn surface_area(&self, scale: f64) -> f64;
+
+    /// Gets a description of the shape and size of this grid.
+    ///
+    /// By default this describes an `Other` surface made of a single row holding every cell, so
+    /// grids should override it to give their shape.
+    fn topology(&self) -> Topology {
+        Topology::uniform(Surface::Other, 1, self.len(), 1)
+    }
+
+    /// Gets the number of cells in this grid.
+    ///
+    /// This is the number of points visited by `points` and one more than the largest index
+    /// returned by `to_index`. By default this is the length of `points`.
+    fn len(&self) -> usize {
+        self.points().len()
+    }
+
+    /// Checks if this grid has no cells.
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
 }
 
+/// A surface grid that can be shared between threads.
+///
+/// This is implemented for every `SurfaceGrid` that is `Send` and `Sync`, which includes all of
+/// the grids in this crate when `T` is `Send` and `Sync`. Such a grid can be shared between
+/// threads through a reference or an `Arc` to read it concurrently. Each grid documents when it
+/// is `Send` and `Sync` under its `Thread Safety` heading.
+pub trait ThreadSafeGrid<T> : SurfaceGrid<T> + Send + Sync {}
+
+impl <T, G: SurfaceGrid<T> + Send + Sync> ThreadSafeGrid<T> for G {}
+
 /// A point on a surface grid.
 /// 
 /// A type implementing this trait should ensure that the following conditions are met:
@@ -355,9 +1664,719 @@ pub trait GridPoint : Eq + PartialEq + Clone {
     /// Gets the point that is immediately to the right of this grid point.
     fn right(&self) -> Self;
 
+    /// Gets the point that is immediately above this grid point if there is one.
+    ///
+    /// This returns `None` if moving up would leave a bounded surface. By default this is
+    /// `Some(self.up())` for surfaces that wrap.
+    fn checked_up(&self) -> Option<Self> {
+        Some(self.up())
+    }
+
+    /// Gets the point that is immediately below this grid point if there is one.
+    ///
+    /// This returns `None` if moving down would leave a bounded surface. By default this is
+    /// `Some(self.down())` for surfaces that wrap.
+    fn checked_down(&self) -> Option<Self> {
+        Some(self.down())
+    }
+
+    /// Gets the point that is immediately to the left of this grid point if there is one.
+    ///
+    /// This returns `None` if moving left would leave a bounded surface. By default this is
+    /// `Some(self.left())` for surfaces that wrap.
+    fn checked_left(&self) -> Option<Self> {
+        Some(self.left())
+    }
+
+    /// Gets the point that is immediately to the right of this grid point if there is one.
+    ///
+    /// This returns `None` if moving right would leave a bounded surface. By default this is
+    /// `Some(self.right())` for surfaces that wrap.
+    fn checked_right(&self) -> Option<Self> {
+        Some(self.right())
+    }
+
+    /// Gets the point that is diagonally above and to the left of this grid point.
+    ///
+    /// By default this is `self.up().left()`.
+    fn up_left(&self) -> Self {
+        self.up().left()
+    }
+
+    /// Gets the point that is diagonally above and to the right of this grid point.
+    ///
+    /// By default this is `self.up().right()`.
+    fn up_right(&self) -> Self {
+        self.up().right()
+    }
+
+    /// Gets the point that is diagonally below and to the left of this grid point.
+    ///
+    /// By default this is `self.down().left()`.
+    fn down_left(&self) -> Self {
+        self.down().left()
+    }
+
+    /// Gets the point that is diagonally below and to the right of this grid point.
+    ///
+    /// By default this is `self.down().right()`.
+    fn down_right(&self) -> Self {
+        self.down().right()
+    }
+
+    /// Gets the direct neighbours of this point.
+    ///
+    /// The neighbours are in the order: up, down, left, right.
+    fn neighbours(&self) -> [Self; 4] {
+        [self.up(), self.down(), self.left(), self.right()]
+    }
+
+    /// Gets the cells that the grid joins to this point by an edge or a corner.
+    ///
+    /// Each cell is given once and this point is never included, so there are fewer than eight
+    /// cells where fewer cells meet at a corner, such as the three cells at each corner of a cube.
+    /// This differs from the diagonal neighbours, which give some cells twice or give a cell that
+    /// does not touch this point at such corners. The cells follow `up`, `down`, `left` and
+    /// `right`, so they are only next to this point on the surface where those are.
+    ///
+    /// By default these are the diagonal neighbours in the order up_left, up, up_right, left,
+    /// right, down_left, down, down_right with this point and any repeated cells removed.
+    fn moore_neighbours(&self) -> impl Iterator<Item = Self> {
+        let mut neighbours: Vec<Self> = Vec::with_capacity(8);
+
+        for point in [
+            self.up_left(), self.up(), self.up_right(),
+            self.left(), self.right(),
+            self.down_left(), self.down(), self.down_right(),
+        ] {
+            if point != *self && !neighbours.contains(&point) {
+                neighbours.push(point);
+            }
+        }
+
+        neighbours.into_iter()
+    }
+
+    /// Gets the direct neighbour of this point in a direction.
+    ///
+    /// - `dir` - The direction of the neighbour.
+    fn neighbour(&self, dir: Direction) -> Self {
+        match dir {
+            Direction::Up => self.up(),
+            Direction::Down => self.down(),
+            Direction::Left => self.left(),
+            Direction::Right => self.right(),
+        }
+    }
+
+    /// Gets the point that is an offset away from this point.
+    ///
+    /// The point is found by moving `dy` steps down, or up when it is negative, and then `dx`
+    /// steps right, or left when it is negative. After each step the direction of travel is found
+    /// again from the direction that leads back, so the path keeps going straight where the grid's
+    /// notion of up changes, such as over a pole or across the edge of a face of a cube.
+    ///
+    /// - `dx` - The number of steps to the right.
+    /// - `dy` - The number of steps down.
+    fn offset(&self, dx: i32, dy: i32) -> Self {
+        let mut point = self.clone();
+        let mut forward = if dy < 0 { Direction::Up } else { Direction::Down };
+        let mut side = if dx < 0 { Direction::Left } else { Direction::Right };
+
+        for _ in 0..dy.unsigned_abs() {
+            (point, forward, side) = step_straight(&point, forward, side);
+        }
+
+        for _ in 0..dx.unsigned_abs() {
+            (point, side, forward) = step_straight(&point, side, forward);
+        }
+
+        point
+    }
+
     /// Gets the position of the point in 3D space.
     ///
     /// - `scale` - The scale of the 3D object.
     fn position(&self, scale: f64) -> (f64, f64, f64);
+
+    /// Gets the positions of the direct neighbours of this point in 3D space.
+    ///
+    /// The positions are in the order: up, down, left, right.
+    ///
+    /// - `scale` - The scale of the 3D object.
+    fn neighbour_positions(&self, scale: f64) -> [(f64, f64, f64); 4] {
+        [
+            self.up().position(scale),
+            self.down().position(scale),
+            self.left().position(scale),
+            self.right().position(scale),
+        ]
+    }
+
+    /// Gets the positions of the direct neighbours of this point including diagonals in 3D space.
+    ///
+    /// The positions are in the order: up_left, up, up_right, left, right, down_left, down,
+    /// down_right.
+    ///
+    /// - `scale` - The scale of the 3D object.
+    fn diagonal_neighbour_positions(&self, scale: f64) -> [(f64, f64, f64); 8] {
+        [
+            self.up_left().position(scale),
+            self.up().position(scale),
+            self.up_right().position(scale),
+            self.left().position(scale),
+            self.right().position(scale),
+            self.down_left().position(scale),
+            self.down().position(scale),
+            self.down_right().position(scale),
+        ]
+    }
+
+    /// Determines whether moving from this point in a direction crosses a seam that reverses the
+    /// orientation of the surface.
+    ///
+    /// This can only be true for non-orientable surfaces such as a Möbius strip or Klein bottle.
+    /// Orientable surfaces, such as those of the sphere grids, always return false even where the
+    /// grid's own notion of up changes direction.
+    ///
+    /// - `dir` - The direction to move in.
+    fn neighbour_flips_orientation(&self, dir: Direction) -> bool {
+        let _ = dir;
+
+        false
+    }
+}
+
+/// A direction of movement from a point on a surface grid.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Direction {
+    /// The direction given by `GridPoint::up`.
+    Up,
+    /// The direction given by `GridPoint::down`.
+    Down,
+    /// The direction given by `GridPoint::left`.
+    Left,
+    /// The direction given by `GridPoint::right`.
+    Right,
+}
+
+impl Direction {
+    /// Gets the direction that points the other way.
+    pub const fn opposite(self) -> Self {
+        match self {
+            Self::Up => Self::Down,
+            Self::Down => Self::Up,
+            Self::Left => Self::Right,
+            Self::Right => Self::Left,
+        }
+    }
+
+    /// Gets the number of quarter turns clockwise from up to this direction.
+    const fn quarter_turns(self) -> u8 {
+        match self {
+            Self::Up => 0,
+            Self::Right => 1,
+            Self::Down => 2,
+            Self::Left => 3,
+        }
+    }
+
+    /// Gets the direction that is a number of quarter turns clockwise from up.
+    ///
+    /// - `turns` - The number of quarter turns.
+    const fn from_quarter_turns(turns: u8) -> Self {
+        match turns % 4 {
+            0 => Self::Up,
+            1 => Self::Right,
+            2 => Self::Down,
+            _ => Self::Left,
+        }
+    }
+}
+
+/// The shape of the neighbourhood of cells around a point.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Neighbourhood {
+    /// The square of cells that are at most the radius away along both axes.
+    Moore,
+    /// The diamond of cells where the steps along both axes add up to at most the radius.
+    VonNeumann,
+}
+
+impl Neighbourhood {
+    /// Gets the number of cells in a neighbourhood of this shape including the current cell.
+    ///
+    /// - `radius` - The radius of the neighbourhood.
+    pub const fn len(&self, radius: usize) -> usize {
+        match self {
+            Self::Moore => (2 * radius + 1) * (2 * radius + 1),
+            Self::VonNeumann => 2 * radius * (radius + 1) + 1,
+        }
+    }
+}
+
+/// The shape of the surface that a grid covers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Surface {
+    /// A whole sphere.
+    Sphere,
+    /// A cap around a pole of a sphere with a boundary at its rim.
+    SphereCap,
+    /// A bounded rectangle.
+    Plane,
+    /// A torus, where both axes wrap.
+    Torus,
+    /// A Klein bottle, where one pair of edges is joined reversed.
+    KleinBottle,
+    /// A cylinder, with or without caps on its ends.
+    Cylinder,
+    /// A cylinder with a hemisphere on each end.
+    Capsule,
+    /// A surface that is not described by the other variants, as given by the default
+    /// `SurfaceGrid::topology`.
+    Other,
+}
+
+/// A description of the shape and size of a grid.
+///
+/// This is given by `SurfaceGrid::topology` so that generic code can report the size of a grid or
+/// allocate buffers without knowing the constant parameters of the grid type.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Topology {
+    /// The shape of the surface covered by the grid.
+    pub surface: Surface,
+    /// The number of faces that the grid is made of, such as the 6 faces of a `CubeSphereGrid`.
+    pub face_count: usize,
+    /// The width of the widest face in cells.
+    pub width: usize,
+    /// The height of the tallest face in cells.
+    pub height: usize,
+    /// The number of cells in the grid.
+    pub len: usize,
+}
+
+impl Topology {
+    /// Creates a new `Topology` for a grid made of faces that all have the same size.
+    ///
+    /// - `surface` - The shape of the surface covered by the grid.
+    /// - `face_count` - The number of faces.
+    /// - `width` - The width of each face in cells.
+    /// - `height` - The height of each face in cells.
+    pub const fn uniform(surface: Surface, face_count: usize, width: usize, height: usize) -> Self {
+        Self {
+            surface,
+            face_count,
+            width,
+            height,
+            len: face_count * width * height,
+        }
+    }
+}
+
+/// The order in which the diagonal neighbour methods pass a cell and its neighbours to their
+/// function.
+///
+/// Each entry gives the vertical and horizontal directions of a neighbour from the current cell,
+/// with `None` meaning no movement, so the order is: up_left, up, up_right, left, current, right,
+/// down_left, down, down_right. The diagonal neighbours are the ones given by `GridPoint::up_left`
+/// and the other diagonal methods.
+pub const DIAGONAL_ORDER: [(Option<Direction>, Option<Direction>); 9] = [
+    (Some(Direction::Up), Some(Direction::Left)),
+    (Some(Direction::Up), None),
+    (Some(Direction::Up), Some(Direction::Right)),
+    (None, Some(Direction::Left)),
+    (None, None),
+    (None, Some(Direction::Right)),
+    (Some(Direction::Down), Some(Direction::Left)),
+    (Some(Direction::Down), None),
+    (Some(Direction::Down), Some(Direction::Right)),
+];
+
+/// Gets references to the values of the direct neighbours of a point.
+///
+/// This is the neighbour lookup used by the default `map_neighbours` and `set_from_neighbours`
+/// methods, including their parallel and `_with_position` versions, which allows custom grids and
+/// algorithms to reuse it.
+///
+/// The neighbours are returned in the order: up, down, left, right.
+///
+/// - `grid` - The grid to read the values from.
+/// - `point` - The point to get the neighbours of.
+///
+/// # Examples
+/// A grid that overrides `map_neighbours` can still use this to look up the neighbours.
+/// ```
+/// use std::{f64::consts::TAU, ops::{Index, IndexMut}, vec};
+///
+/// use rayon::prelude::*;
+/// use surface_grid::{gather_neighbours, GridPoint, SurfaceGrid};
+///
+/// /// A ring of 8 cells where moving up or down stays in the same cell.
+/// struct Ring<T>([T; 8]);
+///
+/// #[derive(Clone, Copy, PartialEq, Eq)]
+/// struct RingPoint(usize);
+///
+/// impl GridPoint for RingPoint {
+///     fn up(&self) -> Self {
+///         *self
+///     }
+///
+///     fn down(&self) -> Self {
+///         *self
+///     }
+///
+///     fn left(&self) -> Self {
+///         RingPoint((self.0 + 7) % 8)
+///     }
+///
+///     fn right(&self) -> Self {
+///         RingPoint((self.0 + 1) % 8)
+///     }
+///
+///     fn position(&self, scale: f64) -> (f64, f64, f64) {
+///         let angle = self.0 as f64 * TAU / 8.0;
+///
+///         (angle.cos() * scale, 0.0, angle.sin() * scale)
+///     }
+/// }
+///
+/// impl <T> SurfaceGrid<T> for Ring<T> {
+///     type Point = RingPoint;
+///
+///     fn from_fn<F: FnMut(&RingPoint) -> T>(mut f: F) -> Self {
+///         Ring(std::array::from_fn(|i| f(&RingPoint(i))))
+///     }
+///
+///     fn from_vec(values: Vec<T>) -> Option<Self> {
+///         values.try_into().ok().map(Ring)
+///     }
+///
+///     fn from_fn_par<F: Fn(&RingPoint) -> T + Send + Sync>(f: F) -> Self where T: Send + Sync {
+///         Self::from_fn(f)
+///     }
+///
+///     fn try_from_fn<X, F: FnMut(&RingPoint) -> Result<T, X>>(mut f: F) -> Result<Self, X> {
+///         let values: Vec<_> = (0..8).map(|i| f(&RingPoint(i))).collect::<Result<_, _>>()?;
+///
+///         Ok(Self::from_vec(values).unwrap())
+///     }
+///
+///     fn try_from_fn_par<X: Send, F: Fn(&RingPoint) -> Result<T, X> + Send + Sync>(f: F) -> Result<Self, X> where T: Send + Sync {
+///         Self::try_from_fn(f)
+///     }
+///
+///     fn set_from_fn<F: FnMut(&RingPoint) -> T>(&mut self, mut f: F) {
+///         for (i, value) in self.0.iter_mut().enumerate() {
+///             *value = f(&RingPoint(i));
+///         }
+///     }
+///
+///     fn set_from_fn_par<F: Fn(&RingPoint) -> T + Send + Sync>(&mut self, f: F) where T: Send + Sync {
+///         self.set_from_fn(f)
+///     }
+///
+///     fn iter<'a>(&'a self) -> impl ExactSizeIterator<Item = (RingPoint, &'a T)> + DoubleEndedIterator where T: 'a {
+///         self.points().zip(&self.0)
+///     }
+///
+///     fn par_iter<'a>(&'a self) -> impl IndexedParallelIterator<Item = (RingPoint, &'a T)> where T: 'a + Send + Sync {
+///         self.par_points().zip(self.0.par_iter())
+///     }
+///
+///     fn par_iter_mut<'a>(&'a mut self) -> impl IndexedParallelIterator<Item = (RingPoint, &'a mut T)> where T: 'a + Send + Sync {
+///         (0..8).into_par_iter().map(RingPoint).zip(self.0.par_iter_mut())
+///     }
+///
+///     fn points(&self) -> impl ExactSizeIterator<Item = RingPoint> + DoubleEndedIterator {
+///         (0..8).map(RingPoint)
+///     }
+///
+///     fn par_points(&self) -> impl IndexedParallelIterator<Item = RingPoint> {
+///         (0..8).into_par_iter().map(RingPoint)
+///     }
+///
+///     fn surface_area(&self, _scale: f64) -> f64 {
+///         0.0
+///     }
+///
+///     fn map_neighbours<F: FnMut(&T, &T, &T, &T, &T) -> T>(&self, mut f: F) -> Self {
+///         Self::from_fn(|point| {
+///             let [up, down, left, right] = gather_neighbours(self, point);
+///
+///             f(&self[*point], up, down, left, right)
+///         })
+///     }
+/// }
+///
+/// impl <T> Index<RingPoint> for Ring<T> {
+///     type Output = T;
+///
+///     fn index(&self, index: RingPoint) -> &T {
+///         &self.0[index.0]
+///     }
+/// }
+///
+/// impl <T> IndexMut<RingPoint> for Ring<T> {
+///     fn index_mut(&mut self, index: RingPoint) -> &mut T {
+///         &mut self.0[index.0]
+///     }
+/// }
+///
+/// impl <T> IntoIterator for Ring<T> {
+///     type Item = (RingPoint, T);
+///
+///     type IntoIter = vec::IntoIter<Self::Item>;
+///
+///     fn into_iter(self) -> Self::IntoIter {
+///         (0..8).map(RingPoint).zip(self.0).collect::<Vec<_>>().into_iter()
+///     }
+/// }
+///
+/// let ring = Ring::from_fn(|point| point.0 as u32);
+///
+/// assert_eq!([&0, &0, &7, &1], gather_neighbours(&ring, &RingPoint(0)));
+///
+/// let sums = ring.map_neighbours(|_, _, _, left, right| left + right);
+///
+/// assert_eq!(8, sums[RingPoint(0)]);
+/// assert_eq!(6, sums[RingPoint(3)]);
+/// ```
+pub fn gather_neighbours<'a, G: SurfaceGrid<T>, T>(grid: &'a G, point: &G::Point) -> [&'a T; 4] {
+    [&grid[point.up()], &grid[point.down()], &grid[point.left()], &grid[point.right()]]
+}
+
+/// Gets references to the values of the neighbours of a point including diagonals.
+///
+/// This is the neighbour lookup used by the default `map_neighbours_diagonals` and
+/// `set_from_neighbours_diagonals` methods, including their parallel and `_with_position` versions.
+///
+/// The neighbours are returned in the order: up_left, up, up_right, left, right, down_left, down,
+/// down_right.
+///
+/// - `grid` - The grid to read the values from.
+/// - `point` - The point to get the neighbours of.
+pub fn gather_neighbours_diagonals<'a, G: SurfaceGrid<T>, T>(grid: &'a G, point: &G::Point) -> [&'a T; 8] {
+    [
+        &grid[point.up_left()], &grid[point.up()], &grid[point.up_right()],
+        &grid[point.left()], &grid[point.right()],
+        &grid[point.down_left()], &grid[point.down()], &grid[point.down_right()]
+    ]
+}
+
+/// Gets references to the values of the cells within a radius of a point.
+///
+/// This is the neighbour lookup used by the default `map_neighbours_radius` methods.
+///
+/// The values are written into `neighbours`, which is cleared first so that it can be reused
+/// between points. The rows are given from the furthest up to the furthest down and each row is
+/// given from left to right, so the current cell is in the middle. Each cell is found by moving up
+/// or down to its row and then left or right along it, keeping to straight lines in the same way
+/// as `GridPoint::offset`. This applies to every radius, so a radius of 1 with
+/// `Neighbourhood::Moore` can differ from the diagonal neighbours given by
+/// `gather_neighbours_diagonals` where the grid is not flat, such as at the corners of a cube.
+///
+/// - `grid` - The grid to read the values from.
+/// - `point` - The point at the centre of the neighbourhood.
+/// - `radius` - The number of steps from the point to the edge of the neighbourhood.
+/// - `neighbourhood` - The shape of the neighbourhood.
+/// - `neighbours` - The buffer to write the values into.
+pub fn gather_neighbours_radius<'a, G: SurfaceGrid<T>, T>(
+    grid: &'a G,
+    point: &G::Point,
+    radius: usize,
+    neighbourhood: Neighbourhood,
+    neighbours: &mut Vec<&'a T>,
+) {
+    neighbours.clear();
+
+    for dy in -(radius as isize)..=radius as isize {
+        let mut row = point.clone();
+        let mut forward = if dy < 0 { Direction::Up } else { Direction::Down };
+        let mut right = Direction::Right;
+
+        for _ in 0..dy.unsigned_abs() {
+            (row, forward, right) = step_straight(&row, forward, right);
+        }
+
+        let width = match neighbourhood {
+            Neighbourhood::Moore => radius,
+            Neighbourhood::VonNeumann => radius - dy.unsigned_abs(),
+        };
+
+        // The cells to the left are found from the nearest outwards so they are reversed after.
+        let start = neighbours.len();
+        let (mut left, mut left_dir, mut left_side) = (row.clone(), right.opposite(), forward);
+
+        for _ in 0..width {
+            (left, left_dir, left_side) = step_straight(&left, left_dir, left_side);
+            neighbours.push(grid.at(&left));
+        }
+
+        neighbours[start..].reverse();
+        neighbours.push(grid.at(&row));
+
+        let mut side = forward;
+
+        for _ in 0..width {
+            (row, right, side) = step_straight(&row, right, side);
+            neighbours.push(grid.at(&row));
+        }
+    }
+}
+
+/// Gets references to the values of the cells at a list of offsets from a point.
+///
+/// This is the lookup used by the default `map_stencil` methods. Each offset is given as `(dx, dy)`
+/// and is found with `GridPoint::offset`, so the stencil keeps its shape across the seams of the
+/// grid.
+///
+/// The values are written into `values` in the same order as `offsets`. It is cleared first so that
+/// it can be reused between points.
+///
+/// - `grid` - The grid to read the values from.
+/// - `point` - The point that the offsets are relative to.
+/// - `offsets` - The offsets of the cells to read.
+/// - `values` - The buffer to write the values into.
+pub fn gather_stencil<'a, G: SurfaceGrid<T>, T>(grid: &'a G, point: &G::Point, offsets: &[(i32, i32)], values: &mut Vec<&'a T>) {
+    values.clear();
+    values.extend(offsets.iter().map(|(dx, dy)| &grid[point.offset(*dx, *dy)]));
+}
+
+/// Gets the offsets of the cells in a square kernel in the order of its flattened weights.
+///
+/// # Panics
+/// Panics if `N` is even since the kernel has no centre.
+fn kernel_offsets<const N: usize>() -> Vec<(i32, i32)> {
+    assert!(N % 2 == 1, "a kernel must have an odd size so that it has a centre");
+
+    let radius = (N / 2) as i32;
+
+    (-radius..=radius)
+        .flat_map(|dy| (-radius..=radius).map(move |dx| (dx, dy)))
+        .collect()
+}
+
+/// Takes one step from a point while keeping to a straight line.
+///
+/// Returns the new point along with the directions, in the frame of the new point, of `forward`
+/// and `side`. The new direction of travel is the opposite of the direction that leads back to the
+/// old point, and `side` is turned by the same amount. It is then reversed where the step crosses a
+/// seam that reverses the orientation of the surface or where the grid mirrors its notion of left
+/// and right.
+///
+/// - `point` - The point to step from.
+/// - `forward` - The direction of travel.
+/// - `side` - A direction at right angles to the direction of travel.
+fn step_straight<P: GridPoint>(point: &P, forward: Direction, side: Direction) -> (P, Direction, Direction) {
+    let next = point.neighbour(forward);
+
+    // Steps that stay in place, such as at the edge of a bounded grid, keep the same directions.
+    if next == *point {
+        return (next, forward, side);
+    }
+
+    // The opposite direction is checked first so that the directions only change when they must.
+    let back = [0, 2, 1, 3].into_iter()
+        .map(|turns| Direction::from_quarter_turns(forward.quarter_turns() + 2 + turns))
+        .find(|dir| next.neighbour(*dir) == *point);
+
+    let Some(back) = back else {
+        return (next, forward, side);
+    };
+
+    let turned = back.opposite();
+    let turns = turned.quarter_turns() + 4 - forward.quarter_turns();
+    let mut next_side = Direction::from_quarter_turns(side.quarter_turns() + turns);
+
+    if point.neighbour_flips_orientation(forward) {
+        next_side = next_side.opposite();
+    }
+
+    // Grids that define their own diagonals when moving up or down, such as over the poles of
+    // `RectangleSphereGrid`, decide which side is which.
+    let diagonal = match (forward, side) {
+        (Direction::Up, Direction::Left) => Some(point.up_left()),
+        (Direction::Up, Direction::Right) => Some(point.up_right()),
+        (Direction::Down, Direction::Left) => Some(point.down_left()),
+        (Direction::Down, Direction::Right) => Some(point.down_right()),
+        _ => None,
+    };
+
+    if let Some(diagonal) = diagonal.filter(|diagonal| *diagonal != next.neighbour(side)) {
+        if next.neighbour(next_side.opposite()) == diagonal {
+            next_side = next_side.opposite();
+        }
+
+        return (next, turned, next_side);
+    }
+
+    // Otherwise the side is the one that leads next to the old point's side neighbour, which
+    // finds where the grid mirrors left and right, such as between some faces of a cube.
+    let beside = point.neighbour(side).neighbours();
+
+    if !beside.contains(&next.neighbour(next_side)) && beside.contains(&next.neighbour(next_side.opposite())) {
+        next_side = next_side.opposite();
+    }
+
+    (next, turned, next_side)
+}
+
+/// A point waiting to be visited by `SurfaceGrid::expand_by_cost`.
+///
+/// Entries are ordered so that the entry with the lowest cost is the greatest.
+struct CostEntry<P> {
+    /// The total cost to reach the point.
+    cost: f64,
+    /// The point to visit.
+    point: P,
+}
+
+impl <P> PartialEq for CostEntry<P> {
+    fn eq(&self, other: &Self) -> bool {
+        self.cost.total_cmp(&other.cost).is_eq()
+    }
+}
+
+impl <P> Eq for CostEntry<P> {}
+
+impl <P> PartialOrd for CostEntry<P> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl <P> Ord for CostEntry<P> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        other.cost.total_cmp(&self.cost)
+    }
+}
+
+/// Sums values by recursively summing each half so that the order of the additions only depends
+/// on the number of values.
+///
+/// - `values` - The values to sum.
+fn pairwise_sum<T: Clone + Add<Output = T> + Default + Send + Sync>(values: &[T]) -> T {
+    if values.len() <= 64 {
+        values.iter()
+            .fold(T::default(), |sum, value| sum + value.clone())
+    } else {
+        let (left, right) = values.split_at(values.len() / 2);
+
+        let (left, right) = rayon::join(|| pairwise_sum(left), || pairwise_sum(right));
+
+        left + right
+    }
+}
+
+/// An error produced when a position lies outside of a grid.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct OutOfBounds;
+
+impl Display for OutOfBounds {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "position is outside of the grid")
+    }
 }
 
+impl Error for OutOfBounds {}
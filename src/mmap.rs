@@ -0,0 +1,306 @@
+//! A module containing grids whose cells are stored in a memory-mapped file.
+
+use std::{fs::{File, OpenOptions}, io, mem, path::Path};
+
+use bytemuck::Pod;
+use memmap2::MmapMut;
+
+use crate::{SurfaceGrid, storage::{GridStorage, OwnedStorage, StoredGrid}};
+
+assert_send_sync!([T: Pod + Send + Sync, G: SurfaceGrid<()> + Send + Sync]
+    MmapGrid<T, G>,
+    MmapStorage,
+);
+
+/// A grid whose cells are stored in a memory-mapped file.
+///
+/// This is a `StoredGrid` that keeps its values in an `MmapStorage`, so it implements
+/// `SurfaceGrid` and is read and written in the same way as any other grid. The shape of the grid
+/// is given by a grid of `()`, such as `CubeSphereGrid<(), 1024>`, which takes up no memory and
+/// provides the points and their order. The file holds the value of every cell in that order with
+/// no header, so the operating system only loads the parts of the grid that are used and grids
+/// larger than the memory of the system can be used. The file persists after the grid is dropped
+/// and can be opened again with `from_mmap`, including by another process.
+///
+/// Writes are saved to the file by the operating system at some point after they are made, or when
+/// `flush` is called. Grids created by the methods of `SurfaceGrid`, such as `map_neighbours`, are
+/// not backed by a file and keep their values in anonymous memory instead.
+///
+/// # Mapping a File Once
+/// The grid reads the file as ordinary memory, so the file must not change while it is mapped
+/// other than through the grid. `create_mmap` and `from_mmap` take an exclusive lock on the file
+/// for as long as the grid exists and return an error with the kind `WouldBlock` if another grid,
+/// in this or another process, already has it mapped. The lock is advisory, so other code and
+/// other programs must not write to or truncate the file while a grid has it mapped.
+///
+/// # Type Parameters
+/// - `T` - The type of data that the grid holds. This must be plain old data as it is read
+///   directly from the file. The file uses the byte order of the system that wrote it.
+/// - `G` - The grid of `()` that gives the shape of the grid.
+///
+/// # Iteration Order
+/// `iter`, `points` and `into_iter` visit the cells in the same order as the grid given by `G`,
+/// which is also the order of the values in the file.
+pub type MmapGrid<T, G> = StoredGrid<T, G, MmapStorage>;
+
+/// A `GridStorage` that keeps the values of a `MmapGrid` in a memory map.
+///
+/// The map is either of a file, which is locked for as long as the storage exists, or of anonymous
+/// memory for grids created from a list of values.
+///
+/// # Thread Safety
+/// The storage is `Send` and `Sync`. Values are only written through a mutable reference to it.
+#[derive(Debug)]
+pub struct MmapStorage {
+    /// The mapped memory.
+    map: MmapMut,
+    /// The mapped file, which is kept open to hold the lock on it, or `None` for anonymous memory.
+    file: Option<File>,
+}
+
+impl MmapStorage {
+    /// Writes any changes to the file and waits for them to be written.
+    ///
+    /// This does nothing for anonymous memory.
+    pub fn flush(&self) -> io::Result<()> {
+        if self.file.is_some() {
+            self.map.flush()
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Checks whether the values are kept in a file rather than in anonymous memory.
+    pub fn is_file_backed(&self) -> bool {
+        self.file.is_some()
+    }
+
+    /// Maps a locked file that has the right length.
+    ///
+    /// - `file` - The file to map, which must already be locked by `lock`.
+    fn map(file: File) -> io::Result<Self> {
+        // Safety: reading the map is undefined behaviour if the file changes while it is mapped
+        // other than through the map, including through a second map of the same file. `lock`
+        // has taken an exclusive lock that stops other grids, in this or another process, from
+        // mapping the file at the same time, and the storage keeps the file open so the lock is
+        // held for as long as the map exists. The lock is advisory, so `MmapGrid` documents that
+        // other code and other programs must not change the file while it is mapped. The file
+        // has the length of the grid and any bytes are a valid value as the values are `Pod`.
+        let map = unsafe { MmapMut::map_mut(&file)? };
+
+        Ok(Self {
+            map,
+            file: Some(file),
+        })
+    }
+
+    /// Takes an exclusive lock on a file without waiting.
+    ///
+    /// This returns an error with the kind `WouldBlock` if the file is already locked.
+    ///
+    /// - `file` - The file to lock.
+    fn lock(file: &File) -> io::Result<()> {
+        file.try_lock().map_err(io::Error::from)
+    }
+}
+
+impl <T: Pod> GridStorage<T> for MmapStorage {
+    fn as_slice(&self) -> &[T] {
+        bytemuck::cast_slice(&self.map)
+    }
+
+    fn as_mut_slice(&mut self) -> &mut [T] {
+        bytemuck::cast_slice_mut(&mut self.map)
+    }
+}
+
+/// Grids created from a list of values keep them in anonymous memory.
+impl <T: Pod> OwnedStorage<T> for MmapStorage {
+    /// Creates a new store holding a list of values.
+    ///
+    /// # Panics
+    /// Panics if the anonymous memory cannot be mapped.
+    fn from_vec(values: Vec<T>) -> Self {
+        let bytes: &[u8] = bytemuck::cast_slice(&values);
+        let mut map = MmapMut::map_anon(bytes.len())
+            .expect("failed to map anonymous memory for the grid");
+
+        map.copy_from_slice(bytes);
+
+        Self {
+            map,
+            file: None,
+        }
+    }
+
+    fn into_vec(self) -> Vec<T> {
+        GridStorage::<T>::as_slice(&self).to_vec()
+    }
+}
+
+impl <T: Pod, G: SurfaceGrid<()> + Default> StoredGrid<T, G, MmapStorage> {
+    /// Creates a new file, replacing any existing file, and maps a grid onto it.
+    ///
+    /// This returns an error with the kind `WouldBlock` if the file is already mapped by another
+    /// grid.
+    ///
+    /// - `path` - The path of the file.
+    /// - `f` - A function that gives the initial value of each cell.
+    pub fn create_mmap<P: AsRef<Path>, F: FnMut(&G::Point) -> T>(path: P, mut f: F) -> io::Result<Self> {
+        let layout = G::default();
+
+        // The file is only truncated once it is locked so a grid that has it mapped is unaffected.
+        let file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(false)
+            .open(path)?;
+
+        MmapStorage::lock(&file)?;
+
+        file.set_len(0)?;
+        file.set_len(Self::file_len(&layout) as u64)?;
+
+        // The file has just been given the length of the grid.
+        let mut grid = Self::from_storage(MmapStorage::map(file)?).unwrap();
+
+        for (point, value) in layout.points().zip(grid.as_mut_slice()) {
+            *value = f(&point);
+        }
+
+        Ok(grid)
+    }
+
+    /// Maps a grid onto an existing file created by `create_mmap`.
+    ///
+    /// This returns an error with the kind `InvalidData` if the length of the file does not match
+    /// the size of the grid, or with the kind `WouldBlock` if the file is already mapped by another
+    /// grid.
+    ///
+    /// - `path` - The path of the file.
+    pub fn from_mmap<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        let layout = G::default();
+
+        let file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .open(path)?;
+
+        MmapStorage::lock(&file)?;
+
+        let expected = Self::file_len(&layout) as u64;
+        let actual = file.metadata()?.len();
+
+        if actual != expected {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("the file is {actual} bytes long but the grid needs {expected} bytes"),
+            ));
+        }
+
+        // The length of the file has been checked above.
+        Ok(Self::from_storage(MmapStorage::map(file)?).unwrap())
+    }
+
+    /// Writes any changes to the grid to the file and waits for them to be written.
+    pub fn flush(&self) -> io::Result<()> {
+        self.storage().flush()
+    }
+
+    /// Gets the number of bytes needed to store every cell of a grid.
+    ///
+    /// - `layout` - The grid that gives the shape of the grid.
+    fn file_len(layout: &G) -> usize {
+        layout.points().count() * mem::size_of::<T>()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::{fs, io, path::PathBuf};
+
+    use crate::{GridPoint, SurfaceGrid, sphere::{CubeSphereGrid, CubeSpherePoint}};
+
+    use super::MmapGrid;
+
+    /// Gets a path for a test file that is unique to the test and this process.
+    fn test_path(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!("surface-grid-mmap-{}-{name}.bin", std::process::id()))
+    }
+
+    #[test]
+    fn test_mmap_index_persists() {
+        let path = test_path("persists");
+
+        let mut grid: MmapGrid<f32, CubeSphereGrid<(), 8>> = MmapGrid::create_mmap(&path, |point: &CubeSpherePoint<8>| point.position(1.0).1 as f32).unwrap();
+        let point = grid.points().nth(10).unwrap();
+
+        grid[point] = 42.0;
+        grid[point.up()] += 1.0;
+        grid.flush().unwrap();
+
+        let expected: Vec<_> = grid.as_slice().to_vec();
+
+        drop(grid);
+
+        let grid: MmapGrid<f32, CubeSphereGrid<(), 8>> = MmapGrid::from_mmap(&path).unwrap();
+
+        assert_eq!(42.0, grid[point]);
+        assert_eq!(expected, grid.as_slice());
+        assert_eq!(6 * 8 * 8 * 4, fs::metadata(&path).unwrap().len());
+
+        fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn test_mmap_wrong_size() {
+        let path = test_path("wrong-size");
+
+        drop(MmapGrid::<u8, CubeSphereGrid<(), 4>>::create_mmap(&path, |_| 0).unwrap());
+
+        let error = MmapGrid::<u8, CubeSphereGrid<(), 8>>::from_mmap(&path).unwrap_err();
+
+        assert_eq!(io::ErrorKind::InvalidData, error.kind());
+
+        fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn test_mmap_mapped_once() {
+        let path = test_path("mapped-once");
+
+        let grid: MmapGrid<u32, CubeSphereGrid<(), 4>> = MmapGrid::create_mmap(&path, |_| 7).unwrap();
+
+        let error = MmapGrid::<u32, CubeSphereGrid<(), 4>>::from_mmap(&path).unwrap_err();
+        assert_eq!(io::ErrorKind::WouldBlock, error.kind());
+
+        let error = MmapGrid::<u32, CubeSphereGrid<(), 4>>::create_mmap(&path, |_| 0).unwrap_err();
+        assert_eq!(io::ErrorKind::WouldBlock, error.kind());
+        assert!(grid.iter().all(|(_, value)| *value == 7));
+
+        drop(grid);
+
+        assert!(MmapGrid::<u32, CubeSphereGrid<(), 4>>::from_mmap(&path).is_ok());
+
+        fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn test_mmap_surface_grid() {
+        let path = test_path("surface-grid");
+
+        let grid: MmapGrid<u32, CubeSphereGrid<(), 4>> = MmapGrid::create_mmap(&path, |_| 1).unwrap();
+        let dense: CubeSphereGrid<u32, 4> = CubeSphereGrid::from_fn(|_| 1);
+
+        let sums = grid.map_neighbours(|current, up, down, left, right| current + up + down + left + right);
+
+        assert!(grid.storage().is_file_backed());
+        assert!(!sums.storage().is_file_backed());
+        assert_eq!(dense.map_neighbours(|current, up, down, left, right| current + up + down + left + right).to_vec(), sums.to_vec());
+
+        drop(grid);
+
+        fs::remove_file(path).unwrap();
+    }
+}
@@ -0,0 +1,567 @@
+//! A module containing grids on spheres made by projecting an octahedron onto the sphere.
+
+use std::{f64::consts::PI, ops::{Index, IndexMut}, vec};
+
+use rayon::prelude::*;
+
+use crate::{GridPoint, OutOfBounds, Surface, SurfaceGrid, Topology, sphere::SpherePoint};
+
+assert_send_sync!([T: Send + Sync, const S: usize]
+    OctaSphereGrid<T, S>,
+    OctaSpherePoint<S>,
+);
+
+/// A grid covering a sphere by projecting an octahedron onto it.
+///
+/// The eight triangular faces of the octahedron are joined in pairs across the equator into four
+/// diamonds, each covering a quarter of the longitudes, and each diamond is covered by an `S` by
+/// `S` grid. The corner of a diamond at `(0, 0)` is the north pole, the corner at `(S, S)` is the
+/// south pole and the corners at `(S, 0)` and `(0, S)` lie on the equator at the western and
+/// eastern edges of the diamond.
+///
+/// At the four corners of the octahedron on the equator only two cells meet, so the cells at
+/// these corners are the `up` and `right` or `down` and `left` neighbours of each other.
+///
+/// # Type Parameters
+/// - `T` - The type of data that the grid holds.
+///
+/// # Constant Parameters
+/// - `S` - The size of each side of each diamond.
+///
+/// # Iteration Order
+/// `iter`, `points` and `into_iter` all visit the diamonds in order of increasing longitude. Each
+/// diamond is visited in row-major order, starting at `(0, 0)` and increasing `x` before `y`. This
+/// order is stable.
+///
+/// # Thread Safety
+/// The cells of the eight faces are kept in one `Vec`, so the grid is `Send` when `T` is `Send` and
+/// `Sync` when `T` is `Sync`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct OctaSphereGrid<T, const S: usize> {
+    /// The data held in this grid.
+    data: Vec<T>,
+}
+
+impl <T: Default, const S: usize> Default for OctaSphereGrid<T, S> {
+    fn default() -> Self {
+        Self::from_fn(|_| T::default())
+    }
+}
+
+impl <T, const S: usize> OctaSphereGrid<T, S> {
+    /// Gets the values of the cells in the same order as the points of the grid.
+    pub fn as_slice(&self) -> &[T] {
+        &self.data
+    }
+
+    /// Gets mutable references to the values of the cells in the same order as the points of the
+    /// grid.
+    pub fn as_mut_slice(&mut self) -> &mut [T] {
+        &mut self.data
+    }
+
+    /// Gets the values of the cells as bytes in the same order as `as_slice`.
+    #[cfg(feature = "bytemuck")]
+    pub fn as_bytes(&self) -> &[u8] where T: bytemuck::Pod {
+        bytemuck::cast_slice(self.as_slice())
+    }
+
+    /// Gets the values of the cells as mutable bytes in the same order as `as_slice`.
+    #[cfg(feature = "bytemuck")]
+    pub fn as_bytes_mut(&mut self) -> &mut [u8] where T: bytemuck::Pod {
+        bytemuck::cast_slice_mut(self.as_mut_slice())
+    }
+}
+
+impl <T, const S: usize> SurfaceGrid<T> for OctaSphereGrid<T, S> {
+    type Point = OctaSpherePoint<S>;
+
+    fn from_fn<F: FnMut(&Self::Point) -> T>(mut f: F) -> Self {
+        Self {
+            data: (0..4 * S * S)
+                .map(|i| f(&OctaSpherePoint::from_index(i)))
+                .collect(),
+        }
+    }
+
+    fn from_fn_par<F: Fn(&Self::Point) -> T + Send + Sync>(f: F) -> Self where T: Send + Sync {
+        let mut data = Vec::with_capacity(4 * S * S);
+
+        (0..4 * S * S).into_par_iter()
+            .map(|i| f(&OctaSpherePoint::from_index(i)))
+            .collect_into_vec(&mut data);
+
+        Self {
+            data,
+        }
+    }
+
+    fn try_from_fn<X, F: FnMut(&Self::Point) -> Result<T, X>>(mut f: F) -> Result<Self, X> {
+        Ok(Self {
+            data: (0..4 * S * S)
+                .map(|i| f(&OctaSpherePoint::from_index(i)))
+                .collect::<Result<_, _>>()?,
+        })
+    }
+
+    fn try_from_fn_par<X: Send, F: Fn(&Self::Point) -> Result<T, X> + Send + Sync>(f: F) -> Result<Self, X> where T: Send + Sync {
+        Ok(Self {
+            data: (0..4 * S * S).into_par_iter()
+                .map(|i| f(&OctaSpherePoint::from_index(i)))
+                .collect::<Result<_, _>>()?,
+        })
+    }
+
+    fn from_vec(values: Vec<T>) -> Option<Self> {
+        if values.len() != 4 * S * S {
+            return None;
+        }
+
+        Some(Self {
+            data: values,
+        })
+    }
+
+    fn set_from_fn<F: FnMut(&Self::Point) -> T>(&mut self, mut f: F) {
+        for (i, value) in self.data.iter_mut().enumerate() {
+            *value = f(&OctaSpherePoint::from_index(i));
+        }
+    }
+
+    fn set_from_fn_par<F: Fn(&Self::Point) -> T + Send + Sync>(&mut self, f: F) where T: Send + Sync {
+        self.data.par_iter_mut().enumerate().for_each(|(i, value)| {
+            *value = f(&OctaSpherePoint::from_index(i));
+        });
+    }
+
+    fn iter<'a>(&'a self) -> impl ExactSizeIterator<Item = (Self::Point, &'a T)> + DoubleEndedIterator where T: 'a {
+        self.data.iter()
+            .enumerate()
+            .map(|(i, value)| (OctaSpherePoint::from_index(i), value))
+    }
+
+    fn par_iter<'a>(&'a self) -> impl IndexedParallelIterator<Item = (Self::Point, &'a T)> where T: 'a + Send + Sync {
+        self.data.par_iter()
+            .enumerate()
+            .map(|(i, value)| (OctaSpherePoint::from_index(i), value))
+    }
+
+    fn par_iter_mut<'a>(&'a mut self) -> impl IndexedParallelIterator<Item = (Self::Point, &'a mut T)> where T: 'a + Send + Sync {
+        self.data.par_iter_mut()
+            .enumerate()
+            .map(|(i, value)| (OctaSpherePoint::from_index(i), value))
+    }
+
+    fn points(&self) -> impl ExactSizeIterator<Item = Self::Point> + DoubleEndedIterator {
+        (0..4 * S * S)
+            .map(OctaSpherePoint::from_index)
+    }
+
+    fn par_points(&self) -> impl IndexedParallelIterator<Item = Self::Point> {
+        (0..4 * S * S).into_par_iter()
+            .map(OctaSpherePoint::from_index)
+    }
+
+    fn par_map_to_vec<U: Send, F: Fn(&Self::Point, &T) -> U + Send + Sync>(&self, f: F) -> Vec<U> where T: Send + Sync {
+        let mut result = Vec::with_capacity(4 * S * S);
+
+        self.data.par_iter()
+            .enumerate()
+            .map(|(i, value)| f(&OctaSpherePoint::from_index(i), value))
+            .collect_into_vec(&mut result);
+
+        result
+    }
+
+    fn to_index(&self, point: &Self::Point) -> usize {
+        point.index()
+    }
+
+    fn at(&self, point: &Self::Point) -> &T {
+        &self[point]
+    }
+
+    fn at_mut(&mut self, point: &Self::Point) -> &mut T {
+        &mut self[point]
+    }
+
+    fn surface_area(&self, scale: f64) -> f64 {
+        4.0 * PI * scale * scale
+    }
+
+    fn topology(&self) -> Topology {
+        Topology::uniform(Surface::Sphere, 4, S, S)
+    }
+
+    fn contiguous_values_mut(&mut self) -> Option<&mut [T]> {
+        Some(self.as_mut_slice())
+    }
+}
+
+impl <T, const S: usize> Index<OctaSpherePoint<S>> for OctaSphereGrid<T, S> {
+    type Output = T;
+
+    fn index(&self, index: OctaSpherePoint<S>) -> &Self::Output {
+        &self.data[index.index()]
+    }
+}
+
+impl <T, const S: usize> IndexMut<OctaSpherePoint<S>> for OctaSphereGrid<T, S> {
+    fn index_mut(&mut self, index: OctaSpherePoint<S>) -> &mut Self::Output {
+        &mut self.data[index.index()]
+    }
+}
+
+impl <T, const S: usize> Index<&OctaSpherePoint<S>> for OctaSphereGrid<T, S> {
+    type Output = T;
+
+    fn index(&self, index: &OctaSpherePoint<S>) -> &Self::Output {
+        &self[*index]
+    }
+}
+
+impl <T, const S: usize> IndexMut<&OctaSpherePoint<S>> for OctaSphereGrid<T, S> {
+    fn index_mut(&mut self, index: &OctaSpherePoint<S>) -> &mut Self::Output {
+        &mut self[*index]
+    }
+}
+
+impl <T, const S: usize> FromIterator<T> for OctaSphereGrid<T, S> {
+    /// Creates a grid from the values of its cells in the same order as `points`.
+    ///
+    /// # Panics
+    /// Panics if the iterator does not yield exactly one value for each cell in the grid.
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        Self::from_vec(iter.into_iter().collect())
+            .expect("the iterator must yield one value for each cell in the grid")
+    }
+}
+
+impl <T, const S: usize> IntoIterator for OctaSphereGrid<T, S> {
+    type Item = (OctaSpherePoint<S>, T);
+
+    type IntoIter = vec::IntoIter<Self::Item>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        let data: Vec<_> = self.data.into_iter()
+            .enumerate()
+            .map(|(i, value)| (OctaSpherePoint::from_index(i), value))
+            .collect();
+
+        data.into_iter()
+    }
+}
+
+#[cfg(feature = "serde")]
+impl <T: serde::Serialize, const S: usize> serde::Serialize for OctaSphereGrid<T, S> {
+    /// Serializes the values in this grid as a sequence in the same order as `iter`.
+    fn serialize<R: serde::Serializer>(&self, serializer: R) -> Result<R::Ok, R::Error> {
+        serializer.collect_seq(self.data.iter())
+    }
+}
+
+#[cfg(feature = "serde")]
+impl <'de, T: serde::Deserialize<'de>, const S: usize> serde::Deserialize<'de> for OctaSphereGrid<T, S> {
+    /// Deserializes a grid from a sequence of values in the same order as `iter`.
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let values = Vec::<T>::deserialize(deserializer)?;
+        let length = values.len();
+
+        Self::from_vec(values)
+            .ok_or_else(|| serde::de::Error::invalid_length(length, &format!("a sequence of {} values", 4 * S * S).as_str()))
+    }
+}
+
+/// A point on an `OctaSphereGrid`.
+///
+/// # Constant Parameters
+/// - `S` - The size of each side of each diamond.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct OctaSpherePoint<const S: usize> {
+    /// The diamond that contains the point.
+    diamond: u8,
+    /// The X position on the diamond.
+    x: u32,
+    /// The Y position on the diamond.
+    y: u32,
+}
+
+impl <const S: usize> OctaSpherePoint<S> {
+    /// Creates a new `OctaSpherePoint`.
+    ///
+    /// - `diamond` - The diamond on which the point lies.
+    /// - `x` - The X position on the diamond.
+    /// - `y` - The Y position on the diamond.
+    fn new(diamond: u8, x: u32, y: u32) -> Self {
+        Self {
+            diamond, x, y,
+        }
+    }
+
+    /// Creates a new `OctaSpherePoint` checking that the position lies on the grid.
+    ///
+    /// This returns an error if `diamond` is 4 or greater or either coordinate is `S` or greater.
+    ///
+    /// - `diamond` - The diamond, from 0 to 3.
+    /// - `x` - The X position on the diamond.
+    /// - `y` - The Y position on the diamond.
+    pub fn try_new(diamond: u8, x: u32, y: u32) -> Result<Self, OutOfBounds> {
+        if diamond < 4 && (x as usize) < S && (y as usize) < S {
+            Ok(Self::new(diamond, x, y))
+        } else {
+            Err(OutOfBounds)
+        }
+    }
+
+    /// Gets the point at an index in the order that `OctaSphereGrid::points` visits them.
+    ///
+    /// - `index` - The index of the point.
+    fn from_index(index: usize) -> Self {
+        Self::new((index / (S * S)) as u8, (index % S) as u32, (index / S % S) as u32)
+    }
+
+    /// Gets the index of this point in the order that `OctaSphereGrid::points` visits them.
+    fn index(&self) -> usize {
+        self.diamond as usize * S * S + self.y as usize * S + self.x as usize
+    }
+
+    /// Gets the diamond that contains this point.
+    ///
+    /// The diamonds are numbered from 0 to 3 and diamond `n` covers the longitudes from `n` to
+    /// `n + 1` quarter turns.
+    pub fn diamond(&self) -> u8 {
+        self.diamond
+    }
+
+    /// Gets the diamond next to this one in the direction of increasing longitude.
+    fn east(&self) -> u8 {
+        (self.diamond + 1) % 4
+    }
+
+    /// Gets the diamond next to this one in the direction of decreasing longitude.
+    fn west(&self) -> u8 {
+        (self.diamond + 3) % 4
+    }
+
+    /// Gets the position on the sphere of a continuous position on a diamond.
+    ///
+    /// - `diamond` - The diamond.
+    /// - `u` - The X position on the diamond from 0 to 1.
+    /// - `v` - The Y position on the diamond from 0 to 1.
+    fn diamond_position(diamond: u8, u: f64, v: f64) -> (f64, f64, f64) {
+        let west = diamond as f64 * PI / 2.0;
+        let (west_x, west_z) = (west.sin().round(), west.cos().round());
+        let (east_x, east_z) = (west_z, -west_x);
+
+        // Find the position on the octahedron as a combination of one of the poles and the two
+        // corners of the diamond on the equator.
+        let (pole, along_west, along_east) = if u + v <= 1.0 {
+            (1.0 - u - v, u, v)
+        } else {
+            (1.0 - u - v, 1.0 - v, 1.0 - u)
+        };
+
+        let x = along_west * west_x + along_east * east_x;
+        let z = along_west * west_z + along_east * east_z;
+        let length = (x * x + pole * pole + z * z).sqrt();
+
+        (x / length, pole / length, z / length)
+    }
+}
+
+impl <const S: usize> GridPoint for OctaSpherePoint<S> {
+    fn up(&self) -> Self {
+        if self.y > 0 {
+            Self::new(self.diamond, self.x, self.y - 1)
+        } else {
+            Self::new(self.west(), 0, self.x)
+        }
+    }
+
+    fn down(&self) -> Self {
+        if self.y < S as u32 - 1 {
+            Self::new(self.diamond, self.x, self.y + 1)
+        } else {
+            Self::new(self.east(), S as u32 - 1, self.x)
+        }
+    }
+
+    fn left(&self) -> Self {
+        if self.x > 0 {
+            Self::new(self.diamond, self.x - 1, self.y)
+        } else {
+            Self::new(self.east(), self.y, 0)
+        }
+    }
+
+    fn right(&self) -> Self {
+        if self.x < S as u32 - 1 {
+            Self::new(self.diamond, self.x + 1, self.y)
+        } else {
+            Self::new(self.west(), self.y, S as u32 - 1)
+        }
+    }
+
+    /// Gets the position of the centre of the cell in 3D space.
+    ///
+    /// - `scale` - The scale of the 3D object.
+    fn position(&self, scale: f64) -> (f64, f64, f64) {
+        let u = (self.x as f64 + 0.5) / S as f64;
+        let v = (self.y as f64 + 0.5) / S as f64;
+
+        let (x, y, z) = Self::diamond_position(self.diamond, u, v);
+
+        (x * scale, y * scale, z * scale)
+    }
+}
+
+impl <const S: usize> SpherePoint for OctaSpherePoint<S> {
+    fn from_geographic(latitude: f64, longitude: f64) -> Self {
+        let longitude = longitude.rem_euclid(PI * 2.0);
+        let diamond = ((longitude / (PI / 2.0)) as u8).min(3);
+
+        // Project onto the octahedron and measure the distance from the two corners of the
+        // diamond on the equator.
+        let local = longitude - diamond as f64 * PI / 2.0;
+        let height = latitude.sin();
+        let radius = latitude.cos();
+        let (along_west, along_east) = (radius * local.cos(), radius * local.sin());
+        let length = along_west + height.abs() + along_east;
+
+        let (along_west, along_east) = (along_west / length, along_east / length);
+
+        let (u, v) = if height >= 0.0 {
+            (along_west, along_east)
+        } else {
+            (1.0 - along_east, 1.0 - along_west)
+        };
+
+        let x = ((u * S as f64) as u32).min(S as u32 - 1);
+        let y = ((v * S as f64) as u32).min(S as u32 - 1);
+
+        Self::new(diamond, x, y)
+    }
+
+    fn latitude(&self) -> f64 {
+        let (x, y, z) = self.position(1.0);
+
+        y.atan2((x * x + z * z).sqrt())
+    }
+
+    fn longitude(&self) -> f64 {
+        let (x, _, z) = self.position(1.0);
+
+        x.atan2(z).rem_euclid(PI * 2.0)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::{collections::HashSet, f64::consts::PI};
+
+    use approx::assert_relative_eq;
+
+    use crate::{GridPoint, SurfaceGrid, sphere::SpherePoint};
+
+    use super::{OctaSphereGrid, OctaSpherePoint};
+
+    #[test]
+    fn test_octa_neighbours() {
+        let grid: OctaSphereGrid<(), 6> = OctaSphereGrid::default();
+
+        for point in grid.points() {
+            let neighbours = [point.up(), point.down(), point.left(), point.right()];
+
+            for neighbour in neighbours {
+                let (x, y, z) = point.position(1.0);
+                let (nx, ny, nz) = neighbour.position(1.0);
+
+                assert_ne!(neighbour, point);
+                assert!(((x - nx).powi(2) + (y - ny).powi(2) + (z - nz).powi(2)).sqrt() < 0.5);
+                assert!([neighbour.up(), neighbour.down(), neighbour.left(), neighbour.right()].contains(&point));
+            }
+        }
+    }
+
+    #[test]
+    fn test_octa_up_across_diamonds() {
+        let point: OctaSpherePoint<4> = OctaSpherePoint::new(1, 2, 0);
+
+        assert_eq!(point.up(), OctaSpherePoint::new(0, 0, 2));
+        assert_eq!(point.up().right(), OctaSpherePoint::new(0, 1, 2));
+        assert_eq!(OctaSpherePoint::<4>::new(0, 0, 2).left(), point);
+        assert_eq!(OctaSpherePoint::<4>::new(3, 1, 3).down(), OctaSpherePoint::new(0, 3, 1));
+        assert_eq!(OctaSpherePoint::<4>::new(0, 3, 1).right(), OctaSpherePoint::new(3, 1, 3));
+    }
+
+    #[test]
+    fn test_octa_corners() {
+        // Four cells meet at each pole.
+        let north: HashSet<_> = (0..4).map(|diamond| OctaSpherePoint::<4>::new(diamond, 0, 0)).collect();
+
+        for point in &north {
+            assert!(north.contains(&point.up()));
+            assert!(north.contains(&point.left()));
+        }
+
+        let south: HashSet<_> = (0..4).map(|diamond| OctaSpherePoint::<4>::new(diamond, 3, 3)).collect();
+
+        for point in &south {
+            assert!(south.contains(&point.down()));
+            assert!(south.contains(&point.right()));
+        }
+
+        // Only two cells meet at the corners on the equator.
+        let west = OctaSpherePoint::<4>::new(1, 3, 0);
+
+        assert_eq!(west.up(), OctaSpherePoint::new(0, 0, 3));
+        assert_eq!(west.right(), west.up());
+    }
+
+    #[test]
+    fn test_octa_from_geographic() {
+        let grid: OctaSphereGrid<(), 7> = OctaSphereGrid::default();
+
+        for point in grid.points() {
+            assert_eq!(OctaSpherePoint::from_geographic(point.latitude(), point.longitude()), point);
+            assert_eq!(OctaSpherePoint::from_geographic(point.latitude(), point.longitude() - 2.0 * PI), point);
+        }
+
+        assert_eq!(OctaSpherePoint::<7>::from_geographic(PI / 2.0, 1.0), OctaSpherePoint::new(0, 0, 0));
+        assert_eq!(OctaSpherePoint::<7>::from_geographic(-PI / 2.0, 2.0), OctaSpherePoint::new(1, 6, 6));
+        assert_eq!(OctaSpherePoint::<7>::from_geographic(0.0, PI).diamond(), 2);
+    }
+
+    #[test]
+    fn test_octa_position() {
+        let grid: OctaSphereGrid<(), 5> = OctaSphereGrid::default();
+
+        for point in grid.points() {
+            let (x, y, z) = point.position(2.0);
+
+            assert_relative_eq!((x * x + y * y + z * z).sqrt(), 2.0, epsilon = 1e-12);
+            assert!(point.longitude() >= point.diamond() as f64 * PI / 2.0);
+            assert!(point.longitude() <= (point.diamond() + 1) as f64 * PI / 2.0);
+        }
+
+        // The northern half of each diamond is above the equator.
+        assert!(OctaSpherePoint::<5>::new(2, 1, 2).latitude() > 0.0);
+        assert!(OctaSpherePoint::<5>::new(2, 3, 2).latitude() < 0.0);
+
+        assert_relative_eq!(grid.surface_area(1.0), 4.0 * PI);
+    }
+
+    #[test]
+    fn test_octa_iteration_order() {
+        let grid: OctaSphereGrid<usize, 3> = OctaSphereGrid::from_index_fn(|i| i);
+
+        for (i, (point, value)) in grid.iter().enumerate() {
+            assert_eq!(*value, i);
+            assert_eq!(grid.to_index(&point), i);
+        }
+
+        assert_eq!(grid.par_map_to_vec(|_, value| *value), grid.to_vec());
+        assert_eq!(grid.clone().into_iter().map(|(_, value)| value).collect::<Vec<_>>(), grid.to_vec());
+        assert_eq!(OctaSphereGrid::from_vec(grid.to_vec()), Some(grid));
+    }
+}
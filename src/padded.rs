@@ -0,0 +1,641 @@
+//! A module containing cube sphere grids that store a border of padding cells around each face.
+
+use std::{f64::consts::PI, ops::{Index, IndexMut}, vec};
+
+use rayon::prelude::*;
+
+use crate::{GridPoint, Surface, SurfaceGrid, Topology, sphere::{CubeFace, CubeSphereGrid, CubeSpherePoint, FACE_ORDER}};
+
+assert_send_sync!([T: Send + Sync, const S: usize]
+    PaddedCubeSphereGrid<T, S>,
+);
+
+/// A grid with the same shape and points as `CubeSphereGrid` that stores each face with a border
+/// one cell wide holding copies of the cells on the neighbouring faces.
+///
+/// Every cell, including the cells on the edges of a face, can find its direct neighbours at a
+/// fixed offset from itself so the neighbour functions, such as `map_neighbours_par`, do not need
+/// to check whether a neighbour lies on another face. The border is refreshed once after the
+/// whole grid is written, for example by `from_fn`, `set_from_fn` or `map_neighbours`, which
+/// clones the `24 * S` cells along the edges of the faces.
+///
+/// Writing to a cell through `IndexMut` leaves the border out of date. The neighbour functions
+/// still give the right results in that case but they look up the cells on other faces in the
+/// same way as `CubeSphereGrid` until `refresh_padding` is called.
+///
+/// The diagonal neighbours of the cells in the top and bottom rows of each face are taken from
+/// `GridPoint::up_left` and the other diagonal steps of `CubeSpherePoint` so that every neighbour
+/// function gives the same results as it does for `CubeSphereGrid`.
+///
+/// # Type Parameters
+/// - `T` - The type of data that the grid holds. Implementing `SurfaceGrid` requires `T` to be
+///   `Clone` so that the border can be filled.
+///
+/// # Constant Parameters
+/// - `S` - The size of each side of each face.
+///
+/// # Iteration Order
+/// `iter`, `points` and `into_iter` visit the cells in the same order as `CubeSphereGrid` and do
+/// not visit the border.
+///
+/// # Thread Safety
+/// The faces and their borders are kept in one `Vec` and the borders are only refreshed through a
+/// mutable reference, so the grid is `Send` when `T` is `Send` and `Sync` when `T` is `Sync`.
+#[derive(Debug, Clone)]
+pub struct PaddedCubeSphereGrid<T, const S: usize> {
+    /// The cells of each face surrounded by their borders, stored row by row.
+    data: Vec<T>,
+    /// Whether a cell has been written since the border was last refreshed.
+    stale: bool,
+}
+
+impl <T, const S: usize> PaddedCubeSphereGrid<T, S> {
+    /// The size of each side of each face including its border.
+    const WIDTH: usize = S + 2;
+
+    /// The number of cells stored for each face including its border.
+    const FACE_LEN: usize = Self::WIDTH * Self::WIDTH;
+
+    /// Checks if the border needs to be refreshed before neighbours can be read with offsets.
+    pub fn is_padding_stale(&self) -> bool {
+        self.stale
+    }
+
+    /// Gets the values of a cell and its direct neighbours.
+    ///
+    /// The values are in the order: current, up, down, left, right.
+    ///
+    /// - `index` - The index of the cell in the order of `points`.
+    fn neighbours_at(&self, index: usize) -> [&T; 5] {
+        let i = Self::storage_index(index);
+        let step = Self::x_step(i);
+
+        [i, i - Self::WIDTH, i + Self::WIDTH, i.wrapping_add_signed(-step), i.wrapping_add_signed(step)]
+            .map(|i| self.read(i))
+    }
+
+    /// Gets the values of a cell and its direct neighbours including diagonals.
+    ///
+    /// The values are in the order given by `DIAGONAL_ORDER`.
+    ///
+    /// - `index` - The index of the cell in the order of `points`.
+    fn neighbours_diagonals_at(&self, index: usize) -> [&T; 9] {
+        let y = index / S % S;
+
+        if y == 0 || y == S - 1 {
+            let point = CubeSpherePoint::<S>::from_index(index);
+
+            return [
+                point.up_left(), point.up(), point.up_right(),
+                point.left(), point, point.right(),
+                point.down_left(), point.down(), point.down_right(),
+            ].map(|point| &self.data[Self::storage_index(point.index())]);
+        }
+
+        let i = Self::storage_index(index);
+        let step = Self::x_step(i);
+        let (up, down) = (i - Self::WIDTH, i + Self::WIDTH);
+
+        [
+            up.wrapping_add_signed(-step), up, up.wrapping_add_signed(step),
+            i.wrapping_add_signed(-step), i, i.wrapping_add_signed(step),
+            down.wrapping_add_signed(-step), down, down.wrapping_add_signed(step),
+        ].map(|i| self.read(i))
+    }
+
+    /// Reads a stored cell, following a border cell to the cell it copies if the border is stale.
+    ///
+    /// - `i` - The index of the cell in `data`.
+    fn read(&self, i: usize) -> &T {
+        if self.stale {
+            &self.data[Self::source_index(i)]
+        } else {
+            &self.data[i]
+        }
+    }
+
+    /// Gets the index in `data` of the cell at an index in the order of `points`.
+    ///
+    /// - `index` - The index of the cell in the order of `points`.
+    fn storage_index(index: usize) -> usize {
+        let face = index / (S * S);
+        let x = index % S;
+        let y = index / S % S;
+
+        face * Self::FACE_LEN + (y + 1) * Self::WIDTH + x + 1
+    }
+
+    /// Gets the index in the order of `points` of a stored cell that is not in a border.
+    ///
+    /// This is the inverse of `storage_index`.
+    ///
+    /// - `i` - The index of the cell in `data`.
+    fn point_index(i: usize) -> usize {
+        let face = i / Self::FACE_LEN;
+        let x = i % Self::WIDTH - 1;
+        let y = i % Self::FACE_LEN / Self::WIDTH - 1;
+
+        (face * S + y) * S + x
+    }
+
+    /// Gets the index in `data` of the cell that a stored cell holds the value of.
+    ///
+    /// This is the cell itself for the cells of the faces and a cell on a neighbouring face for
+    /// the cells of the border.
+    ///
+    /// - `i` - The index of the cell in `data`.
+    fn source_index(i: usize) -> usize {
+        let face = FACE_ORDER[i / Self::FACE_LEN];
+        let x = (i % Self::WIDTH) as isize - 1;
+        let y = (i % Self::FACE_LEN / Self::WIDTH) as isize - 1;
+
+        let (dx, dy) = (Self::border_step(x), Self::border_step(y));
+
+        if dx == 0 && dy == 0 {
+            return i;
+        }
+
+        // The cell of the face next to the border cell.
+        let point = CubeSpherePoint::<S>::new(face, (x + dx) as u16, (y + dy) as u16);
+
+        // The X axis of the back face runs from right to left.
+        let dx = if face == CubeFace::Back { dx } else { -dx };
+
+        let source = match (dx, -dy) {
+            (-1, 0) => point.left(),
+            (1, 0) => point.right(),
+            (0, -1) => point.up(),
+            (0, 1) => point.down(),
+            (-1, -1) => point.up_left(),
+            (1, -1) => point.up_right(),
+            (-1, 1) => point.down_left(),
+            _ => point.down_right(),
+        };
+
+        Self::storage_index(source.index())
+    }
+
+    /// Gets the step back onto the face for a position along an axis of a face with a border.
+    ///
+    /// - `position` - The position along the axis, which is `-1` or `S` in the border.
+    fn border_step(position: isize) -> isize {
+        if position < 0 {
+            1
+        } else if position >= S as isize {
+            -1
+        } else {
+            0
+        }
+    }
+
+    /// Gets the offset in `data` from a cell to its right neighbour.
+    ///
+    /// - `i` - The index of the cell in `data`.
+    fn x_step(i: usize) -> isize {
+        if FACE_ORDER[i / Self::FACE_LEN] == CubeFace::Back {
+            -1
+        } else {
+            1
+        }
+    }
+
+    /// Iterates over the indices in `data` of the cells in the borders.
+    fn border_indices() -> impl Iterator<Item = usize> {
+        (0..6 * Self::FACE_LEN).filter(|i| Self::is_border(*i))
+    }
+
+    /// Checks if a stored cell is in a border.
+    ///
+    /// - `i` - The index of the cell in `data`.
+    fn is_border(i: usize) -> bool {
+        let x = i % Self::WIDTH;
+        let y = i % Self::FACE_LEN / Self::WIDTH;
+
+        x == 0 || y == 0 || x == Self::WIDTH - 1 || y == Self::WIDTH - 1
+    }
+}
+
+impl <T: Clone, const S: usize> PaddedCubeSphereGrid<T, S> {
+    /// Copies the cells along the edges of each face into the borders of the neighbouring faces.
+    ///
+    /// This only needs to be called after cells have been written through `IndexMut`.
+    pub fn refresh_padding(&mut self) {
+        for i in Self::border_indices() {
+            self.data[i] = self.data[Self::source_index(i)].clone();
+        }
+
+        self.stale = false;
+    }
+
+    /// Applies a function to each cell of another padded grid and its direct neighbours in
+    /// parallel writing the results into this grid.
+    ///
+    /// This reads the neighbours of each cell at fixed offsets in the same way as
+    /// `map_neighbours_par`.
+    ///
+    /// The provided function is called with the arguments: current, up, down, left, right.
+    ///
+    /// `source` - The source grid from which to read data.
+    /// `f` - The function to apply.
+    pub fn set_from_padded_neighbours_par<
+                U: Send + Sync,
+                F: Fn(&U, &U, &U, &U, &U) -> T + Send + Sync
+            >(&mut self, source: &PaddedCubeSphereGrid<U, S>, f: F) where T: Send + Sync {
+        self.set_from_index_par(|index| {
+            let [current, up, down, left, right] = source.neighbours_at(index);
+
+            f(current, up, down, left, right)
+        })
+    }
+
+    /// Applies a function to each cell of another padded grid and its direct neighbours including
+    /// diagonals in parallel writing the results into this grid.
+    ///
+    /// The provided function is called with the arguments: up_left, up, up_right,
+    /// left, current, right, down_left, down, down_right. This is the order given by
+    /// `DIAGONAL_ORDER`.
+    ///
+    /// `source` - The source grid from which to read data.
+    /// `f` - The function to apply.
+    pub fn set_from_padded_neighbours_diagonals_par<
+                U: Send + Sync,
+                F: Fn(&U, &U, &U, &U, &U, &U, &U, &U, &U) -> T + Send + Sync
+            >(&mut self, source: &PaddedCubeSphereGrid<U, S>, f: F) where T: Send + Sync {
+        self.set_from_index_par(|index| {
+            let [up_left, up, up_right, left, current, right, down_left, down, down_right] = source.neighbours_diagonals_at(index);
+
+            f(up_left, up, up_right, left, current, right, down_left, down, down_right)
+        })
+    }
+
+    /// Creates a grid from the values of its cells in the order of `points`.
+    ///
+    /// - `values` - The values of the cells.
+    fn from_values(values: Vec<T>) -> Self {
+        let border: Vec<_> = Self::border_indices()
+            .map(|i| values[Self::point_index(Self::source_index(i))].clone())
+            .collect();
+
+        let mut values = values.into_iter();
+        let mut border = border.into_iter();
+
+        let data = (0..6 * Self::FACE_LEN)
+            .map(|i| if Self::is_border(i) { border.next() } else { values.next() }.unwrap())
+            .collect();
+
+        Self {
+            data,
+            stale: false,
+        }
+    }
+
+    /// Sets every cell in parallel from a function of its index in the order of `points`.
+    ///
+    /// - `f` - The function that gives the new value of each cell.
+    fn set_from_index_par<F: Fn(usize) -> T + Send + Sync>(&mut self, f: F) where T: Send + Sync {
+        self.data.par_chunks_mut(Self::WIDTH)
+            .enumerate()
+            .filter(|(row, _)| !Self::is_border(row * Self::WIDTH + 1))
+            .for_each(|(row, cells)| {
+                let face = row / Self::WIDTH;
+                let start = (face * S + row % Self::WIDTH - 1) * S;
+
+                for (x, value) in cells[1..=S].iter_mut().enumerate() {
+                    *value = f(start + x);
+                }
+            });
+
+        self.refresh_padding();
+    }
+
+}
+
+impl <T: Clone + Default, const S: usize> Default for PaddedCubeSphereGrid<T, S> {
+    fn default() -> Self {
+        Self::from_fn(|_| T::default())
+    }
+}
+
+impl <T: PartialEq, const S: usize> PartialEq for PaddedCubeSphereGrid<T, S> {
+    /// Compares the cells of the grids without comparing their borders.
+    fn eq(&self, other: &Self) -> bool {
+        (0..6 * S * S).all(|index| {
+            let i = Self::storage_index(index);
+
+            self.data[i] == other.data[i]
+        })
+    }
+}
+
+impl <T: Eq, const S: usize> Eq for PaddedCubeSphereGrid<T, S> {}
+
+impl <T: Clone, const S: usize> SurfaceGrid<T> for PaddedCubeSphereGrid<T, S> {
+    type Point = CubeSpherePoint<S>;
+
+    fn from_fn<F: FnMut(&Self::Point) -> T>(mut f: F) -> Self {
+        Self::from_values((0..6 * S * S).map(|index| f(&CubeSpherePoint::from_index(index))).collect())
+    }
+
+    fn from_fn_par<F: Fn(&Self::Point) -> T + Send + Sync>(f: F) -> Self where T: Send + Sync {
+        Self::from_values((0..6 * S * S).into_par_iter().map(|index| f(&CubeSpherePoint::from_index(index))).collect())
+    }
+
+    fn try_from_fn<X, F: FnMut(&Self::Point) -> Result<T, X>>(mut f: F) -> Result<Self, X> {
+        Ok(Self::from_values((0..6 * S * S).map(|index| f(&CubeSpherePoint::from_index(index))).collect::<Result<_, _>>()?))
+    }
+
+    fn try_from_fn_par<X: Send, F: Fn(&Self::Point) -> Result<T, X> + Send + Sync>(f: F) -> Result<Self, X> where T: Send + Sync {
+        Ok(Self::from_values((0..6 * S * S).into_par_iter().map(|index| f(&CubeSpherePoint::from_index(index))).collect::<Result<_, _>>()?))
+    }
+
+    fn from_vec(values: Vec<T>) -> Option<Self> {
+        (values.len() == 6 * S * S).then(|| Self::from_values(values))
+    }
+
+    fn map_neighbours<F: FnMut(&T, &T, &T, &T, &T) -> T>(&self, mut f: F) -> Self {
+        Self::from_values((0..6 * S * S).map(|index| {
+            let [current, up, down, left, right] = self.neighbours_at(index);
+
+            f(current, up, down, left, right)
+        }).collect())
+    }
+
+    fn map_neighbours_diagonals<
+                F: FnMut(&T, &T, &T, &T, &T, &T, &T, &T, &T) -> T
+            >(&self, mut f: F) -> Self {
+        Self::from_values((0..6 * S * S).map(|index| {
+            let [up_left, up, up_right, left, current, right, down_left, down, down_right] = self.neighbours_diagonals_at(index);
+
+            f(up_left, up, up_right, left, current, right, down_left, down, down_right)
+        }).collect())
+    }
+
+    fn map_neighbours_par<
+                F: Fn(&T, &T, &T, &T, &T) -> T + Send + Sync
+            >(&self, f: F) -> Self where T: Send + Sync {
+        Self::from_values((0..6 * S * S).into_par_iter().map(|index| {
+            let [current, up, down, left, right] = self.neighbours_at(index);
+
+            f(current, up, down, left, right)
+        }).collect())
+    }
+
+    fn map_neighbours_diagonals_par<
+                F: Fn(&T, &T, &T, &T, &T, &T, &T, &T, &T) -> T + Send + Sync
+            >(&self, f: F) -> Self where T: Send + Sync {
+        Self::from_values((0..6 * S * S).into_par_iter().map(|index| {
+            let [up_left, up, up_right, left, current, right, down_left, down, down_right] = self.neighbours_diagonals_at(index);
+
+            f(up_left, up, up_right, left, current, right, down_left, down, down_right)
+        }).collect())
+    }
+
+    fn set_from_fn<F: FnMut(&Self::Point) -> T>(&mut self, mut f: F) {
+        for (i, value) in self.data.iter_mut().enumerate() {
+            if !Self::is_border(i) {
+                *value = f(&CubeSpherePoint::from_index(Self::point_index(i)));
+            }
+        }
+
+        self.refresh_padding();
+    }
+
+    fn set_from_fn_par<F: Fn(&Self::Point) -> T + Send + Sync>(&mut self, f: F) where T: Send + Sync {
+        self.set_from_index_par(|index| f(&CubeSpherePoint::from_index(index)));
+    }
+
+    fn step_into<
+                F: Fn(&T, &T, &T, &T, &T, &T, &T, &T, &T) -> T + Send + Sync
+            >(&self, dest: &mut Self, f: F) where T: Send + Sync {
+        dest.set_from_padded_neighbours_diagonals_par(self, f)
+    }
+
+    fn iter<'a>(&'a self) -> impl ExactSizeIterator<Item = (Self::Point, &'a T)> + DoubleEndedIterator where T: 'a {
+        (0..6 * S * S).map(|index| (CubeSpherePoint::from_index(index), &self.data[Self::storage_index(index)]))
+    }
+
+    fn par_iter<'a>(&'a self) -> impl IndexedParallelIterator<Item = (Self::Point, &'a T)> where T: 'a + Send + Sync {
+        (0..6 * S * S).into_par_iter().map(|index| (CubeSpherePoint::from_index(index), &self.data[Self::storage_index(index)]))
+    }
+
+    fn par_iter_mut<'a>(&'a mut self) -> impl IndexedParallelIterator<Item = (Self::Point, &'a mut T)> where T: 'a + Send + Sync {
+        // The cells may be changed so the borders can no longer be trusted.
+        self.stale = true;
+
+        self.data.par_iter_mut()
+            .enumerate()
+            .filter(|(i, _)| !Self::is_border(*i))
+            .map(|(i, value)| (CubeSpherePoint::from_index(Self::point_index(i)), value))
+            // The borders are skipped so the cells are gathered in order to index them again.
+            .collect::<Vec<_>>()
+            .into_par_iter()
+    }
+
+    fn points(&self) -> impl ExactSizeIterator<Item = Self::Point> + DoubleEndedIterator {
+        (0..6 * S * S).map(CubeSpherePoint::from_index)
+    }
+
+    fn par_points(&self) -> impl IndexedParallelIterator<Item = Self::Point> {
+        (0..6 * S * S).into_par_iter().map(CubeSpherePoint::from_index)
+    }
+
+    fn par_map_to_vec<U: Send, F: Fn(&Self::Point, &T) -> U + Send + Sync>(&self, f: F) -> Vec<U> where T: Send + Sync {
+        self.par_iter()
+            .map(|(point, value)| f(&point, value))
+            .collect()
+    }
+
+    fn to_index(&self, point: &Self::Point) -> usize {
+        point.index()
+    }
+
+    fn at(&self, point: &Self::Point) -> &T {
+        &self[point]
+    }
+
+    fn at_mut(&mut self, point: &Self::Point) -> &mut T {
+        &mut self[point]
+    }
+
+    fn surface_area(&self, scale: f64) -> f64 {
+        4.0 * PI * scale * scale
+    }
+
+    fn topology(&self) -> Topology {
+        Topology::uniform(Surface::Sphere, 6, S, S)
+    }
+}
+
+impl <T, const S: usize> Index<CubeSpherePoint<S>> for PaddedCubeSphereGrid<T, S> {
+    type Output = T;
+
+    fn index(&self, index: CubeSpherePoint<S>) -> &Self::Output {
+        &self.data[Self::storage_index(index.index())]
+    }
+}
+
+impl <T, const S: usize> IndexMut<CubeSpherePoint<S>> for PaddedCubeSphereGrid<T, S> {
+    /// Gets a mutable reference to the value of a cell, marking the border as stale.
+    fn index_mut(&mut self, index: CubeSpherePoint<S>) -> &mut Self::Output {
+        self.stale = true;
+
+        &mut self.data[Self::storage_index(index.index())]
+    }
+}
+
+impl <T, const S: usize> Index<&CubeSpherePoint<S>> for PaddedCubeSphereGrid<T, S> {
+    type Output = T;
+
+    fn index(&self, index: &CubeSpherePoint<S>) -> &Self::Output {
+        &self[*index]
+    }
+}
+
+impl <T, const S: usize> IndexMut<&CubeSpherePoint<S>> for PaddedCubeSphereGrid<T, S> {
+    fn index_mut(&mut self, index: &CubeSpherePoint<S>) -> &mut Self::Output {
+        &mut self[*index]
+    }
+}
+
+impl <T: Clone, const S: usize> FromIterator<T> for PaddedCubeSphereGrid<T, S> {
+    /// Creates a grid from the values of its cells in the same order as `points`.
+    ///
+    /// # Panics
+    /// Panics if the iterator does not yield exactly one value for each cell in the grid.
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        Self::from_vec(iter.into_iter().collect())
+            .expect("the iterator must yield one value for each cell in the grid")
+    }
+}
+
+impl <T, const S: usize> IntoIterator for PaddedCubeSphereGrid<T, S> {
+    type Item = (CubeSpherePoint<S>, T);
+
+    type IntoIter = vec::IntoIter<Self::Item>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        let data: Vec<_> = self.data.into_iter()
+            .enumerate()
+            .filter(|(i, _)| !Self::is_border(*i))
+            .map(|(i, value)| (CubeSpherePoint::from_index(Self::point_index(i)), value))
+            .collect();
+
+        data.into_iter()
+    }
+}
+
+impl <T: Clone, const S: usize> From<CubeSphereGrid<T, S>> for PaddedCubeSphereGrid<T, S> {
+    fn from(grid: CubeSphereGrid<T, S>) -> Self {
+        Self::from_values(grid.into_iter().map(|(_, value)| value).collect())
+    }
+}
+
+impl <T, const S: usize> From<PaddedCubeSphereGrid<T, S>> for CubeSphereGrid<T, S> {
+    fn from(grid: PaddedCubeSphereGrid<T, S>) -> Self {
+        let values = grid.into_iter().map(|(_, value)| value).collect();
+
+        CubeSphereGrid::from_vec(values).unwrap()
+    }
+}
+
+#[cfg(feature = "serde")]
+impl <T: serde::Serialize, const S: usize> serde::Serialize for PaddedCubeSphereGrid<T, S> {
+    /// Serializes the values in this grid as a sequence in the same order as `iter`, without the
+    /// borders.
+    fn serialize<R: serde::Serializer>(&self, serializer: R) -> Result<R::Ok, R::Error> {
+        serializer.collect_seq((0..6 * S * S).map(|index| &self.data[Self::storage_index(index)]))
+    }
+}
+
+#[cfg(feature = "serde")]
+impl <'de, T: serde::Deserialize<'de> + Clone, const S: usize> serde::Deserialize<'de> for PaddedCubeSphereGrid<T, S> {
+    /// Deserializes a grid from a sequence of values in the same order as `iter`.
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let values = Vec::<T>::deserialize(deserializer)?;
+        let length = values.len();
+
+        Self::from_vec(values)
+            .ok_or_else(|| serde::de::Error::invalid_length(length, &format!("a sequence of {} values", 6 * S * S).as_str()))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{GridPoint, SurfaceGrid, sphere::CubeSphereGrid};
+
+    use super::PaddedCubeSphereGrid;
+
+    /// Creates a grid with a different value in each cell.
+    fn test_grid<const S: usize>() -> CubeSphereGrid<f64, S> {
+        CubeSphereGrid::from_fn(|point| {
+            let (x, y, z) = point.position(1.0);
+
+            x * 7.0 + y * 3.0 - z
+        })
+    }
+
+    #[test]
+    fn test_padded_map_neighbours() {
+        let dense = test_grid::<8>();
+        let padded: PaddedCubeSphereGrid<f64, 8> = dense.clone().into();
+
+        let rule = |current: &f64, up: &f64, down: &f64, left: &f64, right: &f64| current - up * 2.0 + down * 3.0 + left * 0.5 - right;
+
+        assert_eq!(dense.map_neighbours(rule), padded.map_neighbours(rule).into());
+        assert_eq!(dense.map_neighbours_par(rule), padded.map_neighbours_par(rule).into());
+    }
+
+    #[test]
+    fn test_padded_map_neighbours_diagonals() {
+        let dense = test_grid::<8>();
+        let padded: PaddedCubeSphereGrid<f64, 8> = dense.clone().into();
+
+        let rule = |a: &f64, b: &f64, c: &f64, d: &f64, e: &f64, f: &f64, g: &f64, h: &f64, i: &f64| {
+            a + b * 2.0 + c * 3.0 + d * 5.0 + e * 7.0 + f * 11.0 + g * 13.0 + h * 17.0 + i * 19.0
+        };
+
+        assert_eq!(dense.map_neighbours_diagonals(rule), padded.map_neighbours_diagonals(rule).into());
+        assert_eq!(dense.map_neighbours_diagonals_par(rule), padded.map_neighbours_diagonals_par(rule).into());
+
+        let mut dest = PaddedCubeSphereGrid::default();
+        padded.step_into(&mut dest, rule);
+
+        assert_eq!(dense.map_neighbours_diagonals(rule), dest.into());
+    }
+
+    #[test]
+    fn test_padded_stale_after_index_mut() {
+        let mut dense = test_grid::<4>();
+        let mut padded: PaddedCubeSphereGrid<f64, 4> = dense.clone().into();
+
+        let rule = |current: &f64, up: &f64, down: &f64, left: &f64, right: &f64| current + up * 2.0 + down * 3.0 + left * 5.0 + right * 7.0;
+
+        for point in dense.points().filter(|point| point.face_coordinates().1 == 0).collect::<Vec<_>>() {
+            dense[point] = 100.0;
+            padded[point] = 100.0;
+        }
+
+        assert!(padded.is_padding_stale());
+        assert_eq!(dense.map_neighbours(rule), padded.map_neighbours(rule).into());
+
+        padded.refresh_padding();
+
+        assert!(!padded.is_padding_stale());
+        assert_eq!(dense.map_neighbours(rule), padded.map_neighbours(rule).into());
+    }
+
+    #[test]
+    fn test_padded_set_from_neighbours_par() {
+        let dense = test_grid::<4>();
+        let padded: PaddedCubeSphereGrid<f64, 4> = dense.clone().into();
+
+        let mut dest: PaddedCubeSphereGrid<f64, 4> = PaddedCubeSphereGrid::default();
+        dest.set_from_padded_neighbours_par(&padded, |current, up, down, left, right| current * up - down * left + right);
+
+        let expected = dense.map_neighbours(|current, up, down, left, right| current * up - down * left + right);
+
+        assert_eq!(expected, dest.clone().into());
+        assert!(dest.iter().all(|(point, value)| *value == expected[point]));
+        assert_eq!(6 * 4 * 4, dest.into_iter().count());
+    }
+}
@@ -0,0 +1,673 @@
+//! A module containing grids on flat planes with configurable behaviour at their edges.
+
+use std::{fmt::Debug, hash::Hash, marker::PhantomData, ops::{Index, IndexMut}, vec};
+
+use rayon::prelude::*;
+use static_array::HeapArray2D;
+
+use crate::{GridPoint, OutOfBounds, Surface, SurfaceGrid, Topology};
+
+assert_send_sync!([T: Send + Sync, const W: usize, const H: usize, E: PlaneEdges]
+    PlaneGrid<T, W, H, E>,
+    PlanePoint<W, H, E>,
+);
+
+/// The ways that the edges of a plane can behave.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum PlaneEdge {
+    /// Moving off an edge continues from the opposite edge like a torus.
+    Wrap,
+    /// Moving off an edge stays at the edge.
+    Clamp,
+    /// Moving off an edge bounces back into the cell next to the edge.
+    Mirror,
+    /// Moving off an edge reaches a single cell that stands for everything outside of the plane.
+    Constant,
+}
+
+/// A type parameter that selects how the edges of a `PlaneGrid` behave.
+pub trait PlaneEdges: Debug + Clone + Copy + PartialEq + Eq + Hash + Default + Send + Sync {
+    /// The behaviour of the edges.
+    const EDGE: PlaneEdge;
+}
+
+/// The edges of a plane where moving off an edge continues from the opposite edge.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct Wrapped;
+
+impl PlaneEdges for Wrapped {
+    const EDGE: PlaneEdge = PlaneEdge::Wrap;
+}
+
+/// The edges of a plane where moving off an edge stays at the edge.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct Clamped;
+
+impl PlaneEdges for Clamped {
+    const EDGE: PlaneEdge = PlaneEdge::Clamp;
+}
+
+/// The edges of a plane where moving off an edge bounces back into the cell next to the edge.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct Mirrored;
+
+impl PlaneEdges for Mirrored {
+    const EDGE: PlaneEdge = PlaneEdge::Mirror;
+}
+
+/// The edges of a plane surrounded by a single cell holding a constant value.
+///
+/// The cell outside of the plane is its own neighbour in every direction, so with rules such as
+/// Conway's game of life a constant value stays constant.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct Constant;
+
+impl PlaneEdges for Constant {
+    const EDGE: PlaneEdge = PlaneEdge::Constant;
+}
+
+/// A flat rectangular grid where the behaviour at the edges is chosen by a type parameter.
+///
+/// # Type Parameters
+/// - `T` - The type of data that the grid holds.
+/// - `E` - The behaviour of the edges, one of `Wrapped`, `Clamped`, `Mirrored` or `Constant`.
+///
+/// # Constant Parameters
+/// - `W` - The width of the grid.
+/// - `H` - The height of the grid.
+///
+/// # Constant Edges
+/// Grids with `Constant` edges have one extra cell, the outside point, that is the neighbour of
+/// every cell on the edge of the plane and holds the value seen when looking off the plane. It is
+/// visited after all of the other cells so it is included in `iter`, `from_fn` and `from_vec`
+/// like any other cell.
+///
+/// # Iteration Order
+/// `iter`, `points` and `into_iter` all visit the grid in row-major order, starting at `(0, 0)`
+/// and increasing `x` before `y`, followed by the outside point for `Constant` edges. This order
+/// is stable.
+///
+/// # Thread Safety
+/// The rectangle is kept in a `HeapArray2D` and the value outside of it in an `Option`, and `E`
+/// only chooses how the edges behave, so the grid is `Send` when `T` is `Send` and `Sync` when `T`
+/// is `Sync`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct PlaneGrid<T, const W: usize, const H: usize, E: PlaneEdges = Clamped> {
+    /// The data held in this grid.
+    data: HeapArray2D<T, W, H>,
+    /// The value outside of the grid if the edges are constant.
+    outside: Option<T>,
+    /// The behaviour of the edges.
+    edges: PhantomData<E>,
+}
+
+impl <T: Default, const W: usize, const H: usize, E: PlaneEdges> Default for PlaneGrid<T, W, H, E> {
+    fn default() -> Self {
+        Self::from_fn(|_| T::default())
+    }
+}
+
+impl <T, const W: usize, const H: usize, E: PlaneEdges> PlaneGrid<T, W, H, E> {
+    /// Gets the values of the cells in the same order as the points of the grid.
+    ///
+    /// This holds the `W` by `H` cells of the rectangle and does not include the value read outside
+    /// the grid by `Constant` edges.
+    pub fn as_slice(&self) -> &[T] {
+        self.data.as_flattened()
+    }
+
+    /// Gets mutable references to the values of the cells in the same order as the points of the
+    /// grid.
+    ///
+    /// This holds the `W` by `H` cells of the rectangle and does not include the value read outside
+    /// the grid by `Constant` edges.
+    pub fn as_mut_slice(&mut self) -> &mut [T] {
+        self.data.as_flattened_mut()
+    }
+
+    /// Gets the values of the cells as bytes in the same order as `as_slice`.
+    #[cfg(feature = "bytemuck")]
+    pub fn as_bytes(&self) -> &[u8] where T: bytemuck::Pod {
+        bytemuck::cast_slice(self.as_slice())
+    }
+
+    /// Gets the values of the cells as mutable bytes in the same order as `as_slice`.
+    #[cfg(feature = "bytemuck")]
+    pub fn as_bytes_mut(&mut self) -> &mut [u8] where T: bytemuck::Pod {
+        bytemuck::cast_slice_mut(self.as_mut_slice())
+    }
+
+    /// Gets the number of cells in this grid including the outside point.
+    fn cells() -> usize {
+        match E::EDGE {
+            PlaneEdge::Constant => W * H + 1,
+            _ => W * H,
+        }
+    }
+}
+
+impl <T, const W: usize, const H: usize, E: PlaneEdges> SurfaceGrid<T> for PlaneGrid<T, W, H, E> {
+    type Point = PlanePoint<W, H, E>;
+
+    fn from_fn<F: FnMut(&Self::Point) -> T>(mut f: F) -> Self {
+        let data = HeapArray2D::from_fn(|y, x| f(&PlanePoint::new(x as u32, y as u32)));
+        let outside = (E::EDGE == PlaneEdge::Constant).then(|| f(&PlanePoint::outside()));
+
+        Self {
+            data,
+            outside,
+            edges: PhantomData,
+        }
+    }
+
+    fn from_fn_par<F: Fn(&Self::Point) -> T + Send + Sync>(f: F) -> Self where T: Send + Sync {
+        let data = HeapArray2D::from_fn_par(|y, x| f(&PlanePoint::new(x as u32, y as u32)));
+        let outside = (E::EDGE == PlaneEdge::Constant).then(|| f(&PlanePoint::outside()));
+
+        Self {
+            data,
+            outside,
+            edges: PhantomData,
+        }
+    }
+
+    fn try_from_fn<X, F: FnMut(&Self::Point) -> Result<T, X>>(mut f: F) -> Result<Self, X> {
+        let values = (0..Self::cells())
+            .map(PlanePoint::from_index)
+            .map(|point| f(&point))
+            .collect::<Result<_, _>>()?;
+
+        // The values are in the same order as `points`.
+        Ok(Self::from_vec(values).unwrap())
+    }
+
+    fn try_from_fn_par<X: Send, F: Fn(&Self::Point) -> Result<T, X> + Send + Sync>(f: F) -> Result<Self, X> where T: Send + Sync {
+        let values = (0..Self::cells()).into_par_iter()
+            .map(PlanePoint::from_index)
+            .map(|point| f(&point))
+            .collect::<Result<_, _>>()?;
+
+        Ok(Self::from_vec(values).unwrap())
+    }
+
+    fn from_vec(values: Vec<T>) -> Option<Self> {
+        if values.len() != Self::cells() {
+            return None;
+        }
+
+        // `from_fn` visits the points in the same order as `points`.
+        let mut values = values.into_iter();
+
+        Some(Self::from_fn(|_| values.next().unwrap()))
+    }
+
+    fn set_from_fn<F: FnMut(&Self::Point) -> T>(&mut self, mut f: F) {
+        (0..Self::cells())
+            .map(PlanePoint::from_index)
+            .for_each(|point| self[point] = f(&point))
+    }
+
+    fn set_from_fn_par<F: Fn(&Self::Point) -> T + Send + Sync>(&mut self, f: F) where T: Send + Sync {
+        self.data.par_iter_mut().enumerate().for_each(|(y, subarray)| {
+            for (x, value) in subarray.iter_mut().enumerate() {
+                let point = PlanePoint::new(x as u32, y as u32);
+
+                *value = f(&point);
+            }
+        });
+
+        if let Some(outside) = &mut self.outside {
+            *outside = f(&PlanePoint::outside());
+        }
+    }
+
+    fn iter<'a>(&'a self) -> impl ExactSizeIterator<Item = (Self::Point, &'a T)> + DoubleEndedIterator where T: 'a {
+        self.points()
+            .map(|point| (point, &self[point]))
+    }
+
+    fn par_iter<'a>(&'a self) -> impl IndexedParallelIterator<Item = (Self::Point, &'a T)> where T: 'a + Send + Sync {
+        self.par_points()
+            .map(|point| (point, &self[point]))
+    }
+
+    fn par_iter_mut<'a>(&'a mut self) -> impl IndexedParallelIterator<Item = (Self::Point, &'a mut T)> where T: 'a + Send + Sync {
+        self.data.as_flattened_mut().par_iter_mut()
+            .enumerate()
+            .map(|(i, value)| (PlanePoint::new((i % W) as u32, (i / W) as u32), value))
+    }
+
+    fn points(&self) -> impl ExactSizeIterator<Item = Self::Point> + DoubleEndedIterator {
+        (0..Self::cells())
+            .map(PlanePoint::from_index)
+    }
+
+    fn par_points(&self) -> impl IndexedParallelIterator<Item = Self::Point> {
+        (0..Self::cells()).into_par_iter()
+            .map(PlanePoint::from_index)
+    }
+
+    fn par_map_to_vec<U: Send, F: Fn(&Self::Point, &T) -> U + Send + Sync>(&self, f: F) -> Vec<U> where T: Send + Sync {
+        let mut result = Vec::with_capacity(Self::cells());
+
+        (0..Self::cells()).into_par_iter()
+            .map(|i| {
+                let point = PlanePoint::from_index(i);
+
+                f(&point, &self[point])
+            })
+            .collect_into_vec(&mut result);
+
+        result
+    }
+
+    fn to_index(&self, point: &Self::Point) -> usize {
+        point.y as usize * W + point.x as usize
+    }
+
+    fn at(&self, point: &Self::Point) -> &T {
+        &self[point]
+    }
+
+    fn at_mut(&mut self, point: &Self::Point) -> &mut T {
+        &mut self[point]
+    }
+
+    /// Gets the surface area of the plane.
+    ///
+    /// This is the same as `FlatGrid::surface_area` and does not include the outside point.
+    fn surface_area(&self, scale: f64) -> f64 {
+        let size = W.max(H) as f64;
+
+        (W * H) as f64 / (size * size) * scale * scale
+    }
+
+    fn topology(&self) -> Topology {
+        // The outside point of `Constant` edges is a cell but is not part of the face.
+        Topology {
+            len: Self::cells(),
+            ..Topology::uniform(Surface::Plane, 1, W, H)
+        }
+    }
+
+    fn contiguous_values_mut(&mut self) -> Option<&mut [T]> {
+        // The outside point of `Constant` edges is kept apart from the other cells.
+        if self.outside.is_none() {
+            Some(self.as_mut_slice())
+        } else {
+            None
+        }
+    }
+}
+
+impl <T, const W: usize, const H: usize, E: PlaneEdges> Index<PlanePoint<W, H, E>> for PlaneGrid<T, W, H, E> {
+    type Output = T;
+
+    fn index(&self, index: PlanePoint<W, H, E>) -> &Self::Output {
+        if index.is_outside() {
+            self.outside.as_ref().unwrap()
+        } else {
+            &self.data[index.y as usize][index.x as usize]
+        }
+    }
+}
+
+impl <T, const W: usize, const H: usize, E: PlaneEdges> IndexMut<PlanePoint<W, H, E>> for PlaneGrid<T, W, H, E> {
+    fn index_mut(&mut self, index: PlanePoint<W, H, E>) -> &mut Self::Output {
+        if index.is_outside() {
+            self.outside.as_mut().unwrap()
+        } else {
+            &mut self.data[index.y as usize][index.x as usize]
+        }
+    }
+}
+
+impl <T, const W: usize, const H: usize, E: PlaneEdges> Index<&PlanePoint<W, H, E>> for PlaneGrid<T, W, H, E> {
+    type Output = T;
+
+    fn index(&self, index: &PlanePoint<W, H, E>) -> &Self::Output {
+        &self[*index]
+    }
+}
+
+impl <T, const W: usize, const H: usize, E: PlaneEdges> IndexMut<&PlanePoint<W, H, E>> for PlaneGrid<T, W, H, E> {
+    fn index_mut(&mut self, index: &PlanePoint<W, H, E>) -> &mut Self::Output {
+        &mut self[*index]
+    }
+}
+
+impl <T, const W: usize, const H: usize, E: PlaneEdges> FromIterator<T> for PlaneGrid<T, W, H, E> {
+    /// Creates a grid from the values of its cells in the same order as `points`.
+    ///
+    /// # Panics
+    /// Panics if the iterator does not yield exactly one value for each cell in the grid.
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        Self::from_vec(iter.into_iter().collect())
+            .expect("the iterator must yield one value for each cell in the grid")
+    }
+}
+
+impl <T, const W: usize, const H: usize, E: PlaneEdges> IntoIterator for PlaneGrid<T, W, H, E> {
+    type Item = (PlanePoint<W, H, E>, T);
+
+    type IntoIter = vec::IntoIter<Self::Item>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        let data: Vec<_> = self.data.into_iter()
+            .flat_map(|subarray| subarray.into_iter())
+            .chain(self.outside)
+            .enumerate()
+            .map(|(i, value)| (PlanePoint::from_index(i), value))
+            .collect();
+
+        data.into_iter()
+    }
+}
+
+#[cfg(feature = "serde")]
+impl <T: serde::Serialize, const W: usize, const H: usize, E: PlaneEdges> serde::Serialize for PlaneGrid<T, W, H, E> {
+    /// Serializes the values in this grid as a sequence in the same order as `iter`.
+    fn serialize<R: serde::Serializer>(&self, serializer: R) -> Result<R::Ok, R::Error> {
+        serializer.collect_seq(self.iter().map(|(_, value)| value))
+    }
+}
+
+#[cfg(feature = "serde")]
+impl <'de, T: serde::Deserialize<'de>, const W: usize, const H: usize, E: PlaneEdges> serde::Deserialize<'de> for PlaneGrid<T, W, H, E> {
+    /// Deserializes a grid from a sequence of values in the same order as `iter`.
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let values = Vec::<T>::deserialize(deserializer)?;
+        let length = values.len();
+
+        Self::from_vec(values)
+            .ok_or_else(|| serde::de::Error::invalid_length(length, &format!("a sequence of {} values", Self::cells()).as_str()))
+    }
+}
+
+/// A point on a `PlaneGrid`.
+///
+/// With `Clamped`, `Mirrored` and `Constant` edges the `checked_` methods of `GridPoint` return
+/// `None` when moving off an edge or away from the outside point.
+///
+/// # Type Parameters
+/// - `E` - The behaviour of the edges of the plane.
+///
+/// # Constant Parameters
+/// - `W` - The width of the grid.
+/// - `H` - The height of the grid.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct PlanePoint<const W: usize, const H: usize, E: PlaneEdges = Clamped> {
+    x: u32,
+    y: u32,
+    edges: PhantomData<E>,
+}
+
+impl <const W: usize, const H: usize, E: PlaneEdges> PlanePoint<W, H, E> {
+    /// Creates a new point.
+    ///
+    /// - `x` - The X coordinate.
+    /// - `y` - The Y coordinate.
+    fn new(x: u32, y: u32) -> Self {
+        Self {
+            x,
+            y,
+            edges: PhantomData,
+        }
+    }
+
+    /// Creates a new `PlanePoint` checking that the position lies on the grid.
+    ///
+    /// This returns an error if `x` is `W` or greater or `y` is `H` or greater.
+    ///
+    /// - `x` - The X coordinate.
+    /// - `y` - The Y coordinate.
+    pub fn try_new(x: u32, y: u32) -> Result<Self, OutOfBounds> {
+        if (x as usize) < W && (y as usize) < H {
+            Ok(Self::new(x, y))
+        } else {
+            Err(OutOfBounds)
+        }
+    }
+
+    /// Gets the point that stands for everything outside of a plane with `Constant` edges.
+    fn outside() -> Self {
+        // This is the index after the last cell.
+        Self::new(0, H as u32)
+    }
+
+    /// Gets the point at an index in the order that `PlaneGrid::points` visits them.
+    ///
+    /// - `index` - The index of the point.
+    fn from_index(index: usize) -> Self {
+        Self::new((index % W) as u32, (index / W) as u32)
+    }
+
+    /// Determines whether this is the point outside of a plane with `Constant` edges.
+    pub fn is_outside(&self) -> bool {
+        self.y == H as u32
+    }
+
+    /// Gets the coordinate reached by moving off the end of an axis.
+    ///
+    /// - `coordinate` - The coordinate on the axis.
+    /// - `size` - The length of the axis.
+    /// - `forwards` - Whether the movement increases the coordinate.
+    ///
+    /// This returns `None` if the movement reaches the outside point.
+    fn step(coordinate: u32, size: u32, forwards: bool) -> Option<u32> {
+        let end = if forwards { size - 1 } else { 0 };
+
+        if coordinate != end {
+            return Some(if forwards { coordinate + 1 } else { coordinate - 1 });
+        }
+
+        match E::EDGE {
+            PlaneEdge::Wrap => Some(if forwards { 0 } else { size - 1 }),
+            PlaneEdge::Clamp => Some(coordinate),
+            PlaneEdge::Mirror if size > 1 => Some(if forwards { coordinate - 1 } else { coordinate + 1 }),
+            PlaneEdge::Mirror => Some(coordinate),
+            PlaneEdge::Constant => None,
+        }
+    }
+
+    /// Moves this point along the Y axis.
+    ///
+    /// - `forwards` - Whether the movement increases the Y coordinate.
+    fn step_y(&self, forwards: bool) -> Self {
+        if self.is_outside() {
+            return *self;
+        }
+
+        Self::step(self.y, H as u32, forwards)
+            .map(|y| Self::new(self.x, y))
+            .unwrap_or_else(Self::outside)
+    }
+
+    /// Moves this point along the X axis.
+    ///
+    /// - `forwards` - Whether the movement increases the X coordinate.
+    fn step_x(&self, forwards: bool) -> Self {
+        if self.is_outside() {
+            return *self;
+        }
+
+        Self::step(self.x, W as u32, forwards)
+            .map(|x| Self::new(x, self.y))
+            .unwrap_or_else(Self::outside)
+    }
+}
+
+impl <const W: usize, const H: usize, E: PlaneEdges> GridPoint for PlanePoint<W, H, E> {
+    fn up(&self) -> Self {
+        self.step_y(false)
+    }
+
+    fn down(&self) -> Self {
+        self.step_y(true)
+    }
+
+    fn left(&self) -> Self {
+        self.step_x(false)
+    }
+
+    fn right(&self) -> Self {
+        self.step_x(true)
+    }
+
+    fn checked_up(&self) -> Option<Self> {
+        (E::EDGE == PlaneEdge::Wrap || (!self.is_outside() && self.y > 0)).then(|| self.up())
+    }
+
+    fn checked_down(&self) -> Option<Self> {
+        (E::EDGE == PlaneEdge::Wrap || (!self.is_outside() && self.y < H as u32 - 1)).then(|| self.down())
+    }
+
+    fn checked_left(&self) -> Option<Self> {
+        (E::EDGE == PlaneEdge::Wrap || (!self.is_outside() && self.x > 0)).then(|| self.left())
+    }
+
+    fn checked_right(&self) -> Option<Self> {
+        (E::EDGE == PlaneEdge::Wrap || (!self.is_outside() && self.x < W as u32 - 1)).then(|| self.right())
+    }
+
+    /// Gets the position of the point in 3D space.
+    ///
+    /// This is the same as `FlatPoint::position`. The outside point has no position so all of its
+    /// coordinates are NaN.
+    ///
+    /// - `scale` - The scale of the 3D object.
+    fn position(&self, scale: f64) -> (f64, f64, f64) {
+        if self.is_outside() {
+            return (f64::NAN, f64::NAN, f64::NAN);
+        }
+
+        let size = W.max(H) as f64;
+
+        let x = (self.x as f64 - W as f64 / 2.0) / size * scale;
+        let y = (H as f64 / 2.0 - self.y as f64) / size * scale;
+
+        (x, y, 0.0)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use approx::assert_relative_eq;
+
+    use crate::{GridPoint, SurfaceGrid};
+
+    use super::{Clamped, Constant, Mirrored, PlaneGrid, PlanePoint, Wrapped};
+
+    #[test]
+    fn test_plane_wrapped() {
+        let point: PlanePoint<10, 5, Wrapped> = PlanePoint::new(0, 0);
+
+        assert_eq!(point.up(), PlanePoint::new(0, 4));
+        assert_eq!(point.left(), PlanePoint::new(9, 0));
+        assert_eq!(point.checked_up(), Some(point.up()));
+        assert_eq!(PlanePoint::<10, 5, Wrapped>::new(9, 4).down(), PlanePoint::new(9, 0));
+        assert_eq!(PlanePoint::<10, 5, Wrapped>::new(9, 4).right(), PlanePoint::new(0, 4));
+    }
+
+    #[test]
+    fn test_plane_clamped() {
+        let grid: PlaneGrid<(), 10, 5, Clamped> = PlaneGrid::default();
+
+        for point in grid.points() {
+            assert_eq!(point.checked_up().is_none(), point.y == 0);
+            assert_eq!(point.checked_down().is_none(), point.y == 4);
+            assert_eq!(point.checked_left().is_none(), point.x == 0);
+            assert_eq!(point.checked_right().is_none(), point.x == 9);
+
+            assert!(point.checked_up().is_none_or(|up| up == point.up()));
+            assert!(point.checked_right().is_none_or(|right| right == point.right()));
+        }
+
+        assert_eq!(PlanePoint::<10, 5, Clamped>::new(0, 0).up(), PlanePoint::new(0, 0));
+        assert_eq!(PlanePoint::<10, 5, Clamped>::new(9, 4).right(), PlanePoint::new(9, 4));
+    }
+
+    #[test]
+    fn test_plane_mirrored() {
+        let point: PlanePoint<10, 5, Mirrored> = PlanePoint::new(0, 0);
+
+        assert_eq!(point.up(), PlanePoint::new(0, 1));
+        assert_eq!(point.left(), PlanePoint::new(1, 0));
+        assert_eq!(point.checked_up(), None);
+        assert_eq!(PlanePoint::<10, 5, Mirrored>::new(9, 4).down(), PlanePoint::new(9, 3));
+        assert_eq!(PlanePoint::<10, 1, Mirrored>::new(3, 0).down(), PlanePoint::new(3, 0));
+    }
+
+    #[test]
+    fn test_plane_constant() {
+        let grid: PlaneGrid<u32, 4, 3, Constant> = PlaneGrid::from_fn(|point| if point.is_outside() { 7 } else { 1 });
+
+        let outside = PlanePoint::<4, 3, Constant>::outside();
+
+        assert_eq!(grid.points().count(), 13);
+        assert_eq!(grid.points().last(), Some(outside));
+        assert_eq!(grid[outside], 7);
+
+        assert_eq!(PlanePoint::<4, 3, Constant>::new(0, 1).left(), outside);
+        assert_eq!(PlanePoint::<4, 3, Constant>::new(2, 2).down(), outside);
+        assert_eq!(outside.up(), outside);
+        assert_eq!(outside.checked_up(), None);
+
+        // The outside keeps its value when every cell is updated from its neighbours.
+        let sums = grid.map_neighbours(|current, up, down, left, right| {
+            if current == &7 { 7 } else { up + down + left + right }
+        });
+
+        assert_eq!(sums[PlanePoint::new(0, 0)], 16);
+        assert_eq!(sums[PlanePoint::new(1, 1)], 4);
+        assert_eq!(sums[outside], 7);
+    }
+
+    #[test]
+    fn test_plane_iteration_order() {
+        let grid: PlaneGrid<usize, 4, 3, Constant> = PlaneGrid::from_index_fn(|i| i);
+
+        for (i, (point, value)) in grid.iter().enumerate() {
+            assert_eq!(*value, i);
+            assert_eq!(grid.to_index(&point), i);
+        }
+
+        assert_eq!(grid.par_map_to_vec(|_, value| *value), grid.to_vec());
+        assert_eq!(grid.clone().into_iter().map(|(_, value)| value).collect::<Vec<_>>(), grid.to_vec());
+        assert_eq!(PlaneGrid::from_vec(grid.to_vec()), Some(grid));
+        assert_eq!(PlaneGrid::<usize, 4, 3, Clamped>::from_vec((0..13).collect()), None);
+    }
+
+    #[test]
+    fn test_plane_set_from_fn_par() {
+        let mut grid: PlaneGrid<usize, 4, 3, Constant> = PlaneGrid::default();
+
+        grid.set_from_fn_par(|point| if point.is_outside() { 100 } else { point.x as usize });
+
+        assert_eq!(grid[PlanePoint::outside()], 100);
+        assert_eq!(grid[PlanePoint::new(3, 1)], 3);
+    }
+
+    #[test]
+    fn test_plane_position() {
+        let point: PlanePoint<10, 5> = PlanePoint::new(0, 0);
+
+        assert_eq!(point.position(1.0), (-0.5, 0.25, 0.0));
+        assert_relative_eq!(point.right().position(1.0).0 - point.position(1.0).0, 0.1);
+        assert!(PlanePoint::<10, 5, Constant>::outside().position(1.0).0.is_nan());
+    }
+
+    #[test]
+    fn test_plane_len() {
+        let grid: PlaneGrid<u32, 5, 3, Constant> = PlaneGrid::default();
+
+        assert_eq!(grid.points().count(), grid.len());
+        assert_eq!(16, grid.len());
+        assert_eq!(grid.len() - 1, grid.to_index(&PlanePoint::outside()));
+
+        let grid: PlaneGrid<u32, 5, 3, Wrapped> = PlaneGrid::default();
+
+        assert_eq!(grid.points().count(), grid.len());
+        assert_eq!(15, grid.len());
+    }
+}
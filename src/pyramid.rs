@@ -0,0 +1,401 @@
+//! A module containing pyramids of successively coarser versions of a grid.
+
+use std::collections::HashMap;
+
+use crate::{GridPoint, SurfaceGrid};
+
+assert_send_sync!([T: Send + Sync, G: SurfaceGrid<T> + Send + Sync]
+    GridPyramid<T, G>,
+);
+
+/// A way of combining the values of several cells into the value of a coarser cell.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Reduction {
+    /// The mean of the values of the cells of the base grid that make up the coarser cell.
+    Mean,
+    /// The smallest value.
+    Min,
+    /// The largest value.
+    Max,
+}
+
+/// A value that can be combined by a `Reduction`.
+///
+/// The values are combined as `f64`s so integer means are rounded to the nearest integer.
+pub trait PyramidValue: Copy + PartialOrd {
+    /// Converts the value to an `f64`.
+    fn to_f64(self) -> f64;
+
+    /// Converts an `f64` back to a value, rounding if necessary.
+    ///
+    /// - `value` - The value to convert.
+    fn from_f64(value: f64) -> Self;
+}
+
+macro_rules! impl_pyramid_value {
+    (float $($t:ty),*) => {
+        $(
+            impl PyramidValue for $t {
+                fn to_f64(self) -> f64 {
+                    self as f64
+                }
+
+                fn from_f64(value: f64) -> Self {
+                    value as $t
+                }
+            }
+        )*
+    };
+    (int $($t:ty),*) => {
+        $(
+            impl PyramidValue for $t {
+                fn to_f64(self) -> f64 {
+                    self as f64
+                }
+
+                fn from_f64(value: f64) -> Self {
+                    value.round() as $t
+                }
+            }
+        )*
+    };
+}
+
+impl_pyramid_value!(float f32, f64);
+impl_pyramid_value!(int u8, u16, u32, u64, usize, i8, i16, i32, i64, isize);
+
+/// A grid along with successively coarser versions of it, for drawing a surface at a distance or
+/// for analysing it at several scales.
+///
+/// Level 0 is the grid itself. Each following level groups the cells of the previous level
+/// into roughly twice as few cells along each axis by binning the positions of the cells in 3D
+/// space, so a pyramid can be built over any grid. Each cell of a level lies inside exactly one
+/// cell of the next level. The value of a coarse cell is found by combining the values of the cells
+/// of the grid inside it with a `Reduction`.
+///
+/// The levels are built from the positions given by `GridPoint::position` with a scale of 1. The
+/// coarse cells are grouped by position rather than by the structure of the grid so they do not
+/// have neighbours and their shapes are irregular where the grid is distorted.
+///
+/// # Type Parameters
+/// - `T` - The type of data that the grid holds.
+/// - `G` - The type of the grid.
+///
+/// # Thread Safety
+/// The pyramid only owns its grid and values so it is `Send` and `Sync` when `T` and `G` are.
+#[derive(Debug, Clone)]
+pub struct GridPyramid<T, G: SurfaceGrid<T>> {
+    /// The grid at level 0.
+    base: G,
+    /// For each level, the index of the cell of the next level that each of its cells lies in.
+    parents: Vec<Vec<usize>>,
+    /// The values of the cells of each level after level 0.
+    levels: Vec<Vec<T>>,
+    /// How the values of each level are found.
+    reduction: Reduction,
+}
+
+impl <T: PyramidValue, G: SurfaceGrid<T>> GridPyramid<T, G> {
+    /// Creates a new pyramid from a grid.
+    ///
+    /// - `base` - The grid at level 0.
+    /// - `levels` - The number of levels including level 0.
+    /// - `reduction` - How the values of the coarser levels are found.
+    ///
+    /// # Panics
+    /// Panics if `levels` is 0.
+    pub fn new(base: G, levels: usize, reduction: Reduction) -> Self {
+        assert!(levels > 0, "a pyramid must have at least one level");
+
+        let parents = Self::group_cells(&base, levels);
+
+        let mut pyramid = Self {
+            base,
+            parents,
+            levels: Vec::new(),
+            reduction,
+        };
+
+        pyramid.reduce();
+
+        pyramid
+    }
+
+    /// Gets the number of levels including level 0.
+    pub fn levels(&self) -> usize {
+        self.parents.len() + 1
+    }
+
+    /// Gets the number of cells in a level.
+    ///
+    /// - `level` - The level to get the size of.
+    ///
+    /// # Panics
+    /// Panics if `level` is not less than `levels`.
+    pub fn level_len(&self, level: usize) -> usize {
+        match level {
+            0 => self.base.points().count(),
+            _ => self.levels[level - 1].len(),
+        }
+    }
+
+    /// Gets the index of the cell of a level that a point of the grid lies in.
+    ///
+    /// At level 0 this is the index given by `SurfaceGrid::to_index`.
+    ///
+    /// - `level` - The level of the cell.
+    /// - `point` - The point of the grid.
+    ///
+    /// # Panics
+    /// Panics if `level` is not less than `levels`.
+    pub fn cell_index(&self, level: usize, point: &G::Point) -> usize {
+        assert!(level < self.levels(), "level {level} is past the last level, {}", self.levels() - 1);
+
+        self.parents[..level].iter()
+            .fold(self.base.to_index(point), |index, parents| parents[index])
+    }
+
+    /// Gets the value of the cell of a level that a point of the grid lies in.
+    ///
+    /// - `level` - The level to sample.
+    /// - `point` - The point of the grid.
+    ///
+    /// # Panics
+    /// Panics if `level` is not less than `levels`.
+    pub fn sample(&self, level: usize, point: &G::Point) -> T {
+        match level {
+            0 => self.base[point.clone()],
+            _ => self.levels[level - 1][self.cell_index(level, point)],
+        }
+    }
+
+    /// Gets a value between the values of two levels at a point of the grid.
+    ///
+    /// This linearly interpolates between the values of the levels either side of a fractional
+    /// level so that the detail fades smoothly as the level changes. Levels past the last level
+    /// give the value of the last level.
+    ///
+    /// - `level` - The fractional level to sample.
+    /// - `point` - The point of the grid.
+    pub fn sample_blended(&self, level: f64, point: &G::Point) -> T {
+        let level = level.clamp(0.0, (self.levels() - 1) as f64);
+
+        let lower = level.floor() as usize;
+        let upper = (lower + 1).min(self.levels() - 1);
+        let t = level - lower as f64;
+
+        let a = self.sample(lower, point).to_f64();
+        let b = self.sample(upper, point).to_f64();
+
+        T::from_f64(a + (b - a) * t)
+    }
+
+    /// Gets the grid at level 0.
+    pub fn base(&self) -> &G {
+        &self.base
+    }
+
+    /// Gets the reduction used to find the values of the coarser levels.
+    pub fn reduction(&self) -> Reduction {
+        self.reduction
+    }
+
+    /// Modifies the grid at level 0 and then updates the values of the coarser levels.
+    ///
+    /// - `f` - A function that modifies the grid.
+    pub fn update<F: FnOnce(&mut G)>(&mut self, f: F) {
+        f(&mut self.base);
+
+        self.reduce();
+    }
+
+    /// Takes the grid at level 0 out of the pyramid.
+    pub fn into_base(self) -> G {
+        self.base
+    }
+
+    /// Groups the cells of each level into the cells of the next level.
+    ///
+    /// The cells of each level are binned into cubes twice the size of the cubes used for the
+    /// previous level, starting from roughly twice the spacing of the cells of the grid, using
+    /// the mean position of the cells of the grid inside them. The cells of the next level are
+    /// numbered in the order in which they are first reached.
+    ///
+    /// - `base` - The grid at level 0.
+    /// - `levels` - The number of levels including level 0.
+    fn group_cells(base: &G, levels: usize) -> Vec<Vec<usize>> {
+        let mut positions: Vec<_> = base.points()
+            .map(|point| (point.position(1.0), 1.0))
+            .collect();
+
+        let spacing = (base.surface_area(1.0) / positions.len() as f64).sqrt();
+
+        (1..levels).map(|level| {
+            let size = spacing * (1u64 << level.min(63)) as f64;
+
+            let mut bins = HashMap::new();
+            let mut next: Vec<((f64, f64, f64), f64)> = Vec::new();
+
+            let parents = positions.iter()
+                .map(|&((x, y, z), weight)| {
+                    let bin = ((x / size).floor() as i64, (y / size).floor() as i64, (z / size).floor() as i64);
+
+                    let index = *bins.entry(bin).or_insert_with(|| {
+                        next.push(((0.0, 0.0, 0.0), 0.0));
+
+                        next.len() - 1
+                    });
+
+                    let ((sx, sy, sz), total) = &mut next[index];
+
+                    *sx += x * weight;
+                    *sy += y * weight;
+                    *sz += z * weight;
+                    *total += weight;
+
+                    index
+                })
+                .collect();
+
+            positions = next.into_iter()
+                .map(|((x, y, z), total)| ((x / total, y / total, z / total), total))
+                .collect();
+
+            parents
+        }).collect()
+    }
+
+    /// Finds the values of the coarser levels from the grid at level 0.
+    fn reduce(&mut self) {
+        let mut values: Vec<_> = self.base.iter()
+            .map(|(_, value)| (value.to_f64(), 1.0))
+            .collect();
+
+        self.levels = self.parents.iter()
+            .map(|parents| {
+                let len = parents.iter().max().map_or(0, |max| max + 1);
+                let mut next: Vec<Option<(f64, f64)>> = vec![None; len];
+
+                for (&(value, count), &parent) in values.iter().zip(parents) {
+                    next[parent] = Some(match (next[parent], self.reduction) {
+                        (None, _) => (value, count),
+                        (Some((mean, total)), Reduction::Mean) => {
+                            let total = total + count;
+
+                            (mean + (value - mean) * count / total, total)
+                        },
+                        (Some((min, total)), Reduction::Min) => (min.min(value), total + count),
+                        (Some((max, total)), Reduction::Max) => (max.max(value), total + count),
+                    });
+                }
+
+                values = next.into_iter()
+                    .map(|value| value.unwrap())
+                    .collect();
+
+                values.iter()
+                    .map(|(value, _)| T::from_f64(*value))
+                    .collect()
+            })
+            .collect();
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use approx::assert_relative_eq;
+
+    use crate::{SurfaceGrid, flat::FlatGrid, sphere::CubeSphereGrid};
+
+    use super::{GridPyramid, Reduction};
+
+    #[test]
+    fn test_pyramid_levels_shrink() {
+        let grid: CubeSphereGrid<f64, 32> = CubeSphereGrid::from_fn(|_| 1.0);
+
+        let pyramid = GridPyramid::new(grid, 5, Reduction::Mean);
+
+        assert_eq!(5, pyramid.levels());
+        assert_eq!(6 * 32 * 32, pyramid.level_len(0));
+
+        for level in 1..5 {
+            assert!(pyramid.level_len(level) < pyramid.level_len(level - 1));
+        }
+
+        assert!(pyramid.level_len(1) * 2 < pyramid.level_len(0));
+    }
+
+    #[test]
+    fn test_pyramid_cells_nest() {
+        let grid: CubeSphereGrid<f64, 16> = CubeSphereGrid::default();
+
+        let pyramid = GridPyramid::new(grid, 4, Reduction::Mean);
+
+        for level in 1..3 {
+            for a in pyramid.base().points() {
+                for b in pyramid.base().points().step_by(37) {
+                    if pyramid.cell_index(level, &a) == pyramid.cell_index(level, &b) {
+                        assert_eq!(pyramid.cell_index(level + 1, &a), pyramid.cell_index(level + 1, &b));
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_pyramid_mean_preserves_total() {
+        let grid: FlatGrid<f64, 20, 12> = FlatGrid::from_index_fn(|index| (index % 7) as f64);
+
+        let pyramid = GridPyramid::new(grid, 3, Reduction::Mean);
+
+        for level in 0..3 {
+            let mut counts = vec![0.0; pyramid.level_len(level)];
+
+            for point in pyramid.base().points() {
+                counts[pyramid.cell_index(level, &point)] += 1.0;
+            }
+
+            let total: f64 = pyramid.base().points()
+                .map(|point| pyramid.sample(level, &point))
+                .sum();
+
+            assert_relative_eq!(pyramid.base().iter().map(|(_, value)| *value).sum::<f64>(), total, epsilon = 1e-9);
+            assert!(counts.iter().all(|count| *count > 0.0));
+        }
+    }
+
+    #[test]
+    fn test_pyramid_min_max() {
+        let grid: CubeSphereGrid<i32, 8> = CubeSphereGrid::from_index_fn(|index| index as i32);
+
+        let min = GridPyramid::new(grid.clone(), 3, Reduction::Min);
+        let max = GridPyramid::new(grid, 3, Reduction::Max);
+
+        for (point, value) in min.base().iter() {
+            assert!(min.sample(2, &point) <= min.sample(1, &point));
+            assert!(min.sample(1, &point) <= *value);
+            assert!(max.sample(2, &point) >= max.sample(1, &point));
+            assert!(max.sample(1, &point) >= *value);
+        }
+    }
+
+    #[test]
+    fn test_pyramid_sample_blended_update() {
+        let grid: CubeSphereGrid<f64, 8> = CubeSphereGrid::from_fn(|_| 2.0);
+
+        let mut pyramid = GridPyramid::new(grid, 3, Reduction::Max);
+        let point = pyramid.base().points().next().unwrap();
+
+        pyramid.update(|grid| grid[point] = 6.0);
+
+        assert_eq!(6.0, pyramid.sample(0, &point));
+        assert_eq!(6.0, pyramid.sample(2, &point));
+
+        let other = pyramid.base().points()
+            .find(|other| pyramid.cell_index(1, other) == pyramid.cell_index(1, &point) && *other != point)
+            .unwrap();
+
+        assert_relative_eq!(4.0, pyramid.sample_blended(0.5, &other));
+        assert_relative_eq!(6.0, pyramid.sample_blended(10.0, &other));
+    }
+}
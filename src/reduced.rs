@@ -0,0 +1,664 @@
+//! A module containing grids on spheres where the number of cells in each row shrinks towards the
+//! poles.
+
+use std::{f64::consts::PI, ops::{Index, IndexMut}, vec};
+
+use rayon::prelude::*;
+
+use crate::{GridPoint, OutOfBounds, Surface, SurfaceGrid, Topology, sphere::SpherePoint};
+
+assert_send_sync!([T: Send + Sync, const W: usize, const H: usize]
+    ReducedSphereGrid<T, W, H>,
+    ReducedSpherePoint<W, H>,
+);
+
+/// A grid for a sphere made of rings of constant latitude where the rings near the poles have
+/// fewer cells, as in a reduced Gaussian grid.
+///
+/// The rows are the same as the rows of a `RectangleSphereGrid` with the same height but each row
+/// is split into a number of cells proportional to the cosine of its latitude, so the cells all
+/// have roughly the same area instead of shrinking towards the poles. Every row has an even number
+/// of cells, and at least 4, so that each cell has a cell opposite it across the pole.
+///
+/// Moving `left` and `right` wraps around the row. Moving `up` and `down` moves to the cell in the
+/// next row that contains the longitude of the centre of the cell, so moving back may not return
+/// to the same cell when the rows have different lengths. As with `RectangleSphereGrid`, `up` moves
+/// north in the half of the sphere with longitudes below `PI` and south in the other half, so that
+/// moving over a pole continues down the other side of the sphere.
+///
+/// # Type Parameters
+/// - `T` - The type of data that the grid holds.
+///
+/// # Constant Parameters
+/// - `W` - The number of cells in the rows at the equator, which should be even.
+/// - `H` - The number of rows.
+///
+/// # Iteration Order
+/// `iter`, `points` and `into_iter` all visit the rows from north to south. Each row is visited
+/// from a longitude of 0 eastwards, increasing `x`. This order is stable.
+///
+/// # Thread Safety
+/// The rows and the offsets of the rows are kept in `Vec`s owned by the grid, so the grid is `Send`
+/// when `T` is `Send` and `Sync` when `T` is `Sync`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ReducedSphereGrid<T, const W: usize, const H: usize> {
+    /// The data held in this grid, stored row by row.
+    data: Vec<T>,
+    /// The index of the first cell of each row followed by the number of cells.
+    offsets: Vec<usize>,
+}
+
+impl <T, const W: usize, const H: usize> ReducedSphereGrid<T, W, H> {
+    /// Gets the values of the cells in the same order as the points of the grid.
+    pub fn as_slice(&self) -> &[T] {
+        &self.data
+    }
+
+    /// Gets mutable references to the values of the cells in the same order as the points of the
+    /// grid.
+    pub fn as_mut_slice(&mut self) -> &mut [T] {
+        &mut self.data
+    }
+
+    /// Gets the values of the cells as bytes in the same order as `as_slice`.
+    #[cfg(feature = "bytemuck")]
+    pub fn as_bytes(&self) -> &[u8] where T: bytemuck::Pod {
+        bytemuck::cast_slice(self.as_slice())
+    }
+
+    /// Gets the values of the cells as mutable bytes in the same order as `as_slice`.
+    #[cfg(feature = "bytemuck")]
+    pub fn as_bytes_mut(&mut self) -> &mut [u8] where T: bytemuck::Pod {
+        bytemuck::cast_slice_mut(self.as_mut_slice())
+    }
+
+    /// Gets the index of the first cell of each row followed by the number of cells in the grid.
+    fn row_offsets() -> Vec<usize> {
+        let mut offsets = Vec::with_capacity(H + 1);
+        let mut offset = 0;
+
+        offsets.push(0);
+
+        for y in 0..H as u32 {
+            offset += ReducedSpherePoint::<W, H>::row_length(y) as usize;
+            offsets.push(offset);
+        }
+
+        offsets
+    }
+
+    /// Gets every point on the grid in the order that `points` visits them.
+    fn all_points() -> impl Iterator<Item = ReducedSpherePoint<W, H>> {
+        (0..H as u32)
+            .flat_map(|y| (0..ReducedSpherePoint::<W, H>::row_length(y)).map(move |x| ReducedSpherePoint::new(x, y)))
+    }
+
+    /// Gets the point at an index in the order that `points` visits them.
+    ///
+    /// - `offsets` - The offsets of the rows.
+    /// - `index` - The index of the point.
+    fn point_at(offsets: &[usize], index: usize) -> ReducedSpherePoint<W, H> {
+        let y = offsets.partition_point(|offset| *offset <= index) - 1;
+
+        ReducedSpherePoint::new((index - offsets[y]) as u32, y as u32)
+    }
+}
+
+impl <T: Default, const W: usize, const H: usize> Default for ReducedSphereGrid<T, W, H> {
+    fn default() -> Self {
+        Self::from_fn(|_| T::default())
+    }
+}
+
+impl <T, const W: usize, const H: usize> SurfaceGrid<T> for ReducedSphereGrid<T, W, H> {
+    type Point = ReducedSpherePoint<W, H>;
+
+    fn from_fn<F: FnMut(&Self::Point) -> T>(mut f: F) -> Self {
+        let offsets = Self::row_offsets();
+
+        let data = Self::all_points()
+            .map(|point| f(&point))
+            .collect();
+
+        Self {
+            data,
+            offsets,
+        }
+    }
+
+    fn from_fn_par<F: Fn(&Self::Point) -> T + Send + Sync>(f: F) -> Self where T: Send + Sync {
+        let offsets = Self::row_offsets();
+        let mut data = Vec::with_capacity(offsets[H]);
+
+        (0..offsets[H]).into_par_iter()
+            .map(|i| f(&Self::point_at(&offsets, i)))
+            .collect_into_vec(&mut data);
+
+        Self {
+            data,
+            offsets,
+        }
+    }
+
+    fn try_from_fn<X, F: FnMut(&Self::Point) -> Result<T, X>>(mut f: F) -> Result<Self, X> {
+        let offsets = Self::row_offsets();
+
+        let data = Self::all_points()
+            .map(|point| f(&point))
+            .collect::<Result<_, _>>()?;
+
+        Ok(Self {
+            data,
+            offsets,
+        })
+    }
+
+    fn try_from_fn_par<X: Send, F: Fn(&Self::Point) -> Result<T, X> + Send + Sync>(f: F) -> Result<Self, X> where T: Send + Sync {
+        let offsets = Self::row_offsets();
+
+        let data = (0..offsets[H]).into_par_iter()
+            .map(|i| f(&Self::point_at(&offsets, i)))
+            .collect::<Result<_, _>>()?;
+
+        Ok(Self {
+            data,
+            offsets,
+        })
+    }
+
+    fn from_vec(values: Vec<T>) -> Option<Self> {
+        let offsets = Self::row_offsets();
+
+        if values.len() != offsets[H] {
+            return None;
+        }
+
+        Some(Self {
+            data: values,
+            offsets,
+        })
+    }
+
+    fn set_from_fn<F: FnMut(&Self::Point) -> T>(&mut self, mut f: F) {
+        for (point, value) in Self::all_points().zip(self.data.iter_mut()) {
+            *value = f(&point);
+        }
+    }
+
+    fn set_from_fn_par<F: Fn(&Self::Point) -> T + Send + Sync>(&mut self, f: F) where T: Send + Sync {
+        let offsets = &self.offsets;
+
+        self.data.par_iter_mut().enumerate().for_each(|(i, value)| {
+            *value = f(&Self::point_at(offsets, i));
+        });
+    }
+
+    fn iter<'a>(&'a self) -> impl ExactSizeIterator<Item = (Self::Point, &'a T)> + DoubleEndedIterator where T: 'a {
+        self.points().zip(self.data.iter())
+    }
+
+    fn par_iter<'a>(&'a self) -> impl IndexedParallelIterator<Item = (Self::Point, &'a T)> where T: 'a + Send + Sync {
+        let offsets = &self.offsets;
+
+        self.data.par_iter()
+            .enumerate()
+            .map(|(i, value)| (Self::point_at(offsets, i), value))
+    }
+
+    fn par_iter_mut<'a>(&'a mut self) -> impl IndexedParallelIterator<Item = (Self::Point, &'a mut T)> where T: 'a + Send + Sync {
+        let offsets = &self.offsets;
+
+        self.data.par_iter_mut()
+            .enumerate()
+            .map(|(i, value)| (Self::point_at(offsets, i), value))
+    }
+
+    fn points(&self) -> impl ExactSizeIterator<Item = Self::Point> + DoubleEndedIterator {
+        let offsets = &self.offsets;
+
+        (0..self.data.len()).map(|i| Self::point_at(offsets, i))
+    }
+
+    fn par_points(&self) -> impl IndexedParallelIterator<Item = Self::Point> {
+        let offsets = &self.offsets;
+
+        (0..self.data.len()).into_par_iter()
+            .map(|i| Self::point_at(offsets, i))
+    }
+
+    fn par_map_to_vec<U: Send, F: Fn(&Self::Point, &T) -> U + Send + Sync>(&self, f: F) -> Vec<U> where T: Send + Sync {
+        let mut result = Vec::with_capacity(self.data.len());
+        let offsets = &self.offsets;
+
+        self.data.par_iter()
+            .enumerate()
+            .map(|(i, value)| f(&Self::point_at(offsets, i), value))
+            .collect_into_vec(&mut result);
+
+        result
+    }
+
+    fn to_index(&self, point: &Self::Point) -> usize {
+        self.offsets[point.y as usize] + point.x as usize
+    }
+
+    fn at(&self, point: &Self::Point) -> &T {
+        &self[point]
+    }
+
+    fn at_mut(&mut self, point: &Self::Point) -> &mut T {
+        &mut self[point]
+    }
+
+    fn surface_area(&self, scale: f64) -> f64 {
+        4.0 * PI * scale * scale
+    }
+
+    fn topology(&self) -> Topology {
+        Topology {
+            surface: Surface::Sphere,
+            face_count: 1,
+            width: W,
+            height: H,
+            len: self.data.len(),
+        }
+    }
+
+    fn contiguous_values_mut(&mut self) -> Option<&mut [T]> {
+        Some(self.as_mut_slice())
+    }
+}
+
+impl <T, const W: usize, const H: usize> Index<ReducedSpherePoint<W, H>> for ReducedSphereGrid<T, W, H> {
+    type Output = T;
+
+    fn index(&self, index: ReducedSpherePoint<W, H>) -> &Self::Output {
+        &self.data[self.to_index(&index)]
+    }
+}
+
+impl <T, const W: usize, const H: usize> IndexMut<ReducedSpherePoint<W, H>> for ReducedSphereGrid<T, W, H> {
+    fn index_mut(&mut self, index: ReducedSpherePoint<W, H>) -> &mut Self::Output {
+        let index = self.to_index(&index);
+
+        &mut self.data[index]
+    }
+}
+
+impl <T, const W: usize, const H: usize> Index<&ReducedSpherePoint<W, H>> for ReducedSphereGrid<T, W, H> {
+    type Output = T;
+
+    fn index(&self, index: &ReducedSpherePoint<W, H>) -> &Self::Output {
+        &self[*index]
+    }
+}
+
+impl <T, const W: usize, const H: usize> IndexMut<&ReducedSpherePoint<W, H>> for ReducedSphereGrid<T, W, H> {
+    fn index_mut(&mut self, index: &ReducedSpherePoint<W, H>) -> &mut Self::Output {
+        &mut self[*index]
+    }
+}
+
+impl <T, const W: usize, const H: usize> FromIterator<T> for ReducedSphereGrid<T, W, H> {
+    /// Creates a grid from the values of its cells in the same order as `points`.
+    ///
+    /// # Panics
+    /// Panics if the iterator does not yield exactly one value for each cell in the grid.
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        Self::from_vec(iter.into_iter().collect())
+            .expect("the iterator must yield one value for each cell in the grid")
+    }
+}
+
+impl <T, const W: usize, const H: usize> IntoIterator for ReducedSphereGrid<T, W, H> {
+    type Item = (ReducedSpherePoint<W, H>, T);
+
+    type IntoIter = vec::IntoIter<Self::Item>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        let data: Vec<_> = Self::all_points()
+            .zip(self.data)
+            .collect();
+
+        data.into_iter()
+    }
+}
+
+#[cfg(feature = "serde")]
+impl <T: serde::Serialize, const W: usize, const H: usize> serde::Serialize for ReducedSphereGrid<T, W, H> {
+    /// Serializes the values in this grid as a sequence in the same order as `iter`.
+    fn serialize<R: serde::Serializer>(&self, serializer: R) -> Result<R::Ok, R::Error> {
+        serializer.collect_seq(self.data.iter())
+    }
+}
+
+#[cfg(feature = "serde")]
+impl <'de, T: serde::Deserialize<'de>, const W: usize, const H: usize> serde::Deserialize<'de> for ReducedSphereGrid<T, W, H> {
+    /// Deserializes a grid from a sequence of values in the same order as `iter`.
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let values = Vec::<T>::deserialize(deserializer)?;
+        let length = values.len();
+        let expected = Self::row_offsets()[H];
+
+        Self::from_vec(values)
+            .ok_or_else(|| serde::de::Error::invalid_length(length, &format!("a sequence of {} values", expected).as_str()))
+    }
+}
+
+/// A point on a `ReducedSphereGrid`.
+///
+/// # Constant Parameters
+/// - `W` - The number of cells in the rows at the equator.
+/// - `H` - The number of rows.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ReducedSpherePoint<const W: usize, const H: usize> {
+    /// The X position in the row.
+    x: u32,
+    /// The row, counted from the north pole.
+    y: u32,
+}
+
+impl <const W: usize, const H: usize> ReducedSpherePoint<W, H> {
+    /// Creates a new `ReducedSpherePoint`.
+    ///
+    /// - `x` - The X position in the row.
+    /// - `y` - The row.
+    fn new(x: u32, y: u32) -> Self {
+        Self {
+            x, y,
+        }
+    }
+
+    /// Creates a new `ReducedSpherePoint` checking that the position lies on the grid.
+    ///
+    /// This returns an error if `y` is `H` or greater or `x` is not less than the `row_length` of the row.
+    ///
+    /// - `x` - The X position in the row.
+    /// - `y` - The row, counted from the north pole.
+    pub fn try_new(x: u32, y: u32) -> Result<Self, OutOfBounds> {
+        if (y as usize) < H && x < Self::row_length(y) {
+            Ok(Self::new(x, y))
+        } else {
+            Err(OutOfBounds)
+        }
+    }
+
+    /// Gets the number of cells in a row.
+    ///
+    /// This is the even number closest to `W` times the cosine of the latitude of the centre of
+    /// the row, and at least 4.
+    ///
+    /// - `y` - The row, counted from the north pole.
+    pub fn row_length(y: u32) -> u32 {
+        let latitude = PI / 2.0 - (y as f64 + 0.5) / H as f64 * PI;
+
+        (2 * (W as f64 / 2.0 * latitude.cos()).round() as u32).max(4)
+    }
+
+    /// Gets the position of the centre of this cell in 3D space.
+    ///
+    /// - `scale` - The scale of the 3D object.
+    pub fn cell_center(&self, scale: f64) -> (f64, f64, f64) {
+        self.grid_position(self.x as f64 + 0.5, self.y as f64 + 0.5, scale)
+    }
+
+    /// Gets the solid angle covered by this cell in steradians.
+    ///
+    /// This is the area of the cell on a sphere with a radius of 1. The solid angles of all of the
+    /// cells on the grid add up to `4 * PI`.
+    pub fn solid_angle(&self) -> f64 {
+        let top = PI / 2.0 - self.y as f64 / H as f64 * PI;
+        let bottom = PI / 2.0 - (self.y as f64 + 1.0) / H as f64 * PI;
+
+        2.0 * PI / Self::row_length(self.y) as f64 * (top.sin() - bottom.sin())
+    }
+
+    /// Gets the cell in another row that contains the longitude of the centre of this cell.
+    ///
+    /// - `y` - The row to move to.
+    fn in_row(&self, y: u32) -> Self {
+        let from = Self::row_length(self.y) as u64;
+        let to = Self::row_length(y) as u64;
+
+        Self::new(((2 * self.x as u64 + 1) * to / (2 * from)) as u32, y)
+    }
+
+    /// Gets the cell on the opposite side of the sphere in the same row.
+    fn opposite(&self) -> Self {
+        let length = Self::row_length(self.y);
+
+        Self::new((self.x + length / 2) % length, self.y)
+    }
+
+    /// Determines whether this cell is in the half of the sphere where `up` moves south.
+    fn is_reversed(&self) -> bool {
+        self.x >= Self::row_length(self.y) / 2
+    }
+
+    /// Moves one row towards the north pole, or over it onto the opposite side of the sphere.
+    fn north(&self) -> Self {
+        if self.y == 0 {
+            self.opposite()
+        } else {
+            self.in_row(self.y - 1)
+        }
+    }
+
+    /// Moves one row towards the south pole, or over it onto the opposite side of the sphere.
+    fn south(&self) -> Self {
+        if self.y == H as u32 - 1 {
+            self.opposite()
+        } else {
+            self.in_row(self.y + 1)
+        }
+    }
+
+    /// Gets the position in 3D space of a continuous position in the row of this cell.
+    ///
+    /// - `x` - The X position in the row.
+    /// - `y` - The Y position on the grid.
+    /// - `scale` - The scale of the 3D object.
+    fn grid_position(&self, x: f64, y: f64, scale: f64) -> (f64, f64, f64) {
+        let latitude = PI / 2.0 - y / H as f64 * PI;
+        let longitude = x / Self::row_length(self.y) as f64 * PI * 2.0;
+
+        (
+            scale * latitude.cos() * longitude.sin(),
+            scale * latitude.sin(),
+            scale * latitude.cos() * longitude.cos(),
+        )
+    }
+}
+
+impl <const W: usize, const H: usize> GridPoint for ReducedSpherePoint<W, H> {
+    fn up(&self) -> Self {
+        if self.is_reversed() {
+            self.south()
+        } else {
+            self.north()
+        }
+    }
+
+    fn down(&self) -> Self {
+        if self.is_reversed() {
+            self.north()
+        } else {
+            self.south()
+        }
+    }
+
+    fn left(&self) -> Self {
+        let length = Self::row_length(self.y);
+
+        Self::new((self.x + length - 1) % length, self.y)
+    }
+
+    fn right(&self) -> Self {
+        Self::new((self.x + 1) % Self::row_length(self.y), self.y)
+    }
+
+    /// Gets the position of the point in 3D space.
+    ///
+    /// This is the position of the north western corner of the cell. Use `cell_center` to get the
+    /// centre of the cell.
+    ///
+    /// - `scale` - The scale of the 3D object.
+    fn position(&self, scale: f64) -> (f64, f64, f64) {
+        self.grid_position(self.x as f64, self.y as f64, scale)
+    }
+}
+
+impl <const W: usize, const H: usize> SpherePoint for ReducedSpherePoint<W, H> {
+    fn center_position(&self, scale: f64) -> (f64, f64, f64) {
+        self.cell_center(scale)
+    }
+
+    fn from_geographic(latitude: f64, longitude: f64) -> Self {
+        let y = (((PI / 2.0 - latitude) / PI * H as f64) as u32).min(H as u32 - 1);
+        let length = Self::row_length(y);
+
+        let x = ((longitude.rem_euclid(PI * 2.0) / (PI * 2.0) * length as f64) as u32).min(length - 1);
+
+        Self::new(x, y)
+    }
+
+    fn latitude(&self) -> f64 {
+        PI / 2.0 - self.y as f64 / H as f64 * PI
+    }
+
+    fn longitude(&self) -> f64 {
+        self.x as f64 / Self::row_length(self.y) as f64 * PI * 2.0
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::f64::consts::PI;
+
+    use approx::assert_relative_eq;
+    use rayon::iter::ParallelIterator;
+
+    use crate::{GridPoint, SurfaceGrid, sphere::SpherePoint};
+
+    use super::{ReducedSphereGrid, ReducedSpherePoint};
+
+    #[test]
+    fn test_reduced_row_length() {
+        let lengths: Vec<_> = (0..16).map(ReducedSpherePoint::<32, 16>::row_length).collect();
+
+        assert_eq!(lengths[0], 4);
+        assert_eq!(lengths[7], 32);
+        assert_eq!(lengths[8], 32);
+
+        for (y, length) in lengths.iter().enumerate() {
+            assert_eq!(length % 2, 0);
+            assert_eq!(*length, lengths[15 - y]);
+        }
+
+        for pair in lengths[..8].windows(2) {
+            assert!(pair[0] <= pair[1]);
+        }
+
+        let grid: ReducedSphereGrid<(), 32, 16> = ReducedSphereGrid::default();
+
+        assert_eq!(grid.points().count(), lengths.iter().sum::<u32>() as usize);
+    }
+
+    #[test]
+    fn test_reduced_equal_area() {
+        let grid: ReducedSphereGrid<(), 64, 32> = ReducedSphereGrid::default();
+
+        let areas: Vec<_> = grid.points().map(|point| point.solid_angle()).collect();
+
+        assert_relative_eq!(areas.iter().sum::<f64>(), 4.0 * PI, epsilon = 1e-9);
+
+        let min = areas.iter().copied().fold(f64::INFINITY, f64::min);
+        let max = areas.iter().copied().fold(0.0, f64::max);
+
+        assert!(max / min < 1.5);
+    }
+
+    #[test]
+    fn test_reduced_neighbours() {
+        let grid: ReducedSphereGrid<(), 32, 16> = ReducedSphereGrid::default();
+
+        for point in grid.points() {
+            assert_eq!(point.left().right(), point);
+            assert_eq!(point.right().left(), point);
+
+            for neighbour in [point.up(), point.down(), point.left(), point.right()] {
+                let (x, y, z) = point.cell_center(1.0);
+                let (nx, ny, nz) = neighbour.cell_center(1.0);
+
+                assert_ne!(neighbour, point);
+                assert!(((x - nx).powi(2) + (y - ny).powi(2) + (z - nz).powi(2)).sqrt() < 0.35);
+            }
+        }
+
+        // Moving up over the north pole continues down the other side of the sphere.
+        let point = ReducedSpherePoint::<32, 16>::new(1, 0);
+
+        assert_eq!(point.up(), ReducedSpherePoint::new(3, 0));
+        assert_eq!(point.up().up(), ReducedSpherePoint::new(3, 0).in_row(1));
+        assert!(point.up().up().longitude() > PI);
+    }
+
+    #[test]
+    fn test_reduced_from_geographic() {
+        let grid: ReducedSphereGrid<(), 32, 16> = ReducedSphereGrid::default();
+
+        for point in grid.points() {
+            // Move slightly into the cell from its corner to avoid rounding onto a neighbour.
+            assert_eq!(ReducedSpherePoint::from_geographic(point.latitude() - 1e-9, point.longitude() + 1e-9), point);
+
+            let (x, y, z) = point.cell_center(1.0);
+
+            assert_eq!(ReducedSpherePoint::nearest_point(x, y, z), point);
+        }
+
+        assert_eq!(ReducedSpherePoint::<32, 16>::from_geographic(-PI / 2.0, 0.0), ReducedSpherePoint::new(0, 15));
+    }
+
+    #[test]
+    fn test_reduced_iteration_order() {
+        let grid: ReducedSphereGrid<usize, 16, 8> = ReducedSphereGrid::from_index_fn(|i| i);
+
+        for (i, (point, value)) in grid.iter().enumerate() {
+            assert_eq!(*value, i);
+            assert_eq!(grid.to_index(&point), i);
+        }
+
+        assert_eq!(grid.par_points().collect::<Vec<_>>(), grid.points().collect::<Vec<_>>());
+        assert_eq!(ReducedSphereGrid::from_fn_par(|point| grid[*point]), grid);
+        assert_eq!(grid.par_map_to_vec(|_, value| *value), grid.to_vec());
+        assert_eq!(grid.clone().into_iter().map(|(_, value)| value).collect::<Vec<_>>(), grid.to_vec());
+        assert_eq!(ReducedSphereGrid::from_vec(grid.to_vec()), Some(grid.clone()));
+        assert_eq!(ReducedSphereGrid::<usize, 16, 8>::from_vec(vec![0; 3]), None);
+    }
+
+    #[test]
+    fn test_reduced_point_try_new() {
+        type Point = ReducedSpherePoint<32, 16>;
+
+        assert_eq!(Ok(Point::new(3, 0)), Point::try_new(3, 0));
+        assert!(Point::try_new(4, 0).is_err());
+        assert!(Point::try_new(31, 8).is_ok());
+        assert!(Point::try_new(0, 16).is_err());
+    }
+
+    #[test]
+    fn test_reduced_len() {
+        let grid: ReducedSphereGrid<(), 32, 16> = ReducedSphereGrid::default();
+
+        assert_eq!(grid.points().count(), grid.len());
+    }
+
+    #[test]
+    fn test_reduced_iter_exact_size() {
+        let grid: ReducedSphereGrid<usize, 16, 8> = ReducedSphereGrid::from_index_fn(|i| i);
+
+        assert_eq!(grid.len(), grid.points().len());
+        assert!(grid.iter().rev().map(|(_, value)| *value).eq((0..grid.len()).rev()));
+        assert_eq!(grid.points().collect::<Vec<_>>().pop(), grid.points().next_back());
+    }
+}
@@ -0,0 +1,636 @@
+//! A module containing grids on spheres based on the sinusoidal projection.
+
+use std::{f64::consts::PI, ops::{Index, IndexMut}, vec};
+
+use rayon::prelude::*;
+
+use crate::{GridPoint, OutOfBounds, Surface, SurfaceGrid, Topology, sphere::SpherePoint};
+
+assert_send_sync!([T: Send + Sync, const W: usize, const H: usize]
+    SinusoidalSphereGrid<T, W, H>,
+    SinusoidalSpherePoint<W, H>,
+);
+
+/// A grid for a sphere based on the sinusoidal equal area projection.
+///
+/// The grid is stored as a `W` by `H` rectangle in the same way as a `RectangleSphereGrid`, but
+/// the sphere only covers the part of each row that lies inside the outline of the sinusoidal
+/// projection. The middle of each row is split into an even number of cells proportional to the
+/// cosine of its latitude, so the cells that cover the sphere all have roughly the same area. The
+/// cells outside of the outline are padding which keeps the storage rectangular. They are visited
+/// by `iter` and `points` like any other cell but are never reached by moving from a cell on the
+/// sphere or by `from_geographic`.
+///
+/// Moving `left` and `right` wraps around the part of the row on the sphere. Moving `up` and
+/// `down` moves to the cell in the next row that contains the longitude of the centre of the cell,
+/// so moving back may not return to the same cell when the rows have different lengths. As with
+/// `RectangleSphereGrid`, `up` moves north in the western half of the sphere and south in the
+/// eastern half, so that moving over a pole continues down the other side of the sphere. Padding
+/// cells move in the same way as the nearest cell on the sphere in their row.
+///
+/// # Type Parameters
+/// - `T` - The type of data that the grid holds.
+///
+/// # Constant Parameters
+/// - `W` - The width of the grid, which should be even.
+/// - `H` - The height of the grid.
+///
+/// # Iteration Order
+/// `iter`, `points` and `into_iter` all visit the grid in row-major order, starting at `(0, 0)`
+/// and increasing `x` before `y`. This includes the padding cells. This order is stable.
+///
+/// # Thread Safety
+/// The rows of the projection are kept one after another in a `Vec`, so the grid is `Send` when `T`
+/// is `Send` and `Sync` when `T` is `Sync`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct SinusoidalSphereGrid<T, const W: usize, const H: usize> {
+    /// The data held in this grid.
+    data: Vec<T>,
+}
+
+impl <T: Default, const W: usize, const H: usize> Default for SinusoidalSphereGrid<T, W, H> {
+    fn default() -> Self {
+        Self::from_fn(|_| T::default())
+    }
+}
+
+impl <T, const W: usize, const H: usize> SinusoidalSphereGrid<T, W, H> {
+    /// Gets the values of the cells in the same order as the points of the grid.
+    pub fn as_slice(&self) -> &[T] {
+        &self.data
+    }
+
+    /// Gets mutable references to the values of the cells in the same order as the points of the
+    /// grid.
+    pub fn as_mut_slice(&mut self) -> &mut [T] {
+        &mut self.data
+    }
+
+    /// Gets the values of the cells as bytes in the same order as `as_slice`.
+    #[cfg(feature = "bytemuck")]
+    pub fn as_bytes(&self) -> &[u8] where T: bytemuck::Pod {
+        bytemuck::cast_slice(self.as_slice())
+    }
+
+    /// Gets the values of the cells as mutable bytes in the same order as `as_slice`.
+    #[cfg(feature = "bytemuck")]
+    pub fn as_bytes_mut(&mut self) -> &mut [u8] where T: bytemuck::Pod {
+        bytemuck::cast_slice_mut(self.as_mut_slice())
+    }
+}
+
+impl <T, const W: usize, const H: usize> SurfaceGrid<T> for SinusoidalSphereGrid<T, W, H> {
+    type Point = SinusoidalSpherePoint<W, H>;
+
+    fn from_fn<F: FnMut(&Self::Point) -> T>(mut f: F) -> Self {
+        Self {
+            data: (0..W * H)
+                .map(|i| f(&SinusoidalSpherePoint::from_index(i)))
+                .collect(),
+        }
+    }
+
+    fn from_fn_par<F: Fn(&Self::Point) -> T + Send + Sync>(f: F) -> Self where T: Send + Sync {
+        let mut data = Vec::with_capacity(W * H);
+
+        (0..W * H).into_par_iter()
+            .map(|i| f(&SinusoidalSpherePoint::from_index(i)))
+            .collect_into_vec(&mut data);
+
+        Self {
+            data,
+        }
+    }
+
+    fn try_from_fn<X, F: FnMut(&Self::Point) -> Result<T, X>>(mut f: F) -> Result<Self, X> {
+        Ok(Self {
+            data: (0..W * H)
+                .map(|i| f(&SinusoidalSpherePoint::from_index(i)))
+                .collect::<Result<_, _>>()?,
+        })
+    }
+
+    fn try_from_fn_par<X: Send, F: Fn(&Self::Point) -> Result<T, X> + Send + Sync>(f: F) -> Result<Self, X> where T: Send + Sync {
+        Ok(Self {
+            data: (0..W * H).into_par_iter()
+                .map(|i| f(&SinusoidalSpherePoint::from_index(i)))
+                .collect::<Result<_, _>>()?,
+        })
+    }
+
+    fn from_vec(values: Vec<T>) -> Option<Self> {
+        if values.len() != W * H {
+            return None;
+        }
+
+        Some(Self {
+            data: values,
+        })
+    }
+
+    fn set_from_fn<F: FnMut(&Self::Point) -> T>(&mut self, mut f: F) {
+        for (i, value) in self.data.iter_mut().enumerate() {
+            *value = f(&SinusoidalSpherePoint::from_index(i));
+        }
+    }
+
+    fn set_from_fn_par<F: Fn(&Self::Point) -> T + Send + Sync>(&mut self, f: F) where T: Send + Sync {
+        self.data.par_iter_mut().enumerate().for_each(|(i, value)| {
+            *value = f(&SinusoidalSpherePoint::from_index(i));
+        });
+    }
+
+    fn iter<'a>(&'a self) -> impl ExactSizeIterator<Item = (Self::Point, &'a T)> + DoubleEndedIterator where T: 'a {
+        self.data.iter()
+            .enumerate()
+            .map(|(i, value)| (SinusoidalSpherePoint::from_index(i), value))
+    }
+
+    fn par_iter<'a>(&'a self) -> impl IndexedParallelIterator<Item = (Self::Point, &'a T)> where T: 'a + Send + Sync {
+        self.data.par_iter()
+            .enumerate()
+            .map(|(i, value)| (SinusoidalSpherePoint::from_index(i), value))
+    }
+
+    fn par_iter_mut<'a>(&'a mut self) -> impl IndexedParallelIterator<Item = (Self::Point, &'a mut T)> where T: 'a + Send + Sync {
+        self.data.par_iter_mut()
+            .enumerate()
+            .map(|(i, value)| (SinusoidalSpherePoint::from_index(i), value))
+    }
+
+    fn points(&self) -> impl ExactSizeIterator<Item = Self::Point> + DoubleEndedIterator {
+        (0..W * H)
+            .map(SinusoidalSpherePoint::from_index)
+    }
+
+    fn par_points(&self) -> impl IndexedParallelIterator<Item = Self::Point> {
+        (0..W * H).into_par_iter()
+            .map(SinusoidalSpherePoint::from_index)
+    }
+
+    fn par_map_to_vec<U: Send, F: Fn(&Self::Point, &T) -> U + Send + Sync>(&self, f: F) -> Vec<U> where T: Send + Sync {
+        let mut result = Vec::with_capacity(W * H);
+
+        self.data.par_iter()
+            .enumerate()
+            .map(|(i, value)| f(&SinusoidalSpherePoint::from_index(i), value))
+            .collect_into_vec(&mut result);
+
+        result
+    }
+
+    fn to_index(&self, point: &Self::Point) -> usize {
+        point.index()
+    }
+
+    fn at(&self, point: &Self::Point) -> &T {
+        &self[point]
+    }
+
+    fn at_mut(&mut self, point: &Self::Point) -> &mut T {
+        &mut self[point]
+    }
+
+    fn surface_area(&self, scale: f64) -> f64 {
+        4.0 * PI * scale * scale
+    }
+
+    fn topology(&self) -> Topology {
+        Topology::uniform(Surface::Sphere, 1, W, H)
+    }
+
+    fn contiguous_values_mut(&mut self) -> Option<&mut [T]> {
+        Some(self.as_mut_slice())
+    }
+}
+
+impl <T, const W: usize, const H: usize> Index<SinusoidalSpherePoint<W, H>> for SinusoidalSphereGrid<T, W, H> {
+    type Output = T;
+
+    fn index(&self, index: SinusoidalSpherePoint<W, H>) -> &Self::Output {
+        &self.data[index.index()]
+    }
+}
+
+impl <T, const W: usize, const H: usize> IndexMut<SinusoidalSpherePoint<W, H>> for SinusoidalSphereGrid<T, W, H> {
+    fn index_mut(&mut self, index: SinusoidalSpherePoint<W, H>) -> &mut Self::Output {
+        &mut self.data[index.index()]
+    }
+}
+
+impl <T, const W: usize, const H: usize> Index<&SinusoidalSpherePoint<W, H>> for SinusoidalSphereGrid<T, W, H> {
+    type Output = T;
+
+    fn index(&self, index: &SinusoidalSpherePoint<W, H>) -> &Self::Output {
+        &self[*index]
+    }
+}
+
+impl <T, const W: usize, const H: usize> IndexMut<&SinusoidalSpherePoint<W, H>> for SinusoidalSphereGrid<T, W, H> {
+    fn index_mut(&mut self, index: &SinusoidalSpherePoint<W, H>) -> &mut Self::Output {
+        &mut self[*index]
+    }
+}
+
+impl <T, const W: usize, const H: usize> FromIterator<T> for SinusoidalSphereGrid<T, W, H> {
+    /// Creates a grid from the values of its cells in the same order as `points`.
+    ///
+    /// # Panics
+    /// Panics if the iterator does not yield exactly one value for each cell in the grid.
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        Self::from_vec(iter.into_iter().collect())
+            .expect("the iterator must yield one value for each cell in the grid")
+    }
+}
+
+impl <T, const W: usize, const H: usize> IntoIterator for SinusoidalSphereGrid<T, W, H> {
+    type Item = (SinusoidalSpherePoint<W, H>, T);
+
+    type IntoIter = vec::IntoIter<Self::Item>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        let data: Vec<_> = self.data.into_iter()
+            .enumerate()
+            .map(|(i, value)| (SinusoidalSpherePoint::from_index(i), value))
+            .collect();
+
+        data.into_iter()
+    }
+}
+
+#[cfg(feature = "serde")]
+impl <T: serde::Serialize, const W: usize, const H: usize> serde::Serialize for SinusoidalSphereGrid<T, W, H> {
+    /// Serializes the values in this grid as a sequence in the same order as `iter`.
+    fn serialize<R: serde::Serializer>(&self, serializer: R) -> Result<R::Ok, R::Error> {
+        serializer.collect_seq(self.data.iter())
+    }
+}
+
+#[cfg(feature = "serde")]
+impl <'de, T: serde::Deserialize<'de>, const W: usize, const H: usize> serde::Deserialize<'de> for SinusoidalSphereGrid<T, W, H> {
+    /// Deserializes a grid from a sequence of values in the same order as `iter`.
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let values = Vec::<T>::deserialize(deserializer)?;
+        let length = values.len();
+
+        Self::from_vec(values)
+            .ok_or_else(|| serde::de::Error::invalid_length(length, &format!("a sequence of {} values", W * H).as_str()))
+    }
+}
+
+/// A point on a `SinusoidalSphereGrid`.
+///
+/// # Constant Parameters
+/// - `W` - The width of the grid.
+/// - `H` - The height of the grid.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct SinusoidalSpherePoint<const W: usize, const H: usize> {
+    /// The X position on the grid.
+    x: u32,
+    /// The Y position on the grid, counted from the north pole.
+    y: u32,
+}
+
+impl <const W: usize, const H: usize> SinusoidalSpherePoint<W, H> {
+    /// Creates a new `SinusoidalSpherePoint`.
+    ///
+    /// - `x` - The X position on the grid.
+    /// - `y` - The Y position on the grid.
+    fn new(x: u32, y: u32) -> Self {
+        Self {
+            x, y,
+        }
+    }
+
+    /// Creates a new `SinusoidalSpherePoint` checking that the position lies on the grid.
+    ///
+    /// This returns an error if `x` is `W` or greater or `y` is `H` or greater.
+    ///
+    /// - `x` - The X position on the grid.
+    /// - `y` - The Y position on the grid.
+    pub fn try_new(x: u32, y: u32) -> Result<Self, OutOfBounds> {
+        if (x as usize) < W && (y as usize) < H {
+            Ok(Self::new(x, y))
+        } else {
+            Err(OutOfBounds)
+        }
+    }
+
+    /// Gets the point at an index in the order that `SinusoidalSphereGrid::points` visits them.
+    ///
+    /// - `index` - The index of the point.
+    fn from_index(index: usize) -> Self {
+        Self::new((index % W) as u32, (index / W) as u32)
+    }
+
+    /// Gets the index of this point in the order that `SinusoidalSphereGrid::points` visits them.
+    fn index(&self) -> usize {
+        self.x as usize + self.y as usize * W
+    }
+
+    /// Gets the number of cells in a row that lie on the sphere.
+    ///
+    /// This is the even number closest to `W` times the cosine of the latitude of the centre of
+    /// the row, and at least 2.
+    ///
+    /// - `y` - The row, counted from the north pole.
+    pub fn row_length(y: u32) -> u32 {
+        let latitude = PI / 2.0 - (y as f64 + 0.5) / H as f64 * PI;
+        let max = (W as u32 & !1).max(2);
+
+        (2 * (W as f64 / 2.0 * latitude.cos()).round() as u32).clamp(2, max)
+    }
+
+    /// Gets the X position of the first cell of a row that lies on the sphere.
+    ///
+    /// - `y` - The row, counted from the north pole.
+    fn row_start(y: u32) -> u32 {
+        (W as u32).saturating_sub(Self::row_length(y)) / 2
+    }
+
+    /// Determines whether this point is padding outside of the outline of the projection.
+    ///
+    /// Padding cells do not cover any of the sphere.
+    pub fn is_padding(&self) -> bool {
+        let start = Self::row_start(self.y);
+
+        self.x < start || self.x >= start + Self::row_length(self.y)
+    }
+
+    /// Gets the position of this cell within the part of its row that lies on the sphere.
+    ///
+    /// Padding cells use the position of the nearest cell on the sphere.
+    fn row_position(&self) -> u32 {
+        self.x.saturating_sub(Self::row_start(self.y)).min(Self::row_length(self.y) - 1)
+    }
+
+    /// Gets the position of the centre of this cell in 3D space.
+    ///
+    /// Padding cells use the position of the nearest cell on the sphere.
+    ///
+    /// - `scale` - The scale of the 3D object.
+    pub fn cell_center(&self, scale: f64) -> (f64, f64, f64) {
+        self.grid_position(self.row_position() as f64 + 0.5, self.y as f64 + 0.5, scale)
+    }
+
+    /// Gets the solid angle covered by this cell in steradians.
+    ///
+    /// This is the area of the cell on a sphere with a radius of 1, which is 0 for padding cells.
+    /// The solid angles of all of the cells on the grid add up to `4 * PI`.
+    pub fn solid_angle(&self) -> f64 {
+        if self.is_padding() {
+            return 0.0;
+        }
+
+        let top = PI / 2.0 - self.y as f64 / H as f64 * PI;
+        let bottom = PI / 2.0 - (self.y as f64 + 1.0) / H as f64 * PI;
+
+        2.0 * PI / Self::row_length(self.y) as f64 * (top.sin() - bottom.sin())
+    }
+
+    /// Gets the cell on the sphere with a position within the part of a row that lies on the
+    /// sphere.
+    ///
+    /// - `x` - The position within the part of the row on the sphere.
+    /// - `y` - The row.
+    fn in_row_at(x: u32, y: u32) -> Self {
+        Self::new(Self::row_start(y) + x, y)
+    }
+
+    /// Gets the cell in another row that contains the longitude of the centre of this cell.
+    ///
+    /// - `y` - The row to move to.
+    fn in_row(&self, y: u32) -> Self {
+        let from = Self::row_length(self.y) as u64;
+        let to = Self::row_length(y) as u64;
+
+        Self::in_row_at(((2 * self.row_position() as u64 + 1) * to / (2 * from)) as u32, y)
+    }
+
+    /// Gets the cell on the opposite side of the sphere in the same row.
+    fn opposite(&self) -> Self {
+        let length = Self::row_length(self.y);
+
+        Self::in_row_at((self.row_position() + length / 2) % length, self.y)
+    }
+
+    /// Determines whether this cell is in the eastern half of the sphere where `up` moves south.
+    fn is_reversed(&self) -> bool {
+        self.row_position() >= Self::row_length(self.y) / 2
+    }
+
+    /// Moves one row towards the north pole, or over it onto the opposite side of the sphere.
+    fn north(&self) -> Self {
+        if self.y == 0 {
+            self.opposite()
+        } else {
+            self.in_row(self.y - 1)
+        }
+    }
+
+    /// Moves one row towards the south pole, or over it onto the opposite side of the sphere.
+    fn south(&self) -> Self {
+        if self.y == H as u32 - 1 {
+            self.opposite()
+        } else {
+            self.in_row(self.y + 1)
+        }
+    }
+
+    /// Gets the position in 3D space of a continuous position in the row of this cell.
+    ///
+    /// - `x` - The position within the part of the row on the sphere.
+    /// - `y` - The Y position on the grid.
+    /// - `scale` - The scale of the 3D object.
+    fn grid_position(&self, x: f64, y: f64, scale: f64) -> (f64, f64, f64) {
+        let latitude = PI / 2.0 - y / H as f64 * PI;
+        let longitude = x / Self::row_length(self.y) as f64 * PI * 2.0 - PI;
+
+        (
+            scale * latitude.cos() * longitude.sin(),
+            scale * latitude.sin(),
+            scale * latitude.cos() * longitude.cos(),
+        )
+    }
+}
+
+impl <const W: usize, const H: usize> GridPoint for SinusoidalSpherePoint<W, H> {
+    fn up(&self) -> Self {
+        if self.is_reversed() {
+            self.south()
+        } else {
+            self.north()
+        }
+    }
+
+    fn down(&self) -> Self {
+        if self.is_reversed() {
+            self.north()
+        } else {
+            self.south()
+        }
+    }
+
+    fn left(&self) -> Self {
+        let length = Self::row_length(self.y);
+
+        Self::in_row_at((self.row_position() + length - 1) % length, self.y)
+    }
+
+    fn right(&self) -> Self {
+        Self::in_row_at((self.row_position() + 1) % Self::row_length(self.y), self.y)
+    }
+
+    /// Gets the position of the point in 3D space.
+    ///
+    /// This is the position of the north western corner of the cell. Use `cell_center` to get the
+    /// centre of the cell. Padding cells use the position of the nearest cell on the sphere.
+    ///
+    /// - `scale` - The scale of the 3D object.
+    fn position(&self, scale: f64) -> (f64, f64, f64) {
+        self.grid_position(self.row_position() as f64, self.y as f64, scale)
+    }
+}
+
+impl <const W: usize, const H: usize> SpherePoint for SinusoidalSpherePoint<W, H> {
+    fn center_position(&self, scale: f64) -> (f64, f64, f64) {
+        self.cell_center(scale)
+    }
+
+    fn from_geographic(latitude: f64, longitude: f64) -> Self {
+        let y = (((PI / 2.0 - latitude) / PI * H as f64) as u32).min(H as u32 - 1);
+        let length = Self::row_length(y);
+
+        let x = (((longitude + PI).rem_euclid(PI * 2.0) / (PI * 2.0) * length as f64) as u32).min(length - 1);
+
+        Self::in_row_at(x, y)
+    }
+
+    fn latitude(&self) -> f64 {
+        PI / 2.0 - self.y as f64 / H as f64 * PI
+    }
+
+    /// Gets the longitude of this point.
+    ///
+    /// This is the longitude of the western edge of the cell, between `-PI` and `PI`.
+    fn longitude(&self) -> f64 {
+        self.row_position() as f64 / Self::row_length(self.y) as f64 * PI * 2.0 - PI
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::f64::consts::PI;
+
+    use approx::assert_relative_eq;
+    use rayon::iter::ParallelIterator;
+
+    use crate::{GridPoint, SurfaceGrid, sphere::SpherePoint};
+
+    use super::{SinusoidalSphereGrid, SinusoidalSpherePoint};
+
+    #[test]
+    fn test_sinusoidal_padding() {
+        let grid: SinusoidalSphereGrid<(), 32, 16> = SinusoidalSphereGrid::default();
+
+        for y in 0..16 {
+            let length = SinusoidalSpherePoint::<32, 16>::row_length(y);
+            let row: Vec<_> = grid.points()
+                .filter(|point| point.y == y && !point.is_padding())
+                .collect();
+
+            assert_eq!(length % 2, 0);
+            assert_eq!(length, SinusoidalSpherePoint::<32, 16>::row_length(15 - y));
+            assert_eq!(row.len(), length as usize);
+            assert_eq!(row[0].x + row[row.len() - 1].x, 31);
+        }
+
+        assert_eq!(SinusoidalSpherePoint::<32, 16>::row_length(0), 4);
+        assert_eq!(SinusoidalSpherePoint::<32, 16>::row_length(8), 32);
+    }
+
+    #[test]
+    fn test_sinusoidal_equal_area() {
+        let grid: SinusoidalSphereGrid<(), 64, 32> = SinusoidalSphereGrid::default();
+
+        let areas: Vec<_> = grid.points()
+            .filter(|point| !point.is_padding())
+            .map(|point| point.solid_angle())
+            .collect();
+
+        assert_relative_eq!(grid.points().map(|point| point.solid_angle()).sum::<f64>(), 4.0 * PI, epsilon = 1e-9);
+
+        let min = areas.iter().copied().fold(f64::INFINITY, f64::min);
+        let max = areas.iter().copied().fold(0.0, f64::max);
+
+        assert!(max / min < 1.5);
+    }
+
+    #[test]
+    fn test_sinusoidal_neighbours() {
+        let grid: SinusoidalSphereGrid<(), 32, 16> = SinusoidalSphereGrid::default();
+
+        for point in grid.points() {
+            let neighbours = [point.up(), point.down(), point.left(), point.right()];
+
+            for neighbour in neighbours {
+                assert!(!neighbour.is_padding());
+            }
+
+            if point.is_padding() {
+                continue;
+            }
+
+            assert_eq!(point.left().right(), point);
+            assert_eq!(point.right().left(), point);
+
+            for neighbour in neighbours {
+                let (x, y, z) = point.cell_center(1.0);
+                let (nx, ny, nz) = neighbour.cell_center(1.0);
+
+                assert_ne!(neighbour, point);
+                assert!(((x - nx).powi(2) + (y - ny).powi(2) + (z - nz).powi(2)).sqrt() < 0.35);
+            }
+        }
+
+        // Moving up over the north pole continues down the other side of the sphere.
+        let point = SinusoidalSpherePoint::<32, 16>::new(15, 0);
+
+        assert_eq!(point.up(), SinusoidalSpherePoint::new(17, 0));
+        assert!(point.up().up().longitude() >= 0.0);
+        assert_eq!(point.up().up().y, 1);
+    }
+
+    #[test]
+    fn test_sinusoidal_from_geographic() {
+        let grid: SinusoidalSphereGrid<(), 32, 16> = SinusoidalSphereGrid::default();
+
+        for point in grid.points().filter(|point| !point.is_padding()) {
+            // Move slightly into the cell from its corner to avoid rounding onto a neighbour.
+            assert_eq!(SinusoidalSpherePoint::from_geographic(point.latitude() - 1e-9, point.longitude() + 1e-9), point);
+
+            let (x, y, z) = point.cell_center(1.0);
+
+            assert_eq!(SinusoidalSpherePoint::nearest_point(x, y, z), point);
+        }
+
+        assert_eq!(SinusoidalSpherePoint::<32, 16>::from_geographic(0.01, 0.01), SinusoidalSpherePoint::new(16, 7));
+        assert_eq!(SinusoidalSpherePoint::<32, 16>::from_geographic(0.01, -0.01), SinusoidalSpherePoint::new(15, 7));
+    }
+
+    #[test]
+    fn test_sinusoidal_iteration_order() {
+        let grid: SinusoidalSphereGrid<usize, 16, 8> = SinusoidalSphereGrid::from_index_fn(|i| i);
+
+        for (i, (point, value)) in grid.iter().enumerate() {
+            assert_eq!(*value, i);
+            assert_eq!(grid.to_index(&point), i);
+            assert_eq!(point.x as usize, i % 16);
+            assert_eq!(point.y as usize, i / 16);
+        }
+
+        assert_eq!(grid.par_points().collect::<Vec<_>>(), grid.points().collect::<Vec<_>>());
+        assert_eq!(SinusoidalSphereGrid::from_fn_par(|point| grid[*point]), grid);
+        assert_eq!(grid.clone().into_iter().map(|(_, value)| value).collect::<Vec<_>>(), grid.to_vec());
+    }
+}
@@ -0,0 +1,590 @@
+//! A module containing grids that only store the cells that are not set to a default value.
+
+use std::{collections::BTreeMap, ops::{Index, IndexMut}, vec};
+
+use rayon::prelude::*;
+
+use crate::{SurfaceGrid, Topology};
+
+/// A grid that only stores the cells that are not set to a default value.
+///
+/// The shape of the grid is given by a grid of `()`, such as `CubeSphereGrid<(), 64>`, which
+/// takes up no memory and provides the points, their order and their positions. Reading a cell
+/// that is not stored gives the default value, so the grid behaves in the same way as the grid
+/// that it is shaped like while only using memory for the cells that are set. This suits
+/// automata where only a small part of the surface is active. Use `iter_set` to visit only the
+/// stored cells.
+///
+/// Mutably indexing a cell stores it, even when it is left with the default value, so call
+/// `compact` after writing to remove the cells that have returned to the default value. The
+/// functions that create and set the whole grid, such as `from_fn` and `map_neighbours`, only
+/// store the cells that are not the default value, as does `map_in_place`, and `apply_updates` and
+/// `update_where` remove the cells that they set to the default value.
+///
+/// # Type Parameters
+/// - `T` - The type of data that the grid holds.
+/// - `G` - The grid of `()` that gives the shape of the grid.
+///
+/// # Iteration Order
+/// `iter`, `points` and `into_iter` visit the cells in the same order as the grid given by `G`,
+/// including the cells that are not stored. `iter_set` visits the stored cells in the same
+/// order. This order is stable.
+///
+/// # Thread Safety
+/// The stored cells are kept in a `BTreeMap` alongside the grid of `()` that gives the shape, so
+/// the grid is `Send` when `T` and `G` are `Send` and `Sync` when `T` and `G` are `Sync`.
+///
+/// # Serialization
+/// With the `serde` feature enabled a grid serializes every cell, including the ones that are not
+/// stored, in the same format as the grid given by `G` so that the two can be used in place of
+/// each other.
+#[derive(Debug, Clone)]
+pub struct SparseSphereGrid<T, G: SurfaceGrid<()>> {
+    /// The stored cells along with their points, keyed by their index.
+    values: BTreeMap<usize, (G::Point, T)>,
+    /// The value of the cells that are not stored.
+    default: T,
+    /// The grid that gives the shape of this grid.
+    layout: G,
+}
+
+impl <T, G: SurfaceGrid<()> + Default> SparseSphereGrid<T, G> {
+    /// Creates a new grid where every cell is set to a default value.
+    ///
+    /// - `default` - The value of the cells that are not stored.
+    pub fn with_default(default: T) -> Self {
+        Self {
+            values: BTreeMap::new(),
+            default,
+            layout: G::default(),
+        }
+    }
+}
+
+impl <T, G: SurfaceGrid<()>> SparseSphereGrid<T, G> {
+    /// Gets the value of the cells that are not stored.
+    pub fn default_value(&self) -> &T {
+        &self.default
+    }
+
+    /// Gets the number of cells that are stored.
+    pub fn set_count(&self) -> usize {
+        self.values.len()
+    }
+
+    /// Iterates over the cells that are stored.
+    ///
+    /// This visits the cells in the same order as `iter` but skips the cells that are not stored.
+    pub fn iter_set(&self) -> impl Iterator<Item = (G::Point, &T)> {
+        self.values.values()
+            .map(|(point, value)| (point.clone(), value))
+    }
+
+    /// Removes a cell so that it has the default value.
+    ///
+    /// This returns the value that was stored for the cell, if there was one.
+    ///
+    /// - `point` - The cell to remove.
+    pub fn remove(&mut self, point: &G::Point) -> Option<T> {
+        self.values.remove(&self.layout.to_index(point))
+            .map(|(_, value)| value)
+    }
+
+    /// Removes every stored cell so that all of the cells have the default value.
+    pub fn clear(&mut self) {
+        self.values.clear();
+    }
+
+    /// Removes the stored cells that are equal to the default value.
+    pub fn compact(&mut self) where T: PartialEq {
+        let default = &self.default;
+
+        self.values.retain(|_, (_, value)| value != default);
+    }
+
+    /// Creates the map of stored cells from the points and values of the cells that are not the
+    /// default value.
+    ///
+    /// - `cells` - The points and values of the cells to store.
+    fn collect_values<I: IntoIterator<Item = (G::Point, T)>>(&self, cells: I) -> BTreeMap<usize, (G::Point, T)> {
+        cells.into_iter()
+            .map(|(point, value)| (self.layout.to_index(&point), (point, value)))
+            .collect()
+    }
+}
+
+impl <T: Default, G: SurfaceGrid<()> + Default> Default for SparseSphereGrid<T, G> {
+    fn default() -> Self {
+        Self::with_default(T::default())
+    }
+}
+
+impl <T: PartialEq, G: SurfaceGrid<()>> PartialEq for SparseSphereGrid<T, G> {
+    /// Compares the values of every cell, so cells that are stored with the default value are
+    /// equal to cells that are not stored.
+    fn eq(&self, other: &Self) -> bool {
+        self.layout.points()
+            .all(|point| self[point.clone()] == other[point])
+    }
+}
+
+impl <T, G> SurfaceGrid<T> for SparseSphereGrid<T, G>
+where
+    T: Clone + Default + PartialEq,
+    G: SurfaceGrid<()> + Default + Sync,
+    G::Point: Sync {
+    type Point = G::Point;
+
+    fn from_fn<F: FnMut(&Self::Point) -> T>(mut f: F) -> Self {
+        let mut grid = Self::default();
+
+        let cells: Vec<_> = grid.layout.points()
+            .map(|point| {
+                let value = f(&point);
+
+                (point, value)
+            })
+            .filter(|(_, value)| *value != grid.default)
+            .collect();
+
+        grid.values = grid.collect_values(cells);
+
+        grid
+    }
+
+    fn from_fn_par<F: Fn(&Self::Point) -> T + Send + Sync>(f: F) -> Self where T: Send + Sync {
+        let mut grid = Self::default();
+
+        grid.set_from_fn_par(f);
+
+        grid
+    }
+
+    fn try_from_fn<X, F: FnMut(&Self::Point) -> Result<T, X>>(mut f: F) -> Result<Self, X> {
+        let values = G::default().points()
+            .map(|point| f(&point))
+            .collect::<Result<_, _>>()?;
+
+        // The values are in the same order as `points`.
+        Ok(Self::from_vec(values).unwrap())
+    }
+
+    fn try_from_fn_par<X: Send, F: Fn(&Self::Point) -> Result<T, X> + Send + Sync>(f: F) -> Result<Self, X> where T: Send + Sync {
+        let values = G::default().points()
+            .collect::<Vec<_>>()
+            .into_par_iter()
+            .map(|point| f(&point))
+            .collect::<Result<_, _>>()?;
+
+        Ok(Self::from_vec(values).unwrap())
+    }
+
+    fn from_vec(values: Vec<T>) -> Option<Self> {
+        let mut grid = Self::default();
+
+        if values.len() != grid.layout.points().count() {
+            return None;
+        }
+
+        let cells: Vec<_> = grid.layout.points()
+            .zip(values)
+            .filter(|(_, value)| *value != grid.default)
+            .collect();
+
+        grid.values = grid.collect_values(cells);
+
+        Some(grid)
+    }
+
+    fn set_from_fn<F: FnMut(&Self::Point) -> T>(&mut self, mut f: F) {
+        let cells: Vec<_> = self.layout.points()
+            .map(|point| {
+                let value = f(&point);
+
+                (point, value)
+            })
+            .filter(|(_, value)| *value != self.default)
+            .collect();
+
+        self.values = self.collect_values(cells);
+    }
+
+    fn set_from_fn_par<F: Fn(&Self::Point) -> T + Send + Sync>(&mut self, f: F) where T: Send + Sync {
+        let default = &self.default;
+
+        let cells = self.layout.par_map_to_vec(|point, _| {
+            let value = f(point);
+
+            (value != *default).then(|| (point.clone(), value))
+        });
+
+        self.values = self.collect_values(cells.into_iter().flatten());
+    }
+
+    fn apply_updates<I: IntoIterator<Item = (Self::Point, T)>>(&mut self, updates: I) {
+        // Cells that are set to the default value are removed instead of being stored.
+        for (point, value) in updates {
+            let index = self.layout.to_index(&point);
+
+            if value == self.default {
+                self.values.remove(&index);
+            } else {
+                self.values.insert(index, (point, value));
+            }
+        }
+    }
+
+    fn apply_updates_par<I: IntoParallelIterator<Item = (Self::Point, T)>>(&mut self, updates: I) where T: Send + Sync, Self: Sync {
+        // Merging in parallel would store every cell, so only the updates are gathered in parallel.
+        let updates: Vec<_> = updates.into_par_iter().collect();
+
+        self.apply_updates(updates);
+    }
+
+    fn map_in_place<F: FnMut(&T) -> T>(&mut self, mut f: F) {
+        let cells: Vec<_> = self.iter()
+            .map(|(point, value)| (point, f(value)))
+            .filter(|(_, value)| *value != self.default)
+            .collect();
+
+        self.values = self.collect_values(cells);
+    }
+
+    fn map_in_place_par<F: Fn(&T) -> T + Send + Sync>(&mut self, f: F) where T: Send + Sync {
+        let default = &self.default;
+
+        let cells = self.layout.par_map_to_vec(|point, _| {
+            let value = f(&self[point.clone()]);
+
+            (value != *default).then(|| (point.clone(), value))
+        });
+
+        self.values = self.collect_values(cells.into_iter().flatten());
+    }
+
+    fn update_where<
+                P: FnMut(&Self::Point, &T) -> bool,
+                F: FnMut(&T) -> T
+            >(&mut self, mut predicate: P, mut f: F) {
+        let updates: Vec<_> = self.iter()
+            .filter(|(point, value)| predicate(point, value))
+            .map(|(point, value)| (point, f(value)))
+            .collect();
+
+        self.apply_updates(updates);
+    }
+
+    fn update_where_par<
+                P: Fn(&Self::Point, &T) -> bool + Send + Sync,
+                F: Fn(&T) -> T + Send + Sync
+            >(&mut self, predicate: P, f: F) where T: Send + Sync {
+        let updates = self.layout.par_map_to_vec(|point, _| {
+            let value = &self[point.clone()];
+
+            predicate(point, value).then(|| (point.clone(), f(value)))
+        });
+
+        self.apply_updates(updates.into_iter().flatten());
+    }
+
+    fn iter<'a>(&'a self) -> impl ExactSizeIterator<Item = (Self::Point, &'a T)> + DoubleEndedIterator where T: 'a {
+        self.layout.points()
+            .map(|point| (point.clone(), &self[point]))
+    }
+
+    fn par_iter<'a>(&'a self) -> impl IndexedParallelIterator<Item = (Self::Point, &'a T)> where T: 'a + Send + Sync {
+        self.layout.par_points()
+            .map(|point| (point.clone(), &self[point]))
+    }
+
+    fn par_iter_mut<'a>(&'a mut self) -> impl IndexedParallelIterator<Item = (Self::Point, &'a mut T)> where T: 'a + Send + Sync {
+        // Every cell is stored so that it can be borrowed, in the same way as `IndexMut`.
+        for point in self.layout.points() {
+            let default = &self.default;
+
+            self.values.entry(self.layout.to_index(&point))
+                .or_insert_with(|| (point, default.clone()));
+        }
+
+        // The map cannot be split by index so the cells are gathered in order first.
+        self.values.values_mut()
+            .map(|(point, value)| (point.clone(), value))
+            .collect::<Vec<_>>()
+            .into_par_iter()
+    }
+
+    fn points(&self) -> impl ExactSizeIterator<Item = Self::Point> + DoubleEndedIterator {
+        self.layout.points()
+    }
+
+    fn par_points(&self) -> impl IndexedParallelIterator<Item = Self::Point> {
+        self.layout.par_points()
+    }
+
+    fn par_map_to_vec<U: Send, F: Fn(&Self::Point, &T) -> U + Send + Sync>(&self, f: F) -> Vec<U> where T: Send + Sync {
+        self.layout.par_map_to_vec(|point, _| f(point, &self[point.clone()]))
+    }
+
+    fn to_index(&self, point: &Self::Point) -> usize {
+        self.layout.to_index(point)
+    }
+    fn at(&self, point: &Self::Point) -> &T {
+        self.values.get(&self.layout.to_index(point))
+            .map_or(&self.default, |(_, value)| value)
+    }
+
+
+    fn surface_area(&self, scale: f64) -> f64 {
+        self.layout.surface_area(scale)
+    }
+
+    fn topology(&self) -> Topology {
+        self.layout.topology()
+    }
+}
+
+impl <T, G: SurfaceGrid<()>> Index<G::Point> for SparseSphereGrid<T, G> {
+    type Output = T;
+
+    fn index(&self, index: G::Point) -> &Self::Output {
+        self.values.get(&self.layout.to_index(&index))
+            .map_or(&self.default, |(_, value)| value)
+    }
+}
+
+impl <T: Clone, G: SurfaceGrid<()>> IndexMut<G::Point> for SparseSphereGrid<T, G> {
+    /// Gets a mutable reference to a cell, storing it with the default value if it is not stored.
+    fn index_mut(&mut self, index: G::Point) -> &mut Self::Output {
+        let default = &self.default;
+
+        &mut self.values.entry(self.layout.to_index(&index))
+            .or_insert_with(|| (index, default.clone()))
+            .1
+    }
+}
+
+impl <T, G> FromIterator<T> for SparseSphereGrid<T, G>
+where
+    T: Clone + Default + PartialEq,
+    G: SurfaceGrid<()> + Default + Sync,
+    G::Point: Sync {
+    /// Creates a grid from the values of its cells in the same order as `points`.
+    ///
+    /// # Panics
+    /// Panics if the iterator does not yield exactly one value for each cell in the grid.
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        Self::from_vec(iter.into_iter().collect())
+            .expect("the iterator must yield one value for each cell in the grid")
+    }
+}
+
+impl <T: Clone, G: SurfaceGrid<()>> IntoIterator for SparseSphereGrid<T, G> {
+    type Item = (G::Point, T);
+
+    type IntoIter = vec::IntoIter<Self::Item>;
+
+    fn into_iter(mut self) -> Self::IntoIter {
+        let data: Vec<_> = self.layout.points()
+            .map(|point| {
+                let value = self.values.remove(&self.layout.to_index(&point))
+                    .map_or_else(|| self.default.clone(), |(_, value)| value);
+
+                (point, value)
+            })
+            .collect();
+
+        data.into_iter()
+    }
+}
+
+#[cfg(feature = "serde")]
+impl <T: serde::Serialize, G: SurfaceGrid<()>> serde::Serialize for SparseSphereGrid<T, G> {
+    /// Serializes the values of every cell as a sequence in the same order as `iter`.
+    fn serialize<R: serde::Serializer>(&self, serializer: R) -> Result<R::Ok, R::Error> {
+        serializer.collect_seq(self.layout.points().map(|point| &self[point]))
+    }
+}
+
+#[cfg(feature = "serde")]
+impl <'de, T, G> serde::Deserialize<'de> for SparseSphereGrid<T, G>
+where
+    T: serde::Deserialize<'de> + Clone + Default + PartialEq,
+    G: SurfaceGrid<()> + Default + Sync,
+    G::Point: Sync {
+    /// Deserializes a grid from a sequence of values in the same order as `iter`, only storing
+    /// the values that are not the default value.
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let values = Vec::<T>::deserialize(deserializer)?;
+        let length = values.len();
+        let expected = G::default().points().count();
+
+        Self::from_vec(values)
+            .ok_or_else(|| serde::de::Error::invalid_length(length, &format!("a sequence of {} values", expected).as_str()))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{GridPoint, SurfaceGrid, sphere::{CubeFace, CubeSphereGrid, CubeSpherePoint, RectangleSphereGrid, SpherePoint}};
+
+    use super::SparseSphereGrid;
+
+    #[test]
+    fn test_sparse_default() {
+        let grid: SparseSphereGrid<u32, RectangleSphereGrid<(), 16, 8>> = SparseSphereGrid::with_default(7);
+
+        assert_eq!(grid.set_count(), 0);
+        assert_eq!(grid.iter_set().count(), 0);
+        assert_eq!(grid.iter().count(), 128);
+        assert!(grid.iter().all(|(_, value)| *value == 7));
+        assert_eq!(*grid.default_value(), 7);
+    }
+
+    #[test]
+    fn test_sparse_iter_set() {
+        let grid: SparseSphereGrid<u32, CubeSphereGrid<(), 8>> = SparseSphereGrid::from_fn(|point: &CubeSpherePoint<8>| (point.latitude() > 1.3) as u32);
+
+        let set: Vec<_> = grid.iter_set().collect();
+        let dense: CubeSphereGrid<u32, 8> = CubeSphereGrid::from_fn(|point| (point.latitude() > 1.3) as u32);
+
+        assert_eq!(set.len(), dense.iter().filter(|(_, value)| **value == 1).count());
+        assert!(!set.is_empty() && set.len() < 20);
+        assert!(set.iter().all(|(_, value)| **value == 1));
+        assert!(set.windows(2).all(|pair| grid.to_index(&pair[0].0) < grid.to_index(&pair[1].0)));
+        assert_eq!(grid.to_vec(), dense.to_vec());
+    }
+
+    #[test]
+    fn test_sparse_index_mut() {
+        let mut grid: SparseSphereGrid<u32, CubeSphereGrid<(), 4>> = SparseSphereGrid::default();
+        let point = grid.points().nth(10).unwrap();
+
+        grid[point] += 3;
+
+        assert_eq!(grid[point], 3);
+        assert_eq!(grid[point.up()], 0);
+        assert_eq!(grid.set_count(), 1);
+
+        grid[point] = 0;
+        grid[point.up()] = 0;
+
+        assert_eq!(grid.set_count(), 2);
+        assert_eq!(grid, SparseSphereGrid::default());
+
+        grid.compact();
+
+        assert_eq!(grid.set_count(), 0);
+
+        grid[point] = 5;
+
+        assert_eq!(grid.remove(&point), Some(5));
+        assert_eq!(grid.remove(&point), None);
+    }
+
+    #[test]
+    fn test_sparse_map_neighbours() {
+        let grid: SparseSphereGrid<u32, CubeSphereGrid<(), 8>> = SparseSphereGrid::from_fn(|point: &CubeSpherePoint<8>| (point.latitude() > 1.3) as u32);
+
+        let next = grid.map_neighbours(|current, up, down, left, right| *current | *up | *down | *left | *right);
+
+        assert!(next.set_count() > grid.set_count());
+        assert_eq!(next, grid.map_neighbours_par(|current, up, down, left, right| *current | *up | *down | *left | *right));
+
+        for (point, value) in grid.iter_set() {
+            assert_eq!(*value, 1);
+            assert_eq!(next[point.left()], 1);
+        }
+    }
+
+    #[test]
+    fn test_sparse_iteration_order() {
+        let grid: SparseSphereGrid<usize, CubeSphereGrid<(), 3>> = SparseSphereGrid::from_index_fn(|i| i % 5);
+
+        for (i, (point, value)) in grid.iter().enumerate() {
+            assert_eq!(*value, i % 5);
+            assert_eq!(grid.to_index(&point), i);
+        }
+
+        assert_eq!(grid.set_count(), 54 - 11);
+        assert_eq!(grid.par_map_to_vec(|_, value| *value), grid.to_vec());
+        assert_eq!(grid.clone().into_iter().map(|(_, value)| value).collect::<Vec<_>>(), grid.to_vec());
+        assert_eq!(SparseSphereGrid::from_vec(grid.to_vec()), Some(grid.clone()));
+        assert_eq!(SparseSphereGrid::from_fn_par(|point| grid[*point]), grid);
+    }
+
+    #[test]
+    fn test_sparse_par_iter_mut() {
+        use rayon::iter::ParallelIterator;
+
+        let mut grid: SparseSphereGrid<u32, CubeSphereGrid<(), 4>> = SparseSphereGrid::default();
+        let point = grid.points().nth(7).unwrap();
+
+        grid[point] = 5;
+        grid.par_iter_mut().for_each(|(_, value)| *value += 1);
+
+        assert_eq!(6, grid[point]);
+        assert_eq!(6 * 4 * 4 + 5, grid.iter().map(|(_, value)| value).sum::<u32>());
+    }
+
+    #[test]
+    fn test_sparse_from_iter() {
+        let grid: SparseSphereGrid<u8, CubeSphereGrid<(), 4>> = (0..96).map(|i| (i % 3 == 0) as u8).collect();
+
+        assert_eq!(32, grid.iter().filter(|(_, value)| **value == 1).count());
+    }
+
+    #[test]
+    fn test_sparse_apply_updates() {
+        let mut grid: SparseSphereGrid<u32, CubeSphereGrid<(), 4>> = SparseSphereGrid::default();
+
+        grid.apply_updates([
+            (CubeSpherePoint::new(CubeFace::Top, 1, 2), 3),
+            (CubeSpherePoint::new(CubeFace::Left, 0, 0), 4),
+        ]);
+        grid.apply_updates_par(vec![
+            (CubeSpherePoint::new(CubeFace::Left, 0, 0), 0),
+            (CubeSpherePoint::new(CubeFace::Back, 3, 3), 5),
+        ]);
+
+        assert_eq!(2, grid.set_count());
+        assert_eq!(3, grid[CubeSpherePoint::new(CubeFace::Top, 1, 2)]);
+        assert_eq!(0, grid[CubeSpherePoint::new(CubeFace::Left, 0, 0)]);
+        assert_eq!(5, grid[CubeSpherePoint::new(CubeFace::Back, 3, 3)]);
+    }
+
+    #[test]
+    fn test_sparse_map_in_place() {
+        let mut grid: SparseSphereGrid<u32, CubeSphereGrid<(), 4>> = SparseSphereGrid::default();
+
+        grid[CubeSpherePoint::new(CubeFace::Top, 1, 2)] = 3;
+        grid[CubeSpherePoint::new(CubeFace::Left, 0, 0)] = 1;
+
+        let mut par_grid = grid.clone();
+
+        grid.map_in_place(|value| value.saturating_sub(1) * 2);
+        par_grid.map_in_place_par(|value| value.saturating_sub(1) * 2);
+
+        assert_eq!(1, grid.set_count());
+        assert_eq!(4, grid[CubeSpherePoint::new(CubeFace::Top, 1, 2)]);
+        assert_eq!(grid, par_grid);
+        assert_eq!(1, par_grid.set_count());
+    }
+
+    #[test]
+    fn test_sparse_update_where() {
+        let mut grid: SparseSphereGrid<u32, CubeSphereGrid<(), 4>> = SparseSphereGrid::default();
+
+        grid[CubeSpherePoint::new(CubeFace::Top, 1, 2)] = 3;
+        grid[CubeSpherePoint::new(CubeFace::Left, 0, 0)] = 5;
+
+        let mut par_grid = grid.clone();
+
+        grid.update_where(|_, value| *value == 3, |_| 0);
+        par_grid.update_where_par(|_, value| *value == 3, |_| 0);
+
+        assert_eq!(1, grid.set_count());
+        assert_eq!(5, grid[CubeSpherePoint::new(CubeFace::Left, 0, 0)]);
+        assert_eq!(grid, par_grid);
+        assert_eq!(1, par_grid.set_count());
+    }
+}
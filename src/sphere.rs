@@ -1,12 +1,24 @@
 //! A module containing grids wrapped around spheres.
 
-use std::{f64::consts::PI, ops::{Index, IndexMut}, vec, fmt::Debug};
+use std::{collections::{HashMap, HashSet}, f64::consts::PI, hash::{Hash, Hasher}, ops::{Index, IndexMut}, vec};
 
 use itertools::Itertools;
 use rayon::prelude::*;
 use static_array::HeapArray2D;
 
-use crate::{GridPoint, SurfaceGrid};
+use crate::{GridPoint, Surface, SurfaceGrid, Topology, OutOfBounds, flat::FlatGrid};
+
+assert_send_sync!([T: Send + Sync, const W: usize, const H: usize]
+    RectangleSphereGrid<T, W, H>,
+    RectangleSpherePoint<W, H>,
+    CubeSphereGrid<T, W>,
+    CubeSpherePoint<W>,
+    DynRectangleSpherePoint,
+    EqualAreaCubeSphereGrid<T, W>,
+    EqualAreaCubeSpherePoint<W>,
+    EquiAngularCubeSphereGrid<T, W>,
+    EquiAngularCubeSpherePoint<W>,
+);
 
 /// A point on a spherical grid.
 pub trait SpherePoint : GridPoint {
@@ -28,6 +40,41 @@ pub trait SpherePoint : GridPoint {
     fn sphere_coordinates(&self) -> (f64, f64) {
         (self.longitude(), self.latitude())
     }
+
+    /// Returns a coordinate containing the latitude and longitude of the centre of this cell.
+    /// This returns a point with the X component being the longitude and the Y component being the
+    /// latitude.
+    ///
+    /// By default this is found from the direction of `center_position`.
+    fn center_sphere_coordinates(&self) -> (f64, f64) {
+        let (x, y, z) = self.center_position(1.0);
+
+        (x.atan2(z), y.atan2((x * x + z * z).sqrt()))
+    }
+
+    /// Gets the position of the centre of this cell in 3D space.
+    ///
+    /// By default this is the same as `GridPoint::position`.
+    ///
+    /// - `scale` - The scale of the 3D object.
+    fn center_position(&self, scale: f64) -> (f64, f64, f64) {
+        self.position(scale)
+    }
+
+    /// Gets the sphere point nearest to the specified position in 3D space.
+    ///
+    /// The position does not need to lie on the surface of the sphere as only its direction from
+    /// the centre of the sphere is used.
+    ///
+    /// - `x` - The X position.
+    /// - `y` - The Y position.
+    /// - `z` - The Z position.
+    fn nearest_point(x: f64, y: f64, z: f64) -> Self where Self: Sized {
+        let latitude = y.atan2((x * x + z * z).sqrt());
+        let longitude = x.atan2(z);
+
+        Self::from_geographic(latitude, longitude)
+    }
 }
 
 /// A grid for a sphere based on the equirectangular projection.
@@ -38,71 +85,283 @@ pub trait SpherePoint : GridPoint {
 /// # Constant Parameters
 /// - `W` - The width of the grid.
 /// - `H` - The height of the grid.
-#[derive(Debug, Clone, PartialEq, Eq, Hash, Default)]
+///
+/// # Cloning
+/// `clone_from` copies the values into the existing allocation rather than allocating a new grid,
+/// which suits copying a grid into a buffer that is reused between steps.
+///
+/// # Iteration Order
+/// `iter`, `points` and `into_iter` all visit the grid in row-major order, starting at `(0, 0)`
+/// and increasing `x` before `y`. This order is stable and matches the layout used by `as_array`.
+///
+/// # Thread Safety
+/// The cells are kept in a single `HeapArray2D`, so the grid is `Send` when `T` is `Send` and
+/// `Sync` when `T` is `Sync`.
+#[derive(Debug, PartialEq, Eq, Hash, Default)]
 pub struct RectangleSphereGrid<T, const W: usize, const H: usize> {
     /// The data held in this grid.
     data: HeapArray2D<T, W, H>,
 }
 
-impl <T, const W: usize, const H: usize> SurfaceGrid<T> for RectangleSphereGrid<T, W, H> {
-    type Point = RectangleSpherePoint<W, H>;
-
-    fn from_fn<F: FnMut(&Self::Point) -> T>(mut f: F) -> Self {
+impl <T: Clone, const W: usize, const H: usize> Clone for RectangleSphereGrid<T, W, H> {
+    fn clone(&self) -> Self {
         Self {
-            data: HeapArray2D::from_fn(|y, x| {
-                let point = RectangleSpherePoint::new(x as u32, y as u32);
-
-                f(&point)
-            })
+            data: self.data.clone(),
         }
     }
 
-    fn from_fn_par<F: Fn(&Self::Point) -> T + Send + Sync>(f: F) -> Self where T: Send + Sync {
-        Self {
-            data: HeapArray2D::from_fn_par(|y, x| {
-                let point = RectangleSpherePoint::new(x as u32, y as u32);
+    /// Copies the values of another grid into this grid without allocating.
+    fn clone_from(&mut self, source: &Self) {
+        self.as_mut_slice().clone_from_slice(source.as_slice());
+    }
+}
 
-                f(&point)
-            })
+impl <T, const W: usize, const H: usize> RectangleSphereGrid<T, W, H> {
+    /// Gets a reference to the array backing this grid.
+    ///
+    /// The array is indexed as `[y][x]` where `x` and `y` are the coordinates of a
+    /// `RectangleSpherePoint`.
+    pub fn as_array(&self) -> &HeapArray2D<T, W, H> {
+        &self.data
+    }
+
+    /// Gets a mutable reference to the array backing this grid.
+    ///
+    /// The array is indexed as `[y][x]` where `x` and `y` are the coordinates of a
+    /// `RectangleSpherePoint`.
+    pub fn as_array_mut(&mut self) -> &mut HeapArray2D<T, W, H> {
+        &mut self.data
+    }
+
+    /// Converts this grid into the array backing it.
+    ///
+    /// The array is indexed as `[y][x]` where `x` and `y` are the coordinates of a
+    /// `RectangleSpherePoint`.
+    pub fn into_array(self) -> HeapArray2D<T, W, H> {
+        self.data
+    }
+
+    /// Gets the values of the cells in the same order as the points of the grid.
+    pub fn as_slice(&self) -> &[T] {
+        self.data.as_flattened()
+    }
+
+    /// Gets mutable references to the values of the cells in the same order as the points of the
+    /// grid.
+    pub fn as_mut_slice(&mut self) -> &mut [T] {
+        self.data.as_flattened_mut()
+    }
+
+    /// Gets the values of the cells as bytes in the same order as `as_slice`.
+    #[cfg(feature = "bytemuck")]
+    pub fn as_bytes(&self) -> &[u8] where T: bytemuck::Pod {
+        bytemuck::cast_slice(self.as_slice())
+    }
+
+    /// Gets the values of the cells as mutable bytes in the same order as `as_slice`.
+    #[cfg(feature = "bytemuck")]
+    pub fn as_bytes_mut(&mut self) -> &mut [u8] where T: bytemuck::Pod {
+        bytemuck::cast_slice_mut(self.as_mut_slice())
+    }
+
+    /// Creates a grid with different dimensions by resampling this grid.
+    ///
+    /// Each cell of the new grid takes the value of the cell in this grid that contains the
+    /// geographic coordinates of its centre.
+    ///
+    /// # Constant Parameters
+    /// - `W2` - The width of the new grid.
+    /// - `H2` - The height of the new grid.
+    pub fn resize<const W2: usize, const H2: usize>(&self) -> RectangleSphereGrid<T, W2, H2> where T: Clone {
+        RectangleSphereGrid::from_fn(|point: &RectangleSpherePoint<W2, H2>| {
+            let latitude = PI / 2.0 - (point.y as f64 + 0.5) / H2 as f64 * PI;
+            let longitude = (point.x as f64 + 0.5) / W2 as f64 * PI * 2.0;
+
+            self[RectangleSpherePoint::from_geographic(latitude, longitude)].clone()
+        })
+    }
+
+    /// Sets every cell with a centre inside a polygon on the sphere to a value.
+    ///
+    /// The edges of the polygon are great circle arcs between consecutive vertices with the last
+    /// vertex joined back to the first, so the edges between vertices at the same latitude curve
+    /// towards the pole. The vertices may be given in either order and the polygon may cross the
+    /// antimeridian or surround a pole but it should fit within the hemisphere centred on the
+    /// average of its vertices.
+    ///
+    /// - `vertices` - The latitude and longitude of each vertex in radians.
+    /// - `value` - The value to set the cells to.
+    pub fn fill_polygon(&mut self, vertices: &[(f64, f64)], value: T) where T: Clone {
+        let vertices: Vec<_> = vertices.iter()
+            .map(|vertex| geographic_position(*vertex))
+            .collect();
+
+        let centre = vertices.iter()
+            .fold((0.0, 0.0, 0.0), |(x, y, z), (vx, vy, vz)| (x + vx, y + vy, z + vz));
+
+        let points: Vec<_> = self.points()
+            .filter(|point| in_spherical_polygon(point.cell_center(1.0), &vertices, centre))
+            .collect();
+
+        for point in points {
+            self[point] = value.clone();
         }
     }
 
-    fn set_from_fn<F: FnMut(&Self::Point) -> T>(&mut self, mut f: F) {
-        (0..H).cartesian_product(0..W)
-            .map(|(y, x)| RectangleSpherePoint::new(x as u32, y as u32))
-            .for_each(|point| self[point] = f(&point))
+    /// Copies a rectangular region of this grid into a flat grid.
+    ///
+    /// The region wraps around the sphere horizontally but does not continue over the poles.
+    ///
+    /// # Constant Parameters
+    /// - `W2` - The width of the region.
+    /// - `H2` - The height of the region.
+    ///
+    /// - `x0` - The X position of the top left corner of the region.
+    /// - `y0` - The Y position of the top left corner of the region.
+    ///
+    /// # Panics
+    /// Panics if `x0` is not on the grid or if the region extends past the bottom of the grid.
+    pub fn crop<const W2: usize, const H2: usize>(&self, x0: u32, y0: u32) -> FlatGrid<T, W2, H2> where T: Clone {
+        assert!((x0 as usize) < W, "x0 is outside of the grid");
+        assert!(y0 as usize + H2 <= H, "the region extends past the bottom of the grid");
+
+        let values = (0..H2).cartesian_product(0..W2)
+            .map(|(y, x)| self.data[y0 as usize + y][(x0 as usize + x) % W].clone())
+            .collect();
+
+        FlatGrid::from_vec(values).unwrap()
     }
+}
 
-    fn set_from_fn_par<F: Fn(&Self::Point) -> T + Send + Sync>(&mut self, f: F) where T: Send + Sync {
-        self.data.iter_mut().enumerate().par_bridge().for_each(|(y, subarray)| {
-            for x in 0..W {
-                let point = RectangleSpherePoint::new(x as u32, y as u32);
+impl <const W: usize, const H: usize> RectangleSphereGrid<f64, W, H> {
+    /// Creates a grid from the sum of a set of real spherical harmonics.
+    ///
+    /// Each term is a real spherical harmonic of degree `l` and order `m` multiplied by a
+    /// coefficient and evaluated at the centre of each cell. The harmonics are orthonormal over
+    /// the unit sphere and do not include the Condon-Shortley phase. Positive orders vary with the
+    /// cosine of the longitude and negative orders with the sine.
+    ///
+    /// - `coefficients` - The degree, order and coefficient of each term.
+    ///
+    /// # Panics
+    /// Panics if a degree is negative or the magnitude of an order is greater than its degree.
+    pub fn from_spherical_harmonics(coefficients: &[(i32, i32, f64)]) -> Self {
+        Self::from_fn(|point| spherical_harmonic_sum(coefficients, point.cell_center(1.0)))
+    }
 
-                subarray[x] = f(&point);
-            }
+    /// Computes the discrete Laplacian (Laplace-Beltrami operator) of the values in this grid.
+    ///
+    /// Each value is treated as the average over its cell and the differences between
+    /// neighbouring cells are weighted by the physical size of the cells so that the result is not
+    /// biased by the cells shrinking towards the poles.
+    ///
+    /// - `scale` - The radius of the sphere.
+    pub fn laplacian(&self, scale: f64) -> Self {
+        let d_lat = PI / H as f64;
+        let d_long = PI * 2.0 / W as f64;
+
+        Self::from_fn(|point| {
+            let x = point.x as usize;
+            let y = point.y as usize;
+            let value = self.data[y][x];
+
+            let latitude = PI / 2.0 - (y as f64 + 0.5) * d_lat;
+            let cos = latitude.cos();
+
+            // The edges of the cells at the poles have no length so nothing flows across them.
+            let north = if y == 0 {
+                0.0
+            } else {
+                (latitude + d_lat / 2.0).cos() * (self.data[y - 1][x] - value)
+            };
+            let south = if y == H - 1 {
+                0.0
+            } else {
+                (latitude - d_lat / 2.0).cos() * (self.data[y + 1][x] - value)
+            };
+
+            let east = self.data[y][(x + 1) % W];
+            let west = self.data[y][(x + W - 1) % W];
+
+            let meridional = (north + south) / (cos * d_lat * d_lat);
+            let zonal = (east + west - 2.0 * value) / (cos * cos * d_long * d_long);
+
+            (meridional + zonal) / (scale * scale)
         })
     }
 
-    fn iter<'a>(&'a self) -> impl Iterator<Item = (RectangleSpherePoint<W, H>, &'a T)> where T: 'a {
-        (0..H).cartesian_product(0..W)
-            .map(|(y, x)| (RectangleSpherePoint::new(x as u32, y as u32), &self.data[y][x]))
+    /// Averages each value with the value in the cell reflected across the equator.
+    ///
+    /// The result is symmetric between the northern and southern hemispheres.
+    pub fn symmetrize_equator(&self) -> Self {
+        Self::from_fn(|point| {
+            let x = point.x as usize;
+            let y = point.y as usize;
+
+            (self.data[y][x] + self.data[H - 1 - y][x]) / 2.0
+        })
     }
 
-    fn par_iter<'a>(&'a self) -> impl ParallelIterator<Item = (Self::Point, &'a T)> where T: 'a + Send + Sync {
-        (0..H).cartesian_product(0..W)
-            .par_bridge()
-            .map(|(y, x)| (RectangleSpherePoint::new(x as u32, y as u32), &self.data[y][x]))
+    /// Averages each value with the values in the cells found by rotating it about the poles.
+    ///
+    /// The result has `fold`-fold rotational symmetry so it is unchanged by turning it about the
+    /// poles by any multiple of `1 / fold` of a full turn.
+    ///
+    /// - `fold` - The number of rotations to average over.
+    ///
+    /// # Panics
+    /// Panics if `fold` is zero or does not divide the width of the grid.
+    pub fn symmetrize_longitudinal(&self, fold: u32) -> Self {
+        assert!(fold != 0 && W.is_multiple_of(fold as usize), "the fold must divide the width of the grid");
+
+        let step = W / fold as usize;
+
+        Self::from_fn(|point| {
+            let x = point.x as usize;
+            let y = point.y as usize;
+
+            (0..fold as usize)
+                .map(|i| self.data[y][(x + i * step) % W])
+                .sum::<f64>() / fold as f64
+        })
     }
+}
+
+impl <T, const W: usize, const H: usize> SurfaceGrid<T> for RectangleSphereGrid<T, W, H> {
+    type Point = RectangleSpherePoint<W, H>;
+
+    row_major_grid_methods!(RectangleSpherePoint);
 
-    fn points(&self) -> impl Iterator<Item = Self::Point> {
-        (0..H).cartesian_product(0..W)
+    fn points_in_region(&self, lat_range: (f64, f64), lon_range: (f64, f64)) -> impl Iterator<Item = Self::Point> where Self::Point: SpherePoint {
+        // Find the rows and columns that could be in the region then check each point exactly.
+        let (min_lat, max_lat) = (lat_range.0.min(lat_range.1), lat_range.0.max(lat_range.1));
+
+        let start_y = (((PI / 2.0 - max_lat) / PI * H as f64).floor() as i64 - 1).clamp(0, H as i64 - 1) as usize;
+        let end_y = (((PI / 2.0 - min_lat) / PI * H as f64).ceil() as i64 + 1).clamp(0, H as i64 - 1) as usize;
+
+        let columns: Vec<usize> = if lon_range.1 - lon_range.0 >= PI * 2.0 {
+            (0..W).collect()
+        } else {
+            let start_x = (lon_range.0.rem_euclid(PI * 2.0) / (PI * 2.0) * W as f64).floor() as i64 - 1;
+            let end_x = (lon_range.1.rem_euclid(PI * 2.0) / (PI * 2.0) * W as f64).ceil() as i64 + 1;
+            let end_x = if end_x < start_x { end_x + W as i64 } else { end_x };
+
+            (start_x..=end_x.min(start_x + W as i64 - 1))
+                .map(|x| x.rem_euclid(W as i64) as usize)
+                .collect()
+        };
+
+        (start_y..=end_y).cartesian_product(columns)
             .map(|(y, x)| RectangleSpherePoint::new(x as u32, y as u32))
+            .filter(move |point| in_region(point, lat_range, lon_range))
     }
 
-    fn par_points(&self) -> impl ParallelIterator<Item = Self::Point> {
-        (0..H).cartesian_product(0..W)
-            .par_bridge()
-            .map(|(y, x)| RectangleSpherePoint::new(x as u32, y as u32))
+    fn surface_area(&self, scale: f64) -> f64 {
+        4.0 * PI * scale * scale
+    }
+
+    fn topology(&self) -> Topology {
+        Topology::uniform(Surface::Sphere, 1, W, H)
     }
 }
 
@@ -120,6 +379,31 @@ impl <T, const W: usize, const H: usize> IndexMut<RectangleSpherePoint<W, H>> fo
     }
 }
 
+impl <T, const W: usize, const H: usize> Index<&RectangleSpherePoint<W, H>> for RectangleSphereGrid<T, W, H> {
+    type Output = T;
+
+    fn index(&self, index: &RectangleSpherePoint<W, H>) -> &Self::Output {
+        &self[*index]
+    }
+}
+
+impl <T, const W: usize, const H: usize> IndexMut<&RectangleSpherePoint<W, H>> for RectangleSphereGrid<T, W, H> {
+    fn index_mut(&mut self, index: &RectangleSpherePoint<W, H>) -> &mut Self::Output {
+        &mut self[*index]
+    }
+}
+
+impl <T, const W: usize, const H: usize> FromIterator<T> for RectangleSphereGrid<T, W, H> {
+    /// Creates a grid from the values of its cells in the same order as `points`.
+    ///
+    /// # Panics
+    /// Panics if the iterator does not yield exactly one value for each cell in the grid.
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        Self::from_vec(iter.into_iter().collect())
+            .expect("the iterator must yield one value for each cell in the grid")
+    }
+}
+
 impl <T, const W: usize, const H: usize> IntoIterator for RectangleSphereGrid<T, W, H> {
     type Item = (RectangleSpherePoint<W, H>, T);
 
@@ -138,6 +422,26 @@ impl <T, const W: usize, const H: usize> IntoIterator for RectangleSphereGrid<T,
     }
 }
 
+#[cfg(feature = "serde")]
+impl <T: serde::Serialize, const W: usize, const H: usize> serde::Serialize for RectangleSphereGrid<T, W, H> {
+    /// Serializes the values in this grid as a sequence in the same order as `iter`.
+    fn serialize<R: serde::Serializer>(&self, serializer: R) -> Result<R::Ok, R::Error> {
+        serializer.collect_seq(self.iter().map(|(_, value)| value))
+    }
+}
+
+#[cfg(feature = "serde")]
+impl <'de, T: serde::Deserialize<'de>, const W: usize, const H: usize> serde::Deserialize<'de> for RectangleSphereGrid<T, W, H> {
+    /// Deserializes a grid from a sequence of values in the same order as `iter`.
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let values = Vec::<T>::deserialize(deserializer)?;
+        let length = values.len();
+
+        Self::from_vec(values)
+            .ok_or_else(|| serde::de::Error::invalid_length(length, &format!("a sequence of {} values", W * H).as_str()))
+    }
+}
+
 /// A point on a `RectangleSphereGrid`.
 ///
 /// # Constant Parameters
@@ -152,117 +456,221 @@ pub struct RectangleSpherePoint<const W: usize, const H: usize> {
 }
 
 impl <const W: usize, const H: usize> RectangleSpherePoint<W, H> {
+    /// Creates a new `RectangleSpherePoint`.
+    ///
+    /// Coordinates outside of the grid are wrapped in the same way as moving across the grid:
+    /// - `x` wraps around the sphere so `x` and `x + W` are the same point.
+    /// - A `y` in the range `H..2H` continues past the bottom pole and comes back up the other
+    ///   side of the sphere so `(x, H + n)` is the point `(x + W / 2, H - 1 - n)`.
+    /// - `y` repeats every `2H` rows as this passes over both poles so `(x, y + 2H)` is `(x, y)`.
+    ///
+    /// - `x` - The X position in the grid.
+    /// - `y` - The Y position in the grid.
     fn new(x: u32, y: u32) -> Self {
-        let x = (x + y / H as u32).rem_euclid(W as u32);
-        let y = y.rem_euclid(H as u32);
+        let (x, y) = rect_wrap(x as i64, y as i64, W as u32, H as u32);
 
         Self {
-            x,
-            y
+            x, y
+        }
+    }
+
+    /// Creates a new `RectangleSpherePoint` checking that the position lies on the grid.
+    ///
+    /// This returns an error if `x` is `W` or greater or `y` is `H` or greater instead of wrapping the coordinates.
+    ///
+    /// - `x` - The X position in the grid.
+    /// - `y` - The Y position in the grid.
+    pub fn try_new(x: u32, y: u32) -> Result<Self, OutOfBounds> {
+        if (x as usize) < W && (y as usize) < H {
+            Ok(Self::new(x, y))
+        } else {
+            Err(OutOfBounds)
         }
     }
+
+    /// Gets the position of the centre of this cell in 3D space.
+    ///
+    /// - `scale` - The scale of the 3D object.
+    pub fn cell_center(&self, scale: f64) -> (f64, f64, f64) {
+        Self::grid_position(self.x as f64 + 0.5, self.y as f64 + 0.5, scale)
+    }
+
+    /// Gets the position of a corner of this cell in 3D space.
+    ///
+    /// The corners are numbered clockwise starting from the corner with the smallest X and Y
+    /// coordinates:
+    /// - `0` - `(x, y)`
+    /// - `1` - `(x + 1, y)`
+    /// - `2` - `(x + 1, y + 1)`
+    /// - `3` - `(x, y + 1)`
+    ///
+    /// - `corner` - The corner to get.
+    /// - `scale` - The scale of the 3D object.
+    ///
+    /// # Panics
+    /// Panics if `corner` is greater than 3.
+    pub fn cell_corner(&self, corner: u8, scale: f64) -> (f64, f64, f64) {
+        let (x, y) = corner_offset(corner);
+
+        Self::grid_position(self.x as f64 + x, self.y as f64 + y, scale)
+    }
+
+    /// Gets the index of this point along a Hilbert curve covering the grid.
+    ///
+    /// The curve covers the smallest square with a power of two side length that contains the grid
+    /// so each point has a different index but the indices are not contiguous unless `W` and `H`
+    /// are the same power of two. Sorting points by this index keeps nearby points close together.
+    pub fn hilbert_index(&self) -> u64 {
+        hilbert_index(self.x, self.y, W.max(H).next_power_of_two() as u64)
+    }
+
+    /// Gets the shortest offset in grid cells from this point to another point.
+    ///
+    /// The horizontal offset wraps around the sphere so its magnitude is at most `W / 2`. When both
+    /// directions are the same distance the positive direction is chosen.
+    ///
+    /// The vertical offset is the difference between the rows and never crosses a pole. A path over
+    /// a pole can be shorter but it also moves by `W / 2` columns and reverses the direction of
+    /// `up` and `down`, so it cannot be described by a single offset.
+    ///
+    /// - `other` - The point to get the offset to.
+    pub fn shortest_offset(&self, other: &Self) -> (i32, i32) {
+        let width = W as i64;
+
+        let dx = (other.x as i64 - self.x as i64).rem_euclid(width);
+        let dx = if dx > width / 2 {
+            dx - width
+        } else {
+            dx
+        };
+
+        (dx as i32, other.y as i32 - self.y as i32)
+    }
+
+    /// Gets the position in 3D space of a continuous position on the grid.
+    ///
+    /// - `x` - The X position on the grid.
+    /// - `y` - The Y position on the grid.
+    /// - `scale` - The scale of the 3D object.
+    fn grid_position(x: f64, y: f64, scale: f64) -> (f64, f64, f64) {
+        rect_position(x, y, W as u32, H as u32, scale)
+    }
 }
 
 impl <const W: usize, const H: usize> GridPoint for RectangleSpherePoint<W, H> {
     fn up(&self) -> Self {
-        if self.x >= W as u32 / 2 {
-            if self.y == H as u32 - 1 {
-                Self {
-                    x: (self.x + W as u32 / 2).rem_euclid(W as u32),
-                    y: H as u32 - 1,
-                }
-            } else {
-                Self {
-                    x: self.x,
-                    y: self.y + 1,
-                }
-            }
-        } else {
-            if self.y == 0 {
-                Self {
-                    x: (self.x + W as u32 / 2).rem_euclid(W as u32),
-                    y: 0,
-                }
-            } else {
-                Self {
-                    x: self.x,
-                    y: self.y - 1,
-                }
-            }
+        let (x, y) = rect_up(self.x, self.y, W as u32, H as u32);
+
+        Self {
+            x, y
         }
     }
 
     fn down(&self) -> Self {
-        if self.x < W as u32 / 2 {
-            if self.y == H as u32 - 1 {
-                Self {
-                    x: (self.x + W as u32 / 2).rem_euclid(W as u32),
-                    y: H as u32 - 1,
-                }
-            } else {
-                Self {
-                    x: self.x,
-                    y: self.y + 1,
-                }
-            }
-        } else {
-            if self.y == 0 {
-                Self {
-                    x: (self.x + W as u32 / 2).rem_euclid(W as u32),
-                    y: 0,
-                }
-            } else {
-                Self {
-                    x: self.x,
-                    y: self.y - 1,
-                }
-            }
+        let (x, y) = rect_down(self.x, self.y, W as u32, H as u32);
+
+        Self {
+            x, y
         }
     }
 
     fn left(&self) -> Self {
         Self {
-            x: (self.x as i32 - 1).rem_euclid(W as i32) as u32,
+            x: if self.x == 0 { W as u32 - 1 } else { self.x - 1 },
             y: self.y
         }
     }
 
     fn right(&self) -> Self {
         Self {
-            x: (self.x + 1).rem_euclid(W as u32),
+            x: if self.x == W as u32 - 1 { 0 } else { self.x + 1 },
             y: self.y
         }
     }
 
-    fn position(&self, scale: f64) -> (f64, f64, f64) {
-        let (long, lat) = self.sphere_coordinates();
+    /// Gets the point that is diagonally above and to the left of this grid point.
+    ///
+    /// Away from the poles this is `self.up().left()`. When moving up crosses a pole the point
+    /// moves to the opposite side of the sphere, which mirrors left and right, so the diagonal is
+    /// `self.up().right()` instead. This keeps the diagonal on the same side of the line of
+    /// movement as `self.left()` so the eight diagonal neighbours remain physically adjacent.
+    fn up_left(&self) -> Self {
+        let up = self.up();
+
+        if up.y == self.y {
+            up.right()
+        } else {
+            up.left()
+        }
+    }
+
+    /// Gets the point that is diagonally above and to the right of this grid point.
+    ///
+    /// See `up_left` for how this is defined when moving up crosses a pole.
+    fn up_right(&self) -> Self {
+        let up = self.up();
+
+        if up.y == self.y {
+            up.left()
+        } else {
+            up.right()
+        }
+    }
+
+    /// Gets the point that is diagonally below and to the left of this grid point.
+    ///
+    /// See `up_left` for how this is defined when moving down crosses a pole.
+    fn down_left(&self) -> Self {
+        let down = self.down();
+
+        if down.y == self.y {
+            down.right()
+        } else {
+            down.left()
+        }
+    }
 
-        let y = scale * lat.sin();
-        let radius = scale * lat.cos();
+    /// Gets the point that is diagonally below and to the right of this grid point.
+    ///
+    /// See `up_left` for how this is defined when moving down crosses a pole.
+    fn down_right(&self) -> Self {
+        let down = self.down();
 
-        let x = radius * long.sin();
-        let z = radius * long.cos();
+        if down.y == self.y {
+            down.left()
+        } else {
+            down.right()
+        }
+    }
 
-        (x, y, z)
+    /// Gets the position of the point in 3D space.
+    ///
+    /// This is the position of the corner of the cell with the smallest X and Y coordinates, the
+    /// same as `cell_corner(0, scale)`. Use `cell_center` to get the centre of the cell.
+    ///
+    /// - `scale` - The scale of the 3D object.
+    fn position(&self, scale: f64) -> (f64, f64, f64) {
+        Self::grid_position(self.x as f64, self.y as f64, scale)
     }
 }
 
 impl <const W: usize, const H: usize> SpherePoint for RectangleSpherePoint<W, H> {
+    fn center_position(&self, scale: f64) -> (f64, f64, f64) {
+        self.cell_center(scale)
+    }
+
     fn from_geographic(latitude: f64, longitude: f64) -> Self {
         let latitude = -latitude;
 
-        let x = ((longitude / (PI * 2.0) * W as f64) as i32).rem_euclid(W as i32) as u32;
+        let x = ((longitude / (PI * 2.0) * W as f64).floor() as i64).rem_euclid(W as i64) as u32;
         let y = (latitude + PI / 2.0) / PI;
 
-        let y = ((2 * (y.ceil() as i32).rem_euclid(2) - 1)
-            * ((y * H as f64) as i32).rem_euclid(H as i32)
-            + H as i32 * (y.floor() as i32).rem_euclid(2)) as u32;
+        let y = (2 * (y.ceil() as i64).rem_euclid(2) - 1)
+            * ((y * H as f64) as i64).rem_euclid(H as i64)
+            + H as i64 * (y.floor() as i64).rem_euclid(2);
 
-        let y = if y == 100 {
-            99
-        } else {
-            y
-        };
+        // The south pole is on the edge of the last row.
+        let y = y.min(H as i64 - 1) as u32;
 
         Self {
             x, y
@@ -276,42 +684,525 @@ impl <const W: usize, const H: usize> SpherePoint for RectangleSpherePoint<W, H>
     fn longitude(&self) -> f64 {
         self.x as f64 / W as f64 * PI * 2.0
     }
+
+    fn center_sphere_coordinates(&self) -> (f64, f64) {
+        let longitude = (self.x as f64 + 0.5) / W as f64 * PI * 2.0;
+        let latitude = -((self.y as f64 + 0.5) / H as f64 * PI - PI / 2.0);
+
+        (longitude, latitude)
+    }
 }
 
-/// A grid that wraps a cube around a sphere in order to determine grid positions.
-///
-/// # Type Parameters.
-/// - `T` - The type of element stored in each grid cell.
+/// A point on an equirectangular sphere grid with dimensions that are only known at runtime.
 ///
-/// # Constant Parameters
-/// - `S` - The size of each side of each face.
-#[derive(Debug, Clone, PartialEq, Eq, Hash, Default)]
-pub struct CubeSphereGrid<T, const S: usize> {
-    top: HeapArray2D<T, S, S>,
-    left: HeapArray2D<T, S, S>,
-    front: HeapArray2D<T, S, S>,
-    right: HeapArray2D<T, S, S>,
-    back: HeapArray2D<T, S, S>,
-    bottom: HeapArray2D<T, S, S>,
+/// The coordinates are public so that they can be computed directly. Coordinates outside of the
+/// grid are treated in the same way as `RectangleSpherePoint` so `x` wraps around the sphere and
+/// `y` continues over the poles. Use `normalize` to get the equivalent point with coordinates on
+/// the grid. Points are equal if they normalize to the same cell.
+#[derive(Debug, Clone, Copy)]
+pub struct DynRectangleSpherePoint {
+    /// The X position.
+    pub x: i64,
+    /// The Y position.
+    pub y: i64,
+    /// The width of the grid.
+    width: u32,
+    /// The height of the grid.
+    height: u32,
 }
 
-impl <T: Debug, const S: usize> SurfaceGrid<T> for CubeSphereGrid<T, S> {
-    type Point = CubeSpherePoint<S>;
+impl DynRectangleSpherePoint {
+    /// Creates a new `DynRectangleSpherePoint`.
+    ///
+    /// - `x` - The X position in the grid.
+    /// - `y` - The Y position in the grid.
+    /// - `width` - The width of the grid.
+    /// - `height` - The height of the grid.
+    ///
+    /// # Panics
+    /// Panics if `width` or `height` is 0.
+    pub fn new(x: i64, y: i64, width: u32, height: u32) -> Self {
+        assert!(width > 0 && height > 0, "Invalid grid size {}x{}", width, height);
 
-    fn from_fn<F: FnMut(&Self::Point) -> T>(mut f: F) -> Self {
         Self {
-            top: HeapArray2D::from_fn(|y, x| f(&CubeSpherePoint::new(CubeFace::Top, x as u16, y as u16))),
-            left: HeapArray2D::from_fn(|y, x| f(&CubeSpherePoint::new(CubeFace::Left, x as u16, y as u16))),
-            front: HeapArray2D::from_fn(|y, x| f(&CubeSpherePoint::new(CubeFace::Front, x as u16, y as u16))),
-            right: HeapArray2D::from_fn(|y, x| f(&CubeSpherePoint::new(CubeFace::Right, x as u16, y as u16))),
-            back: HeapArray2D::from_fn(|y, x| f(&CubeSpherePoint::new(CubeFace::Back, x as u16, y as u16))),
-            bottom: HeapArray2D::from_fn(|y, x| f(&CubeSpherePoint::new(CubeFace::Bottom, x as u16, y as u16))),
+            x, y, width, height
         }
     }
 
-    fn from_fn_par<F: Fn(&Self::Point) -> T + Send + Sync>(f: F) -> Self where T: Send + Sync {
-        Self {
-            top: HeapArray2D::from_fn_par(|y, x| f(&CubeSpherePoint::new(CubeFace::Top, x as u16, y as u16))),
+    /// Gets the width of the grid that this point lies on.
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    /// Gets the height of the grid that this point lies on.
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    /// Gets the same point with its coordinates wrapped into the grid.
+    ///
+    /// The X coordinate of the result is in `0..width` and the Y coordinate is in `0..height`.
+    pub fn normalize(&self) -> Self {
+        let (x, y) = rect_wrap(self.x, self.y, self.width, self.height);
+
+        Self {
+            x: x as i64,
+            y: y as i64,
+            ..*self
+        }
+    }
+
+    /// Gets the normalized coordinates of this point.
+    fn coordinates(&self) -> (u32, u32) {
+        rect_wrap(self.x, self.y, self.width, self.height)
+    }
+}
+
+impl PartialEq for DynRectangleSpherePoint {
+    fn eq(&self, other: &Self) -> bool {
+        self.width == other.width && self.height == other.height && self.coordinates() == other.coordinates()
+    }
+}
+
+impl Eq for DynRectangleSpherePoint {}
+
+impl Hash for DynRectangleSpherePoint {
+    fn hash<A: Hasher>(&self, state: &mut A) {
+        self.coordinates().hash(state);
+        self.width.hash(state);
+        self.height.hash(state);
+    }
+}
+
+impl GridPoint for DynRectangleSpherePoint {
+    fn up(&self) -> Self {
+        let (x, y) = self.coordinates();
+        let (x, y) = rect_up(x, y, self.width, self.height);
+
+        Self::new(x as i64, y as i64, self.width, self.height)
+    }
+
+    fn down(&self) -> Self {
+        let (x, y) = self.coordinates();
+        let (x, y) = rect_down(x, y, self.width, self.height);
+
+        Self::new(x as i64, y as i64, self.width, self.height)
+    }
+
+    fn left(&self) -> Self {
+        let (x, y) = self.coordinates();
+
+        Self::new(x as i64 - 1, y as i64, self.width, self.height).normalize()
+    }
+
+    fn right(&self) -> Self {
+        let (x, y) = self.coordinates();
+
+        Self::new(x as i64 + 1, y as i64, self.width, self.height).normalize()
+    }
+
+    /// Gets the point that is diagonally above and to the left of this grid point.
+    ///
+    /// This is defined in the same way as `RectangleSpherePoint::up_left` so the diagonals stay
+    /// adjacent when moving up crosses a pole.
+    fn up_left(&self) -> Self {
+        let up = self.up();
+
+        if up.y == self.normalize().y {
+            up.right()
+        } else {
+            up.left()
+        }
+    }
+
+    /// Gets the point that is diagonally above and to the right of this grid point.
+    ///
+    /// See `up_left` for how this is defined when moving up crosses a pole.
+    fn up_right(&self) -> Self {
+        let up = self.up();
+
+        if up.y == self.normalize().y {
+            up.left()
+        } else {
+            up.right()
+        }
+    }
+
+    /// Gets the point that is diagonally below and to the left of this grid point.
+    ///
+    /// See `up_left` for how this is defined when moving down crosses a pole.
+    fn down_left(&self) -> Self {
+        let down = self.down();
+
+        if down.y == self.normalize().y {
+            down.right()
+        } else {
+            down.left()
+        }
+    }
+
+    /// Gets the point that is diagonally below and to the right of this grid point.
+    ///
+    /// See `up_left` for how this is defined when moving down crosses a pole.
+    fn down_right(&self) -> Self {
+        let down = self.down();
+
+        if down.y == self.normalize().y {
+            down.left()
+        } else {
+            down.right()
+        }
+    }
+
+    /// Gets the position of the point in 3D space.
+    ///
+    /// This is the position of the corner of the cell with the smallest X and Y coordinates in the
+    /// same way as `RectangleSpherePoint::position`.
+    ///
+    /// - `scale` - The scale of the 3D object.
+    fn position(&self, scale: f64) -> (f64, f64, f64) {
+        let (x, y) = self.coordinates();
+
+        rect_position(x as f64, y as f64, self.width, self.height, scale)
+    }
+}
+
+/// A grid that wraps a cube around a sphere in order to determine grid positions.
+///
+/// # Type Parameters.
+/// - `T` - The type of element stored in each grid cell.
+///
+/// # Constant Parameters
+/// - `S` - The size of each side of each face.
+///
+/// # Cloning
+/// Each face is stored in a single heap allocation so cloning a grid where `T: Copy` copies each
+/// face in bulk rather than element by element. `clone_from` copies the values into the existing
+/// faces rather than allocating new ones.
+///
+/// # Iteration Order
+/// `iter`, `points` and `into_iter` all visit the faces in the order top, left, front, right,
+/// back and bottom. Each face is visited in row-major order, starting at `(0, 0)` and increasing
+/// `x` before `y`. This order is stable.
+///
+/// # Thread Safety
+/// Each face is kept in its own `HeapArray2D`, so the grid is `Send` when `T` is `Send` and `Sync`
+/// when `T` is `Sync`.
+#[derive(Debug, PartialEq, Eq, Hash, Default)]
+pub struct CubeSphereGrid<T, const S: usize> {
+    top: HeapArray2D<T, S, S>,
+    left: HeapArray2D<T, S, S>,
+    front: HeapArray2D<T, S, S>,
+    right: HeapArray2D<T, S, S>,
+    back: HeapArray2D<T, S, S>,
+    bottom: HeapArray2D<T, S, S>,
+}
+
+impl <T: Clone, const S: usize> Clone for CubeSphereGrid<T, S> {
+    fn clone(&self) -> Self {
+        Self {
+            top: self.top.clone(),
+            left: self.left.clone(),
+            front: self.front.clone(),
+            right: self.right.clone(),
+            back: self.back.clone(),
+            bottom: self.bottom.clone(),
+        }
+    }
+
+    /// Copies the values of another grid into this grid without allocating.
+    fn clone_from(&mut self, source: &Self) {
+        for face in FACE_ORDER {
+            self.face_slice_mut(face).clone_from_slice(source.face_slice(face));
+        }
+    }
+}
+
+impl <T, const S: usize> CubeSphereGrid<T, S> {
+    /// Gets the values of the cells on a face in the same order as the points of the grid.
+    ///
+    /// Each face is `S` by `S` cells in row-major order and the faces are stored separately so there is
+    /// no single slice covering the whole grid. A `StoredGrid` with the layout of a `CubeSphereGrid`
+    /// stores every cell in one slice instead.
+    ///
+    /// - `face` - The face to get the values of.
+    pub fn face_slice(&self, face: CubeFace) -> &[T] {
+        match face {
+            CubeFace::Top => self.top.as_flattened(),
+            CubeFace::Left => self.left.as_flattened(),
+            CubeFace::Front => self.front.as_flattened(),
+            CubeFace::Right => self.right.as_flattened(),
+            CubeFace::Back => self.back.as_flattened(),
+            CubeFace::Bottom => self.bottom.as_flattened(),
+        }
+    }
+
+    /// Gets mutable references to the values of the cells on a face in the same order as the
+    /// points of the grid.
+    ///
+    /// - `face` - The face to get the values of.
+    pub fn face_slice_mut(&mut self, face: CubeFace) -> &mut [T] {
+        match face {
+            CubeFace::Top => self.top.as_flattened_mut(),
+            CubeFace::Left => self.left.as_flattened_mut(),
+            CubeFace::Front => self.front.as_flattened_mut(),
+            CubeFace::Right => self.right.as_flattened_mut(),
+            CubeFace::Back => self.back.as_flattened_mut(),
+            CubeFace::Bottom => self.bottom.as_flattened_mut(),
+        }
+    }
+
+    /// Gets the values of the cells on a face as bytes in the same order as `face_slice`.
+    ///
+    /// - `face` - The face to get the values of.
+    #[cfg(feature = "bytemuck")]
+    pub fn face_bytes(&self, face: CubeFace) -> &[u8] where T: bytemuck::Pod {
+        bytemuck::cast_slice(self.face_slice(face))
+    }
+
+    /// Gets the values of the cells on a face as mutable bytes in the same order as `face_slice`.
+    ///
+    /// - `face` - The face to get the values of.
+    #[cfg(feature = "bytemuck")]
+    pub fn face_bytes_mut(&mut self, face: CubeFace) -> &mut [u8] where T: bytemuck::Pod {
+        bytemuck::cast_slice_mut(self.face_slice_mut(face))
+    }
+
+    /// Creates a copy of this grid as if the cube had been physically rotated about an axis.
+    ///
+    /// Rotations follow the right hand rule so a single quarter turn about the Y axis moves the
+    /// contents of the front face onto the right face.
+    ///
+    /// - `axis` - The axis to rotate around.
+    /// - `quarter_turns` - The number of 90 degree turns to rotate by.
+    pub fn rotate_faces(&self, axis: Axis, quarter_turns: u8) -> Self where T: Clone {
+        // Each destination cell takes the value from the cell that rotates onto it.
+        let inverse_turns = (4 - quarter_turns % 4) % 4;
+
+        Self::from_fn(|point| {
+            let (mut x, mut y, mut z) = point.cube_centre();
+
+            for _ in 0..inverse_turns {
+                (x, y, z) = axis.rotate(x, y, z);
+            }
+
+            self[CubeSpherePoint::from_cube_centre(x, y, z)].clone()
+        })
+    }
+
+    /// Sets every cell with a centre inside a polygon on the sphere to a value.
+    ///
+    /// The edges of the polygon are great circle arcs between consecutive vertices with the last
+    /// vertex joined back to the first, so the edges between vertices at the same latitude curve
+    /// towards the pole. The vertices may be given in either order and the polygon may cross the
+    /// antimeridian or surround a pole but it should fit within the hemisphere centred on the
+    /// average of its vertices.
+    ///
+    /// - `vertices` - The latitude and longitude of each vertex in radians.
+    /// - `value` - The value to set the cells to.
+    pub fn fill_polygon(&mut self, vertices: &[(f64, f64)], value: T) where T: Clone {
+        let vertices: Vec<_> = vertices.iter()
+            .map(|vertex| geographic_position(*vertex))
+            .collect();
+
+        let centre = vertices.iter()
+            .fold((0.0, 0.0, 0.0), |(x, y, z), (vx, vy, vz)| (x + vx, y + vy, z + vz));
+
+        let points: Vec<_> = self.points()
+            .filter(|point| in_spherical_polygon(point.cell_center(1.0), &vertices, centre))
+            .collect();
+
+        for point in points {
+            self[point] = value.clone();
+        }
+    }
+
+    /// Copies the values in this grid into the layout of a cube map texture.
+    ///
+    /// The values are in the order of the layers of a cube map in wgpu, Vulkan, Direct3D and
+    /// OpenGL: positive X, negative X, positive Y, negative Y, positive Z and negative Z. Each
+    /// layer is `S` by `S` texels in row-major order and is oriented so that sampling the cube map
+    /// with the direction of a cell's `cell_center` gives the value of that cell.
+    pub fn to_cube_map_layers(&self) -> Vec<T> where T: Clone {
+        (0..6).cartesian_product(0..S)
+            .cartesian_product(0..S)
+            .map(|((layer, row), column)| self[CubeSpherePoint::from_cube_map(layer, column, row)].clone())
+            .collect()
+    }
+
+    /// Creates a cube map texture containing the values in this grid.
+    ///
+    /// The texture has the layout described by `to_cube_map_layers` so it can be sampled using the
+    /// same directions as the positions of the cells. It has one mip level and can be used as a
+    /// texture binding or copied to.
+    ///
+    /// - `device` - The device to create the texture on.
+    /// - `queue` - The queue used to copy the values into the texture.
+    /// - `format` - The format of the texture, which must have texels the same size as `T`.
+    ///
+    /// # Panics
+    /// Panics if the size of a texel of `format` is not the size of `T`.
+    #[cfg(feature = "wgpu")]
+    pub fn upload_cubemap(&self, device: &wgpu::Device, queue: &wgpu::Queue, format: wgpu::TextureFormat) -> wgpu::Texture where T: bytemuck::Pod {
+        let texel_size = std::mem::size_of::<T>() as u32;
+
+        assert_eq!(format.block_size(None), Some(texel_size), "the texture format does not match the size of the values");
+
+        let size = wgpu::Extent3d {
+            width: S as u32,
+            height: S as u32,
+            depth_or_array_layers: 6,
+        };
+
+        let texture = device.create_texture(&wgpu::TextureDescriptor {
+            label: None,
+            size,
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format,
+            usage: wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_DST,
+            view_formats: &[],
+        });
+
+        queue.write_texture(
+            wgpu::ImageCopyTexture {
+                texture: &texture,
+                mip_level: 0,
+                origin: wgpu::Origin3d::ZERO,
+                aspect: wgpu::TextureAspect::All,
+            },
+            bytemuck::cast_slice(&self.to_cube_map_layers()),
+            wgpu::ImageDataLayout {
+                offset: 0,
+                bytes_per_row: Some(S as u32 * texel_size),
+                rows_per_image: Some(S as u32),
+            },
+            size
+        );
+
+        texture
+    }
+}
+
+impl <const S: usize> CubeSphereGrid<f64, S> {
+    /// Creates a grid from the sum of a set of real spherical harmonics.
+    ///
+    /// Each term is a real spherical harmonic of degree `l` and order `m` multiplied by a
+    /// coefficient and evaluated at the centre of each cell. The harmonics are orthonormal over
+    /// the unit sphere and do not include the Condon-Shortley phase. Positive orders vary with the
+    /// cosine of the longitude and negative orders with the sine.
+    ///
+    /// - `coefficients` - The degree, order and coefficient of each term.
+    ///
+    /// # Panics
+    /// Panics if a degree is negative or the magnitude of an order is greater than its degree.
+    pub fn from_spherical_harmonics(coefficients: &[(i32, i32, f64)]) -> Self {
+        Self::from_fn(|point| spherical_harmonic_sum(coefficients, point.cell_center(1.0)))
+    }
+
+    /// Computes the discrete Laplacian (Laplace-Beltrami operator) of the values in this grid.
+    ///
+    /// Each face is a gnomonic projection of the sphere, so the Laplacian is written in the
+    /// coordinates of the face, where the grid lines are not perpendicular away from the centre of
+    /// the face. The derivatives are found from the values in the cells given by `up`, `down`,
+    /// `left` and `right` and in the cells diagonal to them.
+    ///
+    /// # Limitations
+    /// The grid joins the faces in a way that does not match the positions of the cells, so the
+    /// cells that `up`, `down`, `left` and `right` give across the edge of a face are not next to
+    /// it on the sphere. The values in the cells along the edges of the faces are therefore not
+    /// accurate. The values in all other cells are.
+    ///
+    /// - `scale` - The radius of the sphere.
+    pub fn laplacian(&self, scale: f64) -> Self {
+        let spacing = 2.0 / S as f64;
+
+        Self::from_fn(|point| {
+            // The centre of the cell on a face with a side length of 2, measured in the directions
+            // of `right` and `down`. Moving right on the back face decreases X.
+            let a = (point.x as f64 * 2.0 + 1.0) / S as f64 - 1.0;
+            let a = if point.face == CubeFace::Back { -a } else { a };
+            let b = (point.y as f64 * 2.0 + 1.0) / S as f64 - 1.0;
+
+            let value = self[*point];
+            let (left, right) = (self[point.left()], self[point.right()]);
+            let (up, down) = (self[point.up()], self[point.down()]);
+            let corners = self[point.down().right()] - self[point.up().right()]
+                - self[point.down().left()] + self[point.up().left()];
+
+            let d_aa = (left - 2.0 * value + right) / (spacing * spacing);
+            let d_bb = (up - 2.0 * value + down) / (spacing * spacing);
+            let d_ab = corners / (4.0 * spacing * spacing);
+            let d_a = (right - left) / (2.0 * spacing);
+            let d_b = (down - up) / (2.0 * spacing);
+
+            // The Laplace-Beltrami operator of the unit sphere in gnomonic coordinates.
+            let laplacian = (1.0 + a * a) * d_aa + 2.0 * a * b * d_ab + (1.0 + b * b) * d_bb
+                + 2.0 * (a * d_a + b * d_b);
+
+            (1.0 + a * a + b * b) * laplacian / (scale * scale)
+        })
+    }
+
+    /// Averages each value with the value in the cell reflected across the equator.
+    ///
+    /// The result is symmetric between the northern and southern hemispheres.
+    pub fn symmetrize_equator(&self) -> Self {
+        Self::from_fn(|point| {
+            let (x, y, z) = point.cube_centre();
+
+            (self[*point] + self[CubeSpherePoint::from_cube_centre(x, -y, z)]) / 2.0
+        })
+    }
+
+    /// Averages each value with the values in the cells found by rotating it about the poles.
+    ///
+    /// The result has `fold`-fold rotational symmetry so it is unchanged by turning it about the
+    /// poles by any multiple of `1 / fold` of a full turn. Only quarter turns map the cube onto
+    /// itself so the fold must be 1, 2 or 4.
+    ///
+    /// - `fold` - The number of rotations to average over.
+    ///
+    /// # Panics
+    /// Panics if `fold` is not 1, 2 or 4.
+    pub fn symmetrize_longitudinal(&self, fold: u32) -> Self {
+        assert!(matches!(fold, 1 | 2 | 4), "the fold must be 1, 2 or 4");
+
+        Self::from_fn(|point| {
+            let (mut x, y, mut z) = point.cube_centre();
+            let mut sum = 0.0;
+
+            for _ in 0..fold {
+                sum += self[CubeSpherePoint::from_cube_centre(x, y, z)];
+
+                for _ in 0..4 / fold {
+                    (x, z) = (z, -x);
+                }
+            }
+
+            sum / fold as f64
+        })
+    }
+}
+
+impl <T, const S: usize> SurfaceGrid<T> for CubeSphereGrid<T, S> {
+    type Point = CubeSpherePoint<S>;
+
+    fn from_fn<F: FnMut(&Self::Point) -> T>(mut f: F) -> Self {
+        Self {
+            top: HeapArray2D::from_fn(|y, x| f(&CubeSpherePoint::new(CubeFace::Top, x as u16, y as u16))),
+            left: HeapArray2D::from_fn(|y, x| f(&CubeSpherePoint::new(CubeFace::Left, x as u16, y as u16))),
+            front: HeapArray2D::from_fn(|y, x| f(&CubeSpherePoint::new(CubeFace::Front, x as u16, y as u16))),
+            right: HeapArray2D::from_fn(|y, x| f(&CubeSpherePoint::new(CubeFace::Right, x as u16, y as u16))),
+            back: HeapArray2D::from_fn(|y, x| f(&CubeSpherePoint::new(CubeFace::Back, x as u16, y as u16))),
+            bottom: HeapArray2D::from_fn(|y, x| f(&CubeSpherePoint::new(CubeFace::Bottom, x as u16, y as u16))),
+        }
+    }
+
+    fn from_fn_par<F: Fn(&Self::Point) -> T + Send + Sync>(f: F) -> Self where T: Send + Sync {
+        Self {
+            top: HeapArray2D::from_fn_par(|y, x| f(&CubeSpherePoint::new(CubeFace::Top, x as u16, y as u16))),
             left: HeapArray2D::from_fn_par(|y, x| f(&CubeSpherePoint::new(CubeFace::Left, x as u16, y as u16))),
             front: HeapArray2D::from_fn_par(|y, x| f(&CubeSpherePoint::new(CubeFace::Front, x as u16, y as u16))),
             right: HeapArray2D::from_fn_par(|y, x| f(&CubeSpherePoint::new(CubeFace::Right, x as u16, y as u16))),
@@ -320,18 +1211,41 @@ impl <T: Debug, const S: usize> SurfaceGrid<T> for CubeSphereGrid<T, S> {
         }
     }
 
+    fn try_from_fn<X, F: FnMut(&Self::Point) -> Result<T, X>>(mut f: F) -> Result<Self, X> {
+        let values = (0..6 * S * S)
+            .map(CubeSpherePoint::from_index)
+            .map(|point| f(&point))
+            .collect::<Result<_, _>>()?;
+
+        // The values are in the same order as `points`.
+        Ok(Self::from_vec(values).unwrap())
+    }
+
+    fn try_from_fn_par<X: Send, F: Fn(&Self::Point) -> Result<T, X> + Send + Sync>(f: F) -> Result<Self, X> where T: Send + Sync {
+        let values = (0..6 * S * S).into_par_iter()
+            .map(CubeSpherePoint::from_index)
+            .map(|point| f(&point))
+            .collect::<Result<_, _>>()?;
+
+        Ok(Self::from_vec(values).unwrap())
+    }
+
+    fn from_vec(values: Vec<T>) -> Option<Self> {
+        if values.len() != 6 * S * S {
+            return None;
+        }
+
+        // `from_fn` visits the points in the same order as `points`.
+        let mut values = values.into_iter();
+
+        Some(Self::from_fn(|_| values.next().unwrap()))
+    }
+
     fn set_from_fn<F: FnMut(&Self::Point) -> T>(&mut self, mut f: F) {
-        [
-            CubeFace::Top,
-            CubeFace::Left,
-            CubeFace::Front,
-            CubeFace::Right,
-            CubeFace::Back,
-            CubeFace::Bottom,
-        ].into_iter()
+        FACE_ORDER.into_iter()
             .cartesian_product(0..S)
             .cartesian_product(0..S)
-            .map(|((face, x), y)| CubeSpherePoint::new(face, x as u16, y as u16))
+            .map(|((face, y), x)| CubeSpherePoint::new(face, x as u16, y as u16))
             .map(|point| (point, f(&point)))
             .for_each(|(point, value)| self[point] = value)
     }
@@ -352,51 +1266,80 @@ impl <T: Debug, const S: usize> SurfaceGrid<T> for CubeSphereGrid<T, S> {
                 CubeFace::Right => &mut self.right,
                 CubeFace::Top => &mut self.top,
                 CubeFace::Bottom => &mut self.bottom,
-            }.iter_mut().enumerate().par_bridge().for_each(|(y, subarray)| for x in 0..S {
+            }.par_iter_mut().enumerate().for_each(|(y, subarray)| for (x, value) in subarray.iter_mut().enumerate() {
                 let point = CubeSpherePoint::new(face, x as u16, y as u16);
 
-                subarray[x] = f(&point);
+                *value = f(&point);
             });
         }
     }
 
-    fn iter<'a>(&'a self) -> impl Iterator<Item = (Self::Point, &'a T)> where T: 'a {
+    fn iter<'a>(&'a self) -> impl ExactSizeIterator<Item = (Self::Point, &'a T)> + DoubleEndedIterator where T: 'a {
         self.points()
             .map(|point| (point, &self[point]))
     }
 
-    fn par_iter<'a>(&'a self) -> impl ParallelIterator<Item = (Self::Point, &'a T)> where T: 'a + Send + Sync {
+    fn par_iter<'a>(&'a self) -> impl IndexedParallelIterator<Item = (Self::Point, &'a T)> where T: 'a + Send + Sync {
         self.par_points()
             .map(|point| (point, &self[point]))
     }
 
-    fn points(&self) -> impl Iterator<Item = Self::Point> {
-        [
-            CubeFace::Top,
-            CubeFace::Left,
-            CubeFace::Front,
-            CubeFace::Right,
-            CubeFace::Back,
-            CubeFace::Bottom,
-        ].into_iter()
-            .cartesian_product(0..S)
-            .cartesian_product(0..S)
-            .map(|((face, x), y)| CubeSpherePoint::new(face, x as u16, y as u16))
+    fn par_iter_mut<'a>(&'a mut self) -> impl IndexedParallelIterator<Item = (Self::Point, &'a mut T)> where T: 'a + Send + Sync {
+        // The faces are chained in the order of `FACE_ORDER` so that the iterator stays indexed.
+        let face = |data: &'a mut HeapArray2D<T, S, S>, face| data.as_flattened_mut()
+            .par_iter_mut()
+            .enumerate()
+            .map(move |(i, value)| (CubeSpherePoint::new(face, (i % S) as u16, (i / S) as u16), value));
+
+        face(&mut self.top, CubeFace::Top)
+            .chain(face(&mut self.left, CubeFace::Left))
+            .chain(face(&mut self.front, CubeFace::Front))
+            .chain(face(&mut self.right, CubeFace::Right))
+            .chain(face(&mut self.back, CubeFace::Back))
+            .chain(face(&mut self.bottom, CubeFace::Bottom))
     }
 
-    fn par_points(&self) -> impl ParallelIterator<Item = Self::Point> {
-        [
-            CubeFace::Top,
-            CubeFace::Left,
-            CubeFace::Front,
-            CubeFace::Right,
-            CubeFace::Back,
-            CubeFace::Bottom,
-        ].into_iter()
-            .cartesian_product(0..S)
-            .cartesian_product(0..S)
-            .par_bridge()
-            .map(|((face, x), y)| CubeSpherePoint::new(face, x as u16, y as u16))
+    fn points(&self) -> impl ExactSizeIterator<Item = Self::Point> + DoubleEndedIterator {
+        (0..6 * S * S).map(CubeSpherePoint::from_index)
+    }
+
+    fn par_points(&self) -> impl IndexedParallelIterator<Item = Self::Point> {
+        (0..6 * S * S).into_par_iter()
+            .map(CubeSpherePoint::from_index)
+    }
+
+    fn par_map_to_vec<U: Send, F: Fn(&Self::Point, &T) -> U + Send + Sync>(&self, f: F) -> Vec<U> where T: Send + Sync {
+        let mut result = Vec::with_capacity(6 * S * S);
+
+        (0..6 * S * S).into_par_iter()
+            .map(|i| {
+                let point = CubeSpherePoint::new(FACE_ORDER[i / (S * S)], (i % S) as u16, (i / S % S) as u16);
+
+                f(&point, &self[point])
+            })
+            .collect_into_vec(&mut result);
+
+        result
+    }
+
+    fn to_index(&self, point: &Self::Point) -> usize {
+        point.index()
+    }
+
+    fn at(&self, point: &Self::Point) -> &T {
+        &self[point]
+    }
+
+    fn at_mut(&mut self, point: &Self::Point) -> &mut T {
+        &mut self[point]
+    }
+
+    fn surface_area(&self, scale: f64) -> f64 {
+        4.0 * PI * scale * scale
+    }
+
+    fn topology(&self) -> Topology {
+        Topology::uniform(Surface::Sphere, 6, S, S)
     }
 }
 
@@ -428,15 +1371,40 @@ impl <T, const S: usize> IndexMut<CubeSpherePoint<S>> for CubeSphereGrid<T, S> {
     }
 }
 
-impl <T, const S: usize> IntoIterator for CubeSphereGrid<T, S> {
-    type Item = (CubeSpherePoint<S>, T);
+impl <T, const S: usize> Index<&CubeSpherePoint<S>> for CubeSphereGrid<T, S> {
+    type Output = T;
 
-    type IntoIter = vec::IntoIter<Self::Item>;
+    fn index(&self, index: &CubeSpherePoint<S>) -> &Self::Output {
+        &self[*index]
+    }
+}
 
-    fn into_iter(self) -> Self::IntoIter {
-        let mut data: Vec<_> = self.top.into_iter()
-            .enumerate()
-            .flat_map(|(y, subarray)| subarray.into_iter()
+impl <T, const S: usize> IndexMut<&CubeSpherePoint<S>> for CubeSphereGrid<T, S> {
+    fn index_mut(&mut self, index: &CubeSpherePoint<S>) -> &mut Self::Output {
+        &mut self[*index]
+    }
+}
+
+impl <T, const S: usize> FromIterator<T> for CubeSphereGrid<T, S> {
+    /// Creates a grid from the values of its cells in the same order as `points`.
+    ///
+    /// # Panics
+    /// Panics if the iterator does not yield exactly one value for each cell in the grid.
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        Self::from_vec(iter.into_iter().collect())
+            .expect("the iterator must yield one value for each cell in the grid")
+    }
+}
+
+impl <T, const S: usize> IntoIterator for CubeSphereGrid<T, S> {
+    type Item = (CubeSpherePoint<S>, T);
+
+    type IntoIter = vec::IntoIter<Self::Item>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        let mut data: Vec<_> = self.top.into_iter()
+            .enumerate()
+            .flat_map(|(y, subarray)| subarray.into_iter()
                         .enumerate()
                         .map(move |(x, value)| (CubeSpherePoint::new(CubeFace::Top, x as u16, y as u16), value))
                       )
@@ -477,6 +1445,241 @@ impl <T, const S: usize> IntoIterator for CubeSphereGrid<T, S> {
     }
 }
 
+#[cfg(feature = "serde")]
+impl <T: serde::Serialize, const S: usize> serde::Serialize for CubeSphereGrid<T, S> {
+    /// Serializes the values in this grid as a sequence in the same order as `iter`.
+    fn serialize<R: serde::Serializer>(&self, serializer: R) -> Result<R::Ok, R::Error> {
+        serializer.collect_seq(self.iter().map(|(_, value)| value))
+    }
+}
+
+#[cfg(feature = "serde")]
+impl <'de, T: serde::Deserialize<'de>, const S: usize> serde::Deserialize<'de> for CubeSphereGrid<T, S> {
+    /// Deserializes a grid from a sequence of values in the same order as `iter`.
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let values = Vec::<T>::deserialize(deserializer)?;
+        let length = values.len();
+
+        Self::from_vec(values)
+            .ok_or_else(|| serde::de::Error::invalid_length(length, &format!("a sequence of {} values", 6 * S * S).as_str()))
+    }
+}
+
+/// A grid of booleans for a sphere based on a cube that stores each cell as a single bit.
+///
+/// This uses an eighth of the memory of a `CubeSphereGrid<bool, S>` which makes it suitable for
+/// large cellular automata. The cells are packed into 64 bit words in the same order as
+/// `CubeSphereGrid::points` so a word holds 64 consecutive cells.
+///
+/// A bit cannot be borrowed mutably so this does not implement `SurfaceGrid`. Instead cells are
+/// read with `get` or by indexing and written with `set`, and the neighbour methods of
+/// `SurfaceGrid` that automata use are provided directly.
+///
+/// # Constant Parameters
+/// - `S` - The size of each side of each face.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct BitCubeSphereGrid<const S: usize> {
+    /// The packed cells.
+    words: Vec<u64>,
+}
+
+impl <const S: usize> BitCubeSphereGrid<S> {
+    /// The number of cells in the grid.
+    const CELLS: usize = 6 * S * S;
+
+    /// Creates a new grid with every cell set to `false`.
+    pub fn new() -> Self {
+        Self {
+            words: vec![0; Self::CELLS.div_ceil(64)],
+        }
+    }
+
+    /// Creates a new grid by calling the specified function for each point in the grid.
+    ///
+    /// The function is called for each point in the same order as `points`.
+    ///
+    /// - `f` - The function to apply.
+    pub fn from_fn<F: FnMut(&CubeSpherePoint<S>) -> bool>(mut f: F) -> Self {
+        let mut grid = Self::new();
+
+        for (i, point) in grid.points().enumerate() {
+            if f(&point) {
+                grid.words[i / 64] |= 1 << (i % 64);
+            }
+        }
+
+        grid
+    }
+
+    /// Creates a new grid by calling the specified function in parallel for each point in the
+    /// grid.
+    ///
+    /// - `f` - The function to apply.
+    pub fn from_fn_par<F: Fn(&CubeSpherePoint<S>) -> bool + Send + Sync>(f: F) -> Self {
+        let mut grid = Self::new();
+
+        // Each word is built separately so no two threads write to the same word.
+        grid.words.par_iter_mut().enumerate().for_each(|(word_index, word)| {
+            let start = word_index * 64;
+
+            for i in start..(start + 64).min(Self::CELLS) {
+                if f(&CubeSpherePoint::from_index(i)) {
+                    *word |= 1 << (i - start);
+                }
+            }
+        });
+
+        grid
+    }
+
+    /// Gets the value of a cell.
+    ///
+    /// - `point` - The point to get the value of.
+    pub fn get(&self, point: &CubeSpherePoint<S>) -> bool {
+        let i = point.index();
+
+        self.words[i / 64] & (1 << (i % 64)) != 0
+    }
+
+    /// Sets the value of a cell.
+    ///
+    /// - `point` - The point to set the value of.
+    /// - `value` - The new value of the cell.
+    pub fn set(&mut self, point: &CubeSpherePoint<S>, value: bool) {
+        let i = point.index();
+
+        if value {
+            self.words[i / 64] |= 1 << (i % 64);
+        } else {
+            self.words[i / 64] &= !(1 << (i % 64));
+        }
+    }
+
+    /// Counts the number of cells that are set to `true`.
+    pub fn count_ones(&self) -> usize {
+        self.words.iter()
+            .map(|word| word.count_ones() as usize)
+            .sum()
+    }
+
+    /// Iterates over the points in this grid.
+    ///
+    /// The points are visited in the same order as `CubeSphereGrid::points`.
+    pub fn points(&self) -> impl ExactSizeIterator<Item = CubeSpherePoint<S>> + DoubleEndedIterator {
+        (0..Self::CELLS).map(CubeSpherePoint::from_index)
+    }
+
+    /// Iterates over the points in this grid and their values.
+    ///
+    /// The points are visited in the same order as `points`.
+    pub fn iter(&self) -> impl ExactSizeIterator<Item = (CubeSpherePoint<S>, bool)> + DoubleEndedIterator + '_ {
+        self.points()
+            .map(|point| (point, self.get(&point)))
+    }
+
+    /// Applies a function to each cell and its direct neighbours.
+    ///
+    /// The provided function is called with the arguments: current, up, down, left, right.
+    ///
+    /// `f` - The function to apply.
+    pub fn map_neighbours<F: FnMut(bool, bool, bool, bool, bool) -> bool>(&self, mut f: F) -> Self {
+        Self::from_fn(|current| {
+            f(self.get(current), self.get(&current.up()), self.get(&current.down()), self.get(&current.left()), self.get(&current.right()))
+        })
+    }
+
+    /// Applies a function in parallel to each cell and its direct neighbours.
+    ///
+    /// The provided function is called with the arguments: current, up, down, left, right.
+    ///
+    /// `f` - The function to apply.
+    pub fn map_neighbours_par<F: Fn(bool, bool, bool, bool, bool) -> bool + Send + Sync>(&self, f: F) -> Self {
+        Self::from_fn_par(|current| {
+            f(self.get(current), self.get(&current.up()), self.get(&current.down()), self.get(&current.left()), self.get(&current.right()))
+        })
+    }
+
+    /// Applies a function to each cell and its direct neighbours including diagonals.
+    ///
+    /// The provided function is called with the arguments: up_left, up, up_right,
+    /// left, current, right, down_left, down, down_right. This is the order given by
+    /// `DIAGONAL_ORDER`.
+    ///
+    /// `f` - The function to apply.
+    pub fn map_neighbours_diagonals<
+                F: FnMut(bool, bool, bool, bool, bool, bool, bool, bool, bool) -> bool
+            >(&self, mut f: F) -> Self {
+        Self::from_fn(|current| {
+            f(
+                self.get(&current.up_left()), self.get(&current.up()), self.get(&current.up_right()),
+                self.get(&current.left()), self.get(current), self.get(&current.right()),
+                self.get(&current.down_left()), self.get(&current.down()), self.get(&current.down_right())
+                )
+        })
+    }
+
+    /// Applies a function in parallel to each cell and its direct neighbours including diagonals.
+    ///
+    /// The provided function is called with the arguments: up_left, up, up_right,
+    /// left, current, right, down_left, down, down_right. This is the order given by
+    /// `DIAGONAL_ORDER`.
+    ///
+    /// `f` - The function to apply.
+    pub fn map_neighbours_diagonals_par<
+                F: Fn(bool, bool, bool, bool, bool, bool, bool, bool, bool) -> bool + Send + Sync
+            >(&self, f: F) -> Self {
+        Self::from_fn_par(|current| {
+            f(
+                self.get(&current.up_left()), self.get(&current.up()), self.get(&current.up_right()),
+                self.get(&current.left()), self.get(current), self.get(&current.right()),
+                self.get(&current.down_left()), self.get(&current.down()), self.get(&current.down_right())
+                )
+        })
+    }
+}
+
+impl <const S: usize> Default for BitCubeSphereGrid<S> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl <const S: usize> Index<CubeSpherePoint<S>> for BitCubeSphereGrid<S> {
+    type Output = bool;
+
+    fn index(&self, index: CubeSpherePoint<S>) -> &Self::Output {
+        if self.get(&index) {
+            &true
+        } else {
+            &false
+        }
+    }
+}
+
+impl <const S: usize> Index<&CubeSpherePoint<S>> for BitCubeSphereGrid<S> {
+    type Output = bool;
+
+    fn index(&self, index: &CubeSpherePoint<S>) -> &Self::Output {
+        &self[*index]
+    }
+}
+
+impl <const S: usize> From<&CubeSphereGrid<bool, S>> for BitCubeSphereGrid<S> {
+    fn from(grid: &CubeSphereGrid<bool, S>) -> Self {
+        Self::from_fn(|point| grid[*point])
+    }
+}
+
+impl <const S: usize> From<&BitCubeSphereGrid<S>> for CubeSphereGrid<bool, S> {
+    fn from(grid: &BitCubeSphereGrid<S>) -> Self {
+        Self::from_fn(|point| grid.get(point))
+    }
+}
+
+/// The distance in cells that a position may be outside of a cube face before it is treated as an
+/// error instead of being clamped onto the face.
+const CLAMP_TOLERANCE: f64 = 1e-6;
+
 /// A point on a `CubeSphereGrid`.
 ///
 /// # Constant Parameters
@@ -494,7 +1697,7 @@ impl <const S: usize> CubeSpherePoint<S> {
     /// - `face` - The face on which the point lies.
     /// - `x` - The X position on the face.
     /// - `y` - The Y position on the face.
-    fn new(face: CubeFace, x: u16, y: u16) -> Self {
+    pub(crate) fn new(face: CubeFace, x: u16, y: u16) -> Self {
         Self {
             face,
             // Clamp to account for floating point rounding error.
@@ -502,6 +1705,382 @@ impl <const S: usize> CubeSpherePoint<S> {
             y: y.clamp(0, S as u16 - 1)
         }
     }
+
+    /// Creates a new `CubeSpherePoint` from a position on a face measured in cells.
+    ///
+    /// The position is rounded down and clamped onto the face to account for floating point
+    /// rounding error. In debug builds this panics if the position is more than
+    /// `CLAMP_TOLERANCE` outside of the face as that indicates an error in the projection.
+    ///
+    /// - `face` - The face on which the point lies.
+    /// - `x` - The X position on the face.
+    /// - `y` - The Y position on the face.
+    fn from_face_coordinates(face: CubeFace, x: f64, y: f64) -> Self {
+        let range = -CLAMP_TOLERANCE..=S as f64 + CLAMP_TOLERANCE;
+
+        debug_assert!(
+            range.contains(&x) && range.contains(&y),
+            "the position ({x}, {y}) is outside of the {face:?} face of a cube with {S} cells on each side"
+        );
+
+        Self::new(face, x as u16, y as u16)
+    }
+
+    /// Creates a new `CubeSpherePoint` checking that the position lies on the face.
+    ///
+    /// Unlike the clamping used when converting from geographic coordinates this returns an error
+    /// if either coordinate is `S` or greater.
+    ///
+    /// - `face` - The face on which the point lies.
+    /// - `x` - The X position on the face.
+    /// - `y` - The Y position on the face.
+    pub fn try_new(face: CubeFace, x: u16, y: u16) -> Result<Self, OutOfBounds> {
+        if (x as usize) < S && (y as usize) < S {
+            Ok(Self {
+                face,
+                x,
+                y,
+            })
+        } else {
+            Err(OutOfBounds)
+        }
+    }
+
+    /// Gets all of the points whose latitude is close to a target latitude.
+    ///
+    /// This checks the `latitude` of every point on the grid so it should not be called
+    /// frequently for large grids. The points are returned in the same order as
+    /// `CubeSphereGrid::points`.
+    ///
+    /// - `latitude` - The target latitude in radians.
+    /// - `tolerance` - The greatest difference from the target latitude that a point may have.
+    pub fn iso_latitude_points(latitude: f64, tolerance: f64) -> Vec<Self> {
+        FACE_ORDER.into_iter()
+            .cartesian_product(0..S)
+            .cartesian_product(0..S)
+            .map(|((face, y), x)| Self::new(face, x as u16, y as u16))
+            .filter(|point| (point.latitude() - latitude).abs() <= tolerance)
+            .collect()
+    }
+
+    /// Gets the four cells surrounding a geographic position along with their bilinear
+    /// interpolation weights.
+    ///
+    /// The cells are the ones whose centres surround the position on the face that the position
+    /// lies on. They are returned in the same order as the corners used by `cell_corner`, starting
+    /// with the cell with the smallest X and Y coordinates. Near the edge of a face the cells that
+    /// fall off the face are taken from the neighbouring face. At the corners of the cube only
+    /// three cells meet so one of the cells is returned twice.
+    ///
+    /// The weights are non-negative and add up to `1`.
+    ///
+    /// - `latitude` - The latitude of the position in radians where 0 is the equator.
+    /// - `longitude` - The longitude of the position in radians.
+    pub fn from_geographic_bilinear(latitude: f64, longitude: f64) -> ([Self; 4], [f64; 4]) {
+        let s = S as i32;
+
+        let position = [
+            latitude.cos() * longitude.sin(),
+            latitude.sin(),
+            latitude.cos() * longitude.cos()
+        ];
+
+        let normal = (0..3)
+            .max_by(|a, b| position[*a].abs().total_cmp(&position[*b].abs()))
+            .unwrap();
+
+        let face = match (normal, position[normal] > 0.0) {
+            (0, true) => CubeFace::Right,
+            (0, false) => CubeFace::Left,
+            (1, true) => CubeFace::Top,
+            (1, false) => CubeFace::Bottom,
+            (_, true) => CubeFace::Front,
+            (_, false) => CubeFace::Back,
+        };
+
+        let (x_axis, y_axis, normal) = Self::face_axes(face);
+
+        let along = |axis: [i32; 3]| (0..3).map(|i| position[i] * axis[i] as f64).sum::<f64>();
+
+        // Project onto the face of a cube with a side length of `2S` and find the position on the
+        // face relative to the centres of the cells.
+        let scale = S as f64 / along(normal);
+        let x = (along(x_axis) * scale + S as f64) / 2.0 - 0.5;
+        let y = (along(y_axis) * scale + S as f64) / 2.0 - 0.5;
+
+        let (x0, y0) = (x.floor(), y.floor());
+        let (tx, ty) = (x - x0, y - y0);
+
+        let points = [(0, 0), (1, 0), (1, 1), (0, 1)].map(|(dx, dy)| {
+            let a = (x0 as i32 + dx) * 2 + 1 - s;
+            let b = (y0 as i32 + dy) * 2 + 1 - s;
+
+            Self::from_unfolded_cube_centre([0, 1, 2].map(|i| normal[i] * s + x_axis[i] * a + y_axis[i] * b))
+        });
+
+        let weights = [
+            (1.0 - tx) * (1.0 - ty),
+            tx * (1.0 - ty),
+            tx * ty,
+            (1.0 - tx) * ty,
+        ];
+
+        (points, weights)
+    }
+
+    /// Gets the position of the centre of this cell in 3D space.
+    ///
+    /// - `scale` - The scale of the 3D object.
+    pub fn cell_center(&self, scale: f64) -> (f64, f64, f64) {
+        Self::face_position(self.face, self.x as f64 + 0.5, self.y as f64 + 0.5, scale)
+    }
+
+    /// Gets the position of a corner of this cell in 3D space.
+    ///
+    /// The corners are numbered clockwise starting from the corner with the smallest X and Y
+    /// coordinates:
+    /// - `0` - `(x, y)`
+    /// - `1` - `(x + 1, y)`
+    /// - `2` - `(x + 1, y + 1)`
+    /// - `3` - `(x, y + 1)`
+    ///
+    /// - `corner` - The corner to get.
+    /// - `scale` - The scale of the 3D object.
+    ///
+    /// # Panics
+    /// Panics if `corner` is greater than 3.
+    pub fn cell_corner(&self, corner: u8, scale: f64) -> (f64, f64, f64) {
+        let (x, y) = corner_offset(corner);
+
+        Self::face_position(self.face, self.x as f64 + x, self.y as f64 + y, scale)
+    }
+
+    /// Gets the solid angle covered by this cell in steradians.
+    ///
+    /// This is the area of the cell on a sphere with a radius of 1. The solid angles of all of the
+    /// cells on the grid add up to `4 * PI`.
+    pub fn solid_angle(&self) -> f64 {
+        // The solid angle of the rectangle from the centre of the face to (u, v) on the plane
+        // that is a distance of 1 from the centre of the sphere.
+        let area = |x: f64, y: f64| {
+            let u = x * 2.0 / S as f64 - 1.0;
+            let v = y * 2.0 / S as f64 - 1.0;
+
+            (u * v / (1.0 + u * u + v * v).sqrt()).atan()
+        };
+
+        let (x, y) = (self.x as f64, self.y as f64);
+
+        area(x + 1.0, y + 1.0) - area(x, y + 1.0) - area(x + 1.0, y) + area(x, y)
+    }
+
+    /// Gets the areal distortion of this cell caused by projecting the cube onto the sphere.
+    ///
+    /// This is the ratio of the solid angle of this cell to the average solid angle of a cell,
+    /// `4 * PI / (6 * S * S)`, so a value of `1.0` means the cell has the average size. Cells near
+    /// the centre of each face are larger than average and cells near the corners of the cube are
+    /// smaller.
+    pub fn distortion(&self) -> f64 {
+        self.solid_angle() / (4.0 * PI / (6 * S * S) as f64)
+    }
+
+    /// Gets the index of this point along a Hilbert curve covering its face.
+    ///
+    /// Each face is covered by its own curve, which covers the smallest square with a power of two
+    /// side length that contains the face, and the faces follow each other in the same order as
+    /// `CubeSphereGrid::points`. Each point has a different index but the indices are not
+    /// contiguous unless `S` is a power of two. Sorting points by this index keeps nearby points on
+    /// the same face close together.
+    pub fn hilbert_index(&self) -> u64 {
+        let size = S.next_power_of_two() as u64;
+
+        let face = FACE_ORDER.iter()
+            .position(|face| *face == self.face)
+            .unwrap() as u64;
+
+        face * size * size + hilbert_index(self.x as u32, self.y as u32, size)
+    }
+
+    /// Gets the point on a grid of a different size covering the same part of the same face.
+    ///
+    /// This gives the cell of the new grid which contains the centre of this cell. When `S2` is a
+    /// multiple of `S` this is one of the cells that this cell is split into and when `S` is a
+    /// multiple of `S2` it is the cell that contains this cell, so converting to a finer grid and
+    /// back gives the original point. For other sizes a centre that lies exactly on the edge between
+    /// two cells gives the cell with the larger coordinate.
+    pub fn rescale<const S2: usize>(&self) -> CubeSpherePoint<S2> {
+        let rescale = |c: u16| ((c as u64 * 2 + 1) * S2 as u64 / (S as u64 * 2)) as u16;
+
+        CubeSpherePoint::new(self.face, rescale(self.x), rescale(self.y))
+    }
+
+    /// Gets the geographic coordinates of a continuous position on a face.
+    ///
+    /// The position is measured in cells so it ranges from `0.0` to `S` along each axis with the
+    /// centre of the point `(x, y)` at `(x + 0.5, y + 0.5)`. Unlike `from_geographic` this does not
+    /// round the position to a cell.
+    ///
+    /// This returns the latitude and longitude in radians with the longitude in the range `0..2PI`.
+    ///
+    /// - `face` - The face on which the position lies.
+    /// - `fx` - The X position on the face.
+    /// - `fy` - The Y position on the face.
+    pub fn face_local_to_geographic(face: CubeFace, fx: f64, fy: f64) -> (f64, f64) {
+        let (x, y, z) = Self::face_position(face, fx, fy, 1.0);
+
+        let latitude = y.atan2((x * x + z * z).sqrt());
+        let longitude = x.atan2(z).rem_euclid(2.0 * PI);
+
+        (latitude, longitude)
+    }
+
+    /// Gets the position in 3D space of a continuous position on a face.
+    ///
+    /// - `face` - The face on which the position lies.
+    /// - `x` - The X position on the face.
+    /// - `y` - The Y position on the face.
+    /// - `scale` - The scale of the 3D object.
+    fn face_position(face: CubeFace, x: f64, y: f64, scale: f64) -> (f64, f64, f64) {
+        let (x, y, z) = match face {
+            CubeFace::Front => (x * 2.0 - S as f64, y * 2.0 - S as f64, S as f64),
+            CubeFace::Back => (x * 2.0 - S as f64, -y * 2.0 + S as f64, -(S as f64)),
+            CubeFace::Left => (-(S as f64), y * 2.0 - S as f64, x * 2.0 -(S as f64)),
+            CubeFace::Right => (S as f64, y * 2.0 - S as f64, S as f64 - x * 2.0),
+            CubeFace::Top => (x * 2.0 - S as f64, S as f64, y * 2.0 - S as f64),
+            CubeFace::Bottom => (x * 2.0 - S as f64, -(S as f64), S as f64 - y * 2.0),
+        };
+
+        let length = (x * x + y * y + z * z).sqrt();
+
+        (x / length * scale, y / length * scale, z / length * scale)
+    }
+
+    /// Gets the face of this point along with its X and Y positions on the face.
+    pub(crate) fn face_coordinates(&self) -> (CubeFace, u16, u16) {
+        (self.face, self.x, self.y)
+    }
+
+    /// Gets the index of this point in the order that `CubeSphereGrid::points` visits them.
+    pub(crate) fn index(&self) -> usize {
+        let face = FACE_ORDER.iter()
+            .position(|face| *face == self.face)
+            .unwrap();
+
+        (face * S + self.y as usize) * S + self.x as usize
+    }
+
+    /// Gets the point at an index in the order that `CubeSphereGrid::points` visits them.
+    ///
+    /// This is the inverse of `index`.
+    ///
+    /// - `index` - The index of the point.
+    pub(crate) fn from_index(index: usize) -> Self {
+        Self::new(FACE_ORDER[index / (S * S)], (index % S) as u16, (index / S % S) as u16)
+    }
+
+    /// Gets the directions of the X and Y axes and the outward normal of a face.
+    ///
+    /// - `face` - The face to get the axes of.
+    fn face_axes(face: CubeFace) -> ([i32; 3], [i32; 3], [i32; 3]) {
+        match face {
+            CubeFace::Front => ([1, 0, 0], [0, 1, 0], [0, 0, 1]),
+            CubeFace::Back => ([1, 0, 0], [0, -1, 0], [0, 0, -1]),
+            CubeFace::Left => ([0, 0, 1], [0, 1, 0], [-1, 0, 0]),
+            CubeFace::Right => ([0, 0, -1], [0, 1, 0], [1, 0, 0]),
+            CubeFace::Top => ([1, 0, 0], [0, 0, 1], [0, 1, 0]),
+            CubeFace::Bottom => ([1, 0, 0], [0, 0, -1], [0, -1, 0]),
+        }
+    }
+
+    /// Gets the point for a texel of a cube map.
+    ///
+    /// This follows the layout of cube maps used by wgpu, Vulkan, Direct3D and OpenGL where each
+    /// layer faces along an axis and its texture coordinates are the other two axes.
+    ///
+    /// - `layer` - The layer of the cube map from 0 to 5.
+    /// - `column` - The column of the texel.
+    /// - `row` - The row of the texel.
+    fn from_cube_map(layer: usize, column: usize, row: usize) -> Self {
+        let s = S as i32;
+        let u = column as i32 * 2 + 1 - s;
+        let v = row as i32 * 2 + 1 - s;
+
+        let (x, y, z) = match layer {
+            0 => (s, -v, -u),
+            1 => (-s, -v, u),
+            2 => (u, s, v),
+            3 => (u, -s, -v),
+            4 => (u, -v, s),
+            _ => (-u, -v, -s),
+        };
+
+        Self::from_cube_centre(x, y, z)
+    }
+
+    /// Gets the point with the specified centre on a cube with a side length of `2S` where the
+    /// centre may have been moved past the edge of its face.
+    ///
+    /// Each coordinate past the edge of the cube is folded over the edge onto the next face.
+    ///
+    /// - `centre` - The position of the centre.
+    fn from_unfolded_cube_centre(mut centre: [i32; 3]) -> Self {
+        let s = S as i32;
+
+        while let Some(i) = centre.iter().position(|c| c.abs() > s) {
+            let normal = centre.iter().position(|c| c.abs() == s).unwrap();
+
+            centre[i] = centre[i].signum() * s;
+            centre[normal] = centre[normal].signum() * (s - 1);
+        }
+
+        Self::from_cube_centre(centre[0], centre[1], centre[2])
+    }
+
+    /// Gets the centre of this point on a cube with a side length of `2S`.
+    ///
+    /// Cell centres lie on odd integer coordinates so this can be converted back to a point exactly
+    /// using `from_cube_centre`.
+    fn cube_centre(&self) -> (i32, i32, i32) {
+        let s = S as i32;
+        let a = self.x as i32 * 2 + 1 - s;
+        let b = self.y as i32 * 2 + 1 - s;
+
+        match self.face {
+            CubeFace::Front => (a, b, s),
+            CubeFace::Back => (a, -b, -s),
+            CubeFace::Left => (-s, b, a),
+            CubeFace::Right => (s, b, -a),
+            CubeFace::Top => (a, s, b),
+            CubeFace::Bottom => (a, -s, -b),
+        }
+    }
+
+    /// Gets the point with the specified centre on a cube with a side length of `2S`.
+    ///
+    /// This is the inverse of `cube_centre`.
+    ///
+    /// - `x` - The X position of the centre.
+    /// - `y` - The Y position of the centre.
+    /// - `z` - The Z position of the centre.
+    fn from_cube_centre(x: i32, y: i32, z: i32) -> Self {
+        let s = S as i32;
+
+        let (face, a, b) = if z == s {
+            (CubeFace::Front, x, y)
+        } else if z == -s {
+            (CubeFace::Back, x, -y)
+        } else if x == -s {
+            (CubeFace::Left, z, y)
+        } else if x == s {
+            (CubeFace::Right, -z, y)
+        } else if y == s {
+            (CubeFace::Top, x, z)
+        } else {
+            (CubeFace::Bottom, x, -z)
+        };
+
+        Self::new(face, ((a + s - 1) / 2) as u16, ((b + s - 1) / 2) as u16)
+    }
 }
 
 impl <const S: usize> GridPoint for CubeSpherePoint<S> {
@@ -632,7 +2211,7 @@ impl <const S: usize> GridPoint for CubeSpherePoint<S> {
             CubeFace::Right => if self.y == S as u16 - 1 {
                 Self {
                     face: CubeFace::Bottom,
-                    x: 0,
+                    x: S as u16 - 1,
                     y: self.x
                 }
             } else {
@@ -837,26 +2416,51 @@ impl <const S: usize> GridPoint for CubeSpherePoint<S> {
         }
     }
 
-    fn position(&self, scale: f64) -> (f64, f64, f64) {
-        let x = self.x as f64;
-        let y = self.y as f64;
-
-        let (x, y, z) = match self.face {
-            CubeFace::Front => (x * 2.0 - S as f64, y * 2.0 - S as f64, S as f64),
-            CubeFace::Back => (x * 2.0 - S as f64, -y * 2.0 + S as f64, -(S as f64)),
-            CubeFace::Left => (-(S as f64), y * 2.0 - S as f64, x * 2.0 -(S as f64)),
-            CubeFace::Right => (S as f64, y * 2.0 - S as f64, S as f64 - x * 2.0),
-            CubeFace::Top => (x * 2.0 - S as f64, S as f64, y * 2.0 - S as f64),
-            CubeFace::Bottom => (x * 2.0 - S as f64, -(S as f64), S as f64 - y * 2.0),
+    /// Gets the cells that the grid joins to this point by an edge or a corner.
+    ///
+    /// The cell at each corner is the cell other than this one that neighbours both of the cells
+    /// on either side of the corner. At the corners of the cube only three cells meet so there is
+    /// no such cell and the cells there have seven neighbours. The cells are given in the order
+    /// up_left, up, up_right, left, right, down_left, down, down_right with any missing corners
+    /// skipped.
+    ///
+    /// Within a face these are the cells that touch this one on the sphere. The cells that `up`,
+    /// `down`, `left` and `right` give across the edge of a face are not next to it on the sphere,
+    /// as described in `CubeSphereGrid::laplacian`, so some of the cells given for the cells along
+    /// the edges of the faces are far away from them.
+    fn moore_neighbours(&self) -> impl Iterator<Item = Self> {
+        let [up, down, left, right] = self.neighbours();
+
+        let corner = |a: &Self, b: &Self| {
+            let others = b.neighbours();
+
+            a.neighbours().into_iter()
+                .find(|point| point != self && point != a && point != b && others.contains(point))
         };
 
-        let length = (x * x + y * y + z * z).sqrt();
-
-        (x / length * scale, y / length * scale, z / length * scale)
+        [
+            corner(&up, &left), Some(up), corner(&up, &right),
+            Some(left), Some(right),
+            corner(&down, &left), Some(down), corner(&down, &right),
+        ].into_iter().flatten()
+    }
+
+    /// Gets the position of the point in 3D space.
+    ///
+    /// This is the position of the corner of the cell with the smallest X and Y coordinates, the
+    /// same as `cell_corner(0, scale)`. Use `cell_center` to get the centre of the cell.
+    ///
+    /// - `scale` - The scale of the 3D object.
+    fn position(&self, scale: f64) -> (f64, f64, f64) {
+        Self::face_position(self.face, self.x as f64, self.y as f64, scale)
     }
 }
 
 impl <const S: usize> SpherePoint for CubeSpherePoint<S> {
+    fn center_position(&self, scale: f64) -> (f64, f64, f64) {
+        self.cell_center(scale)
+    }
+
     fn from_geographic(latitude: f64, longitude: f64) -> Self {
         let y = latitude.sin();
 
@@ -876,8 +2480,8 @@ impl <const S: usize> SpherePoint for CubeSpherePoint<S> {
             let x2 = (x + S as f64) / 2.0;
             let y2 = (z + S as f64) / 2.0;
 
-            if (x2 as i32) >= 0 && (x2 as i32) < (S as i32) && (y2 as i32) > 0 && (y2 as i32) < (S as i32) {
-                return CubeSpherePoint::new(CubeFace::Top, x2 as u16, y2 as u16);
+            if (0.0..S as f64).contains(&x2) && (0.0..S as f64).contains(&y2) {
+                return CubeSpherePoint::from_face_coordinates(CubeFace::Top, x2, y2);
             }
                 
             if longitude > PI / 4.0 + 3.0 * PI / 2.0 {
@@ -900,8 +2504,8 @@ impl <const S: usize> SpherePoint for CubeSpherePoint<S> {
             let x2 = (x + S as f64) / 2.0;
             let y2 = (S as f64 - z) / 2.0;
 
-            if (x2 as i32) >= 0 && (x2 as i32) < (S as i32) && (y2 as i32) > 0 && (y2 as i32) < (S as i32) {
-                return CubeSpherePoint::new(CubeFace::Bottom, x2 as u16, y2 as u16);
+            if (0.0..S as f64).contains(&x2) && (0.0..S as f64).contains(&y2) {
+                return CubeSpherePoint::from_face_coordinates(CubeFace::Bottom, x2, y2);
             }
             if longitude > PI / 4.0 + 3.0 * PI / 2.0 {
                 CubeFace::Front
@@ -923,7 +2527,7 @@ impl <const S: usize> SpherePoint for CubeSpherePoint<S> {
                 let x2 = (x * scale + S as f64) / 2.0;
                 let y2 = (y * scale + S as f64) / 2.0;
 
-                CubeSpherePoint::new(CubeFace::Front, x2 as u16, y2 as u16)
+                CubeSpherePoint::from_face_coordinates(CubeFace::Front, x2, y2)
             },
             CubeFace::Back => {
                 let scale = -(S as f64) / z;
@@ -934,7 +2538,7 @@ impl <const S: usize> SpherePoint for CubeSpherePoint<S> {
                 let x2 = (x + S as f64) / 2.0;
                 let y2 = (y - S as f64) / -2.0;
                 
-                CubeSpherePoint::new(CubeFace::Back, x2 as u16, y2 as u16)
+                CubeSpherePoint::from_face_coordinates(CubeFace::Back, x2, y2)
             },
             CubeFace::Left => {
                 let scale = -(S as f64) / x;
@@ -945,7 +2549,7 @@ impl <const S: usize> SpherePoint for CubeSpherePoint<S> {
                 let x2 = (z + S as f64) / 2.0;
                 let y2 = (y + S as f64) / 2.0;
                 
-                CubeSpherePoint::new(CubeFace::Left, x2 as u16, y2 as u16)
+                CubeSpherePoint::from_face_coordinates(CubeFace::Left, x2, y2)
             },
             CubeFace::Right => {
                 let scale = S as f64 / x;
@@ -956,7 +2560,7 @@ impl <const S: usize> SpherePoint for CubeSpherePoint<S> {
                 let x2 = (S as f64 - z) / 2.0;
                 let y2 = (y + S as f64) / 2.0;
                 
-                CubeSpherePoint::new(CubeFace::Right, x2 as u16, y2 as u16)
+                CubeSpherePoint::from_face_coordinates(CubeFace::Right, x2, y2)
             },
             CubeFace::Top => {
                 let scale = S as f64 / y;
@@ -967,7 +2571,7 @@ impl <const S: usize> SpherePoint for CubeSpherePoint<S> {
                 let x2 = (x + S as f64) / 2.0;
                 let y2 = (z + S as f64) / 2.0;
                 
-                CubeSpherePoint::new(CubeFace::Top, x2 as u16, y2 as u16)
+                CubeSpherePoint::from_face_coordinates(CubeFace::Top, x2, y2)
             },
             CubeFace::Bottom => {
                 let scale = -(S as f64) / y;
@@ -978,7 +2582,7 @@ impl <const S: usize> SpherePoint for CubeSpherePoint<S> {
                 let x2 = (x + S as f64) / 2.0;
                 let y2 = (S as f64 - z) / 2.0;
                 
-                CubeSpherePoint::new(CubeFace::Bottom, x2 as u16, y2 as u16)
+                CubeSpherePoint::from_face_coordinates(CubeFace::Bottom, x2, y2)
             },
         }
     }
@@ -998,693 +2602,4806 @@ impl <const S: usize> SpherePoint for CubeSpherePoint<S> {
     }
 }
 
-/// A face of a cube.
+/// A grid on a sphere that uses the same faces as `CubeSphereGrid` but projects them onto the
+/// sphere so that every cell covers the same area.
+///
+/// `CubeSphereGrid` projects each face straight out from the centre of the sphere, which makes the
+/// cells near the corners of the cube much smaller than the cells at the centres of the faces.
+/// This grid instead uses the equal area projection of Roşca and Plonka, which splits each face
+/// into four triangles around its centre and maps them onto the sphere through a Lambert
+/// azimuthal equal area projection. Cells keep the same neighbours as in `CubeSphereGrid` and only
+/// their positions on the sphere change.
+///
+/// # Type Parameters
+/// - `T` - The type of data that the grid holds.
+///
+/// # Constant Parameters
+/// - `S` - The size of each side of each face.
+///
+/// # Iteration Order
+/// The grid is visited in the same order as `CubeSphereGrid`.
+///
+/// # Thread Safety
+/// The grid wraps a `CubeSphereGrid` and only changes where its points are placed, so it is `Send`
+/// and `Sync` in the same cases as a `CubeSphereGrid`.
+#[derive(Debug, PartialEq, Eq, Hash, Default)]
+pub struct EqualAreaCubeSphereGrid<T, const S: usize> {
+    /// The cube grid holding the data in the same layout.
+    grid: CubeSphereGrid<T, S>,
+}
+
+impl <T, const S: usize> From<CubeSphereGrid<T, S>> for EqualAreaCubeSphereGrid<T, S> {
+    fn from(grid: CubeSphereGrid<T, S>) -> Self {
+        Self {
+            grid,
+        }
+    }
+}
+
+impl <T, const S: usize> From<EqualAreaCubeSphereGrid<T, S>> for CubeSphereGrid<T, S> {
+    fn from(grid: EqualAreaCubeSphereGrid<T, S>) -> Self {
+        grid.grid
+    }
+}
+
+impl <T: Clone, const S: usize> Clone for EqualAreaCubeSphereGrid<T, S> {
+    fn clone(&self) -> Self {
+        Self {
+            grid: self.grid.clone(),
+        }
+    }
+
+    /// Copies the values of another grid into this grid without allocating.
+    fn clone_from(&mut self, source: &Self) {
+        self.grid.clone_from(&source.grid);
+    }
+}
+
+impl <T, const S: usize> EqualAreaCubeSphereGrid<T, S> {
+    /// Gets the values of the cells on a face in the same order as the points of the grid.
+    ///
+    /// This has the same layout as `CubeSphereGrid::face_slice`.
+    ///
+    /// - `face` - The face to get the values of.
+    pub fn face_slice(&self, face: CubeFace) -> &[T] {
+        self.grid.face_slice(face)
+    }
+
+    /// Gets mutable references to the values of the cells on a face in the same order as the
+    /// points of the grid.
+    ///
+    /// - `face` - The face to get the values of.
+    pub fn face_slice_mut(&mut self, face: CubeFace) -> &mut [T] {
+        self.grid.face_slice_mut(face)
+    }
+
+    /// Gets the values of the cells on a face as bytes in the same order as `face_slice`.
+    ///
+    /// - `face` - The face to get the values of.
+    #[cfg(feature = "bytemuck")]
+    pub fn face_bytes(&self, face: CubeFace) -> &[u8] where T: bytemuck::Pod {
+        bytemuck::cast_slice(self.face_slice(face))
+    }
+
+    /// Gets the values of the cells on a face as mutable bytes in the same order as `face_slice`.
+    ///
+    /// - `face` - The face to get the values of.
+    #[cfg(feature = "bytemuck")]
+    pub fn face_bytes_mut(&mut self, face: CubeFace) -> &mut [u8] where T: bytemuck::Pod {
+        bytemuck::cast_slice_mut(self.face_slice_mut(face))
+    }
+}
+
+impl <T, const S: usize> SurfaceGrid<T> for EqualAreaCubeSphereGrid<T, S> {
+    type Point = EqualAreaCubeSpherePoint<S>;
+
+    fn from_fn<F: FnMut(&Self::Point) -> T>(mut f: F) -> Self {
+        CubeSphereGrid::from_fn(|point| f(&EqualAreaCubeSpherePoint::from(*point))).into()
+    }
+
+    fn from_fn_par<F: Fn(&Self::Point) -> T + Send + Sync>(f: F) -> Self where T: Send + Sync {
+        CubeSphereGrid::from_fn_par(|point| f(&EqualAreaCubeSpherePoint::from(*point))).into()
+    }
+
+    fn try_from_fn<X, F: FnMut(&Self::Point) -> Result<T, X>>(mut f: F) -> Result<Self, X> {
+        CubeSphereGrid::try_from_fn(|point| f(&EqualAreaCubeSpherePoint::from(*point))).map(Self::from)
+    }
+
+    fn try_from_fn_par<X: Send, F: Fn(&Self::Point) -> Result<T, X> + Send + Sync>(f: F) -> Result<Self, X> where T: Send + Sync {
+        CubeSphereGrid::try_from_fn_par(|point| f(&EqualAreaCubeSpherePoint::from(*point))).map(Self::from)
+    }
+
+    fn from_vec(values: Vec<T>) -> Option<Self> {
+        CubeSphereGrid::from_vec(values).map(Self::from)
+    }
+
+    fn set_from_fn<F: FnMut(&Self::Point) -> T>(&mut self, mut f: F) {
+        self.grid.set_from_fn(|point| f(&EqualAreaCubeSpherePoint::from(*point)))
+    }
+
+    fn set_from_fn_par<F: Fn(&Self::Point) -> T + Send + Sync>(&mut self, f: F) where T: Send + Sync {
+        self.grid.set_from_fn_par(|point| f(&EqualAreaCubeSpherePoint::from(*point)))
+    }
+
+    fn iter<'a>(&'a self) -> impl ExactSizeIterator<Item = (Self::Point, &'a T)> + DoubleEndedIterator where T: 'a {
+        self.grid.iter()
+            .map(|(point, value)| (point.into(), value))
+    }
+
+    fn par_iter<'a>(&'a self) -> impl IndexedParallelIterator<Item = (Self::Point, &'a T)> where T: 'a + Send + Sync {
+        self.grid.par_iter()
+            .map(|(point, value)| (point.into(), value))
+    }
+
+    fn par_iter_mut<'a>(&'a mut self) -> impl IndexedParallelIterator<Item = (Self::Point, &'a mut T)> where T: 'a + Send + Sync {
+        self.grid.par_iter_mut()
+            .map(|(point, value)| (point.into(), value))
+    }
+
+    fn points(&self) -> impl ExactSizeIterator<Item = Self::Point> + DoubleEndedIterator {
+        self.grid.points()
+            .map(EqualAreaCubeSpherePoint::from)
+    }
+
+    fn par_points(&self) -> impl IndexedParallelIterator<Item = Self::Point> {
+        self.grid.par_points()
+            .map(EqualAreaCubeSpherePoint::from)
+    }
+
+    fn par_map_to_vec<U: Send, F: Fn(&Self::Point, &T) -> U + Send + Sync>(&self, f: F) -> Vec<U> where T: Send + Sync {
+        self.grid.par_map_to_vec(|point, value| f(&EqualAreaCubeSpherePoint::from(*point), value))
+    }
+
+    fn to_index(&self, point: &Self::Point) -> usize {
+        self.grid.to_index(&point.point)
+    }
+
+    fn at(&self, point: &Self::Point) -> &T {
+        &self[point]
+    }
+
+    fn at_mut(&mut self, point: &Self::Point) -> &mut T {
+        &mut self[point]
+    }
+
+    fn surface_area(&self, scale: f64) -> f64 {
+        4.0 * PI * scale * scale
+    }
+
+    fn topology(&self) -> Topology {
+        Topology::uniform(Surface::Sphere, 6, S, S)
+    }
+}
+
+impl <T, const S: usize> Index<EqualAreaCubeSpherePoint<S>> for EqualAreaCubeSphereGrid<T, S> {
+    type Output = T;
+
+    fn index(&self, index: EqualAreaCubeSpherePoint<S>) -> &Self::Output {
+        &self.grid[index.point]
+    }
+}
+
+impl <T, const S: usize> IndexMut<EqualAreaCubeSpherePoint<S>> for EqualAreaCubeSphereGrid<T, S> {
+    fn index_mut(&mut self, index: EqualAreaCubeSpherePoint<S>) -> &mut Self::Output {
+        &mut self.grid[index.point]
+    }
+}
+
+impl <T, const S: usize> Index<&EqualAreaCubeSpherePoint<S>> for EqualAreaCubeSphereGrid<T, S> {
+    type Output = T;
+
+    fn index(&self, index: &EqualAreaCubeSpherePoint<S>) -> &Self::Output {
+        &self[*index]
+    }
+}
+
+impl <T, const S: usize> IndexMut<&EqualAreaCubeSpherePoint<S>> for EqualAreaCubeSphereGrid<T, S> {
+    fn index_mut(&mut self, index: &EqualAreaCubeSpherePoint<S>) -> &mut Self::Output {
+        &mut self[*index]
+    }
+}
+
+impl <T, const S: usize> FromIterator<T> for EqualAreaCubeSphereGrid<T, S> {
+    /// Creates a grid from the values of its cells in the same order as `points`.
+    ///
+    /// # Panics
+    /// Panics if the iterator does not yield exactly one value for each cell in the grid.
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        Self::from_vec(iter.into_iter().collect())
+            .expect("the iterator must yield one value for each cell in the grid")
+    }
+}
+
+impl <T, const S: usize> IntoIterator for EqualAreaCubeSphereGrid<T, S> {
+    type Item = (EqualAreaCubeSpherePoint<S>, T);
+
+    type IntoIter = vec::IntoIter<Self::Item>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        let data: Vec<_> = self.grid.into_iter()
+            .map(|(point, value)| (point.into(), value))
+            .collect();
+
+        data.into_iter()
+    }
+}
+
+#[cfg(feature = "serde")]
+impl <T: serde::Serialize, const S: usize> serde::Serialize for EqualAreaCubeSphereGrid<T, S> {
+    /// Serializes the values in this grid as a sequence in the same order as `iter`.
+    fn serialize<R: serde::Serializer>(&self, serializer: R) -> Result<R::Ok, R::Error> {
+        self.grid.serialize(serializer)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl <'de, T: serde::Deserialize<'de>, const S: usize> serde::Deserialize<'de> for EqualAreaCubeSphereGrid<T, S> {
+    /// Deserializes a grid from a sequence of values in the same order as `iter`.
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        CubeSphereGrid::deserialize(deserializer).map(Self::from)
+    }
+}
+
+/// A point on an `EqualAreaCubeSphereGrid`.
+///
+/// # Constant Parameters
+/// - `S` - The size of each side of each face.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
-#[repr(u32)] // For better alignment.
-enum CubeFace {
-    Front,
-    Back,
-    Left,
-    Right,
-    Top,
-    Bottom,
+pub struct EqualAreaCubeSpherePoint<const S: usize> {
+    /// The point on the cube with the same face and position.
+    point: CubeSpherePoint<S>,
 }
 
-#[cfg(test)]
-mod test {
-    use std::{f64::consts::PI, hint::black_box};
+impl <const S: usize> From<CubeSpherePoint<S>> for EqualAreaCubeSpherePoint<S> {
+    fn from(point: CubeSpherePoint<S>) -> Self {
+        Self {
+            point,
+        }
+    }
+}
 
-    use approx::assert_relative_eq;
+impl <const S: usize> From<EqualAreaCubeSpherePoint<S>> for CubeSpherePoint<S> {
+    fn from(point: EqualAreaCubeSpherePoint<S>) -> Self {
+        point.point
+    }
+}
+
+impl <const S: usize> EqualAreaCubeSpherePoint<S> {
+    /// Creates a new `EqualAreaCubeSpherePoint` checking that the position lies on the face.
+    ///
+    /// This returns an error if either coordinate is `S` or greater, in the same way as
+    /// `CubeSpherePoint::try_new`.
+    ///
+    /// - `face` - The face on which the point lies.
+    /// - `x` - The X position on the face.
+    /// - `y` - The Y position on the face.
+    pub fn try_new(face: CubeFace, x: u16, y: u16) -> Result<Self, OutOfBounds> {
+        CubeSpherePoint::try_new(face, x, y).map(Self::from)
+    }
+
+    /// Gets the position of the centre of this cell in 3D space.
+    ///
+    /// - `scale` - The scale of the 3D object.
+    pub fn cell_center(&self, scale: f64) -> (f64, f64, f64) {
+        Self::face_position(self.point.face, self.point.x as f64 + 0.5, self.point.y as f64 + 0.5, scale)
+    }
+
+    /// Gets the position of a corner of this cell in 3D space.
+    ///
+    /// The corners are numbered in the same way as `CubeSpherePoint::cell_corner`.
+    ///
+    /// - `corner` - The corner to get.
+    /// - `scale` - The scale of the 3D object.
+    ///
+    /// # Panics
+    /// Panics if `corner` is greater than 3.
+    pub fn cell_corner(&self, corner: u8, scale: f64) -> (f64, f64, f64) {
+        let (x, y) = corner_offset(corner);
+
+        Self::face_position(self.point.face, self.point.x as f64 + x, self.point.y as f64 + y, scale)
+    }
+
+    /// Gets the solid angle covered by this cell in steradians.
+    ///
+    /// Every cell covers the same area so this is always `4 * PI / (6 * S * S)`.
+    pub fn solid_angle(&self) -> f64 {
+        4.0 * PI / (6 * S * S) as f64
+    }
+
+    /// Gets the position in 3D space of a continuous position on a face.
+    ///
+    /// - `face` - The face on which the position lies.
+    /// - `x` - The X position on the face.
+    /// - `y` - The Y position on the face.
+    /// - `scale` - The scale of the 3D object.
+    fn face_position(face: CubeFace, x: f64, y: f64, scale: f64) -> (f64, f64, f64) {
+        let (x_axis, y_axis, normal) = CubeSpherePoint::<S>::face_axes(face);
+
+        let (u, v, w) = equal_area_from_square(x * 2.0 / S as f64 - 1.0, y * 2.0 / S as f64 - 1.0);
+
+        let axis = |i: usize| (x_axis[i] as f64 * u + y_axis[i] as f64 * v + normal[i] as f64 * w) * scale;
+
+        (axis(0), axis(1), axis(2))
+    }
+}
+
+impl <const S: usize> GridPoint for EqualAreaCubeSpherePoint<S> {
+    fn up(&self) -> Self {
+        self.point.up().into()
+    }
+
+    fn down(&self) -> Self {
+        self.point.down().into()
+    }
+
+    fn left(&self) -> Self {
+        self.point.left().into()
+    }
+
+    fn right(&self) -> Self {
+        self.point.right().into()
+    }
+
+    fn moore_neighbours(&self) -> impl Iterator<Item = Self> {
+        self.point.moore_neighbours().map(Self::from)
+    }
+
+    /// Gets the position of the point in 3D space.
+    ///
+    /// This is the position of the corner of the cell with the smallest X and Y coordinates, the
+    /// same as `cell_corner(0, scale)`. Use `cell_center` to get the centre of the cell.
+    ///
+    /// - `scale` - The scale of the 3D object.
+    fn position(&self, scale: f64) -> (f64, f64, f64) {
+        Self::face_position(self.point.face, self.point.x as f64, self.point.y as f64, scale)
+    }
+}
+
+impl <const S: usize> SpherePoint for EqualAreaCubeSpherePoint<S> {
+    fn center_position(&self, scale: f64) -> (f64, f64, f64) {
+        self.cell_center(scale)
+    }
+
+    fn from_geographic(latitude: f64, longitude: f64) -> Self {
+        let (face, x, y, z) = nearest_cube_face::<S>(latitude, longitude);
+
+        let (u, v) = equal_area_to_square(x, y, z);
+
+        let x = ((u + 1.0) / 2.0 * S as f64).clamp(0.0, S as f64);
+        let y = ((v + 1.0) / 2.0 * S as f64).clamp(0.0, S as f64);
+
+        CubeSpherePoint::new(face, (x as u16).min(S as u16 - 1), (y as u16).min(S as u16 - 1)).into()
+    }
+
+    fn latitude(&self) -> f64 {
+        let (x, y, z) = self.position(1.0);
+
+        y.atan2((x * x + z * z).sqrt())
+    }
+
+    fn longitude(&self) -> f64 {
+        let (x, _, z) = self.position(1.0);
+
+        x.atan2(z).rem_euclid(2.0 * PI)
+    }
+}
+
+/// A grid on a sphere that uses the same faces as `CubeSphereGrid` but projects them onto the
+/// sphere so that the cells are spaced evenly in angle across each face.
+///
+/// `CubeSphereGrid` spaces the cells evenly along each face of the cube before projecting them
+/// onto the sphere, which makes the cells at the centre of a face about 5 times larger than the
+/// cells at the corners. This grid instead spaces the cells evenly in the angle from the centre of
+/// the sphere, as in the equi-angular cubed sphere used by atmospheric models, which reduces the
+/// difference to about 1.3 times. Cells keep the same neighbours as in `CubeSphereGrid` and only
+/// their positions on the sphere change.
+///
+/// # Type Parameters
+/// - `T` - The type of data that the grid holds.
+///
+/// # Constant Parameters
+/// - `S` - The size of each side of each face.
+///
+/// # Iteration Order
+/// The grid is visited in the same order as `CubeSphereGrid`.
+///
+/// # Thread Safety
+/// The grid wraps a `CubeSphereGrid` and only changes where its points are placed, so it is `Send`
+/// and `Sync` in the same cases as a `CubeSphereGrid`.
+#[derive(Debug, PartialEq, Eq, Hash, Default)]
+pub struct EquiAngularCubeSphereGrid<T, const S: usize> {
+    /// The cube grid holding the data in the same layout.
+    grid: CubeSphereGrid<T, S>,
+}
+
+impl <T, const S: usize> From<CubeSphereGrid<T, S>> for EquiAngularCubeSphereGrid<T, S> {
+    fn from(grid: CubeSphereGrid<T, S>) -> Self {
+        Self {
+            grid,
+        }
+    }
+}
+
+impl <T, const S: usize> From<EquiAngularCubeSphereGrid<T, S>> for CubeSphereGrid<T, S> {
+    fn from(grid: EquiAngularCubeSphereGrid<T, S>) -> Self {
+        grid.grid
+    }
+}
+
+impl <T: Clone, const S: usize> Clone for EquiAngularCubeSphereGrid<T, S> {
+    fn clone(&self) -> Self {
+        Self {
+            grid: self.grid.clone(),
+        }
+    }
+
+    /// Copies the values of another grid into this grid without allocating.
+    fn clone_from(&mut self, source: &Self) {
+        self.grid.clone_from(&source.grid);
+    }
+}
+
+impl <T, const S: usize> EquiAngularCubeSphereGrid<T, S> {
+    /// Gets the values of the cells on a face in the same order as the points of the grid.
+    ///
+    /// This has the same layout as `CubeSphereGrid::face_slice`.
+    ///
+    /// - `face` - The face to get the values of.
+    pub fn face_slice(&self, face: CubeFace) -> &[T] {
+        self.grid.face_slice(face)
+    }
+
+    /// Gets mutable references to the values of the cells on a face in the same order as the
+    /// points of the grid.
+    ///
+    /// - `face` - The face to get the values of.
+    pub fn face_slice_mut(&mut self, face: CubeFace) -> &mut [T] {
+        self.grid.face_slice_mut(face)
+    }
 
-    use crate::{GridPoint, SurfaceGrid, sphere::{CubeSpherePoint, CubeFace, CubeSphereGrid}};
+    /// Gets the values of the cells on a face as bytes in the same order as `face_slice`.
+    ///
+    /// - `face` - The face to get the values of.
+    #[cfg(feature = "bytemuck")]
+    pub fn face_bytes(&self, face: CubeFace) -> &[u8] where T: bytemuck::Pod {
+        bytemuck::cast_slice(self.face_slice(face))
+    }
+
+    /// Gets the values of the cells on a face as mutable bytes in the same order as `face_slice`.
+    ///
+    /// - `face` - The face to get the values of.
+    #[cfg(feature = "bytemuck")]
+    pub fn face_bytes_mut(&mut self, face: CubeFace) -> &mut [u8] where T: bytemuck::Pod {
+        bytemuck::cast_slice_mut(self.face_slice_mut(face))
+    }
+}
 
-    use super::{RectangleSpherePoint, SpherePoint, RectangleSphereGrid};
+impl <T, const S: usize> SurfaceGrid<T> for EquiAngularCubeSphereGrid<T, S> {
+    type Point = EquiAngularCubeSpherePoint<S>;
 
+    fn from_fn<F: FnMut(&Self::Point) -> T>(mut f: F) -> Self {
+        CubeSphereGrid::from_fn(|point| f(&EquiAngularCubeSpherePoint::from(*point))).into()
+    }
+
+    fn from_fn_par<F: Fn(&Self::Point) -> T + Send + Sync>(f: F) -> Self where T: Send + Sync {
+        CubeSphereGrid::from_fn_par(|point| f(&EquiAngularCubeSpherePoint::from(*point))).into()
+    }
+
+    fn try_from_fn<X, F: FnMut(&Self::Point) -> Result<T, X>>(mut f: F) -> Result<Self, X> {
+        CubeSphereGrid::try_from_fn(|point| f(&EquiAngularCubeSpherePoint::from(*point))).map(Self::from)
+    }
+
+    fn try_from_fn_par<X: Send, F: Fn(&Self::Point) -> Result<T, X> + Send + Sync>(f: F) -> Result<Self, X> where T: Send + Sync {
+        CubeSphereGrid::try_from_fn_par(|point| f(&EquiAngularCubeSpherePoint::from(*point))).map(Self::from)
+    }
+
+    fn from_vec(values: Vec<T>) -> Option<Self> {
+        CubeSphereGrid::from_vec(values).map(Self::from)
+    }
+
+    fn set_from_fn<F: FnMut(&Self::Point) -> T>(&mut self, mut f: F) {
+        self.grid.set_from_fn(|point| f(&EquiAngularCubeSpherePoint::from(*point)))
+    }
+
+    fn set_from_fn_par<F: Fn(&Self::Point) -> T + Send + Sync>(&mut self, f: F) where T: Send + Sync {
+        self.grid.set_from_fn_par(|point| f(&EquiAngularCubeSpherePoint::from(*point)))
+    }
+
+    fn iter<'a>(&'a self) -> impl ExactSizeIterator<Item = (Self::Point, &'a T)> + DoubleEndedIterator where T: 'a {
+        self.grid.iter()
+            .map(|(point, value)| (point.into(), value))
+    }
+
+    fn par_iter<'a>(&'a self) -> impl IndexedParallelIterator<Item = (Self::Point, &'a T)> where T: 'a + Send + Sync {
+        self.grid.par_iter()
+            .map(|(point, value)| (point.into(), value))
+    }
+
+    fn par_iter_mut<'a>(&'a mut self) -> impl IndexedParallelIterator<Item = (Self::Point, &'a mut T)> where T: 'a + Send + Sync {
+        self.grid.par_iter_mut()
+            .map(|(point, value)| (point.into(), value))
+    }
+
+    fn points(&self) -> impl ExactSizeIterator<Item = Self::Point> + DoubleEndedIterator {
+        self.grid.points()
+            .map(EquiAngularCubeSpherePoint::from)
+    }
+
+    fn par_points(&self) -> impl IndexedParallelIterator<Item = Self::Point> {
+        self.grid.par_points()
+            .map(EquiAngularCubeSpherePoint::from)
+    }
+
+    fn par_map_to_vec<U: Send, F: Fn(&Self::Point, &T) -> U + Send + Sync>(&self, f: F) -> Vec<U> where T: Send + Sync {
+        self.grid.par_map_to_vec(|point, value| f(&EquiAngularCubeSpherePoint::from(*point), value))
+    }
+
+    fn to_index(&self, point: &Self::Point) -> usize {
+        self.grid.to_index(&point.point)
+    }
+
+    fn at(&self, point: &Self::Point) -> &T {
+        &self[point]
+    }
+
+    fn at_mut(&mut self, point: &Self::Point) -> &mut T {
+        &mut self[point]
+    }
+
+    fn surface_area(&self, scale: f64) -> f64 {
+        4.0 * PI * scale * scale
+    }
+
+    fn topology(&self) -> Topology {
+        Topology::uniform(Surface::Sphere, 6, S, S)
+    }
+}
+
+impl <T, const S: usize> Index<EquiAngularCubeSpherePoint<S>> for EquiAngularCubeSphereGrid<T, S> {
+    type Output = T;
+
+    fn index(&self, index: EquiAngularCubeSpherePoint<S>) -> &Self::Output {
+        &self.grid[index.point]
+    }
+}
+
+impl <T, const S: usize> IndexMut<EquiAngularCubeSpherePoint<S>> for EquiAngularCubeSphereGrid<T, S> {
+    fn index_mut(&mut self, index: EquiAngularCubeSpherePoint<S>) -> &mut Self::Output {
+        &mut self.grid[index.point]
+    }
+}
+
+impl <T, const S: usize> Index<&EquiAngularCubeSpherePoint<S>> for EquiAngularCubeSphereGrid<T, S> {
+    type Output = T;
+
+    fn index(&self, index: &EquiAngularCubeSpherePoint<S>) -> &Self::Output {
+        &self[*index]
+    }
+}
+
+impl <T, const S: usize> IndexMut<&EquiAngularCubeSpherePoint<S>> for EquiAngularCubeSphereGrid<T, S> {
+    fn index_mut(&mut self, index: &EquiAngularCubeSpherePoint<S>) -> &mut Self::Output {
+        &mut self[*index]
+    }
+}
+
+impl <T, const S: usize> FromIterator<T> for EquiAngularCubeSphereGrid<T, S> {
+    /// Creates a grid from the values of its cells in the same order as `points`.
+    ///
+    /// # Panics
+    /// Panics if the iterator does not yield exactly one value for each cell in the grid.
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        Self::from_vec(iter.into_iter().collect())
+            .expect("the iterator must yield one value for each cell in the grid")
+    }
+}
+
+impl <T, const S: usize> IntoIterator for EquiAngularCubeSphereGrid<T, S> {
+    type Item = (EquiAngularCubeSpherePoint<S>, T);
+
+    type IntoIter = vec::IntoIter<Self::Item>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        let data: Vec<_> = self.grid.into_iter()
+            .map(|(point, value)| (point.into(), value))
+            .collect();
+
+        data.into_iter()
+    }
+}
+
+#[cfg(feature = "serde")]
+impl <T: serde::Serialize, const S: usize> serde::Serialize for EquiAngularCubeSphereGrid<T, S> {
+    /// Serializes the values in this grid as a sequence in the same order as `iter`.
+    fn serialize<R: serde::Serializer>(&self, serializer: R) -> Result<R::Ok, R::Error> {
+        self.grid.serialize(serializer)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl <'de, T: serde::Deserialize<'de>, const S: usize> serde::Deserialize<'de> for EquiAngularCubeSphereGrid<T, S> {
+    /// Deserializes a grid from a sequence of values in the same order as `iter`.
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        CubeSphereGrid::deserialize(deserializer).map(Self::from)
+    }
+}
+
+/// A point on an `EquiAngularCubeSphereGrid`.
+///
+/// # Constant Parameters
+/// - `S` - The size of each side of each face.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct EquiAngularCubeSpherePoint<const S: usize> {
+    /// The point on the cube with the same face and position.
+    point: CubeSpherePoint<S>,
+}
+
+impl <const S: usize> From<CubeSpherePoint<S>> for EquiAngularCubeSpherePoint<S> {
+    fn from(point: CubeSpherePoint<S>) -> Self {
+        Self {
+            point,
+        }
+    }
+}
+
+impl <const S: usize> From<EquiAngularCubeSpherePoint<S>> for CubeSpherePoint<S> {
+    fn from(point: EquiAngularCubeSpherePoint<S>) -> Self {
+        point.point
+    }
+}
+
+impl <const S: usize> EquiAngularCubeSpherePoint<S> {
+    /// Creates a new `EquiAngularCubeSpherePoint` checking that the position lies on the face.
+    ///
+    /// This returns an error if either coordinate is `S` or greater, in the same way as
+    /// `CubeSpherePoint::try_new`.
+    ///
+    /// - `face` - The face on which the point lies.
+    /// - `x` - The X position on the face.
+    /// - `y` - The Y position on the face.
+    pub fn try_new(face: CubeFace, x: u16, y: u16) -> Result<Self, OutOfBounds> {
+        CubeSpherePoint::try_new(face, x, y).map(Self::from)
+    }
+
+    /// Gets the position of the centre of this cell in 3D space.
+    ///
+    /// - `scale` - The scale of the 3D object.
+    pub fn cell_center(&self, scale: f64) -> (f64, f64, f64) {
+        Self::face_position(self.point.face, self.point.x as f64 + 0.5, self.point.y as f64 + 0.5, scale)
+    }
+
+    /// Gets the position of a corner of this cell in 3D space.
+    ///
+    /// The corners are numbered in the same way as `CubeSpherePoint::cell_corner`.
+    ///
+    /// - `corner` - The corner to get.
+    /// - `scale` - The scale of the 3D object.
+    ///
+    /// # Panics
+    /// Panics if `corner` is greater than 3.
+    pub fn cell_corner(&self, corner: u8, scale: f64) -> (f64, f64, f64) {
+        let (x, y) = corner_offset(corner);
+
+        Self::face_position(self.point.face, self.point.x as f64 + x, self.point.y as f64 + y, scale)
+    }
+
+    /// Gets the solid angle covered by this cell in steradians.
+    ///
+    /// This is the area of the cell on a sphere with a radius of 1. The solid angles of all of the
+    /// cells on the grid add up to `4 * PI`.
+    pub fn solid_angle(&self) -> f64 {
+        // The solid angle of the rectangle from the centre of the face to (u, v) on the plane
+        // that is a distance of 1 from the centre of the sphere.
+        let area = |x: f64, y: f64| {
+            let u = equi_angular_to_plane(x * 2.0 / S as f64 - 1.0);
+            let v = equi_angular_to_plane(y * 2.0 / S as f64 - 1.0);
+
+            (u * v / (1.0 + u * u + v * v).sqrt()).atan()
+        };
+
+        let (x, y) = (self.point.x as f64, self.point.y as f64);
+
+        area(x + 1.0, y + 1.0) - area(x, y + 1.0) - area(x + 1.0, y) + area(x, y)
+    }
+
+    /// Gets the position in 3D space of a continuous position on a face.
+    ///
+    /// - `face` - The face on which the position lies.
+    /// - `x` - The X position on the face.
+    /// - `y` - The Y position on the face.
+    /// - `scale` - The scale of the 3D object.
+    fn face_position(face: CubeFace, x: f64, y: f64, scale: f64) -> (f64, f64, f64) {
+        let (x_axis, y_axis, normal) = CubeSpherePoint::<S>::face_axes(face);
+
+        let u = equi_angular_to_plane(x * 2.0 / S as f64 - 1.0);
+        let v = equi_angular_to_plane(y * 2.0 / S as f64 - 1.0);
+
+        let axis = |i: usize| x_axis[i] as f64 * u + y_axis[i] as f64 * v + normal[i] as f64;
+
+        let (x, y, z) = normalize((axis(0), axis(1), axis(2)));
+
+        (x * scale, y * scale, z * scale)
+    }
+}
+
+impl <const S: usize> GridPoint for EquiAngularCubeSpherePoint<S> {
+    fn up(&self) -> Self {
+        self.point.up().into()
+    }
+
+    fn down(&self) -> Self {
+        self.point.down().into()
+    }
+
+    fn left(&self) -> Self {
+        self.point.left().into()
+    }
+
+    fn right(&self) -> Self {
+        self.point.right().into()
+    }
+
+    fn moore_neighbours(&self) -> impl Iterator<Item = Self> {
+        self.point.moore_neighbours().map(Self::from)
+    }
+
+    /// Gets the position of the point in 3D space.
+    ///
+    /// This is the position of the corner of the cell with the smallest X and Y coordinates, the
+    /// same as `cell_corner(0, scale)`. Use `cell_center` to get the centre of the cell.
+    ///
+    /// - `scale` - The scale of the 3D object.
+    fn position(&self, scale: f64) -> (f64, f64, f64) {
+        Self::face_position(self.point.face, self.point.x as f64, self.point.y as f64, scale)
+    }
+}
+
+impl <const S: usize> SpherePoint for EquiAngularCubeSpherePoint<S> {
+    fn center_position(&self, scale: f64) -> (f64, f64, f64) {
+        self.cell_center(scale)
+    }
+
+    fn from_geographic(latitude: f64, longitude: f64) -> Self {
+        let (face, x, y, z) = nearest_cube_face::<S>(latitude, longitude);
+
+        let u = equi_angular_from_plane(x / z);
+        let v = equi_angular_from_plane(y / z);
+
+        let x = ((u + 1.0) / 2.0 * S as f64).clamp(0.0, S as f64);
+        let y = ((v + 1.0) / 2.0 * S as f64).clamp(0.0, S as f64);
+
+        CubeSpherePoint::new(face, (x as u16).min(S as u16 - 1), (y as u16).min(S as u16 - 1)).into()
+    }
+
+    fn latitude(&self) -> f64 {
+        let (x, y, z) = self.position(1.0);
+
+        y.atan2((x * x + z * z).sqrt())
+    }
+
+    fn longitude(&self) -> f64 {
+        let (x, _, z) = self.position(1.0);
+
+        x.atan2(z).rem_euclid(2.0 * PI)
+    }
+}
+
+/// Checks if the centre of the cell of a point lies within a range of geographic coordinates.
+///
+/// - `point` - The point to check.
+/// - `lat_range` - The inclusive range of latitudes in radians.
+/// - `lon_range` - The inclusive range of longitudes in radians. This wraps around the sphere if
+///   the start is greater than the end.
+pub(crate) fn in_region<P: SpherePoint>(point: &P, lat_range: (f64, f64), lon_range: (f64, f64)) -> bool {
+    let (longitude, latitude) = point.center_sphere_coordinates();
+
+    if latitude < lat_range.0.min(lat_range.1) || latitude > lat_range.0.max(lat_range.1) {
+        return false;
+    }
+
+    if lon_range.1 - lon_range.0 >= PI * 2.0 {
+        return true;
+    }
+
+    let longitude = longitude.rem_euclid(PI * 2.0);
+    let start = lon_range.0.rem_euclid(PI * 2.0);
+    let end = lon_range.1.rem_euclid(PI * 2.0);
+
+    if start <= end {
+        longitude >= start && longitude <= end
+    } else {
+        longitude >= start || longitude <= end
+    }
+}
+
+/// Extracts the lines where a value crosses a threshold using marching squares.
+///
+/// The squares are the loops of four neighbouring cells whose centres form a convex
+/// quadrilateral so they continue across any seams in the grid. Where three cells meet at the
+/// corners of a cube there is no square so there is a small gap in the lines.
+///
+/// - `grid` - The grid to extract the lines from.
+/// - `threshold` - The value to extract the lines at.
+/// - `key` - The function used to get the value of each cell.
+pub(crate) fn contour<T, G: SurfaceGrid<T>, K: Fn(&T) -> f64>(grid: &G, threshold: f64, key: K) -> Vec<Vec<(f64, f64)>> where G::Point: SpherePoint {
+    let neighbours = |point: &G::Point| [point.up(), point.down(), point.left(), point.right()];
+
+    // Find each square once with its corners in order around it.
+    let mut found = HashSet::new();
+    let mut squares = Vec::new();
+
+    for point in grid.points() {
+        let around = neighbours(&point);
+
+        for (i, a) in around.iter().enumerate() {
+            for b in &around[i + 1..] {
+                for c in neighbours(a) {
+                    if c == point || c == *a || c == *b || a == b || !neighbours(b).contains(&c) {
+                        continue;
+                    }
+
+                    let square = [point.clone(), a.clone(), c, b.clone()];
+
+                    let mut indices = square.each_ref().map(|corner| grid.to_index(corner));
+                    indices.sort();
+
+                    if found.insert(indices) {
+                        squares.push(square);
+                    }
+                }
+            }
+        }
+    }
+
+    let mut crossings = HashMap::new();
+    let mut segments = Vec::new();
+
+    for square in squares {
+        let positions = square.each_ref().map(|corner| corner.center_position(1.0));
+
+        // Skip loops that cross over themselves, such as those going over the poles.
+        let turns = (0..4).map(|i| {
+            let (a, b, c) = (positions[(i + 3) % 4], positions[i], positions[(i + 1) % 4]);
+
+            dot(cross(subtract(c, b), subtract(a, b)), b)
+        });
+
+        if !(turns.clone().all(|turn| turn > 0.0) || turns.clone().all(|turn| turn < 0.0)) {
+            continue;
+        }
+
+        let values = square.each_ref().map(|corner| key(&grid[corner.clone()]));
+        let above = values.map(|value| value >= threshold);
+
+        // Find the position where the value crosses the threshold along each edge of the square.
+        let edges: Vec<_> = (0..4)
+            .filter(|i| above[*i] != above[(i + 1) % 4])
+            .map(|i| {
+                let j = (i + 1) % 4;
+
+                let (a, b) = (grid.to_index(&square[i]), grid.to_index(&square[j]));
+                let edge = (a.min(b), a.max(b));
+
+                crossings.entry(edge).or_insert_with(|| {
+                    let t = (threshold - values[i]) / (values[j] - values[i]);
+
+                    let position = add(positions[i], scale(subtract(positions[j], positions[i]), t));
+                    let (x, y, z) = normalize(position);
+
+                    (y.asin(), x.atan2(z).rem_euclid(PI * 2.0))
+                });
+
+                (i, edge)
+            })
+            .collect();
+
+        if edges.len() == 2 {
+            segments.push((edges[0].1, edges[1].1));
+        } else if edges.len() == 4 {
+            // In a saddle the average value decides whether the cells above the threshold are
+            // joined through the centre of the square.
+            let centre = values.iter().sum::<f64>() / 4.0 >= threshold;
+
+            // Each edge starts at the corner with the same index so pair off the edges on either
+            // side of the corners that are cut off.
+            if above[0] != centre {
+                segments.push((edges[3].1, edges[0].1));
+                segments.push((edges[1].1, edges[2].1));
+            } else {
+                segments.push((edges[0].1, edges[1].1));
+                segments.push((edges[2].1, edges[3].1));
+            }
+        }
+    }
+
+    // Join the segments into lines.
+    let mut joined: HashMap<_, Vec<usize>> = HashMap::new();
+
+    for (i, (a, b)) in segments.iter().enumerate() {
+        joined.entry(*a).or_default().push(i);
+        joined.entry(*b).or_default().push(i);
+    }
+
+    let mut used = vec![false; segments.len()];
+    let mut lines = Vec::new();
+
+    // Start with the ends of open lines and then follow the remaining closed loops.
+    let starts: Vec<_> = segments.iter()
+        .flat_map(|(a, b)| [*a, *b])
+        .filter(|edge| joined[edge].len() == 1)
+        .chain(segments.iter().map(|(a, _)| *a))
+        .collect();
+
+    for start in starts {
+        let mut line = vec![start];
+        let mut current = start;
+
+        while let Some(&segment) = joined[&current].iter().find(|segment| !used[**segment]) {
+            used[segment] = true;
+
+            let (a, b) = segments[segment];
+            current = if a == current { b } else { a };
+
+            line.push(current);
+        }
+
+        if line.len() > 1 {
+            lines.push(line.into_iter().map(|edge| crossings[&edge]).collect());
+        }
+    }
+
+    lines
+}
+
+/// Checks if a position lies inside a polygon on the surface of a sphere.
+///
+/// The edges of the polygon are great circle arcs between consecutive vertices with the last
+/// vertex joined back to the first. This sums the angles that the edges turn through as seen from
+/// the position, which is `2 * PI` when the edges separate the position from the point opposite it
+/// and `0` otherwise, so the polygon may cross the antimeridian or surround a pole. Only one of the
+/// two points can be inside a polygon that fits within the hemisphere centred on `centre` so the
+/// position is inside if it is also in that hemisphere.
+///
+/// - `position` - The position to check, which must have a length of 1.
+/// - `vertices` - The positions of the vertices of the polygon, which must have a length of 1.
+/// - `centre` - The direction of the centre of the polygon.
+fn in_spherical_polygon(position: (f64, f64, f64), vertices: &[(f64, f64, f64)], centre: (f64, f64, f64)) -> bool {
+    if dot(position, centre) <= 0.0 {
+        return false;
+    }
+
+    let winding: f64 = vertices.iter()
+        .zip(vertices.iter().cycle().skip(1))
+        .map(|(a, b)| {
+            let sin = dot(position, cross(*a, *b));
+            let cos = dot(*a, *b) - dot(*a, position) * dot(*b, position);
+
+            sin.atan2(cos)
+        })
+        .sum();
+
+    winding.abs() > PI
+}
+
+/// Gets the position of a geographic coordinate on a sphere with a radius of 1.
+///
+/// - `(latitude, longitude)` - The latitude and longitude in radians.
+fn geographic_position((latitude, longitude): (f64, f64)) -> (f64, f64, f64) {
+    (latitude.cos() * longitude.sin(), latitude.sin(), latitude.cos() * longitude.cos())
+}
+
+/// Wraps a position on an equirectangular grid into the grid.
+///
+/// `x` wraps around the sphere and `y` continues over the poles onto the other side of the sphere
+/// as described by `RectangleSpherePoint::new`.
+///
+/// - `x` - The X position.
+/// - `y` - The Y position.
+/// - `width` - The width of the grid.
+/// - `height` - The height of the grid.
+fn rect_wrap(x: i64, y: i64, width: u32, height: u32) -> (u32, u32) {
+    let (width, height) = (width as i64, height as i64);
+
+    let y = y.rem_euclid(2 * height);
+
+    let (x, y) = if y >= height {
+        (x + width / 2, 2 * height - 1 - y)
+    } else {
+        (x, y)
+    };
+
+    (x.rem_euclid(width) as u32, y as u32)
+}
+
+/// Moves up from a position on an equirectangular grid.
+///
+/// - `x` - The X position, which must be on the grid.
+/// - `y` - The Y position, which must be on the grid.
+/// - `width` - The width of the grid.
+/// - `height` - The height of the grid.
+fn rect_up(x: u32, y: u32, width: u32, height: u32) -> (u32, u32) {
+    if x >= width / 2 {
+        if y == height - 1 {
+            (rect_opposite(x, width), height - 1)
+        } else {
+            (x, y + 1)
+        }
+    } else if y == 0 {
+        (rect_opposite(x, width), 0)
+    } else {
+        (x, y - 1)
+    }
+}
+
+/// Moves down from a position on an equirectangular grid.
+///
+/// - `x` - The X position, which must be on the grid.
+/// - `y` - The Y position, which must be on the grid.
+/// - `width` - The width of the grid.
+/// - `height` - The height of the grid.
+fn rect_down(x: u32, y: u32, width: u32, height: u32) -> (u32, u32) {
+    if x < width / 2 {
+        if y == height - 1 {
+            (rect_opposite(x, width), height - 1)
+        } else {
+            (x, y + 1)
+        }
+    } else if y == 0 {
+        (rect_opposite(x, width), 0)
+    } else {
+        (x, y - 1)
+    }
+}
+
+/// Gets the X position on the opposite side of the sphere on an equirectangular grid.
+///
+/// This uses 64 bit arithmetic so that `x + width / 2` cannot overflow for large grids.
+///
+/// - `x` - The X position, which must be on the grid.
+/// - `width` - The width of the grid.
+fn rect_opposite(x: u32, width: u32) -> u32 {
+    ((x as u64 + width as u64 / 2) % width as u64) as u32
+}
+
+/// Gets the position in 3D space of a continuous position on an equirectangular grid.
+///
+/// - `x` - The X position on the grid.
+/// - `y` - The Y position on the grid.
+/// - `width` - The width of the grid.
+/// - `height` - The height of the grid.
+/// - `scale` - The scale of the 3D object.
+fn rect_position(x: f64, y: f64, width: u32, height: u32, scale: f64) -> (f64, f64, f64) {
+    let lat = -(y / height as f64 * PI - PI / 2.0);
+    let long = x / width as f64 * PI * 2.0;
+
+    let y = scale * lat.sin();
+    let radius = scale * lat.cos();
+
+    let x = radius * long.sin();
+    let z = radius * long.cos();
+
+    (x, y, z)
+}
+
+/// Gets the distance along a Hilbert curve covering a square grid of a position on the grid.
+///
+/// - `x` - The X position.
+/// - `y` - The Y position.
+/// - `size` - The size of the side of the square, which must be a power of two.
+pub(crate) fn hilbert_index(x: u32, y: u32, size: u64) -> u64 {
+    let (mut x, mut y) = (x as u64, y as u64);
+    let mut index = 0;
+    let mut s = size / 2;
+
+    while s > 0 {
+        let rx = (x & s > 0) as u64;
+        let ry = (y & s > 0) as u64;
+
+        index += s * s * ((3 * rx) ^ ry);
+
+        // Rotate the quadrant so that the curve inside it starts and ends in the right corners.
+        if ry == 0 {
+            if rx == 1 {
+                x = size - 1 - x;
+                y = size - 1 - y;
+            }
+
+            std::mem::swap(&mut x, &mut y);
+        }
+
+        s /= 2;
+    }
+
+    index
+}
+
+/// Gets the offset of a corner of a cell from the corner with the smallest coordinates.
+///
+/// - `corner` - The corner number.
+fn corner_offset(corner: u8) -> (f64, f64) {
+    match corner {
+        0 => (0.0, 0.0),
+        1 => (1.0, 0.0),
+        2 => (1.0, 1.0),
+        3 => (0.0, 1.0),
+        _ => panic!("Invalid corner {}, expected a value from 0 to 3", corner),
+    }
+}
+
+/// Finds the face of the cube that lies over a geographic coordinate.
+///
+/// This returns the face and the position on a sphere with a radius of 1 along the X and Y axes
+/// of the face and its normal.
+///
+/// - `latitude` - The latitude in radians.
+/// - `longitude` - The longitude in radians.
+fn nearest_cube_face<const S: usize>(latitude: f64, longitude: f64) -> (CubeFace, f64, f64, f64) {
+    let position = [
+        latitude.cos() * longitude.sin(),
+        latitude.sin(),
+        latitude.cos() * longitude.cos()
+    ];
+
+    let normal = (0..3)
+        .max_by(|a, b| position[*a].abs().total_cmp(&position[*b].abs()))
+        .unwrap();
+
+    let face = match (normal, position[normal] > 0.0) {
+        (0, true) => CubeFace::Right,
+        (0, false) => CubeFace::Left,
+        (1, true) => CubeFace::Top,
+        (1, false) => CubeFace::Bottom,
+        (_, true) => CubeFace::Front,
+        (_, false) => CubeFace::Back,
+    };
+
+    let (x_axis, y_axis, normal) = CubeSpherePoint::<S>::face_axes(face);
+
+    let along = |axis: [i32; 3]| (0..3).map(|i| position[i] * axis[i] as f64).sum::<f64>();
+
+    (face, along(x_axis), along(y_axis), along(normal))
+}
+
+/// Maps an equi-angular position on a face of a cube to the position on the face where the same
+/// direction from the centre of the sphere crosses it.
+///
+/// - `x` - The equi-angular position from -1 to 1.
+fn equi_angular_to_plane(x: f64) -> f64 {
+    (x * PI / 4.0).tan()
+}
+
+/// Maps a position on a face of a cube to its equi-angular position, reversing
+/// `equi_angular_to_plane`.
+///
+/// - `x` - The position on the face from -1 to 1.
+fn equi_angular_from_plane(x: f64) -> f64 {
+    x.atan() * 4.0 / PI
+}
+
+/// Maps a position on a face of a cube onto the sphere so that areas are preserved.
+///
+/// This is the projection of Roşca and Plonka. The face is split into four triangles around its
+/// centre and the lines through the centre of the face are mapped so that each triangle covers the
+/// same area as it does on the face.
+///
+/// This returns the position on a sphere with a radius of 1 along the X and Y axes of the face
+/// and its normal.
+///
+/// - `x` - The X position on the face from -1 to 1.
+/// - `y` - The Y position on the face from -1 to 1.
+fn equal_area_from_square(x: f64, y: f64) -> (f64, f64, f64) {
+    if x == 0.0 && y == 0.0 {
+        return (0.0, 0.0, 1.0);
+    }
+
+    let swap = y.abs() > x.abs();
+    let (major, minor) = if swap { (y, x) } else { (x, y) };
+
+    // The angle around the centre of the face in the Lambert azimuthal projection at which the
+    // triangle covers the same area as it does on the face.
+    let c = PI / 12.0 * minor / major.abs();
+    let angle = (2.0f64.sqrt() * c.sin()).atan2(2.0f64.sqrt() * c.cos() - 1.0);
+
+    // Scale the distance from the centre so that the edge of the face lands on the edge of the
+    // face on the sphere.
+    let distance = major.abs() * edge_distance(angle);
+
+    let height = 1.0 - distance * distance / 2.0;
+    let radius = distance * (1.0 - distance * distance / 4.0).sqrt();
+
+    let major = radius * angle.cos() * major.signum();
+    let minor = radius * angle.sin();
+
+    if swap {
+        (minor, major, height)
+    } else {
+        (major, minor, height)
+    }
+}
+
+/// Maps a position on the sphere onto a face of a cube, reversing `equal_area_from_square`.
+///
+/// This returns the X and Y positions on the face from -1 to 1.
+///
+/// - `x` - The position along the X axis of the face.
+/// - `y` - The position along the Y axis of the face.
+/// - `z` - The position along the normal of the face.
+fn equal_area_to_square(x: f64, y: f64, z: f64) -> (f64, f64) {
+    let length = (x * x + y * y + z * z).sqrt();
+    let (x, y, z) = (x / length, y / length, z / length);
+
+    if x == 0.0 && y == 0.0 {
+        return (0.0, 0.0);
+    }
+
+    let swap = y.abs() > x.abs();
+    let (major, minor) = if swap { (y, x) } else { (x, y) };
+
+    let radius_squared = major * major + minor * minor;
+
+    // This avoids the loss of precision in `2 * (1 - z)` near the centre of the face.
+    let distance = (2.0 * radius_squared / (1.0 + z)).sqrt();
+    let angle = minor.atan2(major.abs());
+
+    let c = angle - (angle.sin() / 2.0f64.sqrt()).asin();
+
+    let major = distance / edge_distance(angle) * major.signum();
+    let minor = 12.0 / PI * c * major.abs();
+
+    if swap {
+        (minor, major)
+    } else {
+        (major, minor)
+    }
+}
+
+/// Gets the distance from the centre of a face of a cube to its edge in the Lambert azimuthal
+/// projection of a sphere with a radius of 1 centred on the face.
+///
+/// - `angle` - The angle from the X axis of the face, which must be from `-PI / 4` to `PI / 4`.
+fn edge_distance(angle: f64) -> f64 {
+    let cos = angle.cos();
+
+    (2.0 * (1.0 - cos / (1.0 + cos * cos).sqrt())).sqrt()
+}
+
+/// Evaluates a sum of real spherical harmonics.
+///
+/// - `coefficients` - The degree, order and coefficient of each term.
+/// - `(x, y, z)` - The position on the unit sphere to evaluate the harmonics at.
+fn spherical_harmonic_sum(coefficients: &[(i32, i32, f64)], (x, y, z): (f64, f64, f64)) -> f64 {
+    let longitude = x.atan2(z);
+
+    coefficients.iter()
+        .map(|&(l, m, coefficient)| {
+            assert!(l >= 0 && m.abs() <= l, "the order of a spherical harmonic must not be larger than its degree");
+
+            let order = m.unsigned_abs();
+
+            // (l - |m|)! / (l + |m|)!
+            let ratio = (l as u32 - order + 1..=l as u32 + order)
+                .fold(1.0, |ratio, i| ratio / i as f64);
+
+            let normalization = ((2 * l + 1) as f64 / (4.0 * PI) * ratio).sqrt();
+
+            let azimuthal = match m {
+                0 => 1.0,
+                m if m > 0 => 2f64.sqrt() * (m as f64 * longitude).cos(),
+                m => 2f64.sqrt() * (-m as f64 * longitude).sin(),
+            };
+
+            coefficient * normalization * associated_legendre(l as u32, order, y) * azimuthal
+        })
+        .sum()
+}
+
+/// Evaluates an associated Legendre polynomial without the Condon-Shortley phase.
+///
+/// - `l` - The degree of the polynomial.
+/// - `m` - The order of the polynomial, which must not be greater than `l`.
+/// - `x` - The value to evaluate the polynomial at between -1 and 1.
+fn associated_legendre(l: u32, m: u32, x: f64) -> f64 {
+    // P(m, m) = (2m - 1)!! (1 - x^2)^(m / 2)
+    let sin = (1.0 - x * x).max(0.0).sqrt();
+    let p_mm = (1..=m).fold(1.0, |p, i| p * (2 * i - 1) as f64 * sin);
+
+    if l == m {
+        return p_mm;
+    }
+
+    // Raise the degree using (l - m) P(l, m) = x (2l - 1) P(l - 1, m) - (l + m - 1) P(l - 2, m).
+    let mut previous = p_mm;
+    let mut current = x * (2 * m + 1) as f64 * p_mm;
+
+    for degree in m + 2..=l {
+        let next = (x * (2 * degree - 1) as f64 * current - (degree + m - 1) as f64 * previous) / (degree - m) as f64;
+
+        previous = current;
+        current = next;
+    }
+
+    current
+}
+
+/// Adds two vectors.
+pub(crate) fn add((ax, ay, az): (f64, f64, f64), (bx, by, bz): (f64, f64, f64)) -> (f64, f64, f64) {
+    (ax + bx, ay + by, az + bz)
+}
+
+/// Subtracts one vector from another.
+pub(crate) fn subtract((ax, ay, az): (f64, f64, f64), (bx, by, bz): (f64, f64, f64)) -> (f64, f64, f64) {
+    (ax - bx, ay - by, az - bz)
+}
+
+/// Multiplies a vector by a scalar.
+pub(crate) fn scale((x, y, z): (f64, f64, f64), scale: f64) -> (f64, f64, f64) {
+    (x * scale, y * scale, z * scale)
+}
+
+/// Computes the dot product of two vectors.
+pub(crate) fn dot((ax, ay, az): (f64, f64, f64), (bx, by, bz): (f64, f64, f64)) -> f64 {
+    ax * bx + ay * by + az * bz
+}
+
+/// Computes the cross product of two vectors.
+pub(crate) fn cross((ax, ay, az): (f64, f64, f64), (bx, by, bz): (f64, f64, f64)) -> (f64, f64, f64) {
+    (ay * bz - az * by, az * bx - ax * bz, ax * by - ay * bx)
+}
+
+/// Scales a vector to have a length of 1.
+pub(crate) fn normalize((x, y, z): (f64, f64, f64)) -> (f64, f64, f64) {
+    let length = (x * x + y * y + z * z).sqrt();
+
+    (x / length, y / length, z / length)
+}
+
+/// The order in which the faces of a `CubeSphereGrid` are iterated over.
+pub(crate) const FACE_ORDER: [CubeFace; 6] = [
+    CubeFace::Top,
+    CubeFace::Left,
+    CubeFace::Front,
+    CubeFace::Right,
+    CubeFace::Back,
+    CubeFace::Bottom,
+];
+
+/// A face of a cube.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[repr(u32)] // For better alignment.
+pub enum CubeFace {
+    /// The face facing the positive Z direction.
+    Front,
+    /// The face facing the negative Z direction.
+    Back,
+    /// The face facing the negative X direction.
+    Left,
+    /// The face facing the positive X direction.
+    Right,
+    /// The face facing the positive Y direction.
+    Top,
+    /// The face facing the negative Y direction.
+    Bottom,
+}
+
+/// An axis in 3D space.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Axis {
+    /// The X axis.
+    X,
+    /// The Y axis. This passes through both poles.
+    Y,
+    /// The Z axis.
+    Z,
+}
+
+impl Axis {
+    /// Rotates a position by a quarter turn about this axis following the right hand rule.
+    ///
+    /// - `x` - The X position.
+    /// - `y` - The Y position.
+    /// - `z` - The Z position.
+    fn rotate(&self, x: i32, y: i32, z: i32) -> (i32, i32, i32) {
+        match self {
+            Axis::X => (x, -z, y),
+            Axis::Y => (z, y, -x),
+            Axis::Z => (-y, x, z),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::{collections::{HashMap, HashSet, VecDeque, hash_map::Entry}, f64::consts::{FRAC_1_SQRT_2, PI}, hint::black_box};
+
+    use approx::assert_relative_eq;
+    use itertools::Itertools;
+
+    use crate::{GridPoint, SurfaceGrid, ThreadSafeGrid, OutOfBounds, Direction, Neighbourhood, Surface, Topology, DIAGONAL_ORDER, gather_neighbours, gather_neighbours_radius, flat::FlatGrid, torus::TorusGrid, sphere::{CubeSpherePoint, CubeFace, CubeSphereGrid, BitCubeSphereGrid, Axis}};
+
+    use super::{RectangleSpherePoint, SpherePoint, RectangleSphereGrid, DynRectangleSpherePoint, EqualAreaCubeSphereGrid, EqualAreaCubeSpherePoint, EquiAngularCubeSphereGrid, EquiAngularCubeSpherePoint, cross, dot, normalize, equal_area_from_square, equal_area_to_square, equi_angular_to_plane, equi_angular_from_plane, FACE_ORDER};
+
+    /// Checks that a position lies inside the spherical quad formed by four corners.
+    fn inside_quad(corners: [(f64, f64, f64); 4], (x, y, z): (f64, f64, f64)) -> bool {
+        let sides: Vec<_> = (0..4).map(|i| {
+            let (ax, ay, az) = corners[i];
+            let (bx, by, bz) = corners[(i + 1) % 4];
+
+            let (nx, ny, nz) = (ay * bz - az * by, az * bx - ax * bz, ax * by - ay * bx);
+
+            nx * x + ny * y + nz * z
+        }).collect();
+
+        sides.iter().all(|side| *side > 0.0) || sides.iter().all(|side| *side < 0.0)
+    }
+
+    #[test]
+    fn test_rect_point_up_middle() {
+        let point: RectangleSpherePoint<10, 10> = RectangleSpherePoint::new(3, 4);
+
+        assert_eq!(RectangleSpherePoint::new(3, 3), point.up());
+    }
+    
+    #[test]
+    fn test_rect_point_up_top_left() {
+        let point: RectangleSpherePoint<10, 10> = RectangleSpherePoint::new(0, 0);
+
+        assert_eq!(RectangleSpherePoint::new(5, 0), point.up());
+    }
+    
+    #[test]
+    fn test_rect_point_up_top_right() {
+        let point: RectangleSpherePoint<10, 10> = RectangleSpherePoint::new(9, 0);
+
+        assert_eq!(RectangleSpherePoint::new(9, 1), point.up());
+    }
+    
+    #[test]
+    fn test_rect_point_up_bottom_left() {
+        let point: RectangleSpherePoint<10, 10> = RectangleSpherePoint::new(0, 9);
+
+        assert_eq!(RectangleSpherePoint::new(0, 8), point.up());
+    }
+    
+    #[test]
+    fn test_rect_point_up_bottom_right() {
+        let point: RectangleSpherePoint<10, 10> = RectangleSpherePoint::new(9, 9);
+
+        assert_eq!(RectangleSpherePoint::new(4, 9), point.up());
+    }
+    
+    #[test]
+    fn test_rect_point_down_middle() {
+        let point: RectangleSpherePoint<10, 10> = RectangleSpherePoint::new(3, 4);
+
+        assert_eq!(RectangleSpherePoint::new(3, 5), point.down());
+    }
+    
+    #[test]
+    fn test_rect_point_down_top_left() {
+        let point: RectangleSpherePoint<10, 10> = RectangleSpherePoint::new(0, 0);
+
+        assert_eq!(RectangleSpherePoint::new(0, 1), point.down());
+    }
+    
+    #[test]
+    fn test_rect_point_down_top_right() {
+        let point: RectangleSpherePoint<10, 10> = RectangleSpherePoint::new(9, 0);
+
+        assert_eq!(RectangleSpherePoint::new(4, 0), point.down());
+    }
+    
+    #[test]
+    fn test_rect_point_down_bottom_left() {
+        let point: RectangleSpherePoint<10, 10> = RectangleSpherePoint::new(0, 9);
+
+        assert_eq!(RectangleSpherePoint::new(5, 9), point.down());
+    }
+    
+    #[test]
+    fn test_rect_point_down_bottom_right() {
+        let point: RectangleSpherePoint<10, 10> = RectangleSpherePoint::new(9, 9);
+
+        assert_eq!(RectangleSpherePoint::new(9, 8), point.down());
+    }
+
+    #[test]
+    fn test_rect_point_left_middle() {
+        let point: RectangleSpherePoint<10, 10> = RectangleSpherePoint::new(5, 5);
+
+        assert_eq!(RectangleSpherePoint::new(4, 5), point.left());
+    }
+    
+    #[test]
+    fn test_rect_point_left_left() {
+        let point: RectangleSpherePoint<10, 10> = RectangleSpherePoint::new(0, 5);
+
+        assert_eq!(RectangleSpherePoint::new(9, 5), point.left());
+    }
+   
+    #[test]
+    fn test_rect_point_left_right() {
+        let point: RectangleSpherePoint<10, 10> = RectangleSpherePoint::new(9, 5);
+
+        assert_eq!(RectangleSpherePoint::new(8, 5), point.left());
+    }
+
+    #[test]
+    fn test_rect_point_right_middle() {
+        let point: RectangleSpherePoint<10, 10> = RectangleSpherePoint::new(5, 5);
+
+        assert_eq!(RectangleSpherePoint::new(6, 5), point.right());
+    }
+    
+    #[test]
+    fn test_rect_point_right_left() {
+        let point: RectangleSpherePoint<10, 10> = RectangleSpherePoint::new(0, 5);
+
+        assert_eq!(RectangleSpherePoint::new(1, 5), point.right());
+    }
+   
+    #[test]
+    fn test_rect_point_right_right() {
+        let point: RectangleSpherePoint<10, 10> = RectangleSpherePoint::new(9, 5);
+
+        assert_eq!(RectangleSpherePoint::new(0, 5), point.right());
+    }
+
+    #[test]
+    fn test_rect_point_new_in_range() {
+        let point: RectangleSpherePoint<10, 10> = RectangleSpherePoint::new(3, 9);
+
+        assert_eq!(3, point.x);
+        assert_eq!(9, point.y);
+    }
+
+    #[test]
+    fn test_rect_point_new_wrap_x() {
+        let point: RectangleSpherePoint<10, 10> = RectangleSpherePoint::new(13, 4);
+
+        assert_eq!(RectangleSpherePoint::new(3, 4), point);
+    }
+
+    #[test]
+    fn test_rect_point_new_y_h() {
+        let point: RectangleSpherePoint<10, 10> = RectangleSpherePoint::new(3, 10);
+
+        assert_eq!(8, point.x);
+        assert_eq!(9, point.y);
+        assert_eq!(RectangleSpherePoint::new(3, 9).down(), point);
+    }
+    
+    #[test]
+    fn test_rect_point_new_y_h_plus_1() {
+        let point: RectangleSpherePoint<10, 10> = RectangleSpherePoint::new(3, 11);
+
+        assert_eq!(8, point.x);
+        assert_eq!(8, point.y);
+        assert_eq!(RectangleSpherePoint::new(3, 9).down().down(), point);
+    }
+    
+    #[test]
+    fn test_rect_point_new_y_2h() {
+        let point: RectangleSpherePoint<10, 10> = RectangleSpherePoint::new(3, 20);
+
+        assert_eq!(3, point.x);
+        assert_eq!(0, point.y);
+    }
+
+    #[test]
+    fn test_rect_point_from_geographic_equator() {
+        let point: RectangleSpherePoint<100, 100> = RectangleSpherePoint::from_geographic(0.0, PI);
+
+        assert_eq!(RectangleSpherePoint::new(50, 50), point);
+    }
+    
+    #[test]
+    fn test_rect_point_from_geographic_north_pole() {
+        let point: RectangleSpherePoint<100, 100> = RectangleSpherePoint::from_geographic(PI / 2.0, PI);
+
+        assert_eq!(RectangleSpherePoint::new(50, 0), point);
+    }
+    
+    #[test]
+    fn test_rect_point_from_geographic_south_pole() {
+        let point: RectangleSpherePoint<100, 100> = RectangleSpherePoint::from_geographic(-PI / 2.0, PI);
+
+        assert_eq!(RectangleSpherePoint::new(50, 99), point);
+    }
+    
+    #[test]
+    fn test_rect_point_from_geographic_equator_wrap_north() {
+        let point: RectangleSpherePoint<100, 100> = RectangleSpherePoint::from_geographic(-PI, PI);
+
+        assert_eq!(RectangleSpherePoint::new(50, 50), point);
+    }
+    
+    #[test]
+    fn test_rect_point_from_geographic_equator_wrap_south() {
+        let point: RectangleSpherePoint<100, 100> = RectangleSpherePoint::from_geographic(PI, PI);
+
+        assert_eq!(RectangleSpherePoint::new(50, 50), point);
+    }
+    
+    #[test]
+    fn test_rect_point_from_geographic_east() {
+        let point: RectangleSpherePoint<100, 100> = RectangleSpherePoint::from_geographic(0.0, PI * 2.0);
+
+        assert_eq!(RectangleSpherePoint::new(0, 50), point);
+    }
+    
+    #[test]
+    fn test_rect_point_from_geographic_west() {
+        let point: RectangleSpherePoint<100, 100> = RectangleSpherePoint::from_geographic(0.0, 0.0);
+
+        assert_eq!(RectangleSpherePoint::new(0, 50), point);
+    }
+
+    #[test]
+    fn test_rect_point_nearest_point_equator() {
+        let point: RectangleSpherePoint<100, 100> = RectangleSpherePoint::nearest_point(0.0, 0.0, -2.0);
+
+        assert_eq!(RectangleSpherePoint::from_geographic(0.0, PI), point);
+    }
+
+    #[test]
+    fn test_rect_sample_nearest_3d() {
+        let grid: RectangleSphereGrid<RectangleSpherePoint<100, 100>, 100, 100> = RectangleSphereGrid::from_fn(|point| *point);
+
+        assert_eq!(&RectangleSpherePoint::from_geographic(0.0, PI / 2.0), grid.sample_nearest_3d(4.0, 0.0, 0.0));
+    }
+
+    #[test]
+    fn test_rect_point_cell_corner_0() {
+        let point: RectangleSpherePoint<20, 10> = RectangleSpherePoint::new(5, 3);
+
+        assert_eq!(point.position(2.0), point.cell_corner(0, 2.0));
+    }
+
+    #[test]
+    fn test_rect_point_cell_center_inside_corners() {
+        let grid: RectangleSphereGrid<(), 20, 10> = RectangleSphereGrid::default();
+
+        // The rows at the poles have two corners at the same position so they are skipped.
+        for point in grid.points().filter(|point| point.y != 0 && point.y != 9) {
+            let corners = [0, 1, 2, 3].map(|corner| point.cell_corner(corner, 1.0));
+
+            assert!(inside_quad(corners, point.cell_center(1.0)), "{:?}", point);
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_rect_point_cell_corner_invalid() {
+        let point: RectangleSpherePoint<20, 10> = RectangleSpherePoint::new(5, 3);
+
+        point.cell_corner(4, 1.0);
+    }
+
+    #[test]
+    fn test_rect_point_up_loop() {
+        let start: RectangleSpherePoint<10, 5> = RectangleSpherePoint::new(0, 3);
+
+        assert_eq!(start, start.up().up().up().up().up().up().up().up().up().up());
+    }
+    
+    #[test]
+    fn test_rect_point_down_loop() {
+        let start: RectangleSpherePoint<10, 5> = RectangleSpherePoint::new(5, 3);
+
+        assert_eq!(start, start.down().down().down().down().down().down().down().down().down().down());
+    }
+    
+    #[test]
+    fn test_rect_point_left_loop() {
+        let start: RectangleSpherePoint<10, 5> = RectangleSpherePoint::new(0, 3);
+
+        assert_eq!(start, start.left().left().left().left().left().left().left().left().left().left());
+    }
+    
+    #[test]
+    fn test_rect_point_right_loop() {
+        let start: RectangleSpherePoint<10, 5> = RectangleSpherePoint::new(0, 3);
+
+        assert_eq!(start, start.right().right().right().right().right().right().right().right().right().right());
+    }
+
+    #[test]
+    fn test_rect_point_up_inverse_middle() {
+        let start: RectangleSpherePoint<10, 5> = RectangleSpherePoint::new(5, 3);
+
+        assert_eq!(start, start.up().down());
+    }
+    
+    #[test]
+    fn test_rect_point_down_inverse_middle() {
+        let start: RectangleSpherePoint<10, 5> = RectangleSpherePoint::new(5, 3);
+
+        assert_eq!(start, start.down().up());
+    }
+    
+    #[test]
+    fn test_rect_point_left_inverse_middle() {
+        let start: RectangleSpherePoint<10, 5> = RectangleSpherePoint::new(5, 3);
+
+        assert_eq!(start, start.left().right());
+    }
+    
+    #[test]
+    fn test_rect_point_right_inverse_middle() {
+        let start: RectangleSpherePoint<10, 5> = RectangleSpherePoint::new(5, 3);
+
+        assert_eq!(start, start.right().left());
+    }
+    
+    #[test]
+    fn test_rect_point_up_inverse_edge() {
+        let start: RectangleSpherePoint<10, 5> = RectangleSpherePoint::new(0, 0);
+
+        assert_eq!(start, start.up().down());
+    }
+    
+    #[test]
+    fn test_rect_point_down_inverse_edge() {
+        let start: RectangleSpherePoint<10, 5> = RectangleSpherePoint::new(0, 4);
+
+        assert_eq!(start, start.down().up());
+    }
+    
+    #[test]
+    fn test_rect_point_left_inverse_edge() {
+        let start: RectangleSpherePoint<10, 5> = RectangleSpherePoint::new(0, 0);
+
+        assert_eq!(start, start.left().right());
+    }
+    
+    #[test]
+    fn test_rect_point_right_inverse_edge() {
+        let start: RectangleSpherePoint<10, 5> = RectangleSpherePoint::new(9, 0);
+
+        assert_eq!(start, start.right().left());
+    }
+
+    #[test]
+    fn test_rect_from_fn() {
+        let grid: RectangleSphereGrid<u32, 200, 100> = RectangleSphereGrid::from_fn(|point| point.x + point.y);
+
+        assert_eq!(15, grid[RectangleSpherePoint::new(5, 10)]);
+    }
+
+    #[test]
+    fn test_rect_from_neighbours() {
+        let grid: RectangleSphereGrid<u32, 20, 10> = RectangleSphereGrid::from_fn(|point| point.x);
+
+        let grid2 = grid.map_neighbours(|current, up, down, left, right| current + up + down + left + right);
+
+        assert_eq!(25, grid2[RectangleSpherePoint::new(5, 3)])
+    }
+    
+    #[test]
+    fn test_rect_from_neighbours_diagonals() {
+        let grid: RectangleSphereGrid<u32, 20, 10> = RectangleSphereGrid::from_fn(|point| point.x);
+
+        let grid2 = grid.map_neighbours_diagonals(|up_left, up, up_right, left, current, right, down_left, down, down_right| up_left + up + up_right + left + current + right + down_left + down + down_right);
+
+        assert_eq!(4 * 3 + 5 * 3 + 6 * 3, grid2[RectangleSpherePoint::new(5, 3)])
+    }
+
+    #[test]
+    fn test_rect_set_from_neighbours_with_position() {
+        let source: RectangleSphereGrid<u32, 20, 10> = RectangleSphereGrid::default();
+        let mut grid: RectangleSphereGrid<u32, 20, 10> = RectangleSphereGrid::default();
+
+        grid.set_from_neighbours_with_position(&source, |_, point, _, _, _, _| point.y);
+
+        assert!(grid.iter().all(|(point, value)| point.y == *value));
+    }
+    
+    #[test]
+    fn test_rect_set_from_neighbours_diagonals_with_position() {
+        let source: RectangleSphereGrid<u32, 20, 10> = RectangleSphereGrid::default();
+        let mut grid: RectangleSphereGrid<u32, 20, 10> = RectangleSphereGrid::default();
+
+        grid.set_from_neighbours_diagonals_with_position(&source, |point, _, _, _, _, _, _, _, _, _| point.y);
+
+        assert!(grid.iter().all(|(point, value)| point.y == *value));
+    }
+    
+    #[test]
+    fn test_rect_set_from_neighbours_par_with_position() {
+        let source: RectangleSphereGrid<u32, 20, 10> = RectangleSphereGrid::default();
+        let mut grid: RectangleSphereGrid<u32, 20, 10> = RectangleSphereGrid::default();
+
+        grid.set_from_neighbours_par_with_position(&source, |_, point, _, _, _, _| point.y);
+
+        assert!(grid.iter().all(|(point, value)| point.y == *value));
+    }
+    
+    #[test]
+    fn test_rect_set_from_neighbours_diagonals_par_with_position() {
+        let source: RectangleSphereGrid<u32, 20, 10> = RectangleSphereGrid::default();
+        let mut grid: RectangleSphereGrid<u32, 20, 10> = RectangleSphereGrid::default();
+
+        grid.set_from_neighbours_diagonals_par_with_position(&source, |point, _, _, _, _, _, _, _, _, _| point.y);
+
+        assert!(grid.iter().all(|(point, value)| point.y == *value));
+    }
+
+    #[test]
+    fn test_rect_set_from_neighbours_par() {
+        let source: RectangleSphereGrid<u32, 20, 10> = RectangleSphereGrid::from_fn(|point| point.x);
+        let mut grid: RectangleSphereGrid<u32, 20, 10> = RectangleSphereGrid::default();
+
+        grid.set_from_neighbours_par(&source, |current, up, down, left, right| current + up + down + left + right);
+
+        assert_eq!(source.map_neighbours(|current, up, down, left, right| current + up + down + left + right), grid);
+    }
+
+    #[test]
+    fn test_rect_iteration_order() {
+        let grid: RectangleSphereGrid<(u32, u32), 3, 2> = RectangleSphereGrid::from_fn(|point| (point.x, point.y));
+
+        let expected = [(0, 0), (1, 0), (2, 0), (0, 1), (1, 1), (2, 1)];
+
+        let points: Vec<_> = grid.points().map(|point| (point.x, point.y)).collect();
+        let iter: Vec<_> = grid.iter().map(|(_, value)| *value).collect();
+        let into_iter: Vec<_> = grid.into_iter().map(|(_, value)| value).collect();
+
+        assert_eq!(points, expected);
+        assert_eq!(iter, expected);
+        assert_eq!(into_iter, expected);
+    }
+
+    #[test]
+    fn test_rect_checked() {
+        let grid: RectangleSphereGrid<(), 20, 10> = RectangleSphereGrid::default();
+
+        for point in grid.points() {
+            assert_eq!(point.checked_up(), Some(point.up()));
+            assert_eq!(point.checked_down(), Some(point.down()));
+            assert_eq!(point.checked_left(), Some(point.left()));
+            assert_eq!(point.checked_right(), Some(point.right()));
+        }
+    }
+
+    #[test]
+    fn test_rect_neighbour_flips_orientation() {
+        let grid: RectangleSphereGrid<(), 20, 10> = RectangleSphereGrid::default();
+
+        for point in grid.points() {
+            for dir in [Direction::Up, Direction::Down, Direction::Left, Direction::Right] {
+                assert!(!point.neighbour_flips_orientation(dir));
+            }
+        }
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_rect_serde() {
+        let grid: RectangleSphereGrid<u32, 20, 10> = RectangleSphereGrid::from_fn(|point| point.x * 100 + point.y);
+
+        let json = serde_json::to_string(&grid).unwrap();
+        let restored: RectangleSphereGrid<u32, 20, 10> = serde_json::from_str(&json).unwrap();
+
+        assert_eq!(restored, grid);
+        assert!(serde_json::from_str::<RectangleSphereGrid<u32, 10, 10>>(&json).is_err());
+    }
+
+    #[test]
+    fn test_dyn_rect_matches_static() {
+        let grid: RectangleSphereGrid<(), 10, 10> = RectangleSphereGrid::default();
+
+        let coordinates = |point: DynRectangleSpherePoint| {
+            let point = point.normalize();
+
+            (point.x as u32, point.y as u32)
+        };
+
+        for point in grid.points() {
+            let dynamic = DynRectangleSpherePoint::new(point.x as i64, point.y as i64, 10, 10);
+
+            let neighbours = [
+                (point.up(), dynamic.up()),
+                (point.down(), dynamic.down()),
+                (point.left(), dynamic.left()),
+                (point.right(), dynamic.right()),
+                (point.up_left(), dynamic.up_left()),
+                (point.up_right(), dynamic.up_right()),
+                (point.down_left(), dynamic.down_left()),
+                (point.down_right(), dynamic.down_right()),
+            ];
+
+            for (expected, actual) in neighbours {
+                assert_eq!(coordinates(actual), (expected.x, expected.y), "{:?}", point);
+            }
+
+            assert_eq!(dynamic.position(1.0), point.position(1.0));
+        }
+    }
+
+    #[test]
+    fn test_dyn_rect_normalize() {
+        for x in 0..40 {
+            for y in 0..40 {
+                let expected: RectangleSpherePoint<10, 10> = RectangleSpherePoint::new(x, y);
+                let point = DynRectangleSpherePoint::new(x as i64, y as i64, 10, 10).normalize();
+
+                assert_eq!((point.x as u32, point.y as u32), (expected.x, expected.y));
+            }
+        }
+
+        assert_eq!(DynRectangleSpherePoint::new(-1, 3, 10, 10).normalize().x, 9);
+        assert_eq!(DynRectangleSpherePoint::new(3, -1, 10, 10), DynRectangleSpherePoint::new(3, 0, 10, 10).up());
+        assert_eq!(DynRectangleSpherePoint::new(3, 10, 10, 10), DynRectangleSpherePoint::new(3, 9, 10, 10).down());
+    }
+
+    #[test]
+    fn test_dyn_rect_eq() {
+        assert_eq!(DynRectangleSpherePoint::new(12, 3, 10, 10), DynRectangleSpherePoint::new(2, 3, 10, 10));
+        assert_ne!(DynRectangleSpherePoint::new(2, 3, 10, 10), DynRectangleSpherePoint::new(2, 3, 10, 20));
+        assert_ne!(DynRectangleSpherePoint::new(2, 3, 10, 10), DynRectangleSpherePoint::new(2, 4, 10, 10));
+    }
+
+    #[test]
+    fn test_rect_deterministic_sum() {
+        let grid: RectangleSphereGrid<f64, 200, 100> = RectangleSphereGrid::from_fn(|point| {
+            (point.x as f64 * 0.37).sin() * 10f64.powi(point.y as i32 % 12 - 6)
+        });
+
+        let sum = |threads| rayon::ThreadPoolBuilder::new()
+            .num_threads(threads)
+            .build()
+            .unwrap()
+            .install(|| grid.deterministic_sum());
+
+        let expected: f64 = grid.iter().map(|(_, value)| value).sum();
+
+        assert_eq!(sum(1).to_bits(), sum(8).to_bits());
+        assert_relative_eq!(sum(1), expected, epsilon = 1e-6);
+    }
+
+    #[test]
+    fn test_cube_deterministic_sum_integer() {
+        let grid: CubeSphereGrid<u64, 10> = CubeSphereGrid::from_fn(|point| point.x as u64 + point.y as u64);
+
+        assert_eq!(grid.deterministic_sum(), grid.iter().map(|(_, value)| value).sum::<u64>());
+    }
+
+    /// Gets the geographic coordinates of the centre of a cell.
+    fn cube_cell_center_geographic<const S: usize>(point: &CubeSpherePoint<S>) -> (f64, f64) {
+        let (x, y, z) = point.cell_center(1.0);
+
+        (y.asin(), x.atan2(z))
+    }
+
+    #[test]
+    fn test_cube_from_geographic_bilinear_weights() {
+        for i in 0..200 {
+            let latitude = (i as f64 * 0.731).sin() * PI / 2.0;
+            let longitude = i as f64 * 0.377;
+
+            let (_, weights) = CubeSpherePoint::<10>::from_geographic_bilinear(latitude, longitude);
+
+            assert_relative_eq!(weights.iter().sum::<f64>(), 1.0, epsilon = 1e-9);
+            assert!(weights.iter().all(|weight| *weight >= 0.0));
+        }
+    }
+
+    #[test]
+    fn test_cube_from_geographic_bilinear_centre() {
+        for point in CubeSphereGrid::<(), 10>::default().points() {
+            let (latitude, longitude) = cube_cell_center_geographic(&point);
+
+            let (points, weights) = CubeSpherePoint::<10>::from_geographic_bilinear(latitude, longitude);
+
+            let (i, weight) = weights.iter()
+                .enumerate()
+                .max_by(|(_, a), (_, b)| a.total_cmp(b))
+                .unwrap();
+
+            assert_eq!(points[i], point);
+            assert_relative_eq!(*weight, 1.0, epsilon = 1e-6);
+        }
+    }
+
+    #[test]
+    fn test_cube_from_geographic_cell_centres() {
+        for point in CubeSphereGrid::<(), 10>::default().points() {
+            let (latitude, longitude) = cube_cell_center_geographic(&point);
+
+            assert_eq!(CubeSpherePoint::from_geographic(latitude, longitude), point);
+        }
+    }
+
+    #[test]
+    fn test_cube_from_face_coordinates_rounding() {
+        let point = CubeSpherePoint::<8>::from_face_coordinates(CubeFace::Front, 8.0 + 1e-9, -1e-9);
+
+        assert_eq!(point, CubeSpherePoint::new(CubeFace::Front, 7, 0));
+    }
+
+    #[cfg(debug_assertions)]
+    #[test]
+    #[should_panic(expected = "outside of the Front face")]
+    fn test_cube_from_face_coordinates_out_of_range() {
+        CubeSpherePoint::<8>::from_face_coordinates(CubeFace::Front, 12.0, 3.0);
+    }
+
+    #[test]
+    fn test_cube_from_geographic_bilinear_seam() {
+        // A smooth function sampled at the centres of the cells.
+        let f = |(x, y, z): (f64, f64, f64)| x + 2.0 * y - z;
+
+        let grid: CubeSphereGrid<f64, 20> = CubeSphereGrid::from_fn(|point| f(point.cell_center(1.0)));
+
+        // Positions on the edges between faces and near the corners of the cube.
+        let positions = [
+            (PI / 4.0, 0.0),
+            (-PI / 4.0, PI / 2.0),
+            (0.0, PI / 4.0),
+            (0.0, 5.0 * PI / 4.0),
+            (0.6154797, PI / 4.0 + 0.01),
+            (-0.6, 3.0 * PI / 4.0),
+        ];
+
+        for (latitude, longitude) in positions {
+            let (points, weights) = CubeSpherePoint::<20>::from_geographic_bilinear(latitude, longitude);
+
+            let value: f64 = points.iter()
+                .zip(weights)
+                .map(|(point, weight)| grid[*point] * weight)
+                .sum();
+
+            let expected = f((latitude.cos() * longitude.sin(), latitude.sin(), latitude.cos() * longitude.cos()));
+
+            assert_relative_eq!(value, expected, epsilon = 0.1);
+        }
+    }
+
+    #[test]
+    fn test_rect_iter_with_neighbours() {
+        let grid: RectangleSphereGrid<u32, 20, 10> = RectangleSphereGrid::from_fn(|point| point.x * 100 + point.y);
+
+        let expected = grid.map_neighbours(|_, up, down, left, right| up + down + left + right);
+
+        for (point, value, neighbours) in grid.iter_with_neighbours() {
+            assert_eq!(*value, grid[point]);
+            assert_eq!(neighbours.into_iter().sum::<u32>(), expected[point]);
+        }
+
+        assert_eq!(grid.iter_with_neighbours().count(), 200);
+    }
+
+    #[test]
+    fn test_rect_changed_cells() {
+        let grid: RectangleSphereGrid<u32, 20, 10> = RectangleSphereGrid::from_fn(|point| point.x + point.y);
+        let mut other = grid.clone();
+
+        assert_eq!(grid.changed_cells(&other), 0);
+        assert_eq!(grid.par_changed_cells(&other), 0);
+
+        for x in 0..7 {
+            other[RectangleSpherePoint::new(x, 3)] += 1;
+        }
+
+        assert_eq!(grid.changed_cells(&other), 7);
+        assert_eq!(grid.par_changed_cells(&other), 7);
+    }
+
+    #[test]
+    fn test_cube_changed_cells() {
+        let grid: CubeSphereGrid<usize, 10> = CubeSphereGrid::from_index_fn(|i| i);
+        let other: CubeSphereGrid<usize, 10> = CubeSphereGrid::from_index_fn(|i| if i.is_multiple_of(3) { 0 } else { i });
+
+        assert_eq!(grid.changed_cells(&grid.clone()), 0);
+        assert_eq!(grid.par_changed_cells(&grid.clone()), 0);
+
+        // Every third cell except the first is changed.
+        assert_eq!(grid.changed_cells(&other), 199);
+        assert_eq!(grid.par_changed_cells(&other), 199);
+    }
+
+    #[test]
+    fn test_rect_centroid() {
+        let grid: RectangleSphereGrid<bool, 20, 10> = RectangleSphereGrid::from_fn(|point| {
+            (4..9).contains(&point.x) && (2..5).contains(&point.y)
+        });
+
+        assert_eq!(grid.centroid(|value| *value, 2.0), Some(RectangleSpherePoint::new(6, 3)));
+
+        // The patch crosses the antimeridian.
+        let grid: RectangleSphereGrid<bool, 20, 10> = RectangleSphereGrid::from_fn(|point| {
+            (point.x <= 1 || point.x >= 17) && (6..9).contains(&point.y)
+        });
+
+        assert_eq!(grid.centroid(|value| *value, 1.0), Some(RectangleSpherePoint::new(19, 7)));
+    }
+
+    #[test]
+    fn test_rect_centroid_none() {
+        let grid: RectangleSphereGrid<bool, 20, 10> = RectangleSphereGrid::from_fn(|_| false);
+
+        assert_eq!(grid.centroid(|value| *value, 1.0), None);
+
+        let grid: RectangleSphereGrid<bool, 20, 10> = RectangleSphereGrid::from_fn(|point| point.y == 4 || point.y == 5);
+
+        assert_eq!(grid.centroid(|value| *value, 1.0), None);
+    }
+
+    #[test]
+    fn test_cube_centroid() {
+        let grid: CubeSphereGrid<bool, 9> = CubeSphereGrid::from_fn(|point| {
+            point.face == CubeFace::Right && (3..6).contains(&point.x) && (2..7).contains(&point.y)
+        });
+
+        assert_eq!(grid.centroid(|value| *value, 1.0), Some(CubeSpherePoint::new(CubeFace::Right, 4, 4)));
+    }
+
+    #[test]
+    fn test_rect_neighbours_of() {
+        let grid: RectangleSphereGrid<u32, 20, 10> = RectangleSphereGrid::from_fn(|point| point.x * 100 + point.y);
+
+        for point in grid.points() {
+            let neighbours = grid.neighbours_of(&point);
+
+            for ((neighbour, value), expected) in neighbours.into_iter().zip(point.neighbours()) {
+                assert_eq!(neighbour, expected);
+                assert_eq!(*value, grid[expected]);
+            }
+        }
+    }
+
+    #[test]
+    fn test_cube_neighbours_of() {
+        let grid: CubeSphereGrid<usize, 5> = CubeSphereGrid::from_index_fn(|i| i);
+
+        for point in grid.points() {
+            let neighbours = grid.neighbours_of(&point);
+
+            assert_eq!(neighbours.map(|(neighbour, _)| neighbour), [point.up(), point.down(), point.left(), point.right()]);
+
+            for (neighbour, value) in neighbours {
+                assert_eq!(*value, grid.to_index(&neighbour));
+            }
+        }
+    }
+
+    #[test]
+    fn test_cube_par_iter_with_neighbours() {
+        use rayon::iter::ParallelIterator;
+
+        let grid: CubeSphereGrid<u32, 10> = CubeSphereGrid::from_fn(|point| point.x as u32 * 100 + point.y as u32);
+
+        let expected = grid.map_neighbours(|_, up, down, left, right| up + down + left + right);
+
+        let sums: Vec<_> = grid.par_iter_with_neighbours()
+            .map(|(point, _, neighbours)| (point, neighbours.into_iter().sum::<u32>()))
+            .collect();
+
+        assert_eq!(sums.len(), 600);
+
+        for (point, sum) in sums {
+            assert_eq!(sum, expected[point]);
+        }
+    }
+
+    #[test]
+    fn test_cube_face_local_to_geographic_centre() {
+        let expected = [
+            (CubeFace::Front, 0.0, 0.0),
+            (CubeFace::Right, 0.0, PI / 2.0),
+            (CubeFace::Back, 0.0, PI),
+            (CubeFace::Left, 0.0, 3.0 * PI / 2.0),
+        ];
+
+        for (face, latitude, longitude) in expected {
+            let (lat, long) = CubeSpherePoint::<10>::face_local_to_geographic(face, 5.0, 5.0);
+
+            assert_relative_eq!(lat, latitude, epsilon = 1e-9);
+            assert_relative_eq!(long, longitude, epsilon = 1e-9);
+        }
+
+        let (lat, _) = CubeSpherePoint::<10>::face_local_to_geographic(CubeFace::Top, 5.0, 5.0);
+        assert_relative_eq!(lat, PI / 2.0, epsilon = 1e-9);
+
+        let (lat, _) = CubeSpherePoint::<10>::face_local_to_geographic(CubeFace::Bottom, 5.0, 5.0);
+        assert_relative_eq!(lat, -PI / 2.0, epsilon = 1e-9);
+    }
+
+    #[test]
+    fn test_cube_face_local_to_geographic_cell_center() {
+        for point in CubeSphereGrid::<(), 10>::default().points() {
+            let (latitude, longitude) = CubeSpherePoint::<10>::face_local_to_geographic(point.face, point.x as f64 + 0.5, point.y as f64 + 0.5);
+
+            let (x, y, z) = point.cell_center(1.0);
+
+            assert_relative_eq!(latitude.sin(), y, epsilon = 1e-9);
+            assert_relative_eq!(latitude.cos() * longitude.sin(), x, epsilon = 1e-9);
+            assert_relative_eq!(latitude.cos() * longitude.cos(), z, epsilon = 1e-9);
+        }
+    }
+
+    #[test]
+    fn test_rect_surface_area() {
+        let grid: RectangleSphereGrid<(), 20, 10> = RectangleSphereGrid::default();
+
+        assert_relative_eq!(grid.surface_area(1.0), 4.0 * PI);
+        assert_relative_eq!(grid.surface_area(2.0), 16.0 * PI);
+    }
+
+    #[test]
+    fn test_cube_surface_area() {
+        let grid: CubeSphereGrid<(), 10> = CubeSphereGrid::default();
+
+        assert_relative_eq!(grid.surface_area(1.0), 4.0 * PI);
+
+        let total: f64 = grid.points().map(|point| point.solid_angle()).sum();
+
+        assert_relative_eq!(grid.surface_area(1.0), total, epsilon = 1e-9);
+    }
+
+    #[test]
+    fn test_rect_from_fn_sparse() {
+        let grid: RectangleSphereGrid<u32, 10, 10> = RectangleSphereGrid::from_fn_sparse(|point| {
+            (point.x == point.y).then_some(point.x + 1)
+        });
+
+        for (point, value) in grid.iter() {
+            if point.x == point.y {
+                assert_eq!(*value, point.x + 1);
+            } else {
+                assert_eq!(*value, 0);
+            }
+        }
+    }
+
+    #[test]
+    fn test_cube_from_fn_sparse_par() {
+        let grid: CubeSphereGrid<u32, 10> = CubeSphereGrid::from_fn_sparse_par(|point| {
+            (point.x == point.y).then_some(point.x as u32 + 1)
+        });
+
+        let expected: CubeSphereGrid<u32, 10> = CubeSphereGrid::from_fn_sparse(|point| {
+            (point.x == point.y).then_some(point.x as u32 + 1)
+        });
+
+        assert_eq!(grid, expected);
+
+        for (point, value) in grid.iter() {
+            if point.x == point.y {
+                assert_eq!(*value, point.x as u32 + 1);
+            } else {
+                assert_eq!(*value, 0);
+            }
+        }
+    }
+
+    /// Checks that all of the diagonal neighbour methods agree with each other and with
+    /// `DIAGONAL_ORDER`.
+    fn check_diagonal_order<G: SurfaceGrid<u64> + Default + Sync + PartialEq + std::fmt::Debug>(grid: &G) {
+        let rule = |a: &u64, b: &u64, c: &u64, d: &u64, e: &u64, f: &u64, g: &u64, h: &u64, i: &u64| {
+            [a, b, c, d, e, f, g, h, i].into_iter()
+                .enumerate()
+                .map(|(n, value)| value * 31u64.pow(n as u32))
+                .sum::<u64>()
+        };
+
+        let expected = G::from_fn(|point| {
+            DIAGONAL_ORDER.into_iter()
+                .enumerate()
+                .map(|(n, direction)| {
+                    let neighbour = match direction {
+                        (Some(Direction::Up), Some(Direction::Left)) => point.up_left(),
+                        (Some(Direction::Up), None) => point.up(),
+                        (Some(Direction::Up), Some(Direction::Right)) => point.up_right(),
+                        (None, Some(Direction::Left)) => point.left(),
+                        (None, None) => point.clone(),
+                        (None, Some(Direction::Right)) => point.right(),
+                        (Some(Direction::Down), Some(Direction::Left)) => point.down_left(),
+                        (Some(Direction::Down), None) => point.down(),
+                        (Some(Direction::Down), Some(Direction::Right)) => point.down_right(),
+                        _ => unreachable!(),
+                    };
+
+                    grid[neighbour] * 31u64.pow(n as u32)
+                })
+                .sum()
+        });
+
+        assert_eq!(grid.map_neighbours_diagonals(rule), expected);
+        assert_eq!(grid.map_neighbours_diagonals_par(rule), expected);
+
+        let mut result = G::default();
+        result.set_from_neighbours_diagonals(grid, rule);
+        assert_eq!(result, expected);
+
+        let mut result = G::default();
+        result.set_from_neighbours_diagonals_par(grid, rule);
+        assert_eq!(result, expected);
+    }
+
+    #[test]
+    fn test_rect_diagonal_order() {
+        let mut i = 0;
+        let grid: RectangleSphereGrid<u64, 20, 10> = RectangleSphereGrid::from_fn(|_| {
+            i += 1;
+
+            i
+        });
+
+        check_diagonal_order(&grid);
+    }
+
+    #[test]
+    fn test_cube_diagonal_order() {
+        let mut i = 0;
+        let grid: CubeSphereGrid<u64, 10> = CubeSphereGrid::from_fn(|_| {
+            i += 1;
+
+            i
+        });
+
+        check_diagonal_order(&grid);
+    }
+
+    /// Gets the angle between two geographic coordinates.
+    fn angular_distance(a: (f64, f64), b: (f64, f64)) -> f64 {
+        dot(super::geographic_position(a), super::geographic_position(b)).clamp(-1.0, 1.0).acos()
+    }
+
+    /// Checks that the contour of the distance from a centre is a single closed loop of the
+    /// right radius.
+    fn check_radial_contour<G: SurfaceGrid<f64>>(grid: &G, centre: (f64, f64), radius: f64, tolerance: f64) where G::Point: SpherePoint {
+        let lines = grid.contour(radius, |value| *value);
+
+        assert_eq!(lines.len(), 1);
+
+        let line = &lines[0];
+
+        assert!(line.len() > 10);
+        assert_eq!(line.first(), line.last());
+
+        for position in line {
+            assert_relative_eq!(angular_distance(*position, centre), radius, epsilon = tolerance);
+        }
+    }
+
+    #[test]
+    fn test_rect_contour() {
+        let centre = (0.3, 2.0);
+
+        let grid: RectangleSphereGrid<f64, 72, 36> = RectangleSphereGrid::from_fn(|point| {
+            let (x, y, z) = point.cell_center(1.0);
+
+            angular_distance((y.asin(), x.atan2(z)), centre)
+        });
+
+        check_radial_contour(&grid, centre, 0.5, 0.02);
+    }
+
+    #[test]
+    fn test_rect_contour_seam() {
+        let centre = (-0.2, 0.1);
+
+        let grid: RectangleSphereGrid<f64, 72, 36> = RectangleSphereGrid::from_fn(|point| {
+            let (x, y, z) = point.cell_center(1.0);
+
+            angular_distance((y.asin(), x.atan2(z)), centre)
+        });
+
+        check_radial_contour(&grid, centre, 0.5, 0.02);
+    }
+
+    #[test]
+    fn test_rect_contour_pole() {
+        let centre = (PI / 2.0, 0.0);
+
+        let grid: RectangleSphereGrid<f64, 72, 36> = RectangleSphereGrid::from_fn(|point| {
+            let (x, y, z) = point.cell_center(1.0);
+
+            angular_distance((y.asin(), x.atan2(z)), centre)
+        });
+
+        check_radial_contour(&grid, centre, 0.3, 0.02);
+    }
+
+    #[test]
+    fn test_rect_contour_none() {
+        let grid: RectangleSphereGrid<f64, 20, 10> = RectangleSphereGrid::from_fn(|point| point.x as f64);
+
+        assert!(grid.contour(100.0, |value| *value).is_empty());
+    }
+
+    #[test]
+    fn test_cube_contour() {
+        let centre = (0.0, 0.0);
+
+        let grid: CubeSphereGrid<f64, 20> = CubeSphereGrid::from_fn(|point| {
+            let (x, y, z) = point.cell_center(1.0);
+
+            angular_distance((y.asin(), x.atan2(z)), centre)
+        });
+
+        check_radial_contour(&grid, centre, 0.4, 0.02);
+    }
+
+    #[test]
+    fn test_cube_contour_seam() {
+        // A loop around the middle of the edge between the front and right faces.
+        let centre = (0.0, PI / 4.0);
+
+        let grid: CubeSphereGrid<f64, 20> = CubeSphereGrid::from_fn(|point| {
+            let (x, y, z) = point.cell_center(1.0);
+
+            angular_distance((y.asin(), x.atan2(z)), centre)
+        });
+
+        check_radial_contour(&grid, centre, 0.3, 0.02);
+    }
+
+    #[test]
+    fn test_rect_shared_between_threads() {
+        fn sum<G: ThreadSafeGrid<u32>>(grid: &G) -> u32 {
+            grid.iter().map(|(_, value)| value).sum()
+        }
+
+        let grid: RectangleSphereGrid<u32, 20, 10> = RectangleSphereGrid::from_fn(|point| point.x + point.y);
+        let expected = sum(&grid);
+
+        std::thread::scope(|scope| {
+            let handles: Vec<_> = (0..4).map(|_| scope.spawn(|| sum(&grid))).collect();
+
+            for handle in handles {
+                assert_eq!(handle.join().unwrap(), expected);
+            }
+        });
+    }
+
+    #[test]
+    fn test_rect_expand_by_cost_uniform() {
+        let grid: RectangleSphereGrid<(), 20, 10> = RectangleSphereGrid::default();
+        let start = RectangleSpherePoint::new(3, 4);
+
+        let visited: Vec<_> = grid.expand_by_cost(&start, |_| 1.0).collect();
+
+        assert_eq!(visited.len(), 200);
+        assert_eq!(visited[0], (start, 0.0));
+        assert!(visited.iter().tuple_windows().all(|((_, a), (_, b))| a <= b));
+
+        // Breadth first search for the number of steps to each point.
+        let mut distances = HashMap::from([(start, 0)]);
+        let mut queue = VecDeque::from([start]);
+
+        while let Some(point) = queue.pop_front() {
+            let distance = distances[&point];
+
+            for neighbour in [point.up(), point.down(), point.left(), point.right()] {
+                if let Entry::Vacant(entry) = distances.entry(neighbour) {
+                    entry.insert(distance + 1);
+                    queue.push_back(neighbour);
+                }
+            }
+        }
+
+        for (point, cost) in visited {
+            assert_eq!(cost, distances[&point] as f64);
+        }
+    }
+
+    #[test]
+    fn test_rect_expand_by_cost_barrier() {
+        let grid: RectangleSphereGrid<(), 20, 10> = RectangleSphereGrid::default();
+
+        // A wall around the sphere along y = 5 with a gap at x = 10.
+        let cost = |point: &RectangleSpherePoint<20, 10>| if point.y == 5 && point.x != 10 { 1000.0 } else { 1.0 };
+
+        let costs: HashMap<_, _> = grid.expand_by_cost(&RectangleSpherePoint::new(0, 2), cost).collect();
+
+        // Over the north pole, through the gap and back over the south pole rather than straight
+        // through the wall.
+        assert_eq!(costs[&RectangleSpherePoint::new(0, 7)], 15.0);
+
+        let impassable = |point: &RectangleSpherePoint<20, 10>| if point.y == 5 { f64::INFINITY } else { 1.0 };
+
+        let costs: HashMap<_, _> = grid.expand_by_cost(&RectangleSpherePoint::new(0, 2), impassable).collect();
+
+        // Only the northern hemisphere can be reached.
+        assert_eq!(costs.len(), 100);
+        assert!(costs.keys().all(|point| point.y < 5));
+    }
+
+    #[test]
+    fn test_cube_expand_by_cost_uniform() {
+        let grid: CubeSphereGrid<(), 5> = CubeSphereGrid::default();
+        let start = CubeSpherePoint::new(CubeFace::Front, 2, 2);
+
+        let costs: HashMap<_, _> = grid.expand_by_cost(&start, |_| 2.0).collect();
+
+        assert_eq!(costs.len(), 150);
+        assert_eq!(costs[&start], 0.0);
+        assert_eq!(costs[&start.up()], 2.0);
+        assert_eq!(costs[&start.up().up().up()], 6.0);
+        assert_eq!(costs[&CubeSpherePoint::new(CubeFace::Back, 2, 2)], 20.0);
+    }
+
+    /// Gets whether a cell is live in a repeating pattern.
+    fn bit_pattern(point: &CubeSpherePoint<10>) -> bool {
+        let i = point.index();
+
+        i.is_multiple_of(3) || i.is_multiple_of(7)
+    }
+
+    #[test]
+    fn test_bit_cube_get_set() {
+        let mut grid: BitCubeSphereGrid<10> = BitCubeSphereGrid::new();
+
+        assert_eq!(grid.count_ones(), 0);
+
+        let point = CubeSpherePoint::new(CubeFace::Back, 3, 7);
+
+        grid.set(&point, true);
+
+        assert!(grid.get(&point));
+        assert!(grid[point]);
+        assert!(!grid.get(&point.up()));
+        assert_eq!(grid.count_ones(), 1);
+
+        grid.set(&point, false);
+
+        assert!(!grid.get(&point));
+        assert_eq!(grid.count_ones(), 0);
+    }
+
+    #[test]
+    fn test_bit_cube_matches_cube() {
+        let grid: CubeSphereGrid<bool, 10> = CubeSphereGrid::from_fn(bit_pattern);
+        let bits: BitCubeSphereGrid<10> = BitCubeSphereGrid::from_fn(bit_pattern);
+
+        assert_eq!(BitCubeSphereGrid::from(&grid), bits);
+        assert_eq!(BitCubeSphereGrid::from_fn_par(bit_pattern), bits);
+        assert_eq!(CubeSphereGrid::from(&bits), grid);
+
+        assert!(grid.iter().map(|(point, value)| (point, *value)).eq(bits.iter()));
+        assert_eq!(bits.count_ones(), grid.iter().filter(|(_, value)| **value).count());
+    }
+
+    #[test]
+    fn test_bit_cube_step() {
+        #[allow(clippy::too_many_arguments)]
+        fn conway(s1: bool, s2: bool, s3: bool, s4: bool, current: bool, s6: bool, s7: bool, s8: bool, s9: bool) -> bool {
+            let count = [s1, s2, s3, s4, s6, s7, s8, s9]
+                .into_iter()
+                .filter(|s| *s)
+                .count();
+
+            count == 3 || (current && count == 2)
+        }
+
+        let grid: CubeSphereGrid<bool, 10> = CubeSphereGrid::from_fn(bit_pattern);
+        let bits: BitCubeSphereGrid<10> = BitCubeSphereGrid::from_fn(bit_pattern);
+
+        let grid = grid.map_neighbours_diagonals(|a, b, c, d, e, f, g, h, i| conway(*a, *b, *c, *d, *e, *f, *g, *h, *i));
+
+        assert_eq!(CubeSphereGrid::from(&bits.map_neighbours_diagonals(conway)), grid);
+        assert_eq!(CubeSphereGrid::from(&bits.map_neighbours_diagonals_par(conway)), grid);
+
+        let count = |a: bool, b: bool, c: bool, d: bool, e: bool| [a, b, c, d, e].into_iter().filter(|s| *s).count() >= 2;
+
+        let expected: CubeSphereGrid<bool, 10> = CubeSphereGrid::from(&bits)
+            .map_neighbours(|a, b, c, d, e| count(*a, *b, *c, *d, *e));
+
+        assert_eq!(CubeSphereGrid::from(&bits.map_neighbours(count)), expected);
+        assert_eq!(CubeSphereGrid::from(&bits.map_neighbours_par(count)), expected);
+    }
+
+    #[test]
+    fn test_rect_from_index_fn() {
+        let grid: RectangleSphereGrid<u32, 20, 10> = RectangleSphereGrid::from_index_fn(|i| i as u32);
+
+        for (point, value) in grid.iter() {
+            assert_eq!(*value as usize, grid.to_index(&point));
+        }
+    }
+
+    #[test]
+    fn test_cube_from_index_fn() {
+        let grid: CubeSphereGrid<u32, 10> = CubeSphereGrid::from_index_fn(|i| i as u32);
+
+        for (point, value) in grid.iter() {
+            assert_eq!(*value as usize, grid.to_index(&point));
+        }
+
+        assert_eq!(grid.to_vec(), (0..600).collect_vec());
+    }
+
+    #[test]
+    fn test_rect_step_into() {
+        let rule = |up_left: &u64, up: &u64, up_right: &u64, left: &u64, current: &u64, right: &u64, down_left: &u64, down: &u64, down_right: &u64| {
+            (up_left + up * 3 + up_right + left * 5 + current * 7 + right + down_left * 2 + down + down_right) % 1009
+        };
+
+        let mut current: RectangleSphereGrid<u64, 20, 10> = RectangleSphereGrid::from_fn(|point| point.x as u64 * 31 + point.y as u64);
+        let mut next = RectangleSphereGrid::default();
+        let mut expected = current.clone();
+
+        for _ in 0..5 {
+            current.step_into(&mut next, rule);
+            std::mem::swap(&mut current, &mut next);
+
+            expected = expected.map_neighbours_diagonals(rule);
+        }
+
+        assert_eq!(current, expected);
+    }
+
+    #[test]
+    fn test_cube_step_into() {
+        let rule = |up_left: &u64, up: &u64, up_right: &u64, left: &u64, current: &u64, right: &u64, down_left: &u64, down: &u64, down_right: &u64| {
+            (up_left + up * 3 + up_right + left * 5 + current * 7 + right + down_left * 2 + down + down_right) % 1009
+        };
+
+        let mut current: CubeSphereGrid<u64, 10> = CubeSphereGrid::from_fn(|point| point.x as u64 * 31 + point.y as u64);
+        let mut next = CubeSphereGrid::default();
+        let mut expected = current.clone();
+
+        for _ in 0..5 {
+            current.step_into(&mut next, rule);
+            std::mem::swap(&mut current, &mut next);
+
+            expected = expected.map_neighbours_diagonals(rule);
+        }
+
+        assert_eq!(current, expected);
+    }
+
+    #[test]
+    fn test_rect_to_vec() {
+        let grid: RectangleSphereGrid<u32, 3, 2> = RectangleSphereGrid::from_fn(|point| point.x * 10 + point.y);
+
+        assert_eq!(grid.to_vec(), [0, 10, 20, 1, 11, 21]);
+        assert_eq!(RectangleSphereGrid::from_vec(grid.to_vec()), Some(grid));
+        assert_eq!(RectangleSphereGrid::<u32, 3, 2>::from_vec(vec![0; 5]), None);
+    }
+
+    #[test]
+    fn test_rect_shortest_offset() {
+        let a: RectangleSpherePoint<20, 10> = RectangleSpherePoint::new(3, 2);
+        let b: RectangleSpherePoint<20, 10> = RectangleSpherePoint::new(7, 6);
+
+        assert_eq!(a.shortest_offset(&b), (4, 4));
+        assert_eq!(b.shortest_offset(&a), (-4, -4));
+        assert_eq!(a.shortest_offset(&a), (0, 0));
+    }
+
+    #[test]
+    fn test_rect_shortest_offset_seam() {
+        let a: RectangleSpherePoint<20, 10> = RectangleSpherePoint::new(19, 5);
+        let b: RectangleSpherePoint<20, 10> = RectangleSpherePoint::new(1, 3);
+
+        assert_eq!(a.shortest_offset(&b), (2, -2));
+        assert_eq!(b.shortest_offset(&a), (-2, 2));
+    }
+
+    #[test]
+    fn test_rect_shortest_offset_half() {
+        let a: RectangleSpherePoint<20, 10> = RectangleSpherePoint::new(2, 0);
+        let b: RectangleSpherePoint<20, 10> = RectangleSpherePoint::new(12, 0);
+
+        assert_eq!(a.shortest_offset(&b), (10, 0));
+        assert_eq!(b.shortest_offset(&a), (10, 0));
+
+        let a: RectangleSpherePoint<21, 10> = RectangleSpherePoint::new(0, 0);
+        let b: RectangleSpherePoint<21, 10> = RectangleSpherePoint::new(11, 0);
+
+        assert_eq!(a.shortest_offset(&b), (-10, 0));
+        assert_eq!(b.shortest_offset(&a), (10, 0));
+    }
+
+    #[test]
+    fn test_rect_hilbert_index_locality() {
+        let grid: RectangleSphereGrid<(), 16, 16> = RectangleSphereGrid::default();
+
+        let points: Vec<_> = grid.points()
+            .sorted_by_key(|point| point.hilbert_index())
+            .collect();
+
+        assert_eq!(points.iter().map(|point| point.hilbert_index()).collect_vec(), (0..256).collect_vec());
+
+        for (a, b) in points.iter().tuple_windows() {
+            assert_eq!(a.x.abs_diff(b.x) + a.y.abs_diff(b.y), 1);
+        }
+    }
+
+    #[test]
+    fn test_rect_hilbert_index_unique() {
+        let grid: RectangleSphereGrid<(), 20, 7> = RectangleSphereGrid::default();
+
+        let indices: HashSet<_> = grid.points()
+            .map(|point| point.hilbert_index())
+            .collect();
+
+        assert_eq!(indices.len(), 140);
+        assert!(indices.iter().all(|index| *index < 32 * 32));
+    }
+
+    #[test]
+    fn test_rect_crop() {
+        let grid: RectangleSphereGrid<u32, 10, 10> = RectangleSphereGrid::from_fn(|point| point.x * 100 + point.y);
+
+        let cropped: FlatGrid<u32, 2, 2> = grid.crop(3, 4);
+
+        assert_eq!(cropped.to_vec(), [304, 404, 305, 405]);
+    }
+
+    #[test]
+    fn test_rect_crop_wrap() {
+        let grid: RectangleSphereGrid<u32, 10, 10> = RectangleSphereGrid::from_fn(|point| point.x * 100 + point.y);
+
+        let cropped: FlatGrid<u32, 3, 2> = grid.crop(9, 8);
+
+        assert_eq!(cropped.to_vec(), [908, 8, 108, 909, 9, 109]);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_rect_crop_past_pole() {
+        let grid: RectangleSphereGrid<u32, 10, 10> = RectangleSphereGrid::default();
+
+        let _: FlatGrid<u32, 2, 2> = grid.crop(0, 9);
+    }
+
+    #[test]
+    fn test_rect_resize_same() {
+        let grid: RectangleSphereGrid<u32, 20, 10> = RectangleSphereGrid::from_fn(|point| point.x * 100 + point.y);
+
+        let resized: RectangleSphereGrid<u32, 20, 10> = grid.resize();
+
+        assert_eq!(resized, grid);
+    }
+
+    #[test]
+    fn test_rect_resize_up() {
+        let grid: RectangleSphereGrid<(bool, bool), 20, 10> = RectangleSphereGrid::from_fn(|point| (point.y < 5, point.x < 10));
+
+        let resized: RectangleSphereGrid<(bool, bool), 60, 30> = grid.resize();
+
+        for (point, value) in resized.iter() {
+            assert_eq!(*value, (point.y < 15, point.x < 30));
+        }
+
+        let small: RectangleSphereGrid<(bool, bool), 20, 10> = resized.resize();
+
+        assert_eq!(small, grid);
+    }
+
+    #[test]
+    fn test_rect_to_index() {
+        let grid: RectangleSphereGrid<(), 20, 10> = RectangleSphereGrid::default();
+
+        for (i, point) in grid.points().enumerate() {
+            assert_eq!(grid.to_index(&point), i);
+        }
+    }
+
+    #[test]
+    fn test_rect_par_map_to_vec() {
+        let grid: RectangleSphereGrid<u32, 20, 10> = RectangleSphereGrid::from_fn(|point| point.x * point.y);
+
+        let expected: Vec<_> = grid.iter().map(|(point, value)| value + point.x).collect();
+
+        assert_eq!(grid.par_map_to_vec(|point, value| value + point.x), expected);
+    }
+
+    #[test]
+    fn test_rect_points_in_region() {
+        let grid: RectangleSphereGrid<(), 360, 180> = RectangleSphereGrid::default();
+
+        let points: Vec<_> = grid.points_in_region((10.2f64.to_radians(), 19.8f64.to_radians()), (30.2f64.to_radians(), 39.8f64.to_radians())).collect();
+
+        assert_eq!(100, points.len());
+        assert!(points.iter().all(|point| point.x >= 30 && point.x <= 39 && point.y >= 70 && point.y <= 79));
+    }
+
+    #[test]
+    fn test_rect_points_in_region_centre() {
+        let grid: RectangleSphereGrid<(), 360, 180> = RectangleSphereGrid::default();
+
+        // The box holds the centre of the cell at (31, 71) but none of the corners of any cell.
+        let points: Vec<_> = grid.points_in_region((18.2f64.to_radians(), 18.8f64.to_radians()), (31.2f64.to_radians(), 31.8f64.to_radians())).collect();
+
+        assert_eq!(vec![RectangleSpherePoint::new(31, 71)], points);
+        assert_eq!(0, grid.points().filter(|point| {
+            (18.2..=18.8).contains(&point.latitude().to_degrees()) && (31.2..=31.8).contains(&point.longitude().to_degrees())
+        }).count());
+    }
+    
+    #[test]
+    fn test_rect_points_in_region_antimeridian() {
+        let grid: RectangleSphereGrid<(), 360, 180> = RectangleSphereGrid::default();
+
+        let points: Vec<_> = grid.points_in_region((-19.8f64.to_radians(), -10.2f64.to_radians()), (355.2f64.to_radians(), 4.8f64.to_radians())).collect();
+
+        assert_eq!(100, points.len());
+        assert!(points.iter().all(|point| (point.x >= 355 || point.x <= 4) && point.y >= 100 && point.y <= 109));
+    }
+    
+    #[test]
+    fn test_rect_fill_region() {
+        let mut grid: RectangleSphereGrid<u32, 360, 180> = RectangleSphereGrid::from_fn(|point| point.x);
+
+        grid.fill_region((-19.8f64.to_radians(), -10.2f64.to_radians()), (355.2f64.to_radians(), 4.8f64.to_radians()), 1000);
+
+        for (point, value) in grid.iter() {
+            if (point.x >= 355 || point.x <= 4) && point.y >= 100 && point.y <= 109 {
+                assert_eq!(*value, 1000);
+            } else {
+                assert_eq!(*value, point.x);
+            }
+        }
+    }
+
+    #[test]
+    fn test_rect_fill_region_centre() {
+        let mut grid: RectangleSphereGrid<u32, 360, 180> = RectangleSphereGrid::from_fn(|point| point.x);
+
+        // The box holds the centre of the cell at (31, 71) but none of the corners of any cell.
+        grid.fill_region((18.2f64.to_radians(), 18.8f64.to_radians()), (31.2f64.to_radians(), 31.8f64.to_radians()), 1000);
+
+        for (point, value) in grid.iter() {
+            if point == RectangleSpherePoint::new(31, 71) {
+                assert_eq!(*value, 1000);
+            } else {
+                assert_eq!(*value, point.x);
+            }
+        }
+    }
+
+    #[test]
+    fn test_rect_fill_polygon() {
+        let mut grid: RectangleSphereGrid<bool, 360, 180> = RectangleSphereGrid::default();
+
+        // The edges at constant longitude are great circles so the cells next to them can be
+        // checked exactly.
+        let vertices = [(-10.0, 20.0), (-10.0, 40.0), (10.0, 40.0), (10.0, 20.0)]
+            .map(|(lat, lon): (f64, f64)| (lat.to_radians(), lon.to_radians()));
+
+        grid.fill_polygon(&vertices, true);
+
+        for y in 81..99 {
+            assert!(!grid[RectangleSpherePoint::new(19, y)]);
+            assert!(grid[RectangleSpherePoint::new(20, y)]);
+            assert!(grid[RectangleSpherePoint::new(39, y)]);
+            assert!(!grid[RectangleSpherePoint::new(40, y)]);
+        }
+
+        for x in 20..40 {
+            assert!(!grid[RectangleSpherePoint::new(x, 76)]);
+            assert!(grid[RectangleSpherePoint::new(x, 81)]);
+            assert!(grid[RectangleSpherePoint::new(x, 98)]);
+            assert!(!grid[RectangleSpherePoint::new(x, 103)]);
+        }
+
+        assert!(grid.iter().filter(|(_, value)| **value).all(|(point, _)| (20..40).contains(&point.x)));
+    }
+
+    #[test]
+    fn test_rect_fill_polygon_antimeridian() {
+        let mut grid: RectangleSphereGrid<bool, 360, 180> = RectangleSphereGrid::default();
+
+        // The vertices are in the opposite order to `test_rect_fill_polygon`.
+        let vertices = [(-5.0, 350.0), (5.0, 350.0), (5.0, 10.0), (-5.0, 10.0)]
+            .map(|(lat, lon): (f64, f64)| (lat.to_radians(), lon.to_radians()));
+
+        grid.fill_polygon(&vertices, true);
+
+        for (point, value) in grid.iter() {
+            if (point.x >= 350 || point.x < 10) && (86..94).contains(&point.y) {
+                assert!(*value);
+            } else if !(point.x >= 350 || point.x < 10) || !(83..97).contains(&point.y) {
+                assert!(!*value);
+            }
+        }
+    }
+
+    #[test]
+    fn test_rect_fill_polygon_pole() {
+        let mut grid: RectangleSphereGrid<bool, 36, 18> = RectangleSphereGrid::default();
+
+        let vertices: Vec<_> = (0..36)
+            .map(|i| (60f64.to_radians(), (i as f64 * 10.0).to_radians()))
+            .collect();
+
+        grid.fill_polygon(&vertices, true);
+
+        for (point, value) in grid.iter() {
+            assert_eq!(*value, point.y < 3, "{point:?}");
+        }
+    }
+
+    #[test]
+    fn test_rect_points_in_region_matches_scan() {
+        let grid: RectangleSphereGrid<(), 40, 20> = RectangleSphereGrid::default();
+
+        for (lat_range, lon_range) in [((-1.0, 0.3), (5.0, 1.0)), ((0.0, PI / 2.0), (0.0, PI * 2.0)), ((-PI, PI), (-1.0, 1.0))] {
+            let mut points: Vec<_> = grid.points_in_region(lat_range, lon_range).collect();
+            let mut expected: Vec<_> = grid.points().filter(|point| super::in_region(point, lat_range, lon_range)).collect();
+
+            points.sort_by_key(|point| (point.x, point.y));
+            expected.sort_by_key(|point| (point.x, point.y));
+
+            assert_eq!(expected, points);
+        }
+    }
+
+    #[test]
+    fn test_rect_diagonals_pole() {
+        let point: RectangleSpherePoint<10, 10> = RectangleSpherePoint::new(2, 0);
+
+        assert_eq!(point.up_left(), RectangleSpherePoint::new(8, 0));
+        assert_eq!(point.up_right(), RectangleSpherePoint::new(6, 0));
+        assert_eq!(point.down_left(), RectangleSpherePoint::new(1, 1));
+        assert_eq!(point.down_right(), RectangleSpherePoint::new(3, 1));
+
+        let point: RectangleSpherePoint<10, 10> = RectangleSpherePoint::new(7, 9);
+
+        assert_eq!(point.up_left(), RectangleSpherePoint::new(3, 9));
+        assert_eq!(point.up_right(), RectangleSpherePoint::new(1, 9));
+        assert_eq!(point.down_left(), RectangleSpherePoint::new(6, 8));
+        assert_eq!(point.down_right(), RectangleSpherePoint::new(8, 8));
+    }
+
+    #[test]
+    fn test_rect_diagonals_adjacent() {
+        let grid: RectangleSphereGrid<(), 10, 10> = RectangleSphereGrid::from_fn(|_| ());
+
+        for point in grid.points() {
+            let diagonals = [
+                (point.up(), point.up_left(), point.up_right()),
+                (point.down(), point.down_left(), point.down_right()),
+            ];
+
+            for (vertical, diagonal_left, diagonal_right) in diagonals {
+                // The diagonals are horizontal neighbours of the vertical neighbour.
+                assert!(diagonal_left == vertical.left() || diagonal_left == vertical.right());
+                assert!(diagonal_right == vertical.left() || diagonal_right == vertical.right());
+                assert_ne!(diagonal_left, diagonal_right);
+
+                // The diagonals are on the same side of the line of movement as the horizontal
+                // neighbours.
+                let normal = cross(point.cell_center(1.0), vertical.cell_center(1.0));
+                let side = |other: RectangleSpherePoint<10, 10>| dot(normal, other.cell_center(1.0)).signum();
+
+                assert_eq!(side(diagonal_left), side(point.left()), "{:?}", point);
+                assert_eq!(side(diagonal_right), side(point.right()), "{:?}", point);
+            }
+        }
+    }
+
+    #[test]
+    fn test_rect_as_array() {
+        let grid: RectangleSphereGrid<u32, 20, 10> = RectangleSphereGrid::from_fn(|point| point.x * 100 + point.y);
+
+        for y in 0..10 {
+            for x in 0..20 {
+                assert_eq!(grid[RectangleSpherePoint::new(x as u32, y as u32)], grid.as_array()[y][x]);
+            }
+        }
+    }
+
+    #[test]
+    fn test_rect_as_array_mut() {
+        let mut grid: RectangleSphereGrid<u32, 20, 10> = RectangleSphereGrid::default();
+
+        grid.as_array_mut()[3][5] = 7;
+
+        assert_eq!(7, grid[RectangleSpherePoint::new(5, 3)]);
+    }
+
+    #[test]
+    fn test_rect_into_array() {
+        let grid: RectangleSphereGrid<u32, 20, 10> = RectangleSphereGrid::from_fn(|point| point.x * 100 + point.y);
+
+        let array = grid.clone().into_array();
+
+        assert_eq!(grid.as_array(), &array);
+    }
+
+    #[test]
+    fn test_rect_map_reduce() {
+        let grid: RectangleSphereGrid<u64, 20, 10> = RectangleSphereGrid::from_fn(|point| (point.x + point.y) as u64);
+
+        let squares: RectangleSphereGrid<u64, 20, 10> = RectangleSphereGrid::from_fn(|point| grid[*point] * grid[*point]);
+        let expected: u64 = squares.iter().map(|(_, value)| value).sum();
+
+        assert_eq!(expected, grid.map_reduce(|value| value * value, 0, |a, b| a + b));
+    }
+
+    #[test]
+    fn test_rect_par_map_reduce() {
+        let grid: RectangleSphereGrid<u64, 20, 10> = RectangleSphereGrid::from_fn(|point| (point.x + point.y) as u64);
+
+        let squares: RectangleSphereGrid<u64, 20, 10> = RectangleSphereGrid::from_fn(|point| grid[*point] * grid[*point]);
+        let expected: u64 = squares.iter().map(|(_, value)| value).sum();
+
+        assert_eq!(expected, grid.par_map_reduce(|value| value * value, 0, |a, b| a + b));
+    }
+
+    #[test]
+    fn test_rect_symmetrize_equator() {
+        let symmetric: RectangleSphereGrid<f64, 20, 10> = RectangleSphereGrid::from_fn(|point| {
+            point.y.min(9 - point.y) as f64 * 10.0 + point.x as f64
+        });
+
+        assert_eq!(symmetric.symmetrize_equator(), symmetric);
+
+        let asymmetric: RectangleSphereGrid<f64, 20, 10> = RectangleSphereGrid::from_index_fn(|i| i as f64);
+        let symmetrized = asymmetric.symmetrize_equator();
+
+        for (point, value) in symmetrized.iter() {
+            assert_eq!(*value, symmetrized[RectangleSpherePoint::new(point.x, 9 - point.y)]);
+        }
+
+        assert_ne!(symmetrized, asymmetric);
+    }
+
+    #[test]
+    fn test_rect_symmetrize_longitudinal() {
+        let symmetric: RectangleSphereGrid<f64, 20, 10> = RectangleSphereGrid::from_fn(|point| {
+            (point.x % 5) as f64 + point.y as f64 * 10.0
+        });
+
+        assert_eq!(symmetric.symmetrize_longitudinal(4), symmetric);
+
+        let asymmetric: RectangleSphereGrid<f64, 20, 10> = RectangleSphereGrid::from_index_fn(|i| i as f64);
+        let symmetrized = asymmetric.symmetrize_longitudinal(4);
+
+        for (point, value) in symmetrized.iter() {
+            assert_relative_eq!(*value, symmetrized[RectangleSpherePoint::new((point.x + 5) % 20, point.y)]);
+        }
+
+        assert_ne!(symmetrized, asymmetric);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_rect_symmetrize_longitudinal_uneven() {
+        let grid: RectangleSphereGrid<f64, 20, 10> = RectangleSphereGrid::from_fn(|_| 1.0);
+
+        grid.symmetrize_longitudinal(3);
+    }
+
+    #[test]
+    fn test_rect_from_spherical_harmonics_constant() {
+        let grid: RectangleSphereGrid<f64, 20, 10> = RectangleSphereGrid::from_spherical_harmonics(&[(0, 0, 2.0)]);
+
+        for (_, value) in grid.iter() {
+            assert_relative_eq!(*value, 1.0 / PI.sqrt(), epsilon = 1e-12);
+        }
+    }
+
+    #[test]
+    fn test_rect_from_spherical_harmonics_dipole() {
+        let grid: RectangleSphereGrid<f64, 20, 10> = RectangleSphereGrid::from_spherical_harmonics(&[(1, 0, 1.0)]);
+
+        for (point, value) in grid.iter() {
+            assert_relative_eq!(*value, (3.0 / (4.0 * PI)).sqrt() * point.cell_center(1.0).1, epsilon = 1e-12);
+        }
+
+        assert!(grid[RectangleSpherePoint::new(3, 0)] > 0.0);
+        assert!(grid[RectangleSpherePoint::new(3, 9)] < 0.0);
+    }
+
+    #[test]
+    fn test_rect_from_spherical_harmonics_laplacian() {
+        // Y(3, -2) has an eigenvalue of -l(l + 1) = -12.
+        let grid: RectangleSphereGrid<f64, 256, 128> = RectangleSphereGrid::from_spherical_harmonics(&[(3, -2, 1.0)]);
+
+        let laplacian = grid.laplacian(1.0);
+
+        for (point, value) in laplacian.iter().filter(|(point, _)| point.y >= 4 && point.y < 124) {
+            assert_relative_eq!(-12.0 * grid[point], value, epsilon = 0.01);
+        }
+    }
+
+    #[test]
+    fn test_rect_laplacian_constant() {
+        let grid: RectangleSphereGrid<f64, 64, 32> = RectangleSphereGrid::from_fn(|_| 3.0);
+
+        assert!(grid.laplacian(1.0).iter().all(|(_, value)| value.abs() < 1e-9));
+    }
+
+    #[test]
+    fn test_rect_laplacian_harmonic_l1() {
+        // Y(1, 0) is proportional to sin(latitude) and has an eigenvalue of -l(l + 1) = -2.
+        let grid: RectangleSphereGrid<f64, 256, 128> = RectangleSphereGrid::from_fn(|point| point.cell_center(1.0).1);
+
+        let laplacian = grid.laplacian(2.0);
+
+        for (point, value) in laplacian.iter() {
+            assert_relative_eq!(-2.0 / 4.0 * grid[point], value, epsilon = 0.001);
+        }
+    }
+    
+    #[test]
+    fn test_rect_laplacian_harmonic_l2() {
+        // Y(2, 1) is proportional to sin(latitude) cos(latitude) cos(longitude) and has an
+        // eigenvalue of -6.
+        let grid: RectangleSphereGrid<f64, 256, 128> = RectangleSphereGrid::from_fn(|point| {
+            let (x, y, _) = point.cell_center(1.0);
+
+            x * y
+        });
+
+        let laplacian = grid.laplacian(1.0);
+
+        // The cells become very narrow near the poles so the rows next to them are less accurate.
+        for (point, value) in laplacian.iter().filter(|(point, _)| point.y >= 4 && point.y < 124) {
+            assert_relative_eq!(-6.0 * grid[point], value, epsilon = 0.005);
+        }
+    }
+
+    #[test]
+    fn test_rect_point_huge_fold() {
+        let point: RectangleSpherePoint<4000000000, 10> = RectangleSpherePoint::new(3999999999, 9);
+
+        assert_eq!(point.up(), RectangleSpherePoint::new(1999999999, 9));
+        assert_eq!(point.up().up(), RectangleSpherePoint::new(1999999999, 8));
+
+        let point: RectangleSpherePoint<4000000000, 10> = RectangleSpherePoint::new(1999999999, 0);
+
+        assert_eq!(point.up(), RectangleSpherePoint::new(3999999999, 0));
+        assert_eq!(point.down(), RectangleSpherePoint::new(1999999999, 1));
+
+        let point: RectangleSpherePoint<4000000000, 10> = RectangleSpherePoint::new(3999999999, 0);
+
+        assert_eq!(point.down(), RectangleSpherePoint::new(1999999999, 0));
+        assert_eq!(point.right(), RectangleSpherePoint::new(0, 0));
+        assert_eq!(point.right().left(), point);
+    }
+
+    #[test]
+    fn test_rect_point_huge_from_geographic() {
+        let point: RectangleSpherePoint<4000000000, 3000000000> = RectangleSpherePoint::from_geographic(-1.0, 6.0);
+
+        assert_relative_eq!(point.latitude(), -1.0, epsilon = 1e-6);
+        assert_relative_eq!(point.longitude(), 6.0, epsilon = 1e-6);
+    }
+
+    #[test]
+    fn test_rect_point_south_pole() {
+        let point: RectangleSpherePoint<20, 10> = RectangleSpherePoint::from_geographic(-PI / 2.0, 0.0);
+
+        assert_eq!(point, RectangleSpherePoint::new(0, 9));
+    }
+
+    #[test]
+    fn test_rect_point_latitude_0() {
+        let point: RectangleSpherePoint<1000000000, 500000000> = RectangleSpherePoint::from_geographic(0.0, 0.0);
+
+        assert_relative_eq!(0.0, point.latitude());
+    }
+    
+    #[test]
+    fn test_rect_point_latitude_1() {
+        let point: RectangleSpherePoint<1000000000, 500000000> = RectangleSpherePoint::from_geographic(1.0, 0.0);
+
+        assert_relative_eq!(1.0, point.latitude(), epsilon = 0.001);
+    }
+    
+    #[test]
+    fn test_rect_point_latitude_minus_1() {
+        let point: RectangleSpherePoint<1000000000, 500000000> = RectangleSpherePoint::from_geographic(-1.0, 0.0);
+
+        assert_relative_eq!(-1.0, point.latitude(), epsilon = 0.001);
+    }
+    
+    #[test]
+    fn test_rect_point_latitude_0_far() {
+        let point: RectangleSpherePoint<1000000000, 500000000> = RectangleSpherePoint::from_geographic(0.0, 4.0);
+
+        assert_relative_eq!(0.0, point.latitude());
+    }
+    
+    #[test]
+    fn test_rect_point_latitude_1_far() {
+        let point: RectangleSpherePoint<1000000000, 500000000> = RectangleSpherePoint::from_geographic(1.0, 2.0);
+
+        assert_relative_eq!(1.0, point.latitude(), epsilon = 0.001);
+    }
+    
+    #[test]
+    fn test_rect_point_latitude_minus_1_far() {
+        let point: RectangleSpherePoint<1000000000, 500000000> = RectangleSpherePoint::from_geographic(-1.0, 3.0);
+
+        assert_relative_eq!(-1.0, point.latitude(), epsilon = 0.001);
+    }
+    
+    #[test]
+    fn test_rect_point_longitude_0() {
+        let point: RectangleSpherePoint<1000000000, 500000000> = RectangleSpherePoint::from_geographic(0.0, 0.0);
+
+        assert_relative_eq!(0.0, point.longitude(), epsilon = 0.001);
+    }
+    
+    #[test]
+    fn test_rect_point_longitude_1() {
+        let point: RectangleSpherePoint<1000000000, 500000000> = RectangleSpherePoint::from_geographic(0.0, 1.0);
+
+        assert_relative_eq!(1.0, point.longitude(), epsilon = 0.001);
+    }
+    
+    #[test]
+    fn test_rect_point_longitude_2() {
+        let point: RectangleSpherePoint<1000000000, 500000000> = RectangleSpherePoint::from_geographic(0.0, 2.0);
+
+        assert_relative_eq!(2.0, point.longitude(), epsilon = 0.001);
+    }
+    
+    #[test]
+    fn test_rect_point_longitude_4() {
+        let point: RectangleSpherePoint<1000000000, 500000000> = RectangleSpherePoint::from_geographic(0.0, 4.0);
+
+        assert_relative_eq!(4.0, point.longitude(), epsilon = 0.001);
+    }
+    
+    #[test]
+    fn test_rect_point_longitude_6() {
+        let point: RectangleSpherePoint<1000000000, 500000000> = RectangleSpherePoint::from_geographic(0.0, 6.0);
+
+        assert_relative_eq!(6.0, point.longitude(), epsilon = 0.001);
+    }
+    
+    #[test]
+    fn test_rect_point_longitude_0_north() {
+        let point: RectangleSpherePoint<1000000000, 500000000> = RectangleSpherePoint::from_geographic(-1.5, 0.0);
+
+        assert_relative_eq!(0.0, point.longitude(), epsilon = 0.001);
+    }
+    
+    #[test]
+    fn test_rect_point_longitude_1_north() {
+        let point: RectangleSpherePoint<1000000000, 500000000> = RectangleSpherePoint::from_geographic(-1.5, 1.0);
+
+        assert_relative_eq!(1.0, point.longitude(), epsilon = 0.001);
+    }
+    
+    #[test]
+    fn test_rect_point_longitude_2_north() {
+        let point: RectangleSpherePoint<1000000000, 500000000> = RectangleSpherePoint::from_geographic(-1.5, 2.0);
+
+        assert_relative_eq!(2.0, point.longitude(), epsilon = 0.001);
+    }
+    
+    #[test]
+    fn test_rect_point_longitude_4_north() {
+        let point: RectangleSpherePoint<1000000000, 500000000> = RectangleSpherePoint::from_geographic(-1.5, 4.0);
+
+        assert_relative_eq!(4.0, point.longitude(), epsilon = 0.001);
+    }
+    
+    #[test]
+    fn test_rect_point_longitude_6_north() {
+        let point: RectangleSpherePoint<1000000000, 500000000> = RectangleSpherePoint::from_geographic(-1.5, 6.0);
+
+        assert_relative_eq!(6.0, point.longitude(), epsilon = 0.001);
+    }
+    
+    #[test]
+    fn test_rect_point_longitude_0_south() {
+        let point: RectangleSpherePoint<1000000000, 500000000> = RectangleSpherePoint::from_geographic(1.5, 0.0);
+
+        assert_relative_eq!(0.0, point.longitude(), epsilon = 0.001);
+    }
+    
+    #[test]
+    fn test_rect_point_longitude_1_south() {
+        let point: RectangleSpherePoint<1000000000, 500000000> = RectangleSpherePoint::from_geographic(1.5, 1.0);
+
+        assert_relative_eq!(1.0, point.longitude(), epsilon = 0.001);
+    }
+    
+    #[test]
+    fn test_rect_point_longitude_2_south() {
+        let point: RectangleSpherePoint<1000000000, 500000000> = RectangleSpherePoint::from_geographic(1.5, 2.0);
+
+        assert_relative_eq!(2.0, point.longitude(), epsilon = 0.001);
+    }
+    
+    #[test]
+    fn test_rect_point_longitude_4_south() {
+        let point: RectangleSpherePoint<1000000000, 500000000> = RectangleSpherePoint::from_geographic(1.5, 4.0);
+
+        assert_relative_eq!(4.0, point.longitude(), epsilon = 0.001);
+    }
+    
+    #[test]
+    fn test_rect_point_longitude_6_south() {
+        let point: RectangleSpherePoint<1000000000, 500000000> = RectangleSpherePoint::from_geographic(1.5, 6.0);
+
+        assert_relative_eq!(6.0, point.longitude(), epsilon = 0.001);
+    }
+
+    #[test]
+    fn test_cube_point_up_middle() {
+        let point: CubeSpherePoint<5> = CubeSpherePoint::new(CubeFace::Front, 3, 4);
+
+        assert_eq!(CubeSpherePoint::new(CubeFace::Front, 3, 3), point.up());
+    }
+    
+    #[test]
+    fn test_cube_point_up_top() {
+        let point: CubeSpherePoint<5> = CubeSpherePoint::new(CubeFace::Front, 0, 0);
+
+        assert_eq!(CubeSpherePoint::new(CubeFace::Top, 0, 4), point.up());
+    }
+    
+    #[test]
+    fn test_cube_point_up_bottom() {
+        let point: CubeSpherePoint<5> = CubeSpherePoint::new(CubeFace::Front, 0, 4);
+
+        assert_eq!(CubeSpherePoint::new(CubeFace::Front, 0, 3), point.up());
+    }
+    
+    #[test]
+    fn test_cube_point_down_middle() {
+        let point: CubeSpherePoint<10> = CubeSpherePoint::new(CubeFace::Front, 3, 4);
+
+        assert_eq!(CubeSpherePoint::new(CubeFace::Front, 3, 5), point.down());
+    }
+    
+    #[test]
+    fn test_cube_point_down_top() {
+        let point: CubeSpherePoint<10> = CubeSpherePoint::new(CubeFace::Front, 0, 0);
+
+        assert_eq!(CubeSpherePoint::new(CubeFace::Front, 0, 1), point.down());
+    }
+    
+    #[test]
+    fn test_cube_point_down_bottom() {
+        let point: CubeSpherePoint<10> = CubeSpherePoint::new(CubeFace::Front, 0, 9);
+
+        assert_eq!(CubeSpherePoint::new(CubeFace::Bottom, 0, 0), point.down());
+    }
+
+    #[test]
+    fn test_cube_point_left_middle() {
+        let point: CubeSpherePoint<10> = CubeSpherePoint::new(CubeFace::Left, 5, 5);
+
+        assert_eq!(CubeSpherePoint::new(CubeFace::Left, 4, 5), point.left());
+    }
+    
+    #[test]
+    fn test_cube_point_left_left() {
+        let point: CubeSpherePoint<10> = CubeSpherePoint::new(CubeFace::Left, 0, 5);
+
+        assert_eq!(CubeSpherePoint::new(CubeFace::Back, 0, 5), point.left());
+    }
+   
+    #[test]
+    fn test_cube_point_left_right() {
+        let point: CubeSpherePoint<10> = CubeSpherePoint::new(CubeFace::Left, 9, 5);
+
+        assert_eq!(CubeSpherePoint::new(CubeFace::Left, 8, 5), point.left());
+    }
+
+    #[test]
+    fn test_cube_point_right_middle() {
+        let point: CubeSpherePoint<10> = CubeSpherePoint::new(CubeFace::Right, 5, 5);
+
+        assert_eq!(CubeSpherePoint::new(CubeFace::Right, 6, 5), point.right());
+    }
+    
+    #[test]
+    fn test_cube_point_right_left() {
+        let point: CubeSpherePoint<10> = CubeSpherePoint::new(CubeFace::Right, 0, 5);
+
+        assert_eq!(CubeSpherePoint::new(CubeFace::Right, 1, 5), point.right());
+    }
+   
+    #[test]
+    fn test_cube_point_right_right() {
+        let point: CubeSpherePoint<10> = CubeSpherePoint::new(CubeFace::Right, 9, 5);
+
+        assert_eq!(CubeSpherePoint::new(CubeFace::Back, 9, 5), point.right());
+    }
+
+    #[test]
+    fn test_cube_point_from_geographic_equator() {
+        let point: CubeSpherePoint<100> = CubeSpherePoint::from_geographic(0.0, PI);
+
+        assert_eq!(CubeSpherePoint::new(CubeFace::Back, 50, 50), point);
+    }
+    
+    #[test]
+    fn test_cube_point_from_geographic_north_pole() {
+        let point: CubeSpherePoint<100> = CubeSpherePoint::from_geographic(PI / 2.0, PI);
+
+        assert_eq!(CubeSpherePoint::new(CubeFace::Top, 50, 50), point);
+    }
+    
+    #[test]
+    fn test_cube_point_from_geographic_south_pole() {
+        let point: CubeSpherePoint<100> = CubeSpherePoint::from_geographic(-PI / 2.0, PI);
+
+        assert_eq!(CubeSpherePoint::new(CubeFace::Bottom, 50, 50), point);
+    }
+    
+    #[test]
+    fn test_cube_point_from_geographic_east() {
+        let point: CubeSpherePoint<100> = CubeSpherePoint::from_geographic(0.0, -PI / 2.0);
+
+        assert_eq!(CubeSpherePoint::new(CubeFace::Left, 50, 50), point);
+    }
+    
+    #[test]
+    fn test_cube_point_from_geographic_west() {
+        let point: CubeSpherePoint<100> = CubeSpherePoint::from_geographic(0.0, PI / 2.0);
+
+        assert_eq!(CubeSpherePoint::new(CubeFace::Right, 50, 50), point);
+    }
+    
+    #[test]
+    fn test_cube_point_from_geographic_less_west() {
+        let point: CubeSpherePoint<100> = CubeSpherePoint::from_geographic(0.0, PI / 2.0 - 0.2);
+
+        assert_eq!(CubeSpherePoint::new(CubeFace::Right, 39, 50), point);
+    }
+
+
+    #[test]
+    fn test_cube_point_nearest_point_front() {
+        let point: CubeSpherePoint<10> = CubeSpherePoint::nearest_point(0.0, 0.0, 3.0);
+
+        assert_eq!(CubeSpherePoint::new(CubeFace::Front, 5, 5), point);
+    }
+    
+    #[test]
+    fn test_cube_point_nearest_point_top() {
+        let point: CubeSpherePoint<10> = CubeSpherePoint::nearest_point(0.0, 0.5, 0.0);
+
+        assert_eq!(CubeSpherePoint::new(CubeFace::Top, 5, 5), point);
+    }
+
+    #[test]
+    fn test_cube_sample_nearest_3d_front() {
+        let grid: CubeSphereGrid<CubeSpherePoint<10>, 10> = CubeSphereGrid::from_fn(|point| *point);
+
+        assert_eq!(&CubeSpherePoint::new(CubeFace::Front, 5, 5), grid.sample_nearest_3d(0.0, 0.0, 2.5));
+    }
+
+    #[test]
+    fn test_cube_point_cell_corner_0() {
+        let point: CubeSpherePoint<10> = CubeSpherePoint::new(CubeFace::Back, 5, 3);
+
+        assert_eq!(point.position(2.0), point.cell_corner(0, 2.0));
+    }
+
+    #[test]
+    fn test_cube_point_cell_center_inside_corners() {
+        let grid: CubeSphereGrid<(), 8> = CubeSphereGrid::default();
+
+        for point in grid.points() {
+            let corners = [0, 1, 2, 3].map(|corner| point.cell_corner(corner, 1.0));
+
+            assert!(inside_quad(corners, point.cell_center(1.0)), "{:?}", point);
+        }
+    }
+    
+    #[test]
+    fn test_cube_point_cell_center_front() {
+        let point: CubeSpherePoint<9> = CubeSpherePoint::new(CubeFace::Front, 4, 4);
+
+        let (x, y, z) = point.cell_center(1.0);
+
+        assert_relative_eq!(0.0, x);
+        assert_relative_eq!(0.0, y);
+        assert_relative_eq!(1.0, z);
+    }
+
+    #[test]
+    fn test_cube_point_up_loop() {
+        let start: CubeSpherePoint<3> = CubeSpherePoint::new(CubeFace::Bottom, 1, 2);
+
+        assert_eq!(start, start.up().up().up()
+                   .up().up().up()
+                   .up().up().up()
+                   .up().up().up());
+    }
+    
+    #[test]
+    fn test_cube_point_down_loop() {
+        let start: CubeSpherePoint<3> = CubeSpherePoint::new(CubeFace::Top, 0, 0);
+
+        assert_eq!(start, start.down().down().down()
+                   .down().down().down()
+                   .down().down().down()
+                   .down().down().down());
+    }
+    
+    #[test]
+    fn test_cube_point_left_loop() {
+        let start: CubeSpherePoint<3> = CubeSpherePoint::new(CubeFace::Back, 1, 0);
+
+        assert_eq!(start, start.left().left().left()
+                   .left().left().left()
+                   .left().left().left()
+                   .left().left().left());
+    }
+    
+    #[test]
+    fn test_cube_point_right_loop() {
+        let start: CubeSpherePoint<3> = CubeSpherePoint::new(CubeFace::Front, 0, 2);
+
+        assert_eq!(start, start.right().right().right()
+                   .right().right().right()
+                   .right().right().right()
+                   .right().right().right());
+    }
+
+    #[test]
+    fn test_cube_point_up_inverse_middle() {
+        let start: CubeSpherePoint<10> = CubeSpherePoint::new(CubeFace::Front, 5, 3);
+
+        assert_eq!(start, start.up().down());
+    }
+    
+    #[test]
+    fn test_cube_point_down_inverse_middle() {
+        let start: CubeSpherePoint<10> = CubeSpherePoint::new(CubeFace::Front, 5, 3);
+
+        assert_eq!(start, start.down().up());
+    }
+    
     #[test]
-    fn test_rect_point_up_middle() {
-        let point: RectangleSpherePoint<10, 10> = RectangleSpherePoint::new(3, 4);
+    fn test_cube_point_left_inverse_middle() {
+        let start: CubeSpherePoint<10> = CubeSpherePoint::new(CubeFace::Front, 5, 3);
 
-        assert_eq!(RectangleSpherePoint::new(3, 3), point.up());
+        assert_eq!(start, start.left().right());
     }
     
     #[test]
-    fn test_rect_point_up_top_left() {
-        let point: RectangleSpherePoint<10, 10> = RectangleSpherePoint::new(0, 0);
+    fn test_cube_point_right_inverse_middle() {
+        let start: CubeSpherePoint<10> = CubeSpherePoint::new(CubeFace::Front, 5, 3);
 
-        assert_eq!(RectangleSpherePoint::new(5, 0), point.up());
+        assert_eq!(start, start.right().left());
     }
     
     #[test]
-    fn test_rect_point_up_top_right() {
-        let point: RectangleSpherePoint<10, 10> = RectangleSpherePoint::new(9, 0);
+    fn test_cube_point_up_inverse_edge() {
+        let start: CubeSpherePoint<10> = CubeSpherePoint::new(CubeFace::Front, 0, 0);
 
-        assert_eq!(RectangleSpherePoint::new(9, 1), point.up());
+        assert_eq!(start, start.up().down());
     }
     
     #[test]
-    fn test_rect_point_up_bottom_left() {
-        let point: RectangleSpherePoint<10, 10> = RectangleSpherePoint::new(0, 9);
+    fn test_cube_point_down_inverse_edge() {
+        let start: CubeSpherePoint<10> = CubeSpherePoint::new(CubeFace::Front, 0, 9);
 
-        assert_eq!(RectangleSpherePoint::new(0, 8), point.up());
+        assert_eq!(start, start.down().up());
     }
     
     #[test]
-    fn test_rect_point_up_bottom_right() {
-        let point: RectangleSpherePoint<10, 10> = RectangleSpherePoint::new(9, 9);
+    fn test_cube_point_left_inverse_edge() {
+        let start: CubeSpherePoint<10> = CubeSpherePoint::new(CubeFace::Front, 0, 0);
 
-        assert_eq!(RectangleSpherePoint::new(4, 9), point.up());
+        assert_eq!(start, start.left().right());
     }
     
     #[test]
-    fn test_rect_point_down_middle() {
-        let point: RectangleSpherePoint<10, 10> = RectangleSpherePoint::new(3, 4);
+    fn test_cube_point_right_inverse_edge() {
+        let start: CubeSpherePoint<10> = CubeSpherePoint::new(CubeFace::Front, 9, 0);
 
-        assert_eq!(RectangleSpherePoint::new(3, 5), point.down());
+        assert_eq!(start, start.right().left());
+    }
+
+    #[test]
+    fn test_cube_from_fn() {
+        let grid: CubeSphereGrid<u16, 100> = CubeSphereGrid::from_fn(|point| point.x + point.y);
+
+        assert_eq!(15, grid[CubeSpherePoint::new(CubeFace::Front, 5, 10)]);
     }
     
     #[test]
-    fn test_rect_point_down_top_left() {
-        let point: RectangleSpherePoint<10, 10> = RectangleSpherePoint::new(0, 0);
+    fn test_cube_from_neighbours() {
+        let grid: CubeSphereGrid<u16, 10> = CubeSphereGrid::from_fn(|point| point.x);
 
-        assert_eq!(RectangleSpherePoint::new(0, 1), point.down());
+        let grid2 = grid.map_neighbours(|current, up, down, left, right| current + up + down + left + right);
+
+        assert_eq!(25, grid2[CubeSpherePoint::new(CubeFace::Front, 5, 3)])
     }
     
     #[test]
-    fn test_rect_point_down_top_right() {
-        let point: RectangleSpherePoint<10, 10> = RectangleSpherePoint::new(9, 0);
+    fn test_cube_from_neighbours_diagonals() {
+        let grid: CubeSphereGrid<u16, 10> = CubeSphereGrid::from_fn(|point| point.x);
 
-        assert_eq!(RectangleSpherePoint::new(4, 0), point.down());
+        let grid2 = grid.map_neighbours_diagonals(|up_left, up, up_right, left, current, right, down_left, down, down_right| up_left + up + up_right + left + current + right + down_left + down + down_right);
+
+        assert_eq!(4 * 3 + 5 * 3 + 6 * 3, grid2[CubeSpherePoint::new(CubeFace::Front, 5, 3)])
     }
     
     #[test]
-    fn test_rect_point_down_bottom_left() {
-        let point: RectangleSpherePoint<10, 10> = RectangleSpherePoint::new(0, 9);
+    fn test_cube_set_from_neighbours_with_position() {
+        let source: CubeSphereGrid<u16, 10> = CubeSphereGrid::default();
+        let mut grid: CubeSphereGrid<u16, 10> = CubeSphereGrid::default();
 
-        assert_eq!(RectangleSpherePoint::new(5, 9), point.down());
+        grid.set_from_neighbours_with_position(&source, |_, point, _, _, _, _| point.y);
+
+        assert!(grid.iter().all(|(point, value)| point.y == *value));
     }
     
     #[test]
-    fn test_rect_point_down_bottom_right() {
-        let point: RectangleSpherePoint<10, 10> = RectangleSpherePoint::new(9, 9);
+    fn test_cube_set_from_neighbours_diagonals_par_with_position() {
+        let source: CubeSphereGrid<u16, 10> = CubeSphereGrid::default();
+        let mut grid: CubeSphereGrid<u16, 10> = CubeSphereGrid::default();
 
-        assert_eq!(RectangleSpherePoint::new(9, 8), point.down());
+        grid.set_from_neighbours_diagonals_par_with_position(&source, |point, _, _, _, _, _, _, _, _, _| point.y);
+
+        assert!(grid.iter().all(|(point, value)| point.y == *value));
     }
 
     #[test]
-    fn test_rect_point_left_middle() {
-        let point: RectangleSpherePoint<10, 10> = RectangleSpherePoint::new(5, 5);
+    fn test_cube_iteration_order() {
+        let grid: CubeSphereGrid<(CubeFace, u16, u16), 2> = CubeSphereGrid::from_fn(|point| (point.face, point.x, point.y));
 
-        assert_eq!(RectangleSpherePoint::new(4, 5), point.left());
+        let expected = [
+            (CubeFace::Top, 0, 0), (CubeFace::Top, 1, 0), (CubeFace::Top, 0, 1), (CubeFace::Top, 1, 1),
+            (CubeFace::Left, 0, 0), (CubeFace::Left, 1, 0),
+        ];
+
+        let points: Vec<_> = grid.points().map(|point| (point.face, point.x, point.y)).collect();
+        let iter: Vec<_> = grid.iter().map(|(_, value)| *value).collect();
+        let into_iter: Vec<_> = grid.clone().into_iter().map(|(_, value)| value).collect();
+
+        assert_eq!(points[..6], expected);
+        assert_eq!(points, iter);
+        assert_eq!(points, into_iter);
+
+        let faces: Vec<_> = points.iter().step_by(4).map(|(face, _, _)| *face).collect();
+
+        assert_eq!(faces, [CubeFace::Top, CubeFace::Left, CubeFace::Front, CubeFace::Right, CubeFace::Back, CubeFace::Bottom]);
     }
-    
+
+    #[cfg(feature = "serde")]
     #[test]
-    fn test_rect_point_left_left() {
-        let point: RectangleSpherePoint<10, 10> = RectangleSpherePoint::new(0, 5);
+    fn test_cube_serde() {
+        let grid: CubeSphereGrid<u16, 5> = CubeSphereGrid::from_fn(|point| point.face as u16 * 100 + point.x * 10 + point.y);
 
-        assert_eq!(RectangleSpherePoint::new(9, 5), point.left());
+        let json = serde_json::to_string(&grid).unwrap();
+        let restored: CubeSphereGrid<u16, 5> = serde_json::from_str(&json).unwrap();
+
+        assert_eq!(restored, grid);
+        assert!(serde_json::from_str::<CubeSphereGrid<u16, 4>>(&json).is_err());
     }
-   
+
     #[test]
-    fn test_rect_point_left_right() {
-        let point: RectangleSpherePoint<10, 10> = RectangleSpherePoint::new(9, 5);
+    fn test_cube_solid_angle() {
+        let grid: CubeSphereGrid<(), 10> = CubeSphereGrid::default();
 
-        assert_eq!(RectangleSpherePoint::new(8, 5), point.left());
+        let total: f64 = grid.points().map(|point| point.solid_angle()).sum();
+
+        assert_relative_eq!(total, 4.0 * PI, epsilon = 1e-9);
+        assert!(grid.points().all(|point| point.solid_angle() > 0.0));
     }
 
     #[test]
-    fn test_rect_point_right_middle() {
-        let point: RectangleSpherePoint<10, 10> = RectangleSpherePoint::new(5, 5);
+    fn test_cube_distortion() {
+        let grid: CubeSphereGrid<(), 10> = CubeSphereGrid::default();
 
-        assert_eq!(RectangleSpherePoint::new(6, 5), point.right());
+        let centre: CubeSpherePoint<10> = CubeSpherePoint::new(CubeFace::Front, 5, 5);
+        let corner: CubeSpherePoint<10> = CubeSpherePoint::new(CubeFace::Front, 0, 0);
+
+        assert!(centre.distortion() > 1.0);
+        assert!(corner.distortion() < 1.0);
+
+        for face in [CubeFace::Top, CubeFace::Left, CubeFace::Right, CubeFace::Back, CubeFace::Bottom] {
+            assert_relative_eq!(CubeSpherePoint::<10>::new(face, 4, 4).distortion(), centre.distortion(), epsilon = 1e-12);
+            assert_relative_eq!(CubeSpherePoint::<10>::new(face, 9, 0).distortion(), corner.distortion(), epsilon = 1e-12);
+        }
+
+        let mean = grid.points().map(|point| point.distortion()).sum::<f64>() / 600.0;
+
+        assert_relative_eq!(mean, 1.0, epsilon = 1e-9);
     }
-    
+
     #[test]
-    fn test_rect_point_right_left() {
-        let point: RectangleSpherePoint<10, 10> = RectangleSpherePoint::new(0, 5);
+    fn test_cube_to_vec() {
+        let grid: CubeSphereGrid<u16, 5> = CubeSphereGrid::from_fn(|point| point.face as u16 * 100 + point.x * 10 + point.y);
 
-        assert_eq!(RectangleSpherePoint::new(1, 5), point.right());
+        let values = grid.to_vec();
+
+        let top = CubeFace::Top as u16 * 100;
+
+        assert_eq!(values[..6], [top, top + 10, top + 20, top + 30, top + 40, top + 1]);
+        assert_eq!(values[25], CubeFace::Left as u16 * 100);
+        assert_eq!(CubeSphereGrid::from_vec(values), Some(grid));
+        assert_eq!(CubeSphereGrid::<u16, 5>::from_vec(vec![0; 149]), None);
     }
-   
+
     #[test]
-    fn test_rect_point_right_right() {
-        let point: RectangleSpherePoint<10, 10> = RectangleSpherePoint::new(9, 5);
+    fn test_cube_iso_latitude_points() {
+        let points = CubeSpherePoint::<10>::iso_latitude_points(0.0, 1e-9);
 
-        assert_eq!(RectangleSpherePoint::new(0, 5), point.right());
+        let mut faces: Vec<_> = points.iter().map(|point| point.face).collect();
+        faces.dedup();
+
+        assert_eq!(faces, [CubeFace::Left, CubeFace::Front, CubeFace::Right, CubeFace::Back]);
+        assert_eq!(points.len(), 40);
+        assert!(points.iter().all(|point| point.latitude().abs() <= 1e-9));
     }
 
     #[test]
-    fn test_rect_point_from_geographic_equator() {
-        let point: RectangleSpherePoint<100, 100> = RectangleSpherePoint::from_geographic(0.0, PI);
+    fn test_cube_iso_latitude_points_matches_scan() {
+        let grid: CubeSphereGrid<(), 10> = CubeSphereGrid::default();
 
-        assert_eq!(RectangleSpherePoint::new(50, 50), point);
+        let points = CubeSpherePoint::<10>::iso_latitude_points(0.5, 0.1);
+        let expected: Vec<_> = grid.points()
+            .filter(|point| (point.latitude() - 0.5).abs() <= 0.1)
+            .collect();
+
+        assert!(!points.is_empty());
+        assert_eq!(points, expected);
     }
-    
+
     #[test]
-    fn test_rect_point_from_geographic_north_pole() {
-        let point: RectangleSpherePoint<100, 100> = RectangleSpherePoint::from_geographic(PI / 2.0, PI);
+    fn test_cube_neighbour_positions() {
+        // With a size of 2 the corners of the cells lie on a cube from -2 to 2 before being
+        // projected onto the sphere. Moving down and left crosses onto the bottom and left faces.
+        let point: CubeSpherePoint<2> = CubeSpherePoint::new(CubeFace::Front, 0, 1);
+        let r3 = 1.0 / 3f64.sqrt();
+
+        let expected = [
+            (-r3, -r3, r3), // Front (0, 0)
+            (-r3, -r3, r3), // Bottom (0, 0)
+            (-1.0, 0.0, 0.0), // Left (1, 1)
+            (0.0, 0.0, 1.0), // Front (1, 1)
+        ];
+
+        for (position, expected) in point.neighbour_positions(1.0).into_iter().zip(expected) {
+            assert_relative_eq!(position.0, expected.0, epsilon = 1e-12);
+            assert_relative_eq!(position.1, expected.1, epsilon = 1e-12);
+            assert_relative_eq!(position.2, expected.2, epsilon = 1e-12);
+        }
 
-        assert_eq!(RectangleSpherePoint::new(50, 0), point);
+        let scaled = point.neighbour_positions(2.0);
+
+        assert_relative_eq!(scaled[2].0, -2.0, epsilon = 1e-12);
     }
-    
+
     #[test]
-    fn test_rect_point_from_geographic_south_pole() {
-        let point: RectangleSpherePoint<100, 100> = RectangleSpherePoint::from_geographic(-PI / 2.0, PI);
+    fn test_cube_diagonal_neighbour_positions() {
+        // The corner cell of the top face next to the right and front faces.
+        let point: CubeSpherePoint<2> = CubeSpherePoint::new(CubeFace::Top, 1, 1);
+        let r2 = FRAC_1_SQRT_2;
+        let r3 = 1.0 / 3f64.sqrt();
+
+        let expected = [
+            (-r3, r3, -r3), // Top (0, 0)
+            (0.0, r2, -r2), // Top (1, 0)
+            (r3, -r3, r3), // Right (0, 0)
+            (-r2, r2, 0.0), // Top (0, 1)
+            (r2, -r2, 0.0), // Right (1, 0)
+            (-r3, -r3, r3), // Front (0, 0)
+            (0.0, -r2, r2), // Front (1, 0)
+            (r3, -r3, r3), // Right (0, 0)
+        ];
+
+        for (position, expected) in point.diagonal_neighbour_positions(1.0).into_iter().zip(expected) {
+            assert_relative_eq!(position.0, expected.0, epsilon = 1e-12);
+            assert_relative_eq!(position.1, expected.1, epsilon = 1e-12);
+            assert_relative_eq!(position.2, expected.2, epsilon = 1e-12);
+        }
+    }
 
-        assert_eq!(RectangleSpherePoint::new(50, 99), point);
+    #[test]
+    fn test_cube_checked() {
+        let grid: CubeSphereGrid<(), 5> = CubeSphereGrid::default();
+
+        for point in grid.points() {
+            assert_eq!(point.checked_up(), Some(point.up()));
+            assert_eq!(point.checked_down(), Some(point.down()));
+            assert_eq!(point.checked_left(), Some(point.left()));
+            assert_eq!(point.checked_right(), Some(point.right()));
+        }
     }
-    
+
     #[test]
-    fn test_rect_point_from_geographic_equator_wrap_north() {
-        let point: RectangleSpherePoint<100, 100> = RectangleSpherePoint::from_geographic(-PI, PI);
+    fn test_cube_neighbour_flips_orientation() {
+        let grid: CubeSphereGrid<(), 5> = CubeSphereGrid::default();
 
-        assert_eq!(RectangleSpherePoint::new(50, 50), point);
+        for point in grid.points() {
+            for dir in [Direction::Up, Direction::Down, Direction::Left, Direction::Right] {
+                assert!(!point.neighbour_flips_orientation(dir));
+            }
+        }
     }
-    
+
     #[test]
-    fn test_rect_point_from_geographic_equator_wrap_south() {
-        let point: RectangleSpherePoint<100, 100> = RectangleSpherePoint::from_geographic(PI, PI);
+    fn test_cube_to_index() {
+        let grid: CubeSphereGrid<(), 5> = CubeSphereGrid::default();
 
-        assert_eq!(RectangleSpherePoint::new(50, 50), point);
+        for (i, point) in grid.points().enumerate() {
+            assert_eq!(grid.to_index(&point), i);
+        }
     }
-    
+
     #[test]
-    fn test_rect_point_from_geographic_east() {
-        let point: RectangleSpherePoint<100, 100> = RectangleSpherePoint::from_geographic(0.0, PI * 2.0);
+    fn test_cube_par_map_to_vec() {
+        let grid: CubeSphereGrid<u16, 5> = CubeSphereGrid::from_fn(|point| point.x * point.y);
 
-        assert_eq!(RectangleSpherePoint::new(0, 50), point);
+        let expected: Vec<_> = grid.iter().map(|(point, value)| value + point.x).collect();
+
+        assert_eq!(grid.par_map_to_vec(|point, value| value + point.x), expected);
     }
-    
+
     #[test]
-    fn test_rect_point_from_geographic_west() {
-        let point: RectangleSpherePoint<100, 100> = RectangleSpherePoint::from_geographic(0.0, 0.0);
+    fn test_cube_hilbert_index() {
+        let grid: CubeSphereGrid<(), 8> = CubeSphereGrid::default();
 
-        assert_eq!(RectangleSpherePoint::new(0, 50), point);
+        let points: Vec<_> = grid.points()
+            .sorted_by_key(|point| point.hilbert_index())
+            .collect();
+
+        assert_eq!(points.iter().map(|point| point.hilbert_index()).collect_vec(), (0..384).collect_vec());
+
+        for (a, b) in points.iter().tuple_windows() {
+            if a.face == b.face {
+                assert_eq!(a.x.abs_diff(b.x) + a.y.abs_diff(b.y), 1);
+            }
+        }
     }
 
     #[test]
-    fn test_rect_point_up_loop() {
-        let start: RectangleSpherePoint<10, 5> = RectangleSpherePoint::new(0, 3);
+    fn test_cube_hilbert_index_unique() {
+        let grid: CubeSphereGrid<(), 10> = CubeSphereGrid::default();
 
-        assert_eq!(start, start.up().up().up().up().up().up().up().up().up().up());
+        let indices: HashSet<_> = grid.points()
+            .map(|point| point.hilbert_index())
+            .collect();
+
+        assert_eq!(indices.len(), 600);
     }
-    
+
     #[test]
-    fn test_rect_point_down_loop() {
-        let start: RectangleSpherePoint<10, 5> = RectangleSpherePoint::new(5, 3);
+    fn test_cube_fill_polygon() {
+        let mut grid: CubeSphereGrid<bool, 10> = CubeSphereGrid::default();
 
-        assert_eq!(start, start.down().down().down().down().down().down().down().down().down().down());
+        let vertices = [(-0.3, -0.3), (-0.3, 0.3), (0.3, 0.3), (0.3, -0.3)];
+
+        grid.fill_polygon(&vertices, true);
+
+        for (point, value) in grid.iter() {
+            let (x, y, z) = point.cell_center(1.0);
+            let (latitude, longitude) = (y.asin(), x.atan2(z));
+
+            if latitude.abs() < 0.25 && longitude.abs() < 0.25 {
+                assert!(*value);
+            } else if latitude.abs() > 0.35 || longitude.abs() > 0.35 {
+                assert!(!*value);
+            }
+        }
+
+        assert!(grid.iter().filter(|(_, value)| **value).all(|(point, _)| point.face == CubeFace::Front));
     }
-    
+
     #[test]
-    fn test_rect_point_left_loop() {
-        let start: RectangleSpherePoint<10, 5> = RectangleSpherePoint::new(0, 3);
+    fn test_cube_rescale() {
+        for point in CubeSphereGrid::<(), 10>::default().points() {
+            let fine: CubeSpherePoint<20> = point.rescale();
 
-        assert_eq!(start, start.left().left().left().left().left().left().left().left().left().left());
+            assert_eq!(fine.face, point.face);
+            assert!(fine.x / 2 == point.x && fine.y / 2 == point.y);
+            assert_eq!(fine.rescale::<10>(), point);
+        }
     }
-    
+
     #[test]
-    fn test_rect_point_right_loop() {
-        let start: RectangleSpherePoint<10, 5> = RectangleSpherePoint::new(0, 3);
+    fn test_cube_rescale_coarse() {
+        for point in CubeSphereGrid::<(), 30>::default().points() {
+            let coarse: CubeSpherePoint<10> = point.rescale();
 
-        assert_eq!(start, start.right().right().right().right().right().right().right().right().right().right());
+            assert_eq!(coarse, CubeSpherePoint::new(point.face, point.x / 3, point.y / 3));
+        }
+
+        for point in CubeSphereGrid::<(), 7>::default().points() {
+            let rescaled: CubeSpherePoint<7> = point.rescale();
+
+            assert_eq!(rescaled, point);
+        }
     }
 
     #[test]
-    fn test_rect_point_up_inverse_middle() {
-        let start: RectangleSpherePoint<10, 5> = RectangleSpherePoint::new(5, 3);
+    fn test_cube_to_cube_map_layers() {
+        let grid: CubeSphereGrid<u32, 8> = CubeSphereGrid::from_index_fn(|i| i as u32);
 
-        assert_eq!(start, start.up().down());
+        let layers = grid.to_cube_map_layers();
+
+        assert_eq!(layers.len(), 6 * 8 * 8);
+        assert_eq!(layers.iter().copied().sorted().collect_vec(), (0..384).collect_vec());
+
+        // The direction of the centre of each texel using the cube map conventions.
+        for (i, value) in layers.into_iter().enumerate() {
+            let (layer, row, column) = (i / 64, i / 8 % 8, i % 8);
+
+            let u = (column as f64 + 0.5) / 4.0 - 1.0;
+            let v = (row as f64 + 0.5) / 4.0 - 1.0;
+
+            let direction = match layer {
+                0 => (1.0, -v, -u),
+                1 => (-1.0, -v, u),
+                2 => (u, 1.0, v),
+                3 => (u, -1.0, -v),
+                4 => (u, -v, 1.0),
+                _ => (-u, -v, -1.0),
+            };
+
+            let point = grid.points().nth(value as usize).unwrap();
+
+            assert_relative_eq!(dot(normalize(direction), point.cell_center(1.0)), 1.0, epsilon = 1e-9);
+        }
     }
-    
+
     #[test]
-    fn test_rect_point_down_inverse_middle() {
-        let start: RectangleSpherePoint<10, 5> = RectangleSpherePoint::new(5, 3);
+    fn test_cube_to_cube_map_layers_front() {
+        let grid: CubeSphereGrid<u32, 8> = CubeSphereGrid::from_index_fn(|i| i as u32);
 
-        assert_eq!(start, start.down().up());
+        let layers = grid.to_cube_map_layers();
+
+        // The top left of the positive Z layer is the top left of the front face as seen from
+        // outside of the cube.
+        assert_eq!(layers[4 * 64], grid[CubeSpherePoint::new(CubeFace::Front, 0, 7)]);
+        assert_eq!(layers[4 * 64 + 7], grid[CubeSpherePoint::new(CubeFace::Front, 7, 7)]);
     }
-    
+
     #[test]
-    fn test_rect_point_left_inverse_middle() {
-        let start: RectangleSpherePoint<10, 5> = RectangleSpherePoint::new(5, 3);
+    fn test_cube_fill_region() {
+        let mut grid: CubeSphereGrid<bool, 10> = CubeSphereGrid::default();
 
-        assert_eq!(start, start.left().right());
+        grid.fill_region((-0.2, 0.2), (6.0, 0.3), true);
+
+        for (point, value) in grid.iter() {
+            assert_eq!(*value, super::in_region(&point, (-0.2, 0.2), (6.0, 0.3)));
+        }
+
+        assert!(grid.iter().any(|(_, value)| *value));
     }
-    
+
     #[test]
-    fn test_rect_point_right_inverse_middle() {
-        let start: RectangleSpherePoint<10, 5> = RectangleSpherePoint::new(5, 3);
+    fn test_cube_points_in_region() {
+        let grid: CubeSphereGrid<(), 32> = CubeSphereGrid::default();
 
-        assert_eq!(start, start.right().left());
+        let points: Vec<_> = grid.points_in_region((-0.2, 0.2), (6.0, 0.3)).collect();
+
+        assert!(!points.is_empty());
+        assert!(points.iter().all(|point| point.face == CubeFace::Front));
+        assert!(points.iter().all(|point| {
+            let (longitude, latitude) = point.center_sphere_coordinates();
+            let longitude = longitude.rem_euclid(PI * 2.0);
+
+            (-0.2..=0.2).contains(&latitude) && (longitude >= 6.0 || longitude <= 0.3)
+        }));
     }
-    
+
     #[test]
-    fn test_rect_point_up_inverse_edge() {
-        let start: RectangleSpherePoint<10, 5> = RectangleSpherePoint::new(0, 0);
+    fn test_cube_map_reduce() {
+        let grid: CubeSphereGrid<u64, 10> = CubeSphereGrid::from_fn(|point| (point.x + point.y) as u64);
 
-        assert_eq!(start, start.up().down());
+        let squares: CubeSphereGrid<u64, 10> = CubeSphereGrid::from_fn(|point| grid[*point] * grid[*point]);
+        let expected: u64 = squares.iter().map(|(_, value)| value).sum();
+
+        assert_eq!(expected, grid.map_reduce(|value| value * value, 0, |a, b| a + b));
     }
-    
+
     #[test]
-    fn test_rect_point_down_inverse_edge() {
-        let start: RectangleSpherePoint<10, 5> = RectangleSpherePoint::new(0, 4);
+    fn test_cube_par_map_reduce() {
+        let grid: CubeSphereGrid<u64, 10> = CubeSphereGrid::from_fn(|point| (point.x + point.y) as u64);
 
-        assert_eq!(start, start.down().up());
+        let squares: CubeSphereGrid<u64, 10> = CubeSphereGrid::from_fn(|point| grid[*point] * grid[*point]);
+        let expected: u64 = squares.iter().map(|(_, value)| value).sum();
+
+        assert_eq!(expected, grid.par_map_reduce(|value| value * value, 0, |a, b| a + b));
     }
-    
+
     #[test]
-    fn test_rect_point_left_inverse_edge() {
-        let start: RectangleSpherePoint<10, 5> = RectangleSpherePoint::new(0, 0);
+    fn test_cube_point_try_new() {
+        let point: Result<CubeSpherePoint<10>, _> = CubeSpherePoint::try_new(CubeFace::Left, 3, 9);
 
-        assert_eq!(start, start.left().right());
+        assert_eq!(Ok(CubeSpherePoint::new(CubeFace::Left, 3, 9)), point);
     }
     
     #[test]
-    fn test_rect_point_right_inverse_edge() {
-        let start: RectangleSpherePoint<10, 5> = RectangleSpherePoint::new(9, 0);
+    fn test_cube_point_try_new_x_out_of_bounds() {
+        let point: Result<CubeSpherePoint<10>, _> = CubeSpherePoint::try_new(CubeFace::Left, 10, 9);
 
-        assert_eq!(start, start.right().left());
+        assert_eq!(Err(OutOfBounds), point);
     }
-
+    
     #[test]
-    fn test_rect_from_fn() {
-        let grid: RectangleSphereGrid<u32, 200, 100> = RectangleSphereGrid::from_fn(|point| point.x + point.y);
+    fn test_cube_point_try_new_y_out_of_bounds() {
+        let point: Result<CubeSpherePoint<10>, _> = CubeSpherePoint::try_new(CubeFace::Top, 0, 655);
 
-        assert_eq!(15, grid[RectangleSpherePoint::new(5, 10)]);
+        assert_eq!(Err(OutOfBounds), point);
     }
 
     #[test]
-    fn test_rect_from_neighbours() {
-        let grid: RectangleSphereGrid<u32, 20, 10> = RectangleSphereGrid::from_fn(|point| point.x);
-
-        let grid2 = grid.map_neighbours(|current, up, down, left, right| current + up + down + left + right);
+    fn test_rect_point_try_new() {
+        let point: Result<RectangleSpherePoint<10, 5>, _> = RectangleSpherePoint::try_new(9, 4);
 
-        assert_eq!(25, grid2[RectangleSpherePoint::new(5, 3)])
+        assert_eq!(Ok(RectangleSpherePoint::new(9, 4)), point);
+        assert_eq!(Err(OutOfBounds), RectangleSpherePoint::<10, 5>::try_new(10, 4));
+        assert_eq!(Err(OutOfBounds), RectangleSpherePoint::<10, 5>::try_new(0, 5));
     }
-    
+
     #[test]
-    fn test_rect_from_neighbours_diagonals() {
-        let grid: RectangleSphereGrid<u32, 20, 10> = RectangleSphereGrid::from_fn(|point| point.x);
+    fn test_grid_get() {
+        let mut grid: CubeSphereGrid<u32, 4> = CubeSphereGrid::from_index_fn(|i| i as u32);
+        let point = CubeSpherePoint::try_new(CubeFace::Back, 1, 2).unwrap();
 
-        let grid2 = grid.map_neighbours_diagonals(|up_left, up, up_right, left, current, right, down_left, down, down_right| up_left + up + up_right + left + current + right + down_left + down + down_right);
+        assert_eq!(Some(&grid[point]), grid.get(&point));
+
+        *grid.get_mut(&point).unwrap() = 100;
 
-        assert_eq!(4 * 3 + 5 * 3 + 6 * 3, grid2[RectangleSpherePoint::new(5, 3)])
+        assert_eq!(100, grid[point]);
     }
 
     #[test]
-    fn test_rect_point_latitude_0() {
-        let point: RectangleSpherePoint<1000000000, 500000000> = RectangleSpherePoint::from_geographic(0.0, 0.0);
+    fn test_cube_index_by_reference() {
+        let mut grid: CubeSphereGrid<u32, 4> = CubeSphereGrid::from_index_fn(|i| i as u32);
 
-        assert_relative_eq!(0.0, point.latitude());
-    }
-    
-    #[test]
-    fn test_rect_point_latitude_1() {
-        let point: RectangleSpherePoint<1000000000, 500000000> = RectangleSpherePoint::from_geographic(1.0, 0.0);
+        for point in grid.points().collect::<Vec<_>>() {
+            assert_eq!(grid[point], grid[&point]);
 
-        assert_relative_eq!(1.0, point.latitude(), epsilon = 0.001);
+            grid[&point] += 1;
+        }
+
+        assert_eq!((1..=grid.points().count() as u32).collect::<Vec<_>>(), grid.to_vec());
     }
-    
+
     #[test]
-    fn test_rect_point_latitude_minus_1() {
-        let point: RectangleSpherePoint<1000000000, 500000000> = RectangleSpherePoint::from_geographic(-1.0, 0.0);
+    fn test_grid_zip() {
+        let a: CubeSphereGrid<u32, 4> = CubeSphereGrid::from_index_fn(|i| i as u32);
+        let b: CubeSphereGrid<u32, 4> = CubeSphereGrid::from_index_fn(|i| 2 * i as u32);
 
-        assert_relative_eq!(-1.0, point.latitude(), epsilon = 0.001);
+        for (point, x, y) in a.zip(&b) {
+            assert_eq!(a[point], *x);
+            assert_eq!(b[point], *y);
+        }
+
+        let sum: CubeSphereGrid<u64, 4> = a.zip_map(&b, |_, x, y| (x + y) as u64);
+
+        assert_eq!((0..a.points().count() as u64).map(|i| 3 * i).collect::<Vec<_>>(), sum.to_vec());
     }
-    
+
     #[test]
-    fn test_rect_point_latitude_0_far() {
-        let point: RectangleSpherePoint<1000000000, 500000000> = RectangleSpherePoint::from_geographic(0.0, 4.0);
+    fn test_grid_fold() {
+        let grid: CubeSphereGrid<u64, 4> = CubeSphereGrid::from_index_fn(|i| i as u64);
+        let expected = (0..grid.points().count() as u64).sum::<u64>();
 
-        assert_relative_eq!(0.0, point.latitude());
+        assert_eq!(expected, grid.sum());
+        assert_eq!(expected, grid.par_sum());
+
+        let front = grid.fold(0, |count, point, _| count + (point.face == CubeFace::Front) as usize);
+        let par_front = grid.par_fold(|| 0, |count, point, _| count + (point.face == CubeFace::Front) as usize, |a, b| a + b);
+
+        assert_eq!(16, front);
+        assert_eq!(16, par_front);
     }
-    
+
     #[test]
-    fn test_rect_point_latitude_1_far() {
-        let point: RectangleSpherePoint<1000000000, 500000000> = RectangleSpherePoint::from_geographic(1.0, 2.0);
+    fn test_grid_topology() {
+        let cube: CubeSphereGrid<(), 4> = CubeSphereGrid::default();
+        let rect: RectangleSphereGrid<(), 10, 5> = RectangleSphereGrid::default();
 
-        assert_relative_eq!(1.0, point.latitude(), epsilon = 0.001);
+        assert_eq!(Topology::uniform(Surface::Sphere, 6, 4, 4), cube.topology());
+        assert_eq!(Topology::uniform(Surface::Sphere, 1, 10, 5), rect.topology());
+        assert_eq!(cube.points().count(), cube.len());
+        assert_eq!(rect.points().count(), rect.len());
+        assert!(!cube.is_empty());
     }
-    
+
     #[test]
-    fn test_rect_point_latitude_minus_1_far() {
-        let point: RectangleSpherePoint<1000000000, 500000000> = RectangleSpherePoint::from_geographic(-1.0, 3.0);
+    fn test_grid_from_iter() {
+        let grid: CubeSphereGrid<u32, 4> = CubeSphereGrid::from_index_fn(|i| i as u32);
+        let collected: CubeSphereGrid<u32, 4> = grid.clone().into_iter().map(|(_, value)| value).collect();
 
-        assert_relative_eq!(-1.0, point.latitude(), epsilon = 0.001);
+        assert_eq!(grid, collected);
     }
-    
-    #[test]
-    fn test_rect_point_longitude_0() {
-        let point: RectangleSpherePoint<1000000000, 500000000> = RectangleSpherePoint::from_geographic(0.0, 0.0);
 
-        assert_relative_eq!(0.0, point.longitude(), epsilon = 0.001);
+    #[test]
+    #[should_panic]
+    fn test_grid_from_iter_wrong_length() {
+        let _: CubeSphereGrid<u32, 4> = (0..10).collect();
     }
-    
+
     #[test]
-    fn test_rect_point_longitude_1() {
-        let point: RectangleSpherePoint<1000000000, 500000000> = RectangleSpherePoint::from_geographic(0.0, 1.0);
+    fn test_grid_try_from_fn() {
+        let grid: Result<CubeSphereGrid<u32, 4>, ()> = CubeSphereGrid::try_from_fn(|point| Ok(point.x as u32));
 
-        assert_relative_eq!(1.0, point.longitude(), epsilon = 0.001);
+        assert_eq!(CubeSphereGrid::from_fn(|point| point.x as u32), grid.unwrap());
+
+        let par_grid: Result<CubeSphereGrid<u32, 4>, ()> = CubeSphereGrid::try_from_fn_par(|point| Ok(point.x as u32));
+
+        assert_eq!(CubeSphereGrid::from_fn(|point| point.x as u32), par_grid.unwrap());
     }
-    
+
     #[test]
-    fn test_rect_point_longitude_2() {
-        let point: RectangleSpherePoint<1000000000, 500000000> = RectangleSpherePoint::from_geographic(0.0, 2.0);
+    fn test_grid_try_from_fn_error() {
+        let mut calls = 0;
 
-        assert_relative_eq!(2.0, point.longitude(), epsilon = 0.001);
+        let grid: Result<CubeSphereGrid<u32, 4>, CubeSpherePoint<4>> = CubeSphereGrid::try_from_fn(|point| {
+            calls += 1;
+
+            if point.face == CubeFace::Left { Err(*point) } else { Ok(0) }
+        });
+
+        assert_eq!(Err(CubeSpherePoint::new(CubeFace::Left, 0, 0)), grid);
+        assert_eq!(17, calls);
+
+        let par_grid: Result<CubeSphereGrid<u32, 4>, ()> = CubeSphereGrid::try_from_fn_par(|point| {
+            if point.face == CubeFace::Left { Err(()) } else { Ok(0) }
+        });
+
+        assert_eq!(Err(()), par_grid);
     }
-    
+
     #[test]
-    fn test_rect_point_longitude_4() {
-        let point: RectangleSpherePoint<1000000000, 500000000> = RectangleSpherePoint::from_geographic(0.0, 4.0);
+    fn test_grid_as_slice() {
+        let mut rect: RectangleSphereGrid<u32, 10, 5> = RectangleSphereGrid::from_index_fn(|i| i as u32);
 
-        assert_relative_eq!(4.0, point.longitude(), epsilon = 0.001);
+        assert_eq!(rect.to_vec(), rect.as_slice());
+
+        rect.as_mut_slice()[12] = 100;
+
+        assert_eq!(100, rect[RectangleSpherePoint::new(2, 1)]);
     }
-    
+
     #[test]
-    fn test_rect_point_longitude_6() {
-        let point: RectangleSpherePoint<1000000000, 500000000> = RectangleSpherePoint::from_geographic(0.0, 6.0);
+    fn test_cube_face_slice() {
+        let mut grid: CubeSphereGrid<u32, 4> = CubeSphereGrid::from_index_fn(|i| i as u32);
 
-        assert_relative_eq!(6.0, point.longitude(), epsilon = 0.001);
+        let faces: Vec<u32> = [CubeFace::Top, CubeFace::Left, CubeFace::Front, CubeFace::Right, CubeFace::Back, CubeFace::Bottom]
+            .into_iter()
+            .flat_map(|face| grid.face_slice(face).to_vec())
+            .collect();
+
+        assert_eq!(grid.to_vec(), faces);
+
+        grid.face_slice_mut(CubeFace::Back)[7] = 1000;
+
+        assert_eq!(1000, grid[CubeSpherePoint::new(CubeFace::Back, 3, 1)]);
     }
-    
+
+    #[cfg(feature = "bytemuck")]
     #[test]
-    fn test_rect_point_longitude_0_north() {
-        let point: RectangleSpherePoint<1000000000, 500000000> = RectangleSpherePoint::from_geographic(-1.5, 0.0);
+    fn test_grid_as_bytes() {
+        let mut rect: RectangleSphereGrid<u32, 10, 5> = RectangleSphereGrid::from_index_fn(|i| i as u32);
+        let cube: CubeSphereGrid<f32, 4> = CubeSphereGrid::from_index_fn(|i| i as f32);
 
-        assert_relative_eq!(0.0, point.longitude(), epsilon = 0.001);
+        assert_eq!(200, rect.as_bytes().len());
+        assert_eq!(&1u32.to_ne_bytes(), &rect.as_bytes()[4..8]);
+
+        rect.as_bytes_mut()[4..8].copy_from_slice(&7u32.to_ne_bytes());
+
+        assert_eq!(7, rect[RectangleSpherePoint::new(1, 0)]);
+        assert_eq!(&16f32.to_ne_bytes(), &cube.face_bytes(CubeFace::Left)[0..4]);
     }
-    
+
     #[test]
-    fn test_rect_point_longitude_1_north() {
-        let point: RectangleSpherePoint<1000000000, 500000000> = RectangleSpherePoint::from_geographic(-1.5, 1.0);
+    fn test_grid_iter_exact_size() {
+        let grid: CubeSphereGrid<u32, 4> = CubeSphereGrid::from_index_fn(|i| i as u32);
 
-        assert_relative_eq!(1.0, point.longitude(), epsilon = 0.001);
+        assert_eq!(96, grid.iter().len());
+        assert_eq!(96, grid.points().len());
+        assert_eq!(96, grid.clone().into_iter().len());
+
+        let reversed: Vec<_> = grid.points().rev().collect();
+        let mut points: Vec<_> = grid.points().collect();
+        points.reverse();
+
+        assert_eq!(points, reversed);
+        assert_eq!(Some((CubeSpherePoint::new(CubeFace::Bottom, 3, 3), &95)), grid.iter().next_back());
+
+        let grid: RectangleSphereGrid<u32, 5, 3> = RectangleSphereGrid::from_fn(|point| point.x + point.y * 5);
+
+        assert_eq!(15, grid.iter().len());
+        assert_eq!(Some((RectangleSpherePoint::new(4, 2), &14)), grid.iter().next_back());
+        assert!(grid.iter().rev().map(|(_, value)| *value).eq((0..15).rev()));
     }
-    
+
     #[test]
-    fn test_rect_point_longitude_2_north() {
-        let point: RectangleSpherePoint<1000000000, 500000000> = RectangleSpherePoint::from_geographic(-1.5, 2.0);
+    fn test_grid_par_iter_indexed() {
+        use rayon::iter::{IndexedParallelIterator, ParallelIterator};
 
-        assert_relative_eq!(2.0, point.longitude(), epsilon = 0.001);
+        let mut grid: CubeSphereGrid<u32, 4> = CubeSphereGrid::from_index_fn(|i| i as u32);
+
+        assert_eq!(96, grid.par_iter().len());
+        assert_eq!(96, grid.par_points().len());
+        assert_eq!(grid.points().collect::<Vec<_>>(), grid.par_points().collect::<Vec<_>>());
+        assert_eq!(grid.iter().collect::<Vec<_>>(), grid.par_iter().collect::<Vec<_>>());
+
+        let points: Vec<_> = grid.par_iter_mut().map(|(point, value)| (point, *value)).collect();
+
+        assert_eq!(grid.iter().map(|(point, value)| (point, *value)).collect::<Vec<_>>(), points);
+
+        let grid: RectangleSphereGrid<u32, 5, 3> = RectangleSphereGrid::from_fn(|point| point.x + point.y * 5);
+
+        assert_eq!(Some((RectangleSpherePoint::new(2, 1), &7)), grid.par_iter().skip(7).find_first(|_| true));
+        assert_eq!(grid.iter().collect::<Vec<_>>(), grid.par_iter().collect::<Vec<_>>());
     }
-    
+
     #[test]
-    fn test_rect_point_longitude_4_north() {
-        let point: RectangleSpherePoint<1000000000, 500000000> = RectangleSpherePoint::from_geographic(-1.5, 4.0);
+    fn test_grid_apply_updates() {
+        let updates = vec![
+            (CubeSpherePoint::new(CubeFace::Top, 1, 2), 5),
+            (CubeSpherePoint::new(CubeFace::Bottom, 3, 0), 6),
+            (CubeSpherePoint::new(CubeFace::Top, 1, 2), 7),
+        ];
 
-        assert_relative_eq!(4.0, point.longitude(), epsilon = 0.001);
+        let mut grid: CubeSphereGrid<u32, 4> = CubeSphereGrid::default();
+        grid.apply_updates(updates.clone());
+
+        let mut par_grid: CubeSphereGrid<u32, 4> = CubeSphereGrid::default();
+        par_grid.apply_updates_par(updates);
+
+        assert_eq!(7, grid[CubeSpherePoint::new(CubeFace::Top, 1, 2)]);
+        assert_eq!(6, grid[CubeSpherePoint::new(CubeFace::Bottom, 3, 0)]);
+        assert_eq!(13, grid.sum());
+        assert_eq!(grid, par_grid);
     }
-    
+
     #[test]
-    fn test_rect_point_longitude_6_north() {
-        let point: RectangleSpherePoint<1000000000, 500000000> = RectangleSpherePoint::from_geographic(-1.5, 6.0);
+    fn test_grid_apply_updates_contiguous() {
+        let updates: Vec<_> = (0..200)
+            .map(|i| (RectangleSpherePoint::new((i * 7) % 16, (i * 3) % 8), i))
+            .collect();
 
-        assert_relative_eq!(6.0, point.longitude(), epsilon = 0.001);
+        let mut grid: RectangleSphereGrid<u32, 16, 8> = RectangleSphereGrid::default();
+        grid.apply_updates(updates.clone());
+
+        let mut par_grid: RectangleSphereGrid<u32, 16, 8> = RectangleSphereGrid::default();
+        par_grid.apply_updates_par(updates);
+
+        assert_eq!(grid, par_grid);
+
+        let point = RectangleSpherePoint::new(5, 3);
+        let index = par_grid.to_index(&point);
+
+        par_grid.contiguous_values_mut().unwrap()[index] = 1000;
+
+        assert_eq!(1000, par_grid[point]);
+        assert!(CubeSphereGrid::<u32, 4>::default().contiguous_values_mut().is_none());
     }
-    
+
     #[test]
-    fn test_rect_point_longitude_0_south() {
-        let point: RectangleSpherePoint<1000000000, 500000000> = RectangleSpherePoint::from_geographic(1.5, 0.0);
+    fn test_grid_map_neighbours_radius() {
+        let grid: RectangleSphereGrid<u32, 16, 8> = RectangleSphereGrid::from_index_fn(|i| i as u32);
 
-        assert_relative_eq!(0.0, point.longitude(), epsilon = 0.001);
+        let radius = grid.map_neighbours_radius::<1, _>(Neighbourhood::Moore, |neighbours| neighbours.iter().copied().sum());
+        let diagonals = grid.map_neighbours_diagonals(|a, b, c, d, e, f, g, h, i| a + b + c + d + e + f + g + h + i);
+
+        assert_eq!(diagonals, radius);
+
+        let par_radius = grid.map_neighbours_radius_par::<1, _>(Neighbourhood::Moore, |neighbours| neighbours.iter().copied().sum());
+
+        assert_eq!(diagonals, par_radius);
+
+        let grid: TorusGrid<u32, 10, 10> = TorusGrid::from_index_fn(|i| i as u32);
+        let mut counts: TorusGrid<usize, 10, 10> = TorusGrid::default();
+
+        counts.set_from_neighbours_radius::<2, _, _, _>(&grid, Neighbourhood::VonNeumann, |neighbours| neighbours.len());
+
+        assert!(counts.iter().all(|(_, count)| *count == 13));
+
+        let mut centres: TorusGrid<u32, 10, 10> = TorusGrid::default();
+
+        centres.set_from_neighbours_radius_par::<3, _, _, _>(&grid, Neighbourhood::Moore, |neighbours| *neighbours[neighbours.len() / 2]);
+
+        assert_eq!(grid, centres);
+
+        let mut neighbours = Vec::new();
+        gather_neighbours_radius(&grid, &grid.points().nth(55).unwrap(), 2, Neighbourhood::VonNeumann, &mut neighbours);
+
+        assert_eq!(vec![&35, &44, &45, &46, &53, &54, &55, &56, &57, &64, &65, &66, &75], neighbours);
     }
-    
+
     #[test]
-    fn test_rect_point_longitude_1_south() {
-        let point: RectangleSpherePoint<1000000000, 500000000> = RectangleSpherePoint::from_geographic(1.5, 1.0);
+    fn test_grid_neighbours_radius_corner() {
+        let grid: CubeSphereGrid<u32, 8> = CubeSphereGrid::from_index_fn(|i| i as u32);
+        let point = CubeSpherePoint::new(CubeFace::Left, 0, 0);
 
-        assert_relative_eq!(1.0, point.longitude(), epsilon = 0.001);
+        // Every radius keeps to straight lines, so the corners of the neighbourhood are the cells
+        // at the same offsets even at the corners of the cube.
+        let mut neighbours = Vec::new();
+        gather_neighbours_radius(&grid, &point, 1, Neighbourhood::Moore, &mut neighbours);
+
+        assert_eq!(&grid[point.offset(-1, -1)], neighbours[0]);
+        assert_eq!(&grid[point], neighbours[4]);
+        assert_eq!(&grid[point.offset(1, 1)], neighbours[8]);
+
+        gather_neighbours_radius(&grid, &point, 2, Neighbourhood::Moore, &mut neighbours);
+
+        assert_eq!(&grid[point.offset(-2, -2)], neighbours[0]);
+        assert_eq!(&grid[point.offset(-1, -1)], neighbours[6]);
+        assert_eq!(&grid[point], neighbours[12]);
+        assert_eq!(&grid[point.offset(2, 2)], neighbours[24]);
     }
-    
+
     #[test]
-    fn test_rect_point_longitude_2_south() {
-        let point: RectangleSpherePoint<1000000000, 500000000> = RectangleSpherePoint::from_geographic(1.5, 2.0);
+    fn test_cube_map_neighbours_radius() {
+        let grid: CubeSphereGrid<u32, 8> = CubeSphereGrid::from_index_fn(|i| i as u32);
 
-        assert_relative_eq!(2.0, point.longitude(), epsilon = 0.001);
+        let mut neighbours = Vec::new();
+
+        // The middle of each row and column of the neighbourhood are the direct neighbours, even
+        // across the edges of the faces.
+        for point in grid.points() {
+            gather_neighbours_radius(&grid, &point, 1, Neighbourhood::Moore, &mut neighbours);
+
+            assert_eq!(9, neighbours.len());
+            assert_eq!(&grid[point], neighbours[4]);
+            assert_eq!([neighbours[1], neighbours[7], neighbours[3], neighbours[5]], gather_neighbours(&grid, &point));
+        }
+
+        let radius = grid.map_neighbours_radius::<1, _>(Neighbourhood::Moore, |neighbours| neighbours.iter().copied().sum());
+        let diagonals = grid.map_neighbours_diagonals(|a, b, c, d, e, f, g, h, i| a + b + c + d + e + f + g + h + i);
+
+        assert_eq!(radius, grid.map_neighbours_radius_par::<1, _>(Neighbourhood::Moore, |neighbours| neighbours.iter().copied().sum()));
+
+        // Away from the edges of the faces the neighbourhood is the same as the diagonal neighbours.
+        for point in grid.points().filter(away_from_edges) {
+            assert_eq!(diagonals[point], radius[point]);
+        }
     }
-    
+
     #[test]
-    fn test_rect_point_longitude_4_south() {
-        let point: RectangleSpherePoint<1000000000, 500000000> = RectangleSpherePoint::from_geographic(1.5, 4.0);
+    fn test_grid_map_stencil() {
+        let knight = [(1, -2), (2, -1), (2, 1), (1, 2), (-1, 2), (-2, 1), (-2, -1), (-1, -2)];
 
-        assert_relative_eq!(4.0, point.longitude(), epsilon = 0.001);
+        let grid: TorusGrid<u32, 10, 10> = TorusGrid::from_index_fn(|i| i as u32);
+        let moves = grid.map_stencil(&knight, |values| values.iter().copied().sum());
+        let par_moves = grid.map_stencil_par(&knight, |values| values.iter().copied().sum());
+
+        assert_eq!(moves, par_moves);
+        assert_eq!(8 * 55, moves[grid.points().nth(55).unwrap()]);
+
+        let grid: CubeSphereGrid<u32, 4> = CubeSphereGrid::from_index_fn(|i| i as u32);
+        let copy = grid.map_stencil(&[(0, 0)], |values| *values[0]);
+
+        assert_eq!(grid, copy);
     }
-    
-    #[test]
-    fn test_rect_point_longitude_6_south() {
-        let point: RectangleSpherePoint<1000000000, 500000000> = RectangleSpherePoint::from_geographic(1.5, 6.0);
 
-        assert_relative_eq!(6.0, point.longitude(), epsilon = 0.001);
+    #[test]
+    fn test_grid_convolve() {
+        let grid: TorusGrid<u32, 10, 10> = TorusGrid::from_index_fn(|i| (i * 7 % 13) as u32);
+        let boxed = grid.convolve(&[[1; 3]; 3]);
+
+        assert_eq!(boxed, grid.convolve_par(&[[1; 3]; 3]));
+        assert_eq!(boxed, grid.map_neighbours_diagonals(|s1, s2, s3, s4, s5, s6, s7, s8, s9| {
+            s1 + s2 + s3 + s4 + s5 + s6 + s7 + s8 + s9
+        }));
+
+        // An edge detection kernel gives zero everywhere on a constant grid, including over the
+        // seams between faces.
+        let sobel = [
+            [-1, 0, 1],
+            [-2, 0, 2],
+            [-1, 0, 1],
+        ];
+        let grid: CubeSphereGrid<i32, 4> = CubeSphereGrid::from_fn(|_| 3);
+
+        assert!(grid.convolve(&sobel).iter().all(|(_, value)| *value == 0));
+        assert!(grid.convolve_par(&sobel).iter().all(|(_, value)| *value == 0));
+
+        // The weights can be a different type to the cells.
+        let gaussian = [
+            [1, 2, 1],
+            [2, 4, 2],
+            [1, 2, 1],
+        ];
+        let grid: CubeSphereGrid<f64, 4> = CubeSphereGrid::from_fn(|_| 2.0);
+        let combine = |sum: f64, weight: &u8, value: &f64| sum + *weight as f64 / 16.0 * value;
+
+        assert!(grid.convolve_with(&gaussian, combine).iter().all(|(_, value)| (*value - 2.0).abs() < 1e-9));
+        assert!(grid.convolve_with_par(&gaussian, combine).iter().all(|(_, value)| (*value - 2.0).abs() < 1e-9));
     }
 
     #[test]
-    fn test_cube_point_up_middle() {
-        let point: CubeSpherePoint<5> = CubeSpherePoint::new(CubeFace::Front, 3, 4);
+    #[should_panic]
+    fn test_grid_convolve_even_kernel() {
+        let grid: TorusGrid<u32, 10, 10> = TorusGrid::default();
 
-        assert_eq!(CubeSpherePoint::new(CubeFace::Front, 3, 3), point.up());
+        grid.convolve(&[[1; 2]; 2]);
     }
-    
+
     #[test]
-    fn test_cube_point_up_top() {
-        let point: CubeSpherePoint<5> = CubeSpherePoint::new(CubeFace::Front, 0, 0);
+    fn test_cube_moore_neighbours() {
+        let grid: CubeSphereGrid<(), 4> = CubeSphereGrid::default();
 
-        assert_eq!(CubeSpherePoint::new(CubeFace::Top, 0, 4), point.up());
+        let mut corners = 0;
+
+        for point in grid.points() {
+            let neighbours: Vec<_> = point.moore_neighbours().collect();
+
+            assert!(!neighbours.contains(&point));
+            assert_eq!(neighbours.len(), neighbours.iter().collect::<HashSet<_>>().len());
+
+            for neighbour in &neighbours {
+                assert!(neighbour.moore_neighbours().any(|p| p == point));
+            }
+
+            for direct in point.neighbours() {
+                assert!(neighbours.contains(&direct));
+            }
+
+            let (x, y) = (point.x as usize, point.y as usize);
+
+            if (x == 0 || x == 3) && (y == 0 || y == 3) {
+                corners += 1;
+
+                assert_eq!(7, neighbours.len());
+            } else {
+                assert_eq!(8, neighbours.len());
+            }
+        }
+
+        assert_eq!(24, corners);
     }
-    
+
     #[test]
-    fn test_cube_point_up_bottom() {
-        let point: CubeSpherePoint<5> = CubeSpherePoint::new(CubeFace::Front, 0, 4);
+    fn test_cube_moore_neighbours_geometric() {
+        let grid: CubeSphereGrid<(), 8> = CubeSphereGrid::default();
 
-        assert_eq!(CubeSpherePoint::new(CubeFace::Front, 0, 3), point.up());
+        for point in grid.points().filter(away_from_edges) {
+            let corners = [0, 1, 2, 3].map(|corner| point.cell_corner(corner, 1.0));
+
+            // Each neighbour touches this cell so they share at least one corner.
+            for neighbour in point.moore_neighbours() {
+                let touches = [0, 1, 2, 3].into_iter().any(|corner| {
+                    let (x, y, z) = neighbour.cell_corner(corner, 1.0);
+
+                    corners.iter().any(|other| {
+                        (other.0 - x).abs() < 1e-9 && (other.1 - y).abs() < 1e-9 && (other.2 - z).abs() < 1e-9
+                    })
+                });
+
+                assert!(touches, "{:?} {:?}", point, neighbour);
+            }
+        }
     }
-    
+
     #[test]
-    fn test_cube_point_down_middle() {
-        let point: CubeSpherePoint<10> = CubeSpherePoint::new(CubeFace::Front, 3, 4);
+    fn test_grid_map_neighbours_exact() {
+        let grid: CubeSphereGrid<u32, 4> = CubeSphereGrid::from_fn(|_| 1);
+        let counts = grid.map_neighbours_exact(|_, neighbours| neighbours.iter().copied().sum());
 
-        assert_eq!(CubeSpherePoint::new(CubeFace::Front, 3, 5), point.down());
+        assert_eq!(counts, grid.map_neighbours_exact_par(|_, neighbours| neighbours.iter().copied().sum()));
+        assert_eq!(7, counts[CubeSpherePoint::new(CubeFace::Top, 0, 0)]);
+        assert_eq!(8, counts[CubeSpherePoint::new(CubeFace::Top, 1, 0)]);
+        assert_eq!(6 * 16 * 8 - 24, counts.sum());
+
+        // Away from the corners of a cube the neighbours are the diagonal neighbours.
+        let grid: TorusGrid<u32, 10, 10> = TorusGrid::from_index_fn(|i| i as u32);
+        let exact = grid.map_neighbours_exact(|current, neighbours| current + neighbours.iter().copied().sum::<u32>());
+
+        assert_eq!(exact, grid.map_neighbours_diagonals(|s1, s2, s3, s4, s5, s6, s7, s8, s9| {
+            s1 + s2 + s3 + s4 + s5 + s6 + s7 + s8 + s9
+        }));
     }
-    
+
     #[test]
-    fn test_cube_point_down_top() {
-        let point: CubeSpherePoint<10> = CubeSpherePoint::new(CubeFace::Front, 0, 0);
+    fn test_grid_for_each_with_point() {
+        // Seed the cells in a band around the equator.
+        let seed = |point: &RectangleSpherePoint<16, 8>, value: &mut u32| if point.latitude().abs() < 0.5 {
+            *value += 1;
+        };
 
-        assert_eq!(CubeSpherePoint::new(CubeFace::Front, 0, 1), point.down());
+        let mut grid: RectangleSphereGrid<u32, 16, 8> = RectangleSphereGrid::from_fn(|_| 1);
+        let mut par_grid = grid.clone();
+
+        grid.for_each_with_point(seed);
+        par_grid.for_each_with_point_par(seed);
+
+        let expected = RectangleSphereGrid::from_fn(|point| if point.latitude().abs() < 0.5 { 2 } else { 1 });
+
+        assert_eq!(expected, grid);
+        assert_eq!(expected, par_grid);
+
+        grid.for_each(|value| *value *= 3);
+        par_grid.for_each_par(|value| *value *= 3);
+
+        assert_eq!(grid, par_grid);
+        assert_eq!(expected.sum() * 3, grid.sum());
     }
-    
+
     #[test]
-    fn test_cube_point_down_bottom() {
-        let point: CubeSpherePoint<10> = CubeSpherePoint::new(CubeFace::Front, 0, 9);
+    fn test_grid_map_in_place() {
+        let mut grid: CubeSphereGrid<u32, 4> = CubeSphereGrid::from_index_fn(|i| i as u32);
+        let mut par_grid = grid.clone();
 
-        assert_eq!(CubeSpherePoint::new(CubeFace::Bottom, 0, 0), point.down());
+        grid.map_in_place(|value| value * 2 + 1);
+        par_grid.map_in_place_par(|value| value * 2 + 1);
+
+        assert_eq!(CubeSphereGrid::from_index_fn(|i| i as u32 * 2 + 1), grid);
+        assert_eq!(grid, par_grid);
     }
 
     #[test]
-    fn test_cube_point_left_middle() {
-        let point: CubeSpherePoint<10> = CubeSpherePoint::new(CubeFace::Left, 5, 5);
+    fn test_grid_update_where() {
+        // Melt the ice in the cells that are warmer than -2 degrees.
+        let temperature: RectangleSphereGrid<f64, 16, 8> = RectangleSphereGrid::from_fn(|point| 20.0 - point.latitude().abs() * 20.0);
+        let ice: RectangleSphereGrid<f64, 16, 8> = RectangleSphereGrid::from_fn(|_| 1.0);
 
-        assert_eq!(CubeSpherePoint::new(CubeFace::Left, 4, 5), point.left());
+        let warm = |point: &RectangleSpherePoint<16, 8>, _: &f64| temperature[*point] > -2.0;
+
+        let mut melted = ice.clone();
+        let mut par_melted = ice.clone();
+
+        melted.update_where(warm, |ice| ice * 0.5);
+        par_melted.update_where_par(warm, |ice| ice * 0.5);
+
+        assert_eq!(melted, par_melted);
+
+        for (point, value) in melted.iter() {
+            assert_eq!(if temperature[point] > -2.0 { 0.5 } else { 1.0 }, *value);
+        }
+
+        assert!(melted.iter().any(|(_, value)| *value == 1.0));
+        assert!(melted.iter().any(|(_, value)| *value == 0.5));
     }
-    
+
     #[test]
-    fn test_cube_point_left_left() {
-        let point: CubeSpherePoint<10> = CubeSpherePoint::new(CubeFace::Left, 0, 5);
+    fn test_point_offset() {
+        let point = CubeSpherePoint::<4>::new(CubeFace::Front, 1, 2);
 
-        assert_eq!(CubeSpherePoint::new(CubeFace::Back, 0, 5), point.left());
+        assert_eq!(point, point.offset(0, 0));
+        assert_eq!(point.up().up().right(), point.offset(1, -2));
+        assert_eq!(point.down().left().left().left(), point.offset(-3, 1));
+
+        // Moving up from the top face crosses onto a face where left and right are mirrored.
+        let point = CubeSpherePoint::<4>::new(CubeFace::Top, 1, 0);
+
+        assert_eq!(point.right().up(), point.offset(1, -1));
+        assert_eq!(point.left().up(), point.offset(-1, -1));
+
+        for point in RectangleSphereGrid::<(), 8, 4>::default().points() {
+            assert_eq!(point.up_left(), point.offset(-1, -1));
+            assert_eq!(point.down_right(), point.offset(1, 1));
+        }
     }
-   
+
     #[test]
-    fn test_cube_point_left_right() {
-        let point: CubeSpherePoint<10> = CubeSpherePoint::new(CubeFace::Left, 9, 5);
+    fn test_grid_map_neighbours_radius_with_position() {
+        // Cells only keep the total of their neighbourhood in the northern hemisphere.
+        let rule = |point: &RectangleSpherePoint<16, 8>, neighbours: &[&u32]| if point.latitude() > 0.0 {
+            neighbours.iter().copied().sum()
+        } else {
+            0
+        };
 
-        assert_eq!(CubeSpherePoint::new(CubeFace::Left, 8, 5), point.left());
+        let grid: RectangleSphereGrid<u32, 16, 8> = RectangleSphereGrid::from_fn(|_| 1);
+        let result = grid.map_neighbours_radius_with_position::<1, _>(Neighbourhood::Moore, rule);
+
+        assert_eq!(result, grid.map_neighbours_radius_par_with_position::<1, _>(Neighbourhood::Moore, rule));
+        assert!(result.iter().all(|(point, value)| *value == if point.latitude() > 0.0 { 9 } else { 0 }));
+
+        let mut set: RectangleSphereGrid<u32, 16, 8> = RectangleSphereGrid::default();
+        set.set_from_neighbours_radius_with_position::<1, _, _, _>(&grid, Neighbourhood::Moore, rule);
+
+        assert_eq!(result, set);
+
+        set.set_from_neighbours_radius_par_with_position::<1, _, _, _>(&grid, Neighbourhood::VonNeumann, rule);
+
+        assert!(set.iter().all(|(point, value)| *value == if point.latitude() > 0.0 { 5 } else { 0 }));
+
+        let stencil = grid.map_stencil_with_position(&[(0, 0), (0, 1)], rule);
+
+        assert_eq!(stencil, grid.map_stencil_par_with_position(&[(0, 0), (0, 1)], rule));
+        assert!(stencil.iter().all(|(point, value)| *value == if point.latitude() > 0.0 { 2 } else { 0 }));
     }
 
     #[test]
-    fn test_cube_point_right_middle() {
-        let point: CubeSpherePoint<10> = CubeSpherePoint::new(CubeFace::Right, 5, 5);
+    fn test_cube_right_bottom_seam() {
+        for x in 0..4 {
+            let point = CubeSpherePoint::<4>::new(CubeFace::Right, x, 3);
+            let below = point.down();
+
+            // The bottom row of the right face meets the last column of the bottom face, where
+            // moving right leads back.
+            assert_eq!(CubeSpherePoint::new(CubeFace::Bottom, 3, x), below);
+            assert_eq!(point, below.right());
+        }
 
-        assert_eq!(CubeSpherePoint::new(CubeFace::Right, 6, 5), point.right());
-    }
-    
-    #[test]
-    fn test_cube_point_right_left() {
-        let point: CubeSpherePoint<10> = CubeSpherePoint::new(CubeFace::Right, 0, 5);
+        // The three cells at the corner of the front, right and bottom faces all touch.
+        let front = CubeSpherePoint::<4>::new(CubeFace::Front, 3, 3);
 
-        assert_eq!(CubeSpherePoint::new(CubeFace::Right, 1, 5), point.right());
+        assert_eq!(front.down(), front.right().down());
+        assert_eq!(front, front.down().up());
+        assert_eq!(front.right(), front.down().right());
     }
-   
+
     #[test]
-    fn test_cube_point_right_right() {
-        let point: CubeSpherePoint<10> = CubeSpherePoint::new(CubeFace::Right, 9, 5);
+    fn test_cube_point_cube_centre_inverse() {
+        let grid: CubeSphereGrid<u8, 5> = CubeSphereGrid::default();
 
-        assert_eq!(CubeSpherePoint::new(CubeFace::Back, 9, 5), point.right());
+        for point in grid.points() {
+            let (x, y, z) = point.cube_centre();
+
+            assert_eq!(point, CubeSpherePoint::from_cube_centre(x, y, z));
+        }
     }
 
     #[test]
-    fn test_cube_point_from_geographic_equator() {
-        let point: CubeSpherePoint<100> = CubeSpherePoint::from_geographic(0.0, PI);
+    fn test_cube_rotate_faces_full_turn() {
+        let grid: CubeSphereGrid<CubeSpherePoint<6>, 6> = CubeSphereGrid::from_fn(|point| *point);
 
-        assert_eq!(CubeSpherePoint::new(CubeFace::Back, 50, 50), point);
-    }
-    
-    #[test]
-    fn test_cube_point_from_geographic_north_pole() {
-        let point: CubeSpherePoint<100> = CubeSpherePoint::from_geographic(PI / 2.0, PI);
+        for axis in [Axis::X, Axis::Y, Axis::Z] {
+            let rotated = grid.rotate_faces(axis, 1)
+                .rotate_faces(axis, 1)
+                .rotate_faces(axis, 1)
+                .rotate_faces(axis, 1);
 
-        assert_eq!(CubeSpherePoint::new(CubeFace::Top, 50, 50), point);
+            assert_eq!(grid, rotated);
+        }
     }
     
     #[test]
-    fn test_cube_point_from_geographic_south_pole() {
-        let point: CubeSpherePoint<100> = CubeSpherePoint::from_geographic(-PI / 2.0, PI);
+    fn test_cube_rotate_faces_zero() {
+        let grid: CubeSphereGrid<CubeSpherePoint<6>, 6> = CubeSphereGrid::from_fn(|point| *point);
 
-        assert_eq!(CubeSpherePoint::new(CubeFace::Bottom, 50, 50), point);
+        assert_eq!(grid, grid.rotate_faces(Axis::X, 0));
+        assert_eq!(grid, grid.rotate_faces(Axis::X, 4));
     }
     
     #[test]
-    fn test_cube_point_from_geographic_east() {
-        let point: CubeSpherePoint<100> = CubeSpherePoint::from_geographic(0.0, -PI / 2.0);
+    fn test_cube_rotate_faces_inverse() {
+        let grid: CubeSphereGrid<CubeSpherePoint<6>, 6> = CubeSphereGrid::from_fn(|point| *point);
 
-        assert_eq!(CubeSpherePoint::new(CubeFace::Left, 50, 50), point);
+        assert_eq!(grid, grid.rotate_faces(Axis::Z, 1).rotate_faces(Axis::Z, 3));
     }
     
     #[test]
-    fn test_cube_point_from_geographic_west() {
-        let point: CubeSpherePoint<100> = CubeSpherePoint::from_geographic(0.0, PI / 2.0);
+    fn test_cube_rotate_faces_y() {
+        let grid: CubeSphereGrid<CubeSpherePoint<6>, 6> = CubeSphereGrid::from_fn(|point| *point);
 
-        assert_eq!(CubeSpherePoint::new(CubeFace::Right, 50, 50), point);
+        let rotated = grid.rotate_faces(Axis::Y, 1);
+
+        assert_eq!(CubeSpherePoint::new(CubeFace::Front, 1, 4), rotated[CubeSpherePoint::new(CubeFace::Right, 1, 4)]);
+        assert_eq!(CubeSpherePoint::new(CubeFace::Right, 1, 4), rotated[CubeSpherePoint::new(CubeFace::Back, 4, 1)]);
+        assert_eq!(CubeSpherePoint::new(CubeFace::Top, 2, 2), rotated[CubeSpherePoint::new(CubeFace::Top, 2, 3)]);
     }
-    
+
     #[test]
-    fn test_cube_point_from_geographic_less_west() {
-        let point: CubeSpherePoint<100> = CubeSpherePoint::from_geographic(0.0, PI / 2.0 - 0.2);
+    fn test_cube_symmetrize_equator() {
+        let symmetric: CubeSphereGrid<f64, 8> = CubeSphereGrid::from_fn(|point| {
+            let (x, y, z) = point.cell_center(1.0);
 
-        assert_eq!(CubeSpherePoint::new(CubeFace::Right, 39, 50), point);
-    }
+            y * y + x * z
+        });
 
+        for (point, value) in symmetric.symmetrize_equator().iter() {
+            assert_relative_eq!(*value, symmetric[point], epsilon = 1e-12);
+        }
 
-    #[test]
-    fn test_cube_point_up_loop() {
-        let start: CubeSpherePoint<3> = CubeSpherePoint::new(CubeFace::Bottom, 1, 2);
+        let asymmetric: CubeSphereGrid<f64, 8> = CubeSphereGrid::from_fn(|point| point.cell_center(1.0).1 + 2.0);
+        let symmetrized = asymmetric.symmetrize_equator();
 
-        assert_eq!(start, start.up().up().up()
-                   .up().up().up()
-                   .up().up().up()
-                   .up().up().up());
-    }
-    
-    #[test]
-    fn test_cube_point_down_loop() {
-        let start: CubeSpherePoint<3> = CubeSpherePoint::new(CubeFace::Top, 0, 0);
+        for (point, value) in symmetrized.iter() {
+            let (x, y, z) = point.cube_centre();
 
-        assert_eq!(start, start.down().down().down()
-                   .down().down().down()
-                   .down().down().down()
-                   .down().down().down());
+            assert_eq!(*value, symmetrized[CubeSpherePoint::from_cube_centre(x, -y, z)]);
+            assert_relative_eq!(*value, 2.0, epsilon = 1e-12);
+        }
     }
-    
-    #[test]
-    fn test_cube_point_left_loop() {
-        let start: CubeSpherePoint<3> = CubeSpherePoint::new(CubeFace::Back, 1, 0);
 
-        assert_eq!(start, start.left().left().left()
-                   .left().left().left()
-                   .left().left().left()
-                   .left().left().left());
-    }
-    
     #[test]
-    fn test_cube_point_right_loop() {
-        let start: CubeSpherePoint<3> = CubeSpherePoint::new(CubeFace::Front, 0, 2);
+    fn test_cube_symmetrize_longitudinal() {
+        // x² + z² and y are unchanged by any rotation about the poles.
+        let symmetric: CubeSphereGrid<f64, 8> = CubeSphereGrid::from_fn(|point| {
+            let (x, y, z) = point.cell_center(1.0);
 
-        assert_eq!(start, start.right().right().right()
-                   .right().right().right()
-                   .right().right().right()
-                   .right().right().right());
-    }
+            x * x + z * z + y
+        });
 
-    #[test]
-    fn test_cube_point_up_inverse_middle() {
-        let start: CubeSpherePoint<10> = CubeSpherePoint::new(CubeFace::Front, 5, 3);
+        for (point, value) in symmetric.symmetrize_longitudinal(4).iter() {
+            assert_relative_eq!(*value, symmetric[point], epsilon = 1e-12);
+        }
 
-        assert_eq!(start, start.up().down());
-    }
-    
-    #[test]
-    fn test_cube_point_down_inverse_middle() {
-        let start: CubeSpherePoint<10> = CubeSpherePoint::new(CubeFace::Front, 5, 3);
+        let asymmetric: CubeSphereGrid<f64, 8> = CubeSphereGrid::from_index_fn(|i| i as f64);
 
-        assert_eq!(start, start.down().up());
-    }
-    
-    #[test]
-    fn test_cube_point_left_inverse_middle() {
-        let start: CubeSpherePoint<10> = CubeSpherePoint::new(CubeFace::Front, 5, 3);
+        let half = asymmetric.symmetrize_longitudinal(2);
+        let quarter = asymmetric.symmetrize_longitudinal(4);
 
-        assert_eq!(start, start.left().right());
+        for point in asymmetric.points() {
+            let (x, y, z) = point.cube_centre();
+
+            assert_relative_eq!(half[point], half[CubeSpherePoint::from_cube_centre(-x, y, -z)]);
+            assert_relative_eq!(quarter[point], quarter[CubeSpherePoint::from_cube_centre(z, y, -x)]);
+        }
+
+        assert_ne!(half, asymmetric);
     }
-    
+
     #[test]
-    fn test_cube_point_right_inverse_middle() {
-        let start: CubeSpherePoint<10> = CubeSpherePoint::new(CubeFace::Front, 5, 3);
+    #[should_panic]
+    fn test_cube_symmetrize_longitudinal_uneven() {
+        let grid: CubeSphereGrid<f64, 8> = CubeSphereGrid::from_fn(|_| 1.0);
 
-        assert_eq!(start, start.right().left());
+        grid.symmetrize_longitudinal(3);
     }
-    
+
     #[test]
-    fn test_cube_point_up_inverse_edge() {
-        let start: CubeSpherePoint<10> = CubeSpherePoint::new(CubeFace::Front, 0, 0);
+    fn test_cube_from_spherical_harmonics_constant() {
+        let grid: CubeSphereGrid<f64, 8> = CubeSphereGrid::from_spherical_harmonics(&[(0, 0, 1.0)]);
 
-        assert_eq!(start, start.up().down());
+        for (_, value) in grid.iter() {
+            assert_relative_eq!(*value, 0.5 / PI.sqrt(), epsilon = 1e-12);
+        }
     }
-    
+
     #[test]
-    fn test_cube_point_down_inverse_edge() {
-        let start: CubeSpherePoint<10> = CubeSpherePoint::new(CubeFace::Front, 0, 9);
+    fn test_cube_from_spherical_harmonics_dipole() {
+        let grid: CubeSphereGrid<f64, 8> = CubeSphereGrid::from_spherical_harmonics(&[(1, 0, 1.0)]);
 
-        assert_eq!(start, start.down().up());
+        for (point, value) in grid.iter() {
+            assert_relative_eq!(*value, (3.0 / (4.0 * PI)).sqrt() * point.cell_center(1.0).1, epsilon = 1e-12);
+        }
     }
-    
+
     #[test]
-    fn test_cube_point_left_inverse_edge() {
-        let start: CubeSpherePoint<10> = CubeSpherePoint::new(CubeFace::Front, 0, 0);
+    fn test_cube_from_spherical_harmonics_orthonormal() {
+        let terms = [(1, 1), (2, 0), (2, -1), (3, 2), (4, -3)];
 
-        assert_eq!(start, start.left().right());
+        let grids = terms.map(|(l, m)| CubeSphereGrid::<f64, 32>::from_spherical_harmonics(&[(l, m, 1.0)]));
+
+        for (i, a) in grids.iter().enumerate() {
+            for (j, b) in grids.iter().enumerate() {
+                let integral: f64 = a.points()
+                    .map(|point| a[point] * b[point] * point.solid_angle())
+                    .sum();
+
+                assert_relative_eq!(integral, if i == j { 1.0 } else { 0.0 }, epsilon = 0.01);
+            }
+        }
     }
-    
-    #[test]
-    fn test_cube_point_right_inverse_edge() {
-        let start: CubeSpherePoint<10> = CubeSpherePoint::new(CubeFace::Front, 9, 0);
 
-        assert_eq!(start, start.right().left());
+    /// Checks whether a cell of a `CubeSphereGrid` is away from the edges of its face.
+    fn away_from_edges<const S: usize>(point: &CubeSpherePoint<S>) -> bool {
+        (1..S as u16 - 1).contains(&point.x) && (1..S as u16 - 1).contains(&point.y)
     }
 
     #[test]
-    fn test_cube_from_fn() {
-        let grid: CubeSphereGrid<u16, 100> = CubeSphereGrid::from_fn(|point| point.x + point.y);
+    fn test_cube_laplacian_constant() {
+        let grid: CubeSphereGrid<f64, 16> = CubeSphereGrid::from_fn(|_| 3.0);
 
-        assert_eq!(15, grid[CubeSpherePoint::new(CubeFace::Front, 5, 10)]);
+        assert!(grid.laplacian(1.0).iter().all(|(_, value)| value.abs() < 1e-9));
     }
-    
+
     #[test]
-    fn test_cube_from_neighbours() {
-        let grid: CubeSphereGrid<u16, 10> = CubeSphereGrid::from_fn(|point| point.x);
+    fn test_cube_laplacian_harmonic_l1() {
+        // Y(1, 0) is proportional to sin(latitude) and has an eigenvalue of -l(l + 1) = -2.
+        let grid: CubeSphereGrid<f64, 64> = CubeSphereGrid::from_fn(|point| point.cell_center(1.0).1);
 
-        let grid2 = grid.map_neighbours(|current, up, down, left, right| current + up + down + left + right);
+        let laplacian = grid.laplacian(2.0);
 
-        assert_eq!(25, grid2[CubeSpherePoint::new(CubeFace::Front, 5, 3)])
+        for (point, value) in laplacian.iter().filter(|(point, _)| away_from_edges(point)) {
+            assert_relative_eq!(-2.0 / 4.0 * grid[point], value, epsilon = 0.02);
+        }
     }
     
     #[test]
-    fn test_cube_from_neighbours_diagonals() {
-        let grid: CubeSphereGrid<u16, 10> = CubeSphereGrid::from_fn(|point| point.x);
+    fn test_cube_laplacian_harmonic_l2() {
+        // Y(2, 1) is proportional to x y and has an eigenvalue of -6.
+        let grid: CubeSphereGrid<f64, 64> = CubeSphereGrid::from_fn(|point| {
+            let (x, y, _) = point.cell_center(1.0);
 
-        let grid2 = grid.map_neighbours_diagonals(|up_left, up, up_right, left, current, right, down_left, down, down_right| up_left + up + up_right + left + current + right + down_left + down + down_right);
+            x * y
+        });
 
-        assert_eq!(4 * 3 + 5 * 3 + 6 * 3, grid2[CubeSpherePoint::new(CubeFace::Front, 5, 3)])
+        let laplacian = grid.laplacian(1.0);
+
+        for (point, value) in laplacian.iter().filter(|(point, _)| away_from_edges(point)) {
+            assert_relative_eq!(-6.0 * grid[point], value, epsilon = 0.02);
+        }
     }
-    
+
     #[test]
     fn test_cube_point_latitude_0() {
         let point: CubeSpherePoint<32000> = CubeSpherePoint::from_geographic(0.0, 0.0);
@@ -1968,11 +7685,256 @@ mod test {
         assert_eq!(grid, black_box(grid.clone()));
     }
     
+    #[test]
+    fn test_cube_clone_values() {
+        let grid: CubeSphereGrid<u64, 256> = CubeSphereGrid::from_fn(|point| point.x as u64 * 1000 + point.y as u64 + point.face as u64 * 1000000);
+
+        let clone = black_box(grid.clone());
+
+        assert_eq!(grid, clone);
+        assert_eq!(4005006, clone[CubeSpherePoint::new(CubeFace::Top, 5, 6)]);
+    }
+
+    #[test]
+    fn test_cube_clone_from() {
+        let grid: CubeSphereGrid<u64, 16> = CubeSphereGrid::from_index_fn(|i| i as u64);
+        let mut target: CubeSphereGrid<u64, 16> = CubeSphereGrid::default();
+
+        let faces = FACE_ORDER.map(|face| target.face_slice(face).as_ptr());
+
+        target.clone_from(&grid);
+
+        assert_eq!(grid, target);
+        assert_eq!(faces, FACE_ORDER.map(|face| target.face_slice(face).as_ptr()));
+
+        let grid: RectangleSphereGrid<u64, 16, 8> = RectangleSphereGrid::from_index_fn(|i| i as u64);
+        let mut target: RectangleSphereGrid<u64, 16, 8> = RectangleSphereGrid::default();
+
+        let data = target.as_slice().as_ptr();
+
+        target.clone_from(&grid);
+
+        assert_eq!(grid, target);
+        assert_eq!(data, target.as_slice().as_ptr());
+
+        let grid: EqualAreaCubeSphereGrid<u64, 4> = EqualAreaCubeSphereGrid::from_index_fn(|i| i as u64);
+        let mut target = EqualAreaCubeSphereGrid::default();
+
+        target.clone_from(&grid);
+
+        assert_eq!(grid, target);
+    }
+    
     #[test]
     fn test_cube_clone_4096() {
         let grid: CubeSphereGrid<u64, 4096> = CubeSphereGrid::default();
 
         assert_eq!(grid, black_box(grid.clone()));
     }
-}
 
+    #[test]
+    fn test_equal_area_square_round_trip() {
+        for i in 0..=20 {
+            for j in 0..=20 {
+                let (x, y) = (i as f64 / 10.0 - 1.0, j as f64 / 10.0 - 1.0);
+                let (u, v, w) = equal_area_from_square(x, y);
+
+                assert_relative_eq!(u * u + v * v + w * w, 1.0, epsilon = 1e-12);
+
+                let (x2, y2) = equal_area_to_square(u, v, w);
+
+                assert_relative_eq!(x2, x, epsilon = 1e-9);
+                assert_relative_eq!(y2, y, epsilon = 1e-9);
+            }
+        }
+
+        // The corners and edges of the face stay in the same place as in the cube projection.
+        let (u, v, w) = equal_area_from_square(1.0, 1.0);
+
+        assert_relative_eq!(u, 1.0 / 3.0f64.sqrt(), epsilon = 1e-12);
+        assert_relative_eq!(v, 1.0 / 3.0f64.sqrt(), epsilon = 1e-12);
+        assert_relative_eq!(w, 1.0 / 3.0f64.sqrt(), epsilon = 1e-12);
+
+        let (u, v, w) = equal_area_from_square(1.0, 0.3);
+
+        assert_relative_eq!(u, w, epsilon = 1e-12);
+        assert!(v > 0.0);
+    }
+
+    #[test]
+    fn test_equal_area_cube_from_geographic() {
+        let grid: EqualAreaCubeSphereGrid<(), 7> = EqualAreaCubeSphereGrid::default();
+
+        for point in grid.points() {
+            let (x, y, z) = point.cell_center(1.0);
+
+            let latitude = y.atan2((x * x + z * z).sqrt());
+            let longitude = x.atan2(z);
+
+            assert_eq!(EqualAreaCubeSpherePoint::from_geographic(latitude, longitude), point);
+        }
+    }
+
+    #[test]
+    fn test_equal_area_cube_neighbours() {
+        let grid: EqualAreaCubeSphereGrid<(), 6> = EqualAreaCubeSphereGrid::default();
+
+        for point in grid.points() {
+            let cube_point = CubeSpherePoint::from(point);
+
+            assert_eq!(CubeSpherePoint::from(point.up()), cube_point.up());
+            assert_eq!(CubeSpherePoint::from(point.right()), cube_point.right());
+
+            // Cells that share an edge within a face have centres that are close together on the
+            // sphere.
+            if !away_from_edges(&cube_point) {
+                continue;
+            }
+
+            for neighbour in [point.up(), point.down(), point.left(), point.right()] {
+                let (x, y, z) = point.cell_center(1.0);
+                let (nx, ny, nz) = neighbour.cell_center(1.0);
+
+                assert!(((x - nx).powi(2) + (y - ny).powi(2) + (z - nz).powi(2)).sqrt() < 0.4);
+            }
+        }
+    }
+
+    #[test]
+    fn test_equal_area_cube_equal_area() {
+        // Sampling the sphere evenly puts the same number of samples in each cell.
+        let samples = 400;
+        let mut counts = EqualAreaCubeSphereGrid::<usize, 4>::default();
+
+        for i in 0..samples {
+            for j in 0..samples {
+                let z = (i as f64 + 0.5) / samples as f64 * 2.0 - 1.0;
+                let longitude = (j as f64 + 0.5) / samples as f64 * 2.0 * PI;
+
+                counts[EqualAreaCubeSpherePoint::from_geographic(z.asin(), longitude)] += 1;
+            }
+        }
+
+        let expected = (samples * samples) as f64 / 96.0;
+
+        for (point, count) in counts.iter() {
+            assert!((*count as f64 - expected).abs() < expected * 0.05);
+            assert_relative_eq!(point.solid_angle() * 96.0, 4.0 * PI);
+        }
+
+        let grid: CubeSphereGrid<usize, 4> = CubeSphereGrid::from_index_fn(|i| i);
+        let equal_area = EqualAreaCubeSphereGrid::from(grid.clone());
+
+        assert_eq!(equal_area.to_vec(), grid.to_vec());
+        assert_eq!(CubeSphereGrid::from(equal_area), grid);
+    }
+
+    #[test]
+    fn test_equi_angular_plane_round_trip() {
+        for i in 0..=20 {
+            let x = i as f64 / 10.0 - 1.0;
+
+            assert_relative_eq!(equi_angular_from_plane(equi_angular_to_plane(x)), x, epsilon = 1e-12);
+        }
+
+        assert_relative_eq!(equi_angular_to_plane(1.0), 1.0, epsilon = 1e-12);
+        assert_relative_eq!(equi_angular_to_plane(-1.0), -1.0, epsilon = 1e-12);
+        assert_relative_eq!(equi_angular_to_plane(0.0), 0.0);
+    }
+
+    #[test]
+    fn test_equi_angular_cube_from_geographic() {
+        let grid: EquiAngularCubeSphereGrid<(), 7> = EquiAngularCubeSphereGrid::default();
+
+        for point in grid.points() {
+            let (x, y, z) = point.cell_center(1.0);
+
+            let latitude = y.atan2((x * x + z * z).sqrt());
+            let longitude = x.atan2(z);
+
+            assert_eq!(EquiAngularCubeSpherePoint::from_geographic(latitude, longitude), point);
+        }
+    }
+
+    #[test]
+    fn test_equi_angular_cube_neighbours() {
+        let grid: EquiAngularCubeSphereGrid<(), 6> = EquiAngularCubeSphereGrid::default();
+
+        for point in grid.points() {
+            let cube_point = CubeSpherePoint::from(point);
+
+            assert_eq!(CubeSpherePoint::from(point.up()), cube_point.up());
+            assert_eq!(CubeSpherePoint::from(point.right()), cube_point.right());
+
+            // The corners of the faces are in the same place as in the cube projection.
+            if cube_point.x == 0 && cube_point.y == 0 {
+                let (x, y, z) = point.position(1.0);
+                let (cx, cy, cz) = normalize(cube_point.position(1.0));
+
+                assert_relative_eq!(x, cx, epsilon = 1e-12);
+                assert_relative_eq!(y, cy, epsilon = 1e-12);
+                assert_relative_eq!(z, cz, epsilon = 1e-12);
+            }
+
+            // Cells that share an edge within a face have centres that are close together on the
+            // sphere.
+            if !away_from_edges(&cube_point) {
+                continue;
+            }
+
+            for neighbour in [point.up(), point.down(), point.left(), point.right()] {
+                let (x, y, z) = point.cell_center(1.0);
+                let (nx, ny, nz) = neighbour.cell_center(1.0);
+
+                assert!(((x - nx).powi(2) + (y - ny).powi(2) + (z - nz).powi(2)).sqrt() < 0.4);
+            }
+        }
+    }
+
+    #[test]
+    fn test_equi_angular_cube_solid_angle() {
+        let grid: EquiAngularCubeSphereGrid<(), 16> = EquiAngularCubeSphereGrid::default();
+
+        let angles: Vec<_> = grid.points().map(|point| point.solid_angle()).collect();
+
+        assert_relative_eq!(angles.iter().sum::<f64>(), 4.0 * PI, epsilon = 1e-9);
+
+        let ratio = |angles: &[f64]| {
+            let min = angles.iter().copied().fold(f64::INFINITY, f64::min);
+            let max = angles.iter().copied().fold(0.0, f64::max);
+
+            max / min
+        };
+
+        let cube: CubeSphereGrid<(), 16> = CubeSphereGrid::default();
+        let cube_angles: Vec<_> = cube.points().map(|point| point.solid_angle()).collect();
+
+        // The cells vary in size far less than with the cube projection.
+        assert!(ratio(&angles) < 1.5);
+        assert!(ratio(&cube_angles) > 4.0);
+
+        let grid: CubeSphereGrid<usize, 4> = CubeSphereGrid::from_index_fn(|i| i);
+        let equi_angular = EquiAngularCubeSphereGrid::from(grid.clone());
+
+        assert_eq!(equi_angular.to_vec(), grid.to_vec());
+        assert_eq!(CubeSphereGrid::from(equi_angular), grid);
+    }
+
+    #[test]
+    fn test_cube_par_iter_mut() {
+        use rayon::iter::ParallelIterator;
+
+        let mut grid: CubeSphereGrid<f64, 8> = CubeSphereGrid::from_fn(|point| point.position(1.0).0);
+
+        grid.par_iter_mut().for_each(|(point, value)| *value += point.position(1.0).1);
+
+        assert_eq!(CubeSphereGrid::from_fn(|point| point.position(1.0).0 + point.position(1.0).1), grid);
+        assert_eq!(6 * 8 * 8, grid.par_iter_mut().count());
+
+        let mut rect: RectangleSphereGrid<u32, 10, 5> = RectangleSphereGrid::default();
+
+        rect.par_iter_mut().for_each(|(point, value)| *value = point.x + point.y * 10);
+
+        assert_eq!((0..50).collect::<Vec<_>>(), rect.to_vec());
+    }
+}
@@ -0,0 +1,472 @@
+//! A module containing grids that store their cells in a storage backend chosen by the user.
+//!
+//! Only `StoredGrid` is generic over its storage. The other grids in this crate, such as
+//! `CubeSphereGrid` and `RectangleSphereGrid`, still keep their values in a `Vec` or a
+//! `HeapArray2D`. To use another storage with the shape of one of them, use a `StoredGrid` shaped
+//! like it. Arena and GPU staging storage are not provided.
+
+use std::{marker::PhantomData, ops::{Index, IndexMut}, vec};
+
+use rayon::prelude::*;
+
+use crate::{SurfaceGrid, Topology};
+
+assert_send_sync!([T: Send + Sync, G: SurfaceGrid<()> + Send + Sync]
+    StoredGrid<T, G>,
+    StoredGrid<T, G, Box<[T]>>,
+    StoredGrid<T, G, &mut [T]>,
+);
+
+/// Somewhere that the values of the cells of a `StoredGrid` are kept.
+///
+/// The values are kept in a contiguous slice in the order of the points of the grid.
+pub trait GridStorage<T> {
+    /// Gets the values of the cells.
+    fn as_slice(&self) -> &[T];
+
+    /// Gets mutable references to the values of the cells.
+    fn as_mut_slice(&mut self) -> &mut [T];
+}
+
+/// A `GridStorage` that owns its values and can be created from them.
+///
+/// This is needed for a `StoredGrid` to implement `SurfaceGrid`, which creates new grids.
+pub trait OwnedStorage<T>: GridStorage<T> + Sized {
+    /// Creates a new store holding a list of values.
+    ///
+    /// - `values` - The values of the cells.
+    fn from_vec(values: Vec<T>) -> Self;
+
+    /// Takes the values out of the store.
+    fn into_vec(self) -> Vec<T>;
+}
+
+impl <T> GridStorage<T> for Vec<T> {
+    fn as_slice(&self) -> &[T] {
+        self
+    }
+
+    fn as_mut_slice(&mut self) -> &mut [T] {
+        self
+    }
+}
+
+impl <T> OwnedStorage<T> for Vec<T> {
+    fn from_vec(values: Vec<T>) -> Self {
+        values
+    }
+
+    fn into_vec(self) -> Vec<T> {
+        self
+    }
+}
+
+impl <T> GridStorage<T> for Box<[T]> {
+    fn as_slice(&self) -> &[T] {
+        self
+    }
+
+    fn as_mut_slice(&mut self) -> &mut [T] {
+        self
+    }
+}
+
+impl <T> OwnedStorage<T> for Box<[T]> {
+    fn from_vec(values: Vec<T>) -> Self {
+        values.into_boxed_slice()
+    }
+
+    fn into_vec(self) -> Vec<T> {
+        Vec::from(self)
+    }
+}
+
+/// Borrows values kept elsewhere, such as in an arena, a static buffer or a staging buffer that
+/// is shared with a GPU, without copying them.
+impl <T> GridStorage<T> for &mut [T] {
+    fn as_slice(&self) -> &[T] {
+        self
+    }
+
+    fn as_mut_slice(&mut self) -> &mut [T] {
+        self
+    }
+}
+
+/// A grid with the shape of another grid that keeps its cells in a `GridStorage`.
+///
+/// The shape of the grid is given by a grid of `()`, such as `CubeSphereGrid<(), 64>`, which
+/// takes up no memory and provides the points, their order and their positions, so any storage
+/// can be used with any shape of grid. The storage defaults to a `Vec`. With an owned storage,
+/// such as a `Vec` or a `Box<[T]>`, the grid implements `SurfaceGrid` and behaves in the same way
+/// as the grid that it is shaped like. Other storage, such as a borrowed slice, can be wrapped with
+/// `from_storage` and read and written by indexing the grid.
+///
+/// # Storage of the Other Grids
+/// The other grids in this crate own their values in a `Vec` or a `HeapArray2D` and are not
+/// generic over their storage, so that methods such as `RectangleSphereGrid::as_array` and
+/// `CubeSphereGrid::face_slice` can lend out the arrays they use. A `StoredGrid` shaped
+/// like one of them, such as `StoredGrid<T, CubeSphereGrid<(), 64>, Box<[T]>>`, is the same grid
+/// with a different storage and reuses its points, projection and topology rather than
+/// duplicating them.
+///
+/// # Type Parameters
+/// - `T` - The type of data that the grid holds.
+/// - `G` - The grid of `()` that gives the shape of the grid.
+/// - `D` - The storage that the values are kept in.
+///
+/// # Iteration Order
+/// `iter`, `points` and `into_iter` visit the cells in the same order as the grid given by `G`,
+/// which is also the order of the values in the storage.
+///
+/// # Thread Safety
+/// The grid is `Send` when `G` and `D` are `Send` and `Sync` when `G` and `D` are `Sync`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StoredGrid<T, G: SurfaceGrid<()>, D: GridStorage<T> = Vec<T>> {
+    /// The values of the cells in the order of the points of the grid.
+    data: D,
+    /// The grid that gives the shape of this grid.
+    layout: G,
+    values: PhantomData<fn() -> T>,
+}
+
+impl <T, G: SurfaceGrid<()> + Default, D: GridStorage<T>> StoredGrid<T, G, D> {
+    /// Creates a new grid from a storage that already holds the values of the cells.
+    ///
+    /// This returns `None` if the storage does not hold exactly one value for each cell.
+    ///
+    /// - `data` - The storage holding the values in the order of the points of the grid.
+    pub fn from_storage(data: D) -> Option<Self> {
+        let layout = G::default();
+
+        (data.as_slice().len() == layout.points().count()).then_some(Self {
+            data,
+            layout,
+            values: PhantomData,
+        })
+    }
+}
+
+impl <T, G: SurfaceGrid<()>, D: GridStorage<T>> StoredGrid<T, G, D> {
+    /// Gets the storage that the values are kept in.
+    pub fn storage(&self) -> &D {
+        &self.data
+    }
+
+    /// Gets the storage that the values are kept in mutably.
+    ///
+    /// The storage must keep the same number of values.
+    pub fn storage_mut(&mut self) -> &mut D {
+        &mut self.data
+    }
+
+    /// Takes the storage out of the grid.
+    pub fn into_storage(self) -> D {
+        self.data
+    }
+
+    /// Gets the values of the cells in the same order as the points of the grid.
+    pub fn as_slice(&self) -> &[T] {
+        self.data.as_slice()
+    }
+
+    /// Gets mutable references to the values of the cells in the same order as the points of the
+    /// grid.
+    pub fn as_mut_slice(&mut self) -> &mut [T] {
+        self.data.as_mut_slice()
+    }
+
+    /// Gets the values of the cells as bytes in the same order as `as_slice`.
+    #[cfg(feature = "bytemuck")]
+    pub fn as_bytes(&self) -> &[u8] where T: bytemuck::Pod {
+        bytemuck::cast_slice(self.as_slice())
+    }
+
+    /// Gets the values of the cells as mutable bytes in the same order as `as_slice`.
+    #[cfg(feature = "bytemuck")]
+    pub fn as_bytes_mut(&mut self) -> &mut [u8] where T: bytemuck::Pod {
+        bytemuck::cast_slice_mut(self.as_mut_slice())
+    }
+
+    /// Gets the grid that gives the shape of this grid.
+    pub fn layout(&self) -> &G {
+        &self.layout
+    }
+}
+
+impl <T: Default, G: SurfaceGrid<()> + Default, D: OwnedStorage<T>> Default for StoredGrid<T, G, D> {
+    fn default() -> Self {
+        let layout = G::default();
+
+        Self {
+            data: D::from_vec(layout.points().map(|_| T::default()).collect()),
+            layout,
+            values: PhantomData,
+        }
+    }
+}
+
+impl <T, G, D> SurfaceGrid<T> for StoredGrid<T, G, D>
+where
+    G: SurfaceGrid<()> + Default + Sync,
+    G::Point: Sync,
+    D: OwnedStorage<T> + Sync {
+    type Point = G::Point;
+
+    fn from_fn<F: FnMut(&Self::Point) -> T>(mut f: F) -> Self {
+        let layout = G::default();
+
+        Self {
+            data: D::from_vec(layout.points().map(|point| f(&point)).collect()),
+            layout,
+            values: PhantomData,
+        }
+    }
+
+    fn from_fn_par<F: Fn(&Self::Point) -> T + Send + Sync>(f: F) -> Self where T: Send + Sync {
+        let layout = G::default();
+
+        Self {
+            data: D::from_vec(layout.par_map_to_vec(|point, _| f(point))),
+            layout,
+            values: PhantomData,
+        }
+    }
+
+    fn try_from_fn<X, F: FnMut(&Self::Point) -> Result<T, X>>(mut f: F) -> Result<Self, X> {
+        let layout = G::default();
+
+        Ok(Self {
+            data: D::from_vec(layout.points().map(|point| f(&point)).collect::<Result<_, _>>()?),
+            layout,
+            values: PhantomData,
+        })
+    }
+
+    fn try_from_fn_par<X: Send, F: Fn(&Self::Point) -> Result<T, X> + Send + Sync>(f: F) -> Result<Self, X> where T: Send + Sync {
+        let layout = G::default();
+        let points: Vec<_> = layout.points().collect();
+
+        Ok(Self {
+            data: D::from_vec(points.par_iter().map(&f).collect::<Result<_, _>>()?),
+            layout,
+            values: PhantomData,
+        })
+    }
+
+    fn from_vec(values: Vec<T>) -> Option<Self> {
+        Self::from_storage(D::from_vec(values))
+    }
+
+    fn set_from_fn<F: FnMut(&Self::Point) -> T>(&mut self, mut f: F) {
+        for (point, value) in self.layout.points().zip(self.data.as_mut_slice()) {
+            *value = f(&point);
+        }
+    }
+
+    fn set_from_fn_par<F: Fn(&Self::Point) -> T + Send + Sync>(&mut self, f: F) where T: Send + Sync {
+        let values = self.layout.par_map_to_vec(|point, _| f(point));
+
+        for (value, new) in self.data.as_mut_slice().iter_mut().zip(values) {
+            *value = new;
+        }
+    }
+
+    fn iter<'a>(&'a self) -> impl ExactSizeIterator<Item = (Self::Point, &'a T)> + DoubleEndedIterator where T: 'a {
+        self.layout.points()
+            .zip(self.data.as_slice())
+    }
+
+    fn par_iter<'a>(&'a self) -> impl IndexedParallelIterator<Item = (Self::Point, &'a T)> where T: 'a + Send + Sync {
+        self.layout.par_points()
+            .map(|point| (point.clone(), &self[point]))
+    }
+
+    fn par_iter_mut<'a>(&'a mut self) -> impl IndexedParallelIterator<Item = (Self::Point, &'a mut T)> where T: 'a + Send + Sync {
+        let points: Vec<_> = self.layout.points().collect();
+
+        points.into_par_iter()
+            .zip(self.data.as_mut_slice())
+    }
+
+    fn points(&self) -> impl ExactSizeIterator<Item = Self::Point> + DoubleEndedIterator {
+        self.layout.points()
+    }
+
+    fn par_points(&self) -> impl IndexedParallelIterator<Item = Self::Point> {
+        self.layout.par_points()
+    }
+
+    fn par_map_to_vec<U: Send, F: Fn(&Self::Point, &T) -> U + Send + Sync>(&self, f: F) -> Vec<U> where T: Send + Sync {
+        let values = self.data.as_slice();
+
+        self.layout.par_map_to_vec(|point, _| f(point, &values[self.layout.to_index(point)]))
+    }
+
+    fn to_index(&self, point: &Self::Point) -> usize {
+        self.layout.to_index(point)
+    }
+    fn at(&self, point: &Self::Point) -> &T {
+        &self.data.as_slice()[self.layout.to_index(point)]
+    }
+
+    fn at_mut(&mut self, point: &Self::Point) -> &mut T {
+        let index = self.layout.to_index(point);
+
+        &mut self.data.as_mut_slice()[index]
+    }
+
+
+    fn surface_area(&self, scale: f64) -> f64 {
+        self.layout.surface_area(scale)
+    }
+
+    fn topology(&self) -> Topology {
+        self.layout.topology()
+    }
+
+    fn contiguous_values_mut(&mut self) -> Option<&mut [T]> {
+        Some(self.as_mut_slice())
+    }
+}
+
+impl <T, G: SurfaceGrid<()>, D: GridStorage<T>> Index<G::Point> for StoredGrid<T, G, D> {
+    type Output = T;
+
+    fn index(&self, index: G::Point) -> &Self::Output {
+        &self.data.as_slice()[self.layout.to_index(&index)]
+    }
+}
+
+impl <T, G: SurfaceGrid<()>, D: GridStorage<T>> IndexMut<G::Point> for StoredGrid<T, G, D> {
+    fn index_mut(&mut self, index: G::Point) -> &mut Self::Output {
+        let index = self.layout.to_index(&index);
+
+        &mut self.data.as_mut_slice()[index]
+    }
+}
+
+impl <T, G, D> FromIterator<T> for StoredGrid<T, G, D>
+where
+    G: SurfaceGrid<()> + Default + Sync,
+    G::Point: Sync,
+    D: OwnedStorage<T> + Sync {
+    /// Creates a grid from the values of its cells in the same order as `points`.
+    ///
+    /// # Panics
+    /// Panics if the iterator does not yield exactly one value for each cell in the grid.
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        Self::from_vec(iter.into_iter().collect())
+            .expect("the iterator must yield one value for each cell in the grid")
+    }
+}
+
+impl <T, G: SurfaceGrid<()>, D: OwnedStorage<T>> IntoIterator for StoredGrid<T, G, D> {
+    type Item = (G::Point, T);
+
+    type IntoIter = vec::IntoIter<Self::Item>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        let data: Vec<_> = self.layout.points()
+            .zip(self.data.into_vec())
+            .collect();
+
+        data.into_iter()
+    }
+}
+
+#[cfg(feature = "serde")]
+impl <T: serde::Serialize, G: SurfaceGrid<()>, D: GridStorage<T>> serde::Serialize for StoredGrid<T, G, D> {
+    /// Serializes the values of every cell as a sequence in the same order as `iter`.
+    fn serialize<R: serde::Serializer>(&self, serializer: R) -> Result<R::Ok, R::Error> {
+        serializer.collect_seq(self.data.as_slice())
+    }
+}
+
+#[cfg(feature = "serde")]
+impl <'de, T, G, D> serde::Deserialize<'de> for StoredGrid<T, G, D>
+where
+    T: serde::Deserialize<'de>,
+    G: SurfaceGrid<()> + Default,
+    D: OwnedStorage<T> {
+    /// Deserializes a grid from a sequence of values in the same order as `iter`.
+    fn deserialize<R: serde::Deserializer<'de>>(deserializer: R) -> Result<Self, R::Error> {
+        let values = Vec::<T>::deserialize(deserializer)?;
+        let length = values.len();
+        let expected = G::default().points().count();
+
+        Self::from_storage(D::from_vec(values))
+            .ok_or_else(|| serde::de::Error::invalid_length(length, &format!("a sequence of {} values", expected).as_str()))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{GridPoint, SurfaceGrid, sphere::{CubeSphereGrid, CubeSpherePoint, RectangleSphereGrid}};
+
+    use super::StoredGrid;
+
+    #[test]
+    fn test_stored_matches_layout_grid() {
+        let stored: StoredGrid<f64, CubeSphereGrid<(), 8>> = StoredGrid::from_fn(|point: &CubeSpherePoint<8>| point.position(1.0).0);
+        let dense: CubeSphereGrid<f64, 8> = CubeSphereGrid::from_fn(|point| point.position(1.0).0);
+
+        let stored = stored.map_neighbours(|current, up, down, left, right| current + up - down + left * 2.0 - right);
+        let dense = dense.map_neighbours(|current, up, down, left, right| current + up - down + left * 2.0 - right);
+
+        assert_eq!(dense.to_vec(), stored.to_vec());
+        assert_eq!(dense.to_vec(), stored.as_slice());
+    }
+
+    #[test]
+    fn test_stored_boxed_slice() {
+        let mut grid: StoredGrid<u32, RectangleSphereGrid<(), 8, 4>, Box<[u32]>> = StoredGrid::from_index_fn(|index| index as u32);
+
+        let point = grid.points().nth(5).unwrap();
+        grid[point] = 100;
+
+        assert_eq!(100, grid.storage()[5]);
+        assert_eq!(grid.par_map_to_vec(|_, value| *value), grid.to_vec());
+
+        let values: Vec<_> = grid.into_iter().map(|(_, value)| value).collect();
+
+        assert_eq!(32, values.len());
+        assert_eq!(100, values[5]);
+    }
+
+    #[test]
+    fn test_stored_borrowed_slice() {
+        let mut buffer = [0u8; 6 * 4 * 4];
+
+        assert!(StoredGrid::<u8, CubeSphereGrid<(), 4>, &mut [u8]>::from_storage(&mut buffer[1..]).is_none());
+
+        let mut grid: StoredGrid<u8, CubeSphereGrid<(), 4>, &mut [u8]> = StoredGrid::from_storage(&mut buffer[..]).unwrap();
+
+        let point = grid.layout().points().nth(3).unwrap();
+        grid[point] = 9;
+        grid[point.up()] = 4;
+
+        assert_eq!(9, buffer[3]);
+        assert_eq!(13, buffer.iter().map(|value| *value as u32).sum::<u32>());
+    }
+
+    #[test]
+    fn test_stored_try_from_fn() {
+        let stored: Result<StoredGrid<f64, CubeSphereGrid<(), 8>>, ()> = StoredGrid::try_from_fn_par(|point: &CubeSpherePoint<8>| Ok(point.position(1.0).0));
+        let dense: CubeSphereGrid<f64, 8> = CubeSphereGrid::from_fn(|point| point.position(1.0).0);
+
+        assert_eq!(dense.to_vec(), stored.unwrap().as_slice());
+    }
+
+    #[test]
+    fn test_stored_at() {
+        let mut grid: StoredGrid<u32, CubeSphereGrid<(), 4>> = StoredGrid::from_index_fn(|index| index as u32);
+        let point = grid.points().nth(7).unwrap();
+
+        *grid.at_mut(&point) += 10;
+
+        assert_eq!(17, *grid.at(&point));
+        assert_eq!(grid[point], *grid.at(&point));
+        assert_eq!(Some(&17), grid.get(&point));
+    }
+}
@@ -0,0 +1,363 @@
+//! A module containing grids wrapped around the surface of a torus.
+
+use std::{f64::consts::PI, ops::{Index, IndexMut}, vec};
+
+use itertools::Itertools;
+use rayon::prelude::*;
+use static_array::HeapArray2D;
+
+use crate::{GridPoint, OutOfBounds, Surface, SurfaceGrid, Topology};
+
+assert_send_sync!([T: Send + Sync, const W: usize, const H: usize]
+    TorusGrid<T, W, H>,
+    TorusPoint<W, H>,
+);
+
+/// A rectangular grid wrapped around a torus so that both axes wrap seamlessly.
+///
+/// The X axis goes around the hole of the torus and the Y axis goes around its tube.
+///
+/// # Type Parameters
+/// - `T` - The type of data that the grid holds.
+///
+/// # Constant Parameters
+/// - `W` - The width of the grid.
+/// - `H` - The height of the grid.
+///
+/// # Iteration Order
+/// `iter`, `points` and `into_iter` all visit the grid in row-major order, starting at `(0, 0)`
+/// and increasing `x` before `y`. This order is stable.
+///
+/// # Thread Safety
+/// The cells are kept in a single `HeapArray2D` and the radii of the torus are given to `position`
+/// rather than stored, so the grid is `Send` when `T` is `Send` and `Sync` when `T` is `Sync`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Default)]
+pub struct TorusGrid<T, const W: usize, const H: usize> {
+    /// The data held in this grid.
+    data: HeapArray2D<T, W, H>,
+}
+
+impl <T, const W: usize, const H: usize> TorusGrid<T, W, H> {
+    /// Gets the values of the cells in the same order as the points of the grid.
+    pub fn as_slice(&self) -> &[T] {
+        self.data.as_flattened()
+    }
+
+    /// Gets mutable references to the values of the cells in the same order as the points of the
+    /// grid.
+    pub fn as_mut_slice(&mut self) -> &mut [T] {
+        self.data.as_flattened_mut()
+    }
+
+    /// Gets the values of the cells as bytes in the same order as `as_slice`.
+    #[cfg(feature = "bytemuck")]
+    pub fn as_bytes(&self) -> &[u8] where T: bytemuck::Pod {
+        bytemuck::cast_slice(self.as_slice())
+    }
+
+    /// Gets the values of the cells as mutable bytes in the same order as `as_slice`.
+    #[cfg(feature = "bytemuck")]
+    pub fn as_bytes_mut(&mut self) -> &mut [u8] where T: bytemuck::Pod {
+        bytemuck::cast_slice_mut(self.as_mut_slice())
+    }
+}
+
+impl <T, const W: usize, const H: usize> SurfaceGrid<T> for TorusGrid<T, W, H> {
+    type Point = TorusPoint<W, H>;
+
+    row_major_grid_methods!(TorusPoint);
+
+    /// Gets the surface area of the torus used by `GridPoint::position`.
+    ///
+    /// This is `4 * PI^2 * R * r` where the major radius `R` is `scale` and the minor radius `r`
+    /// is half of `scale`.
+    fn surface_area(&self, scale: f64) -> f64 {
+        2.0 * PI * PI * scale * scale
+    }
+
+    fn topology(&self) -> Topology {
+        Topology::uniform(Surface::Torus, 1, W, H)
+    }
+}
+
+impl <T, const W: usize, const H: usize> Index<TorusPoint<W, H>> for TorusGrid<T, W, H> {
+    type Output = T;
+
+    fn index(&self, index: TorusPoint<W, H>) -> &Self::Output {
+        &self.data[index.y as usize][index.x as usize]
+    }
+}
+
+impl <T, const W: usize, const H: usize> IndexMut<TorusPoint<W, H>> for TorusGrid<T, W, H> {
+    fn index_mut(&mut self, index: TorusPoint<W, H>) -> &mut Self::Output {
+        &mut self.data[index.y as usize][index.x as usize]
+    }
+}
+
+impl <T, const W: usize, const H: usize> Index<&TorusPoint<W, H>> for TorusGrid<T, W, H> {
+    type Output = T;
+
+    fn index(&self, index: &TorusPoint<W, H>) -> &Self::Output {
+        &self[*index]
+    }
+}
+
+impl <T, const W: usize, const H: usize> IndexMut<&TorusPoint<W, H>> for TorusGrid<T, W, H> {
+    fn index_mut(&mut self, index: &TorusPoint<W, H>) -> &mut Self::Output {
+        &mut self[*index]
+    }
+}
+
+impl <T, const W: usize, const H: usize> FromIterator<T> for TorusGrid<T, W, H> {
+    /// Creates a grid from the values of its cells in the same order as `points`.
+    ///
+    /// # Panics
+    /// Panics if the iterator does not yield exactly one value for each cell in the grid.
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        Self::from_vec(iter.into_iter().collect())
+            .expect("the iterator must yield one value for each cell in the grid")
+    }
+}
+
+impl <T, const W: usize, const H: usize> IntoIterator for TorusGrid<T, W, H> {
+    type Item = (TorusPoint<W, H>, T);
+
+    type IntoIter = vec::IntoIter<Self::Item>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        let data: Vec<_> = self.data.into_iter()
+            .enumerate()
+            .flat_map(|(y, subarray)| subarray.into_iter()
+                      .enumerate()
+                      .map(move |(x, value)| (TorusPoint::new(x as u32, y as u32), value))
+                      )
+            .collect();
+
+        data.into_iter()
+    }
+}
+
+#[cfg(feature = "serde")]
+impl <T: serde::Serialize, const W: usize, const H: usize> serde::Serialize for TorusGrid<T, W, H> {
+    /// Serializes the values in this grid as a sequence in the same order as `iter`.
+    fn serialize<R: serde::Serializer>(&self, serializer: R) -> Result<R::Ok, R::Error> {
+        serializer.collect_seq(self.iter().map(|(_, value)| value))
+    }
+}
+
+#[cfg(feature = "serde")]
+impl <'de, T: serde::Deserialize<'de>, const W: usize, const H: usize> serde::Deserialize<'de> for TorusGrid<T, W, H> {
+    /// Deserializes a grid from a sequence of values in the same order as `iter`.
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let values = Vec::<T>::deserialize(deserializer)?;
+        let length = values.len();
+
+        Self::from_vec(values)
+            .ok_or_else(|| serde::de::Error::invalid_length(length, &format!("a sequence of {} values", W * H).as_str()))
+    }
+}
+
+/// A point on a `TorusGrid`.
+///
+/// Moving off any edge of the grid continues from the opposite edge.
+///
+/// # Constant Parameters
+/// - `W` - The width of the grid.
+/// - `H` - The height of the grid.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct TorusPoint<const W: usize, const H: usize> {
+    x: u32,
+    y: u32,
+}
+
+impl <const W: usize, const H: usize> TorusPoint<W, H> {
+    /// Creates a new point, wrapping the coordinates onto the grid.
+    ///
+    /// - `x` - The X coordinate.
+    /// - `y` - The Y coordinate.
+    fn new(x: u32, y: u32) -> Self {
+        Self {
+            x: x % W as u32,
+            y: y % H as u32,
+        }
+    }
+
+    /// Creates a new `TorusPoint` checking that the position lies on the grid.
+    ///
+    /// This returns an error if `x` is `W` or greater or `y` is `H` or greater instead of wrapping the coordinates.
+    ///
+    /// - `x` - The X coordinate.
+    /// - `y` - The Y coordinate.
+    pub fn try_new(x: u32, y: u32) -> Result<Self, OutOfBounds> {
+        if (x as usize) < W && (y as usize) < H {
+            Ok(Self::new(x, y))
+        } else {
+            Err(OutOfBounds)
+        }
+    }
+
+    /// Gets the position of the point on a torus with the specified radii.
+    ///
+    /// The torus is centred on the origin with the Y axis passing through its hole. X values
+    /// increase anticlockwise around the hole when viewed from above and the row at `y = 0` lies
+    /// on the outer equator of the tube with moving up going over the top of the tube.
+    ///
+    /// - `major_radius` - The distance from the centre of the torus to the centre of the tube.
+    /// - `minor_radius` - The radius of the tube.
+    pub fn torus_position(&self, major_radius: f64, minor_radius: f64) -> (f64, f64, f64) {
+        let around = self.x as f64 / W as f64 * PI * 2.0;
+        let tube = -(self.y as f64) / H as f64 * PI * 2.0;
+
+        let distance = major_radius + minor_radius * tube.cos();
+
+        (distance * around.cos(), minor_radius * tube.sin(), -distance * around.sin())
+    }
+}
+
+impl <const W: usize, const H: usize> GridPoint for TorusPoint<W, H> {
+    fn up(&self) -> Self {
+        Self::new(self.x, self.y + H as u32 - 1)
+    }
+
+    fn down(&self) -> Self {
+        Self::new(self.x, self.y + 1)
+    }
+
+    fn left(&self) -> Self {
+        Self::new(self.x + W as u32 - 1, self.y)
+    }
+
+    fn right(&self) -> Self {
+        Self::new(self.x + 1, self.y)
+    }
+
+    /// Gets the position of the point in 3D space.
+    ///
+    /// This is `torus_position` with a major radius of `scale` and a minor radius of half of
+    /// `scale`.
+    ///
+    /// - `scale` - The scale of the 3D object.
+    fn position(&self, scale: f64) -> (f64, f64, f64) {
+        self.torus_position(scale, scale / 2.0)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use approx::assert_relative_eq;
+
+    use crate::{GridPoint, SurfaceGrid};
+
+    use super::{TorusGrid, TorusPoint};
+
+    #[test]
+    fn test_torus_up() {
+        let point: TorusPoint<10, 5> = TorusPoint::new(3, 2);
+
+        assert_eq!(point.up(), TorusPoint::new(3, 1));
+        assert_eq!(TorusPoint::<10, 5>::new(3, 0).up(), TorusPoint::new(3, 4));
+    }
+
+    #[test]
+    fn test_torus_down() {
+        let point: TorusPoint<10, 5> = TorusPoint::new(3, 2);
+
+        assert_eq!(point.down(), TorusPoint::new(3, 3));
+        assert_eq!(TorusPoint::<10, 5>::new(3, 4).down(), TorusPoint::new(3, 0));
+    }
+
+    #[test]
+    fn test_torus_left() {
+        let point: TorusPoint<10, 5> = TorusPoint::new(3, 2);
+
+        assert_eq!(point.left(), TorusPoint::new(2, 2));
+        assert_eq!(TorusPoint::<10, 5>::new(0, 2).left(), TorusPoint::new(9, 2));
+    }
+
+    #[test]
+    fn test_torus_right() {
+        let point: TorusPoint<10, 5> = TorusPoint::new(3, 2);
+
+        assert_eq!(point.right(), TorusPoint::new(4, 2));
+        assert_eq!(TorusPoint::<10, 5>::new(9, 2).right(), TorusPoint::new(0, 2));
+    }
+
+    #[test]
+    fn test_torus_loops() {
+        let grid: TorusGrid<(), 10, 5> = TorusGrid::default();
+
+        for point in grid.points() {
+            assert_eq!((0..10).fold(point, |point, _| point.right()), point);
+            assert_eq!((0..10).fold(point, |point, _| point.left()), point);
+            assert_eq!((0..5).fold(point, |point, _| point.up()), point);
+            assert_eq!((0..5).fold(point, |point, _| point.down()), point);
+
+            assert_eq!(point.up().down(), point);
+            assert_eq!(point.left().right(), point);
+            assert_eq!(point.up_left().down_right(), point);
+            assert_eq!(point.checked_up(), Some(point.up()));
+        }
+    }
+
+    #[test]
+    fn test_torus_iteration_order() {
+        let grid: TorusGrid<(u32, u32), 3, 2> = TorusGrid::from_fn(|point| (point.x, point.y));
+
+        let expected = [(0, 0), (1, 0), (2, 0), (0, 1), (1, 1), (2, 1)];
+
+        let points: Vec<_> = grid.points().map(|point| (point.x, point.y)).collect();
+        let iter: Vec<_> = grid.iter().map(|(_, value)| *value).collect();
+        let par_map: Vec<_> = grid.par_map_to_vec(|_, value| *value);
+        let into_iter: Vec<_> = grid.into_iter().map(|(_, value)| value).collect();
+
+        assert_eq!(points, expected);
+        assert_eq!(iter, expected);
+        assert_eq!(par_map, expected);
+        assert_eq!(into_iter, expected);
+    }
+
+    #[test]
+    fn test_torus_to_index() {
+        let grid: TorusGrid<(), 10, 5> = TorusGrid::default();
+
+        for (i, point) in grid.points().enumerate() {
+            assert_eq!(grid.to_index(&point), i);
+        }
+    }
+
+    #[test]
+    fn test_torus_map_neighbours() {
+        let grid: TorusGrid<u32, 10, 5> = TorusGrid::from_fn(|_| 1);
+
+        let grid2 = grid.map_neighbours_diagonals(|a, b, c, d, e, f, g, h, i| a + b + c + d + e + f + g + h + i);
+
+        assert!(grid2.iter().all(|(_, value)| *value == 9));
+    }
+
+    #[test]
+    fn test_torus_position() {
+        let point: TorusPoint<8, 4> = TorusPoint::new(0, 0);
+
+        assert_relative_eq!(point.torus_position(3.0, 1.0).0, 4.0);
+        assert_relative_eq!(TorusPoint::<8, 4>::new(0, 2).torus_position(3.0, 1.0).0, 2.0);
+        assert_relative_eq!(TorusPoint::<8, 4>::new(0, 1).torus_position(3.0, 1.0).1, -1.0);
+        assert_relative_eq!(point.up().torus_position(3.0, 1.0).1, 1.0);
+
+        // Every point lies on the surface of the torus.
+        for point in TorusGrid::<(), 8, 4>::default().points() {
+            let (x, y, z) = point.position(2.0);
+            let distance = (x * x + z * z).sqrt() - 2.0;
+
+            assert_relative_eq!(distance * distance + y * y, 1.0, epsilon = 1e-12);
+        }
+    }
+
+    #[test]
+    fn test_torus_from_vec() {
+        let grid: TorusGrid<u32, 3, 2> = TorusGrid::from_fn(|point| point.x * 10 + point.y);
+
+        assert_eq!(grid.to_vec(), [0, 10, 20, 1, 11, 21]);
+        assert_eq!(TorusGrid::from_vec(grid.to_vec()), Some(grid));
+        assert_eq!(TorusGrid::<u32, 3, 2>::from_vec(vec![0; 5]), None);
+    }
+}
@@ -0,0 +1,431 @@
+//! A module containing views that borrow a region of a grid.
+
+use std::{marker::PhantomData, ops::{Index, IndexMut}};
+
+use crate::{GridPoint, SurfaceGrid, sphere::SpherePoint};
+
+assert_send_sync!(['a, T: Send + Sync, G: SurfaceGrid<T> + Send + Sync + 'a] where [G::Point: Sync]
+    GridView<'a, T, G>,
+    GridViewMut<'a, T, G>,
+);
+
+/// A region of a grid, kept in the same order as the points of the grid.
+#[derive(Debug, Clone)]
+struct Region<P> {
+    /// The points in the region.
+    points: Vec<P>,
+    /// The indices of the points in the region, sorted and without duplicates.
+    indices: Vec<usize>,
+}
+
+impl <P: GridPoint> Region<P> {
+    /// Creates a region from a list of points, which may contain duplicates.
+    ///
+    /// - `grid` - The grid that the region is in.
+    /// - `points` - The points in the region.
+    fn new<T, G: SurfaceGrid<T, Point = P>>(grid: &G, points: impl IntoIterator<Item = P>) -> Self {
+        let mut points: Vec<_> = points.into_iter()
+            .map(|point| (grid.to_index(&point), point))
+            .collect();
+
+        points.sort_by_key(|(index, _)| *index);
+        points.dedup_by_key(|(index, _)| *index);
+
+        let (indices, points) = points.into_iter().unzip();
+
+        Self {
+            points,
+            indices,
+        }
+    }
+
+    /// Gets the points in a rectangle that starts at a corner and extends to the right and down.
+    ///
+    /// The rectangle is found by stepping with `right` and `down` so it follows the grid across
+    /// seams and faces.
+    ///
+    /// - `corner` - The top left corner of the rectangle.
+    /// - `width` - The number of cells along each row.
+    /// - `height` - The number of rows.
+    fn rect_points(corner: P, width: usize, height: usize) -> impl Iterator<Item = P> {
+        std::iter::successors(Some(corner), |point| Some(point.down()))
+            .take(height)
+            .flat_map(move |start| std::iter::successors(Some(start), |point| Some(point.right())).take(width))
+    }
+
+    /// Gets the position of a point in the region.
+    ///
+    /// - `index` - The index of the point in the grid.
+    fn position(&self, index: usize) -> Option<usize> {
+        self.indices.binary_search(&index).ok()
+    }
+}
+
+/// A view of a region of a grid that allows the cells in the region to be read.
+///
+/// The region is a set of points chosen when the view is created, such as a rectangle of cells or
+/// the cells within a range of latitudes and longitudes. Creating a view may check every point on
+/// the grid but iterating over the view only visits the cells in the region.
+///
+/// # Type Parameters
+/// - `T` - The type of data that the grid holds.
+/// - `G` - The type of the grid.
+///
+/// # Iteration Order
+/// `iter` and `points` visit the cells in the same order as the grid.
+///
+/// # Panics
+/// Indexing the view with a point outside of the region panics.
+#[derive(Debug)]
+pub struct GridView<'a, T, G: SurfaceGrid<T>> {
+    /// The grid that the view borrows.
+    grid: &'a G,
+    /// The cells in the view.
+    region: Region<G::Point>,
+    data: PhantomData<T>,
+}
+
+impl <'a, T, G: SurfaceGrid<T>> GridView<'a, T, G> {
+    /// Creates a view of a set of points of a grid.
+    ///
+    /// Points that appear more than once are only included once.
+    ///
+    /// - `grid` - The grid to view.
+    /// - `points` - The points in the region.
+    pub fn new(grid: &'a G, points: impl IntoIterator<Item = G::Point>) -> Self {
+        Self {
+            region: Region::new(grid, points),
+            grid,
+            data: PhantomData,
+        }
+    }
+
+    /// Creates a view of a rectangle of cells starting at a corner and extending to the right and
+    /// down.
+    ///
+    /// The rectangle is found by stepping from the corner with `GridPoint::right` and
+    /// `GridPoint::down` so it follows the grid across seams. Cells that are reached more than
+    /// once, for example when the rectangle is wider than a grid that wraps, are only included
+    /// once.
+    ///
+    /// - `grid` - The grid to view.
+    /// - `corner` - The top left corner of the rectangle.
+    /// - `width` - The number of cells along each row.
+    /// - `height` - The number of rows.
+    pub fn rect(grid: &'a G, corner: G::Point, width: usize, height: usize) -> Self {
+        Self::new(grid, Region::rect_points(corner, width, height))
+    }
+
+    /// Creates a view of the cells within a range of geographic coordinates.
+    ///
+    /// The cells are the same as those given by `SurfaceGrid::points_in_region` so the longitude
+    /// range wraps around the sphere if its start is greater than its end.
+    ///
+    /// - `grid` - The grid to view.
+    /// - `lat_range` - The range of latitudes in radians.
+    /// - `lon_range` - The range of longitudes in radians.
+    pub fn geographic(grid: &'a G, lat_range: (f64, f64), lon_range: (f64, f64)) -> Self where G::Point: SpherePoint {
+        Self::new(grid, grid.points_in_region(lat_range, lon_range))
+    }
+
+    /// Gets the number of cells in the view.
+    pub fn len(&self) -> usize {
+        self.region.points.len()
+    }
+
+    /// Checks if the view has no cells.
+    pub fn is_empty(&self) -> bool {
+        self.region.points.is_empty()
+    }
+
+    /// Checks if a point is in the view.
+    ///
+    /// - `point` - The point to check.
+    pub fn contains(&self, point: &G::Point) -> bool {
+        self.region.position(self.grid.to_index(point)).is_some()
+    }
+
+    /// Gets the value of a cell if it is in the view.
+    ///
+    /// - `point` - The point to get the value of.
+    pub fn get(&self, point: &G::Point) -> Option<&T> {
+        self.contains(point).then(|| &self.grid[point.clone()])
+    }
+
+    /// Iterates over the points in the view.
+    pub fn points(&self) -> impl Iterator<Item = G::Point> + '_ {
+        self.region.points.iter().cloned()
+    }
+
+    /// Iterates over the points in the view along with their values.
+    pub fn iter(&self) -> impl Iterator<Item = (G::Point, &T)> + '_ {
+        self.region.points.iter()
+            .map(|point| (point.clone(), &self.grid[point.clone()]))
+    }
+
+    /// Gets the grid that the view borrows.
+    pub fn grid(&self) -> &'a G {
+        self.grid
+    }
+}
+
+impl <T, G: SurfaceGrid<T>> Index<G::Point> for GridView<'_, T, G> {
+    type Output = T;
+
+    fn index(&self, index: G::Point) -> &Self::Output {
+        self.get(&index).expect("the point is not in the view")
+    }
+}
+
+/// A view of a region of a grid that allows the cells in the region to be read and written.
+///
+/// This is the mutable version of `GridView`. Only the cells in the region can be written through
+/// the view, although the whole grid is borrowed while the view exists.
+///
+/// # Type Parameters
+/// - `T` - The type of data that the grid holds.
+/// - `G` - The type of the grid.
+///
+/// # Iteration Order
+/// `iter`, `points`, `for_each_mut` and `set_from_fn` visit the cells in the same order as the
+/// grid.
+///
+/// # Panics
+/// Indexing the view with a point outside of the region panics.
+#[derive(Debug)]
+pub struct GridViewMut<'a, T, G: SurfaceGrid<T>> {
+    /// The grid that the view borrows.
+    grid: &'a mut G,
+    /// The cells in the view.
+    region: Region<G::Point>,
+    data: PhantomData<T>,
+}
+
+impl <'a, T, G: SurfaceGrid<T>> GridViewMut<'a, T, G> {
+    /// Creates a mutable view of a set of points of a grid.
+    ///
+    /// Points that appear more than once are only included once.
+    ///
+    /// - `grid` - The grid to view.
+    /// - `points` - The points in the region.
+    pub fn new(grid: &'a mut G, points: impl IntoIterator<Item = G::Point>) -> Self {
+        Self {
+            region: Region::new(grid, points),
+            grid,
+            data: PhantomData,
+        }
+    }
+
+    /// Creates a mutable view of a rectangle of cells starting at a corner and extending to the
+    /// right and down.
+    ///
+    /// The cells are the same as those given by `GridView::rect`.
+    ///
+    /// - `grid` - The grid to view.
+    /// - `corner` - The top left corner of the rectangle.
+    /// - `width` - The number of cells along each row.
+    /// - `height` - The number of rows.
+    pub fn rect(grid: &'a mut G, corner: G::Point, width: usize, height: usize) -> Self {
+        Self::new(grid, Region::rect_points(corner, width, height))
+    }
+
+    /// Creates a mutable view of the cells within a range of geographic coordinates.
+    ///
+    /// The cells are the same as those given by `GridView::geographic`.
+    ///
+    /// - `grid` - The grid to view.
+    /// - `lat_range` - The range of latitudes in radians.
+    /// - `lon_range` - The range of longitudes in radians.
+    pub fn geographic(grid: &'a mut G, lat_range: (f64, f64), lon_range: (f64, f64)) -> Self where G::Point: SpherePoint {
+        let points: Vec<_> = grid.points_in_region(lat_range, lon_range).collect();
+
+        Self::new(grid, points)
+    }
+
+    /// Gets the number of cells in the view.
+    pub fn len(&self) -> usize {
+        self.region.points.len()
+    }
+
+    /// Checks if the view has no cells.
+    pub fn is_empty(&self) -> bool {
+        self.region.points.is_empty()
+    }
+
+    /// Checks if a point is in the view.
+    ///
+    /// - `point` - The point to check.
+    pub fn contains(&self, point: &G::Point) -> bool {
+        self.region.position(self.grid.to_index(point)).is_some()
+    }
+
+    /// Gets the value of a cell if it is in the view.
+    ///
+    /// - `point` - The point to get the value of.
+    pub fn get(&self, point: &G::Point) -> Option<&T> {
+        self.contains(point).then(|| &self.grid[point.clone()])
+    }
+
+    /// Gets a mutable reference to the value of a cell if it is in the view.
+    ///
+    /// - `point` - The point to get the value of.
+    pub fn get_mut(&mut self, point: &G::Point) -> Option<&mut T> {
+        if self.contains(point) {
+            Some(&mut self.grid[point.clone()])
+        } else {
+            None
+        }
+    }
+
+    /// Iterates over the points in the view.
+    pub fn points(&self) -> impl Iterator<Item = G::Point> + '_ {
+        self.region.points.iter().cloned()
+    }
+
+    /// Iterates over the points in the view along with their values.
+    pub fn iter(&self) -> impl Iterator<Item = (G::Point, &T)> + '_ {
+        self.region.points.iter()
+            .map(|point| (point.clone(), &self.grid[point.clone()]))
+    }
+
+    /// Calls a function with a mutable reference to the value of each cell in the view.
+    ///
+    /// - `f` - The function to call with each point and its value.
+    pub fn for_each_mut<F: FnMut(&G::Point, &mut T)>(&mut self, mut f: F) {
+        for point in &self.region.points {
+            f(point, &mut self.grid[point.clone()]);
+        }
+    }
+
+    /// Sets the value of every cell in the view.
+    ///
+    /// - `f` - A function that gives the new value of each cell.
+    pub fn set_from_fn<F: FnMut(&G::Point) -> T>(&mut self, mut f: F) {
+        self.for_each_mut(|point, value| *value = f(point));
+    }
+
+    /// Sets every cell in the view to a value.
+    ///
+    /// - `value` - The value to set the cells to.
+    pub fn fill(&mut self, value: T) where T: Clone {
+        self.for_each_mut(|_, cell| *cell = value.clone());
+    }
+
+    /// Gets a read only view of the same region.
+    pub fn as_view(&self) -> GridView<'_, T, G> {
+        GridView {
+            grid: self.grid,
+            region: self.region.clone(),
+            data: PhantomData,
+        }
+    }
+
+    /// Gets the grid that the view borrows.
+    pub fn grid(&self) -> &G {
+        self.grid
+    }
+}
+
+impl <T, G: SurfaceGrid<T>> Index<G::Point> for GridViewMut<'_, T, G> {
+    type Output = T;
+
+    fn index(&self, index: G::Point) -> &Self::Output {
+        self.get(&index).expect("the point is not in the view")
+    }
+}
+
+impl <T, G: SurfaceGrid<T>> IndexMut<G::Point> for GridViewMut<'_, T, G> {
+    fn index_mut(&mut self, index: G::Point) -> &mut Self::Output {
+        self.get_mut(&index).expect("the point is not in the view")
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::f64::consts::PI;
+
+    use crate::{GridPoint, SurfaceGrid, sphere::{CubeSphereGrid, SpherePoint}, torus::TorusGrid};
+
+    use super::{GridView, GridViewMut};
+
+    #[test]
+    fn test_view_rect() {
+        let grid: TorusGrid<usize, 8, 8> = TorusGrid::from_index_fn(|index| index);
+        let corner = grid.points().nth(6 * 8 + 6).unwrap();
+
+        let view = GridView::rect(&grid, corner, 3, 2);
+
+        assert_eq!(6, view.len());
+        assert!(view.contains(&corner.right().right()));
+        assert!(!view.contains(&corner.up()));
+        assert_eq!(None, view.get(&corner.left()));
+        assert_eq!(grid[corner.down()], view[corner.down()]);
+
+        // The rectangle wraps around the right and bottom edges of the torus.
+        let values: Vec<_> = view.iter().map(|(_, value)| *value).collect();
+
+        assert_eq!(vec![48, 54, 55, 56, 62, 63], values);
+    }
+
+    #[test]
+    fn test_view_rect_duplicates() {
+        let grid: TorusGrid<u8, 4, 4> = TorusGrid::default();
+        let corner = grid.points().next().unwrap();
+
+        assert_eq!(8, GridView::rect(&grid, corner, 6, 2).len());
+    }
+
+    #[test]
+    fn test_view_geographic() {
+        let mut grid: CubeSphereGrid<f64, 16> = CubeSphereGrid::default();
+
+        let lat_range = (PI / 8.0, PI / 4.0);
+        let lon_range = (-PI / 4.0, PI / 4.0);
+
+        let mut view = GridViewMut::geographic(&mut grid, lat_range, lon_range);
+        view.set_from_fn(|point| point.latitude());
+
+        let expected: Vec<_> = view.grid().points_in_region(lat_range, lon_range).collect();
+
+        assert!(!view.is_empty());
+        assert_eq!(expected, view.points().collect::<Vec<_>>());
+        assert_eq!(expected.len(), view.as_view().len());
+
+        assert!(grid.iter().all(|(point, value)| {
+            if expected.contains(&point) {
+                *value == point.latitude()
+            } else {
+                *value == 0.0
+            }
+        }));
+    }
+
+    #[test]
+    fn test_view_mut_index() {
+        let mut grid: CubeSphereGrid<u32, 4> = CubeSphereGrid::default();
+        let point = grid.points().nth(5).unwrap();
+
+        let mut view = GridViewMut::new(&mut grid, [point, point.up(), point]);
+
+        view[point] = 3;
+        view.fill(1);
+        view[point.up()] += 1;
+
+        assert_eq!(2, view.len());
+        assert_eq!(None, view.get_mut(&point.down()));
+        assert_eq!(1, grid[point]);
+        assert_eq!(2, grid[point.up()]);
+        assert_eq!(3, grid.iter().map(|(_, value)| value).sum::<u32>());
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_view_index_outside() {
+        let grid: CubeSphereGrid<u32, 4> = CubeSphereGrid::default();
+        let point = grid.points().next().unwrap();
+
+        let view = GridView::new(&grid, [point]);
+
+        let _ = view[point.down()];
+    }
+}
@@ -0,0 +1,824 @@
+//! A module containing grids on spheres made from two overlapping latitude-longitude patches.
+
+use std::{f64::consts::PI, ops::{Index, IndexMut}, vec};
+
+use rayon::prelude::*;
+
+use crate::{GridPoint, OutOfBounds, Surface, SurfaceGrid, Topology, sphere::SpherePoint};
+
+assert_send_sync!([T: Send + Sync, const W: usize, const H: usize]
+    YinYangSphereGrid<T, W, H>,
+    YinYangSpherePoint<W, H>,
+);
+
+/// One of the two patches of a `YinYangSphereGrid`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum YinYangPatch {
+    /// The patch around the equator, covering latitudes from `-PI / 4` to `PI / 4` and longitudes
+    /// from `-3 * PI / 4` to `3 * PI / 4`.
+    Yin,
+    /// The patch covering the poles and the longitudes around `PI`, which is the same as `Yin`
+    /// rotated so that its equator passes through the poles.
+    Yang,
+}
+
+/// A grid covering a sphere with two overlapping latitude-longitude patches.
+///
+/// Each patch is an equirectangular grid covering latitudes from `-PI / 4` to `PI / 4` and
+/// longitudes from `-3 * PI / 4` to `3 * PI / 4` in its own coordinates. The `Yin` patch uses the
+/// geographic coordinates of the sphere and the `Yang` patch is rotated so that it covers the
+/// poles and the gap left by `Yin`. Neither patch contains a pole so the cells never shrink
+/// towards a point and the cells at the equator of each patch are about `sqrt(2)` times larger
+/// than the smallest ones.
+///
+/// The two patches only just cover the sphere so each patch is extended by two extra cells on
+/// every side, which leaves `W - 4` by `H - 4` cells covering the region above. The patches
+/// overlap around their edges. Moving off the edge of a patch moves to the cell of
+/// the other patch that contains the centre of the cell that would have been there, so the
+/// neighbours across the overlap are not always joined both ways. `from_geographic` picks the
+/// patch that the position lies furthest inside. For grids of `f64` values, `sample` interpolates
+/// between the cells of the patch that a position lies furthest inside and
+/// `exchange_boundaries` sets the cells on the edges of each patch from the other patch, which is
+/// usually done after each step of a simulation.
+///
+/// # Type Parameters
+/// - `T` - The type of data that the grid holds.
+///
+/// # Constant Parameters
+/// - `W` - The width of each patch, which must be greater than 4. The cells are square at the
+///   equator of each patch when this is `3 * H - 8`.
+/// - `H` - The height of each patch, which must be greater than 4.
+///
+/// # Iteration Order
+/// `iter`, `points` and `into_iter` all visit the `Yin` patch and then the `Yang` patch. Each
+/// patch is visited in row-major order, starting at `(0, 0)` and increasing `x` before `y`. This
+/// order is stable.
+///
+/// # Thread Safety
+/// The yin and yang patches are kept together in one `Vec`, so the grid is `Send` when `T` is
+/// `Send` and `Sync` when `T` is `Sync`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct YinYangSphereGrid<T, const W: usize, const H: usize> {
+    /// The data held in this grid.
+    data: Vec<T>,
+}
+
+impl <const W: usize, const H: usize> YinYangSphereGrid<f64, W, H> {
+    /// Interpolates the value of this grid at a geographic position.
+    ///
+    /// This uses the weights from `YinYangSpherePoint::from_geographic_bilinear`.
+    ///
+    /// - `latitude` - The latitude of the position in radians where 0 is the equator.
+    /// - `longitude` - The longitude of the position in radians.
+    pub fn sample(&self, latitude: f64, longitude: f64) -> f64 {
+        let (points, weights) = YinYangSpherePoint::from_geographic_bilinear(latitude, longitude);
+
+        points.into_iter()
+            .zip(weights)
+            .map(|(point, weight)| self[point] * weight)
+            .sum()
+    }
+
+    /// Sets the cells on the edges of each patch by interpolating the values of the other patch
+    /// at their centres.
+    ///
+    /// All of the new values are calculated before any cells are changed.
+    pub fn exchange_boundaries(&mut self) {
+        let values: Vec<_> = self.points()
+            .filter(YinYangSpherePoint::is_boundary)
+            .map(|point| {
+                let (latitude, longitude) = point.center_geographic();
+
+                let (points, weights) = YinYangSpherePoint::patch_bilinear(point.patch.other(), latitude, longitude);
+
+                let value: f64 = points.into_iter()
+                    .zip(weights)
+                    .map(|(point, weight)| self[point] * weight)
+                    .sum();
+
+                (point, value)
+            })
+            .collect();
+
+        for (point, value) in values {
+            self[point] = value;
+        }
+    }
+}
+
+impl <T: Default, const W: usize, const H: usize> Default for YinYangSphereGrid<T, W, H> {
+    fn default() -> Self {
+        Self::from_fn(|_| T::default())
+    }
+}
+
+impl <T, const W: usize, const H: usize> YinYangSphereGrid<T, W, H> {
+    /// Gets the values of the cells in the same order as the points of the grid.
+    pub fn as_slice(&self) -> &[T] {
+        &self.data
+    }
+
+    /// Gets mutable references to the values of the cells in the same order as the points of the
+    /// grid.
+    pub fn as_mut_slice(&mut self) -> &mut [T] {
+        &mut self.data
+    }
+
+    /// Gets the values of the cells as bytes in the same order as `as_slice`.
+    #[cfg(feature = "bytemuck")]
+    pub fn as_bytes(&self) -> &[u8] where T: bytemuck::Pod {
+        bytemuck::cast_slice(self.as_slice())
+    }
+
+    /// Gets the values of the cells as mutable bytes in the same order as `as_slice`.
+    #[cfg(feature = "bytemuck")]
+    pub fn as_bytes_mut(&mut self) -> &mut [u8] where T: bytemuck::Pod {
+        bytemuck::cast_slice_mut(self.as_mut_slice())
+    }
+}
+
+impl <T, const W: usize, const H: usize> SurfaceGrid<T> for YinYangSphereGrid<T, W, H> {
+    type Point = YinYangSpherePoint<W, H>;
+
+    fn from_fn<F: FnMut(&Self::Point) -> T>(mut f: F) -> Self {
+        Self {
+            data: (0..2 * W * H)
+                .map(|i| f(&YinYangSpherePoint::from_index(i)))
+                .collect(),
+        }
+    }
+
+    fn from_fn_par<F: Fn(&Self::Point) -> T + Send + Sync>(f: F) -> Self where T: Send + Sync {
+        let mut data = Vec::with_capacity(2 * W * H);
+
+        (0..2 * W * H).into_par_iter()
+            .map(|i| f(&YinYangSpherePoint::from_index(i)))
+            .collect_into_vec(&mut data);
+
+        Self {
+            data,
+        }
+    }
+
+    fn try_from_fn<X, F: FnMut(&Self::Point) -> Result<T, X>>(mut f: F) -> Result<Self, X> {
+        Ok(Self {
+            data: (0..2 * W * H)
+                .map(|i| f(&YinYangSpherePoint::from_index(i)))
+                .collect::<Result<_, _>>()?,
+        })
+    }
+
+    fn try_from_fn_par<X: Send, F: Fn(&Self::Point) -> Result<T, X> + Send + Sync>(f: F) -> Result<Self, X> where T: Send + Sync {
+        Ok(Self {
+            data: (0..2 * W * H).into_par_iter()
+                .map(|i| f(&YinYangSpherePoint::from_index(i)))
+                .collect::<Result<_, _>>()?,
+        })
+    }
+
+    fn from_vec(values: Vec<T>) -> Option<Self> {
+        if values.len() != 2 * W * H {
+            return None;
+        }
+
+        Some(Self {
+            data: values,
+        })
+    }
+
+    fn set_from_fn<F: FnMut(&Self::Point) -> T>(&mut self, mut f: F) {
+        for (i, value) in self.data.iter_mut().enumerate() {
+            *value = f(&YinYangSpherePoint::from_index(i));
+        }
+    }
+
+    fn set_from_fn_par<F: Fn(&Self::Point) -> T + Send + Sync>(&mut self, f: F) where T: Send + Sync {
+        self.data.par_iter_mut().enumerate().for_each(|(i, value)| {
+            *value = f(&YinYangSpherePoint::from_index(i));
+        });
+    }
+
+    fn iter<'a>(&'a self) -> impl ExactSizeIterator<Item = (Self::Point, &'a T)> + DoubleEndedIterator where T: 'a {
+        self.data.iter()
+            .enumerate()
+            .map(|(i, value)| (YinYangSpherePoint::from_index(i), value))
+    }
+
+    fn par_iter<'a>(&'a self) -> impl IndexedParallelIterator<Item = (Self::Point, &'a T)> where T: 'a + Send + Sync {
+        self.data.par_iter()
+            .enumerate()
+            .map(|(i, value)| (YinYangSpherePoint::from_index(i), value))
+    }
+
+    fn par_iter_mut<'a>(&'a mut self) -> impl IndexedParallelIterator<Item = (Self::Point, &'a mut T)> where T: 'a + Send + Sync {
+        self.data.par_iter_mut()
+            .enumerate()
+            .map(|(i, value)| (YinYangSpherePoint::from_index(i), value))
+    }
+
+    fn points(&self) -> impl ExactSizeIterator<Item = Self::Point> + DoubleEndedIterator {
+        (0..2 * W * H)
+            .map(YinYangSpherePoint::from_index)
+    }
+
+    fn par_points(&self) -> impl IndexedParallelIterator<Item = Self::Point> {
+        (0..2 * W * H).into_par_iter()
+            .map(YinYangSpherePoint::from_index)
+    }
+
+    fn par_map_to_vec<U: Send, F: Fn(&Self::Point, &T) -> U + Send + Sync>(&self, f: F) -> Vec<U> where T: Send + Sync {
+        let mut result = Vec::with_capacity(2 * W * H);
+
+        self.data.par_iter()
+            .enumerate()
+            .map(|(i, value)| f(&YinYangSpherePoint::from_index(i), value))
+            .collect_into_vec(&mut result);
+
+        result
+    }
+
+    fn to_index(&self, point: &Self::Point) -> usize {
+        point.index()
+    }
+
+    fn at(&self, point: &Self::Point) -> &T {
+        &self[point]
+    }
+
+    fn at_mut(&mut self, point: &Self::Point) -> &mut T {
+        &mut self[point]
+    }
+
+    /// Gets the surface area of the sphere.
+    ///
+    /// The patches overlap so the areas of the cells add up to slightly more than this.
+    ///
+    /// - `scale` - The radius of the sphere.
+    fn surface_area(&self, scale: f64) -> f64 {
+        4.0 * PI * scale * scale
+    }
+
+    fn topology(&self) -> Topology {
+        Topology::uniform(Surface::Sphere, 2, W, H)
+    }
+
+    fn contiguous_values_mut(&mut self) -> Option<&mut [T]> {
+        Some(self.as_mut_slice())
+    }
+}
+
+impl <T, const W: usize, const H: usize> Index<YinYangSpherePoint<W, H>> for YinYangSphereGrid<T, W, H> {
+    type Output = T;
+
+    fn index(&self, index: YinYangSpherePoint<W, H>) -> &Self::Output {
+        &self.data[index.index()]
+    }
+}
+
+impl <T, const W: usize, const H: usize> IndexMut<YinYangSpherePoint<W, H>> for YinYangSphereGrid<T, W, H> {
+    fn index_mut(&mut self, index: YinYangSpherePoint<W, H>) -> &mut Self::Output {
+        &mut self.data[index.index()]
+    }
+}
+
+impl <T, const W: usize, const H: usize> Index<&YinYangSpherePoint<W, H>> for YinYangSphereGrid<T, W, H> {
+    type Output = T;
+
+    fn index(&self, index: &YinYangSpherePoint<W, H>) -> &Self::Output {
+        &self[*index]
+    }
+}
+
+impl <T, const W: usize, const H: usize> IndexMut<&YinYangSpherePoint<W, H>> for YinYangSphereGrid<T, W, H> {
+    fn index_mut(&mut self, index: &YinYangSpherePoint<W, H>) -> &mut Self::Output {
+        &mut self[*index]
+    }
+}
+
+impl <T, const W: usize, const H: usize> FromIterator<T> for YinYangSphereGrid<T, W, H> {
+    /// Creates a grid from the values of its cells in the same order as `points`.
+    ///
+    /// # Panics
+    /// Panics if the iterator does not yield exactly one value for each cell in the grid.
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        Self::from_vec(iter.into_iter().collect())
+            .expect("the iterator must yield one value for each cell in the grid")
+    }
+}
+
+impl <T, const W: usize, const H: usize> IntoIterator for YinYangSphereGrid<T, W, H> {
+    type Item = (YinYangSpherePoint<W, H>, T);
+
+    type IntoIter = vec::IntoIter<Self::Item>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        let data: Vec<_> = self.data.into_iter()
+            .enumerate()
+            .map(|(i, value)| (YinYangSpherePoint::from_index(i), value))
+            .collect();
+
+        data.into_iter()
+    }
+}
+
+#[cfg(feature = "serde")]
+impl <T: serde::Serialize, const W: usize, const H: usize> serde::Serialize for YinYangSphereGrid<T, W, H> {
+    /// Serializes the values in this grid as a sequence in the same order as `iter`.
+    fn serialize<R: serde::Serializer>(&self, serializer: R) -> Result<R::Ok, R::Error> {
+        serializer.collect_seq(self.data.iter())
+    }
+}
+
+#[cfg(feature = "serde")]
+impl <'de, T: serde::Deserialize<'de>, const W: usize, const H: usize> serde::Deserialize<'de> for YinYangSphereGrid<T, W, H> {
+    /// Deserializes a grid from a sequence of values in the same order as `iter`.
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let values = Vec::<T>::deserialize(deserializer)?;
+        let length = values.len();
+
+        Self::from_vec(values)
+            .ok_or_else(|| serde::de::Error::invalid_length(length, &format!("a sequence of {} values", 2 * W * H).as_str()))
+    }
+}
+
+/// A point on a `YinYangSphereGrid`.
+///
+/// # Constant Parameters
+/// - `W` - The width of each patch.
+/// - `H` - The height of each patch.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct YinYangSpherePoint<const W: usize, const H: usize> {
+    /// The patch that contains the point.
+    patch: YinYangPatch,
+    /// The X position on the patch, increasing towards the east of the patch.
+    x: u32,
+    /// The Y position on the patch, increasing towards the south of the patch.
+    y: u32,
+}
+
+impl YinYangPatch {
+    /// Gets the other patch.
+    fn other(&self) -> Self {
+        match self {
+            YinYangPatch::Yin => YinYangPatch::Yang,
+            YinYangPatch::Yang => YinYangPatch::Yin,
+        }
+    }
+
+    /// Converts a position in 3D space between the coordinates of the sphere and the coordinates
+    /// of this patch.
+    ///
+    /// The rotation between the patches is its own inverse so this converts in both directions.
+    ///
+    /// - `position` - The position to convert.
+    fn rotate(&self, (x, y, z): (f64, f64, f64)) -> (f64, f64, f64) {
+        match self {
+            YinYangPatch::Yin => (x, y, z),
+            YinYangPatch::Yang => (y, x, -z),
+        }
+    }
+
+    /// Converts geographic coordinates between the coordinates of the sphere and the coordinates
+    /// of this patch.
+    ///
+    /// The returned longitude is from `-PI` to `PI`.
+    ///
+    /// - `latitude` - The latitude in radians.
+    /// - `longitude` - The longitude in radians.
+    fn rotate_geographic(&self, latitude: f64, longitude: f64) -> (f64, f64) {
+        let (x, y, z) = self.rotate((
+            latitude.cos() * longitude.sin(),
+            latitude.sin(),
+            latitude.cos() * longitude.cos(),
+        ));
+
+        (y.clamp(-1.0, 1.0).asin(), x.atan2(z))
+    }
+
+    /// Gets how far inside this patch a position is, as the angle to the nearest edge of the patch.
+    ///
+    /// This is negative for positions outside of the patch.
+    ///
+    /// - `latitude` - The latitude in radians.
+    /// - `longitude` - The longitude in radians.
+    fn margin(&self, latitude: f64, longitude: f64) -> f64 {
+        let (latitude, longitude) = self.rotate_geographic(latitude, longitude);
+
+        (PI / 4.0 - latitude.abs()).min((3.0 * PI / 4.0 - longitude.abs()) * latitude.cos())
+    }
+}
+
+impl <const W: usize, const H: usize> YinYangSpherePoint<W, H> {
+    /// Creates a new `YinYangSpherePoint`.
+    ///
+    /// - `patch` - The patch.
+    /// - `x` - The X position on the patch.
+    /// - `y` - The Y position on the patch.
+    fn new(patch: YinYangPatch, x: u32, y: u32) -> Self {
+        Self {
+            patch, x, y,
+        }
+    }
+
+    /// Creates a new `YinYangSpherePoint` checking that the position lies on the grid.
+    ///
+    /// This returns an error if `x` is `W` or greater or `y` is `H` or greater.
+    ///
+    /// - `patch` - The patch.
+    /// - `x` - The X position on the patch.
+    /// - `y` - The Y position on the patch.
+    pub fn try_new(patch: YinYangPatch, x: u32, y: u32) -> Result<Self, OutOfBounds> {
+        if (x as usize) < W && (y as usize) < H {
+            Ok(Self::new(patch, x, y))
+        } else {
+            Err(OutOfBounds)
+        }
+    }
+
+    /// Gets the point at an index in the order that `YinYangSphereGrid::points` visits them.
+    ///
+    /// - `index` - The index of the point.
+    fn from_index(index: usize) -> Self {
+        let patch = if index < W * H { YinYangPatch::Yin } else { YinYangPatch::Yang };
+
+        Self::new(patch, (index % W) as u32, (index / W % H) as u32)
+    }
+
+    /// Gets the size of a cell in longitude and latitude within its patch.
+    ///
+    /// # Panics
+    /// Panics if `W` or `H` is not greater than 4.
+    fn cell_size() -> (f64, f64) {
+        assert!(W > 4 && H > 4, "the width and height of a Yin-Yang grid must be greater than 4");
+
+        (3.0 * PI / 2.0 / (W - 4) as f64, PI / 2.0 / (H - 4) as f64)
+    }
+
+    /// Gets the index of this point in the order that `YinYangSphereGrid::points` visits them.
+    fn index(&self) -> usize {
+        let patch = match self.patch {
+            YinYangPatch::Yin => 0,
+            YinYangPatch::Yang => 1,
+        };
+
+        patch * W * H + self.y as usize * W + self.x as usize
+    }
+
+    /// Gets the patch that contains this point.
+    pub fn patch(&self) -> YinYangPatch {
+        self.patch
+    }
+
+    /// Determines whether this point is on the edge of its patch.
+    ///
+    /// These are the cells set by `YinYangSphereGrid::exchange_boundaries`.
+    pub fn is_boundary(&self) -> bool {
+        self.x == 0 || self.y == 0 || self.x == W as u32 - 1 || self.y == H as u32 - 1
+    }
+
+    /// Gets the four cells surrounding a geographic position along with their bilinear
+    /// interpolation weights.
+    ///
+    /// The cells are taken from the patch that the position lies furthest inside, which is the
+    /// patch of `from_geographic`. They are returned in the order `(x, y)`, `(x + 1, y)`,
+    /// `(x + 1, y + 1)` and `(x, y + 1)`. At the edges of a patch the cells are clamped to the
+    /// patch so a cell may be returned more than once.
+    ///
+    /// The weights are non-negative and add up to `1`.
+    ///
+    /// - `latitude` - The latitude of the position in radians where 0 is the equator.
+    /// - `longitude` - The longitude of the position in radians.
+    pub fn from_geographic_bilinear(latitude: f64, longitude: f64) -> ([Self; 4], [f64; 4]) {
+        Self::patch_bilinear(Self::owner(latitude, longitude), latitude, longitude)
+    }
+
+    /// Gets the four cells of a patch surrounding a geographic position along with their bilinear
+    /// interpolation weights.
+    ///
+    /// - `patch` - The patch to take the cells from.
+    /// - `latitude` - The latitude of the position in radians.
+    /// - `longitude` - The longitude of the position in radians.
+    fn patch_bilinear(patch: YinYangPatch, latitude: f64, longitude: f64) -> ([Self; 4], [f64; 4]) {
+        let (x, y) = Self::patch_coordinates(patch, latitude, longitude);
+
+        // Find the position relative to the centres of the cells.
+        let axis = |value: f64, size: usize| {
+            let value = value - 0.5;
+            let start = value.floor();
+
+            if start < 0.0 {
+                (0, 0, 0.0)
+            } else if start >= size as f64 - 1.0 {
+                (size as u32 - 1, size as u32 - 1, 0.0)
+            } else {
+                (start as u32, start as u32 + 1, value - start)
+            }
+        };
+
+        let (x0, x1, tx) = axis(x, W);
+        let (y0, y1, ty) = axis(y, H);
+
+        let points = [(x0, y0), (x1, y0), (x1, y1), (x0, y1)]
+            .map(|(x, y)| Self::new(patch, x, y));
+
+        let weights = [
+            (1.0 - tx) * (1.0 - ty),
+            tx * (1.0 - ty),
+            tx * ty,
+            (1.0 - tx) * ty,
+        ];
+
+        (points, weights)
+    }
+
+    /// Gets the patch that a geographic position lies furthest inside.
+    ///
+    /// - `latitude` - The latitude in radians.
+    /// - `longitude` - The longitude in radians.
+    fn owner(latitude: f64, longitude: f64) -> YinYangPatch {
+        if YinYangPatch::Yin.margin(latitude, longitude) >= YinYangPatch::Yang.margin(latitude, longitude) {
+            YinYangPatch::Yin
+        } else {
+            YinYangPatch::Yang
+        }
+    }
+
+    /// Gets the continuous position on a patch of a geographic position.
+    ///
+    /// The centre of the cell at `(x, y)` is at `(x + 0.5, y + 0.5)`. The position may lie off the
+    /// patch.
+    ///
+    /// - `patch` - The patch.
+    /// - `latitude` - The latitude in radians.
+    /// - `longitude` - The longitude in radians.
+    fn patch_coordinates(patch: YinYangPatch, latitude: f64, longitude: f64) -> (f64, f64) {
+        let (latitude, longitude) = patch.rotate_geographic(latitude, longitude);
+        let (width, height) = Self::cell_size();
+
+        (
+            (longitude + 3.0 * PI / 4.0) / width + 2.0,
+            (PI / 4.0 - latitude) / height + 2.0,
+        )
+    }
+
+    /// Gets the cell of a patch that contains a geographic position, clamped to the patch.
+    ///
+    /// - `patch` - The patch.
+    /// - `latitude` - The latitude in radians.
+    /// - `longitude` - The longitude in radians.
+    fn patch_point(patch: YinYangPatch, latitude: f64, longitude: f64) -> Self {
+        let (x, y) = Self::patch_coordinates(patch, latitude, longitude);
+
+        Self::new(
+            patch,
+            x.clamp(0.0, W as f64 - 1.0) as u32,
+            y.clamp(0.0, H as f64 - 1.0) as u32,
+        )
+    }
+
+    /// Gets the geographic coordinates of a continuous position on a patch.
+    ///
+    /// - `patch` - The patch.
+    /// - `x` - The X position on the patch.
+    /// - `y` - The Y position on the patch.
+    fn geographic(patch: YinYangPatch, x: f64, y: f64) -> (f64, f64) {
+        let (width, height) = Self::cell_size();
+
+        let latitude = PI / 4.0 - (y - 2.0) * height;
+        let longitude = (x - 2.0) * width - 3.0 * PI / 4.0;
+
+        patch.rotate_geographic(latitude, longitude)
+    }
+
+    /// Gets the geographic coordinates of the centre of this cell.
+    fn center_geographic(&self) -> (f64, f64) {
+        Self::geographic(self.patch, self.x as f64 + 0.5, self.y as f64 + 0.5)
+    }
+
+    /// Gets the point reached by moving one cell across the patch.
+    ///
+    /// Moving off the edge of the patch gives the cell of the other patch that contains the
+    /// centre of the cell that would have been there.
+    ///
+    /// - `dx` - The step in the X direction.
+    /// - `dy` - The step in the Y direction.
+    fn step(&self, dx: i64, dy: i64) -> Self {
+        let x = self.x as i64 + dx;
+        let y = self.y as i64 + dy;
+
+        if (0..W as i64).contains(&x) && (0..H as i64).contains(&y) {
+            return Self::new(self.patch, x as u32, y as u32);
+        }
+
+        let (latitude, longitude) = Self::geographic(self.patch, x as f64 + 0.5, y as f64 + 0.5);
+
+        Self::patch_point(self.patch.other(), latitude, longitude)
+    }
+}
+
+impl <const W: usize, const H: usize> GridPoint for YinYangSpherePoint<W, H> {
+    fn up(&self) -> Self {
+        self.step(0, -1)
+    }
+
+    fn down(&self) -> Self {
+        self.step(0, 1)
+    }
+
+    fn left(&self) -> Self {
+        self.step(-1, 0)
+    }
+
+    fn right(&self) -> Self {
+        self.step(1, 0)
+    }
+
+    /// Gets the position of the centre of the cell in 3D space.
+    ///
+    /// - `scale` - The scale of the 3D object.
+    fn position(&self, scale: f64) -> (f64, f64, f64) {
+        let (latitude, longitude) = self.center_geographic();
+
+        (
+            scale * latitude.cos() * longitude.sin(),
+            scale * latitude.sin(),
+            scale * latitude.cos() * longitude.cos(),
+        )
+    }
+}
+
+impl <const W: usize, const H: usize> SpherePoint for YinYangSpherePoint<W, H> {
+    /// Gets the cell containing the specified geographic coordinates.
+    ///
+    /// Where the patches overlap this picks the patch that the position lies furthest inside.
+    ///
+    /// - `latitude` - The latitude of the point in radians where 0 is the equator.
+    /// - `longitude` - The longitude of the point in radians.
+    fn from_geographic(latitude: f64, longitude: f64) -> Self {
+        Self::patch_point(Self::owner(latitude, longitude), latitude, longitude)
+    }
+
+    fn latitude(&self) -> f64 {
+        self.center_geographic().0
+    }
+
+    fn longitude(&self) -> f64 {
+        self.center_geographic().1.rem_euclid(2.0 * PI)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::f64::consts::PI;
+
+    use approx::assert_relative_eq;
+
+    use crate::{GridPoint, SurfaceGrid, sphere::SpherePoint};
+
+    use super::{YinYangPatch, YinYangSphereGrid, YinYangSpherePoint};
+
+    /// Gets the distance between the centres of two cells on a sphere with a radius of 1.
+    fn distance<const W: usize, const H: usize>(a: &YinYangSpherePoint<W, H>, b: &YinYangSpherePoint<W, H>) -> f64 {
+        let (x, y, z) = a.position(1.0);
+        let (bx, by, bz) = b.position(1.0);
+
+        ((x - bx).powi(2) + (y - by).powi(2) + (z - bz).powi(2)).sqrt()
+    }
+
+    #[test]
+    fn test_yin_yang_patches() {
+        assert_eq!(YinYangSpherePoint::<16, 8>::from_geographic(0.0, 0.0).patch(), YinYangPatch::Yin);
+        assert_eq!(YinYangSpherePoint::<16, 8>::from_geographic(0.0, PI).patch(), YinYangPatch::Yang);
+        assert_eq!(YinYangSpherePoint::<16, 8>::from_geographic(PI / 2.0, 0.0).patch(), YinYangPatch::Yang);
+        assert_eq!(YinYangSpherePoint::<16, 8>::from_geographic(-PI / 2.0, 0.0).patch(), YinYangPatch::Yang);
+
+        // The rotation between the patches is its own inverse.
+        for (latitude, longitude) in [(0.3f64, 1.2f64), (-0.6, -2.0), (1.4, 2.0)] {
+            let (yang_latitude, yang_longitude) = YinYangPatch::Yang.rotate_geographic(latitude, longitude);
+            let (yin_latitude, yin_longitude) = YinYangPatch::Yang.rotate_geographic(yang_latitude, yang_longitude);
+
+            assert_relative_eq!(yin_latitude, latitude, epsilon = 1e-12);
+            assert_relative_eq!(yin_longitude, longitude, epsilon = 1e-12);
+        }
+
+        // The centre of the Yang patch is opposite the centre of the Yin patch.
+        let (latitude, longitude) = YinYangPatch::Yang.rotate_geographic(0.0, PI);
+
+        assert_relative_eq!(latitude, 0.0, epsilon = 1e-12);
+        assert_relative_eq!(longitude, 0.0, epsilon = 1e-12);
+    }
+
+    #[test]
+    fn test_yin_yang_from_geographic() {
+        let grid: YinYangSphereGrid<(), 40, 16> = YinYangSphereGrid::default();
+
+        for point in grid.points() {
+            let found = YinYangSpherePoint::from_geographic(point.latitude(), point.longitude());
+
+            // Cells in the overlap may be found in the other patch.
+            if found.patch() == point.patch() {
+                assert_eq!(found, point);
+            } else {
+                assert!(distance(&found, &point) < 0.1);
+            }
+        }
+
+        // Every position on the sphere is in a cell that is close to it.
+        for i in 0..50 {
+            for j in 0..100 {
+                let latitude = (i as f64 + 0.5) / 50.0 * PI - PI / 2.0;
+                let longitude = j as f64 / 100.0 * 2.0 * PI;
+
+                let point = YinYangSpherePoint::<40, 16>::from_geographic(latitude, longitude);
+                let (x, y, z) = point.position(1.0);
+
+                let dot = x * latitude.cos() * longitude.sin() + y * latitude.sin() + z * latitude.cos() * longitude.cos();
+
+                assert!(dot.clamp(-1.0, 1.0).acos() < 0.1);
+            }
+        }
+    }
+
+    #[test]
+    fn test_yin_yang_neighbours() {
+        let grid: YinYangSphereGrid<(), 40, 16> = YinYangSphereGrid::default();
+
+        for point in grid.points() {
+            for neighbour in [point.up(), point.down(), point.left(), point.right()] {
+                assert_ne!(neighbour, point);
+
+                // Cells are about 0.13 apart but the cells of the other patch do not line up.
+                assert!(distance(&point, &neighbour) < 0.25);
+            }
+
+            if !point.is_boundary() {
+                assert_eq!(point.up().down(), point);
+                assert_eq!(point.left().right(), point);
+            }
+        }
+    }
+
+    #[test]
+    fn test_yin_yang_sample() {
+        let field = |(x, y, z): (f64, f64, f64)| x + 2.0 * y - z;
+
+        let grid: YinYangSphereGrid<f64, 88, 32> = YinYangSphereGrid::from_fn(|point| field(point.position(1.0)));
+
+        for i in 0..20 {
+            for j in 0..40 {
+                let latitude = (i as f64 + 0.5) / 20.0 * PI - PI / 2.0;
+                let longitude = j as f64 / 40.0 * 2.0 * PI;
+
+                let (_, weights) = YinYangSpherePoint::<88, 32>::from_geographic_bilinear(latitude, longitude);
+
+                assert_relative_eq!(weights.iter().sum::<f64>(), 1.0, epsilon = 1e-12);
+
+                let expected = field((latitude.cos() * longitude.sin(), latitude.sin(), latitude.cos() * longitude.cos()));
+
+                assert!((grid.sample(latitude, longitude) - expected).abs() < 0.01);
+            }
+        }
+    }
+
+    #[test]
+    fn test_yin_yang_exchange_boundaries() {
+        let field = |(x, y, z): (f64, f64, f64)| x * y + z;
+
+        let mut grid: YinYangSphereGrid<f64, 88, 32> = YinYangSphereGrid::from_fn(|point| {
+            if point.is_boundary() {
+                0.0
+            } else {
+                field(point.position(1.0))
+            }
+        });
+
+        grid.exchange_boundaries();
+
+        for (point, value) in grid.iter() {
+            assert!((value - field(point.position(1.0))).abs() < 0.005);
+        }
+
+        assert_eq!(grid.points().filter(YinYangSpherePoint::is_boundary).count(), 2 * (2 * 88 + 2 * 32 - 4));
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_yin_yang_too_small() {
+        YinYangSpherePoint::<4, 8>::from_geographic(0.0, 0.0);
+    }
+
+    #[test]
+    fn test_yin_yang_iteration_order() {
+        let grid: YinYangSphereGrid<usize, 7, 5> = YinYangSphereGrid::from_index_fn(|i| i);
+
+        for (i, (point, value)) in grid.iter().enumerate() {
+            assert_eq!(*value, i);
+            assert_eq!(grid.to_index(&point), i);
+            assert_eq!(point.patch() == YinYangPatch::Yin, i < 35);
+        }
+
+        assert_eq!(grid.par_map_to_vec(|_, value| *value), grid.to_vec());
+        assert_eq!(grid.clone().into_iter().map(|(_, value)| value).collect::<Vec<_>>(), grid.to_vec());
+        assert_eq!(YinYangSphereGrid::from_vec(grid.to_vec()), Some(grid));
+    }
+}